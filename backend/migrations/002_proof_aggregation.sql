@@ -0,0 +1,9 @@
+-- Records covered by aggregate proofs (zk_proofs.health_record_id holds the first one)
+CREATE TABLE zk_proof_records (
+    proof_id UUID NOT NULL REFERENCES zk_proofs(id) ON DELETE CASCADE,
+    health_record_id UUID NOT NULL REFERENCES health_records(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL, -- Order of the record within the proof's public inputs
+    PRIMARY KEY (proof_id, health_record_id)
+);
+
+CREATE INDEX idx_zk_proof_records_health_record_id ON zk_proof_records(health_record_id);
@@ -0,0 +1,3 @@
+-- Version of the signable message format each record was signed with
+-- (see services::message_format); existing signatures all use version 1
+ALTER TABLE health_records ADD COLUMN format_version INTEGER NOT NULL DEFAULT 1;
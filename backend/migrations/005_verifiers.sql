@@ -0,0 +1,16 @@
+-- Registered verifiers allowed to call the public verification endpoint
+CREATE TABLE verifiers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE, -- SHA-256 of the X-Verifier-Token value
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Which registered verifier performed a public verification
+ALTER TABLE proof_verifications
+    ADD COLUMN registered_verifier_id UUID REFERENCES verifiers(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_proof_verifications_registered_verifier_id ON proof_verifications(registered_verifier_id);
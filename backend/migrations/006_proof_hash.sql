@@ -0,0 +1,7 @@
+-- SHA-256 of proof_data, so verification can find a proof through an index
+-- instead of comparing whole blobs. Generated, so every insert (and every
+-- existing row) gets it without the application computing it.
+ALTER TABLE zk_proofs
+    ADD COLUMN proof_hash BYTEA GENERATED ALWAYS AS (sha256(proof_data)) STORED;
+
+CREATE INDEX idx_zk_proofs_proof_hash ON zk_proofs(proof_hash);
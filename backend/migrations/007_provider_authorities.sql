@@ -0,0 +1,10 @@
+-- Authorities each provider may sign health records for
+CREATE TABLE provider_authorities (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    authority_id UUID NOT NULL REFERENCES health_authorities(id) ON DELETE CASCADE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (user_id, authority_id)
+);
+
+CREATE INDEX idx_provider_authorities_authority_id ON provider_authorities(authority_id);
@@ -0,0 +1,19 @@
+-- Further authorities that must co-sign a health record, in signing order.
+-- The record's own authority signs first (health_records.signature_*); each
+-- co-signature covers that same message hash.
+CREATE TABLE record_signatures (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    health_record_id UUID NOT NULL REFERENCES health_records(id) ON DELETE CASCADE,
+    authority_id UUID NOT NULL REFERENCES health_authorities(id),
+    position INTEGER NOT NULL, -- 1-based; the record's own authority is position 0
+    signature_r BYTEA,
+    signature_s BYTEA,
+    message_hash BYTEA, -- Hash that was co-signed; NULL until signed
+    signed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    signed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (health_record_id, position),
+    UNIQUE (health_record_id, authority_id)
+);
+
+CREATE INDEX idx_record_signatures_health_record_id ON record_signatures(health_record_id);
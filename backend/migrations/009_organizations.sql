@@ -0,0 +1,23 @@
+-- Tenants (hospitals, operators); users, authorities and records are isolated per organization
+CREATE TABLE organizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Everything created before organizations existed belongs to the default one
+INSERT INTO organizations (id, name) VALUES ('00000000-0000-0000-0000-000000000001', 'Default');
+
+ALTER TABLE users
+    ADD COLUMN org_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES organizations(id),
+    ADD COLUMN is_superadmin BOOLEAN NOT NULL DEFAULT FALSE; -- Not confined to org_id
+
+ALTER TABLE health_authorities
+    ADD COLUMN org_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES organizations(id);
+
+ALTER TABLE health_records
+    ADD COLUMN org_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES organizations(id);
+
+CREATE INDEX idx_users_org_id ON users(org_id);
+CREATE INDEX idx_health_authorities_org_id ON health_authorities(org_id);
+CREATE INDEX idx_health_records_org_id ON health_records(org_id);
@@ -0,0 +1,14 @@
+-- Pending invitations for providers to join an authority
+CREATE TABLE provider_invites (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    email VARCHAR(255) NOT NULL,
+    authority_id UUID NOT NULL REFERENCES health_authorities(id) ON DELETE CASCADE,
+    org_id UUID NOT NULL REFERENCES organizations(id),
+    token_hash VARCHAR(64) NOT NULL UNIQUE, -- SHA-256 of the invite token
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    accepted_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_provider_invites_authority_id ON provider_invites(authority_id);
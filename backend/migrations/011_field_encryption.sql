@@ -0,0 +1,7 @@
+-- Envelope-encrypted patient_identifier and details. While these are set, the
+-- plaintext columns only hold redacted placeholders.
+ALTER TABLE health_records
+    ADD COLUMN encrypted_fields BYTEA, -- AES-256-GCM ciphertext of {"patient_identifier", "details"}
+    ADD COLUMN encryption_nonce BYTEA,
+    ADD COLUMN encrypted_data_key BYTEA, -- Per-record data key wrapped with the master key
+    ADD COLUMN encryption_key_id VARCHAR(16); -- Fingerprint of the master key that wrapped it
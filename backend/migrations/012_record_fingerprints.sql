@@ -0,0 +1,9 @@
+-- Fingerprint of authority, type, patient, key details and issue date, used to
+-- reject duplicate records. NULL for records created with allow_duplicate and
+-- for records created before fingerprints existed.
+ALTER TABLE health_records ADD COLUMN content_fingerprint BYTEA;
+
+-- Revoking a record frees its fingerprint so a corrected record can be reissued
+CREATE UNIQUE INDEX idx_health_records_content_fingerprint
+    ON health_records(content_fingerprint)
+    WHERE content_fingerprint IS NOT NULL AND is_revoked = FALSE;
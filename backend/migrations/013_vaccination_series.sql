@@ -0,0 +1,13 @@
+-- Doses of one vaccination course. New doses join the series of earlier doses
+-- of the same vaccine given to the same patient by the same authority.
+CREATE TABLE vaccination_series (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    authority_id UUID NOT NULL REFERENCES health_authorities(id),
+    org_id UUID NOT NULL REFERENCES organizations(id),
+    series_key BYTEA NOT NULL UNIQUE, -- Fingerprint of authority, patient and vaccine
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+ALTER TABLE health_records ADD COLUMN series_id UUID REFERENCES vaccination_series(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_health_records_series_id ON health_records(series_id);
@@ -0,0 +1,17 @@
+-- Read access to a record granted by its owner to another user
+CREATE TABLE record_shares (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    health_record_id UUID NOT NULL REFERENCES health_records(id) ON DELETE CASCADE,
+    owner_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    grantee_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- One live share per record and grantee; sharing again extends it
+CREATE UNIQUE INDEX idx_record_shares_active
+    ON record_shares(health_record_id, grantee_id)
+    WHERE revoked_at IS NULL;
+
+CREATE INDEX idx_record_shares_grantee_id ON record_shares(grantee_id);
@@ -0,0 +1,13 @@
+-- Pending and completed handovers of a record to another user
+CREATE TABLE record_transfers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    health_record_id UUID NOT NULL REFERENCES health_records(id) ON DELETE CASCADE,
+    from_user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    to_user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the confirmation token
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    accepted_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_record_transfers_health_record_id ON record_transfers(health_record_id);
@@ -0,0 +1,12 @@
+-- Reusable record details for an authority, merged with per-patient overrides
+CREATE TABLE record_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    authority_id UUID NOT NULL REFERENCES health_authorities(id) ON DELETE CASCADE,
+    org_id UUID NOT NULL REFERENCES organizations(id),
+    name VARCHAR(255) NOT NULL,
+    record_type VARCHAR(50) NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (authority_id, name)
+);
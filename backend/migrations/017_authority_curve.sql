@@ -0,0 +1,2 @@
+-- Curve of the authority's signing key. Existing authorities all use secp256k1.
+ALTER TABLE health_authorities ADD COLUMN curve VARCHAR(20) NOT NULL DEFAULT 'secp256k1';
@@ -0,0 +1,4 @@
+-- RFC 3161 timestamp over proof_hash from the configured TSA, when enabled
+ALTER TABLE zk_proofs
+    ADD COLUMN timestamp_token BYTEA,
+    ADD COLUMN timestamped_at TIMESTAMP WITH TIME ZONE;
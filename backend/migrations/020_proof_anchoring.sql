@@ -0,0 +1,3 @@
+-- Transaction that submitted proof_hash on-chain, when blockchain anchoring is enabled
+ALTER TABLE zk_proofs
+    ADD COLUMN anchor_tx_hash VARCHAR(66);
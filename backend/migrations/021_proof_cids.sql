@@ -0,0 +1,3 @@
+-- IPFS CID of the proof data pinned during on-chain anchoring
+ALTER TABLE zk_proofs
+    ADD COLUMN proof_cid VARCHAR(128);
@@ -0,0 +1,16 @@
+-- When a proof was revoked, by its holder or along with a record it covers;
+-- published through the proof revocation list
+ALTER TABLE zk_proofs
+    ADD COLUMN revoked_at TIMESTAMP WITH TIME ZONE;
+
+-- Proofs of records revoked before this column existed. Earlier holder
+-- revocations can't be told apart from used-up proofs and stay unlisted.
+UPDATE zk_proofs zp SET revoked_at = NOW()
+WHERE EXISTS (SELECT 1 FROM health_records hr WHERE hr.id = zp.health_record_id AND hr.is_revoked)
+   OR EXISTS (
+       SELECT 1 FROM zk_proof_records zpr
+       JOIN health_records hr ON hr.id = zpr.health_record_id
+       WHERE zpr.proof_id = zp.id AND hr.is_revoked
+   );
+
+CREATE INDEX idx_zk_proofs_revoked_at ON zk_proofs(revoked_at) WHERE revoked_at IS NOT NULL;
@@ -0,0 +1,4 @@
+-- Where a verification came from, looked up from its IP when GeoIP is enabled
+ALTER TABLE proof_verifications
+    ADD COLUMN country_code VARCHAR(2),
+    ADD COLUMN region VARCHAR(255);
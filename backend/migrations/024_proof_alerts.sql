@@ -0,0 +1,19 @@
+-- Proofs verified from more distinct locations in a short window than the
+-- anomaly policy allows, a sign of a copied or stolen pass
+CREATE TABLE proof_alerts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    proof_id UUID NOT NULL REFERENCES zk_proofs(id) ON DELETE CASCADE,
+    distinct_locations INTEGER NOT NULL,
+    window_secs INTEGER NOT NULL,
+    suspended BOOLEAN NOT NULL DEFAULT FALSE, -- Whether the proof was suspended automatically
+    detected_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    resolved_by UUID REFERENCES users(id)
+);
+
+-- At most one open alert per proof
+CREATE UNIQUE INDEX idx_proof_alerts_open ON proof_alerts(proof_id) WHERE resolved_at IS NULL;
+
+-- Suspended proofs fail verification until an admin resolves the alert
+ALTER TABLE zk_proofs
+    ADD COLUMN suspended_at TIMESTAMP WITH TIME ZONE;
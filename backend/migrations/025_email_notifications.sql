@@ -0,0 +1,2 @@
+-- Users can opt out of emails about their records being issued or revoked
+ALTER TABLE users ADD COLUMN email_notifications BOOLEAN NOT NULL DEFAULT TRUE;
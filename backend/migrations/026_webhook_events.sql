@@ -0,0 +1,2 @@
+-- Event types a webhook subscribes to; existing webhooks keep receiving proof verifications only
+ALTER TABLE webhooks ADD COLUMN events TEXT[] NOT NULL DEFAULT '{proof.verified}';
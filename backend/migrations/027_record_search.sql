@@ -0,0 +1,19 @@
+-- Free-text search over the descriptive fields of record details (vaccine, lab, facility, ...)
+ALTER TABLE health_records ADD COLUMN details_search TSVECTOR GENERATED ALWAYS AS (
+    to_tsvector('simple',
+        coalesce(details->>'vaccine_name', '') || ' ' ||
+        coalesce(details->>'manufacturer', '') || ' ' ||
+        coalesce(details->>'vaccination_site', '') || ' ' ||
+        coalesce(details->>'administrator', '') || ' ' ||
+        coalesce(details->>'test_type', '') || ' ' ||
+        coalesce(details->>'result', '') || ' ' ||
+        coalesce(details->>'test_method', '') || ' ' ||
+        coalesce(details->>'laboratory', '') || ' ' ||
+        coalesce(details->>'clearance_type', '') || ' ' ||
+        coalesce(details->>'physician', '') || ' ' ||
+        coalesce(details->>'medical_facility', '') || ' ' ||
+        coalesce(details->>'immunity_type', '')
+    )
+) STORED;
+
+CREATE INDEX idx_health_records_details_search ON health_records USING GIN (details_search);
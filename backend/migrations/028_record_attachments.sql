@@ -0,0 +1,15 @@
+-- Documents attached to a record (lab reports, scanned cards), kept in S3-compatible storage
+CREATE TABLE attachments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    health_record_id UUID NOT NULL REFERENCES health_records(id) ON DELETE CASCADE,
+    object_key TEXT NOT NULL UNIQUE,
+    file_name TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    -- Hex SHA-256 of the stored bytes, returned so downloads can be verified
+    sha256 TEXT NOT NULL,
+    uploaded_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_attachments_health_record_id ON attachments(health_record_id);
@@ -0,0 +1,2 @@
+-- Language of a user's notification emails; NULL means English
+ALTER TABLE users ADD COLUMN locale VARCHAR(16);
@@ -0,0 +1,13 @@
+-- Proofs generated in the background, and how far each one has got
+CREATE TABLE proof_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    stage VARCHAR(32) NOT NULL DEFAULT 'queued', -- queued, witness_generated, proving, done, failed
+    result JSONB, -- ProofResponse once done
+    error_code VARCHAR(64), -- Set once failed
+    error_message TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_proof_jobs_user_id ON proof_jobs(user_id);
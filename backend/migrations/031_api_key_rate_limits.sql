@@ -0,0 +1,2 @@
+-- Requests per minute an API key may make; NULL leaves the key unlimited
+ALTER TABLE api_keys ADD COLUMN rate_limit_rpm INTEGER CHECK (rate_limit_rpm > 0);
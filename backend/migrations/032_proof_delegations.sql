@@ -0,0 +1,16 @@
+-- Bearers a proof owner lets present, but not regenerate, one of their proofs
+CREATE TABLE proof_delegations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    proof_id UUID NOT NULL REFERENCES zk_proofs(id) ON DELETE CASCADE,
+    owner_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    delegate_email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the delegation token
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_proof_delegations_proof_id ON proof_delegations(proof_id);
+
+-- Delegation a verified proof was presented under, if any
+ALTER TABLE proof_verifications ADD COLUMN delegation_id UUID REFERENCES proof_delegations(id) ON DELETE SET NULL;
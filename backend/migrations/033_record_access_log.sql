@@ -0,0 +1,11 @@
+-- Reads of health records, written while ACCESS_LOG_ENABLED is set
+CREATE TABLE record_access_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    health_record_id UUID NOT NULL REFERENCES health_records(id) ON DELETE CASCADE,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    access_type VARCHAR(20) NOT NULL, -- 'read' of the record itself, 'list' when it was on a listed page
+    purpose VARCHAR(255) NOT NULL,
+    accessed_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_record_access_log_record ON record_access_log(health_record_id, accessed_at DESC);
@@ -0,0 +1,8 @@
+-- Why and when a health record was revoked, shown to owners and verifiers
+ALTER TABLE health_records
+    ADD COLUMN revoked_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN revocation_reason VARCHAR(255);
+
+-- Records revoked before these columns existed: the revocation was their last
+-- change, so updated_at is the best estimate; the reason is unknown
+UPDATE health_records SET revoked_at = updated_at WHERE is_revoked;
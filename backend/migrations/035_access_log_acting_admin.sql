@@ -0,0 +1,3 @@
+-- Admin who read the record while impersonating the actor, if any
+ALTER TABLE record_access_log
+    ADD COLUMN acting_admin_id UUID REFERENCES users(id) ON DELETE SET NULL;
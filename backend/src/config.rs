@@ -1,18 +1,80 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::Deserialize;
-use std::env;
+use std::{env, path::{Path, PathBuf}};
+
+use crate::services::captcha::CaptchaProvider;
+use crate::services::crypto::DEFAULT_RECORD_READ_PARALLELISM;
+use crate::services::field_encryption::FieldCipher;
+use crate::services::message_format::{formatter, DEFAULT_MESSAGE_FORMAT_VERSION};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     pub server_address: String,
+    pub metrics_address: Option<String>,
+    pub log_format: LogFormat,
     pub database_url: String,
+    pub db_connect_max_attempts: u32,
+    pub db_connect_retry_interval_secs: u64,
     pub jwt_secret: String,
     pub jwt_expiration_hours: i64,
+    pub invite_expiration_hours: i64,
     pub noir_circuit_path: String,
+    pub proof_backend: ProofBackendKind,
     pub cors_origins: Vec<String>,
+    pub max_request_body_bytes: usize,
+    pub request_timeout_secs: u64,
+    pub compression_enabled: bool,
+    pub proof_generation_timeout_secs: u64,
     pub rate_limit_requests_per_minute: u64,
+    pub redis_url: Option<String>,
+    pub idempotency_key_ttl_secs: u64,
+    pub authority_cache_ttl_secs: u64,
+    pub public_verification_allow_anonymous: bool,
+    pub captcha_provider: Option<CaptchaProvider>,
+    pub captcha_secret: Option<String>,
     pub max_proof_usage: Option<i32>,
+    pub message_format_version: i32,
+    pub field_encryption_key: Option<String>,
+    pub field_encryption_previous_keys: Vec<String>,
+    pub access_log_enabled: bool,
+    pub record_read_parallelism: usize,
     pub default_proof_expiration_hours: u32,
+    pub shutdown_drain_timeout_secs: u64,
+    pub webauthn_rp_id: String,
+    pub webauthn_rp_origin: String,
+    pub webauthn_rp_name: String,
+    pub tsa_url: Option<String>,
+    pub ipfs_api_url: Option<String>,
+    pub ipfs_gateway_url: String,
+    pub crl_signing_key: Option<String>,
+    pub geoip_enabled: bool,
+    pub geoip_db_path: String,
+    pub verification_dedup_window_secs: u64,
+    pub anomaly_window_secs: u64,
+    pub anomaly_max_distinct_locations: u32,
+    pub anomaly_auto_suspend: bool,
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_from: String,
+    pub public_base_url: String,
+    pub apple_pass_certificate_path: Option<String>,
+    pub apple_pass_certificate_password: String,
+    pub apple_wwdr_certificate_path: Option<String>,
+    pub apple_pass_type_identifier: Option<String>,
+    pub apple_team_identifier: Option<String>,
+    pub apple_pass_organization_name: String,
+    pub google_wallet_enabled: bool,
+    pub google_wallet_service_account_path: Option<String>,
+    pub google_wallet_issuer_id: Option<String>,
+    pub google_wallet_class_suffix: String,
+    pub s3_endpoint: Option<String>,
+    pub s3_bucket: Option<String>,
+    pub s3_region: String,
+    pub s3_access_key_id: Option<String>,
+    pub s3_secret_access_key: Option<String>,
+    pub attachment_max_bytes: usize,
     // Blockchain configuration
     pub blockchain_enabled: bool,
     pub blockchain_network: String,
@@ -23,56 +85,576 @@ pub struct Config {
     pub health_authority_registry_address: String,
 }
 
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(anyhow::anyhow!("Unknown log format: {}", other)),
+        }
+    }
+}
+
+/// What generates and verifies proofs
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofBackendKind {
+    /// The `nargo` and `bb` binaries, with the circuits under `noir_circuit_path`
+    Nargo,
+    /// Fixed, toolchain-free proofs for tests; needs the `stub-proofs` feature
+    Stub,
+}
+
+/// Configuration keys paired with the environment variables that override them
+const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("server_address", "SERVER_ADDRESS"),
+    ("metrics_address", "METRICS_ADDRESS"),
+    ("database_url", "DATABASE_URL"),
+    ("db_connect_max_attempts", "DB_CONNECT_MAX_ATTEMPTS"),
+    ("db_connect_retry_interval_secs", "DB_CONNECT_RETRY_INTERVAL_SECS"),
+    ("jwt_secret", "JWT_SECRET"),
+    ("jwt_expiration_hours", "JWT_EXPIRATION_HOURS"),
+    ("invite_expiration_hours", "INVITE_EXPIRATION_HOURS"),
+    ("noir_circuit_path", "NOIR_CIRCUIT_PATH"),
+    ("max_request_body_bytes", "MAX_REQUEST_BODY_BYTES"),
+    ("request_timeout_secs", "REQUEST_TIMEOUT_SECS"),
+    ("compression_enabled", "COMPRESSION_ENABLED"),
+    ("proof_generation_timeout_secs", "PROOF_GENERATION_TIMEOUT_SECS"),
+    ("rate_limit_requests_per_minute", "RATE_LIMIT_RPM"),
+    ("redis_url", "REDIS_URL"),
+    ("idempotency_key_ttl_secs", "IDEMPOTENCY_KEY_TTL_SECS"),
+    ("authority_cache_ttl_secs", "AUTHORITY_CACHE_TTL_SECS"),
+    ("public_verification_allow_anonymous", "PUBLIC_VERIFICATION_ALLOW_ANONYMOUS"),
+    ("captcha_secret", "CAPTCHA_SECRET"),
+    ("max_proof_usage", "MAX_PROOF_USAGE"),
+    ("message_format_version", "MESSAGE_FORMAT_VERSION"),
+    ("field_encryption_key", "FIELD_ENCRYPTION_KEY"),
+    ("access_log_enabled", "ACCESS_LOG_ENABLED"),
+    ("record_read_parallelism", "RECORD_READ_PARALLELISM"),
+    ("default_proof_expiration_hours", "DEFAULT_PROOF_EXPIRATION_HOURS"),
+    ("shutdown_drain_timeout_secs", "SHUTDOWN_DRAIN_TIMEOUT_SECS"),
+    ("webauthn_rp_id", "WEBAUTHN_RP_ID"),
+    ("webauthn_rp_origin", "WEBAUTHN_RP_ORIGIN"),
+    ("webauthn_rp_name", "WEBAUTHN_RP_NAME"),
+    ("tsa_url", "TSA_URL"),
+    ("ipfs_api_url", "IPFS_API_URL"),
+    ("ipfs_gateway_url", "IPFS_GATEWAY_URL"),
+    ("crl_signing_key", "CRL_SIGNING_KEY"),
+    ("geoip_enabled", "GEOIP_ENABLED"),
+    ("geoip_db_path", "GEOIP_DB_PATH"),
+    ("verification_dedup_window_secs", "VERIFICATION_DEDUP_WINDOW_SECS"),
+    ("anomaly_window_secs", "ANOMALY_WINDOW_SECS"),
+    ("anomaly_max_distinct_locations", "ANOMALY_MAX_DISTINCT_LOCATIONS"),
+    ("anomaly_auto_suspend", "ANOMALY_AUTO_SUSPEND"),
+    ("smtp_host", "SMTP_HOST"),
+    ("smtp_port", "SMTP_PORT"),
+    ("smtp_username", "SMTP_USERNAME"),
+    ("smtp_password", "SMTP_PASSWORD"),
+    ("smtp_from", "SMTP_FROM"),
+    ("public_base_url", "PUBLIC_BASE_URL"),
+    ("apple_pass_certificate_path", "APPLE_PASS_CERTIFICATE_PATH"),
+    ("apple_pass_certificate_password", "APPLE_PASS_CERTIFICATE_PASSWORD"),
+    ("apple_wwdr_certificate_path", "APPLE_WWDR_CERTIFICATE_PATH"),
+    ("apple_pass_type_identifier", "APPLE_PASS_TYPE_IDENTIFIER"),
+    ("apple_team_identifier", "APPLE_TEAM_IDENTIFIER"),
+    ("apple_pass_organization_name", "APPLE_PASS_ORGANIZATION_NAME"),
+    ("google_wallet_enabled", "GOOGLE_WALLET_ENABLED"),
+    ("google_wallet_service_account_path", "GOOGLE_WALLET_SERVICE_ACCOUNT_PATH"),
+    ("google_wallet_issuer_id", "GOOGLE_WALLET_ISSUER_ID"),
+    ("google_wallet_class_suffix", "GOOGLE_WALLET_CLASS_SUFFIX"),
+    ("s3_endpoint", "S3_ENDPOINT"),
+    ("s3_bucket", "S3_BUCKET"),
+    ("s3_region", "S3_REGION"),
+    ("s3_access_key_id", "S3_ACCESS_KEY_ID"),
+    ("s3_secret_access_key", "S3_SECRET_ACCESS_KEY"),
+    ("attachment_max_bytes", "ATTACHMENT_MAX_BYTES"),
+    ("blockchain_enabled", "BLOCKCHAIN_ENABLED"),
+    ("blockchain_network", "BLOCKCHAIN_NETWORK"),
+    ("blockchain_rpc_url", "BLOCKCHAIN_RPC_URL"),
+    ("blockchain_private_key", "BLOCKCHAIN_PRIVATE_KEY"),
+    ("zk_health_pass_registry_address", "ZK_HEALTH_PASS_REGISTRY_ADDRESS"),
+    ("zk_proof_verifier_address", "ZK_PROOF_VERIFIER_ADDRESS"),
+    ("health_authority_registry_address", "HEALTH_AUTHORITY_REGISTRY_ADDRESS"),
+];
+
+/// Shortest JWT signing secret accepted at startup (256 bits of ASCII)
+const MIN_JWT_SECRET_LEN: usize = 32;
+
+/// Keys without a default; they must come from the file or the environment
+const REQUIRED_KEYS: &[(&str, &str)] = &[
+    ("database_url", "DATABASE_URL"),
+    ("jwt_secret", "JWT_SECRET"),
+];
+
 impl Config {
+    /// Load from the TOML file named by `--config <path>` or `CONFIG_FILE`,
+    /// falling back to environment variables alone
+    pub fn load() -> Result<Self> {
+        dotenvy::dotenv().ok(); // Load .env file if it exists
+
+        match config_file_path(env::args().skip(1)) {
+            Some(path) => Self::from_file(path),
+            None => Self::from_env(),
+        }
+    }
+
     pub fn from_env() -> Result<Self> {
         dotenvy::dotenv().ok(); // Load .env file if it exists
 
-        Ok(Config {
-            server_address: env::var("SERVER_ADDRESS")
-                .unwrap_or_else(|_| "0.0.0.0:3000".to_string()),
-            database_url: env::var("DATABASE_URL")
-                .expect("DATABASE_URL must be set"),
-            jwt_secret: env::var("JWT_SECRET")
-                .expect("JWT_SECRET must be set"),
-            jwt_expiration_hours: env::var("JWT_EXPIRATION_HOURS")
-                .unwrap_or_else(|_| "24".to_string())
-                .parse()
-                .expect("JWT_EXPIRATION_HOURS must be a valid number"),
-            noir_circuit_path: env::var("NOIR_CIRCUIT_PATH")
-                .unwrap_or_else(|_| "../noir".to_string()),
-            cors_origins: env::var("CORS_ORIGINS")
-                .unwrap_or_else(|_| "http://localhost:3000,http://localhost:5173".to_string())
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .collect(),
-            rate_limit_requests_per_minute: env::var("RATE_LIMIT_RPM")
-                .unwrap_or_else(|_| "60".to_string())
-                .parse()
-                .expect("RATE_LIMIT_RPM must be a valid number"),
-            max_proof_usage: env::var("MAX_PROOF_USAGE")
-                .ok()
-                .and_then(|s| s.parse().ok()),
-            default_proof_expiration_hours: env::var("DEFAULT_PROOF_EXPIRATION_HOURS")
-                .unwrap_or_else(|_| "24".to_string())
-                .parse()
-                .expect("DEFAULT_PROOF_EXPIRATION_HOURS must be a valid number"),
+        Self::build(None, |name| env::var(name).ok())
+    }
+
+    /// Load from a TOML file keyed by field name (e.g. `jwt_secret = "..."`).
+    /// Environment variables still override any value set in the file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        dotenvy::dotenv().ok(); // Load .env file if it exists
+
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+
+        Self::build(Some(&contents), |name| env::var(name).ok())
+            .with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Layer defaults, then the TOML file, then environment variables
+    fn build(toml: Option<&str>, env_var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let mut builder = ::config::Config::builder()
+            .set_default("server_address", "0.0.0.0:3000")?
+            .set_default("log_format", "text")?
+            .set_default("db_connect_max_attempts", 10)?
+            .set_default("db_connect_retry_interval_secs", 2)?
+            .set_default("jwt_expiration_hours", 24)?
+            .set_default("invite_expiration_hours", 72)?
+            .set_default("noir_circuit_path", "../noir")?
+            .set_default("proof_backend", "nargo")?
+            .set_default("cors_origins", vec!["http://localhost:3000", "http://localhost:5173"])?
+            .set_default("max_request_body_bytes", 1048576)?
+            .set_default("request_timeout_secs", 30)?
+            .set_default("compression_enabled", true)?
+            .set_default("proof_generation_timeout_secs", 300)?
+            .set_default("rate_limit_requests_per_minute", 60)?
+            .set_default("idempotency_key_ttl_secs", 86400)?
+            .set_default("authority_cache_ttl_secs", 60)?
+            .set_default("public_verification_allow_anonymous", false)?
+            .set_default("message_format_version", DEFAULT_MESSAGE_FORMAT_VERSION)?
+            .set_default("field_encryption_previous_keys", Vec::<String>::new())?
+            .set_default("access_log_enabled", false)?
+            .set_default("record_read_parallelism", DEFAULT_RECORD_READ_PARALLELISM as u64)?
+            .set_default("default_proof_expiration_hours", 24)?
+            .set_default("shutdown_drain_timeout_secs", 30)?
+            .set_default("webauthn_rp_id", "localhost")?
+            .set_default("webauthn_rp_origin", "http://localhost:3000")?
+            .set_default("webauthn_rp_name", "VeryFi")?
+            .set_default("ipfs_gateway_url", "https://ipfs.io")?
+            .set_default("geoip_enabled", false)?
+            .set_default("geoip_db_path", "GeoLite2-City.mmdb")?
+            .set_default("verification_dedup_window_secs", 30)?
+            .set_default("anomaly_window_secs", 3600)?
+            .set_default("anomaly_max_distinct_locations", 5)?
+            .set_default("anomaly_auto_suspend", false)?
+            .set_default("smtp_port", 587)?
+            .set_default("smtp_from", "VeryFi <no-reply@localhost>")?
+            .set_default("public_base_url", "http://localhost:3000")?
+            .set_default("apple_pass_certificate_password", "")?
+            .set_default("apple_pass_organization_name", "VeryFi")?
+            .set_default("google_wallet_enabled", false)?
+            .set_default("google_wallet_class_suffix", "health_pass")?
+            .set_default("s3_region", "us-east-1")?
+            .set_default("attachment_max_bytes", 10485760)?
             // Blockchain configuration
-            blockchain_enabled: env::var("BLOCKCHAIN_ENABLED")
-                .unwrap_or_else(|_| "false".to_string())
-                .parse()
-                .unwrap_or(false),
-            blockchain_network: env::var("BLOCKCHAIN_NETWORK")
-                .unwrap_or_else(|_| "lisk-sepolia".to_string()),
-            blockchain_rpc_url: env::var("BLOCKCHAIN_RPC_URL")
-                .unwrap_or_else(|_| "https://rpc.sepolia-api.lisk.com".to_string()),
-            blockchain_private_key: env::var("BLOCKCHAIN_PRIVATE_KEY")
-                .unwrap_or_else(|_| "".to_string()),
-            zk_health_pass_registry_address: env::var("ZK_HEALTH_PASS_REGISTRY_ADDRESS")
-                .unwrap_or_else(|_| "".to_string()),
-            zk_proof_verifier_address: env::var("ZK_PROOF_VERIFIER_ADDRESS")
-                .unwrap_or_else(|_| "".to_string()),
-            health_authority_registry_address: env::var("HEALTH_AUTHORITY_REGISTRY_ADDRESS")
-                .unwrap_or_else(|_| "".to_string()),
-        })
+            .set_default("blockchain_enabled", false)?
+            .set_default("blockchain_network", "lisk-sepolia")?
+            .set_default("blockchain_rpc_url", "https://rpc.sepolia-api.lisk.com")?
+            .set_default("blockchain_private_key", "")?
+            .set_default("zk_health_pass_registry_address", "")?
+            .set_default("zk_proof_verifier_address", "")?
+            .set_default("health_authority_registry_address", "")?;
+
+        if let Some(toml) = toml {
+            builder = builder.add_source(::config::File::from_str(toml, ::config::FileFormat::Toml));
+        }
+
+        for (key, var) in ENV_OVERRIDES {
+            builder = builder.set_override_option(*key, env_var(var))?;
+        }
+        builder = builder
+            .set_override_option("log_format", env_var("LOG_FORMAT").map(|format| format.trim().to_lowercase()))?
+            .set_override_option("proof_backend", env_var("PROOF_BACKEND").map(|backend| backend.trim().to_lowercase()))?
+            .set_override_option("captcha_provider", env_var("CAPTCHA_PROVIDER").map(|provider| provider.trim().to_lowercase()))?
+            .set_override_option(
+                "cors_origins",
+                env_var("CORS_ORIGINS").map(|origins| {
+                    origins.split(',').map(|s| s.trim().to_string()).collect::<Vec<_>>()
+                }),
+            )?
+            .set_override_option(
+                "field_encryption_previous_keys",
+                env_var("FIELD_ENCRYPTION_PREVIOUS_KEYS").map(|keys| {
+                    keys.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect::<Vec<_>>()
+                }),
+            )?;
+
+        let settings = builder.build()?;
+
+        let missing: Vec<String> = REQUIRED_KEYS
+            .iter()
+            .filter(|(key, _)| settings.get_string(key).map_or(true, |value| value.is_empty()))
+            .map(|(key, var)| format!("{} ({})", key, var))
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!("Missing required configuration: {}", missing.join(", "));
+        }
+
+        settings.try_deserialize().context("Invalid configuration")
+    }
+}
+
+impl Config {
+    /// Check invariants that would otherwise only surface as confusing runtime
+    /// failures, reporting every problem at once
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        if self.jwt_secret.len() < MIN_JWT_SECRET_LEN {
+            problems.push(format!(
+                "jwt_secret (JWT_SECRET) must be at least {} characters, got {}",
+                MIN_JWT_SECRET_LEN,
+                self.jwt_secret.len()
+            ));
+        }
+
+        let circuit_manifest = Path::new(&self.noir_circuit_path).join("Nargo.toml");
+        if self.proof_backend == ProofBackendKind::Nargo && !circuit_manifest.is_file() {
+            problems.push(format!(
+                "noir_circuit_path (NOIR_CIRCUIT_PATH) must point to the Noir circuit directory, but {} does not exist",
+                circuit_manifest.display()
+            ));
+        }
+
+        if self.proof_backend == ProofBackendKind::Stub && !cfg!(any(test, feature = "stub-proofs")) {
+            problems.push(
+                "proof_backend (PROOF_BACKEND) can only be stub in builds with the stub-proofs feature".to_string(),
+            );
+        }
+
+        if self.captcha_provider.is_some() && self.captcha_secret.as_deref().map_or(true, str::is_empty) {
+            problems.push("captcha_secret (CAPTCHA_SECRET) must be set when captcha_provider is set".to_string());
+        }
+
+        if self.geoip_enabled && !Path::new(&self.geoip_db_path).is_file() {
+            problems.push(format!(
+                "geoip_db_path (GEOIP_DB_PATH) must point to a MaxMind City database when geoip_enabled is true, but {} does not exist",
+                self.geoip_db_path
+            ));
+        }
+
+        if self.record_read_parallelism == 0 {
+            problems.push("record_read_parallelism (RECORD_READ_PARALLELISM) must be at least 1".to_string());
+        }
+
+        if formatter(self.message_format_version).is_err() {
+            problems.push(format!(
+                "message_format_version (MESSAGE_FORMAT_VERSION) must be 1, 2 or 3, got {}",
+                self.message_format_version
+            ));
+        }
+
+        if let Some(key) = &self.field_encryption_key {
+            if let Err(e) = FieldCipher::new(key, &self.field_encryption_previous_keys) {
+                problems.push(format!(
+                    "field_encryption_key (FIELD_ENCRYPTION_KEY) and field_encryption_previous_keys must be base64-encoded 32-byte keys: {}",
+                    e
+                ));
+            }
+        }
+
+        match webauthn_rs::prelude::Url::parse(&self.webauthn_rp_origin) {
+            Ok(origin) if origin.host_str().map_or(false, |host| {
+                host == self.webauthn_rp_id || host.ends_with(&format!(".{}", self.webauthn_rp_id))
+            }) => {}
+            _ => problems.push(format!(
+                "webauthn_rp_origin (WEBAUTHN_RP_ORIGIN) must be a URL on webauthn_rp_id ({}), got {}",
+                self.webauthn_rp_id, self.webauthn_rp_origin
+            )),
+        }
+
+        if let Some(tsa_url) = &self.tsa_url {
+            if !reqwest::Url::parse(tsa_url).map_or(false, |url| matches!(url.scheme(), "http" | "https")) {
+                problems.push(format!("tsa_url (TSA_URL) must be an http(s) URL, got {}", tsa_url));
+            }
+        }
+
+        let ipfs_urls = [
+            ("ipfs_api_url", "IPFS_API_URL", self.ipfs_api_url.as_deref()),
+            ("ipfs_gateway_url", "IPFS_GATEWAY_URL", Some(self.ipfs_gateway_url.as_str())),
+        ];
+        for (key, var, url) in ipfs_urls {
+            if let Some(url) = url {
+                if !reqwest::Url::parse(url).map_or(false, |url| matches!(url.scheme(), "http" | "https")) {
+                    problems.push(format!("{} ({}) must be an http(s) URL, got {}", key, var, url));
+                }
+            }
+        }
+
+        if self.smtp_host.is_some() && self.smtp_from.parse::<lettre::message::Mailbox>().is_err() {
+            problems.push(format!("smtp_from (SMTP_FROM) must be an email address, got {}", self.smtp_from));
+        }
+
+        if !reqwest::Url::parse(&self.public_base_url).map_or(false, |url| matches!(url.scheme(), "http" | "https")) {
+            problems.push(format!("public_base_url (PUBLIC_BASE_URL) must be an http(s) URL, got {}", self.public_base_url));
+        }
+
+        if let Some(certificate_path) = &self.apple_pass_certificate_path {
+            if !Path::new(certificate_path).is_file() {
+                problems.push(format!(
+                    "apple_pass_certificate_path (APPLE_PASS_CERTIFICATE_PATH) must point to a .p12 file, but {} does not exist",
+                    certificate_path
+                ));
+            }
+            match &self.apple_wwdr_certificate_path {
+                Some(path) if !Path::new(path).is_file() => problems.push(format!(
+                    "apple_wwdr_certificate_path (APPLE_WWDR_CERTIFICATE_PATH) must point to Apple's WWDR certificate, but {} does not exist",
+                    path
+                )),
+                Some(_) => {}
+                None => problems.push(
+                    "apple_wwdr_certificate_path (APPLE_WWDR_CERTIFICATE_PATH) must be set when apple_pass_certificate_path is set".to_string(),
+                ),
+            }
+            let required = [
+                ("apple_pass_type_identifier", "APPLE_PASS_TYPE_IDENTIFIER", &self.apple_pass_type_identifier),
+                ("apple_team_identifier", "APPLE_TEAM_IDENTIFIER", &self.apple_team_identifier),
+            ];
+            for (key, var, value) in required {
+                if value.as_deref().map_or(true, |value| value.trim().is_empty()) {
+                    problems.push(format!("{} ({}) must be set when apple_pass_certificate_path is set", key, var));
+                }
+            }
+        }
+
+        if self.google_wallet_enabled {
+            match &self.google_wallet_service_account_path {
+                Some(path) if !Path::new(path).is_file() => problems.push(format!(
+                    "google_wallet_service_account_path (GOOGLE_WALLET_SERVICE_ACCOUNT_PATH) must point to a service-account key file, but {} does not exist",
+                    path
+                )),
+                Some(_) => {}
+                None => problems.push(
+                    "google_wallet_service_account_path (GOOGLE_WALLET_SERVICE_ACCOUNT_PATH) must be set when google_wallet_enabled is true".to_string(),
+                ),
+            }
+            if self.google_wallet_issuer_id.as_deref().map_or(true, |id| id.trim().is_empty()) {
+                problems.push("google_wallet_issuer_id (GOOGLE_WALLET_ISSUER_ID) must be set when google_wallet_enabled is true".to_string());
+            }
+        }
+
+        if let Some(endpoint) = &self.s3_endpoint {
+            if !reqwest::Url::parse(endpoint).map_or(false, |url| matches!(url.scheme(), "http" | "https")) {
+                problems.push(format!("s3_endpoint (S3_ENDPOINT) must be an http(s) URL, got {}", endpoint));
+            }
+            let required = [
+                ("s3_bucket", "S3_BUCKET", &self.s3_bucket),
+                ("s3_access_key_id", "S3_ACCESS_KEY_ID", &self.s3_access_key_id),
+                ("s3_secret_access_key", "S3_SECRET_ACCESS_KEY", &self.s3_secret_access_key),
+            ];
+            for (key, var, value) in required {
+                if value.as_deref().map_or(true, |value| value.trim().is_empty()) {
+                    problems.push(format!("{} ({}) must be set when s3_endpoint is set", key, var));
+                }
+            }
+        }
+
+        if self.blockchain_enabled {
+            let required = [
+                ("blockchain_private_key", "BLOCKCHAIN_PRIVATE_KEY", &self.blockchain_private_key),
+                ("blockchain_rpc_url", "BLOCKCHAIN_RPC_URL", &self.blockchain_rpc_url),
+                ("zk_health_pass_registry_address", "ZK_HEALTH_PASS_REGISTRY_ADDRESS", &self.zk_health_pass_registry_address),
+                ("zk_proof_verifier_address", "ZK_PROOF_VERIFIER_ADDRESS", &self.zk_proof_verifier_address),
+                ("health_authority_registry_address", "HEALTH_AUTHORITY_REGISTRY_ADDRESS", &self.health_authority_registry_address),
+            ];
+            for (key, var, value) in required {
+                if value.trim().is_empty() {
+                    problems.push(format!("{} ({}) must be set when blockchain_enabled is true", key, var));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            anyhow::bail!("Invalid configuration:\n  - {}", problems.join("\n  - "))
+        }
+    }
+}
+
+/// Config file path from `--config <path>` / `--config=<path>`, else `CONFIG_FILE`
+fn config_file_path(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+
+    env::var("CONFIG_FILE").ok().filter(|path| !path.is_empty()).map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const FILE: &str = r#"
+        database_url = "postgresql://file@localhost/zk_health_pass"
+        jwt_secret = "file-secret"
+        rate_limit_requests_per_minute = 120
+        cors_origins = ["https://app.example.com"]
+        blockchain_enabled = true
+        blockchain_private_key = "0xabc"
+    "#;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_file_values_fill_in_over_defaults() {
+        let config = Config::build(Some(FILE), env(&[])).unwrap();
+
+        assert_eq!(config.database_url, "postgresql://file@localhost/zk_health_pass");
+        assert_eq!(config.rate_limit_requests_per_minute, 120);
+        assert_eq!(config.cors_origins, vec!["https://app.example.com"]);
+        assert!(config.blockchain_enabled);
+        // Untouched keys keep their defaults
+        assert_eq!(config.request_timeout_secs, 30);
+        assert_eq!(config.log_format, LogFormat::Text);
+        assert_eq!(config.proof_backend, ProofBackendKind::Nargo);
+        assert_eq!(config.redis_url, None);
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let config = Config::build(
+            Some(FILE),
+            env(&[
+                ("JWT_SECRET", "env-secret"),
+                ("RATE_LIMIT_RPM", "0"),
+                ("CORS_ORIGINS", "http://a.test, http://b.test"),
+                ("LOG_FORMAT", "JSON"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(config.jwt_secret, "env-secret");
+        assert_eq!(config.rate_limit_requests_per_minute, 0);
+        assert_eq!(config.cors_origins, vec!["http://a.test", "http://b.test"]);
+        assert_eq!(config.log_format, LogFormat::Json);
+        assert_eq!(config.blockchain_private_key, "0xabc");
+    }
+
+    #[test]
+    fn test_missing_required_fields_are_all_reported() {
+        let error = Config::build(Some("rate_limit_requests_per_minute = 5"), env(&[])).unwrap_err();
+        let message = error.to_string();
+
+        assert!(message.contains("database_url (DATABASE_URL)"));
+        assert!(message.contains("jwt_secret (JWT_SECRET)"));
+    }
+
+    #[test]
+    fn test_validate_accepts_sound_config() {
+        let config = Config::build(
+            None,
+            env(&[
+                ("DATABASE_URL", "postgresql://localhost/zk_health_pass"),
+                ("JWT_SECRET", "a-jwt-secret-that-is-long-enough-to-use"),
+                ("NOIR_CIRCUIT_PATH", concat!(env!("CARGO_MANIFEST_DIR"), "/../noir")),
+            ]),
+        )
+        .unwrap();
+
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_stub_proof_backend_needs_no_circuits() {
+        let config = Config::build(
+            None,
+            env(&[
+                ("DATABASE_URL", "postgresql://localhost/zk_health_pass"),
+                ("JWT_SECRET", "a-jwt-secret-that-is-long-enough-to-use"),
+                ("NOIR_CIRCUIT_PATH", "/nonexistent/noir"),
+                ("PROOF_BACKEND", "Stub"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(config.proof_backend, ProofBackendKind::Stub);
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_validate_lists_every_problem() {
+        let config = Config::build(
+            None,
+            env(&[
+                ("DATABASE_URL", "postgresql://localhost/zk_health_pass"),
+                ("JWT_SECRET", "short"),
+                ("NOIR_CIRCUIT_PATH", "/nonexistent/noir"),
+                ("BLOCKCHAIN_ENABLED", "true"),
+                ("FIELD_ENCRYPTION_KEY", "too-short"),
+                ("WEBAUTHN_RP_ORIGIN", "https://evil.example"),
+                ("IPFS_API_URL", "localhost:5001"),
+                ("GEOIP_ENABLED", "true"),
+                ("GEOIP_DB_PATH", "/nonexistent/GeoLite2-City.mmdb"),
+                ("SMTP_HOST", "smtp.example.com"),
+                ("SMTP_FROM", "VeryFi"),
+                ("APPLE_PASS_CERTIFICATE_PATH", "/nonexistent/pass.p12"),
+                ("GOOGLE_WALLET_ENABLED", "true"),
+                ("S3_ENDPOINT", "minio:9000"),
+                ("CAPTCHA_PROVIDER", "Turnstile"),
+            ]),
+        )
+        .unwrap();
+
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("field_encryption_key (FIELD_ENCRYPTION_KEY)"));
+        assert!(message.contains("webauthn_rp_origin (WEBAUTHN_RP_ORIGIN) must be a URL on webauthn_rp_id"));
+        assert!(message.contains("ipfs_api_url (IPFS_API_URL) must be an http(s) URL"));
+        assert!(message.contains("/nonexistent/GeoLite2-City.mmdb does not exist"));
+        assert!(message.contains("smtp_from (SMTP_FROM) must be an email address"));
+        assert!(message.contains("/nonexistent/pass.p12 does not exist"));
+        assert!(message.contains("apple_wwdr_certificate_path (APPLE_WWDR_CERTIFICATE_PATH) must be set"));
+        assert!(message.contains("apple_team_identifier (APPLE_TEAM_IDENTIFIER) must be set"));
+        assert!(message.contains("google_wallet_service_account_path (GOOGLE_WALLET_SERVICE_ACCOUNT_PATH) must be set"));
+        assert!(message.contains("google_wallet_issuer_id (GOOGLE_WALLET_ISSUER_ID) must be set"));
+        assert!(message.contains("s3_endpoint (S3_ENDPOINT) must be an http(s) URL"));
+        assert!(message.contains("s3_bucket (S3_BUCKET) must be set when s3_endpoint is set"));
+        assert!(message.contains("captcha_secret (CAPTCHA_SECRET) must be set when captcha_provider is set"));
+        assert!(message.contains("jwt_secret (JWT_SECRET) must be at least 32 characters"));
+        assert!(message.contains("/nonexistent/noir/Nargo.toml does not exist"));
+        assert!(message.contains("blockchain_private_key (BLOCKCHAIN_PRIVATE_KEY) must be set"));
+        assert!(message.contains("zk_proof_verifier_address (ZK_PROOF_VERIFIER_ADDRESS) must be set"));
+    }
+
+    #[test]
+    fn test_config_flag_is_read_from_args() {
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>().into_iter();
+
+        assert_eq!(config_file_path(args(&["--config", "app.toml"])), Some(PathBuf::from("app.toml")));
+        assert_eq!(config_file_path(args(&["--config=/etc/zk.toml"])), Some(PathBuf::from("/etc/zk.toml")));
     }
 }
@@ -1,18 +1,27 @@
 use axum::{
-    http::StatusCode,
+    BoxError,
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::json;
+use std::collections::HashMap;
 use thiserror::Error;
 
+use crate::i18n::{current_locale, Locale};
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
-    #[error("Validation error: {0}")]
-    Validation(String),
+    /// `message` is the flat summary used in logs; `fields` maps each invalid
+    /// field to its messages when the error came from request validation
+    #[error("Validation error: {message}")]
+    Validation {
+        message: String,
+        fields: Option<HashMap<String, Vec<String>>>,
+    },
 
     #[error("Authentication error: {0}")]
     Unauthorized(String),
@@ -35,8 +44,14 @@ pub enum AppError {
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
 
-    #[error("Rate limit exceeded")]
-    RateLimitExceeded,
+    #[error("Rate limit exceeded; retry after {retry_after_secs}s")]
+    RateLimitExceeded { retry_after_secs: u64 },
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Request timed out")]
+    RequestTimeout,
 
     #[error("Invalid proof: {0}")]
     InvalidProof(String),
@@ -47,16 +62,25 @@ pub enum AppError {
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let fields = match &self {
+            AppError::Validation { fields, .. } => fields.clone(),
+            _ => None,
+        };
+        let retry_after_secs = match &self {
+            AppError::RateLimitExceeded { retry_after_secs } => Some(*retry_after_secs),
+            _ => None,
+        };
+
         let (status, error_message, error_code) = match self {
             AppError::Database(ref e) => {
-                tracing::error!("Database error: {:?}", e);
+                tracing::error!(error = ?e, code = "DATABASE_ERROR", "Database error");
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     "Internal server error".to_string(),
                     "DATABASE_ERROR",
                 )
             }
-            AppError::Validation(ref message) => (
+            AppError::Validation { ref message, .. } => (
                 StatusCode::BAD_REQUEST,
                 message.clone(),
                 "VALIDATION_ERROR",
@@ -87,7 +111,7 @@ impl IntoResponse for AppError {
                 "BAD_REQUEST",
             ),
             AppError::InternalServerError(ref message) => {
-                tracing::error!("Internal server error: {}", message);
+                tracing::error!(error = %message, code = "INTERNAL_SERVER_ERROR", "Internal server error");
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     "Internal server error".to_string(),
@@ -99,18 +123,28 @@ impl IntoResponse for AppError {
                 message.clone(),
                 "SERVICE_UNAVAILABLE",
             ),
-            AppError::RateLimitExceeded => (
+            AppError::RateLimitExceeded { .. } => (
                 StatusCode::TOO_MANY_REQUESTS,
                 "Rate limit exceeded".to_string(),
                 "RATE_LIMIT_EXCEEDED",
             ),
+            AppError::PayloadTooLarge(ref message) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                message.clone(),
+                "PAYLOAD_TOO_LARGE",
+            ),
+            AppError::RequestTimeout => (
+                StatusCode::REQUEST_TIMEOUT,
+                "Request timed out".to_string(),
+                "REQUEST_TIMEOUT",
+            ),
             AppError::InvalidProof(ref message) => (
                 StatusCode::BAD_REQUEST,
                 format!("Invalid proof: {}", message),
                 "INVALID_PROOF",
             ),
             AppError::CryptographicError(ref message) => {
-                tracing::error!("Cryptographic error: {}", message);
+                tracing::error!(error = %message, code = "CRYPTOGRAPHIC_ERROR", "Cryptographic error");
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     "Cryptographic operation failed".to_string(),
@@ -119,33 +153,179 @@ impl IntoResponse for AppError {
             }
         };
 
-        let body = Json(json!({
-            "error": {
-                "code": error_code,
-                "message": error_message,
-                "timestamp": chrono::Utc::now().to_rfc3339(),
+        // Clients get the message in their language; logs keep the canonical English
+        let locale = current_locale();
+        if locale != Locale::En {
+            tracing::debug!(code = error_code, message = %error_message, %locale, "Localizing error response");
+        }
+        let (error_message, fields) = match (locale, fields) {
+            (Locale::En, fields) => (error_message, fields),
+            (_, Some(fields)) => {
+                let fields = localize_fields(locale, fields);
+                (flat_validation_message(&fields), Some(fields))
             }
-        }));
+            (_, None) => (locale.translate(&error_message), None),
+        };
+
+        let mut error = json!({
+            "code": error_code,
+            "message": error_message,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        });
+        if let Some(fields) = &fields {
+            error["fields"] = json!(fields);
+        }
+        if let Some(retry_after_secs) = retry_after_secs {
+            error["retry_after_secs"] = json!(retry_after_secs);
+        }
+        let body = Json(json!({ "error": error }));
+
+        // Kept on the response so it can be re-rendered as problem+json on request
+        let details = ErrorDetails {
+            status,
+            code: error_code,
+            message: error_message,
+            fields,
+            retry_after_secs,
+        };
+
+        let mut response = (status, body).into_response();
+        if let Some(retry_after_secs) = retry_after_secs {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(retry_after_secs));
+        }
+        response.extensions_mut().insert(details);
+        response
+    }
+}
+
+/// The parts of an error response, independent of how the body is rendered
+#[derive(Debug, Clone)]
+pub struct ErrorDetails {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    pub fields: Option<HashMap<String, Vec<String>>>,
+    pub retry_after_secs: Option<u64>,
+}
+
+impl ErrorDetails {
+    /// Stable RFC 7807 `type` URI for the error code, e.g. `NOT_FOUND` ->
+    /// `urn:zk-health-pass:problem:not-found`
+    pub fn problem_type(&self) -> String {
+        format!("urn:zk-health-pass:problem:{}", self.code.to_lowercase().replace('_', "-"))
+    }
 
-        (status, body).into_response()
+    /// RFC 7807 problem details object; `code`, `fields` and `retry_after_secs`
+    /// are kept as extension members
+    pub fn to_problem_json(&self, instance: &str) -> serde_json::Value {
+        let mut problem = json!({
+            "type": self.problem_type(),
+            "title": self.status.canonical_reason().unwrap_or("Error"),
+            "status": self.status.as_u16(),
+            "detail": self.message,
+            "instance": instance,
+            "code": self.code,
+        });
+        if let Some(fields) = &self.fields {
+            problem["fields"] = json!(fields);
+        }
+        if let Some(retry_after_secs) = self.retry_after_secs {
+            problem["retry_after_secs"] = json!(retry_after_secs);
+        }
+        problem
+    }
+}
+
+// Map errors raised by tower middleware (e.g. timeouts) into API errors
+pub async fn handle_middleware_error(err: BoxError) -> AppError {
+    if err.is::<tower::timeout::error::Elapsed>() {
+        AppError::RequestTimeout
+    } else {
+        AppError::InternalServerError(format!("Unhandled middleware error: {}", err))
     }
 }
 
 // Helper function to convert validation errors
 pub fn validation_error(errors: validator::ValidationErrors) -> AppError {
-    let error_messages: Vec<String> = errors
-        .field_errors()
+    let mut fields: HashMap<String, Vec<String>> = HashMap::new();
+    for (field, errors) in errors.field_errors() {
+        fields.insert(
+            field.to_string(),
+            errors
+                .iter()
+                .map(|error| {
+                    error
+                        .message
+                        .as_ref()
+                        .map(|message| message.to_string())
+                        .unwrap_or_else(|| format!("Invalid value ({})", error.code))
+                })
+                .collect(),
+        );
+    }
+
+    AppError::Validation {
+        message: flat_validation_message(&fields),
+        fields: Some(fields),
+    }
+}
+
+/// `field: message` pairs joined into one line, sorted so it is stable for logs
+fn flat_validation_message(fields: &HashMap<String, Vec<String>>) -> String {
+    let mut field_names: Vec<&String> = fields.keys().collect();
+    field_names.sort();
+    field_names
         .iter()
-        .flat_map(|(field, errors)| {
-            errors.iter().map(move |error| {
-                format!(
-                    "{}: {}",
-                    field,
-                    error.message.as_ref().unwrap_or(&"Invalid value".into())
-                )
-            })
+        .flat_map(|field| fields[*field].iter().map(move |message| format!("{}: {}", field, message)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn localize_fields(locale: Locale, fields: HashMap<String, Vec<String>>) -> HashMap<String, Vec<String>> {
+    fields
+        .into_iter()
+        .map(|(field, messages)| {
+            let messages = messages.iter().map(|message| locale.translate(message)).collect();
+            (field, messages)
         })
-        .collect();
+        .collect()
+}
 
-    AppError::Validation(error_messages.join(", "))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CreateUserRequest;
+    use axum::body::to_bytes;
+    use validator::Validate;
+
+    #[tokio::test]
+    async fn test_validation_errors_are_reported_per_field() {
+        let request = CreateUserRequest {
+            email: "not-an-email".to_string(),
+            password: "short".to_string(),
+            full_name: "A".to_string(),
+            role: None,
+            org_id: None,
+        };
+        let error = validation_error(request.validate().unwrap_err());
+
+        assert_eq!(
+            error.to_string(),
+            "Validation error: email: Invalid value (email), full_name: Full name must be at least 2 characters, password: Password must be at least 8 characters"
+        );
+
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+
+        assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+        let fields = &body["error"]["fields"];
+        assert_eq!(fields["password"], json!(["Password must be at least 8 characters"]));
+        assert_eq!(fields["full_name"], json!(["Full name must be at least 2 characters"]));
+        assert_eq!(fields["email"], json!(["Invalid value (email)"]));
+        assert!(fields.get("role").is_none());
+    }
 }
@@ -0,0 +1,54 @@
+//! Batch loaders for the fields a list of records would otherwise resolve with
+//! one query per record. Each request gets its own loaders, so results are
+//! cached for the request only.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::dataloader::Loader;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{graphql_error, types::Authority};
+use crate::models::{HealthAuthority, ProofResponse};
+use crate::services::ZkProofService;
+
+/// Authorities by ID
+pub struct AuthorityLoader {
+    pub db: PgPool,
+}
+
+impl Loader<Uuid> for AuthorityLoader {
+    type Value = Authority;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, authority_ids: &[Uuid]) -> Result<HashMap<Uuid, Authority>, Self::Error> {
+        let authorities = sqlx::query_as::<_, HealthAuthority>("SELECT * FROM health_authorities WHERE id = ANY($1)")
+            .bind(authority_ids)
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| graphql_error(e.into()))?;
+
+        Ok(authorities
+            .into_iter()
+            .map(|authority| (authority.id, Authority(authority.into())))
+            .collect())
+    }
+}
+
+/// Proofs by the ID of the record they were generated from, for records owned by `owner_id`
+pub struct RecordProofsLoader {
+    pub zk_proof_service: Arc<ZkProofService>,
+    pub owner_id: Uuid,
+}
+
+impl Loader<Uuid> for RecordProofsLoader {
+    type Value = Vec<ProofResponse>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, record_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<ProofResponse>>, Self::Error> {
+        self.zk_proof_service
+            .get_record_proofs(record_ids, self.owner_id)
+            .await
+            .map_err(graphql_error)
+    }
+}
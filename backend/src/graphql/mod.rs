@@ -0,0 +1,278 @@
+//! Read-only GraphQL API over the caller's account, served at `POST /graphql`.
+//!
+//! Resolvers call the same services as the REST handlers and see what the
+//! authenticated user would see there. A record's `authority` and `proofs` go
+//! through per-request [`DataLoader`]s, so a page of records costs one query
+//! for all of their authorities and one for all of their proofs.
+
+mod loaders;
+mod types;
+
+use async_graphql::{
+    dataloader::DataLoader, Context, EmptyMutation, EmptySubscription, ErrorExtensions, Object, Result, Schema,
+};
+use axum::response::IntoResponse;
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::{
+    errors::{AppError, ErrorDetails},
+    middleware::auth::AuthUser,
+    models::{AccessType, AuthorityQuery, Cursor, HealthRecordQuery, ProofSort},
+    services::HealthRecordService,
+    AppState,
+};
+use loaders::{AuthorityLoader, RecordProofsLoader};
+use types::{Authority, HealthRecord, HealthRecordFilter, HealthRecordPage, Proof, ProofPage, User};
+
+/// Deepest selection a query may nest
+const MAX_QUERY_DEPTH: usize = 8;
+/// Upper bound on a query's complexity, one per selected field
+const MAX_QUERY_COMPLEXITY: usize = 500;
+/// Largest page, as on the REST list endpoints
+const MAX_PAGE_SIZE: u32 = 100;
+
+pub type ApiSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn schema() -> ApiSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+/// Run `request` on behalf of `auth_user`
+pub async fn execute(
+    schema: &ApiSchema,
+    state: &AppState,
+    auth_user: AuthUser,
+    request: async_graphql::Request,
+) -> async_graphql::Response {
+    let authority_loader = AuthorityLoader { db: state.auth_service.db.clone() };
+    let record_proofs_loader = RecordProofsLoader {
+        zk_proof_service: state.zk_proof_service.clone(),
+        owner_id: auth_user.user.id,
+    };
+
+    let request = request
+        .data(DataLoader::new(authority_loader, tokio::spawn))
+        .data(DataLoader::new(record_proofs_loader, tokio::spawn))
+        .data(state.clone())
+        .data(auth_user);
+    schema.execute(request).await
+}
+
+/// The message and `code` extension the REST API would answer `error` with
+fn graphql_error(error: AppError) -> async_graphql::Error {
+    let response = error.into_response();
+    let Some(details) = response.extensions().get::<ErrorDetails>() else {
+        return async_graphql::Error::new("Internal server error");
+    };
+    let code = details.code;
+    async_graphql::Error::new(details.message.clone()).extend_with(|_, extensions| extensions.set("code", code))
+}
+
+/// An enum argument given by its REST name, e.g. `Vaccination`
+fn parse_enum<T: DeserializeOwned>(argument: &str, value: &str) -> Result<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| graphql_error(AppError::BadRequest(format!("Invalid {}: {}", argument, value))))
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The authenticated user
+    async fn user(&self, ctx: &Context<'_>) -> User {
+        User(ctx.data_unchecked::<AuthUser>().user.clone().into())
+    }
+
+    /// Health records owned by the caller, as listed by `GET /api/v1/health-records`
+    async fn health_records(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<HealthRecordFilter>,
+        page: Option<u32>,
+        limit: Option<u32>,
+        cursor: Option<String>,
+        #[graphql(desc = "Why the records are read; required while the access log is enabled")] purpose: Option<String>,
+    ) -> Result<HealthRecordPage> {
+        let state = ctx.data_unchecked::<AppState>();
+        let auth_user = ctx.data_unchecked::<AuthUser>();
+        let filter = filter.unwrap_or_default();
+        let purpose = state.access_log_service.purpose(purpose.as_deref()).map_err(graphql_error)?;
+
+        let query = HealthRecordQuery {
+            record_type: filter.record_type.as_deref().map(|value| parse_enum("recordType", value)).transpose()?,
+            authority_id: filter.authority_id,
+            from_date: filter.from_date,
+            to_date: filter.to_date,
+            include_revoked: filter.include_revoked,
+            page,
+            limit,
+            cursor,
+            sort: None,
+            search: filter.search,
+        };
+        let records = HealthRecordService::new(state.auth_service.clone(), state.crypto_service.clone())
+            .get_user_health_records(auth_user.user.id, query)
+            .await
+            .map_err(graphql_error)?;
+
+        let record_ids: Vec<Uuid> = records.data.iter().map(|record| record.id).collect();
+        state.access_log_service
+            .record(auth_user.user.id, auth_user.acting_admin, &record_ids, AccessType::List, purpose.as_deref())
+            .await
+            .map_err(graphql_error)?;
+
+        Ok(HealthRecordPage {
+            data: records.data.into_iter().map(HealthRecord).collect(),
+            next_cursor: records.next_cursor,
+        })
+    }
+
+    /// A health record the caller owns or was shared
+    async fn health_record(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        #[graphql(desc = "Why the record is read; required while the access log is enabled")] purpose: Option<String>,
+    ) -> Result<HealthRecord> {
+        let state = ctx.data_unchecked::<AppState>();
+        let auth_user = ctx.data_unchecked::<AuthUser>();
+        let purpose = state.access_log_service.purpose(purpose.as_deref()).map_err(graphql_error)?;
+
+        let record = HealthRecordService::new(state.auth_service.clone(), state.crypto_service.clone())
+            .get_health_record_by_id(id, Some(auth_user.user.id))
+            .await
+            .map_err(graphql_error)?;
+
+        state.access_log_service
+            .record(auth_user.user.id, auth_user.acting_admin, &[record.id], AccessType::Read, purpose.as_deref())
+            .await
+            .map_err(graphql_error)?;
+
+        Ok(HealthRecord(record))
+    }
+
+    /// Proofs of the caller's records, newest first
+    async fn proofs(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+        page: Option<u32>,
+        limit: Option<u32>,
+        cursor: Option<String>,
+    ) -> Result<ProofPage> {
+        let state = ctx.data_unchecked::<AppState>();
+        let auth_user = ctx.data_unchecked::<AuthUser>();
+        let status = status.as_deref().map(|value| parse_enum("status", value)).transpose()?;
+        let cursor = cursor.as_deref().map(Cursor::decode).transpose().map_err(graphql_error)?;
+
+        let proofs = state
+            .zk_proof_service
+            .get_user_proofs(
+                auth_user.user.id,
+                page.unwrap_or(1),
+                limit.unwrap_or(20).min(MAX_PAGE_SIZE),
+                cursor,
+                status,
+                ProofSort::default(),
+            )
+            .await
+            .map_err(graphql_error)?;
+
+        Ok(ProofPage {
+            data: proofs.data.into_iter().map(Proof).collect(),
+            next_cursor: proofs.next_cursor,
+        })
+    }
+
+    /// Health authorities of the caller's organization, newest first
+    async fn authorities(
+        &self,
+        ctx: &Context<'_>,
+        authority_type: Option<String>,
+        is_active: Option<bool>,
+        search: Option<String>,
+        page: Option<u32>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Authority>> {
+        let state = ctx.data_unchecked::<AppState>();
+        let auth_user = ctx.data_unchecked::<AuthUser>();
+
+        let query = AuthorityQuery {
+            authority_type: authority_type
+                .as_deref()
+                .map(|value| parse_enum("authorityType", value))
+                .transpose()?,
+            is_active,
+            search,
+            page,
+            limit,
+        };
+        let authorities =
+            crate::routes::health_authorities::list_authorities(&state.auth_service.db, &query, auth_user.org_scope())
+                .await
+                .map_err(graphql_error)?;
+
+        Ok(authorities.into_iter().map(|authority| Authority(authority.into())).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::Value;
+
+    #[test]
+    fn test_errors_carry_the_rest_message_and_code() {
+        let error = graphql_error(AppError::NotFound("Health record not found".to_string()));
+        assert_eq!(error.message, "Health record not found");
+        assert_eq!(error.extensions.unwrap().get("code"), Some(&Value::from("NOT_FOUND")));
+
+        // Internal details stay in the logs
+        let error = graphql_error(AppError::Database(sqlx::Error::PoolTimedOut));
+        assert_eq!(error.message, "Internal server error");
+        assert_eq!(error.extensions.unwrap().get("code"), Some(&Value::from("DATABASE_ERROR")));
+    }
+
+    #[test]
+    fn test_schema_is_read_only() {
+        let sdl = schema().sdl();
+        assert!(sdl.contains("healthRecords("));
+        assert!(sdl.contains("authorities("));
+        assert!(!sdl.contains("type Mutation"));
+
+        let error = parse_enum::<crate::models::HealthRecordType>("recordType", "XRay").unwrap_err();
+        assert_eq!(error.message, "Invalid recordType: XRay");
+        assert!(parse_enum::<crate::models::HealthRecordType>("recordType", "Vaccination").is_ok());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_authorities_are_loaded_in_one_batch(db: sqlx::PgPool) {
+        let mut authority_ids = Vec::new();
+        for name in ["Clinic A", "Clinic B"] {
+            let id: Uuid = sqlx::query_scalar(
+                "INSERT INTO health_authorities (name, authority_type, public_key) VALUES ($1, 'clinic', '02') RETURNING id"
+            )
+            .bind(name)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+            authority_ids.push(id);
+        }
+        let missing = Uuid::new_v4();
+
+        let loader = DataLoader::new(AuthorityLoader { db }, tokio::spawn);
+        let authorities = loader
+            .load_many([authority_ids[0], authority_ids[1], missing])
+            .await
+            .unwrap();
+
+        assert_eq!(authorities.len(), 2);
+        assert_eq!(authorities[&authority_ids[0]].0.name, "Clinic A");
+        assert_eq!(authorities[&authority_ids[1]].0.name, "Clinic B");
+        assert!(!authorities.contains_key(&missing));
+    }
+}
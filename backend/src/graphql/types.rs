@@ -0,0 +1,293 @@
+//! GraphQL views of the REST response models. Enum values are rendered the way
+//! the REST API serializes them, e.g. `recordType: "Vaccination"`.
+
+use async_graphql::{dataloader::DataLoader, Context, InputObject, Json, Object, Result, SimpleObject};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::loaders::{AuthorityLoader, RecordProofsLoader};
+use crate::models::{AuthorityResponse, HealthRecordResponse, ProofResponse, SigningAuthority, UserResponse};
+
+/// The value `value` serializes to in REST responses, for enums
+fn json_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        Ok(other) => other.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+pub struct User(pub UserResponse);
+
+#[Object]
+impl User {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn email(&self) -> &str {
+        &self.0.email
+    }
+
+    async fn full_name(&self) -> &str {
+        &self.0.full_name
+    }
+
+    async fn role(&self) -> String {
+        json_name(&self.0.role)
+    }
+
+    async fn is_verified(&self) -> bool {
+        self.0.is_verified
+    }
+
+    async fn org_id(&self) -> Uuid {
+        self.0.org_id
+    }
+
+    async fn email_notifications(&self) -> bool {
+        self.0.email_notifications
+    }
+
+    async fn locale(&self) -> Option<&str> {
+        self.0.locale.as_deref()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+}
+
+pub struct HealthRecord(pub HealthRecordResponse);
+
+#[Object]
+impl HealthRecord {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn record_type(&self) -> String {
+        json_name(&self.0.record_type)
+    }
+
+    async fn patient_identifier(&self) -> &str {
+        &self.0.patient_identifier
+    }
+
+    async fn details(&self) -> Json<serde_json::Value> {
+        Json(self.0.details.clone())
+    }
+
+    async fn issue_date(&self) -> NaiveDate {
+        self.0.issue_date
+    }
+
+    async fn expiry_date(&self) -> Option<NaiveDate> {
+        self.0.expiry_date
+    }
+
+    async fn authority_name(&self) -> &str {
+        &self.0.authority_name
+    }
+
+    /// The issuing authority, loaded together with those of the other records in the response
+    async fn authority(&self, ctx: &Context<'_>) -> Result<Option<Authority>> {
+        ctx.data_unchecked::<DataLoader<AuthorityLoader>>()
+            .load_one(self.0.authority_id)
+            .await
+    }
+
+    /// Proofs generated from the record, newest first
+    async fn proofs(&self, ctx: &Context<'_>) -> Result<Vec<Proof>> {
+        let proofs = ctx
+            .data_unchecked::<DataLoader<RecordProofsLoader>>()
+            .load_one(self.0.id)
+            .await?;
+        Ok(proofs.unwrap_or_default().into_iter().map(Proof).collect())
+    }
+
+    async fn is_revoked(&self) -> bool {
+        self.0.is_revoked
+    }
+
+    async fn revoked_at(&self) -> Option<DateTime<Utc>> {
+        self.0.revoked_at
+    }
+
+    async fn revocation_reason(&self) -> Option<&str> {
+        self.0.revocation_reason.as_deref()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn has_valid_signature(&self) -> bool {
+        self.0.has_valid_signature
+    }
+
+    async fn integrity_ok(&self) -> bool {
+        self.0.integrity_ok
+    }
+
+    async fn signing_authorities(&self) -> Vec<RecordSigner> {
+        self.0.signing_authorities.iter().cloned().map(RecordSigner).collect()
+    }
+
+    async fn series_id(&self) -> Option<Uuid> {
+        self.0.series_id
+    }
+}
+
+/// An authority whose signature a record requires
+pub struct RecordSigner(SigningAuthority);
+
+#[Object]
+impl RecordSigner {
+    async fn authority_id(&self) -> Uuid {
+        self.0.authority_id
+    }
+
+    async fn authority_name(&self) -> &str {
+        &self.0.authority_name
+    }
+
+    /// 0 is the record's own authority
+    async fn position(&self) -> i32 {
+        self.0.position
+    }
+
+    async fn signed(&self) -> bool {
+        self.0.signed
+    }
+}
+
+pub struct Proof(pub ProofResponse);
+
+#[Object]
+impl Proof {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    /// Base64 encoded proof
+    async fn proof_data(&self) -> &str {
+        &self.0.proof_data
+    }
+
+    /// Base64 encoded verification key
+    async fn verification_key(&self) -> &str {
+        &self.0.verification_key
+    }
+
+    async fn proof_type(&self) -> String {
+        json_name(&self.0.proof_type)
+    }
+
+    async fn generated_at(&self) -> DateTime<Utc> {
+        self.0.generated_at
+    }
+
+    async fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.0.expires_at
+    }
+
+    async fn usage_count(&self) -> i32 {
+        self.0.usage_count
+    }
+
+    async fn max_usage(&self) -> Option<i32> {
+        self.0.max_usage
+    }
+
+    async fn health_record_type(&self) -> &str {
+        &self.0.health_record_type
+    }
+
+    async fn status(&self) -> String {
+        json_name(&self.0.status)
+    }
+
+    async fn timestamped_at(&self) -> Option<DateTime<Utc>> {
+        self.0.timestamped_at
+    }
+
+    async fn anchor_tx_hash(&self) -> Option<&str> {
+        self.0.anchor_tx_hash.as_deref()
+    }
+
+    async fn proof_cid(&self) -> Option<&str> {
+        self.0.proof_cid.as_deref()
+    }
+}
+
+#[derive(Clone)]
+pub struct Authority(pub AuthorityResponse);
+
+#[Object]
+impl Authority {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn authority_type(&self) -> String {
+        json_name(&self.0.authority_type)
+    }
+
+    /// SEC1 public key on `curve` in hex format
+    async fn public_key(&self) -> &str {
+        &self.0.public_key
+    }
+
+    async fn curve(&self) -> String {
+        json_name(&self.0.curve)
+    }
+
+    async fn has_certificate(&self) -> bool {
+        self.0.has_certificate
+    }
+
+    async fn is_active(&self) -> bool {
+        self.0.is_active
+    }
+
+    async fn org_id(&self) -> Uuid {
+        self.0.org_id
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct HealthRecordPage {
+    pub data: Vec<HealthRecord>,
+    /// Pass as `cursor` to fetch the next page; null on the last page
+    pub next_cursor: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct ProofPage {
+    pub data: Vec<Proof>,
+    /// Pass as `cursor` to fetch the next page; null on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Filters of `healthRecords`, as taken by `GET /api/v1/health-records`
+#[derive(Default, InputObject)]
+pub struct HealthRecordFilter {
+    /// e.g. `Vaccination`
+    pub record_type: Option<String>,
+    pub authority_id: Option<Uuid>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub include_revoked: Option<bool>,
+    /// Free text matched against the record details
+    pub search: Option<String>,
+}
@@ -0,0 +1,95 @@
+//! Spanish catalog, the reference translation for new locales
+
+pub const MESSAGES: &[(&str, &str)] = &[
+    // Generic responses
+    ("Internal server error", "Error interno del servidor"),
+    ("Rate limit exceeded", "Se superó el límite de solicitudes"),
+    ("Request timed out", "La solicitud excedió el tiempo de espera"),
+    ("Cryptographic operation failed", "Falló la operación criptográfica"),
+    ("Invalid proof: {}", "Prueba no válida: {}"),
+    ("Request body of {} bytes exceeds the {} byte limit", "El cuerpo de la solicitud de {} bytes supera el límite de {} bytes"),
+    ("Request body exceeds the configured limit", "El cuerpo de la solicitud supera el límite configurado"),
+    ("Invalid multipart body: {}", "Cuerpo multipart no válido: {}"),
+    // Authentication and authorization
+    ("Missing authorization header", "Falta el encabezado de autorización"),
+    ("Invalid token", "Token no válido"),
+    ("Invalid credentials", "Credenciales no válidas"),
+    ("User not found", "Usuario no encontrado"),
+    ("User with this email already exists", "Ya existe un usuario con este correo electrónico"),
+    ("Admin access required", "Se requiere acceso de administrador"),
+    ("Provider or admin access required", "Se requiere acceso de proveedor o administrador"),
+    ("Superadmin access required", "Se requiere acceso de superadministrador"),
+    ("Impersonation tokens are read-only", "Los tokens de suplantación son de solo lectura"),
+    ("A registered verifier token is required", "Se requiere un token de verificador registrado"),
+    ("Unregistered verifier token", "Token de verificador no registrado"),
+    // Health records
+    ("Health record not found", "Registro de salud no encontrado"),
+    ("Health record not found or access denied", "Registro de salud no encontrado o acceso denegado"),
+    ("An identical health record already exists", "Ya existe un registro de salud idéntico"),
+    ("Health authority not found", "Autoridad sanitaria no encontrada"),
+    ("Health authority not found or inactive", "Autoridad sanitaria no encontrada o inactiva"),
+    ("Invalid CSV: {}", "CSV no válido: {}"),
+    ("CSV header must be {}", "La cabecera del CSV debe ser {}"),
+    ("At most {} authorities can be imported at once", "Se pueden importar como máximo {} autoridades a la vez"),
+    ("Provider is not associated with this authority", "El proveedor no está asociado a esta autoridad"),
+    ("Vaccination series not found", "Serie de vacunación no encontrada"),
+    ("Share not found or access denied", "Acceso compartido no encontrado o acceso denegado"),
+    ("Share expiry must be in the future", "La caducidad del acceso compartido debe estar en el futuro"),
+    ("Records cannot be shared with their owner", "Los registros no se pueden compartir con su propietario"),
+    ("Records cannot be transferred to their owner", "Los registros no se pueden transferir a su propietario"),
+    ("Revoked records cannot be transferred", "Los registros revocados no se pueden transferir"),
+    ("Transfer not found, already accepted or expired", "Transferencia no encontrada, ya aceptada o caducada"),
+    ("Record template not found", "Plantilla de registro no encontrada"),
+    ("Cursors can only be used with the default sort", "Los cursores solo se pueden usar con el orden predeterminado"),
+    ("Attachment exceeds the {} byte limit", "El archivo adjunto supera el límite de {} bytes"),
+    ("Attachment is empty", "El archivo adjunto está vacío"),
+    ("Attachment not found or access denied", "Archivo adjunto no encontrado o acceso denegado"),
+    ("Attachments are not enabled on this server", "Los archivos adjuntos no están habilitados en este servidor"),
+    ("Field encryption is not enabled", "El cifrado de campos no está habilitado"),
+    ("A re-encryption job is already running", "Ya hay un trabajo de recifrado en ejecución"),
+    ("An access purpose is required", "Se requiere un motivo de acceso"),
+    ("Access purpose must be at most {} characters", "El motivo de acceso debe tener como máximo {} caracteres"),
+    ("Context version not found", "Versión de contexto no encontrada"),
+    // Proofs
+    ("Proof not found", "Prueba no encontrada"),
+    ("Proof not found or access denied", "Prueba no encontrada o acceso denegado"),
+    ("Proof job not found", "Trabajo de prueba no encontrado"),
+    ("Proof generation was interrupted; start a new job", "La generación de la prueba se interrumpió; inicie un nuevo trabajo"),
+    ("Verification key is required", "La clave de verificación es obligatoria"),
+    ("Revoked proofs cannot be delegated", "Las pruebas revocadas no se pueden delegar"),
+    ("Delegation expiry must be in the future", "La caducidad de la delegación debe estar en el futuro"),
+    ("Delegation cannot outlive the proof", "La delegación no puede durar más que la prueba"),
+    ("Delegation not found or access denied", "Delegación no encontrada o acceso denegado"),
+    ("Delegation token is invalid, expired or revoked", "El token de delegación no es válido, ha caducado o fue revocado"),
+    ("max_usage must not exceed {}", "max_usage no debe superar {}"),
+    ("UTC offset must be between -840 and 840 minutes", "El desfase respecto a UTC debe estar entre -840 y 840 minutos"),
+    ("`from` must not be after `to`", "`from` no debe ser posterior a `to`"),
+    ("Webhook not found", "Webhook no encontrado"),
+    ("Verifier not found", "Verificador no encontrado"),
+    ("Passkey not found", "Llave de acceso no encontrada"),
+    // Validation
+    ("Invalid value ({})", "Valor no válido ({})"),
+    ("Password must be at least 8 characters", "La contraseña debe tener al menos 8 caracteres"),
+    ("New password must be at least 8 characters", "La nueva contraseña debe tener al menos 8 caracteres"),
+    ("Current password is required", "La contraseña actual es obligatoria"),
+    ("Full name must be at least 2 characters", "El nombre completo debe tener al menos 2 caracteres"),
+    ("Patient identifier is required", "El identificador del paciente es obligatorio"),
+    ("Private key is required", "La clave privada es obligatoria"),
+    ("Public key is required", "La clave pública es obligatoria"),
+    ("Nonce must be at most 128 characters", "El nonce debe tener como máximo 128 caracteres"),
+    // Record notification emails
+    ("vaccination record", "registro de vacunación"),
+    ("test result", "resultado de prueba"),
+    ("medical clearance", "autorización médica"),
+    ("immunity proof", "comprobante de inmunidad"),
+    ("Your {} has been issued", "Se ha emitido su {}"),
+    ("Your {} has been revoked", "Se ha revocado su {}"),
+    (
+        "Hello {},\n\n{} has signed your {}. It is now available in your account, and you can generate proofs from it.\n",
+        "Hola, {}:\n\n{} ha firmado su {}. Ya está disponible en su cuenta y puede generar pruebas con él.\n",
+    ),
+    (
+        "Hello {},\n\n{} has revoked your {}. Proofs generated from it no longer verify. Contact {} if you believe this is a mistake.\n",
+        "Hola, {}:\n\n{} ha revocado su {}. Las pruebas generadas con él ya no se verifican. Póngase en contacto con {} si cree que se trata de un error.\n",
+    ),
+];
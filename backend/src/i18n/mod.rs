@@ -0,0 +1,252 @@
+//! Localized error and notification messages.
+//!
+//! English is the canonical language: code, logs and `AppError` hold English
+//! messages, and each locale's catalog maps English templates to translations.
+//! `{}` in a template stands for a value that is copied into the translation in
+//! the same order, e.g. `"Attachment exceeds the {} byte limit"`. Messages
+//! without a catalog entry stay in English.
+
+mod es;
+
+use std::fmt;
+
+use axum::{
+    extract::Request,
+    http::header::{ACCEPT_LANGUAGE, CONTENT_LANGUAGE},
+    http::{HeaderMap, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::errors::ErrorDetails;
+
+tokio::task_local! {
+    /// Locale negotiated for the request being handled
+    static REQUEST_LOCALE: Locale;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+impl Locale {
+    pub const ALL: &'static [Locale] = &[Locale::En, Locale::Es];
+
+    /// The locale of a language tag such as `es` or `es-MX`, by its primary language
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let language = tag.trim().split(['-', '_']).next().unwrap_or_default();
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|locale| locale.code().eq_ignore_ascii_case(language))
+    }
+
+    /// Best supported locale of an `Accept-Language` header, English when none match
+    pub fn negotiate(accept_language: &str) -> Locale {
+        let mut best: Option<(Locale, f32)> = None;
+        for range in accept_language.split(',') {
+            let mut params = range.split(';');
+            let tag = params.next().unwrap_or_default();
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if let Some(locale) = Locale::from_tag(tag) {
+                if quality > 0.0 && best.map_or(true, |(_, best_quality)| quality > best_quality) {
+                    best = Some((locale, quality));
+                }
+            }
+        }
+        best.map(|(locale, _)| locale).unwrap_or_default()
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+        }
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => &[],
+            Locale::Es => es::MESSAGES,
+        }
+    }
+
+    /// Fill a catalog template with `args`, e.g. `text("Hello {},", &[name])`
+    pub fn text(&self, template: &str, args: &[&str]) -> String {
+        let translated = self
+            .catalog()
+            .iter()
+            .find(|(english, _)| *english == template)
+            .map_or(template, |(_, translated)| translated);
+        fill(translated, args)
+    }
+
+    /// Translate an already formatted English message by matching it against
+    /// the catalog's templates
+    pub fn translate(&self, message: &str) -> String {
+        self.catalog()
+            .iter()
+            .find_map(|(english, translated)| match_template(english, message).map(|args| fill(translated, &args)))
+            .unwrap_or_else(|| message.to_string())
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// The locale of the request being handled; English outside a request
+pub fn current_locale() -> Locale {
+    REQUEST_LOCALE.try_with(|locale| *locale).unwrap_or_default()
+}
+
+/// Negotiate the client's locale from `Accept-Language` for `AppError`
+/// responses rendered while handling the request, and label localized error
+/// responses with `Content-Language`
+pub async fn negotiate_locale(request: Request, next: Next) -> Response {
+    let locale = request_locale(request.headers());
+    let mut response = REQUEST_LOCALE.scope(locale, next.run(request)).await;
+
+    if locale != Locale::En && response.extensions().get::<ErrorDetails>().is_some() {
+        response
+            .headers_mut()
+            .insert(CONTENT_LANGUAGE, HeaderValue::from_static(locale.code()));
+    }
+    response
+}
+
+fn request_locale(headers: &HeaderMap) -> Locale {
+    headers
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(Locale::negotiate)
+        .unwrap_or_default()
+}
+
+/// The values `{}` stands for in `message`, if `message` was made from `template`
+fn match_template<'a>(template: &str, message: &'a str) -> Option<Vec<&'a str>> {
+    let mut literals = template.split("{}");
+    let mut rest = message.strip_prefix(literals.next().unwrap_or_default())?;
+    let literals: Vec<&str> = literals.collect();
+
+    let mut args = Vec::with_capacity(literals.len());
+    for (i, literal) in literals.iter().enumerate() {
+        if i + 1 == literals.len() {
+            args.push(rest.strip_suffix(literal)?);
+            rest = "";
+        } else {
+            let end = rest.find(literal).filter(|_| !literal.is_empty())?;
+            args.push(&rest[..end]);
+            rest = &rest[end + literal.len()..];
+        }
+    }
+
+    rest.is_empty().then_some(args)
+}
+
+fn fill(template: &str, args: &[&str]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut literals = template.split("{}").peekable();
+    while let Some(literal) = literals.next() {
+        filled.push_str(literal);
+        if literals.peek().is_some() {
+            filled.push_str(args.next().copied().unwrap_or_default());
+        }
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiates_the_preferred_supported_locale() {
+        assert_eq!(Locale::negotiate("es-MX,es;q=0.9,en;q=0.8"), Locale::Es);
+        assert_eq!(Locale::negotiate("fr-CH, fr;q=0.9, es;q=0.5, en;q=0.7"), Locale::En);
+        assert_eq!(Locale::negotiate("de, fr"), Locale::En);
+        assert_eq!(Locale::negotiate("es;q=0"), Locale::En);
+        assert_eq!(Locale::negotiate(""), Locale::En);
+    }
+
+    #[test]
+    fn test_translates_formatted_messages() {
+        assert_eq!(Locale::Es.translate("User not found"), "Usuario no encontrado");
+        assert_eq!(
+            Locale::Es.translate("Attachment exceeds the 1048576 byte limit"),
+            "El archivo adjunto supera el límite de 1048576 bytes"
+        );
+        assert_eq!(
+            Locale::Es.translate("Request body of 20 bytes exceeds the 10 byte limit"),
+            "El cuerpo de la solicitud de 20 bytes supera el límite de 10 bytes"
+        );
+        // Unknown messages and English stay as they are
+        assert_eq!(Locale::Es.translate("Something new went wrong"), "Something new went wrong");
+        assert_eq!(Locale::En.translate("User not found"), "User not found");
+    }
+
+    #[test]
+    fn test_templates_match_whole_messages_only() {
+        assert_eq!(match_template("User not found", "User not found"), Some(vec![]));
+        assert_eq!(match_template("User not found", "User not found!"), None);
+        assert_eq!(match_template("Invalid proof: {}", "Invalid proof: bad hash"), Some(vec!["bad hash"]));
+        assert_eq!(match_template("a {} b {} c", "a 1 b 2 c"), Some(vec!["1", "2"]));
+        assert_eq!(match_template("a {} b {} c", "a 1 c"), None);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_localized_for_the_request() {
+        use axum::{body::{to_bytes, Body}, middleware::from_fn, routing::get, Router};
+        use tower::ServiceExt;
+
+        use crate::errors::AppError;
+
+        let app = Router::new()
+            .route("/missing", get(|| async { Err::<(), _>(AppError::NotFound("User not found".to_string())) }))
+            .layer(from_fn(negotiate_locale));
+        let request = |language: &str| {
+            Request::get("/missing")
+                .header(ACCEPT_LANGUAGE, language)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(request("es-ES,es;q=0.9")).await.unwrap();
+        assert_eq!(response.headers()[CONTENT_LANGUAGE], "es");
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["error"]["message"], "Usuario no encontrado");
+        assert_eq!(body["error"]["code"], "NOT_FOUND");
+
+        let response = app.oneshot(request("pt-BR")).await.unwrap();
+        assert!(response.headers().get(CONTENT_LANGUAGE).is_none());
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["error"]["message"], "User not found");
+    }
+
+    #[test]
+    fn test_every_translation_keeps_its_placeholders() {
+        for locale in Locale::ALL {
+            for (english, translated) in locale.catalog() {
+                assert_eq!(
+                    english.matches("{}").count(),
+                    translated.matches("{}").count(),
+                    "{}: {}",
+                    locale,
+                    english
+                );
+            }
+        }
+    }
+}
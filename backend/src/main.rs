@@ -1,25 +1,47 @@
 mod config;
 mod errors;
+mod graphql;
+mod i18n;
+mod metrics;
 mod middleware;
 mod models;
 mod routes;
+mod seed;
 mod services;
 
 use anyhow::Result;
-use axum::Router;
-use sqlx::postgres::PgPoolOptions;
-use std::sync::Arc;
-use tokio::net::TcpListener;
+use axum::{
+    extract::DefaultBodyLimit,
+    middleware::{from_fn, from_fn_with_state},
+    Router,
+};
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use std::{future::IntoFuture, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{net::TcpListener, signal, sync::oneshot};
 use tower::ServiceBuilder;
 use tower_http::{
     cors::CorsLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
     trace::TraceLayer,
 };
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::{
-    config::Config,
-    services::{AuthService, ZkProofService, CryptoService},
+    config::{Config, ProofBackendKind},
+    services::{
+        blockchain::ContractAddresses,
+        captcha::CaptchaVerifier,
+        crypto,
+        geoip::GeoIpService,
+        field_encryption::{self, FieldCipher, ReencryptJob},
+        email::{EmailService, SmtpSettings},
+        ipfs::IpfsService,
+        object_store::{ObjectStore, ObjectStoreSettings},
+        wallet::{ApplePassSigner, GoogleWalletSigner},
+        store::{self, Store},
+        timestamp::TimestampClient,
+        AuthService, ZkProofService, CryptoService, WebhookService, VerifierService, PasskeyService, BlockchainService, AnomalyPolicy, ProofDefaults, AttachmentService,
+        HealthAuthorityService, AccessLogService, DashboardService,
+    },
 };
 
 #[derive(Clone)]
@@ -27,66 +49,402 @@ pub struct AppState {
     pub auth_service: Arc<AuthService>,
     pub zk_proof_service: Arc<ZkProofService>,
     pub crypto_service: Arc<CryptoService>,
+    pub webhook_service: Arc<WebhookService>,
+    pub verifier_service: Arc<VerifierService>,
+    pub passkey_service: Arc<PasskeyService>,
+    pub health_authority_service: Arc<HealthAuthorityService>,
+    /// `None` when no SMTP server is configured
+    pub email_service: Option<Arc<EmailService>>,
+    /// `None` when no object storage is configured
+    pub attachment_service: Option<Arc<AttachmentService>>,
+    pub access_log_service: Arc<AccessLogService>,
+    pub dashboard_service: Arc<DashboardService>,
+    pub reencrypt_job: ReencryptJob,
+    pub store: Arc<dyn Store>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Load configuration (TOML file from --config / CONFIG_FILE, overridden by env vars)
+    let config = Config::load()?;
+    config.validate()?;
+
     // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "zk_health_pass_backend=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    middleware::logging::init_tracing(config.log_format);
 
-    // Load configuration
-    let config = Config::from_env()?;
+    // Install the Prometheus recorder before any metrics are emitted
+    let metrics_handle = metrics::install_recorder()?;
 
-    // Setup database connection
-    let db_pool = PgPoolOptions::new()
-        .max_connections(20)
-        .connect(&config.database_url)
-        .await?;
+    // Setup database connection, waiting for Postgres if it is still starting
+    let db_pool = connect_with_retry(&config).await?;
 
     // Run database migrations
     sqlx::migrate!("./migrations").run(&db_pool).await?;
 
+    // Shared state store (Redis when configured)
+    let store = store::connect(config.redis_url.as_deref()).await?;
+
+    // Patient identifiers and details are encrypted at rest when a key is configured
+    let field_cipher = config
+        .field_encryption_key
+        .as_deref()
+        .map(|key| FieldCipher::new(key, &config.field_encryption_previous_keys))
+        .transpose()?;
+
+    // `reencrypt-records` encrypts plaintext records and re-wraps data keys
+    // after a key rotation, then exits without starting the server
+    if std::env::args().skip(1).any(|arg| arg == "reencrypt-records") {
+        let cipher = field_cipher
+            .ok_or_else(|| anyhow::anyhow!("FIELD_ENCRYPTION_KEY must be set to re-encrypt records"))?;
+        let summary = field_encryption::reencrypt_records(&db_pool, &cipher, field_encryption::REENCRYPT_BATCH_SIZE).await?;
+        tracing::info!(
+            "Re-encryption finished: {} encrypted, {} re-wrapped, {} failed",
+            summary.encrypted,
+            summary.rewrapped,
+            summary.failed
+        );
+        db_pool.close().await;
+        return Ok(());
+    }
+
     // Initialize services
-    let crypto_service = Arc::new(CryptoService::new());
-    let auth_service = Arc::new(AuthService::new(
-        db_pool.clone(),
-        config.jwt_secret.clone(),
-        config.jwt_expiration_hours,
-    ));
-    let zk_proof_service = Arc::new(ZkProofService::new(
+    let mut crypto_service = CryptoService::new()
+        .with_message_format_version(config.message_format_version)?
+        .with_authority_cache_ttl(Duration::from_secs(config.authority_cache_ttl_secs))
+        .with_record_read_parallelism(config.record_read_parallelism);
+    if let Some(cipher) = field_cipher {
+        crypto_service = crypto_service.with_field_cipher(cipher);
+    }
+    let crypto_service = Arc::new(crypto_service);
+    let auth_service = Arc::new(
+        AuthService::new(db_pool.clone(), config.jwt_secret.clone(), config.jwt_expiration_hours)
+            .with_invite_expiration_hours(config.invite_expiration_hours),
+    );
+    let webhook_service = Arc::new(WebhookService::new(db_pool.clone()));
+    let mut zk_proof_service = ZkProofService::new(
         db_pool.clone(),
         crypto_service.clone(),
         config.noir_circuit_path.clone(),
-    ));
+        (*webhook_service).clone(),
+    )
+    .with_generation_timeout(Duration::from_secs(config.proof_generation_timeout_secs));
+    match config.proof_backend {
+        ProofBackendKind::Nargo => {}
+        #[cfg(feature = "stub-proofs")]
+        ProofBackendKind::Stub => {
+            tracing::warn!("Using the stub proof backend: proofs are placeholders and prove nothing");
+            zk_proof_service = zk_proof_service
+                .with_proof_backend(Arc::new(services::proof_backend::StubProofBackend));
+        }
+        // Rejected by Config::validate
+        #[cfg(not(feature = "stub-proofs"))]
+        ProofBackendKind::Stub => unreachable!("stub proof backend requires the stub-proofs feature"),
+    }
+    // Proofs get RFC 3161 timestamps only when a timestamp authority is configured
+    if let Some(tsa_url) = &config.tsa_url {
+        zk_proof_service = zk_proof_service.with_timestamp_client(TimestampClient::new(tsa_url.clone()));
+    }
+    if config.blockchain_enabled {
+        zk_proof_service = zk_proof_service.with_blockchain(Arc::new(BlockchainService::new(
+            config.blockchain_rpc_url.clone(),
+            config.blockchain_private_key.clone(),
+            ContractAddresses {
+                zk_health_pass_registry: config.zk_health_pass_registry_address.clone(),
+                zk_proof_verifier: config.zk_proof_verifier_address.clone(),
+                health_authority_registry: config.health_authority_registry_address.clone(),
+            },
+        )));
+    }
+    // Anchored proofs are pinned on IPFS only when a node is configured
+    if let Some(ipfs_api_url) = &config.ipfs_api_url {
+        zk_proof_service = zk_proof_service.with_ipfs(IpfsService::new(
+            ipfs_api_url.clone(),
+            config.ipfs_gateway_url.clone(),
+        ));
+    }
+    // The proof revocation list is published only with a key to sign it
+    if let Some(crl_signing_key) = &config.crl_signing_key {
+        let key = if crypto::is_pem(crl_signing_key) {
+            crypto_service.private_key_from_pem(crl_signing_key)?
+        } else {
+            crypto_service.parse_private_key(crl_signing_key)?
+        };
+        zk_proof_service = zk_proof_service.with_crl_signing_key(key);
+    }
+    if config.geoip_enabled {
+        zk_proof_service = zk_proof_service.with_geoip(GeoIpService::open(&config.geoip_db_path)?);
+    }
+    // Proofs are offered as Apple Wallet passes only with a Pass Type ID certificate
+    if let (Some(certificate_path), Some(wwdr_certificate_path), Some(pass_type_identifier), Some(team_identifier)) = (
+        &config.apple_pass_certificate_path,
+        &config.apple_wwdr_certificate_path,
+        &config.apple_pass_type_identifier,
+        &config.apple_team_identifier,
+    ) {
+        let signer = ApplePassSigner::open(
+            certificate_path,
+            &config.apple_pass_certificate_password,
+            wwdr_certificate_path,
+            pass_type_identifier.clone(),
+            team_identifier.clone(),
+            config.apple_pass_organization_name.clone(),
+        )?;
+        zk_proof_service = zk_proof_service.with_apple_wallet(signer, config.public_base_url.clone());
+    }
+    if config.google_wallet_enabled {
+        if let (Some(service_account_path), Some(issuer_id)) =
+            (&config.google_wallet_service_account_path, &config.google_wallet_issuer_id)
+        {
+            let signer = GoogleWalletSigner::open(
+                service_account_path,
+                issuer_id.clone(),
+                config.google_wallet_class_suffix.clone(),
+            )?;
+            zk_proof_service = zk_proof_service.with_google_wallet(signer, config.public_base_url.clone());
+        }
+    }
+    zk_proof_service = zk_proof_service.with_public_base_url(config.public_base_url.clone());
+    zk_proof_service = zk_proof_service.with_proof_defaults(ProofDefaults {
+        expiration_hours: config.default_proof_expiration_hours,
+        max_usage: config.max_proof_usage,
+    });
+    zk_proof_service = zk_proof_service.with_anomaly_policy(AnomalyPolicy {
+        window_secs: config.anomaly_window_secs,
+        max_distinct_locations: config.anomaly_max_distinct_locations,
+        auto_suspend: config.anomaly_auto_suspend,
+    });
+    if config.verification_dedup_window_secs > 0 {
+        zk_proof_service = zk_proof_service
+            .with_verification_dedup(store.clone(), Duration::from_secs(config.verification_dedup_window_secs));
+    }
+    let zk_proof_service = Arc::new(zk_proof_service);
+
+    // `seed` fills a development database with demo data, then exits
+    if std::env::args().skip(1).any(|arg| arg == "seed") {
+        seed::run(&auth_service, &crypto_service, &zk_proof_service).await?;
+        db_pool.close().await;
+        return Ok(());
+    }
+    let mut verifier_service = VerifierService::new(db_pool.clone(), config.public_verification_allow_anonymous);
+    // Anonymous public verifications must solve a CAPTCHA only when a provider is configured
+    if let (Some(provider), Some(secret)) = (config.captcha_provider, &config.captcha_secret) {
+        verifier_service = verifier_service.with_captcha(CaptchaVerifier::new(provider, secret.clone()));
+    }
+    let verifier_service = Arc::new(verifier_service);
+
+    let passkey_service = Arc::new(PasskeyService::new(
+        auth_service.clone(),
+        store.clone(),
+        &config.webauthn_rp_id,
+        &config.webauthn_rp_origin,
+        &config.webauthn_rp_name,
+    )?);
+
+    // Record owners are emailed only when an SMTP server is configured
+    let email_service = config
+        .smtp_host
+        .as_deref()
+        .map(|host| {
+            EmailService::smtp(SmtpSettings {
+                host,
+                port: config.smtp_port,
+                username: config.smtp_username.as_deref(),
+                password: config.smtp_password.as_deref(),
+                from: &config.smtp_from,
+            })
+        })
+        .transpose()?
+        .map(Arc::new);
+
+    // Record attachments are accepted only when object storage is configured
+    let attachment_service = config
+        .s3_endpoint
+        .as_deref()
+        .map(|endpoint| {
+            ObjectStore::new(ObjectStoreSettings {
+                endpoint,
+                bucket: config.s3_bucket.as_deref().unwrap_or_default(),
+                region: &config.s3_region,
+                access_key_id: config.s3_access_key_id.as_deref().unwrap_or_default(),
+                secret_access_key: config.s3_secret_access_key.as_deref().unwrap_or_default(),
+            })
+        })
+        .transpose()?
+        .map(|store| Arc::new(AttachmentService::new(db_pool.clone(), store, config.attachment_max_bytes)));
+
+    let health_authority_service = Arc::new(HealthAuthorityService::new(
+        db_pool.clone(),
+        crypto_service.clone(),
+        &config.public_base_url,
+    )?);
 
     // Create application state
     let app_state = AppState {
         auth_service,
         zk_proof_service,
         crypto_service,
+        webhook_service,
+        verifier_service,
+        passkey_service,
+        health_authority_service,
+        email_service,
+        attachment_service,
+        access_log_service: Arc::new(AccessLogService::new(db_pool.clone(), config.access_log_enabled)),
+        dashboard_service: Arc::new(DashboardService::new(db_pool.clone(), store.clone())),
+        reencrypt_job: ReencryptJob::default(),
+        store: store.clone(),
     };
 
     // Build the application with middleware
-    let app = Router::new()
-        .merge(routes::create_routes())
+    let mut app = Router::new()
+        .merge(routes::create_routes(&config, &app_state))
+        .route_layer(from_fn(metrics::track_http_metrics))
         .layer(
             ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
+                .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+                .layer(TraceLayer::new_for_http().make_span_with(middleware::logging::make_request_span))
+                .layer(PropagateRequestIdLayer::x_request_id())
                 .layer(CorsLayer::permissive()) // Configure CORS as needed
+                .layer(from_fn(middleware::problem_json::render_problem_json))
+                .layer(from_fn(i18n::negotiate_locale))
+                .layer(from_fn_with_state(
+                    middleware::rate_limit::RateLimiter {
+                        store,
+                        requests_per_minute: config.rate_limit_requests_per_minute,
+                    },
+                    middleware::rate_limit::enforce_rate_limit,
+                ))
+                .layer(from_fn_with_state(
+                    middleware::limits::BodyLimits {
+                        max_bytes: config.max_request_body_bytes,
+                        attachment_max_bytes: config.attachment_max_bytes,
+                    },
+                    middleware::limits::reject_oversized_bodies,
+                ))
+                .layer(DefaultBodyLimit::max(config.max_request_body_bytes))
         )
         .with_state(app_state);
 
+    // Compress responses for clients that send Accept-Encoding (gzip/br)
+    if config.compression_enabled {
+        app = app.layer(middleware::compression::compression_layer());
+    }
+
+    // Serve /metrics on a dedicated admin port when configured, otherwise alongside the API
+    let metrics_router = metrics::router(metrics_handle, db_pool.clone());
+    if let Some(metrics_address) = &config.metrics_address {
+        let metrics_listener = TcpListener::bind(metrics_address).await?;
+        tracing::info!("📈 Metrics endpoint listening on {}", metrics_address);
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(metrics_listener, metrics_router).await {
+                tracing::error!("Metrics server error: {}", e);
+            }
+        });
+    } else {
+        app = app.merge(metrics_router);
+    }
+
     // Start the server
     let listener = TcpListener::bind(&config.server_address).await?;
     tracing::info!("🚀 ZK Health Pass API server starting on {}", config.server_address);
-    
-    axum::serve(listener, app).await?;
+
+    // Serve until a shutdown signal arrives, then drain in-flight requests
+    let (signal_tx, signal_rx) = oneshot::channel();
+    let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            let _ = signal_tx.send(());
+        })
+        .into_future();
+
+    let drain_timeout = Duration::from_secs(config.shutdown_drain_timeout_secs);
+    let drain_deadline = async move {
+        if signal_rx.await.is_ok() {
+            tokio::time::sleep(drain_timeout).await;
+        } else {
+            std::future::pending::<()>().await;
+        }
+    };
+
+    tokio::select! {
+        result = server => {
+            result?;
+            tracing::info!("All in-flight requests drained");
+        }
+        _ = drain_deadline => {
+            tracing::warn!(
+                "Drain timeout of {}s elapsed, dropping remaining requests",
+                drain_timeout.as_secs()
+            );
+        }
+    }
+
+    tracing::info!("Closing database pool");
+    db_pool.close().await;
+    tracing::info!("Shutdown complete");
 
     Ok(())
 }
+
+/// Connect to Postgres, retrying with exponential backoff until the configured attempts are exhausted
+async fn connect_with_retry(config: &Config) -> Result<PgPool> {
+    let max_attempts = config.db_connect_max_attempts.max(1);
+    let mut delay = Duration::from_secs(config.db_connect_retry_interval_secs);
+    let max_delay = Duration::from_secs(60);
+
+    for attempt in 1..=max_attempts {
+        tracing::info!("Connecting to database (attempt {}/{})", attempt, max_attempts);
+
+        match PgPoolOptions::new()
+            .max_connections(20)
+            .connect(&config.database_url)
+            .await
+        {
+            Ok(pool) => {
+                tracing::info!("Database connection established");
+                return Ok(pool);
+            }
+            Err(e) if attempt < max_attempts => {
+                tracing::warn!(
+                    "Database connection attempt {} failed: {}. Retrying in {}s",
+                    attempt,
+                    e,
+                    delay.as_secs()
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(max_delay);
+            }
+            Err(e) => {
+                tracing::error!("Giving up on database after {} attempts: {}", max_attempts, e);
+                return Err(e.into());
+            }
+        }
+    }
+
+    unreachable!("retry loop always returns")
+}
+
+/// Resolves when the process receives SIGINT (Ctrl+C) or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("Received SIGINT, starting graceful shutdown"),
+        _ = terminate => tracing::info!("Received SIGTERM, starting graceful shutdown"),
+    }
+}
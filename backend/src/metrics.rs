@@ -0,0 +1,68 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+use sqlx::PgPool;
+use std::time::Instant;
+
+const SECONDS_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+/// Install the global Prometheus recorder
+pub fn install_recorder() -> Result<PrometheusHandle, BuildError> {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("seconds".to_string()), SECONDS_BUCKETS)?
+        .install_recorder()
+}
+
+/// Router exposing `GET /metrics` in the Prometheus text format
+pub fn router(handle: PrometheusHandle, db_pool: PgPool) -> Router {
+    Router::new().route(
+        "/metrics",
+        get(move || {
+            let handle = handle.clone();
+            let db_pool = db_pool.clone();
+            async move {
+                record_db_pool_metrics(&db_pool);
+                handle.render()
+            }
+        }),
+    )
+}
+
+/// Middleware recording request counts and latency per matched route
+pub async fn track_http_metrics(request: Request, next: Next) -> impl IntoResponse {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    // Use the route template rather than the raw URI to keep label cardinality bounded
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    let labels = [("method", method), ("path", path), ("status", status)];
+
+    metrics::counter!("http_requests_total", &labels).increment(1);
+    metrics::histogram!("http_request_duration_seconds", &labels)
+        .record(start.elapsed().as_secs_f64());
+
+    response
+}
+
+fn record_db_pool_metrics(db_pool: &PgPool) {
+    let size = db_pool.size();
+    let idle = db_pool.num_idle() as u32;
+
+    metrics::gauge!("db_pool_connections").set(size as f64);
+    metrics::gauge!("db_pool_idle_connections").set(idle as f64);
+    metrics::gauge!("db_pool_active_connections").set(size.saturating_sub(idle) as f64);
+}
@@ -1,7 +1,7 @@
 use axum::{
     async_trait,
     extract::{FromRequestParts, State},
-    http::{request::Parts, StatusCode},
+    http::{request::Parts, Method, StatusCode},
     RequestPartsExt,
 };
 use axum_extra::{
@@ -9,6 +9,8 @@ use axum_extra::{
     TypedHeader,
 };
 
+use uuid::Uuid;
+
 use crate::{
     errors::AppError,
     models::User,
@@ -17,6 +19,19 @@ use crate::{
 
 pub struct AuthUser {
     pub user: User,
+    pub acting_admin: Option<Uuid>, // Admin impersonating `user`, from the token's `act` claim
+}
+
+impl AuthUser {
+    /// Organization the user's queries are confined to; `None` for superadmins,
+    /// who see every organization
+    pub fn org_scope(&self) -> Option<Uuid> {
+        if self.user.is_superadmin {
+            None
+        } else {
+            Some(self.user.org_id)
+        }
+    }
 }
 
 #[async_trait]
@@ -52,7 +67,31 @@ where
             .map_err(|_| AppError::InternalServerError("Failed to fetch user".to_string()))?
             .ok_or_else(|| AppError::Unauthorized("User not found".to_string()))?;
 
-        Ok(AuthUser { user })
+        let acting_admin = claims
+            .act
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| AppError::Unauthorized("Invalid acting user ID in token".to_string()))?;
+
+        if let Some(admin_id) = acting_admin {
+            tracing::info!(
+                target: "audit",
+                event = "impersonated_request",
+                admin_id = %admin_id,
+                user_id = %user.id,
+                method = %parts.method,
+                path = %parts.uri.path(),
+                "Request made by an admin impersonating a user"
+            );
+
+            // Impersonation is for seeing what the user sees, not acting for them
+            if !matches!(parts.method, Method::GET | Method::HEAD) {
+                return Err(AppError::Forbidden("Impersonation tokens are read-only".to_string()));
+            }
+        }
+
+        Ok(AuthUser { user, acting_admin })
     }
 }
 
@@ -0,0 +1,108 @@
+use tower_http::compression::{predicate::DefaultPredicate, CompressionLayer};
+
+/// gzip/brotli response compression negotiated from `Accept-Encoding`.
+///
+/// The default predicate skips `image/*` bodies (QR codes are already
+/// compressed PNGs), event streams, gRPC and responses under 32 bytes.
+pub fn compression_layer() -> CompressionLayer<DefaultPredicate> {
+    CompressionLayer::new()
+        .gzip(true)
+        .br(true)
+        .compress_when(DefaultPredicate::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProofResponse, ProofStatus, ProofType};
+    use axum::{
+        body::{to_bytes, Body},
+        extract::Request,
+        http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE},
+        response::Response,
+        routing::get,
+        Json, Router,
+    };
+    use base64::{Engine as _, engine::general_purpose};
+    use rand::RngCore;
+    use tower::ServiceExt;
+
+    fn proofs() -> Vec<ProofResponse> {
+        // Proofs of one circuit share a verification key; the proofs themselves are incompressible
+        let mut verification_key = vec![0u8; 4096];
+        rand::thread_rng().fill_bytes(&mut verification_key);
+        let verification_key = general_purpose::STANDARD.encode(verification_key);
+
+        (0..50)
+            .map(|_| {
+                let mut proof = vec![0u8; 2048];
+                rand::thread_rng().fill_bytes(&mut proof);
+                ProofResponse {
+                    id: uuid::Uuid::new_v4(),
+                    proof_data: general_purpose::STANDARD.encode(proof),
+                    verification_key: verification_key.clone(),
+                    proof_type: ProofType::EcdsaSignatureVerification,
+                    generated_at: chrono::Utc::now(),
+                    expires_at: None,
+                    usage_count: 0,
+                    max_usage: Some(10),
+                    health_record_type: "Vaccination".to_string(),
+                    status: ProofStatus::Active,
+                    timestamp_token: None,
+                    timestamped_at: None,
+                    anchor_tx_hash: None,
+                    proof_cid: None,
+                }
+            })
+            .collect()
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/proofs", get(|| async { Json(proofs()) }))
+            .route(
+                "/qr",
+                get(|| async { ([(CONTENT_TYPE, "image/png")], vec![0u8; 4096]) }),
+            )
+            .layer(compression_layer())
+    }
+
+    async fn get_with(path: &str, encoding: Option<&str>) -> Response {
+        let mut request = Request::get(path);
+        if let Some(encoding) = encoding {
+            request = request.header(ACCEPT_ENCODING, encoding);
+        }
+        app().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    async fn body_len(response: Response) -> usize {
+        to_bytes(response.into_body(), usize::MAX).await.unwrap().len()
+    }
+
+    #[tokio::test]
+    async fn test_proof_list_shrinks_when_compressed() {
+        let plain = get_with("/proofs", None).await;
+        assert!(plain.headers().get(CONTENT_ENCODING).is_none());
+        let plain_len = body_len(plain).await;
+
+        for encoding in ["gzip", "br"] {
+            let compressed = get_with("/proofs", Some(encoding)).await;
+            assert_eq!(compressed.headers()[CONTENT_ENCODING], encoding);
+            let compressed_len = body_len(compressed).await;
+            assert!(
+                compressed_len * 2 < plain_len,
+                "{} body of {} bytes is not under half of {}",
+                encoding,
+                compressed_len,
+                plain_len
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_images_are_not_recompressed() {
+        let response = get_with("/qr", Some("gzip, br")).await;
+        assert!(response.headers().get(CONTENT_ENCODING).is_none());
+        assert_eq!(body_len(response).await, 4096);
+    }
+}
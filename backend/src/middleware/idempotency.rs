@@ -0,0 +1,275 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header::{AUTHORIZATION, CONTENT_TYPE}, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    errors::AppError,
+    services::{store::Store, AuthService},
+};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+#[derive(Clone)]
+pub struct Idempotency {
+    pub store: Arc<dyn Store>,
+    pub auth_service: Arc<AuthService>,
+    pub ttl: Duration,
+    pub max_body_bytes: usize,
+}
+
+/// What is kept under an idempotency key: the request fingerprint and, once
+/// the first request has finished, its response
+#[derive(Debug, Serialize, Deserialize)]
+struct IdempotencyRecord {
+    request_hash: String,
+    response: Option<StoredResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredResponse {
+    status: u16,
+    content_type: Option<String>,
+    body: String, // Base64 encoded
+}
+
+/// Replay the original response for POST requests repeated with the same
+/// `Idempotency-Key` header instead of executing them again.
+///
+/// Keys are scoped per user and endpoint. Reusing a key with a different body
+/// is a conflict, as is repeating it while the first request is still running.
+/// Only successful responses are kept, so a failed request can be retried.
+pub async fn replay_idempotent_requests(
+    State(idempotency): State<Idempotency>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if request.method() != Method::POST {
+        return Ok(next.run(request).await);
+    }
+
+    let Some(idempotency_key) = request
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+    else {
+        return Ok(next.run(request).await);
+    };
+
+    // Unauthenticated requests are left for the handler to reject
+    let Some(user_id) = bearer_subject(&idempotency.auth_service, &request) else {
+        return Ok(next.run(request).await);
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, idempotency.max_body_bytes)
+        .await
+        .map_err(|_| AppError::PayloadTooLarge("Request body exceeds the configured limit".to_string()))?;
+
+    let store_key = format!("idempotency:{}:{}:{}", user_id, parts.uri.path(), idempotency_key);
+    let request_hash = hex::encode(Sha256::digest(&body));
+    let pending = IdempotencyRecord { request_hash: request_hash.clone(), response: None };
+
+    if !idempotency.store.set_if_absent(&store_key, &encode(&pending)?, idempotency.ttl).await? {
+        return replay(&idempotency, &store_key, &request_hash).await;
+    }
+
+    // Release the key if the request is dropped (e.g. timed out) before finishing
+    let mut guard = InFlight { store: idempotency.store.clone(), key: Some(store_key.clone()) };
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    if !response.status().is_success() {
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to buffer response: {}", e)))?;
+
+    let completed = IdempotencyRecord {
+        request_hash,
+        response: Some(StoredResponse {
+            status: parts.status.as_u16(),
+            content_type: parts
+                .headers
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+            body: general_purpose::STANDARD.encode(&body),
+        }),
+    };
+    idempotency.store.set(&store_key, &encode(&completed)?, idempotency.ttl).await?;
+    guard.key = None;
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+async fn replay(idempotency: &Idempotency, store_key: &str, request_hash: &str) -> Result<Response, AppError> {
+    let record: IdempotencyRecord = match idempotency.store.get(store_key).await? {
+        Some(raw) => serde_json::from_str(&raw)
+            .map_err(|e| AppError::InternalServerError(format!("Corrupt idempotency record: {}", e)))?,
+        // The first request failed and released the key in the meantime
+        None => return Err(AppError::Conflict("Request with this Idempotency-Key failed; retry it".to_string())),
+    };
+
+    if record.request_hash != request_hash {
+        return Err(AppError::Conflict(
+            "Idempotency-Key was already used with a different request body".to_string(),
+        ));
+    }
+
+    let Some(stored) = record.response else {
+        return Err(AppError::Conflict(
+            "A request with this Idempotency-Key is still in progress".to_string(),
+        ));
+    };
+
+    let body = general_purpose::STANDARD
+        .decode(&stored.body)
+        .map_err(|e| AppError::InternalServerError(format!("Corrupt idempotency record: {}", e)))?;
+
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+    if let Some(content_type) = stored.content_type.and_then(|value| HeaderValue::from_str(&value).ok()) {
+        response.headers_mut().insert(CONTENT_TYPE, content_type);
+    }
+    response
+        .headers_mut()
+        .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+
+    Ok(response)
+}
+
+fn bearer_subject(auth_service: &AuthService, request: &Request) -> Option<String> {
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")?;
+
+    auth_service.verify_token(token).ok().map(|claims| claims.sub)
+}
+
+fn encode(record: &IdempotencyRecord) -> Result<String, AppError> {
+    serde_json::to_string(record)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to encode idempotency record: {}", e)))
+}
+
+struct InFlight {
+    store: Arc<dyn Store>,
+    key: Option<String>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let store = self.store.clone();
+            tokio::spawn(async move {
+                if let Err(e) = store.delete(&key).await {
+                    tracing::warn!("Failed to release idempotency key {}: {}", key, e);
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::UserRole;
+    use crate::services::{store::MemoryStore, Claims};
+    use axum::{middleware::from_fn_with_state, routing::post, Router};
+    use sqlx::postgres::PgPoolOptions;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    fn app(calls: Arc<AtomicUsize>) -> (Router, String) {
+        // The pool is never used: token checks don't touch the database
+        let db = PgPoolOptions::new().connect_lazy("postgres://localhost/unused").unwrap();
+        let auth_service = Arc::new(AuthService::new(db, "test-secret".to_string(), 1));
+        let claims = Claims {
+            sub: uuid::Uuid::new_v4().to_string(),
+            email: "holder@example.com".to_string(),
+            role: UserRole::Patient,
+            exp: chrono::Utc::now().timestamp() + 3600,
+            iat: chrono::Utc::now().timestamp(),
+            act: None,
+        };
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(b"test-secret"),
+        )
+        .unwrap();
+
+        let idempotency = Idempotency {
+            store: Arc::new(MemoryStore::new()),
+            auth_service,
+            ttl: Duration::from_secs(60),
+            max_body_bytes: 1024,
+        };
+
+        let router = Router::new()
+            .route(
+                "/records",
+                post(move |body: String| async move {
+                    let n = calls.fetch_add(1, Ordering::SeqCst);
+                    (StatusCode::CREATED, format!("{}:{}", n, body))
+                }),
+            )
+            .layer(from_fn_with_state(idempotency, replay_idempotent_requests));
+
+        (router, token)
+    }
+
+    fn request(token: &str, key: &str, body: &str) -> Request {
+        Request::post("/records")
+            .header(AUTHORIZATION, format!("Bearer {}", token))
+            .header(IDEMPOTENCY_KEY_HEADER, key)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn body_of(response: Response) -> String {
+        String::from_utf8(to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_repeated_key_replays_original_response() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (app, token) = app(calls.clone());
+
+        let first = app.clone().oneshot(request(&token, "k1", "a")).await.unwrap();
+        assert_eq!(first.status(), StatusCode::CREATED);
+        assert_eq!(body_of(first).await, "0:a");
+
+        let second = app.clone().oneshot(request(&token, "k1", "a")).await.unwrap();
+        assert_eq!(second.status(), StatusCode::CREATED);
+        assert!(second.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER));
+        assert_eq!(body_of(second).await, "0:a");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let other_key = app.oneshot(request(&token, "k2", "a")).await.unwrap();
+        assert_eq!(body_of(other_key).await, "1:a");
+    }
+
+    #[tokio::test]
+    async fn test_reused_key_with_different_body_conflicts() {
+        let (app, token) = app(Arc::new(AtomicUsize::new(0)));
+
+        app.clone().oneshot(request(&token, "k1", "a")).await.unwrap();
+        let response = app.oneshot(request(&token, "k1", "b")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+}
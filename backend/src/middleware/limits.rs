@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Request, State},
+    http::{header::CONTENT_LENGTH, Method},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::errors::AppError;
+
+/// Room for the multipart framing around an uploaded file
+pub const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
+#[derive(Clone, Copy)]
+pub struct BodyLimits {
+    pub max_bytes: usize,
+    /// Limit of attachment uploads, which may be larger than other requests
+    pub attachment_max_bytes: usize,
+}
+
+impl BodyLimits {
+    fn for_request(&self, request: &Request) -> usize {
+        let path = request.uri().path();
+        let is_attachment_upload = request.method() == Method::POST
+            && path.starts_with("/api/v1/health-records/")
+            && path.ends_with("/attachments");
+
+        if is_attachment_upload {
+            self.attachment_max_bytes + MULTIPART_OVERHEAD_BYTES
+        } else {
+            self.max_bytes
+        }
+    }
+}
+
+/// Reject requests whose declared Content-Length exceeds the configured limit.
+///
+/// Bodies without a Content-Length (chunked uploads) are still capped by the
+/// `DefaultBodyLimit` layer when the extractor reads them, and attachment
+/// uploads by the upload handler.
+pub async fn reject_oversized_bodies(
+    State(limits): State<BodyLimits>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let max_bytes = limits.for_request(&request);
+    let declared_length = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+
+    if let Some(length) = declared_length {
+        if length > max_bytes {
+            return Err(AppError::PayloadTooLarge(format!(
+                "Request body of {} bytes exceeds the {} byte limit",
+                length, max_bytes
+            )));
+        }
+    }
+
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,49 @@
+use axum::http::Request;
+use tracing::Span;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::config::LogFormat;
+
+const DEFAULT_LOG_FILTER: &str = "zk_health_pass_backend=debug,tower_http=debug";
+
+/// Initialize the global tracing subscriber in the configured output format
+pub fn init_tracing(log_format: LogFormat) {
+    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| DEFAULT_LOG_FILTER.into());
+
+    let (json_layer, text_layer) = match log_format {
+        LogFormat::Json => (
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(false)
+                    .with_target(true),
+            ),
+            None,
+        ),
+        LogFormat::Text => (None, Some(tracing_subscriber::fmt::layer())),
+    };
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(json_layer)
+        .with(text_layer)
+        .init();
+}
+
+/// Build the per-request span, tagging it with the `x-request-id` set by `SetRequestIdLayer`
+pub fn make_request_span<B>(request: &Request<B>) -> Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("-");
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        request_id = %request_id,
+    )
+}
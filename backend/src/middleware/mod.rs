@@ -1,3 +1,9 @@
 pub mod auth;
+pub mod compression;
 pub mod cors;
+pub mod idempotency;
+pub mod limits;
 pub mod logging;
+pub mod permissions;
+pub mod problem_json;
+pub mod rate_limit;
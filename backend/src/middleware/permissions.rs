@@ -0,0 +1,187 @@
+//! Which roles may do what.
+//!
+//! Every role-gated action is a [`Permission`], and [`role_can`] is the one
+//! table deciding which roles hold it. Handlers state what they need in their
+//! signature with the [`RequirePermission`] extractor, e.g.
+//! `auth_user: RequirePermission<can::SignRecords>`, and never match on roles.
+
+use std::{marker::PhantomData, ops::Deref};
+
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+
+use crate::{errors::AppError, middleware::auth::AuthUser, models::UserRole, AppState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Sign and co-sign health records for an authority
+    SignRecords,
+    RevokeRecords,
+    /// Create and list record templates
+    ManageTemplates,
+    /// Create and update authorities and assign their providers
+    ManageAuthorities,
+    ManageVerifiers,
+    /// Mark user accounts as verified
+    VerifyUsers,
+    InviteProviders,
+    /// Issue read-only tokens acting as another user
+    ImpersonateUsers,
+    ResolveProofAlerts,
+    /// Export verification logs and analytics
+    ExportVerifications,
+    /// See an authority's activity statistics
+    ViewAuthorityStats,
+    /// See field encryption status and re-encrypt records under the current key
+    ReencryptRecords,
+    /// See aggregate user, record, proof and verification counts
+    ViewDashboardStats,
+}
+
+impl Permission {
+    pub const ALL: &'static [Permission] = &[
+        Permission::SignRecords,
+        Permission::RevokeRecords,
+        Permission::ManageTemplates,
+        Permission::ManageAuthorities,
+        Permission::ManageVerifiers,
+        Permission::VerifyUsers,
+        Permission::InviteProviders,
+        Permission::ImpersonateUsers,
+        Permission::ResolveProofAlerts,
+        Permission::ExportVerifications,
+        Permission::ViewAuthorityStats,
+        Permission::ReencryptRecords,
+        Permission::ViewDashboardStats,
+    ];
+
+    fn denied_message(self) -> &'static str {
+        if role_can(&UserRole::Provider, self) {
+            "Provider or admin access required"
+        } else {
+            "Admin access required"
+        }
+    }
+}
+
+/// The authorization policy: whether `role` holds `permission`
+pub fn role_can(role: &UserRole, permission: Permission) -> bool {
+    use Permission::*;
+
+    match role {
+        UserRole::Admin => true,
+        UserRole::Provider => matches!(permission, SignRecords | RevokeRecords | ManageTemplates | ViewAuthorityStats),
+        UserRole::Patient | UserRole::Verifier => false,
+    }
+}
+
+/// Names the permission a [`RequirePermission`] extractor checks
+pub trait RequiredPermission {
+    const PERMISSION: Permission;
+}
+
+macro_rules! permission_markers {
+    ($($name:ident),* $(,)?) => {
+        /// One marker type per [`Permission`], for use as `RequirePermission<can::SignRecords>`
+        pub mod can {
+            $(
+                pub struct $name;
+
+                impl super::RequiredPermission for $name {
+                    const PERMISSION: super::Permission = super::Permission::$name;
+                }
+            )*
+        }
+    };
+}
+
+permission_markers!(
+    SignRecords,
+    RevokeRecords,
+    ManageTemplates,
+    ManageAuthorities,
+    ManageVerifiers,
+    VerifyUsers,
+    InviteProviders,
+    ImpersonateUsers,
+    ResolveProofAlerts,
+    ExportVerifications,
+    ViewAuthorityStats,
+    ReencryptRecords,
+    ViewDashboardStats,
+);
+
+/// An authenticated user whose role holds `P`'s permission; rejects everyone
+/// else with `403 Forbidden`
+pub struct RequirePermission<P> {
+    auth_user: AuthUser,
+    permission: PhantomData<P>,
+}
+
+impl<P> Deref for RequirePermission<P> {
+    type Target = AuthUser;
+
+    fn deref(&self) -> &AuthUser {
+        &self.auth_user
+    }
+}
+
+#[async_trait]
+impl<S, P> FromRequestParts<S> for RequirePermission<P>
+where
+    AppState: FromRequestParts<S>,
+    S: Send + Sync,
+    P: RequiredPermission,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let auth_user = AuthUser::from_request_parts(parts, state).await?;
+
+        if !role_can(&auth_user.user.role, P::PERMISSION) {
+            return Err(AppError::Forbidden(P::PERMISSION.denied_message().to_string()));
+        }
+
+        Ok(RequirePermission { auth_user, permission: PhantomData })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_permission_matrix() {
+        use Permission::*;
+
+        // Patient, Provider, Verifier, Admin
+        let matrix = [
+            (SignRecords, [false, true, false, true]),
+            (RevokeRecords, [false, true, false, true]),
+            (ManageTemplates, [false, true, false, true]),
+            (ManageAuthorities, [false, false, false, true]),
+            (ManageVerifiers, [false, false, false, true]),
+            (VerifyUsers, [false, false, false, true]),
+            (InviteProviders, [false, false, false, true]),
+            (ImpersonateUsers, [false, false, false, true]),
+            (ResolveProofAlerts, [false, false, false, true]),
+            (ExportVerifications, [false, false, false, true]),
+            (ViewAuthorityStats, [false, true, false, true]),
+            (ReencryptRecords, [false, false, false, true]),
+            (ViewDashboardStats, [false, false, false, true]),
+        ];
+        let roles = [UserRole::Patient, UserRole::Provider, UserRole::Verifier, UserRole::Admin];
+
+        assert_eq!(matrix.len(), Permission::ALL.len(), "every permission needs a row");
+        for (permission, allowed) in matrix {
+            for (role, allowed) in roles.iter().zip(allowed) {
+                assert_eq!(role_can(role, permission), allowed, "{:?} / {:?}", role, permission);
+            }
+        }
+    }
+
+    #[test]
+    fn test_denials_name_the_roles_that_may() {
+        assert_eq!(Permission::SignRecords.denied_message(), "Provider or admin access required");
+        assert_eq!(Permission::ManageVerifiers.denied_message(), "Admin access required");
+    }
+}
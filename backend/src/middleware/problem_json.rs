@@ -0,0 +1,114 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE}, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::errors::ErrorDetails;
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Render `AppError` responses as RFC 7807 `application/problem+json` for
+/// clients that ask for it in `Accept`. Everyone else keeps the default
+/// `{"error": {...}}` body.
+pub async fn render_problem_json(request: Request, next: Next) -> Response {
+    let wants_problem_json = accepts_problem_json(request.headers());
+    let instance = request.uri().path().to_string();
+
+    let mut response = next.run(request).await;
+    let Some(details) = response.extensions_mut().remove::<ErrorDetails>() else {
+        return response;
+    };
+    if !wants_problem_json {
+        return response;
+    }
+
+    let body = serde_json::to_vec(&details.to_problem_json(&instance))
+        .expect("problem details serialize to JSON");
+
+    // Keep the status and any headers set alongside the error
+    let (mut parts, _) = response.into_parts();
+    parts.headers.insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+    parts.headers.remove(CONTENT_LENGTH);
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+fn accepts_problem_json(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media_type| {
+            media_type
+                .split(';')
+                .next()
+                .map_or(false, |essence| essence.trim().eq_ignore_ascii_case(PROBLEM_JSON))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::AppError;
+    use axum::{body::to_bytes, http::StatusCode, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/records/missing",
+                get(|| async { Err::<(), _>(AppError::NotFound("Health record not found".to_string())) }),
+            )
+            .route("/ok", get(|| async { "fine" }))
+            .layer(from_fn(render_problem_json))
+    }
+
+    async fn json_body(response: Response) -> serde_json::Value {
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_problem_json_when_requested() {
+        let request = Request::get("/records/missing")
+            .header(ACCEPT, "application/json;q=0.5, application/problem+json")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[CONTENT_TYPE], PROBLEM_JSON);
+        let body = json_body(response).await;
+        assert_eq!(body["type"], "urn:zk-health-pass:problem:not-found");
+        assert_eq!(body["title"], "Not Found");
+        assert_eq!(body["status"], 404);
+        assert_eq!(body["detail"], "Health record not found");
+        assert_eq!(body["instance"], "/records/missing");
+    }
+
+    #[tokio::test]
+    async fn test_default_error_shape_is_unchanged() {
+        let request = Request::get("/records/missing").body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+        let body = json_body(response).await;
+        assert_eq!(body["error"]["code"], "NOT_FOUND");
+        assert_eq!(body["error"]["message"], "Health record not found");
+    }
+
+    #[tokio::test]
+    async fn test_success_responses_pass_through() {
+        let request = Request::get("/ok")
+            .header(ACCEPT, PROBLEM_JSON)
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(to_bytes(response.into_body(), usize::MAX).await.unwrap(), "fine");
+    }
+}
@@ -0,0 +1,216 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use uuid::Uuid;
+
+use crate::{errors::AppError, models::ApiKeyUsage, services::store::Store};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    pub store: Arc<dyn Store>,
+    pub requests_per_minute: u64,
+}
+
+/// Fixed-window per-client rate limit; counters live in the shared store so the
+/// limit holds across instances. A limit of 0 disables it.
+pub async fn enforce_rate_limit(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if limiter.requests_per_minute == 0 {
+        return Ok(next.run(request).await);
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let window = now / WINDOW.as_secs();
+    let key = format!("rate_limit:{}:{}", client_id(&request), window);
+
+    if limiter.store.increment(&key, WINDOW).await? > limiter.requests_per_minute {
+        return Err(AppError::RateLimitExceeded {
+            retry_after_secs: seconds_until_next_window(now),
+        });
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Time until the current window's counter resets; never 0 so clients always back off
+fn seconds_until_next_window(now_secs: u64) -> u64 {
+    WINDOW.as_secs() - now_secs % WINDOW.as_secs()
+}
+
+/// First `X-Forwarded-For` hop when behind the proxy, otherwise the peer address
+fn client_id(request: &Request) -> String {
+    request
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(|ip| ip.trim().to_string())
+        .or_else(|| {
+            request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Per-API-key quotas, on top of the per-client limit. Each key has a token
+/// bucket holding up to its `rate_limit_rpm` requests, refilled at that rate,
+/// so integrators can burst up to their quota and are then paced. Buckets live
+/// in this process.
+#[derive(Clone, Default)]
+pub struct ApiKeyLimiter {
+    buckets: Arc<Mutex<HashMap<Uuid, TokenBucket>>>,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// Add the tokens earned since the last refill, up to `rpm`
+    fn refill(&mut self, rpm: u32, now: Instant) {
+        let earned = now.saturating_duration_since(self.refilled_at).as_secs_f64() * rpm as f64 / 60.0;
+        self.tokens = (self.tokens + earned).min(rpm as f64);
+        self.refilled_at = now;
+    }
+}
+
+impl ApiKeyLimiter {
+    /// Take one request from `key_id`'s quota of `rpm` requests per minute;
+    /// keys without a quota are not limited
+    pub fn acquire(&self, key_id: Uuid, rpm: Option<u32>) -> Result<(), AppError> {
+        self.acquire_at(key_id, rpm, Instant::now())
+    }
+
+    fn acquire_at(&self, key_id: Uuid, rpm: Option<u32>, now: Instant) -> Result<(), AppError> {
+        let Some(rpm) = rpm.filter(|rpm| *rpm > 0) else {
+            return Ok(());
+        };
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let bucket = buckets
+            .entry(key_id)
+            .or_insert(TokenBucket { tokens: rpm as f64, refilled_at: now });
+        bucket.refill(rpm, now);
+
+        if bucket.tokens < 1.0 {
+            let seconds_per_token = 60.0 / rpm as f64;
+            return Err(AppError::RateLimitExceeded {
+                retry_after_secs: ((1.0 - bucket.tokens) * seconds_per_token).ceil().max(1.0) as u64,
+            });
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+
+    /// How much of `key_id`'s quota is left right now
+    pub fn usage(&self, key_id: Uuid, rpm: Option<u32>) -> Option<ApiKeyUsage> {
+        self.usage_at(key_id, rpm, Instant::now())
+    }
+
+    fn usage_at(&self, key_id: Uuid, rpm: Option<u32>, now: Instant) -> Option<ApiKeyUsage> {
+        let rpm = rpm.filter(|rpm| *rpm > 0)?;
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let remaining = match buckets.get_mut(&key_id) {
+            Some(bucket) => {
+                bucket.refill(rpm, now);
+                bucket.tokens.floor() as u32
+            }
+            None => rpm,
+        };
+        Some(ApiKeyUsage { rate_limit_rpm: rpm, remaining })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::store::MemoryStore;
+    use axum::{
+        body::Body,
+        http::{header::RETRY_AFTER, StatusCode},
+        middleware::from_fn_with_state,
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_api_keys_are_throttled_independently() {
+        let limiter = ApiKeyLimiter::default();
+        let (busy, quiet) = (Uuid::new_v4(), Uuid::new_v4());
+        let start = Instant::now();
+
+        for _ in 0..2 {
+            limiter.acquire_at(busy, Some(2), start).unwrap();
+        }
+        let error = limiter.acquire_at(busy, Some(2), start).unwrap_err();
+        assert!(matches!(error, AppError::RateLimitExceeded { retry_after_secs: 30 }));
+        assert_eq!(limiter.usage_at(busy, Some(2), start).unwrap().remaining, 0);
+
+        // The other key's quota is untouched
+        assert_eq!(limiter.usage_at(quiet, Some(2), start).unwrap().remaining, 2);
+        limiter.acquire_at(quiet, Some(2), start).unwrap();
+        assert_eq!(limiter.usage_at(quiet, Some(2), start).unwrap().remaining, 1);
+
+        // Half a minute refills one of the two requests
+        let later = start + Duration::from_secs(30);
+        limiter.acquire_at(busy, Some(2), later).unwrap();
+        assert!(limiter.acquire_at(busy, Some(2), later).is_err());
+
+        // Keys without a quota are not limited
+        for _ in 0..100 {
+            limiter.acquire_at(Uuid::new_v4(), None, start).unwrap();
+        }
+        assert!(limiter.usage_at(quiet, None, start).is_none());
+    }
+
+    #[test]
+    fn test_retry_after_counts_down_to_the_next_window() {
+        assert_eq!(seconds_until_next_window(120), 60);
+        assert_eq!(seconds_until_next_window(121), 59);
+        assert_eq!(seconds_until_next_window(179), 1);
+    }
+
+    #[tokio::test]
+    async fn test_limited_request_carries_retry_after() {
+        let limiter = RateLimiter {
+            store: Arc::new(MemoryStore::new()),
+            requests_per_minute: 1,
+        };
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(from_fn_with_state(limiter, enforce_rate_limit));
+
+        let request = || Request::get("/").header("x-forwarded-for", "203.0.113.7").body(Body::empty()).unwrap();
+        assert_eq!(app.clone().oneshot(request()).await.unwrap().status(), StatusCode::OK);
+
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()[RETRY_AFTER].to_str().unwrap().parse().unwrap();
+        assert!((1..=WINDOW.as_secs()).contains(&retry_after));
+
+        let body: serde_json::Value = serde_json::from_slice(
+            &axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap(),
+        )
+        .unwrap();
+        assert_eq!(body["error"]["retry_after_secs"], retry_after);
+    }
+}
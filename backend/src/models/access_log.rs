@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AccessType {
+    /// The record itself was read
+    Read,
+    /// The record was on a page of listed records
+    List,
+    /// The record was included in an export
+    Export,
+}
+
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct RecordAccess {
+    pub id: Uuid,
+    pub health_record_id: Uuid,
+    /// `null` once the user has been deleted
+    pub actor_id: Option<Uuid>,
+    pub actor_name: Option<String>,
+    /// Admin who read the record while impersonating the actor
+    pub acting_admin_id: Option<Uuid>,
+    pub acting_admin_name: Option<String>,
+    pub access_type: AccessType,
+    pub purpose: String,
+    pub accessed_at: DateTime<Utc>,
+}
+
+/// Why records are being read; the `X-Access-Purpose` header works as well
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct AccessPurposeQuery {
+    pub purpose: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AccessLogQuery {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
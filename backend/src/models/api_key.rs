@@ -15,6 +15,8 @@ pub struct ApiKey {
     pub expires_at: Option<DateTime<Utc>>,
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
+    /// Requests per minute the key may make; `None` leaves it unlimited
+    pub rate_limit_rpm: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -23,6 +25,8 @@ pub struct CreateApiKeyRequest {
     pub name: String,
     pub permissions: Vec<String>,
     pub expires_in_days: Option<u32>,
+    #[validate(range(min = 1, message = "Rate limit must be at least 1 request per minute"))]
+    pub rate_limit_rpm: Option<u32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -35,4 +39,13 @@ pub struct ApiKeyResponse {
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub key: Option<String>, // Only returned when creating a new key
+    /// Quota and what is left of it; `None` for unlimited keys
+    pub usage: Option<ApiKeyUsage>,
+}
+
+/// A key's rate limit and the requests it can make right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ApiKeyUsage {
+    pub rate_limit_rpm: u32,
+    pub remaining: u32,
 }
@@ -0,0 +1,55 @@
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub health_record_id: Uuid,
+    /// Key of the object in the attachment bucket
+    pub object_key: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    /// Hex SHA-256 of the uploaded bytes
+    pub sha256: String,
+    pub uploaded_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AttachmentResponse {
+    pub id: Uuid,
+    pub health_record_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    /// Hex SHA-256 of the file, to check a download against
+    pub sha256: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Attachment> for AttachmentResponse {
+    fn from(attachment: Attachment) -> Self {
+        Self {
+            id: attachment.id,
+            health_record_id: attachment.health_record_id,
+            file_name: attachment.file_name,
+            content_type: attachment.content_type,
+            size_bytes: attachment.size_bytes,
+            sha256: attachment.sha256,
+            created_at: attachment.created_at,
+        }
+    }
+}
+
+/// Multipart form of an attachment upload; only documents the request body
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct AttachmentUploadForm {
+    /// The file, with its name and content type in the part headers
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use utoipa::ToSchema;
+
+/// Overview of the admin's organization, or of every organization for superadmins
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DashboardStats {
+    pub users: UserCounts,
+    pub active_authorities: i64,
+    pub inactive_authorities: i64,
+    pub records_issued: i64,
+    pub records_revoked: i64,
+    pub proofs_generated: i64,
+    /// Background proof generations, finished or not
+    pub proof_jobs: i64,
+    pub failed_proof_jobs: i64,
+    pub proof_job_failure_rate: f64,
+    pub total_verifications: i64,
+    pub failed_verifications: i64,
+    pub verification_failure_rate: f64,
+    /// When the numbers were computed; responses are cached briefly
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserCounts {
+    pub total: i64,
+    pub patients: i64,
+    pub providers: i64,
+    pub verifiers: i64,
+    pub admins: i64,
+}
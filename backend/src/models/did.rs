@@ -0,0 +1,34 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// W3C DID document for a health authority, as resolved from its `did:web` DID
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    pub verification_method: Vec<VerificationMethod>,
+    /// Keys the authority issues credentials with
+    pub assertion_method: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub method_type: String,
+    pub controller: String,
+    pub public_key_jwk: PublicKeyJwk,
+}
+
+/// EC public key as a JSON Web Key (RFC 7517), coordinates base64url-encoded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PublicKeyJwk {
+    pub kty: String,
+    /// `secp256k1` or `P-256`
+    pub crv: String,
+    pub x: String,
+    pub y: String,
+}
@@ -0,0 +1,22 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FieldEncryptionStatus {
+    /// Fingerprint of the master key new records are encrypted with
+    pub current_key_id: String,
+    pub keys: Vec<EncryptionKeyUsage>,
+    /// Records still to be encrypted or re-wrapped under the current key
+    pub pending_records: i64,
+    /// Whether a re-encryption job is running on this instance
+    pub reencrypt_running: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EncryptionKeyUsage {
+    /// Master key fingerprint; `null` counts records stored in plaintext
+    pub key_id: Option<String>,
+    pub records: i64,
+    /// Whether the key is configured, so its records can be read
+    pub configured: bool,
+}
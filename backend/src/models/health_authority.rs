@@ -2,21 +2,35 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use validator::Validate;
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct HealthAuthority {
     pub id: Uuid,
     pub name: String,
     pub authority_type: AuthorityType,
-    pub public_key: String, // secp256k1 public key in hex format
+    pub public_key: String, // SEC1 public key on `curve` in hex format
     pub certificate: Option<String>, // X.509 certificate
     pub is_active: bool,
+    pub org_id: Uuid,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub curve: SigningCurve,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+/// Elliptic curve an authority signs records with. Only secp256k1 records can
+/// be proven with the Noir circuits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum SigningCurve {
+    #[default]
+    Secp256k1,
+    P256,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
 pub enum AuthorityType {
     Hospital,
@@ -27,38 +41,198 @@ pub enum AuthorityType {
     University,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateAuthorityRequest {
     #[validate(length(min = 2, message = "Authority name must be at least 2 characters"))]
     pub name: String,
     pub authority_type: AuthorityType,
-    #[validate(length(min = 1, message = "Public key is required"))]
+    /// SEC1 hex, or a `PUBLIC KEY` PEM block as exported by OpenSSL
+    #[validate(
+        length(min = 1, message = "Public key is required"),
+        custom(function = "validate_public_key_format")
+    )]
     pub public_key: String,
+    /// Curve of `public_key`; defaults to secp256k1
+    #[serde(default)]
+    pub curve: SigningCurve,
     pub certificate: Option<String>,
+    /// Owning organization; only superadmins may set it, others create in their own
+    pub org_id: Option<Uuid>,
+}
+
+/// Hex keys must decode to a SEC1 point encoding: 33 bytes starting with
+/// 02/03, or 65 bytes starting with 04. Whether the point is on the curve is
+/// left to the crypto layer, as is parsing PEM blocks.
+pub fn validate_public_key_format(public_key: &str) -> Result<(), ValidationError> {
+    let public_key = public_key.trim();
+    if public_key.is_empty() || public_key.starts_with("-----BEGIN ") {
+        return Ok(());
+    }
+
+    let invalid = |message: String| {
+        let mut error = ValidationError::new("public_key_format");
+        error.message = Some(message.into());
+        error
+    };
+    let bytes = hex::decode(public_key.trim_start_matches("0x"))
+        .map_err(|_| invalid("Public key must be SEC1 hex or a PEM block".to_string()))?;
+    match (bytes.len(), bytes.first()) {
+        (33, Some(0x02 | 0x03)) | (65, Some(0x04)) => Ok(()),
+        (33 | 65, _) => Err(invalid(
+            "Public key must start with 02 or 03 (compressed) or 04 (uncompressed)".to_string(),
+        )),
+        (len, _) => Err(invalid(format!(
+            "Public key must be 33 bytes (compressed) or 65 bytes (uncompressed), got {}",
+            len
+        ))),
+    }
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateAuthorityRequest {
     pub name: Option<String>,
     pub authority_type: Option<AuthorityType>,
+    /// SEC1 hex or PEM, as on creation
+    #[validate(custom(function = "validate_public_key_format"))]
     pub public_key: Option<String>,
+    /// Curve of the new `public_key`; required when changing curves
+    pub curve: Option<SigningCurve>,
     pub certificate: Option<String>,
     pub is_active: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct AuthorityResponse {
     pub id: Uuid,
     pub name: String,
     pub authority_type: AuthorityType,
     pub public_key: String,
+    pub curve: SigningCurve,
     pub has_certificate: bool,
     pub is_active: bool,
+    pub org_id: Uuid,
     pub created_at: DateTime<Utc>,
     pub health_records_count: Option<i64>,
 }
 
+/// A provider allowed to sign health records on behalf of an authority
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ProviderAuthority {
+    pub user_id: Uuid,
+    pub authority_id: Uuid,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AssignProviderRequest {
+    pub user_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AuthorityImportQuery {
+    /// Import the valid rows even if others are invalid; by default any invalid row rejects the whole file
+    pub partial: Option<bool>,
+    /// Owning organization; only superadmins may set it, others import into their own
+    pub org_id: Option<Uuid>,
+}
+
+/// One data row of an authority CSV import: `name,authority_type,public_key,certificate`
 #[derive(Debug, Deserialize)]
+pub struct AuthorityCsvRow {
+    pub name: String,
+    pub authority_type: AuthorityType,
+    /// secp256k1 key as SEC1 hex or PEM
+    pub public_key: String,
+    pub certificate: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportRowStatus {
+    Created,
+    Invalid,
+    /// Valid, but left out because another row was invalid
+    NotImported,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuthorityImportRow {
+    /// Line of the row in the CSV, counting the header as line 1
+    pub line: u64,
+    pub status: ImportRowStatus,
+    pub authority_id: Option<Uuid>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuthorityImportResponse {
+    pub created: usize,
+    pub invalid: usize,
+    pub rows: Vec<AuthorityImportRow>,
+}
+
+/// Width of the periods in a statistics timeline
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsInterval {
+    Day,
+    /// Weeks start on Monday
+    Week,
+    #[default]
+    Month,
+}
+
+impl StatsInterval {
+    /// Postgres `date_trunc` field
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatsInterval::Day => "day",
+            StatsInterval::Week => "week",
+            StatsInterval::Month => "month",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct AuthorityStatsQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Timeline period; defaults to `month`
+    pub interval: Option<StatsInterval>,
+}
+
+/// Activity of one authority over a time range. Records count by creation,
+/// proofs by generation and verifications by when they happened.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuthorityStats {
+    pub authority_id: Uuid,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub interval: StatsInterval,
+    pub records_issued: i64,
+    pub active_records: i64,
+    pub revoked_records: i64,
+    pub proofs_generated: i64,
+    pub total_verifications: i64,
+    pub successful_verifications: i64,
+    pub success_rate: f64,
+    /// Every period from the first to the last with activity, quiet ones included
+    pub timeline: Vec<AuthorityStatsPeriod>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, ToSchema)]
+pub struct AuthorityStatsPeriod {
+    pub period_start: DateTime<Utc>,
+    pub records_issued: i64,
+    /// Records issued in this period that have been revoked since
+    pub records_revoked: i64,
+    pub proofs_generated: i64,
+    pub verifications: i64,
+    pub successful_verifications: i64,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct AuthorityQuery {
     pub authority_type: Option<AuthorityType>,
     pub is_active: Option<bool>,
@@ -74,10 +248,61 @@ impl From<HealthAuthority> for AuthorityResponse {
             name: authority.name,
             authority_type: authority.authority_type,
             public_key: authority.public_key,
+            curve: authority.curve,
             has_certificate: authority.certificate.is_some(),
             is_active: authority.is_active,
+            org_id: authority.org_id,
             created_at: authority.created_at,
             health_records_count: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public_key_errors(public_key: &str) -> Vec<String> {
+        let request = CreateAuthorityRequest {
+            name: "Clinic".to_string(),
+            authority_type: AuthorityType::Clinic,
+            public_key: public_key.to_string(),
+            curve: SigningCurve::default(),
+            certificate: None,
+            org_id: None,
+        };
+        let Err(errors) = request.validate() else {
+            return Vec::new();
+        };
+        errors.field_errors()["public_key"]
+            .iter()
+            .map(|error| error.message.as_deref().unwrap_or_default().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_public_key_format_is_checked_before_parsing() {
+        let x = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let y = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+        assert!(public_key_errors(&format!("02{}", x)).is_empty());
+        assert!(public_key_errors(&format!("0x03{}", x)).is_empty());
+        assert!(public_key_errors(&format!("04{}{}", x, y)).is_empty());
+        assert!(public_key_errors("-----BEGIN PUBLIC KEY-----\n...\n-----END PUBLIC KEY-----").is_empty());
+
+        assert_eq!(public_key_errors(""), vec!["Public key is required"]);
+        assert_eq!(public_key_errors("not-a-key!"), vec!["Public key must be SEC1 hex or a PEM block"]);
+        assert_eq!(public_key_errors("abc"), vec!["Public key must be SEC1 hex or a PEM block"]);
+        assert_eq!(
+            public_key_errors("0123456789"),
+            vec!["Public key must be 33 bytes (compressed) or 65 bytes (uncompressed), got 5"]
+        );
+        assert_eq!(
+            public_key_errors(&format!("04{}", x)),
+            vec!["Public key must start with 02 or 03 (compressed) or 04 (uncompressed)"]
+        );
+        assert_eq!(
+            public_key_errors(&format!("02{}{}", x, y)),
+            vec!["Public key must start with 02 or 03 (compressed) or 04 (uncompressed)"]
+        );
+    }
+}
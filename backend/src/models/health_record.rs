@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc, NaiveDate};
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 use std::collections::HashMap;
 
@@ -18,12 +19,28 @@ pub struct HealthRecord {
     pub signature_r: Vec<u8>,
     pub signature_s: Vec<u8>,
     pub message_hash: Vec<u8>,
+    pub format_version: i32, // services::message_format version the signature was made with
     pub is_revoked: bool,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revocation_reason: Option<String>,
+    pub org_id: Uuid,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    // Set when patient_identifier and details are encrypted (see services::field_encryption)
+    #[serde(skip)]
+    pub encrypted_fields: Option<Vec<u8>>,
+    #[serde(skip)]
+    pub encryption_nonce: Option<Vec<u8>>,
+    #[serde(skip)]
+    pub encrypted_data_key: Option<Vec<u8>>,
+    #[serde(skip)]
+    pub encryption_key_id: Option<String>,
+    #[serde(skip)]
+    pub content_fingerprint: Option<Vec<u8>>, // Duplicate detection, see HealthRecordService::content_fingerprint
+    pub series_id: Option<Uuid>, // Vaccination series the dose belongs to
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
 pub enum HealthRecordType {
     Vaccination,
@@ -32,7 +49,7 @@ pub enum HealthRecordType {
     ImmunityProof,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateHealthRecordRequest {
     pub authority_id: Uuid,
     pub record_type: HealthRecordType,
@@ -41,9 +58,42 @@ pub struct CreateHealthRecordRequest {
     pub details: HashMap<String, serde_json::Value>,
     pub issue_date: NaiveDate,
     pub expiry_date: Option<NaiveDate>,
+    /// Further authorities that must co-sign after `authority_id`, in signing order
+    #[serde(default)]
+    pub co_signing_authority_ids: Vec<Uuid>,
+    /// Create the record even if an identical one already exists
+    #[serde(default)]
+    pub allow_duplicate: bool,
+    /// Vaccination series to add this dose to. When omitted, doses of the same
+    /// vaccine for the same patient and authority are grouped automatically.
+    pub series_id: Option<Uuid>,
 }
 
-#[derive(Debug, Serialize)]
+/// One co-signature slot of a record; unsigned until `message_hash` is set
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RecordSignature {
+    pub id: Uuid,
+    pub health_record_id: Uuid,
+    pub authority_id: Uuid,
+    pub position: i32,
+    pub signature_r: Option<Vec<u8>>,
+    pub signature_s: Option<Vec<u8>>,
+    pub message_hash: Option<Vec<u8>>,
+    pub signed_by: Option<Uuid>,
+    pub signed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An authority whose signature a record requires
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SigningAuthority {
+    pub authority_id: Uuid,
+    pub authority_name: String,
+    pub position: i32, // 0 is the record's own authority
+    pub signed: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HealthRecordResponse {
     pub id: Uuid,
     pub record_type: HealthRecordType,
@@ -52,12 +102,49 @@ pub struct HealthRecordResponse {
     pub issue_date: NaiveDate,
     pub expiry_date: Option<NaiveDate>,
     pub authority_name: String,
+    pub authority_id: Uuid,
     pub is_revoked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// Why the record was revoked, e.g. `superseded`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revocation_reason: Option<String>,
     pub created_at: DateTime<Utc>,
-    pub has_valid_signature: bool,
+    pub has_valid_signature: bool, // Every required signature, co-signatures included, is in place
+    pub integrity_ok: bool, // Stored message hash still matches the record's current contents
+    pub signing_authorities: Vec<SigningAuthority>,
+    pub series_id: Option<Uuid>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct RevokeHealthRecordRequest {
+    /// Why the record is revoked, e.g. `superseded` or `issued in error`;
+    /// shown to the owner and to verifiers of its proofs
+    #[validate(length(min = 1, max = 255, message = "Revocation reason must be 1 to 255 characters"))]
+    pub reason: String,
+}
+
+/// One dose of a vaccination series
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SeriesDose {
+    pub record_id: Uuid,
+    pub dose_number: Option<u32>, // From the record's `VaccinationDetails`
+    pub issue_date: NaiveDate,
+    pub is_revoked: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VaccinationSeriesResponse {
+    pub series_id: Uuid,
+    pub vaccine_name: Option<String>,
+    pub authority_name: String,
+    pub total_doses: Option<u32>,
+    pub doses_received: u32, // Distinct dose numbers among unrevoked doses
+    pub is_complete: bool,
+    pub doses: Vec<SeriesDose>, // Ordered by dose number, then issue date
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct HealthRecordQuery {
     pub record_type: Option<HealthRecordType>,
     pub authority_id: Option<Uuid>,
@@ -66,6 +153,52 @@ pub struct HealthRecordQuery {
     pub include_revoked: Option<bool>,
     pub page: Option<u32>,
     pub limit: Option<u32>,
+    pub cursor: Option<String>, // Opaque keyset cursor from a previous page's `next_cursor`
+    pub sort: Option<RecordSort>, // Cursors only page the default `created_at_desc` order
+    /// Free text matched against the record details; results are ranked by relevance
+    pub search: Option<String>,
+}
+
+/// File format of `GET /health-records/export`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordExportFormat {
+    #[default]
+    Csv,
+    /// A JSON array of `HealthRecordResponse`
+    Json,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct RecordExportQuery {
+    pub format: Option<RecordExportFormat>,
+    pub include_revoked: Option<bool>,
+}
+
+/// Orders `GET /health-records` can be listed in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordSort {
+    #[default]
+    CreatedAtDesc,
+    CreatedAtAsc,
+    IssueDateDesc,
+    IssueDateAsc,
+    /// By record type, newest first within a type
+    RecordType,
+}
+
+impl RecordSort {
+    /// `ORDER BY` clause over `hr`; `id` breaks ties so pages don't overlap
+    pub fn order_by(self) -> &'static str {
+        match self {
+            RecordSort::CreatedAtDesc => "hr.created_at DESC, hr.id DESC",
+            RecordSort::CreatedAtAsc => "hr.created_at ASC, hr.id ASC",
+            RecordSort::IssueDateDesc => "hr.issue_date DESC, hr.id DESC",
+            RecordSort::IssueDateAsc => "hr.issue_date ASC, hr.id ASC",
+            RecordSort::RecordType => "hr.record_type ASC, hr.created_at DESC, hr.id DESC",
+        }
+    }
 }
 
 // Specific health record detail structures
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ProviderInvite {
+    pub id: Uuid,
+    pub email: String,
+    pub authority_id: Uuid,
+    pub org_id: Uuid,
+    pub token_hash: String,
+    pub invited_by: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateInviteRequest {
+    #[validate(email)]
+    pub email: String,
+    /// Authority the provider will be allowed to sign for
+    pub authority_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct AcceptInviteRequest {
+    pub token: String,
+    #[validate(length(min = 8, message = "Password must be at least 8 characters"))]
+    pub password: String,
+    #[validate(length(min = 2, message = "Full name must be at least 2 characters"))]
+    pub full_name: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InviteResponse {
+    pub id: Uuid,
+    pub email: String,
+    pub authority_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub token: Option<String>, // Only returned when the invite is created
+}
+
+impl From<ProviderInvite> for InviteResponse {
+    fn from(invite: ProviderInvite) -> Self {
+        Self {
+            id: invite.id,
+            email: invite.email,
+            authority_id: invite.authority_id,
+            expires_at: invite.expires_at,
+            token: None,
+        }
+    }
+}
@@ -3,9 +3,37 @@ pub mod health_record;
 pub mod health_authority;
 pub mod zk_proof;
 pub mod api_key;
+pub mod webhook;
+pub mod pagination;
+pub mod verifier;
+pub mod organization;
+pub mod invite;
+pub mod record_share;
+pub mod record_transfer;
+pub mod record_template;
+pub mod webauthn;
+pub mod attachment;
+pub mod did;
+pub mod field_encryption;
+pub mod access_log;
+pub mod dashboard;
 
 pub use user::*;
 pub use health_record::*;
 pub use health_authority::*;
 pub use zk_proof::*;
 pub use api_key::*;
+pub use webhook::*;
+pub use pagination::*;
+pub use verifier::*;
+pub use organization::*;
+pub use invite::*;
+pub use record_share::*;
+pub use record_transfer::*;
+pub use record_template::*;
+pub use webauthn::*;
+pub use attachment::*;
+pub use did::*;
+pub use field_encryption::*;
+pub use access_log::*;
+pub use dashboard::*;
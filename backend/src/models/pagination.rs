@@ -0,0 +1,121 @@
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+/// Keyset position in a list ordered by `(timestamp, id)` descending
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn new(created_at: DateTime<Utc>, id: Uuid) -> Self {
+        Self { created_at, id }
+    }
+
+    /// Opaque form handed to clients: base64 of `<created_at>|<id>`
+    pub fn encode(&self) -> String {
+        general_purpose::URL_SAFE_NO_PAD.encode(format!("{}|{}", self.created_at.to_rfc3339(), self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, AppError> {
+        let invalid = || AppError::BadRequest("Invalid pagination cursor".to_string());
+
+        let raw = general_purpose::URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+        let (created_at, id) = raw.split_once('|').ok_or_else(invalid)?;
+
+        Ok(Self {
+            created_at: DateTime::parse_from_rfc3339(created_at).map_err(|_| invalid())?.with_timezone(&Utc),
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(ProofPage = Paginated<crate::models::ProofResponse>, HealthRecordPage = Paginated<crate::models::HealthRecordResponse>)]
+pub struct Paginated<T> {
+    pub data: Vec<T>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Paginated<T> {
+    /// Wrap a page of results, emitting a cursor only when the page was full
+    pub fn new(data: Vec<T>, limit: u32, cursor_of: impl Fn(&T) -> Cursor) -> Self {
+        let next_cursor = if data.len() == limit as usize {
+            data.last().map(|item| cursor_of(item).encode())
+        } else {
+            None
+        };
+
+        Self { data, next_cursor }
+    }
+
+    /// A page of a list that can only be paged by offset
+    pub fn without_cursor(data: Vec<T>) -> Self {
+        Self { data, next_cursor: None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn next_page(rows: &[Cursor], after: Option<Cursor>, limit: usize) -> Vec<Cursor> {
+        // Mirrors `WHERE (created_at, id) < ($cursor) ORDER BY created_at DESC, id DESC LIMIT $limit`
+        let mut page: Vec<Cursor> = rows
+            .iter()
+            .copied()
+            .filter(|row| after.map_or(true, |c| (row.created_at, row.id) < (c.created_at, c.id)))
+            .collect();
+        page.sort_by(|a, b| (b.created_at, b.id).cmp(&(a.created_at, a.id)));
+        page.truncate(limit);
+        page
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor::new(Utc::now(), Uuid::new_v4());
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(Cursor::decode("not-a-cursor").is_err());
+    }
+
+    #[test]
+    fn test_inserts_between_pages_do_not_duplicate_results() {
+        let start = Utc::now();
+        let mut rows: Vec<Cursor> = (0..10)
+            .map(|i| Cursor::new(start - Duration::minutes(i), Uuid::new_v4()))
+            .collect();
+        // Two rows sharing a timestamp are still ordered by id
+        rows.push(Cursor::new(start - Duration::minutes(4), Uuid::new_v4()));
+
+        let mut seen = Vec::new();
+        let first = Paginated::new(next_page(&rows, None, 4), 4, |c| *c);
+        seen.extend(first.data);
+
+        // New rows arrive after the first page was served
+        rows.push(Cursor::new(start + Duration::minutes(1), Uuid::new_v4()));
+        rows.push(Cursor::new(start + Duration::minutes(2), Uuid::new_v4()));
+
+        let mut cursor = first.next_cursor;
+        while let Some(encoded) = cursor {
+            let after = Cursor::decode(&encoded).unwrap();
+            let page = Paginated::new(next_page(&rows, Some(after), 4), 4, |c| *c);
+            seen.extend(page.data);
+            cursor = page.next_cursor;
+        }
+
+        let mut unique = seen.clone();
+        unique.sort_by_key(|c| (c.created_at, c.id));
+        unique.dedup();
+        assert_eq!(unique.len(), seen.len());
+        assert_eq!(seen.len(), 11);
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RecordShare {
+    pub id: Uuid,
+    pub health_record_id: Uuid,
+    pub owner_id: Uuid,
+    pub grantee_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateShareRequest {
+    /// User who may view the record
+    #[validate(email)]
+    pub grantee_email: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct RecordShareResponse {
+    pub id: Uuid,
+    pub health_record_id: Uuid,
+    pub grantee_email: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, NaiveDate, Utc};
+use utoipa::ToSchema;
+use validator::Validate;
+use std::collections::HashMap;
+
+use crate::models::HealthRecordType;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RecordTemplate {
+    pub id: Uuid,
+    pub authority_id: Uuid,
+    pub org_id: Uuid,
+    pub name: String,
+    pub record_type: HealthRecordType,
+    pub details: serde_json::Value,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateTemplateRequest {
+    #[validate(length(min = 1, max = 255, message = "Template name must be between 1 and 255 characters"))]
+    pub name: String,
+    pub record_type: HealthRecordType,
+    /// Details shared by every record issued from the template; may be partial
+    pub details: HashMap<String, serde_json::Value>,
+}
+
+/// The per-patient fields of a record issued from a template
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateFromTemplateRequest {
+    #[validate(length(min = 1, message = "Patient identifier is required"))]
+    pub patient_identifier: String,
+    pub issue_date: NaiveDate,
+    pub expiry_date: Option<NaiveDate>,
+    /// Merged over the template's details, replacing keys it also sets
+    #[serde(default)]
+    pub details: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub allow_duplicate: bool,
+    pub series_id: Option<Uuid>,
+}
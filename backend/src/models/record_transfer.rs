@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RecordTransfer {
+    pub id: Uuid,
+    pub health_record_id: Uuid,
+    pub from_user_id: Uuid,
+    pub to_user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateTransferRequest {
+    /// User who will own the record once they accept
+    #[validate(email)]
+    pub recipient_email: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AcceptTransferRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecordTransferResponse {
+    pub id: Uuid,
+    pub health_record_id: Uuid,
+    pub recipient_email: String,
+    pub expires_at: DateTime<Utc>,
+    pub token: String, // Only returned when the transfer is created
+}
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use utoipa::ToSchema;
 use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -12,11 +13,17 @@ pub struct User {
     pub full_name: String,
     pub role: UserRole,
     pub is_verified: bool,
+    pub org_id: Uuid,
+    pub is_superadmin: bool, // Sees and manages every organization
+    /// Emailed when one of their records is issued or revoked
+    pub email_notifications: bool,
+    /// Language of their notification emails; `None` for English
+    pub locale: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
 pub enum UserRole {
     Patient,
@@ -25,7 +32,7 @@ pub enum UserRole {
     Admin,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateUserRequest {
     #[validate(email)]
     pub email: String,
@@ -34,32 +41,66 @@ pub struct CreateUserRequest {
     #[validate(length(min = 2, message = "Full name must be at least 2 characters"))]
     pub full_name: String,
     pub role: Option<UserRole>,
+    /// Organization to join; defaults to the default organization
+    pub org_id: Option<Uuid>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct LoginRequest {
     #[validate(email)]
     pub email: String,
     pub password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserResponse {
     pub id: Uuid,
     pub email: String,
     pub full_name: String,
     pub role: UserRole,
     pub is_verified: bool,
+    pub org_id: Uuid,
+    pub email_notifications: bool,
+    pub locale: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NotificationPreferencesRequest {
+    pub email_notifications: bool,
+    /// Language of notification emails, e.g. `es`; left unchanged when omitted
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
     pub token: String,
     pub user: UserResponse,
     pub expires_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct IntrospectRequest {
+    pub token: String,
+}
+
+/// RFC 7662-style token status; only `active` is set for unusable tokens
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct IntrospectionResponse {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<UserRole>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    /// Admin acting as `sub` when the token is an impersonation token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub act: Option<String>,
+}
+
 impl From<User> for UserResponse {
     fn from(user: User) -> Self {
         Self {
@@ -68,6 +109,9 @@ impl From<User> for UserResponse {
             full_name: user.full_name,
             role: user.role,
             is_verified: user.is_verified,
+            org_id: user.org_id,
+            email_notifications: user.email_notifications,
+            locale: user.locale,
             created_at: user.created_at,
         }
     }
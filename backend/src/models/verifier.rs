@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Verifier {
+    pub id: Uuid,
+    pub name: String,
+    pub token_hash: String,
+    pub created_by: Option<Uuid>,
+    pub is_active: bool,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateVerifierRequest {
+    #[validate(length(min = 1, max = 255, message = "Verifier name is required"))]
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VerifierResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub is_active: bool,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub token: Option<String>, // Only returned when registering a new verifier
+}
+
+impl From<Verifier> for VerifierResponse {
+    fn from(verifier: Verifier) -> Self {
+        Self {
+            id: verifier.id,
+            name: verifier.name,
+            is_active: verifier.is_active,
+            last_used_at: verifier.last_used_at,
+            created_at: verifier.created_at,
+            token: None,
+        }
+    }
+}
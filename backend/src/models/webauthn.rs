@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use utoipa::ToSchema;
+use validator::Validate;
+use webauthn_rs::prelude::{CreationChallengeResponse, PublicKeyCredential, RegisterPublicKeyCredential, RequestChallengeResponse};
+
+#[derive(Debug, Clone, FromRow)]
+pub struct WebauthnCredential {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub credential_id: Vec<u8>,
+    pub passkey: serde_json::Value,
+    pub name: Option<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StartPasskeyRegistrationResponse {
+    /// Options to pass to `navigator.credentials.create()`
+    #[schema(value_type = Object)]
+    pub options: CreationChallengeResponse,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct FinishPasskeyRegistrationRequest {
+    /// Label to tell the user's passkeys apart, e.g. "Laptop"
+    #[validate(length(max = 100, message = "Name must be at most 100 characters"))]
+    pub name: Option<String>,
+    /// `PublicKeyCredential` returned by `navigator.credentials.create()`
+    #[schema(value_type = Object)]
+    pub credential: RegisterPublicKeyCredential,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct StartPasskeyLoginRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StartPasskeyLoginResponse {
+    /// Identifies this login attempt when finishing it
+    pub ceremony_id: Uuid,
+    /// Options to pass to `navigator.credentials.get()`
+    #[schema(value_type = Object)]
+    pub options: RequestChallengeResponse,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FinishPasskeyLoginRequest {
+    pub ceremony_id: Uuid,
+    /// `PublicKeyCredential` returned by `navigator.credentials.get()`
+    #[schema(value_type = Object)]
+    pub credential: PublicKeyCredential,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebauthnCredentialResponse {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<WebauthnCredential> for WebauthnCredentialResponse {
+    fn from(credential: WebauthnCredential) -> Self {
+        Self {
+            id: credential.id,
+            name: credential.name,
+            last_used_at: credential.last_used_at,
+            created_at: credential.created_at,
+        }
+    }
+}
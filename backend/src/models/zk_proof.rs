@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -15,23 +16,91 @@ pub struct ZkProof {
     pub expires_at: Option<DateTime<Utc>>,
     pub usage_count: i32,
     pub max_usage: Option<i32>,
+    pub timestamp_token: Option<Vec<u8>>, // DER RFC 3161 TimeStampToken over proof_hash
+    pub timestamped_at: Option<DateTime<Utc>>,
+    pub anchor_tx_hash: Option<String>, // Transaction that submitted the proof on-chain
+    pub proof_cid: Option<String>, // IPFS CID of proof_data, pinned when anchoring
+    pub suspended_at: Option<DateTime<Utc>>, // Set when anomaly detection suspends the proof
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
 pub enum ProofType {
+    /// The record carries a valid authority signature
     EcdsaSignatureVerification,
+    /// The record is signed and has not yet expired
+    NotExpiredProof,
+    /// The record is signed and is of the disclosed record type
+    RecordTypeProof,
+    /// The record is signed by the disclosed (trusted) authority key
+    AuthorityTrustedProof,
+    /// Several records are all validly signed and non-revoked
+    AggregateSignatureVerification,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+impl Default for ProofType {
+    fn default() -> Self {
+        ProofType::EcdsaSignatureVerification
+    }
+}
+
+/// Whether a proof can still be presented. When several conditions hold the
+/// first one applies: `revoked`, then `expired`, then `used_up`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ProofStatus {
+    /// Can still be verified
+    Active,
+    /// Past its `expires_at`
+    Expired,
+    /// A health record it covers has been revoked
+    Revoked,
+    /// `usage_count` has reached `max_usage` (including proofs revoked by their holder)
+    UsedUp,
+}
+
+impl ProofStatus {
+    /// SQL expression deriving the status of `zp` joined to its primary record `hr`
+    pub const SQL: &'static str = r#"
+        CASE
+            WHEN hr.is_revoked OR EXISTS (
+                SELECT 1 FROM zk_proof_records zpr
+                JOIN health_records chr ON chr.id = zpr.health_record_id
+                WHERE zpr.proof_id = zp.id AND chr.is_revoked
+            ) THEN 'revoked'
+            WHEN zp.expires_at IS NOT NULL AND zp.expires_at <= NOW() THEN 'expired'
+            WHEN zp.max_usage IS NOT NULL AND zp.usage_count >= zp.max_usage THEN 'used_up'
+            ELSE 'active'
+        END
+    "#;
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct GenerateProofRequest {
     pub health_record_id: Uuid,
-    pub expires_in_hours: Option<u32>, // Optional expiration
-    pub max_usage: Option<i32>,        // Optional usage limit
+    #[serde(default)]
+    pub health_record_ids: Vec<Uuid>, // Additional records to aggregate into one proof
+    pub proof_type: Option<ProofType>, // Defaults to EcdsaSignatureVerification
+    pub expires_in_hours: Option<u32>, // Defaults to DEFAULT_PROOF_EXPIRATION_HOURS
+    pub max_usage: Option<i32>,        // Defaults to, and may not exceed, MAX_PROOF_USAGE
     pub proof_context: Option<serde_json::Value>, // Additional context
 }
 
-#[derive(Debug, Serialize)]
+impl GenerateProofRequest {
+    /// All records covered by the proof, primary record first, without duplicates
+    pub fn record_ids(&self) -> Vec<Uuid> {
+        let mut ids = vec![self.health_record_id];
+        for id in &self.health_record_ids {
+            if !ids.contains(id) {
+                ids.push(*id);
+            }
+        }
+        ids
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct ProofResponse {
     pub id: Uuid,
     pub proof_data: String, // Base64 encoded proof
@@ -42,51 +111,450 @@ pub struct ProofResponse {
     pub usage_count: i32,
     pub max_usage: Option<i32>,
     pub health_record_type: String,
+    pub status: ProofStatus,
+    /// Base64 DER RFC 3161 timestamp token over the SHA-256 of the proof data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp_token: Option<String>,
+    /// Time the timestamp authority attests the proof existed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamped_at: Option<DateTime<Utc>>,
+    /// Transaction that anchored the proof hash on-chain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_tx_hash: Option<String>,
+    /// IPFS CID the proof data was pinned under when anchoring
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof_cid: Option<String>,
+}
+
+/// How far a background proof generation has got. Stages only move forward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ProofJobStage {
+    /// Accepted and waiting to start
+    Queued,
+    /// The circuit inputs were built from the health records
+    WitnessGenerated,
+    /// The proof backend is running
+    Proving,
+    /// The proof was stored; `result` holds it
+    Done,
+    /// Generation failed; `error_code` and `error_message` say why
+    Failed,
+}
+
+impl ProofJobStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProofJobStage::Queued => "queued",
+            ProofJobStage::WitnessGenerated => "witness_generated",
+            ProofJobStage::Proving => "proving",
+            ProofJobStage::Done => "done",
+            ProofJobStage::Failed => "failed",
+        }
+    }
+
+    pub fn is_final(&self) -> bool {
+        matches!(self, ProofJobStage::Done | ProofJobStage::Failed)
+    }
+}
+
+/// A proof generated in the background, started with `POST /proofs/jobs`
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ProofJob {
+    pub id: Uuid,
+    #[serde(skip)]
+    pub user_id: Uuid,
+    pub stage: ProofJobStage,
+    /// The generated `ProofResponse`, once done
+    #[schema(value_type = Option<Object>)]
+    pub result: Option<serde_json::Value>,
+    /// Error code, as in REST error responses, once failed
+    pub error_code: Option<String>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Orders `GET /proofs` can be listed in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofSort {
+    #[default]
+    GeneratedAtDesc,
+    GeneratedAtAsc,
+    /// Soonest to expire first; proofs that never expire last
+    ExpiresAtAsc,
+    /// By the primary record's type, newest first within a type
+    RecordType,
+}
+
+impl ProofSort {
+    /// `ORDER BY` clause over the `zp` subquery of `get_user_proofs`
+    pub fn order_by(self) -> &'static str {
+        match self {
+            ProofSort::GeneratedAtDesc => "zp.generated_at DESC, zp.id DESC",
+            ProofSort::GeneratedAtAsc => "zp.generated_at ASC, zp.id ASC",
+            ProofSort::ExpiresAtAsc => "zp.expires_at ASC NULLS LAST, zp.id ASC",
+            ProofSort::RecordType => "zp.record_type ASC, zp.generated_at DESC, zp.id DESC",
+        }
+    }
+}
+
+/// A proof row together with its derived `ProofStatus`
+#[derive(Debug, FromRow)]
+pub struct ZkProofWithStatus {
+    #[sqlx(flatten)]
+    pub proof: ZkProof,
+    pub status: ProofStatus,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct VerifyProofRequest {
-    #[validate(length(min = 1, message = "Proof data is required"))]
-    pub proof_data: String, // Base64 encoded proof
-    #[validate(length(min = 1, message = "Verification key is required"))]
-    pub verification_key: String, // Base64 encoded verification key
+    /// Base64 encoded proof; may be omitted when `proof_cid` is given
+    #[serde(default)]
+    pub proof_data: String,
+    /// IPFS CID of an anchored proof, fetched when `proof_data` is omitted
+    pub proof_cid: Option<String>,
+    /// Base64 encoded verification key; may be omitted with `delegation_token`
+    #[serde(default)]
+    pub verification_key: String,
     pub proof_type: ProofType,
     pub verification_context: Option<serde_json::Value>,
+    pub predicate: Option<serde_json::Value>, // Verifier requirements, see services::predicate
+    /// Require a trusted timestamp showing the proof existed at or before this time
+    pub existed_before: Option<DateTime<Utc>>,
+    /// Holder's offset from UTC in minutes, e.g. `-300` for UTC-5. Records
+    /// expire at the end of their expiry date in this zone; UTC when omitted.
+    #[validate(range(min = -840, max = 840, message = "UTC offset must be between -840 and 840 minutes"))]
+    pub client_utc_offset_minutes: Option<i32>,
+    /// hCaptcha or Turnstile response token, required by `/proofs/public/verify`
+    /// without a verifier token when a CAPTCHA is configured
+    pub captcha_token: Option<String>,
+    /// Token from the proof's owner letting a delegate present the proof
+    /// without holding its data or verification key
+    pub delegation_token: Option<String>,
+    /// Client-chosen value telling deliberate re-verifications apart from
+    /// retries; identical requests within the dedup window count once
+    #[validate(length(max = 128, message = "Nonce must be at most 128 characters"))]
+    pub nonce: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct VerificationResponse {
     pub is_valid: bool,
     pub proof_id: Option<Uuid>,
     pub verified_at: DateTime<Utc>,
     pub verification_details: VerificationDetails,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct VerificationDetails {
     pub health_record_type: Option<String>,
     pub issue_date: Option<String>,
     pub authority_name: Option<String>,
+    pub authority_type: Option<String>,
     pub is_expired: bool,
     pub usage_exceeded: bool,
     pub revocation_status: RevocationStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<DateTime<Utc>>, // When the record, or first revoked constituent, was revoked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_reason: Option<String>, // Why it was revoked, as given by the revoker
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constituent_record_types: Vec<String>, // Populated for aggregate proofs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub co_signing_authorities: Vec<String>, // Authorities that co-signed the record, in order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamped_at: Option<DateTime<Utc>>, // From the proof's trusted timestamp, if it has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchored_on_chain: Option<bool>, // On-chain cross-check of an anchored proof, when the chain was reachable
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub enum RevocationStatus {
     Valid,
     Revoked,
     Unknown,
 }
 
-#[derive(Debug, Serialize, FromRow)]
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
 pub struct ProofVerification {
     pub id: Uuid,
     pub proof_id: Uuid,
     pub verifier_id: Option<Uuid>,
+    /// Registered verifier that used the public endpoint, if any
+    pub registered_verifier_id: Option<Uuid>,
+    pub verifier_name: Option<String>,
     pub verification_result: bool,
     pub verification_context: Option<serde_json::Value>,
     pub verified_at: DateTime<Utc>,
+    #[schema(value_type = Option<String>)]
     pub ip_address: Option<std::net::IpAddr>,
     pub user_agent: Option<String>,
+    /// ISO 3166-1 alpha-2 country of `ip_address`, when GeoIP lookup is enabled
+    pub country_code: Option<String>,
+    /// Region (state, province) of `ip_address`, when GeoIP lookup is enabled
+    pub region: Option<String>,
+    /// Delegation the proof was presented under, if any
+    pub delegation_id: Option<Uuid>,
+    pub delegate_email: Option<String>,
+}
+
+/// A verification as it is logged, with the owner and organization of the
+/// verified proof's record so live feeds can tell who may see it
+#[derive(Debug, Clone, FromRow)]
+pub struct VerificationEvent {
+    #[sqlx(flatten)]
+    pub verification: ProofVerification,
+    pub owner_id: Uuid,
+    pub org_id: Uuid,
+}
+
+impl VerificationEvent {
+    /// The verification as a feed subscriber may see it, or `None` when it is
+    /// outside their scope. Subscribers confined to their own proofs
+    /// (`scope_user_id`) don't get IP addresses and user agents, as in the history.
+    pub fn visible_to(&self, scope_user_id: Option<Uuid>, org_scope: Option<Uuid>) -> Option<ProofVerification> {
+        if scope_user_id.map_or(false, |user_id| user_id != self.owner_id)
+            || org_scope.map_or(false, |org_id| org_id != self.org_id)
+        {
+            return None;
+        }
+
+        let mut verification = self.verification.clone();
+        if scope_user_id.is_some() {
+            verification.ip_address = None;
+            verification.user_agent = None;
+        }
+        Some(verification)
+    }
+}
+
+/// Messages of the live verification feed, as JSON text frames
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VerificationFeedMessage {
+    Verification { verification: ProofVerification },
+    /// The subscriber fell behind and missed `skipped` verifications
+    Lagged { skipped: u64 },
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct VerificationStatsQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Link that adds a proof to Google Wallet
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GoogleWalletLink {
+    pub save_url: String,
+}
+
+/// Filters of the audit export of the verification log
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct VerificationExportQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Only successful (`true`) or failed (`false`) verifications
+    pub result: Option<bool>,
+    /// Only verifications of proofs over records issued by this authority
+    pub authority_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VerificationStats {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub total_verifications: i64,
+    pub successful_verifications: i64,
+    pub failed_verifications: i64,
+    pub success_rate: f64,
+    pub by_authority: Vec<VerificationStatsBucket>,
+    pub by_record_type: Vec<VerificationStatsBucket>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VerificationStatsBucket {
+    pub key: String,
+    pub total_verifications: i64,
+    pub successful_verifications: i64,
+    pub success_rate: f64,
+}
+
+impl VerificationStatsBucket {
+    pub fn new(key: String, total_verifications: i64, successful_verifications: i64) -> Self {
+        Self {
+            key,
+            total_verifications,
+            successful_verifications,
+            success_rate: success_rate(total_verifications, successful_verifications),
+        }
+    }
+}
+
+/// Fraction of successful verifications, 0.0 when there were none
+pub fn success_rate(total: i64, successful: i64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        successful as f64 / total as f64
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RevocationListQuery {
+    /// Only list proofs revoked after this time, for incremental updates
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// One revoked proof in the revocation list
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RevokedProof {
+    pub proof_id: Uuid,
+    pub proof_hash: String, // Hex SHA-256 of the proof data
+    pub revoked_at: DateTime<Utc>,
+}
+
+/// Content of the revocation list covered by its signature
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RevocationListPayload {
+    pub issued_at: DateTime<Utc>,
+    /// Set for an incremental list holding only revocations after this time
+    pub since: Option<DateTime<Utc>>,
+    pub entries: Vec<RevokedProof>,
+}
+
+/// Revocation list for offline verifiers, signed with the server's CRL key
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SignedRevocationList {
+    /// Base64 of the JSON `RevocationListPayload`, exactly as signed
+    pub payload: String,
+    /// Hex compact (r || s) secp256k1 ECDSA signature over the SHA-256 of the decoded payload
+    pub signature: String,
+    /// Hex compressed secp256k1 public key to check `signature` against
+    pub public_key: String,
+}
+
+/// Status reported by `GET /proofs/{id}/status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveProofStatus {
+    Valid,
+    Revoked,
+    Expired,
+    UsedUp,
+}
+
+impl LiveProofStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LiveProofStatus::Valid => "valid",
+            LiveProofStatus::Revoked => "revoked",
+            LiveProofStatus::Expired => "expired",
+            LiveProofStatus::UsedUp => "used_up",
+        }
+    }
+}
+
+impl From<ProofStatus> for LiveProofStatus {
+    fn from(status: ProofStatus) -> Self {
+        match status {
+            ProofStatus::Active => LiveProofStatus::Valid,
+            ProofStatus::Revoked => LiveProofStatus::Revoked,
+            ProofStatus::Expired => LiveProofStatus::Expired,
+            ProofStatus::UsedUp => LiveProofStatus::UsedUp,
+        }
+    }
+}
+
+/// Signed live status of one proof
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProofStatusResponse {
+    pub proof_id: Uuid,
+    pub status: LiveProofStatus,
+    /// Whole seconds, so the signed message can be rebuilt from the response
+    pub checked_at: DateTime<Utc>,
+    /// Hex compact (r || s) secp256k1 ECDSA signature over the SHA-256 of
+    /// `{proof_id}:{status}:{checked_at}`, e.g. `…:valid:2025-01-01T00:00:00Z`
+    pub signature: String,
+    /// Hex compressed public key of the server's signing key, as on the revocation list
+    pub public_key: String,
+}
+
+impl ProofStatusResponse {
+    /// Message the response signature covers
+    pub fn signed_message(proof_id: Uuid, status: LiveProofStatus, checked_at: DateTime<Utc>) -> String {
+        format!(
+            "{}:{}:{}",
+            proof_id,
+            status.as_str(),
+            checked_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        )
+    }
+}
+
+/// A proof verified from unusually many locations within a short window
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct ProofAlert {
+    pub id: Uuid,
+    pub proof_id: Uuid,
+    /// Distinct IP addresses or GeoIP regions seen within the window
+    pub distinct_locations: i32,
+    pub window_secs: i32,
+    /// Whether the proof was suspended, failing verification until the alert is resolved
+    pub suspended: bool,
+    pub detected_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub resolved_by: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ProofDelegation {
+    pub id: Uuid,
+    pub proof_id: Uuid,
+    pub owner_id: Uuid,
+    pub delegate_email: String,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateDelegationRequest {
+    /// Person who will present the proof
+    #[validate(email)]
+    pub delegate_email: String,
+    /// When the delegation stops working; no later than the proof's own expiry
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProofDelegationResponse {
+    pub id: Uuid,
+    pub proof_id: Uuid,
+    pub delegate_email: String,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>, // Only returned when the delegation is created
+}
+
+impl From<ProofDelegation> for ProofDelegationResponse {
+    fn from(delegation: ProofDelegation) -> Self {
+        Self {
+            id: delegation.id,
+            proof_id: delegation.proof_id,
+            delegate_email: delegation.delegate_email,
+            expires_at: delegation.expires_at,
+            revoked_at: delegation.revoked_at,
+            created_at: delegation.created_at,
+            token: None,
+        }
+    }
 }
@@ -0,0 +1,31 @@
+use axum::{extract::State, routing::get, Json, Router};
+
+use crate::{
+    errors::AppError,
+    middleware::permissions::{can, RequirePermission},
+    models::DashboardStats,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/stats", get(get_dashboard_stats))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/stats",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "User, authority, record, proof and verification counts of the admin's organization (every organization for superadmins), cached for up to 30 seconds", body = DashboardStats),
+        (status = 403, description = "Admin access required"),
+    )
+)]
+async fn get_dashboard_stats(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ViewDashboardStats>,
+) -> Result<Json<DashboardStats>, AppError> {
+    let stats = state.dashboard_service.stats(auth_user.org_scope()).await?;
+
+    Ok(Json(stats))
+}
@@ -1,15 +1,24 @@
 use axum::{
-    extract::State,
-    http::StatusCode,
-    routing::{post, get, put},
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, post, get, put},
     Json, Router,
 };
+use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
     errors::{AppError, validation_error},
-    models::{CreateUserRequest, LoginRequest, UserResponse},
+    i18n::Locale,
+    models::{
+        AcceptInviteRequest, CreateInviteRequest, CreateUserRequest, InviteResponse, LoginRequest,
+        LoginResponse, NotificationPreferencesRequest, UserResponse, IntrospectRequest, IntrospectionResponse,
+        StartPasskeyRegistrationResponse, FinishPasskeyRegistrationRequest, StartPasskeyLoginRequest,
+        StartPasskeyLoginResponse, FinishPasskeyLoginRequest, WebauthnCredentialResponse,
+    },
     middleware::auth::AuthUser,
+    middleware::permissions::{can, RequirePermission},
+    services::verifier::VERIFIER_TOKEN_HEADER,
     AppState,
 };
 
@@ -18,10 +27,32 @@ pub fn routes() -> Router<AppState> {
         .route("/register", post(register))
         .route("/login", post(login))
         .route("/me", get(get_current_user))
+        .route("/me/notifications", put(update_notification_preferences))
         .route("/change-password", put(change_password))
         .route("/verify", post(verify_user))
+        .route("/invites", post(invite_provider))
+        .route("/invites/accept", post(accept_invite))
+        .route("/introspect", post(introspect))
+        .route("/impersonate/:user_id", post(impersonate))
+        .route("/webauthn/register/start", post(start_passkey_registration))
+        .route("/webauthn/register/finish", post(finish_passkey_registration))
+        .route("/webauthn/login/start", post(start_passkey_login))
+        .route("/webauthn/login/finish", post(finish_passkey_login))
+        .route("/webauthn/credentials", get(list_passkeys))
+        .route("/webauthn/credentials/:id", delete(delete_passkey))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/register",
+    tag = "auth",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 201, description = "User registered", body = UserResponse),
+        (status = 404, description = "Organization not found"),
+        (status = 409, description = "Email already registered"),
+    )
+)]
 async fn register(
     State(state): State<AppState>,
     Json(request): Json<CreateUserRequest>,
@@ -34,10 +65,20 @@ async fn register(
     Ok((StatusCode::CREATED, Json(user)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Login succeeded", body = LoginResponse),
+        (status = 401, description = "Invalid credentials"),
+    )
+)]
 async fn login(
     State(state): State<AppState>,
     Json(request): Json<LoginRequest>,
-) -> Result<Json<crate::models::LoginResponse>, AppError> {
+) -> Result<Json<LoginResponse>, AppError> {
     // Validate request
     request.validate().map_err(validation_error)?;
 
@@ -46,20 +87,250 @@ async fn login(
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/introspect",
+    tag = "auth",
+    request_body = IntrospectRequest,
+    params(("X-Verifier-Token" = String, Header, description = "Registered verifier token of the calling resource server")),
+    responses(
+        (status = 200, description = "Token status; `{\"active\": false}` for invalid, expired or orphaned tokens", body = IntrospectionResponse),
+        (status = 401, description = "Missing verifier token"),
+        (status = 403, description = "Unregistered verifier token"),
+    )
+)]
+async fn introspect(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<IntrospectRequest>,
+) -> Result<Json<IntrospectionResponse>, AppError> {
+    // Resource servers authenticate as registered verifiers, even in anonymous mode
+    let client_token = headers
+        .get(VERIFIER_TOKEN_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .ok_or_else(|| AppError::Unauthorized("A registered verifier token is required".to_string()))?;
+    state.verifier_service.authenticate(Some(client_token)).await?;
+
+    let response = state.auth_service.introspect(&request.token).await?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/impersonate/{user_id}",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    params(("user_id" = Uuid, Path, description = "User to act as")),
+    responses(
+        (status = 200, description = "Short-lived, read-only token for the user, naming the admin in its `act` claim", body = LoginResponse),
+        (status = 403, description = "Admin access required, or the target is an admin"),
+        (status = 404, description = "User not found in the caller's organization"),
+    )
+)]
+async fn impersonate(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ImpersonateUsers>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<LoginResponse>, AppError> {
+    let response = state
+        .auth_service
+        .impersonate(auth_user.user.id, user_id, auth_user.org_scope())
+        .await?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/webauthn/register/start",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Credential creation options for the browser", body = StartPasskeyRegistrationResponse),
+        (status = 401, description = "Missing or invalid token"),
+    )
+)]
+async fn start_passkey_registration(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<StartPasskeyRegistrationResponse>, AppError> {
+    let options = state.passkey_service.start_registration(auth_user.user.id).await?;
+
+    Ok(Json(StartPasskeyRegistrationResponse { options }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/webauthn/register/finish",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    request_body = FinishPasskeyRegistrationRequest,
+    responses(
+        (status = 201, description = "Passkey registered", body = WebauthnCredentialResponse),
+        (status = 400, description = "No registration in progress, or the attestation was rejected"),
+        (status = 409, description = "Passkey is already registered"),
+    )
+)]
+async fn finish_passkey_registration(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(request): Json<FinishPasskeyRegistrationRequest>,
+) -> Result<(StatusCode, Json<WebauthnCredentialResponse>), AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let credential = state.passkey_service.finish_registration(auth_user.user.id, request).await?;
+
+    Ok((StatusCode::CREATED, Json(credential)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/webauthn/login/start",
+    tag = "auth",
+    request_body = StartPasskeyLoginRequest,
+    responses(
+        (status = 200, description = "Credential request options for the browser", body = StartPasskeyLoginResponse),
+        (status = 401, description = "No passkey login possible for this account"),
+    )
+)]
+async fn start_passkey_login(
+    State(state): State<AppState>,
+    Json(request): Json<StartPasskeyLoginRequest>,
+) -> Result<Json<StartPasskeyLoginResponse>, AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let (ceremony_id, options) = state.passkey_service.start_login(&request.email).await?;
+
+    Ok(Json(StartPasskeyLoginResponse { ceremony_id, options }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/webauthn/login/finish",
+    tag = "auth",
+    request_body = FinishPasskeyLoginRequest,
+    responses(
+        (status = 200, description = "Login succeeded", body = LoginResponse),
+        (status = 401, description = "Assertion rejected, or the login expired or was already used"),
+    )
+)]
+async fn finish_passkey_login(
+    State(state): State<AppState>,
+    Json(request): Json<FinishPasskeyLoginRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    let response = state.passkey_service.finish_login(request).await?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/webauthn/credentials",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The caller's passkeys", body = [WebauthnCredentialResponse]),
+        (status = 401, description = "Missing or invalid token"),
+    )
+)]
+async fn list_passkeys(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<WebauthnCredentialResponse>>, AppError> {
+    let credentials = state.passkey_service.list_credentials(auth_user.user.id).await?;
+
+    Ok(Json(credentials))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/auth/webauthn/credentials/{id}",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Passkey ID")),
+    responses(
+        (status = 204, description = "Passkey deleted"),
+        (status = 404, description = "Passkey not found"),
+    )
+)]
+async fn delete_passkey(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(credential_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state.passkey_service.delete_credential(auth_user.user.id, credential_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/me",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Current user", body = UserResponse),
+        (status = 401, description = "Missing or invalid token"),
+    )
+)]
 async fn get_current_user(
     auth_user: AuthUser,
 ) -> Result<Json<UserResponse>, AppError> {
     Ok(Json(auth_user.user.into()))
 }
 
-#[derive(serde::Deserialize, validator::Validate)]
-struct ChangePasswordRequest {
+#[utoipa::path(
+    put,
+    path = "/api/v1/auth/me/notifications",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    request_body = NotificationPreferencesRequest,
+    responses(
+        (status = 200, description = "Notification preferences updated", body = UserResponse),
+        (status = 400, description = "Unsupported locale"),
+    )
+)]
+async fn update_notification_preferences(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(request): Json<NotificationPreferencesRequest>,
+) -> Result<Json<UserResponse>, AppError> {
+    let locale = request
+        .locale
+        .as_deref()
+        .map(|tag| Locale::from_tag(tag).ok_or_else(|| AppError::BadRequest(format!("Unsupported locale {}", tag))))
+        .transpose()?;
+
+    let mut user = state.auth_service
+        .set_email_notifications(auth_user.user.id, request.email_notifications)
+        .await?;
+    if let Some(locale) = locale {
+        user = state.auth_service.set_locale(auth_user.user.id, locale.code()).await?;
+    }
+
+    Ok(Json(user.into()))
+}
+
+#[derive(serde::Deserialize, validator::Validate, utoipa::ToSchema)]
+pub(crate) struct ChangePasswordRequest {
     #[validate(length(min = 8, message = "Current password is required"))]
     current_password: String,
     #[validate(length(min = 8, message = "New password must be at least 8 characters"))]
     new_password: String,
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/auth/change-password",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 204, description = "Password changed"),
+        (status = 401, description = "Current password is incorrect"),
+    )
+)]
 async fn change_password(
     State(state): State<AppState>,
     auth_user: AuthUser,
@@ -77,23 +348,83 @@ async fn change_password(
     Ok(StatusCode::NO_CONTENT)
 }
 
-#[derive(serde::Deserialize)]
-struct VerifyUserRequest {
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub(crate) struct VerifyUserRequest {
     user_id: uuid::Uuid,
     is_verified: bool,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/verify",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    request_body = VerifyUserRequest,
+    responses(
+        (status = 204, description = "Verification status updated"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "User not found in the caller's organization"),
+    )
+)]
 async fn verify_user(
     State(state): State<AppState>,
-    auth_user: AuthUser,
+    auth_user: RequirePermission<can::VerifyUsers>,
     Json(request): Json<VerifyUserRequest>,
 ) -> Result<StatusCode, AppError> {
-    // Only admins can verify users
-    if !matches!(auth_user.user.role, crate::models::UserRole::Admin) {
-        return Err(AppError::Forbidden("Admin access required".to_string()));
-    }
-
-    state.auth_service.update_user_verification(request.user_id, request.is_verified).await?;
+    state.auth_service
+        .update_user_verification(request.user_id, request.is_verified, auth_user.org_scope())
+        .await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/invites",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    request_body = CreateInviteRequest,
+    responses(
+        (status = 201, description = "Invite created; the token is only returned here", body = InviteResponse),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Authority not found in the caller's organization"),
+        (status = 409, description = "Email already registered"),
+    )
+)]
+async fn invite_provider(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::InviteProviders>,
+    Json(request): Json<CreateInviteRequest>,
+) -> Result<(StatusCode, Json<InviteResponse>), AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let invite = state.auth_service
+        .invite_provider(&request.email, request.authority_id, auth_user.user.id, auth_user.org_scope())
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(invite)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/invites/accept",
+    tag = "auth",
+    request_body = AcceptInviteRequest,
+    responses(
+        (status = 201, description = "Provider account created and associated with the invite's authority", body = UserResponse),
+        (status = 404, description = "Invite not found, already used or expired"),
+        (status = 409, description = "Email already registered"),
+    )
+)]
+async fn accept_invite(
+    State(state): State<AppState>,
+    Json(request): Json<AcceptInviteRequest>,
+) -> Result<(StatusCode, Json<UserResponse>), AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let user = state.auth_service
+        .accept_invite(&request.token, &request.password, &request.full_name)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(user)))
+}
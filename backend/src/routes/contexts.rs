@@ -0,0 +1,46 @@
+use axum::{
+    extract::Path,
+    http::header,
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+
+use crate::{errors::AppError, AppState};
+
+/// Published versions of the health record JSON-LD context. A published
+/// document must never change, since issued credentials keep pointing at it;
+/// new terms go into a new version.
+const HEALTH_RECORD_CONTEXTS: &[(&str, &str)] = &[
+    ("v1", include_str!("../../assets/contexts/health-record-v1.jsonld")),
+];
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/health-record/:version", get(get_health_record_context))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/contexts/health-record/{version}",
+    tag = "health-records",
+    params(("version" = String, Path, description = "Context version, e.g. v1")),
+    responses(
+        (status = 200, description = "JSON-LD context defining the health record credential terms", content_type = "application/ld+json", body = Object),
+        (status = 404, description = "Unknown context version"),
+    )
+)]
+pub async fn get_health_record_context(Path(version): Path<String>) -> Result<impl IntoResponse, AppError> {
+    let (_, context) = HEALTH_RECORD_CONTEXTS
+        .iter()
+        .find(|(published, _)| *published == version)
+        .ok_or_else(|| AppError::NotFound("Context version not found".to_string()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/ld+json"),
+            // Safe to cache forever: published versions are immutable
+            (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+        ],
+        *context,
+    ))
+}
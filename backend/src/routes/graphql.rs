@@ -0,0 +1,28 @@
+use axum::{
+    extract::State,
+    routing::post,
+    Extension, Json, Router,
+};
+
+use crate::{
+    graphql::{self, ApiSchema},
+    middleware::auth::AuthUser,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(execute_query))
+        .layer(Extension(graphql::schema()))
+}
+
+/// Run a read-only GraphQL query as the authenticated user. Errors in the
+/// query are reported in the response's `errors`, with status 200.
+async fn execute_query(
+    State(state): State<AppState>,
+    Extension(schema): Extension<ApiSchema>,
+    auth_user: AuthUser,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(graphql::execute(&schema, &state, auth_user, request).await)
+}
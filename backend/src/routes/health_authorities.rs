@@ -1,7 +1,7 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
     Json, Router,
 };
 use uuid::Uuid;
@@ -9,8 +9,14 @@ use validator::Validate;
 
 use crate::{
     errors::{AppError, validation_error},
-    models::{CreateAuthorityRequest, UpdateAuthorityRequest, AuthorityResponse, AuthorityQuery, UserRole},
-    middleware::auth::AuthUser,
+    models::{
+        AssignProviderRequest, CreateAuthorityRequest, UpdateAuthorityRequest, AuthorityResponse, AuthorityQuery,
+        ProviderAuthority, UserRole, CreateTemplateRequest, RecordTemplate, SigningCurve, DidDocument,
+        AuthorityImportQuery, AuthorityImportResponse, AuthorityStats, AuthorityStatsQuery,
+    },
+    middleware::auth::{AuthUser, OptionalAuthUser},
+    middleware::permissions::{can, RequirePermission},
+    services::{health_authority::normalize_public_key, HealthRecordService},
     AppState,
 };
 
@@ -18,50 +24,131 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", post(create_authority))
         .route("/", get(get_authorities))
+        .route("/import", post(import_authorities))
         .route("/:id", get(get_authority))
         .route("/:id", put(update_authority))
+        .route("/:id/stats", get(get_authority_stats))
+        .route("/:id/providers", get(get_authority_providers))
+        .route("/:id/providers", post(assign_provider))
+        .route("/:id/providers/:user_id", delete(unassign_provider))
+        .route("/:id/templates", post(create_template))
+        .route("/:id/templates", get(get_templates))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/authorities",
+    tag = "authorities",
+    security(("bearer_auth" = [])),
+    request_body = CreateAuthorityRequest,
+    responses(
+        (status = 201, description = "Authority created", body = AuthorityResponse),
+        (status = 400, description = "Public key is not a valid point on the requested curve"),
+        (status = 403, description = "Admin access required, or another organization requested by a non-superadmin"),
+    )
+)]
 async fn create_authority(
     State(state): State<AppState>,
-    auth_user: AuthUser,
+    auth_user: RequirePermission<can::ManageAuthorities>,
     Json(request): Json<CreateAuthorityRequest>,
 ) -> Result<(StatusCode, Json<AuthorityResponse>), AppError> {
-    // Only admins can create health authorities
-    if !matches!(auth_user.user.role, UserRole::Admin) {
-        return Err(AppError::Forbidden("Admin access required".to_string()));
-    }
-
     // Validate request
     request.validate().map_err(validation_error)?;
 
-    // Validate the public key format
-    let _public_key = state.crypto_service.parse_public_key(&request.public_key)?;
+    // Validate the public key format, accepting PEM as well as hex
+    let public_key = normalize_public_key(&state.crypto_service, request.curve, &request.public_key)?;
+
+    let org_id = target_org(&auth_user, request.org_id)?;
 
     let db = &state.auth_service.db;
 
     let authority = sqlx::query_as::<_, crate::models::HealthAuthority>(
         r#"
-        INSERT INTO health_authorities (name, authority_type, public_key, certificate)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO health_authorities (name, authority_type, public_key, certificate, org_id, curve)
+        VALUES ($1, $2, $3, $4, $5, $6)
         RETURNING *
         "#
     )
     .bind(&request.name)
     .bind(&request.authority_type)
-    .bind(&request.public_key)
+    .bind(&public_key)
     .bind(&request.certificate)
+    .bind(org_id)
+    .bind(request.curve)
     .fetch_one(db)
     .await?;
 
     Ok((StatusCode::CREATED, Json(authority.into())))
 }
 
+/// Admins create authorities in their own organization; superadmins may pick one
+fn target_org(auth_user: &AuthUser, requested: Option<Uuid>) -> Result<Uuid, AppError> {
+    match (auth_user.org_scope(), requested) {
+        (None, Some(org_id)) => Ok(org_id),
+        (Some(own_org_id), Some(org_id)) if org_id != own_org_id => {
+            Err(AppError::Forbidden("Cannot create authorities in another organization".to_string()))
+        }
+        _ => Ok(auth_user.user.org_id),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/authorities/import",
+    tag = "authorities",
+    security(("bearer_auth" = [])),
+    params(AuthorityImportQuery),
+    request_body(content = String, content_type = "text/csv", description = "Header `name,authority_type,public_key,certificate`, then one secp256k1 authority per row"),
+    responses(
+        (status = 200, description = "Result of every row; nothing is created when a row is invalid and `partial` is not set", body = AuthorityImportResponse),
+        (status = 400, description = "Not a CSV with the expected header, or too many rows"),
+        (status = 403, description = "Admin access required, or another organization requested by a non-superadmin"),
+    )
+)]
+async fn import_authorities(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ManageAuthorities>,
+    Query(query): Query<AuthorityImportQuery>,
+    csv: String,
+) -> Result<Json<AuthorityImportResponse>, AppError> {
+    let org_id = target_org(&auth_user, query.org_id)?;
+
+    let response = state
+        .health_authority_service
+        .import_authorities(org_id, &csv, query.partial.unwrap_or(false))
+        .await?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/authorities",
+    tag = "authorities",
+    params(AuthorityQuery),
+    responses(
+        (status = 200, description = "Health authorities (only the caller's organization when authenticated)", body = [AuthorityResponse]),
+    )
+)]
 async fn get_authorities(
     State(state): State<AppState>,
+    auth_user: OptionalAuthUser,
     Query(query): Query<AuthorityQuery>,
 ) -> Result<Json<Vec<AuthorityResponse>>, AppError> {
-    let db = &state.auth_service.db;
+    let org_scope = auth_user.user.as_ref().and_then(org_scope_of);
+    let authorities = list_authorities(&state.auth_service.db, &query, org_scope).await?;
+
+    let responses: Vec<AuthorityResponse> = authorities.into_iter().map(|a| a.into()).collect();
+
+    Ok(Json(responses))
+}
+
+/// Authorities matching `query`, newest first, confined to `org_scope` when set
+pub(crate) async fn list_authorities(
+    db: &sqlx::PgPool,
+    query: &AuthorityQuery,
+    org_scope: Option<Uuid>,
+) -> Result<Vec<crate::models::HealthAuthority>, AppError> {
     let page = query.page.unwrap_or(1);
     let limit = query.limit.unwrap_or(20).min(100);
     let offset = (page.saturating_sub(1)) * limit;
@@ -81,6 +168,10 @@ async fn get_authorities(
         conditions.push(format!("name ILIKE '%{}%'", search.replace('\'', "''")));
     }
 
+    if let Some(org_id) = org_scope {
+        conditions.push(format!("org_id = '{}'", org_id));
+    }
+
     if !conditions.is_empty() {
         sql.push_str(" AND ");
         sql.push_str(&conditions.join(" AND "));
@@ -93,21 +184,31 @@ async fn get_authorities(
         .fetch_all(db)
         .await?;
 
-    let responses: Vec<AuthorityResponse> = authorities.into_iter().map(|a| a.into()).collect();
-
-    Ok(Json(responses))
+    Ok(authorities)
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/authorities/{id}",
+    tag = "authorities",
+    params(("id" = Uuid, Path, description = "Authority ID")),
+    responses(
+        (status = 200, description = "Health authority", body = AuthorityResponse),
+        (status = 404, description = "Authority not found"),
+    )
+)]
 async fn get_authority(
     State(state): State<AppState>,
+    auth_user: OptionalAuthUser,
     Path(authority_id): Path<Uuid>,
 ) -> Result<Json<AuthorityResponse>, AppError> {
     let db = &state.auth_service.db;
 
     let authority = sqlx::query_as::<_, crate::models::HealthAuthority>(
-        "SELECT * FROM health_authorities WHERE id = $1"
+        "SELECT * FROM health_authorities WHERE id = $1 AND ($2::uuid IS NULL OR org_id = $2)"
     )
     .bind(authority_id)
+    .bind(auth_user.user.as_ref().and_then(org_scope_of))
     .fetch_optional(db)
     .await?
     .ok_or_else(|| AppError::NotFound("Health authority not found".to_string()))?;
@@ -115,30 +216,77 @@ async fn get_authority(
     Ok(Json(authority.into()))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/authorities/{id}/stats",
+    tag = "authorities",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Authority ID"), AuthorityStatsQuery),
+    responses(
+        (status = 200, description = "Records, proofs and verifications of the authority, in total and per period", body = AuthorityStats),
+        (status = 400, description = "`from` is after `to`"),
+        (status = 403, description = "Neither an admin nor a provider associated with the authority"),
+        (status = 404, description = "Authority not found"),
+    )
+)]
+async fn get_authority_stats(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ViewAuthorityStats>,
+    Path(authority_id): Path<Uuid>,
+    Query(query): Query<AuthorityStatsQuery>,
+) -> Result<Json<AuthorityStats>, AppError> {
+    let stats = state
+        .health_authority_service
+        .statistics(authority_id, &query, provider_scope(&auth_user), auth_user.org_scope())
+        .await?;
+
+    Ok(Json(stats))
+}
+
+/// Served outside `/api/v1` so it resolves from the authority's `did:web` DID
+#[utoipa::path(
+    get,
+    path = "/authorities/{id}/did.json",
+    tag = "authorities",
+    params(("id" = Uuid, Path, description = "Authority ID")),
+    responses(
+        (status = 200, description = "DID document with the authority's public key", body = DidDocument),
+        (status = 404, description = "Authority not found or inactive"),
+    )
+)]
+pub async fn get_did_document(
+    State(state): State<AppState>,
+    Path(authority_id): Path<Uuid>,
+) -> Result<Json<DidDocument>, AppError> {
+    Ok(Json(state.health_authority_service.did_document(authority_id).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/authorities/{id}",
+    tag = "authorities",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Authority ID")),
+    request_body = UpdateAuthorityRequest,
+    responses(
+        (status = 200, description = "Authority updated", body = AuthorityResponse),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Authority not found"),
+    )
+)]
 async fn update_authority(
     State(state): State<AppState>,
-    auth_user: AuthUser,
+    auth_user: RequirePermission<can::ManageAuthorities>,
     Path(authority_id): Path<Uuid>,
     Json(request): Json<UpdateAuthorityRequest>,
 ) -> Result<Json<AuthorityResponse>, AppError> {
-    // Only admins can update health authorities
-    if !matches!(auth_user.user.role, UserRole::Admin) {
-        return Err(AppError::Forbidden("Admin access required".to_string()));
-    }
-
     // Validate request
     request.validate().map_err(validation_error)?;
 
     let db = &state.auth_service.db;
 
-    // Verify authority exists
-    let existing_authority = sqlx::query!(
-        "SELECT id FROM health_authorities WHERE id = $1",
-        authority_id
-    )
-    .fetch_optional(db)
-    .await?
-    .ok_or_else(|| AppError::NotFound("Health authority not found".to_string()))?;
+    // Verify authority exists in the caller's organization
+    find_authority_org(db, authority_id, auth_user.org_scope()).await?;
 
     // Update fields if provided
     if let Some(name) = &request.name {
@@ -162,16 +310,28 @@ async fn update_authority(
     }
 
     if let Some(public_key) = &request.public_key {
-        // Validate the public key format
-        let _validated_key = state.crypto_service.parse_public_key(public_key)?;
-        
+        // Validate the public key format on the new curve, or the current one
+        let curve = match request.curve {
+            Some(curve) => curve,
+            None => sqlx::query_scalar!(
+                r#"SELECT curve as "curve: SigningCurve" FROM health_authorities WHERE id = $1"#,
+                authority_id
+            )
+            .fetch_one(db)
+            .await?,
+        };
+        let public_key = normalize_public_key(&state.crypto_service, curve, public_key)?;
+
         sqlx::query!(
-            "UPDATE health_authorities SET public_key = $1, updated_at = NOW() WHERE id = $2",
-            public_key,
+            "UPDATE health_authorities SET public_key = $1, curve = $2, updated_at = NOW() WHERE id = $3",
+            &public_key,
+            curve as SigningCurve,
             authority_id
         )
         .execute(db)
         .await?;
+    } else if request.curve.is_some() {
+        return Err(AppError::BadRequest("Changing the curve requires a new public key".to_string()));
     }
 
     if let Some(certificate) = &request.certificate {
@@ -194,6 +354,9 @@ async fn update_authority(
         .await?;
     }
 
+    // Proofs must not keep using a rotated key or a deactivated authority
+    state.crypto_service.authorities().invalidate(authority_id);
+
     // Return updated authority
     let updated_authority = sqlx::query_as::<_, crate::models::HealthAuthority>(
         "SELECT * FROM health_authorities WHERE id = $1"
@@ -204,3 +367,222 @@ async fn update_authority(
 
     Ok(Json(updated_authority.into()))
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/authorities/{id}/providers",
+    tag = "authorities",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Authority ID")),
+    responses(
+        (status = 200, description = "Providers allowed to sign for the authority", body = [ProviderAuthority]),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Authority not found"),
+    )
+)]
+async fn get_authority_providers(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ManageAuthorities>,
+    Path(authority_id): Path<Uuid>,
+) -> Result<Json<Vec<ProviderAuthority>>, AppError> {
+    let db = &state.auth_service.db;
+    find_authority_org(db, authority_id, auth_user.org_scope()).await?;
+
+    let providers = sqlx::query_as::<_, ProviderAuthority>(
+        "SELECT * FROM provider_authorities WHERE authority_id = $1 ORDER BY created_at DESC"
+    )
+    .bind(authority_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(Json(providers))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/authorities/{id}/providers",
+    tag = "authorities",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Authority ID")),
+    request_body = AssignProviderRequest,
+    responses(
+        (status = 201, description = "Provider may now sign for the authority", body = ProviderAuthority),
+        (status = 400, description = "User is not a provider"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Authority not found, or user not found in the authority's organization"),
+        (status = 409, description = "Provider is already associated with the authority"),
+    )
+)]
+async fn assign_provider(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ManageAuthorities>,
+    Path(authority_id): Path<Uuid>,
+    Json(request): Json<AssignProviderRequest>,
+) -> Result<(StatusCode, Json<ProviderAuthority>), AppError> {
+    let db = &state.auth_service.db;
+
+    let org_id = find_authority_org(db, authority_id, auth_user.org_scope()).await?;
+
+    // Providers only sign for authorities of their own organization
+    let role = sqlx::query_scalar::<_, UserRole>("SELECT role FROM users WHERE id = $1 AND org_id = $2")
+        .bind(request.user_id)
+        .bind(org_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    if !matches!(role, UserRole::Provider) {
+        return Err(AppError::BadRequest("Only providers can be associated with an authority".to_string()));
+    }
+
+    let association = sqlx::query_as::<_, ProviderAuthority>(
+        r#"
+        INSERT INTO provider_authorities (user_id, authority_id, created_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, authority_id) DO NOTHING
+        RETURNING *
+        "#
+    )
+    .bind(request.user_id)
+    .bind(authority_id)
+    .bind(auth_user.user.id)
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| AppError::Conflict("Provider is already associated with this authority".to_string()))?;
+
+    Ok((StatusCode::CREATED, Json(association)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/authorities/{id}/providers/{user_id}",
+    tag = "authorities",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "Authority ID"),
+        ("user_id" = Uuid, Path, description = "Provider user ID"),
+    ),
+    responses(
+        (status = 204, description = "Provider can no longer sign for the authority"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Association not found"),
+    )
+)]
+async fn unassign_provider(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ManageAuthorities>,
+    Path((authority_id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    let db = &state.auth_service.db;
+    find_authority_org(db, authority_id, auth_user.org_scope()).await?;
+
+    let result = sqlx::query("DELETE FROM provider_authorities WHERE user_id = $1 AND authority_id = $2")
+        .bind(user_id)
+        .bind(authority_id)
+        .execute(db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Provider is not associated with this authority".to_string()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Organization of an authority, treating authorities outside `org_scope` as missing
+#[utoipa::path(
+    post,
+    path = "/api/v1/authorities/{id}/templates",
+    tag = "authorities",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Authority ID")),
+    request_body = CreateTemplateRequest,
+    responses(
+        (status = 201, description = "Template created", body = RecordTemplate),
+        (status = 403, description = "Provider or admin access required, or provider does not represent the authority"),
+        (status = 404, description = "Authority not found"),
+        (status = 409, description = "The authority already has a template with this name"),
+    )
+)]
+async fn create_template(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ManageTemplates>,
+    Path(authority_id): Path<Uuid>,
+    Json(request): Json<CreateTemplateRequest>,
+) -> Result<(StatusCode, Json<RecordTemplate>), AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let health_record_service = HealthRecordService::new(
+        state.auth_service.clone(),
+        state.crypto_service.clone(),
+    );
+
+    let template = health_record_service
+        .create_template(
+            authority_id,
+            request,
+            provider_scope(&auth_user),
+            auth_user.user.id,
+            auth_user.org_scope(),
+        )
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(template)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/authorities/{id}/templates",
+    tag = "authorities",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Authority ID")),
+    responses(
+        (status = 200, description = "The authority's record templates", body = [RecordTemplate]),
+        (status = 403, description = "Provider or admin access required, or provider does not represent the authority"),
+        (status = 404, description = "Authority not found"),
+    )
+)]
+async fn get_templates(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ManageTemplates>,
+    Path(authority_id): Path<Uuid>,
+) -> Result<Json<Vec<RecordTemplate>>, AppError> {
+    let health_record_service = HealthRecordService::new(
+        state.auth_service.clone(),
+        state.crypto_service.clone(),
+    );
+
+    let templates = health_record_service
+        .get_templates(authority_id, provider_scope(&auth_user), auth_user.org_scope())
+        .await?;
+
+    Ok(Json(templates))
+}
+
+/// Providers manage templates only for authorities they represent; admins for any
+fn provider_scope(auth_user: &AuthUser) -> Option<Uuid> {
+    match auth_user.user.role {
+        UserRole::Admin => None,
+        _ => Some(auth_user.user.id),
+    }
+}
+
+async fn find_authority_org(db: &sqlx::PgPool, authority_id: Uuid, org_scope: Option<Uuid>) -> Result<Uuid, AppError> {
+    sqlx::query_scalar::<_, Uuid>(
+        "SELECT org_id FROM health_authorities WHERE id = $1 AND ($2::uuid IS NULL OR org_id = $2)"
+    )
+    .bind(authority_id)
+    .bind(org_scope)
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Health authority not found".to_string()))
+}
+
+/// `AuthUser::org_scope` for an optionally authenticated user
+fn org_scope_of(user: &crate::models::User) -> Option<Uuid> {
+    if user.is_superadmin {
+        None
+    } else {
+        Some(user.org_id)
+    }
+}
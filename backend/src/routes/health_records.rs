@@ -1,17 +1,33 @@
+use std::sync::Arc;
+
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    body::Body,
+    extract::{multipart::MultipartError, DefaultBodyLimit, Multipart, Path, Query, State},
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::IntoResponse,
     routing::{get, post, put, delete},
     Json, Router,
 };
+use base64::{Engine as _, engine::general_purpose};
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
     errors::{AppError, validation_error},
-    models::{CreateHealthRecordRequest, HealthRecordResponse, HealthRecordQuery, UserRole},
+    models::{
+        CreateHealthRecordRequest, HealthRecordResponse, HealthRecordQuery, Paginated, UserRole,
+        VaccinationSeriesResponse, CreateShareRequest, RecordShareResponse, CreateTransferRequest,
+        AcceptTransferRequest, RecordTransferResponse, CreateFromTemplateRequest,
+        RecordExportFormat, RecordExportQuery, AttachmentResponse, AttachmentUploadForm,
+        FieldEncryptionStatus, AccessLogQuery, AccessPurposeQuery, AccessType, RecordAccess,
+        RevokeHealthRecordRequest,
+    },
     middleware::auth::AuthUser,
-    services::HealthRecordService,
+    middleware::permissions::{can, RequirePermission},
+    services::{
+        access_log::ACCESS_PURPOSE_HEADER, attachment::AttachmentUpload, field_encryption::{self, FieldCipher},
+        AttachmentService, HealthRecordService,
+    },
     AppState,
 };
 
@@ -19,13 +35,42 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", post(create_health_record))
         .route("/", get(get_health_records))
+        .route("/from-template/:template_id", post(create_health_record_from_template))
+        .route("/export", get(export_health_records))
+        .route("/encryption", get(get_encryption_status))
+        .route("/encryption/reencrypt", post(start_reencryption))
+        .route("/series/:id", get(get_vaccination_series))
+        .route("/shared-with-me", get(get_shared_records))
+        .route("/shares/:id", delete(revoke_share))
+        .route("/transfers/accept", post(accept_transfer))
         .route("/:id", get(get_health_record))
         .route("/:id", put(update_health_record))
         .route("/:id", delete(delete_health_record))
         .route("/:id/revoke", put(revoke_health_record))
         .route("/:id/sign", post(sign_health_record))
+        .route("/:id/co-sign", post(co_sign_health_record))
+        .route("/:id/shares", post(share_health_record))
+        .route("/:id/shares", get(get_record_shares))
+        .route("/:id/transfer", post(transfer_health_record))
+        .route("/:id/access-log", get(get_record_access_log))
+        // The upload handler enforces ATTACHMENT_MAX_BYTES itself
+        .route("/:id/attachments", post(upload_attachment).layer(DefaultBodyLimit::disable()))
+        .route("/:id/attachments", get(get_attachments))
+        .route("/:id/attachments/:attachment_id", get(download_attachment))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/health-records",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    request_body = CreateHealthRecordRequest,
+    responses(
+        (status = 201, description = "Health record created", body = HealthRecordResponse),
+        (status = 404, description = "Health authority not found, inactive or outside the caller's organization"),
+        (status = 409, description = "An identical record already exists and allow_duplicate is not set"),
+    )
+)]
 async fn create_health_record(
     State(state): State<AppState>,
     auth_user: AuthUser,
@@ -37,20 +82,70 @@ async fn create_health_record(
     let health_record_service = HealthRecordService::new(
         state.auth_service.clone(),
         state.crypto_service.clone(),
-    );
+    )
+    .with_webhooks((*state.webhook_service).clone());
+
+    let response = health_record_service
+        .create_health_record(request, auth_user.user.id, auth_user.org_scope())
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/health-records/from-template/{template_id}",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("template_id" = Uuid, Path, description = "Record template ID")),
+    request_body = CreateFromTemplateRequest,
+    responses(
+        (status = 201, description = "Health record created from the template", body = HealthRecordResponse),
+        (status = 404, description = "Template not found or outside the caller's organization"),
+        (status = 409, description = "An identical record already exists and allow_duplicate is not set"),
+        (status = 400, description = "Merged details are incomplete for the template's record type"),
+    )
+)]
+async fn create_health_record_from_template(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(template_id): Path<Uuid>,
+    Json(request): Json<CreateFromTemplateRequest>,
+) -> Result<(StatusCode, Json<HealthRecordResponse>), AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let health_record_service = HealthRecordService::new(
+        state.auth_service.clone(),
+        state.crypto_service.clone(),
+    )
+    .with_webhooks((*state.webhook_service).clone());
 
     let response = health_record_service
-        .create_health_record(request, auth_user.user.id)
+        .create_health_record_from_template(template_id, request, auth_user.user.id, auth_user.org_scope())
         .await?;
 
     Ok((StatusCode::CREATED, Json(response)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/health-records",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(HealthRecordQuery, AccessPurposeQuery),
+    responses(
+        (status = 200, description = "Health records owned by the caller", body = HealthRecordPage),
+        (status = 400, description = "No access purpose given while the access log is enabled"),
+    )
+)]
 async fn get_health_records(
     State(_state): State<AppState>,
     auth_user: AuthUser,
+    headers: HeaderMap,
     Query(query): Query<HealthRecordQuery>,
-) -> Result<Json<Vec<HealthRecordResponse>>, AppError> {
+    Query(access): Query<AccessPurposeQuery>,
+) -> Result<Json<Paginated<HealthRecordResponse>>, AppError> {
+    let purpose = access_purpose(&_state, &access, &headers)?;
     let health_record_service = HealthRecordService::new(
         _state.auth_service.clone(),
         _state.crypto_service.clone(),
@@ -60,14 +155,72 @@ async fn get_health_records(
         .get_user_health_records(auth_user.user.id, query)
         .await?;
 
+    let record_ids: Vec<Uuid> = records.data.iter().map(|record| record.id).collect();
+    _state.access_log_service
+        .record(auth_user.user.id, auth_user.acting_admin, &record_ids, AccessType::List, purpose.as_deref())
+        .await?;
+
     Ok(Json(records))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/health-records/export",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(RecordExportQuery, AccessPurposeQuery),
+    responses(
+        (status = 200, description = "All health records owned by the caller, streamed as CSV or a JSON array", content_type = "text/csv", body = String),
+        (status = 400, description = "No access purpose given while the access log is enabled"),
+    )
+)]
+async fn export_health_records(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    headers: HeaderMap,
+    Query(query): Query<RecordExportQuery>,
+    Query(access): Query<AccessPurposeQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let purpose = access_purpose(&state, &access, &headers)?;
+    let health_record_service = HealthRecordService::new(
+        state.auth_service.clone(),
+        state.crypto_service.clone(),
+    );
+
+    let (content_type, filename) = match query.format.unwrap_or_default() {
+        RecordExportFormat::Csv => ("text/csv; charset=utf-8", "attachment; filename=\"health-records.csv\""),
+        RecordExportFormat::Json => ("application/json", "attachment; filename=\"health-records.json\""),
+    };
+    let access = state.access_log_service
+        .for_reader(auth_user.user.id, auth_user.acting_admin, AccessType::Export, purpose);
+    let export = health_record_service.export_records(auth_user.user.id, query, access);
+
+    Ok((
+        [(header::CONTENT_TYPE, content_type), (header::CONTENT_DISPOSITION, filename)],
+        Body::from_stream(export),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/health-records/{id}",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Health record ID"), AccessPurposeQuery),
+    responses(
+        (status = 200, description = "Health record", body = HealthRecordResponse),
+        (status = 400, description = "No access purpose given while the access log is enabled"),
+        (status = 404, description = "Health record not found or access denied"),
+    )
+)]
 async fn get_health_record(
     State(_state): State<AppState>,
     auth_user: AuthUser,
+    headers: HeaderMap,
     Path(record_id): Path<Uuid>,
+    Query(access): Query<AccessPurposeQuery>,
 ) -> Result<Json<HealthRecordResponse>, AppError> {
+    let purpose = access_purpose(&_state, &access, &headers)?;
     let health_record_service = HealthRecordService::new(
         _state.auth_service.clone(),
         _state.crypto_service.clone(),
@@ -77,15 +230,293 @@ async fn get_health_record(
         .get_health_record_by_id(record_id, Some(auth_user.user.id))
         .await?;
 
+    _state.access_log_service
+        .record(auth_user.user.id, auth_user.acting_admin, &[record.id], AccessType::Read, purpose.as_deref())
+        .await?;
+
+    Ok(Json(record))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/health-records/series/{id}",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Vaccination series ID"), AccessPurposeQuery),
+    responses(
+        (status = 200, description = "Doses of the series in order, with completeness", body = VaccinationSeriesResponse),
+        (status = 400, description = "No access purpose given while the access log is enabled"),
+        (status = 404, description = "Vaccination series not found or access denied"),
+    )
+)]
+async fn get_vaccination_series(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    headers: HeaderMap,
+    Path(series_id): Path<Uuid>,
+    Query(access): Query<AccessPurposeQuery>,
+) -> Result<Json<VaccinationSeriesResponse>, AppError> {
+    let purpose = access_purpose(&state, &access, &headers)?;
+    let health_record_service = HealthRecordService::new(
+        state.auth_service.clone(),
+        state.crypto_service.clone(),
+    );
+
+    // Admins see every dose in their organization, everyone else only their own
+    let user_id = match auth_user.user.role {
+        UserRole::Admin => None,
+        _ => Some(auth_user.user.id),
+    };
+
+    let series = health_record_service
+        .get_vaccination_series(series_id, user_id, auth_user.org_scope())
+        .await?;
+
+    let record_ids: Vec<Uuid> = series.doses.iter().map(|dose| dose.record_id).collect();
+    state.access_log_service
+        .record(auth_user.user.id, auth_user.acting_admin, &record_ids, AccessType::List, purpose.as_deref())
+        .await?;
+
+    Ok(Json(series))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/health-records/{id}/shares",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Health record ID")),
+    request_body = CreateShareRequest,
+    responses(
+        (status = 201, description = "Record shared; sharing again with the same user replaces the expiry", body = RecordShareResponse),
+        (status = 400, description = "Expiry is in the past or the grantee is the owner"),
+        (status = 404, description = "Record not found, or grantee not found in the record's organization"),
+    )
+)]
+async fn share_health_record(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(record_id): Path<Uuid>,
+    Json(request): Json<CreateShareRequest>,
+) -> Result<(StatusCode, Json<RecordShareResponse>), AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let health_record_service = HealthRecordService::new(
+        state.auth_service.clone(),
+        state.crypto_service.clone(),
+    );
+
+    let share = health_record_service
+        .share_record(record_id, auth_user.user.id, &request.grantee_email, request.expires_at)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(share)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/health-records/{id}/shares",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Health record ID")),
+    responses(
+        (status = 200, description = "Active shares of a record owned by the caller", body = [RecordShareResponse]),
+    )
+)]
+async fn get_record_shares(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(record_id): Path<Uuid>,
+) -> Result<Json<Vec<RecordShareResponse>>, AppError> {
+    let health_record_service = HealthRecordService::new(
+        state.auth_service.clone(),
+        state.crypto_service.clone(),
+    );
+
+    let shares = health_record_service
+        .get_record_shares(record_id, auth_user.user.id)
+        .await?;
+
+    Ok(Json(shares))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/health-records/shares/{id}",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Share ID")),
+    responses(
+        (status = 204, description = "Share revoked"),
+        (status = 404, description = "Share not found or access denied"),
+    )
+)]
+async fn revoke_share(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(share_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let health_record_service = HealthRecordService::new(
+        state.auth_service.clone(),
+        state.crypto_service.clone(),
+    );
+
+    health_record_service
+        .revoke_share(share_id, auth_user.user.id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/health-records/shared-with-me",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(AccessPurposeQuery),
+    responses(
+        (status = 200, description = "Records other users currently share with the caller", body = [HealthRecordResponse]),
+        (status = 400, description = "No access purpose given while the access log is enabled"),
+    )
+)]
+async fn get_shared_records(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    headers: HeaderMap,
+    Query(access): Query<AccessPurposeQuery>,
+) -> Result<Json<Vec<HealthRecordResponse>>, AppError> {
+    let purpose = access_purpose(&state, &access, &headers)?;
+    let health_record_service = HealthRecordService::new(
+        state.auth_service.clone(),
+        state.crypto_service.clone(),
+    );
+
+    let records = health_record_service
+        .get_shared_records(auth_user.user.id)
+        .await?;
+
+    let record_ids: Vec<Uuid> = records.iter().map(|record| record.id).collect();
+    state.access_log_service
+        .record(auth_user.user.id, auth_user.acting_admin, &record_ids, AccessType::List, purpose.as_deref())
+        .await?;
+
+    Ok(Json(records))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/health-records/{id}/access-log",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Health record ID"), AccessLogQuery),
+    responses(
+        (status = 200, description = "Who read the record and why, newest first", body = [RecordAccess]),
+        (status = 404, description = "Health record not found or not owned by the caller"),
+    )
+)]
+async fn get_record_access_log(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(record_id): Path<Uuid>,
+    Query(query): Query<AccessLogQuery>,
+) -> Result<Json<Vec<RecordAccess>>, AppError> {
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(20).min(100);
+
+    let accesses = state.access_log_service
+        .record_history(record_id, auth_user.user.id, page, limit)
+        .await?;
+
+    Ok(Json(accesses))
+}
+
+/// Why the caller reads records, from `?purpose=` or the `X-Access-Purpose`
+/// header; required while the access log is enabled
+fn access_purpose(state: &AppState, query: &AccessPurposeQuery, headers: &HeaderMap) -> Result<Option<String>, AppError> {
+    let header = headers.get(ACCESS_PURPOSE_HEADER).and_then(|h| h.to_str().ok());
+    state.access_log_service.purpose(query.purpose.as_deref().or(header))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/health-records/{id}/transfer",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Health record ID")),
+    request_body = CreateTransferRequest,
+    responses(
+        (status = 201, description = "Transfer offered; the token is only returned here", body = RecordTransferResponse),
+        (status = 400, description = "Record is revoked or the recipient is the owner"),
+        (status = 404, description = "Record not found, or recipient not found in the record's organization"),
+    )
+)]
+async fn transfer_health_record(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(record_id): Path<Uuid>,
+    Json(request): Json<CreateTransferRequest>,
+) -> Result<(StatusCode, Json<RecordTransferResponse>), AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let health_record_service = HealthRecordService::new(
+        state.auth_service.clone(),
+        state.crypto_service.clone(),
+    );
+
+    let transfer = health_record_service
+        .transfer_record(record_id, auth_user.user.id, &request.recipient_email)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(transfer)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/health-records/transfers/accept",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    request_body = AcceptTransferRequest,
+    responses(
+        (status = 200, description = "The caller now owns the record", body = HealthRecordResponse),
+        (status = 400, description = "Record was revoked after the transfer was offered"),
+        (status = 404, description = "Transfer not found, not addressed to the caller, already accepted or expired"),
+    )
+)]
+async fn accept_transfer(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(request): Json<AcceptTransferRequest>,
+) -> Result<Json<HealthRecordResponse>, AppError> {
+    let health_record_service = HealthRecordService::new(
+        state.auth_service.clone(),
+        state.crypto_service.clone(),
+    );
+
+    let record = health_record_service
+        .accept_transfer(&request.token, auth_user.user.id)
+        .await?;
+
     Ok(Json(record))
 }
 
-#[derive(serde::Deserialize, validator::Validate)]
-struct UpdateHealthRecordRequest {
+#[derive(serde::Deserialize, validator::Validate, utoipa::ToSchema)]
+pub(crate) struct UpdateHealthRecordRequest {
     pub details: Option<std::collections::HashMap<String, serde_json::Value>>,
     pub expiry_date: Option<chrono::NaiveDate>,
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/health-records/{id}",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Health record ID")),
+    request_body = UpdateHealthRecordRequest,
+    responses(
+        (status = 200, description = "Health record updated", body = HealthRecordResponse),
+        (status = 404, description = "Health record not found or access denied"),
+    )
+)]
 async fn update_health_record(
     State(_state): State<AppState>,
     auth_user: AuthUser,
@@ -107,6 +538,17 @@ async fn update_health_record(
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/v1/health-records/{id}",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Health record ID")),
+    responses(
+        (status = 204, description = "Health record deleted"),
+        (status = 404, description = "Health record not found or access denied"),
+    )
+)]
 async fn delete_health_record(
     State(_state): State<AppState>,
     auth_user: AuthUser,
@@ -124,56 +566,334 @@ async fn delete_health_record(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/health-records/{id}/revoke",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Health record ID")),
+    request_body = RevokeHealthRecordRequest,
+    responses(
+        (status = 204, description = "Health record and its proofs revoked"),
+        (status = 400, description = "Missing or too long revocation reason"),
+        (status = 403, description = "Provider or admin access required, or provider not associated with the record's authority"),
+        (status = 404, description = "Health record not found"),
+    )
+)]
 async fn revoke_health_record(
     State(_state): State<AppState>,
-    auth_user: AuthUser,
+    auth_user: RequirePermission<can::RevokeRecords>,
     Path(record_id): Path<Uuid>,
+    Json(request): Json<RevokeHealthRecordRequest>,
 ) -> Result<StatusCode, AppError> {
-    // Only providers and admins can revoke health records
-    if !matches!(auth_user.user.role, UserRole::Provider | UserRole::Admin) {
-        return Err(AppError::Forbidden("Provider or admin access required".to_string()));
+    request.validate().map_err(validation_error)?;
+    if request.reason.trim().is_empty() {
+        return Err(AppError::BadRequest("Revocation reason is required".to_string()));
     }
 
     let health_record_service = HealthRecordService::new(
         _state.auth_service.clone(),
         _state.crypto_service.clone(),
-    );
+    )
+    .with_email(_state.email_service.clone())
+    .with_webhooks((*_state.webhook_service).clone());
 
     health_record_service
-        .revoke_health_record(record_id, auth_user.user.id)
+        .revoke_health_record(record_id, auth_user.user.id, &request.reason, auth_user.org_scope())
+        .await?;
+
+    // Outstanding proofs of the record stop verifying immediately
+    _state.zk_proof_service
+        .revoke_proofs_for_record(record_id, auth_user.user.id)
         .await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-#[derive(serde::Deserialize, validator::Validate)]
-struct SignHealthRecordRequest {
+#[derive(serde::Deserialize, validator::Validate, utoipa::ToSchema)]
+pub(crate) struct SignHealthRecordRequest {
     #[validate(length(min = 1, message = "Private key is required"))]
     authority_private_key: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/health-records/{id}/sign",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Health record ID")),
+    request_body = SignHealthRecordRequest,
+    responses(
+        (status = 200, description = "Health record signed", body = HealthRecordResponse),
+        (status = 403, description = "Provider or admin access required, or provider not associated with the record's authority"),
+        (status = 404, description = "Health record not found"),
+    )
+)]
 async fn sign_health_record(
     State(state): State<AppState>,
-    auth_user: AuthUser,
+    auth_user: RequirePermission<can::SignRecords>,
     Path(record_id): Path<Uuid>,
     Json(request): Json<SignHealthRecordRequest>,
 ) -> Result<Json<HealthRecordResponse>, AppError> {
-    // Only providers and admins can sign health records
-    if !matches!(auth_user.user.role, UserRole::Provider | UserRole::Admin) {
-        return Err(AppError::Forbidden("Provider or admin access required".to_string()));
-    }
-
     // Validate request
     request.validate().map_err(validation_error)?;
 
+    let health_record_service = HealthRecordService::new(
+        state.auth_service.clone(),
+        state.crypto_service.clone(),
+    )
+    .with_email(state.email_service.clone())
+    .with_webhooks((*state.webhook_service).clone());
+
+    // Admins may sign for any authority, providers only for their own
+    let provider_id = match auth_user.user.role {
+        UserRole::Admin => None,
+        _ => Some(auth_user.user.id),
+    };
+
+    let response = health_record_service
+        .sign_health_record(record_id, &request.authority_private_key, provider_id, auth_user.org_scope())
+        .await?;
+
+    Ok(Json(response))
+}
+
+#[derive(serde::Deserialize, validator::Validate, utoipa::ToSchema)]
+pub(crate) struct CoSignHealthRecordRequest {
+    /// Co-signing authority named when the record was created
+    authority_id: Uuid,
+    #[validate(length(min = 1, message = "Private key is required"))]
+    authority_private_key: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/health-records/{id}/co-sign",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Health record ID")),
+    request_body = CoSignHealthRecordRequest,
+    responses(
+        (status = 200, description = "Co-signature added", body = HealthRecordResponse),
+        (status = 400, description = "Record not yet signed by its authority, earlier co-signers pending, or key mismatch"),
+        (status = 403, description = "Provider or admin access required, or provider not associated with the co-signing authority"),
+        (status = 404, description = "Health record not found or authority is not a required co-signer"),
+        (status = 409, description = "Authority has already co-signed"),
+    )
+)]
+async fn co_sign_health_record(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::SignRecords>,
+    Path(record_id): Path<Uuid>,
+    Json(request): Json<CoSignHealthRecordRequest>,
+) -> Result<Json<HealthRecordResponse>, AppError> {
+    request.validate().map_err(validation_error)?;
+
     let health_record_service = HealthRecordService::new(
         state.auth_service.clone(),
         state.crypto_service.clone(),
     );
 
+    let provider_id = match auth_user.user.role {
+        UserRole::Admin => None,
+        _ => Some(auth_user.user.id),
+    };
+
     let response = health_record_service
-        .sign_health_record(record_id, &request.authority_private_key, auth_user.user.id)
+        .co_sign_health_record(
+            record_id,
+            request.authority_id,
+            &request.authority_private_key,
+            provider_id,
+            auth_user.org_scope(),
+        )
         .await?;
 
     Ok(Json(response))
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/health-records/encryption",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Records per master key and how many still need re-encryption", body = FieldEncryptionStatus),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Field encryption is not enabled"),
+    )
+)]
+async fn get_encryption_status(
+    State(state): State<AppState>,
+    _auth_user: RequirePermission<can::ReencryptRecords>,
+) -> Result<Json<FieldEncryptionStatus>, AppError> {
+    let status = field_encryption::encryption_status(
+        &state.auth_service.db,
+        field_cipher(&state)?,
+        state.reencrypt_job.is_running(),
+    )
+    .await?;
+
+    Ok(Json(status))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/health-records/encryption/reencrypt",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 202, description = "Re-encryption started in the background", body = FieldEncryptionStatus),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Field encryption is not enabled"),
+        (status = 409, description = "A re-encryption job is already running"),
+    )
+)]
+async fn start_reencryption(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ReencryptRecords>,
+) -> Result<(StatusCode, Json<FieldEncryptionStatus>), AppError> {
+    let cipher = field_cipher(&state)?;
+    state.reencrypt_job.start(state.auth_service.db.clone(), cipher.clone())?;
+
+    tracing::info!(
+        target: "audit",
+        event = "records_reencryption_started",
+        admin_id = %auth_user.user.id,
+        key_id = %cipher.key_id(),
+        "Health record re-encryption started"
+    );
+
+    let status = field_encryption::encryption_status(&state.auth_service.db, cipher, true).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(status)))
+}
+
+fn field_cipher(state: &AppState) -> Result<&FieldCipher, AppError> {
+    state
+        .crypto_service
+        .field_cipher()
+        .ok_or_else(|| AppError::NotFound("Field encryption is not enabled".to_string()))
+}
+
+fn attachment_service(state: &AppState) -> Result<Arc<AttachmentService>, AppError> {
+    state
+        .attachment_service
+        .clone()
+        .ok_or_else(|| AppError::NotFound("Attachments are not enabled on this server".to_string()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/health-records/{id}/attachments",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Health record ID")),
+    request_body(content = AttachmentUploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "File stored and attached to the record", body = AttachmentResponse),
+        (status = 400, description = "No `file` part, or the file is empty"),
+        (status = 404, description = "Record not found, revoked or not owned by the caller, or attachments are disabled"),
+        (status = 413, description = "File exceeds ATTACHMENT_MAX_BYTES"),
+    )
+)]
+async fn upload_attachment(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(record_id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<AttachmentResponse>), AppError> {
+    let attachments = attachment_service(&state)?;
+    let multipart_error = |e: MultipartError| AppError::BadRequest(format!("Invalid multipart body: {}", e));
+
+    while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let file_name = field.file_name().unwrap_or("attachment").to_string();
+        let content_type = field.content_type().map(str::to_string);
+        // Counted while reading, since chunked uploads declare no length up front
+        let mut data = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+            if data.len() + chunk.len() > attachments.max_bytes() {
+                return Err(AppError::PayloadTooLarge(format!(
+                    "Attachment exceeds the {} byte limit",
+                    attachments.max_bytes()
+                )));
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        let attachment = attachments
+            .upload(record_id, auth_user.user.id, AttachmentUpload { file_name, content_type, data })
+            .await?;
+        return Ok((StatusCode::CREATED, Json(attachment)));
+    }
+
+    Err(AppError::BadRequest("Multipart body must contain a `file` part".to_string()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/health-records/{id}/attachments",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Health record ID")),
+    responses(
+        (status = 200, description = "Attachments of a record owned by the caller, with their SHA-256", body = [AttachmentResponse]),
+        (status = 404, description = "Record not found or not owned by the caller, or attachments are disabled"),
+    )
+)]
+async fn get_attachments(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(record_id): Path<Uuid>,
+) -> Result<Json<Vec<AttachmentResponse>>, AppError> {
+    let attachments = attachment_service(&state)?
+        .list(record_id, auth_user.user.id)
+        .await?;
+
+    Ok(Json(attachments))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/health-records/{id}/attachments/{attachment_id}",
+    tag = "health-records",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "Health record ID"),
+        ("attachment_id" = Uuid, Path, description = "Attachment ID"),
+    ),
+    responses(
+        (status = 200, description = "The file, streamed from object storage; `Repr-Digest` carries its SHA-256", content_type = "application/octet-stream", body = String),
+        (status = 404, description = "Attachment not found or record not owned by the caller, or attachments are disabled"),
+        (status = 503, description = "Object storage unavailable"),
+    )
+)]
+async fn download_attachment(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path((record_id, attachment_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    let (attachment, object) = attachment_service(&state)?
+        .download(record_id, attachment_id, auth_user.user.id)
+        .await?;
+
+    // RFC 9530 digest of the whole file, so clients can check what they received
+    let sha256 = hex::decode(&attachment.sha256)
+        .map_err(|_| AppError::InternalServerError("Stored attachment checksum is not hex".to_string()))?;
+    let digest = format!("sha-256=:{}:", general_purpose::STANDARD.encode(sha256));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.content_type),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", attachment.file_name)),
+            (header::CONTENT_LENGTH, attachment.size_bytes.to_string()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (HeaderName::from_static("repr-digest"), digest),
+        ],
+        Body::from_stream(object.bytes_stream()),
+    ))
+}
@@ -1,25 +1,167 @@
+pub mod admin;
 pub mod auth;
 pub mod health_records;
 pub mod zk_proofs;
 pub mod health_authorities;
+pub mod webhooks;
+pub mod verifiers;
+pub mod organizations;
+pub mod graphql;
+pub mod contexts;
+pub mod openapi;
 
 use axum::{
+    error_handling::HandleErrorLayer,
+    extract::State,
+    http::StatusCode,
+    middleware::from_fn_with_state,
     routing::{get, post},
-    Router,
+    Json, Router,
 };
-use crate::AppState;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::process::Command;
+use tower::ServiceBuilder;
+use crate::{
+    config::Config,
+    errors::handle_middleware_error,
+    middleware::idempotency::{replay_idempotent_requests, Idempotency},
+    AppState,
+};
+
+pub fn create_routes(config: &Config, state: &AppState) -> Router<AppState> {
+    let request_timeout = Duration::from_secs(config.request_timeout_secs);
+    let proof_generation_timeout = Duration::from_secs(config.proof_generation_timeout_secs);
+
+    // Idempotency-Key support for record and proof creation
+    let idempotency = from_fn_with_state(
+        Idempotency {
+            store: state.store.clone(),
+            auth_service: state.auth_service.clone(),
+            ttl: Duration::from_secs(config.idempotency_key_ttl_secs),
+            max_body_bytes: config.max_request_body_bytes,
+        },
+        replay_idempotent_requests,
+    );
 
-pub fn create_routes() -> Router<AppState> {
     Router::new()
         // Health check
         .route("/health", get(health_check))
+        .route("/health/ready", get(readiness_check))
         // API v1 routes
         .nest("/api/v1/auth", auth::routes())
-        .nest("/api/v1/health-records", health_records::routes())
+        .nest("/api/v1/health-records", health_records::routes().route_layer(idempotency.clone()))
         .nest("/api/v1/proofs", zk_proofs::routes())
         .nest("/api/v1/authorities", health_authorities::routes())
+        .nest("/api/v1/webhooks", webhooks::routes())
+        .nest("/api/v1/verifiers", verifiers::routes())
+        .nest("/api/v1/organizations", organizations::routes())
+        .nest("/api/v1/contexts", contexts::routes())
+        .nest("/api/v1/admin", admin::routes())
+        // did:web resolution for authorities
+        .route("/authorities/:id/did.json", get(health_authorities::get_did_document))
+        // Read-only GraphQL over the same services
+        .nest("/graphql", graphql::routes())
+        // OpenAPI spec and Swagger UI
+        .merge(openapi::swagger_ui())
+        .layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(handle_middleware_error))
+                .timeout(request_timeout),
+        )
+        // Proof generation shells out to nargo, so it is registered after the
+        // default timeout layer and gets its own, longer budget
+        .route(
+            "/api/v1/proofs/generate",
+            post(zk_proofs::generate_proof)
+                .layer(
+                    ServiceBuilder::new()
+                        .layer(HandleErrorLayer::new(handle_middleware_error))
+                        .timeout(proof_generation_timeout),
+                )
+                .layer(idempotency.clone()),
+        )
+        .route("/api/v1/proofs/jobs", post(zk_proofs::start_proof_job).layer(idempotency))
 }
 
 async fn health_check() -> &'static str {
     "ZK Health Pass API is running"
 }
+
+#[derive(Debug, Serialize)]
+struct SubsystemStatus {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+impl SubsystemStatus {
+    fn up(detail: Option<String>) -> Self {
+        Self { status: "up", detail }
+    }
+
+    fn down(detail: String) -> Self {
+        Self { status: "down", detail: Some(detail) }
+    }
+
+    fn is_up(&self) -> bool {
+        self.status == "up"
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ReadinessChecks {
+    database: SubsystemStatus,
+    nargo: SubsystemStatus,
+    bb: SubsystemStatus,
+}
+
+#[derive(Debug, Serialize)]
+struct ReadinessResponse {
+    status: &'static str,
+    checks: ReadinessChecks,
+}
+
+/// Readiness probe: verifies Postgres and the proving toolchain are reachable
+async fn readiness_check(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let database = match sqlx::query("SELECT 1").execute(&state.auth_service.db).await {
+        Ok(_) => SubsystemStatus::up(None),
+        Err(e) => SubsystemStatus::down(e.to_string()),
+    };
+
+    let checks = if state.zk_proof_service.uses_noir_toolchain() {
+        ReadinessChecks {
+            database,
+            nargo: check_binary("nargo").await,
+            bb: check_binary("bb").await,
+        }
+    } else {
+        let unused = || SubsystemStatus::up(Some("not used by the stub proof backend".to_string()));
+        ReadinessChecks { database, nargo: unused(), bb: unused() }
+    };
+
+    let ready = checks.database.is_up() && checks.nargo.is_up() && checks.bb.is_up();
+    let (status_code, status) = if ready {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    };
+
+    (status_code, Json(ReadinessResponse { status, checks }))
+}
+
+async fn check_binary(binary: &str) -> SubsystemStatus {
+    match Command::new(binary).arg("--version").output().await {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            SubsystemStatus::up(Some(version))
+        }
+        Ok(output) => SubsystemStatus::down(format!(
+            "{} --version exited with {}",
+            binary, output.status
+        )),
+        Err(e) => SubsystemStatus::down(format!("{} not found: {}", binary, e)),
+    }
+}
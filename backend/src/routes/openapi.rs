@@ -0,0 +1,254 @@
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::models::{
+    AuthorityResponse, AuthorityType, SigningCurve, CreateAuthorityRequest, CreateHealthRecordRequest,
+    CreateUserRequest, GenerateProofRequest, HealthRecordResponse, HealthRecordType,
+    LoginRequest, LoginResponse, NotificationPreferencesRequest, ProofJob, ProofJobStage, ProofResponse, ProofType, ProofVerification, RevocationStatus,
+    UpdateAuthorityRequest, UpdateWebhookRequest, UserResponse, UserRole, VerificationDetails,
+    VerificationResponse, VerificationStats, VerificationStatsBucket, VerifyProofRequest,
+    CreateWebhookRequest, WebhookResponse, ProofPage, HealthRecordPage, ProofStatus,
+    CreateVerifierRequest, VerifierResponse, AssignProviderRequest, ProviderAuthority,
+    SigningAuthority, Organization, CreateOrganizationRequest, CreateInviteRequest, AcceptInviteRequest,
+    InviteResponse, SeriesDose, VaccinationSeriesResponse, CreateShareRequest, RecordShareResponse,
+    CreateTransferRequest, AcceptTransferRequest, RecordTransferResponse, RecordTemplate,
+    CreateTemplateRequest, CreateFromTemplateRequest, IntrospectRequest, IntrospectionResponse, RecordSort, RecordExportFormat,
+    ProofSort, StartPasskeyRegistrationResponse, FinishPasskeyRegistrationRequest, StartPasskeyLoginRequest,
+    StartPasskeyLoginResponse, FinishPasskeyLoginRequest, WebauthnCredentialResponse,
+    RevokedProof, RevocationListPayload, SignedRevocationList, LiveProofStatus, ProofStatusResponse,
+    ProofAlert, GoogleWalletLink, AttachmentResponse, AttachmentUploadForm, DidDocument, VerificationMethod,
+    PublicKeyJwk, AuthorityImportResponse, AuthorityImportRow, ImportRowStatus, AuthorityStats, AuthorityStatsPeriod,
+    StatsInterval, CreateDelegationRequest, ProofDelegationResponse, FieldEncryptionStatus, EncryptionKeyUsage,
+    AccessType, RecordAccess, DashboardStats, UserCounts, RevokeHealthRecordRequest,
+};
+use crate::routes::{admin, auth, contexts, health_authorities, health_records, organizations, verifiers, webhooks, zk_proofs};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "ZK Health Pass API",
+        description = "Health record issuance, signing and zero-knowledge proof verification",
+    ),
+    paths(
+        auth::register,
+        auth::login,
+        auth::get_current_user,
+        auth::change_password,
+        auth::update_notification_preferences,
+        auth::verify_user,
+        auth::invite_provider,
+        auth::accept_invite,
+        auth::introspect,
+        auth::impersonate,
+        auth::start_passkey_registration,
+        auth::finish_passkey_registration,
+        auth::start_passkey_login,
+        auth::finish_passkey_login,
+        auth::list_passkeys,
+        auth::delete_passkey,
+        health_records::create_health_record,
+        health_records::create_health_record_from_template,
+        health_records::get_health_records,
+        health_records::export_health_records,
+        health_records::get_encryption_status,
+        health_records::start_reencryption,
+        health_records::get_health_record,
+        health_records::get_vaccination_series,
+        health_records::share_health_record,
+        health_records::get_record_shares,
+        health_records::revoke_share,
+        health_records::get_shared_records,
+        health_records::get_record_access_log,
+        health_records::transfer_health_record,
+        health_records::accept_transfer,
+        health_records::update_health_record,
+        health_records::delete_health_record,
+        health_records::revoke_health_record,
+        health_records::sign_health_record,
+        health_records::co_sign_health_record,
+        health_records::upload_attachment,
+        health_records::get_attachments,
+        health_records::download_attachment,
+        zk_proofs::generate_proof,
+        zk_proofs::start_proof_job,
+        zk_proofs::proof_job_events,
+        zk_proofs::verify_proof,
+        zk_proofs::public_verify_proof,
+        zk_proofs::get_user_proofs,
+        zk_proofs::get_proof,
+        zk_proofs::revoke_proof,
+        zk_proofs::delegate_proof,
+        zk_proofs::get_delegations,
+        zk_proofs::revoke_delegation,
+        zk_proofs::get_verification_history,
+        zk_proofs::get_verification_stats,
+        zk_proofs::export_verification_analytics,
+        zk_proofs::export_verifications,
+        zk_proofs::verification_feed,
+        zk_proofs::get_revocation_list,
+        zk_proofs::get_proof_status,
+        zk_proofs::get_apple_wallet_pass,
+        zk_proofs::get_google_wallet_link,
+        zk_proofs::get_proof_pdf,
+        zk_proofs::get_proof_alerts,
+        zk_proofs::resolve_proof_alert,
+        health_authorities::create_authority,
+        health_authorities::import_authorities,
+        health_authorities::get_authorities,
+        health_authorities::get_authority,
+        health_authorities::get_did_document,
+        health_authorities::get_authority_stats,
+        health_authorities::update_authority,
+        health_authorities::get_authority_providers,
+        health_authorities::assign_provider,
+        health_authorities::unassign_provider,
+        health_authorities::create_template,
+        health_authorities::get_templates,
+        webhooks::create_webhook,
+        webhooks::get_webhooks,
+        webhooks::get_webhook,
+        webhooks::update_webhook,
+        webhooks::delete_webhook,
+        verifiers::register_verifier,
+        verifiers::get_verifiers,
+        verifiers::deactivate_verifier,
+        organizations::create_organization,
+        organizations::get_organizations,
+        contexts::get_health_record_context,
+        admin::get_dashboard_stats,
+    ),
+    components(schemas(
+        CreateUserRequest,
+        LoginRequest,
+        LoginResponse,
+        UserResponse,
+        UserRole,
+        auth::ChangePasswordRequest,
+        NotificationPreferencesRequest,
+        auth::VerifyUserRequest,
+        CreateInviteRequest,
+        AcceptInviteRequest,
+        InviteResponse,
+        IntrospectRequest,
+        IntrospectionResponse,
+        StartPasskeyRegistrationResponse,
+        FinishPasskeyRegistrationRequest,
+        StartPasskeyLoginRequest,
+        StartPasskeyLoginResponse,
+        FinishPasskeyLoginRequest,
+        WebauthnCredentialResponse,
+        CreateHealthRecordRequest,
+        HealthRecordResponse,
+        HealthRecordType,
+        HealthRecordPage,
+        RecordSort,
+        RecordExportFormat,
+        health_records::UpdateHealthRecordRequest,
+        health_records::SignHealthRecordRequest,
+        health_records::CoSignHealthRecordRequest,
+        RevokeHealthRecordRequest,
+        SigningAuthority,
+        SeriesDose,
+        VaccinationSeriesResponse,
+        CreateShareRequest,
+        RecordShareResponse,
+        AttachmentResponse,
+        AttachmentUploadForm,
+        FieldEncryptionStatus,
+        EncryptionKeyUsage,
+        AccessType,
+        RecordAccess,
+        CreateTransferRequest,
+        AcceptTransferRequest,
+        RecordTransferResponse,
+        RecordTemplate,
+        CreateTemplateRequest,
+        CreateFromTemplateRequest,
+        GenerateProofRequest,
+        ProofResponse,
+        ProofJob,
+        ProofJobStage,
+        CreateDelegationRequest,
+        ProofDelegationResponse,
+        ProofType,
+        ProofPage,
+        ProofStatus,
+        ProofSort,
+        VerifyProofRequest,
+        VerificationResponse,
+        VerificationDetails,
+        RevocationStatus,
+        RevokedProof,
+        RevocationListPayload,
+        SignedRevocationList,
+        LiveProofStatus,
+        ProofStatusResponse,
+        ProofAlert,
+        GoogleWalletLink,
+        ProofVerification,
+        VerificationStats,
+        VerificationStatsBucket,
+        CreateAuthorityRequest,
+        UpdateAuthorityRequest,
+        AuthorityResponse,
+        AuthorityType,
+        SigningCurve,
+        AuthorityStats,
+        AuthorityStatsPeriod,
+        StatsInterval,
+        AuthorityImportResponse,
+        AuthorityImportRow,
+        ImportRowStatus,
+        DidDocument,
+        VerificationMethod,
+        PublicKeyJwk,
+        AssignProviderRequest,
+        ProviderAuthority,
+        CreateWebhookRequest,
+        UpdateWebhookRequest,
+        WebhookResponse,
+        CreateVerifierRequest,
+        VerifierResponse,
+        Organization,
+        CreateOrganizationRequest,
+        DashboardStats,
+        UserCounts,
+    )),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "auth", description = "Registration, login and account management"),
+        (name = "health-records", description = "Health record management and signing"),
+        (name = "proofs", description = "Zero-knowledge proof generation and verification"),
+        (name = "authorities", description = "Trusted health authorities"),
+        (name = "webhooks", description = "Event notifications for proof holders"),
+        (name = "verifiers", description = "Verifiers allowed to use public proof verification"),
+        (name = "organizations", description = "Tenants that users, authorities and records belong to"),
+        (name = "admin", description = "Administration dashboard"),
+    )
+)]
+pub struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Swagger UI at `/docs`, backed by the spec served at `/api/v1/openapi.json`
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new("/docs").url("/api/v1/openapi.json", ApiDoc::openapi())
+}
@@ -0,0 +1,89 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use validator::Validate;
+
+use crate::{
+    errors::{AppError, validation_error},
+    models::{CreateOrganizationRequest, Organization},
+    middleware::auth::AuthUser,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(create_organization))
+        .route("/", get(get_organizations))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/organizations",
+    tag = "organizations",
+    security(("bearer_auth" = [])),
+    request_body = CreateOrganizationRequest,
+    responses(
+        (status = 201, description = "Organization created", body = Organization),
+        (status = 403, description = "Superadmin access required"),
+        (status = 409, description = "Organization name already taken"),
+    )
+)]
+async fn create_organization(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(request): Json<CreateOrganizationRequest>,
+) -> Result<(StatusCode, Json<Organization>), AppError> {
+    if !auth_user.user.is_superadmin {
+        return Err(AppError::Forbidden("Superadmin access required".to_string()));
+    }
+
+    request.validate().map_err(validation_error)?;
+
+    let db = &state.auth_service.db;
+
+    let existing = sqlx::query_scalar::<_, uuid::Uuid>("SELECT id FROM organizations WHERE name = $1")
+        .bind(&request.name)
+        .fetch_optional(db)
+        .await?;
+
+    if existing.is_some() {
+        return Err(AppError::Conflict("Organization with this name already exists".to_string()));
+    }
+
+    let organization = sqlx::query_as::<_, Organization>(
+        "INSERT INTO organizations (name) VALUES ($1) RETURNING *"
+    )
+    .bind(&request.name)
+    .fetch_one(db)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(organization)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/organizations",
+    tag = "organizations",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "All organizations", body = [Organization]),
+        (status = 403, description = "Superadmin access required"),
+    )
+)]
+async fn get_organizations(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<Organization>>, AppError> {
+    if !auth_user.user.is_superadmin {
+        return Err(AppError::Forbidden("Superadmin access required".to_string()));
+    }
+
+    let organizations = sqlx::query_as::<_, Organization>("SELECT * FROM organizations ORDER BY name")
+        .fetch_all(&state.auth_service.db)
+        .await?;
+
+    Ok(Json(organizations))
+}
@@ -0,0 +1,86 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    errors::{AppError, validation_error},
+    models::{CreateVerifierRequest, VerifierResponse},
+    middleware::permissions::{can, RequirePermission},
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(register_verifier))
+        .route("/", get(get_verifiers))
+        .route("/:id", delete(deactivate_verifier))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/verifiers",
+    tag = "verifiers",
+    security(("bearer_auth" = [])),
+    request_body = CreateVerifierRequest,
+    responses(
+        (status = 201, description = "Verifier registered; the token is only returned here", body = VerifierResponse),
+        (status = 403, description = "Admin access required"),
+    )
+)]
+async fn register_verifier(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ManageVerifiers>,
+    Json(request): Json<CreateVerifierRequest>,
+) -> Result<(StatusCode, Json<VerifierResponse>), AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let verifier = state.verifier_service.register_verifier(auth_user.user.id, request).await?;
+
+    Ok((StatusCode::CREATED, Json(verifier)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/verifiers",
+    tag = "verifiers",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Registered verifiers", body = [VerifierResponse]),
+        (status = 403, description = "Admin access required"),
+    )
+)]
+async fn get_verifiers(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ManageVerifiers>,
+) -> Result<Json<Vec<VerifierResponse>>, AppError> {
+    let verifiers = state.verifier_service.list_verifiers().await?;
+
+    Ok(Json(verifiers))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/verifiers/{id}",
+    tag = "verifiers",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Verifier ID")),
+    responses(
+        (status = 204, description = "Verifier deactivated"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Verifier not found"),
+    )
+)]
+async fn deactivate_verifier(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ManageVerifiers>,
+    Path(verifier_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state.verifier_service.deactivate_verifier(verifier_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
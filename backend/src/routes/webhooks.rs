@@ -0,0 +1,134 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    errors::{AppError, validation_error},
+    models::{CreateWebhookRequest, UpdateWebhookRequest, WebhookResponse},
+    middleware::auth::AuthUser,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(create_webhook))
+        .route("/", get(get_webhooks))
+        .route("/:id", get(get_webhook))
+        .route("/:id", put(update_webhook))
+        .route("/:id", delete(delete_webhook))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks",
+    tag = "webhooks",
+    security(("bearer_auth" = [])),
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, description = "Webhook registered; the signing secret is only returned here", body = WebhookResponse),
+    )
+)]
+async fn create_webhook(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(request): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<WebhookResponse>), AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let webhook = state.webhook_service.create_webhook(auth_user.user.id, request).await?;
+
+    Ok((StatusCode::CREATED, Json(webhook)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks",
+    tag = "webhooks",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Webhooks registered by the current user", body = [WebhookResponse]),
+    )
+)]
+async fn get_webhooks(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<WebhookResponse>>, AppError> {
+    let webhooks = state.webhook_service.list_webhooks(auth_user.user.id).await?;
+
+    Ok(Json(webhooks))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/{id}",
+    tag = "webhooks",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Webhook ID")),
+    responses(
+        (status = 200, description = "Webhook", body = WebhookResponse),
+        (status = 404, description = "Webhook not found"),
+    )
+)]
+async fn get_webhook(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(webhook_id): Path<Uuid>,
+) -> Result<Json<WebhookResponse>, AppError> {
+    let webhook = state.webhook_service.get_webhook(webhook_id, auth_user.user.id).await?;
+
+    Ok(Json(webhook))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/webhooks/{id}",
+    tag = "webhooks",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Webhook ID")),
+    request_body = UpdateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook updated", body = WebhookResponse),
+        (status = 404, description = "Webhook not found"),
+    )
+)]
+async fn update_webhook(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(webhook_id): Path<Uuid>,
+    Json(request): Json<UpdateWebhookRequest>,
+) -> Result<Json<WebhookResponse>, AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let webhook = state
+        .webhook_service
+        .update_webhook(webhook_id, auth_user.user.id, request)
+        .await?;
+
+    Ok(Json(webhook))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/webhooks/{id}",
+    tag = "webhooks",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Webhook ID")),
+    responses(
+        (status = 204, description = "Webhook deleted"),
+        (status = 404, description = "Webhook not found"),
+    )
+)]
+async fn delete_webhook(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(webhook_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state.webhook_service.delete_webhook(webhook_id, auth_user.user.id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
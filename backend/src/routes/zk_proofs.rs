@@ -1,31 +1,73 @@
 use axum::{
-    extract::{Path, Query, State, ConnectInfo},
-    http::{StatusCode, HeaderMap},
-    routing::{get, post, put},
+    body::Body,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State, ConnectInfo,
+    },
+    http::{header, StatusCode, HeaderMap},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{delete, get, post, put},
     Json, Router,
 };
+use futures::{Stream, StreamExt};
+use tokio::sync::broadcast::{self, error::RecvError};
 use uuid::Uuid;
 use validator::Validate;
 use std::net::SocketAddr;
 
 use crate::{
     errors::{AppError, validation_error},
-    models::{GenerateProofRequest, ProofResponse, VerifyProofRequest, VerificationResponse},
+    i18n::{current_locale, Locale},
+    models::{
+        CreateDelegationRequest, Cursor, GenerateProofRequest, ProofJob, ProofJobStage, Paginated, RevocationListQuery, SignedRevocationList, ProofStatusResponse, ProofAlert, GoogleWalletLink, ProofResponse, ProofSort, ProofStatus, ProofVerification, UserRole, VerificationStats,
+        VerificationEvent, VerificationExportQuery, VerificationFeedMessage, VerificationStatsQuery, VerifyProofRequest, VerificationResponse,
+        ProofDelegationResponse,
+    },
     middleware::auth::{AuthUser, OptionalAuthUser},
+    middleware::permissions::{can, RequirePermission},
+    services::verifier::VERIFIER_TOKEN_HEADER,
     AppState,
 };
 
 pub fn routes() -> Router<AppState> {
     Router::new()
-        .route("/generate", post(generate_proof))
         .route("/verify", post(verify_proof))
         .route("/", get(get_user_proofs))
+        .route("/stats", get(get_verification_stats))
+        .route("/stats/export", get(export_verification_analytics))
+        .route("/verifications/export", get(export_verifications))
+        .route("/verifications/ws", get(verification_feed))
+        .route("/crl", get(get_revocation_list))
+        .route("/alerts", get(get_proof_alerts))
+        .route("/alerts/:id/resolve", put(resolve_proof_alert))
+        .route("/delegations/:id", delete(revoke_delegation))
         .route("/:id", get(get_proof))
         .route("/:id/revoke", put(revoke_proof))
+        .route("/:id/delegations", post(delegate_proof).get(get_delegations))
+        .route("/:id/status", get(get_proof_status))
+        .route("/:id/pkpass", get(get_apple_wallet_pass))
+        .route("/:id/google-wallet", get(get_google_wallet_link))
+        .route("/:id/pdf", get(get_proof_pdf))
+        .route("/:id/verifications", get(get_verification_history))
+        .route("/:id/events", get(proof_job_events))
         .route("/public/verify", post(public_verify_proof)) // Public endpoint for verification
 }
 
-async fn generate_proof(
+#[utoipa::path(
+    post,
+    path = "/api/v1/proofs/generate",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    request_body = GenerateProofRequest,
+    responses(
+        (status = 201, description = "Proof generated", body = ProofResponse),
+        (status = 404, description = "Health record not found or access denied"),
+    )
+)]
+pub(crate) async fn generate_proof(
     State(state): State<AppState>,
     auth_user: AuthUser,
     Json(request): Json<GenerateProofRequest>,
@@ -40,6 +82,89 @@ async fn generate_proof(
     Ok((StatusCode::CREATED, Json(response)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/proofs/jobs",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    request_body = GenerateProofRequest,
+    responses(
+        (status = 202, description = "Proof generation started; follow it at /api/v1/proofs/{job_id}/events", body = ProofJob),
+    )
+)]
+pub(crate) async fn start_proof_job(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(request): Json<GenerateProofRequest>,
+) -> Result<(StatusCode, Json<ProofJob>), AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let job = state.zk_proof_service
+        .start_proof_job(request, auth_user.user.id)
+        .await?;
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/{job_id}/events",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(
+        ("job_id" = Uuid, Path, description = "Proof job ID"),
+        ("Last-Event-ID" = Option<String>, Header, description = "Last stage received, to resume without repeating it"),
+    ),
+    responses(
+        (status = 200, description = "Server-sent events named after each stage reached: `queued`, `witness_generated` and `proving` with `{job_id, stage}`, then `done` with the ProofResponse or `failed` with `{code, message}`, after which the stream ends", content_type = "text/event-stream"),
+        (status = 404, description = "Proof job not found"),
+    )
+)]
+async fn proof_job_events(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(job_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, AppError> {
+    // EventSource resends the ID of the last event it received when it reconnects
+    let last_seen = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|id| serde_json::from_value::<ProofJobStage>(serde_json::Value::String(id.to_string())).ok());
+    // The stream outlives the request's locale scope
+    let locale = current_locale();
+
+    let updates = state.zk_proof_service
+        .proof_job_updates(job_id, auth_user.user.id, last_seen)
+        .await?;
+
+    Ok(Sse::new(updates.map(move |job| proof_job_event(&job, locale))).keep_alive(KeepAlive::default()))
+}
+
+/// A job's stage as an event named and identified by the stage
+fn proof_job_event(job: &ProofJob, locale: Locale) -> Result<Event, axum::Error> {
+    let event = Event::default().event(job.stage.as_str()).id(job.stage.as_str());
+    match job.stage {
+        ProofJobStage::Done => event.json_data(&job.result),
+        ProofJobStage::Failed => event.json_data(serde_json::json!({
+            "code": job.error_code,
+            "message": job.error_message.as_deref().map(|message| locale.translate(message)),
+        })),
+        stage => event.json_data(serde_json::json!({ "job_id": job.id, "stage": stage })),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/proofs/verify",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    request_body = VerifyProofRequest,
+    responses(
+        (status = 200, description = "Verification result", body = VerificationResponse),
+        (status = 403, description = "Invalid, expired or revoked `delegation_token`"),
+    )
+)]
 async fn verify_proof(
     State(state): State<AppState>,
     auth_user: AuthUser,
@@ -59,6 +184,7 @@ async fn verify_proof(
         .verify_proof(
             request,
             Some(auth_user.user.id),
+            None,
             Some(addr.ip()),
             user_agent,
         )
@@ -67,6 +193,17 @@ async fn verify_proof(
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/proofs/public/verify",
+    tag = "proofs",
+    request_body = VerifyProofRequest,
+    params(("X-Verifier-Token" = Option<String>, Header, description = "Registered verifier token; required unless anonymous verification is enabled")),
+    responses(
+        (status = 200, description = "Verification result", body = VerificationResponse),
+        (status = 403, description = "Missing or unregistered verifier token, missing or invalid `captcha_token` when a CAPTCHA is configured, or an invalid, expired or revoked `delegation_token`"),
+    )
+)]
 async fn public_verify_proof(
     State(state): State<AppState>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
@@ -76,6 +213,17 @@ async fn public_verify_proof(
     // Validate request
     request.validate().map_err(validation_error)?;
 
+    let verifier_token = headers
+        .get(VERIFIER_TOKEN_HEADER)
+        .and_then(|h| h.to_str().ok());
+    let registered_verifier_id = state.verifier_service.authenticate(verifier_token).await?;
+    // Registered verifiers are exempt from the CAPTCHA
+    if registered_verifier_id.is_none() {
+        state.verifier_service
+            .check_captcha(request.captcha_token.as_deref(), Some(addr.ip()))
+            .await?;
+    }
+
     let user_agent = headers
         .get("user-agent")
         .and_then(|h| h.to_str().ok())
@@ -85,6 +233,7 @@ async fn public_verify_proof(
         .verify_proof(
             request,
             None, // No authenticated user for public verification
+            registered_verifier_id,
             Some(addr.ip()),
             user_agent,
         )
@@ -93,27 +242,178 @@ async fn public_verify_proof(
     Ok(Json(response))
 }
 
-#[derive(serde::Deserialize)]
-struct ProofQuery {
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/crl",
+    tag = "proofs",
+    params(
+        RevocationListQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a list the verifier already has"),
+        ("If-Modified-Since" = Option<String>, Header, description = "HTTP date of the newest revocation the verifier has seen"),
+    ),
+    responses(
+        (status = 200, description = "Signed list of revoked proofs", body = SignedRevocationList),
+        (status = 304, description = "No revocations since the given ETag or date"),
+        (status = 404, description = "No CRL signing key is configured"),
+    )
+)]
+async fn get_revocation_list(
+    State(state): State<AppState>,
+    Query(query): Query<RevocationListQuery>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    // Revocations are only ever added, so their count and latest time identify the list
+    let (count, last_revoked_at) = state.zk_proof_service
+        .revocation_list_version(query.since)
+        .await?;
+    let etag = format!("\"{}-{}\"", count, last_revoked_at.map_or(0, |at| at.timestamp_micros()));
+
+    let not_modified = match headers.get(header::IF_NONE_MATCH).and_then(|h| h.to_str().ok()) {
+        Some(tags) => tags.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"),
+        None => headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|date| chrono::DateTime::parse_from_rfc2822(date).ok())
+            .map_or(false, |date| last_revoked_at.map_or(true, |at| at.timestamp() <= date.timestamp())),
+    };
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
+    let list = state.zk_proof_service.revocation_list(query.since).await?;
+
+    let mut response = Json(list).into_response();
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::CACHE_CONTROL, header::HeaderValue::from_static("no-cache"));
+    if let Ok(etag) = header::HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, etag);
+    }
+    if let Some(at) = last_revoked_at {
+        if let Ok(date) = header::HeaderValue::from_str(&at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()) {
+            response_headers.insert(header::LAST_MODIFIED, date);
+        }
+    }
+    Ok(response)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/{id}/status",
+    tag = "proofs",
+    params(("id" = Uuid, Path, description = "Proof ID")),
+    responses(
+        (status = 200, description = "Signed current status of the proof", body = ProofStatusResponse),
+        (status = 404, description = "Proof not found, or no signing key is configured"),
+        (status = 429, description = "Rate limit exceeded"),
+    )
+)]
+async fn get_proof_status(
+    State(state): State<AppState>,
+    Path(proof_id): Path<Uuid>,
+) -> Result<Json<ProofStatusResponse>, AppError> {
+    let status = state.zk_proof_service.proof_status(proof_id).await?;
+    Ok(Json(status))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/alerts",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Anomaly alerts on the caller's proofs, or on all proofs in the organization for admins", body = [ProofAlert]),
+    )
+)]
+async fn get_proof_alerts(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<ProofAlert>>, AppError> {
+    let scope_user_id = match auth_user.user.role {
+        UserRole::Admin => None,
+        _ => Some(auth_user.user.id),
+    };
+
+    let alerts = state.zk_proof_service
+        .get_proof_alerts(scope_user_id, auth_user.org_scope())
+        .await?;
+
+    Ok(Json(alerts))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/proofs/alerts/{id}/resolve",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Alert ID")),
+    responses(
+        (status = 200, description = "Alert resolved and the proof's suspension lifted", body = ProofAlert),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Open alert not found"),
+    )
+)]
+async fn resolve_proof_alert(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ResolveProofAlerts>,
+    Path(alert_id): Path<Uuid>,
+) -> Result<Json<ProofAlert>, AppError> {
+    let alert = state.zk_proof_service
+        .resolve_proof_alert(alert_id, auth_user.user.id, auth_user.org_scope())
+        .await?;
+
+    Ok(Json(alert))
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+pub(crate) struct ProofQuery {
     page: Option<u32>,
     limit: Option<u32>,
+    /// Opaque keyset cursor from a previous page's `next_cursor`; takes precedence over `page`
+    cursor: Option<String>,
+    /// Only return proofs in this status: `active`, `expired`, `revoked` or `used_up`
+    status: Option<ProofStatus>,
+    /// `generated_at_desc` (default), `generated_at_asc`, `expires_at_asc` or `record_type`;
+    /// other orders are paged with `page` only
+    sort: Option<ProofSort>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(ProofQuery),
+    responses(
+        (status = 200, description = "Proofs owned by the caller", body = ProofPage),
+    )
+)]
 async fn get_user_proofs(
     State(state): State<AppState>,
     auth_user: AuthUser,
     Query(query): Query<ProofQuery>,
-) -> Result<Json<Vec<ProofResponse>>, AppError> {
+) -> Result<Json<Paginated<ProofResponse>>, AppError> {
     let page = query.page.unwrap_or(1);
     let limit = query.limit.unwrap_or(20).min(100); // Cap at 100 items per page
+    let cursor = query.cursor.as_deref().map(Cursor::decode).transpose()?;
 
     let proofs = state.zk_proof_service
-        .get_user_proofs(auth_user.user.id, page, limit)
+        .get_user_proofs(auth_user.user.id, page, limit, cursor, query.status, query.sort.unwrap_or_default())
         .await?;
 
     Ok(Json(proofs))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/{id}",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Proof ID")),
+    responses(
+        (status = 200, description = "Proof", body = ProofResponse),
+        (status = 404, description = "Proof not found or access denied"),
+    )
+)]
 async fn get_proof(
     State(state): State<AppState>,
     auth_user: AuthUser,
@@ -121,10 +421,11 @@ async fn get_proof(
 ) -> Result<Json<ProofResponse>, AppError> {
     // Get all user proofs and find the specific one
     let proofs = state.zk_proof_service
-        .get_user_proofs(auth_user.user.id, 1, 1000)
+        .get_user_proofs(auth_user.user.id, 1, 1000, None, None, ProofSort::default())
         .await?;
 
     let proof = proofs
+        .data
         .into_iter()
         .find(|p| p.id == proof_id)
         .ok_or_else(|| AppError::NotFound("Proof not found or access denied".to_string()))?;
@@ -132,6 +433,98 @@ async fn get_proof(
     Ok(Json(proof))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/{id}/pkpass",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Proof ID")),
+    responses(
+        (status = 200, description = "Signed Apple Wallet pass of the proof", content_type = "application/vnd.apple.pkpass", body = Vec<u8>),
+        (status = 404, description = "Proof not found or access denied, or Apple Wallet passes are not enabled"),
+    )
+)]
+async fn get_apple_wallet_pass(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(proof_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let pass = state.zk_proof_service
+        .apple_wallet_pass(proof_id, auth_user.user.id)
+        .await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/vnd.apple.pkpass".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"health-pass-{}.pkpass\"", proof_id)),
+        ],
+        pass,
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/{id}/google-wallet",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Proof ID")),
+    responses(
+        (status = 200, description = "Link that saves the proof to Google Wallet", body = GoogleWalletLink),
+        (status = 404, description = "Proof not found or access denied, or Google Wallet passes are not enabled"),
+    )
+)]
+async fn get_google_wallet_link(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(proof_id): Path<Uuid>,
+) -> Result<Json<GoogleWalletLink>, AppError> {
+    let link = state.zk_proof_service
+        .google_wallet_link(proof_id, auth_user.user.id)
+        .await?;
+
+    Ok(Json(link))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/{id}/pdf",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Proof ID")),
+    responses(
+        (status = 200, description = "Printable one-page PDF pass of the proof", content_type = "application/pdf", body = Vec<u8>),
+        (status = 404, description = "Proof not found or access denied"),
+    )
+)]
+async fn get_proof_pdf(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(proof_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let pdf = state.zk_proof_service
+        .proof_pdf(proof_id, auth_user.user.id)
+        .await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"health-pass-{}.pdf\"", proof_id)),
+        ],
+        pdf,
+    ))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/proofs/{id}/revoke",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Proof ID")),
+    responses(
+        (status = 204, description = "Proof revoked"),
+        (status = 404, description = "Proof not found or access denied"),
+    )
+)]
 async fn revoke_proof(
     State(state): State<AppState>,
     auth_user: AuthUser,
@@ -143,3 +536,259 @@ async fn revoke_proof(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/proofs/{id}/delegations",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Proof ID")),
+    request_body = CreateDelegationRequest,
+    responses(
+        (status = 201, description = "Delegation created; the token is only returned here", body = ProofDelegationResponse),
+        (status = 400, description = "Proof is revoked, or the expiry is past or after the proof's"),
+        (status = 404, description = "Proof not found or access denied"),
+    )
+)]
+async fn delegate_proof(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(proof_id): Path<Uuid>,
+    Json(request): Json<CreateDelegationRequest>,
+) -> Result<(StatusCode, Json<ProofDelegationResponse>), AppError> {
+    request.validate().map_err(validation_error)?;
+
+    let delegation = state.zk_proof_service
+        .delegate_proof(proof_id, auth_user.user.id, &request.delegate_email, request.expires_at)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(delegation)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/{id}/delegations",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Proof ID")),
+    responses(
+        (status = 200, description = "Delegations of the proof, newest first", body = [ProofDelegationResponse]),
+    )
+)]
+async fn get_delegations(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(proof_id): Path<Uuid>,
+) -> Result<Json<Vec<ProofDelegationResponse>>, AppError> {
+    let delegations = state.zk_proof_service
+        .list_delegations(proof_id, auth_user.user.id)
+        .await?;
+
+    Ok(Json(delegations))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/proofs/delegations/{id}",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Delegation ID")),
+    responses(
+        (status = 204, description = "Delegation revoked"),
+        (status = 404, description = "Delegation not found or access denied"),
+    )
+)]
+async fn revoke_delegation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(delegation_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state.zk_proof_service
+        .revoke_delegation(delegation_id, auth_user.user.id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/{id}/verifications",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "Proof ID"), ProofQuery),
+    responses(
+        (status = 200, description = "Verification attempts, newest first; IP addresses and user agents are only included for admins", body = [ProofVerification]),
+        (status = 404, description = "Proof not found or access denied"),
+    )
+)]
+async fn get_verification_history(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(proof_id): Path<Uuid>,
+    Query(query): Query<ProofQuery>,
+) -> Result<Json<Vec<ProofVerification>>, AppError> {
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(20).min(100); // Cap at 100 items per page
+
+    // Admins may read any proof's raw log in their organization; owners get it without IPs and user agents
+    let scope_user_id = match auth_user.user.role {
+        UserRole::Admin => None,
+        _ => Some(auth_user.user.id),
+    };
+
+    let verifications = state.zk_proof_service
+        .get_verification_history(proof_id, scope_user_id, auth_user.org_scope(), page, limit)
+        .await?;
+
+    Ok(Json(verifications))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/stats",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(VerificationStatsQuery),
+    responses(
+        (status = 200, description = "Verification statistics (organization-wide for admins, system-wide for superadmins, otherwise the caller's proofs)", body = VerificationStats),
+    )
+)]
+async fn get_verification_stats(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<VerificationStatsQuery>,
+) -> Result<Json<VerificationStats>, AppError> {
+    // Admins see their organization's figures; everyone else only their own proofs
+    let scope_user_id = match auth_user.user.role {
+        UserRole::Admin => None,
+        _ => Some(auth_user.user.id),
+    };
+
+    let stats = state.zk_proof_service
+        .verification_stats(query.from, query.to, scope_user_id, auth_user.org_scope())
+        .await?;
+
+    Ok(Json(stats))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/stats/export",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(VerificationStatsQuery),
+    responses(
+        (status = 200, description = "Anonymized verification log of the caller's organization", content_type = "text/csv", body = String),
+        (status = 403, description = "Admin access required"),
+    )
+)]
+async fn export_verification_analytics(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ExportVerifications>,
+    Query(query): Query<VerificationStatsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let csv = state.zk_proof_service
+        .export_verification_analytics(query.from, query.to, auth_user.org_scope())
+        .await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"verification-analytics.csv\""),
+        ],
+        csv,
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/verifications/export",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    params(VerificationExportQuery),
+    responses(
+        (status = 200, description = "Full verification log of the caller's organization, streamed", content_type = "text/csv", body = String),
+        (status = 400, description = "`from` is after `to`"),
+        (status = 403, description = "Admin access required"),
+    )
+)]
+async fn export_verifications(
+    State(state): State<AppState>,
+    auth_user: RequirePermission<can::ExportVerifications>,
+    Query(query): Query<VerificationExportQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let csv = state.zk_proof_service.export_verifications(query, auth_user.org_scope())?;
+
+    tracing::info!(
+        target: "audit",
+        event = "verifications_exported",
+        admin_id = %auth_user.user.id,
+        "Verification log exported"
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"verifications.csv\""),
+        ],
+        Body::from_stream(csv),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/proofs/verifications/ws",
+    tag = "proofs",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 101, description = "WebSocket of `{\"type\": \"verification\", \"verification\"}` messages as verifications are logged (the caller's proofs, or the organization's for admins), and `{\"type\": \"lagged\", \"skipped\"}` when the client fell behind"),
+        (status = 401, description = "Missing or invalid token"),
+    )
+)]
+async fn verification_feed(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    // Admins follow their organization's proofs; everyone else only their own
+    let scope_user_id = match auth_user.user.role {
+        UserRole::Admin => None,
+        _ => Some(auth_user.user.id),
+    };
+    let org_scope = auth_user.org_scope();
+    let events = state.zk_proof_service.subscribe_verifications();
+
+    upgrade.on_upgrade(move |socket| stream_verifications(socket, events, scope_user_id, org_scope))
+}
+
+/// Forward verifications in scope until the client goes away. A client too
+/// slow to keep up misses the oldest events and is told how many it skipped.
+async fn stream_verifications(
+    mut socket: WebSocket,
+    mut events: broadcast::Receiver<VerificationEvent>,
+    scope_user_id: Option<Uuid>,
+    org_scope: Option<Uuid>,
+) {
+    loop {
+        let message = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => match event.visible_to(scope_user_id, org_scope) {
+                    Some(verification) => VerificationFeedMessage::Verification { verification },
+                    None => continue,
+                },
+                Err(RecvError::Lagged(skipped)) => VerificationFeedMessage::Lagged { skipped },
+                Err(RecvError::Closed) => break,
+            },
+            // Incoming frames are ignored; axum answers pings itself
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+        };
+
+        let Ok(text) = serde_json::to_string(&message) else { continue };
+        if socket.send(Message::Text(text)).await.is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! `seed` command: demo data for trying out a fresh development instance.
+//!
+//! Creates a demo admin and patient, an active health authority with a
+//! well-known keypair, a signed vaccination record for the patient and a proof
+//! over it. Anything that already exists is reused, so running it again
+//! creates nothing new. Never run this against a production database: the
+//! passwords and the authority key are public.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::models::{CreateHealthRecordRequest, CreateUserRequest, GenerateProofRequest, HealthRecordType, UserRole, DEFAULT_ORG_ID};
+use crate::services::{AuthService, CryptoService, HealthRecordService, ZkProofService};
+
+const ADMIN_EMAIL: &str = "admin@demo.veryfi.local";
+const ADMIN_PASSWORD: &str = "demo-admin-password";
+const PATIENT_EMAIL: &str = "patient@demo.veryfi.local";
+const PATIENT_PASSWORD: &str = "demo-patient-password";
+const AUTHORITY_NAME: &str = "Demo Health Authority";
+/// The deterministic test key `0x00..01` also used by `generate_inputs`
+const AUTHORITY_PRIVATE_KEY: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const PATIENT_IDENTIFIER: &str = "DEMO-PATIENT-001";
+
+pub async fn run(
+    auth_service: &Arc<AuthService>,
+    crypto_service: &Arc<CryptoService>,
+    zk_proof_service: &ZkProofService,
+) -> Result<()> {
+    let db = &auth_service.db;
+
+    let admin_id = ensure_user(auth_service, ADMIN_EMAIL, ADMIN_PASSWORD, "Demo Admin", UserRole::Admin).await?;
+    let patient_id = ensure_user(auth_service, PATIENT_EMAIL, PATIENT_PASSWORD, "Demo Patient", UserRole::Patient).await?;
+
+    let private_key = crypto_service.parse_private_key(AUTHORITY_PRIVATE_KEY)?;
+    let public_key = hex::encode(
+        secp256k1::PublicKey::from_secret_key(&secp256k1::Secp256k1::new(), &private_key).serialize_uncompressed(),
+    );
+    let authority_id = match sqlx::query_scalar::<_, Uuid>("SELECT id FROM health_authorities WHERE name = $1")
+        .bind(AUTHORITY_NAME)
+        .fetch_optional(db)
+        .await?
+    {
+        Some(id) => id,
+        None => {
+            sqlx::query_scalar::<_, Uuid>(
+                "INSERT INTO health_authorities (name, authority_type, public_key, org_id) VALUES ($1, 'government', $2, $3) RETURNING id"
+            )
+            .bind(AUTHORITY_NAME)
+            .bind(&public_key)
+            .bind(DEFAULT_ORG_ID)
+            .fetch_one(db)
+            .await?
+        }
+    };
+
+    let health_record_service = HealthRecordService::new(auth_service.clone(), crypto_service.clone());
+    let record_id = match sqlx::query_scalar::<_, Uuid>(
+        "SELECT id FROM health_records WHERE user_id = $1 AND authority_id = $2 ORDER BY created_at LIMIT 1"
+    )
+    .bind(patient_id)
+    .bind(authority_id)
+    .fetch_optional(db)
+    .await?
+    {
+        Some(id) => id,
+        None => {
+            let details = HashMap::from([
+                ("vaccine_name".to_string(), "COVID19".into()),
+                ("manufacturer".to_string(), "Demo Pharma".into()),
+                ("lot_number".to_string(), "DEMO-LOT-1".into()),
+                ("dose_number".to_string(), 1.into()),
+                ("total_doses".to_string(), 2.into()),
+                ("vaccination_site".to_string(), "Demo Clinic".into()),
+                ("administrator".to_string(), "Demo Nurse".into()),
+            ]);
+            let record = health_record_service
+                .create_health_record(
+                    CreateHealthRecordRequest {
+                        authority_id,
+                        record_type: HealthRecordType::Vaccination,
+                        patient_identifier: PATIENT_IDENTIFIER.to_string(),
+                        details,
+                        issue_date: NaiveDate::from_ymd_opt(2025, 1, 1).expect("valid date"),
+                        expiry_date: None,
+                        co_signing_authority_ids: Vec::new(),
+                        allow_duplicate: true,
+                        series_id: None,
+                    },
+                    patient_id,
+                    None,
+                )
+                .await?;
+            health_record_service
+                .sign_health_record(record.id, AUTHORITY_PRIVATE_KEY, None, None)
+                .await?;
+            record.id
+        }
+    };
+
+    // Proof generation needs `nargo`; the rest of the demo data is usable without it
+    let proof = match sqlx::query_scalar::<_, Uuid>("SELECT id FROM zk_proofs WHERE health_record_id = $1 LIMIT 1")
+        .bind(record_id)
+        .fetch_optional(db)
+        .await?
+    {
+        Some(id) => id.to_string(),
+        None => {
+            let request = GenerateProofRequest {
+                health_record_id: record_id,
+                health_record_ids: Vec::new(),
+                proof_type: None,
+                expires_in_hours: None,
+                max_usage: None,
+                proof_context: None,
+            };
+            match zk_proof_service.generate_proof(request, patient_id).await {
+                Ok(proof) => proof.id.to_string(),
+                Err(e) => format!("not generated ({}); re-run once nargo is installed", e),
+            }
+        }
+    };
+
+    println!("Demo data ready:");
+    println!("  Admin:     {} / {} ({})", ADMIN_EMAIL, ADMIN_PASSWORD, admin_id);
+    println!("  Patient:   {} / {} ({})", PATIENT_EMAIL, PATIENT_PASSWORD, patient_id);
+    println!("  Authority: {} ({})", AUTHORITY_NAME, authority_id);
+    println!("    public key:  {}", public_key);
+    println!("    private key: {}", AUTHORITY_PRIVATE_KEY);
+    println!("  Record:    {} (signed vaccination record owned by the patient)", record_id);
+    println!("  Proof:     {}", proof);
+
+    Ok(())
+}
+
+/// ID of the verified user with `email`, registering them if needed
+async fn ensure_user(
+    auth_service: &AuthService,
+    email: &str,
+    password: &str,
+    full_name: &str,
+    role: UserRole,
+) -> Result<Uuid> {
+    let existing = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE email = $1")
+        .bind(email)
+        .fetch_optional(&auth_service.db)
+        .await?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let user = auth_service
+        .register_user(CreateUserRequest {
+            email: email.to_string(),
+            password: password.to_string(),
+            full_name: full_name.to_string(),
+            role: Some(role),
+            org_id: None,
+        })
+        .await?;
+
+    sqlx::query("UPDATE users SET is_verified = TRUE WHERE id = $1")
+        .bind(user.id)
+        .execute(&auth_service.db)
+        .await?;
+
+    Ok(user.id)
+}
@@ -0,0 +1,261 @@
+//! Who read which health record, and why.
+//!
+//! Unlike the `audit` log target, which covers changes, this log records every
+//! read of a record so owners can see who viewed their data. It is off by
+//! default (`ACCESS_LOG_ENABLED`), since it adds a write to every read.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{AccessType, RecordAccess};
+
+/// Header carrying the access purpose, as an alternative to `?purpose=`
+pub const ACCESS_PURPOSE_HEADER: &str = "x-access-purpose";
+
+/// Longest purpose stored, matching the column
+const MAX_PURPOSE_LEN: usize = 255;
+
+#[derive(Clone)]
+pub struct AccessLogService {
+    db: PgPool,
+    enabled: bool,
+}
+
+impl AccessLogService {
+    pub fn new(db: PgPool, enabled: bool) -> Self {
+        Self { db, enabled }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// The caller's stated purpose, required while the log is enabled
+    pub fn purpose(&self, purpose: Option<&str>) -> Result<Option<String>, AppError> {
+        if !self.enabled {
+            return Ok(None);
+        }
+
+        let purpose = purpose
+            .map(str::trim)
+            .filter(|purpose| !purpose.is_empty())
+            .ok_or_else(|| AppError::BadRequest("An access purpose is required".to_string()))?;
+        if purpose.chars().count() > MAX_PURPOSE_LEN {
+            return Err(AppError::BadRequest(format!(
+                "Access purpose must be at most {} characters",
+                MAX_PURPOSE_LEN
+            )));
+        }
+
+        Ok(Some(purpose.to_string()))
+    }
+
+    /// Log that `actor_id` accessed `record_ids`, through `acting_admin_id` when
+    /// an admin is impersonating them. Does nothing while the log is disabled; a
+    /// failed write fails the read, so no access goes unrecorded.
+    pub async fn record(
+        &self,
+        actor_id: Uuid,
+        acting_admin_id: Option<Uuid>,
+        record_ids: &[Uuid],
+        access_type: AccessType,
+        purpose: Option<&str>,
+    ) -> Result<(), AppError> {
+        let Some(purpose) = purpose.filter(|_| self.enabled) else {
+            return Ok(());
+        };
+        if record_ids.is_empty() {
+            return Ok(());
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO record_access_log (health_record_id, actor_id, acting_admin_id, access_type, purpose)
+            SELECT record_id, $2, $3, $4, $5 FROM UNNEST($1::uuid[]) AS record_id
+            "#
+        )
+        .bind(record_ids)
+        .bind(actor_id)
+        .bind(acting_admin_id)
+        .bind(access_type)
+        .bind(purpose)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Accesses by `actor_id` to log as they happen, e.g. during an export
+    pub fn for_reader(
+        &self,
+        actor_id: Uuid,
+        acting_admin_id: Option<Uuid>,
+        access_type: AccessType,
+        purpose: Option<String>,
+    ) -> LoggedAccess {
+        LoggedAccess { log: self.clone(), actor_id, acting_admin_id, access_type, purpose }
+    }
+
+    /// Accesses to one of the owner's records, newest first
+    pub async fn record_history(
+        &self,
+        record_id: Uuid,
+        owner_id: Uuid,
+        page: u32,
+        limit: u32,
+    ) -> Result<Vec<RecordAccess>, AppError> {
+        let owned = sqlx::query_scalar::<_, Uuid>("SELECT id FROM health_records WHERE id = $1 AND user_id = $2")
+            .bind(record_id)
+            .bind(owner_id)
+            .fetch_optional(&self.db)
+            .await?;
+        if owned.is_none() {
+            return Err(AppError::NotFound("Health record not found or access denied".to_string()));
+        }
+
+        let offset = (page.max(1) as i64 - 1) * limit as i64;
+        let accesses = sqlx::query_as::<_, RecordAccess>(
+            r#"
+            SELECT ral.id, ral.health_record_id, ral.actor_id, u.full_name AS actor_name,
+                   ral.acting_admin_id, a.full_name AS acting_admin_name,
+                   ral.access_type, ral.purpose, ral.accessed_at
+            FROM record_access_log ral
+            LEFT JOIN users u ON ral.actor_id = u.id
+            LEFT JOIN users a ON ral.acting_admin_id = a.id
+            WHERE ral.health_record_id = $1
+            ORDER BY ral.accessed_at DESC
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(record_id)
+        .bind(limit as i64)
+        .bind(offset)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(accesses)
+    }
+}
+
+/// A reader and their purpose, for reads that happen in parts
+#[derive(Clone)]
+pub struct LoggedAccess {
+    log: AccessLogService,
+    actor_id: Uuid,
+    acting_admin_id: Option<Uuid>,
+    access_type: AccessType,
+    purpose: Option<String>,
+}
+
+impl LoggedAccess {
+    pub async fn record(&self, record_ids: &[Uuid]) -> Result<(), AppError> {
+        self.log
+            .record(self.actor_id, self.acting_admin_id, record_ids, self.access_type, self.purpose.as_deref())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An owner, a provider reading their records and one record, returned as
+    /// (owner, reader, record)
+    async fn seed(db: &PgPool) -> (Uuid, Uuid, Uuid) {
+        let owner_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name) VALUES ('owner@example.com', 'x', 'Owner') RETURNING id"
+        )
+        .fetch_one(db)
+        .await
+        .unwrap();
+        let reader_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role) VALUES ('dr@example.com', 'x', 'Dr Reader', 'provider') RETURNING id"
+        )
+        .fetch_one(db)
+        .await
+        .unwrap();
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key) VALUES ('Clinic', 'clinic', '02') RETURNING id"
+        )
+        .fetch_one(db)
+        .await
+        .unwrap();
+        let record_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO health_records (user_id, authority_id, record_type, patient_identifier, details, issue_date, signature_r, signature_s, message_hash)
+            VALUES ($1, $2, 'vaccination', 'P-1', '{}', CURRENT_DATE, '\x01', '\x01', '\x01')
+            RETURNING id
+            "#
+        )
+        .bind(owner_id)
+        .bind(authority_id)
+        .fetch_one(db)
+        .await
+        .unwrap();
+
+        (owner_id, reader_id, record_id)
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_reads_are_logged_for_the_owner_only_when_enabled(db: PgPool) {
+        let (owner_id, reader_id, record_id) = seed(&db).await;
+
+        // Disabled: no purpose needed and nothing is written
+        let disabled = AccessLogService::new(db.clone(), false);
+        assert_eq!(disabled.purpose(None).unwrap(), None);
+        disabled.record(reader_id, None, &[record_id], AccessType::Read, None).await.unwrap();
+
+        let enabled = AccessLogService::new(db.clone(), true);
+        assert!(matches!(enabled.purpose(None), Err(AppError::BadRequest(_))));
+        assert!(matches!(enabled.purpose(Some("  ")), Err(AppError::BadRequest(_))));
+        let purpose = enabled.purpose(Some(" treatment ")).unwrap();
+        assert_eq!(purpose.as_deref(), Some("treatment"));
+        enabled.record(reader_id, None, &[record_id], AccessType::Read, purpose.as_deref()).await.unwrap();
+
+        let history = enabled.record_history(record_id, owner_id, 1, 20).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].actor_id, Some(reader_id));
+        assert_eq!(history[0].actor_name.as_deref(), Some("Dr Reader"));
+        assert_eq!(history[0].acting_admin_id, None);
+        assert_eq!(history[0].access_type, AccessType::Read);
+        assert_eq!(history[0].purpose, "treatment");
+        assert!(enabled.record_history(record_id, owner_id, u32::MAX, 100).await.unwrap().is_empty());
+
+        // Only the owner sees who viewed the record
+        assert!(matches!(
+            enabled.record_history(record_id, reader_id, 1, 20).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_impersonated_reads_record_the_acting_admin(db: PgPool) {
+        let (owner_id, reader_id, record_id) = seed(&db).await;
+        let admin_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role) VALUES ('admin@example.com', 'x', 'Admin', 'admin') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let log = AccessLogService::new(db.clone(), true);
+        let long_purpose = "x".repeat(MAX_PURPOSE_LEN + 1);
+        assert!(matches!(log.purpose(Some(&long_purpose)), Err(AppError::BadRequest(_))));
+        log.record(reader_id, Some(admin_id), &[record_id], AccessType::Read, Some("support"))
+            .await
+            .unwrap();
+
+        let history = log.record_history(record_id, owner_id, 1, 20).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].actor_id, Some(reader_id));
+        assert_eq!(history[0].acting_admin_id, Some(admin_id));
+        assert_eq!(history[0].acting_admin_name.as_deref(), Some("Admin"));
+
+        // The impersonating admin is not the owner either
+        assert!(matches!(
+            log.record_history(record_id, admin_id, 1, 20).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
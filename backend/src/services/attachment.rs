@@ -0,0 +1,263 @@
+//! Documents attached to health records (lab reports, scanned cards).
+//!
+//! Files are kept in S3-compatible object storage under
+//! `records/{record_id}/{attachment_id}`, while the database holds their
+//! metadata and SHA-256. The checksum is returned with every listing and
+//! download so clients can verify the bytes they received.
+
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{Attachment, AttachmentResponse};
+use crate::services::object_store::ObjectStore;
+
+/// Lifetime of the presigned URL a download is streamed through
+const DOWNLOAD_URL_TTL: Duration = Duration::from_secs(60);
+const MAX_FILE_NAME_CHARS: usize = 255;
+const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// A file read from an upload request
+pub struct AttachmentUpload {
+    pub file_name: String,
+    pub content_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+pub struct AttachmentService {
+    db: PgPool,
+    store: ObjectStore,
+    /// Largest accepted file
+    max_bytes: usize,
+}
+
+impl AttachmentService {
+    pub fn new(db: PgPool, store: ObjectStore, max_bytes: usize) -> Self {
+        Self { db, store, max_bytes }
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Store a file and attach it to a record owned by `owner_id`
+    pub async fn upload(
+        &self,
+        record_id: Uuid,
+        owner_id: Uuid,
+        upload: AttachmentUpload,
+    ) -> Result<AttachmentResponse, AppError> {
+        if upload.data.len() > self.max_bytes {
+            return Err(AppError::PayloadTooLarge(format!(
+                "Attachment exceeds the {} byte limit",
+                self.max_bytes
+            )));
+        }
+        if upload.data.is_empty() {
+            return Err(AppError::BadRequest("Attachment is empty".to_string()));
+        }
+
+        let owns_record = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM health_records WHERE id = $1 AND user_id = $2 AND is_revoked = FALSE)"
+        )
+        .bind(record_id)
+        .bind(owner_id)
+        .fetch_one(&self.db)
+        .await?;
+        if !owns_record {
+            return Err(AppError::NotFound("Health record not found or access denied".to_string()));
+        }
+
+        let attachment_id = Uuid::new_v4();
+        let object_key = format!("records/{}/{}", record_id, attachment_id);
+        let sha256 = hex::encode(Sha256::digest(&upload.data));
+        let size_bytes = upload.data.len() as i64;
+        let file_name = sanitize_file_name(&upload.file_name);
+        let content_type = upload
+            .content_type
+            .filter(|content_type| is_valid_content_type(content_type))
+            .unwrap_or_else(|| DEFAULT_CONTENT_TYPE.to_string());
+
+        // Store the object first, so a failed insert leaves an unreferenced
+        // object rather than a row pointing at nothing
+        self.store.put(&object_key, &content_type, upload.data).await?;
+
+        let attachment = sqlx::query_as::<_, Attachment>(
+            r#"
+            INSERT INTO attachments (id, health_record_id, object_key, file_name, content_type, size_bytes, sha256, uploaded_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#
+        )
+        .bind(attachment_id)
+        .bind(record_id)
+        .bind(&object_key)
+        .bind(&file_name)
+        .bind(&content_type)
+        .bind(size_bytes)
+        .bind(&sha256)
+        .bind(owner_id)
+        .fetch_one(&self.db)
+        .await?;
+
+        tracing::info!(
+            target: "audit",
+            event = "record_attachment_uploaded",
+            record_id = %record_id,
+            attachment_id = %attachment_id,
+            size_bytes = size_bytes,
+            "Attachment added to a health record"
+        );
+
+        Ok(attachment.into())
+    }
+
+    /// Attachments of a record owned by `owner_id`, oldest first
+    pub async fn list(&self, record_id: Uuid, owner_id: Uuid) -> Result<Vec<AttachmentResponse>, AppError> {
+        let owns_record = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM health_records WHERE id = $1 AND user_id = $2)"
+        )
+        .bind(record_id)
+        .bind(owner_id)
+        .fetch_one(&self.db)
+        .await?;
+        if !owns_record {
+            return Err(AppError::NotFound("Health record not found or access denied".to_string()));
+        }
+
+        let attachments = sqlx::query_as::<_, Attachment>(
+            "SELECT * FROM attachments WHERE health_record_id = $1 ORDER BY created_at, id"
+        )
+        .bind(record_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(attachments.into_iter().map(AttachmentResponse::from).collect())
+    }
+
+    /// An attachment of a record owned by `owner_id`, with the object response to stream its bytes from
+    pub async fn download(
+        &self,
+        record_id: Uuid,
+        attachment_id: Uuid,
+        owner_id: Uuid,
+    ) -> Result<(Attachment, reqwest::Response), AppError> {
+        let attachment = self.owned_attachment(record_id, attachment_id, owner_id).await?;
+        let object = self.store.get(&attachment.object_key, DOWNLOAD_URL_TTL).await?;
+        Ok((attachment, object))
+    }
+
+    async fn owned_attachment(&self, record_id: Uuid, attachment_id: Uuid, owner_id: Uuid) -> Result<Attachment, AppError> {
+        sqlx::query_as::<_, Attachment>(
+            r#"
+            SELECT a.*
+            FROM attachments a
+            JOIN health_records hr ON a.health_record_id = hr.id
+            WHERE a.id = $1 AND a.health_record_id = $2 AND hr.user_id = $3
+            "#
+        )
+        .bind(attachment_id)
+        .bind(record_id)
+        .bind(owner_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Attachment not found or access denied".to_string()))
+    }
+}
+
+/// File name safe to echo in a `Content-Disposition` header: printable ASCII
+/// without quotes, backslashes or path separators
+fn sanitize_file_name(file_name: &str) -> String {
+    let base_name = file_name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let sanitized: String = base_name
+        .chars()
+        .map(|c| match c {
+            '"' | ';' => '_',
+            c if c.is_ascii_graphic() || c == ' ' => c,
+            _ => '_',
+        })
+        .take(MAX_FILE_NAME_CHARS)
+        .collect();
+
+    match sanitized.trim() {
+        "" | "." | ".." => "attachment".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+/// `type/subtype` with optional parameters, in characters valid in a header
+fn is_valid_content_type(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    content_type.len() <= 255
+        && content_type.chars().all(|c| c.is_ascii_graphic() || c == ' ')
+        && essence.split_once('/').map_or(false, |(kind, subtype)| !kind.is_empty() && !subtype.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::object_store::ObjectStoreSettings;
+
+    fn service(db: PgPool) -> AttachmentService {
+        let store = ObjectStore::new(ObjectStoreSettings {
+            endpoint: "http://127.0.0.1:9",
+            bucket: "attachments",
+            region: "us-east-1",
+            access_key_id: "test",
+            secret_access_key: "test-secret",
+        })
+        .unwrap();
+        AttachmentService::new(db, store, 16)
+    }
+
+    #[test]
+    fn test_file_names_are_safe_for_headers() {
+        assert_eq!(sanitize_file_name("lab results.pdf"), "lab results.pdf");
+        assert_eq!(sanitize_file_name("C:\\scans\\card.png"), "card.png");
+        assert_eq!(sanitize_file_name("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_file_name("résumé\".pdf"), "r_sum__.pdf");
+        assert_eq!(sanitize_file_name(".."), "attachment");
+        assert_eq!(sanitize_file_name(&"a".repeat(300)).len(), MAX_FILE_NAME_CHARS);
+    }
+
+    #[test]
+    fn test_content_types_are_validated() {
+        assert!(is_valid_content_type("application/pdf"));
+        assert!(is_valid_content_type("text/plain; charset=utf-8"));
+        assert!(!is_valid_content_type("pdf"));
+        assert!(!is_valid_content_type("text/html\r\nX-Injected: 1"));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_uploads_are_limited_to_owners_and_size(db: PgPool) {
+        let owner_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role) VALUES ('o@example.com', 'x', 'O', 'patient') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let service = service(db);
+        let upload = |size: usize| AttachmentUpload {
+            file_name: "lab.pdf".to_string(),
+            content_type: Some("application/pdf".to_string()),
+            data: vec![0; size],
+        };
+
+        // Rejected before the object store is contacted
+        assert!(matches!(
+            service.upload(Uuid::new_v4(), owner_id, upload(17)).await,
+            Err(AppError::PayloadTooLarge(_))
+        ));
+        assert!(matches!(
+            service.upload(Uuid::new_v4(), owner_id, upload(16)).await,
+            Err(AppError::NotFound(_))
+        ));
+        assert!(matches!(
+            service.list(Uuid::new_v4(), owner_id).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -1,11 +1,16 @@
-use crate::models::{User, UserRole, CreateUserRequest, LoginRequest, LoginResponse, UserResponse};
+use crate::models::{
+    User, UserRole, CreateUserRequest, LoginRequest, LoginResponse, UserResponse, DEFAULT_ORG_ID,
+    InviteResponse, ProviderInvite, IntrospectionResponse,
+};
 use crate::errors::AppError;
 use anyhow::Result;
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use argon2::password_hash::{rand_core::OsRng, SaltString};
 use chrono::{DateTime, Utc, Duration};
 use jsonwebtoken::{encode, decode, Header, Algorithm, Validation, EncodingKey, DecodingKey};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -16,23 +21,37 @@ pub struct Claims {
     pub role: UserRole,
     pub exp: i64, // Expiration timestamp
     pub iat: i64, // Issued at timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<String>, // ID of the admin impersonating `sub`
 }
 
 pub struct AuthService {
     pub db: PgPool,
     jwt_secret: String,
     jwt_expiration_hours: i64,
+    invite_expiration_hours: i64,
 }
 
+const DEFAULT_INVITE_EXPIRATION_HOURS: i64 = 72;
+const INVITE_TOKEN_PREFIX: &str = "inv_";
+const IMPERSONATION_TOKEN_MINUTES: i64 = 15;
+
 impl AuthService {
     pub fn new(db: PgPool, jwt_secret: String, jwt_expiration_hours: i64) -> Self {
         Self {
             db,
             jwt_secret,
             jwt_expiration_hours,
+            invite_expiration_hours: DEFAULT_INVITE_EXPIRATION_HOURS,
         }
     }
 
+    /// How long provider invites can be accepted
+    pub fn with_invite_expiration_hours(mut self, hours: i64) -> Self {
+        self.invite_expiration_hours = hours;
+        self
+    }
+
     pub async fn register_user(&self, request: CreateUserRequest) -> Result<UserResponse, AppError> {
         // Check if user already exists
         let existing_user = sqlx::query_as::<_, User>(
@@ -46,14 +65,23 @@ impl AuthService {
             return Err(AppError::Conflict("User with this email already exists".to_string()));
         }
 
+        let org_id = request.org_id.unwrap_or(DEFAULT_ORG_ID);
+        let org_exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM organizations WHERE id = $1)")
+            .bind(org_id)
+            .fetch_one(&self.db)
+            .await?;
+        if !org_exists {
+            return Err(AppError::NotFound("Organization not found".to_string()));
+        }
+
         // Hash password
         let password_hash = self.hash_password(&request.password)?;
 
         // Create user
         let user = sqlx::query_as::<_, User>(
             r#"
-            INSERT INTO users (email, password_hash, full_name, role)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO users (email, password_hash, full_name, role, org_id)
+            VALUES ($1, $2, $3, $4, $5)
             RETURNING *
             "#
         )
@@ -61,6 +89,7 @@ impl AuthService {
         .bind(&password_hash)
         .bind(&request.full_name)
         .bind(request.role.unwrap_or(UserRole::Patient))
+        .bind(org_id)
         .fetch_one(&self.db)
         .await?;
 
@@ -82,9 +111,13 @@ impl AuthService {
             return Err(AppError::Unauthorized("Invalid credentials".to_string()));
         }
 
-        // Generate JWT token
+        self.issue_login(user)
+    }
+
+    /// Session token for a user who has just authenticated, by password or passkey
+    pub fn issue_login(&self, user: User) -> Result<LoginResponse, AppError> {
         let expires_at = Utc::now() + Duration::hours(self.jwt_expiration_hours);
-        let token = self.generate_token(&user, expires_at)?;
+        let token = self.generate_token(&user, expires_at, None)?;
 
         Ok(LoginResponse {
             token,
@@ -115,13 +148,89 @@ impl AuthService {
         Ok(token_data.claims)
     }
 
-    fn generate_token(&self, user: &User, expires_at: DateTime<Utc>) -> Result<String, AppError> {
+    /// Report whether a token would currently be accepted, and for whom. Tokens
+    /// of users that no longer exist are inactive.
+    pub async fn introspect(&self, token: &str) -> Result<IntrospectionResponse, AppError> {
+        let Ok(claims) = self.verify_token(token) else {
+            return Ok(IntrospectionResponse::default());
+        };
+
+        let user = match Uuid::parse_str(&claims.sub) {
+            Ok(user_id) => self.get_user_by_id(user_id).await?,
+            Err(_) => None,
+        };
+        if user.is_none() {
+            return Ok(IntrospectionResponse::default());
+        }
+
+        Ok(IntrospectionResponse {
+            active: true,
+            sub: Some(claims.sub),
+            email: Some(claims.email),
+            role: Some(claims.role),
+            exp: Some(claims.exp),
+            act: claims.act,
+        })
+    }
+
+    /// Issue a short-lived token that lets an admin act as `target_user_id`.
+    /// The token names the admin in its `act` claim. `org_scope` confines the
+    /// target to one organization (`None` for superadmins).
+    pub async fn impersonate(
+        &self,
+        admin_id: Uuid,
+        target_user_id: Uuid,
+        org_scope: Option<Uuid>,
+    ) -> Result<LoginResponse, AppError> {
+        let admin = self
+            .get_user_by_id(admin_id)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("User not found".to_string()))?;
+        if !matches!(admin.role, UserRole::Admin) {
+            return Err(AppError::Forbidden("Admin access required".to_string()));
+        }
+
+        let target = sqlx::query_as::<_, User>(
+            "SELECT * FROM users WHERE id = $1 AND ($2::uuid IS NULL OR org_id = $2)"
+        )
+        .bind(target_user_id)
+        .bind(org_scope)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        // Impersonation must not become a way to gain another admin's access
+        if matches!(target.role, UserRole::Admin) || target.is_superadmin {
+            return Err(AppError::Forbidden("Admins cannot be impersonated".to_string()));
+        }
+
+        let expires_at = Utc::now() + Duration::minutes(IMPERSONATION_TOKEN_MINUTES);
+        let token = self.generate_token(&target, expires_at, Some(admin_id))?;
+
+        tracing::info!(
+            target: "audit",
+            event = "impersonation_started",
+            admin_id = %admin_id,
+            user_id = %target_user_id,
+            expires_at = %expires_at,
+            "Admin started impersonating a user"
+        );
+
+        Ok(LoginResponse {
+            token,
+            user: target.into(),
+            expires_at,
+        })
+    }
+
+    fn generate_token(&self, user: &User, expires_at: DateTime<Utc>, act: Option<Uuid>) -> Result<String, AppError> {
         let claims = Claims {
             sub: user.id.to_string(),
             email: user.email.clone(),
             role: user.role.clone(),
             exp: expires_at.timestamp(),
             iat: Utc::now().timestamp(),
+            act: act.map(|admin_id| admin_id.to_string()),
         };
 
         let token = encode(
@@ -154,15 +263,26 @@ impl AuthService {
         Ok(argon2.verify_password(password.as_bytes(), &parsed_hash).is_ok())
     }
 
-    pub async fn update_user_verification(&self, user_id: Uuid, is_verified: bool) -> Result<(), AppError> {
-        sqlx::query(
-            "UPDATE users SET is_verified = $1, updated_at = NOW() WHERE id = $2"
+    /// `org_scope` confines the update to users of one organization (`None` for superadmins)
+    pub async fn update_user_verification(
+        &self,
+        user_id: Uuid,
+        is_verified: bool,
+        org_scope: Option<Uuid>,
+    ) -> Result<(), AppError> {
+        let result = sqlx::query(
+            "UPDATE users SET is_verified = $1, updated_at = NOW() WHERE id = $2 AND ($3::uuid IS NULL OR org_id = $3)"
         )
         .bind(is_verified)
         .bind(user_id)
+        .bind(org_scope)
         .execute(&self.db)
         .await?;
 
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("User not found".to_string()));
+        }
+
         Ok(())
     }
 
@@ -190,4 +310,305 @@ impl AuthService {
 
         Ok(())
     }
+
+    /// Opt in to or out of record notification emails
+    pub async fn set_email_notifications(&self, user_id: Uuid, enabled: bool) -> Result<User, AppError> {
+        sqlx::query_as::<_, User>(
+            "UPDATE users SET email_notifications = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+        )
+        .bind(enabled)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+
+    /// Language of the user's notification emails, as a supported locale code
+    pub async fn set_locale(&self, user_id: Uuid, locale: &str) -> Result<User, AppError> {
+        sqlx::query_as::<_, User>(
+            "UPDATE users SET locale = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+        )
+        .bind(locale)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+
+    /// Invite a provider to sign for `authority_id`; the returned token is only shown once.
+    /// `org_scope` confines the authority to one organization (`None` for superadmins).
+    pub async fn invite_provider(
+        &self,
+        email: &str,
+        authority_id: Uuid,
+        invited_by: Uuid,
+        org_scope: Option<Uuid>,
+    ) -> Result<InviteResponse, AppError> {
+        let org_id = sqlx::query_scalar::<_, Uuid>(
+            "SELECT org_id FROM health_authorities WHERE id = $1 AND is_active = TRUE AND ($2::uuid IS NULL OR org_id = $2)"
+        )
+        .bind(authority_id)
+        .bind(org_scope)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Health authority not found or inactive".to_string()))?;
+
+        let email_taken = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)")
+            .bind(email)
+            .fetch_one(&self.db)
+            .await?;
+        if email_taken {
+            return Err(AppError::Conflict("User with this email already exists".to_string()));
+        }
+
+        let token = generate_invite_token();
+        let invite = sqlx::query_as::<_, ProviderInvite>(
+            r#"
+            INSERT INTO provider_invites (email, authority_id, org_id, token_hash, invited_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#
+        )
+        .bind(email)
+        .bind(authority_id)
+        .bind(org_id)
+        .bind(hash_invite_token(&token))
+        .bind(invited_by)
+        .bind(Utc::now() + Duration::hours(self.invite_expiration_hours))
+        .fetch_one(&self.db)
+        .await?;
+
+        let mut response = InviteResponse::from(invite);
+        response.token = Some(token);
+
+        Ok(response)
+    }
+
+    /// Create the invited provider, already associated with the invite's authority
+    pub async fn accept_invite(&self, token: &str, password: &str, full_name: &str) -> Result<UserResponse, AppError> {
+        let password_hash = self.hash_password(password)?;
+        let mut tx = self.db.begin().await?;
+
+        // Claiming the invite first means a token can only ever be used once
+        let invite = sqlx::query_as::<_, ProviderInvite>(
+            r#"
+            UPDATE provider_invites SET accepted_at = NOW()
+            WHERE token_hash = $1 AND accepted_at IS NULL AND expires_at > NOW()
+            RETURNING *
+            "#
+        )
+        .bind(hash_invite_token(token))
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Invite not found, already used or expired".to_string()))?;
+
+        let email_taken = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)")
+            .bind(&invite.email)
+            .fetch_one(&mut *tx)
+            .await?;
+        if email_taken {
+            return Err(AppError::Conflict("User with this email already exists".to_string()));
+        }
+
+        // The inviting admin vouches for the provider, so no separate verification step
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (email, password_hash, full_name, role, org_id, is_verified)
+            VALUES ($1, $2, $3, $4, $5, TRUE)
+            RETURNING *
+            "#
+        )
+        .bind(&invite.email)
+        .bind(&password_hash)
+        .bind(full_name)
+        .bind(UserRole::Provider)
+        .bind(invite.org_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("INSERT INTO provider_authorities (user_id, authority_id, created_by) VALUES ($1, $2, $3)")
+            .bind(user.id)
+            .bind(invite.authority_id)
+            .bind(invite.invited_by)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(user.into())
+    }
+}
+
+fn generate_invite_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", INVITE_TOKEN_PREFIX, hex::encode(bytes))
+}
+
+/// Only this digest is stored, like verifier tokens
+fn hash_invite_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_accepted_invite_provisions_associated_provider(db: PgPool) {
+        let service = AuthService::new(db.clone(), "test-secret".to_string(), 1);
+
+        let admin_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role) VALUES ('admin@example.com', 'x', 'Admin', 'admin') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key) VALUES ('Clinic A', 'clinic', '02') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let invite = service.invite_provider("nurse@example.com", authority_id, admin_id, None).await.unwrap();
+        let token = invite.token.unwrap();
+        assert!(token.starts_with(INVITE_TOKEN_PREFIX));
+
+        let user = service.accept_invite(&token, "password123", "Nurse Joy").await.unwrap();
+        assert_eq!(user.email, "nurse@example.com");
+        assert!(matches!(user.role, UserRole::Provider));
+        assert!(user.is_verified);
+
+        let associated: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM provider_authorities WHERE user_id = $1 AND authority_id = $2)"
+        )
+        .bind(user.id)
+        .bind(authority_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert!(associated);
+
+        // Tokens are single use
+        let reused = service.accept_invite(&token, "password123", "Nurse Joy").await;
+        assert!(matches!(reused, Err(AppError::NotFound(_))));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_users_can_opt_out_of_notification_emails(db: PgPool) {
+        let service = AuthService::new(db.clone(), "test-secret".to_string(), 1);
+
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name) VALUES ('holder@example.com', 'x', 'Holder') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert!(service.get_user_by_id(user_id).await.unwrap().unwrap().email_notifications);
+
+        let user = service.set_email_notifications(user_id, false).await.unwrap();
+        assert!(!user.email_notifications);
+
+        let missing = service.set_email_notifications(Uuid::new_v4(), false).await;
+        assert!(matches!(missing, Err(AppError::NotFound(_))));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_users_choose_the_language_of_their_emails(db: PgPool) {
+        let service = AuthService::new(db.clone(), "test-secret".to_string(), 1);
+
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name) VALUES ('holder@example.com', 'x', 'Holder') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert_eq!(service.get_user_by_id(user_id).await.unwrap().unwrap().locale, None);
+
+        let user = service.set_locale(user_id, "es").await.unwrap();
+        assert_eq!(user.locale.as_deref(), Some("es"));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_expired_invites_are_rejected(db: PgPool) {
+        let service = AuthService::new(db.clone(), "test-secret".to_string(), 1).with_invite_expiration_hours(0);
+
+        let admin_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role) VALUES ('admin@example.com', 'x', 'Admin', 'admin') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key) VALUES ('Clinic A', 'clinic', '02') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let invite = service.invite_provider("nurse@example.com", authority_id, admin_id, None).await.unwrap();
+        let result = service.accept_invite(&invite.token.unwrap(), "password123", "Nurse Joy").await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_introspection_reports_only_usable_tokens(db: PgPool) {
+        let service = AuthService::new(db.clone(), "test-secret".to_string(), 1);
+
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role) VALUES ('p@example.com', 'x', 'P', 'patient') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let user = service.get_user_by_id(user_id).await.unwrap().unwrap();
+        let token = service.generate_token(&user, Utc::now() + Duration::hours(1), None).unwrap();
+
+        let introspection = service.introspect(&token).await.unwrap();
+        assert!(introspection.active);
+        assert_eq!(introspection.sub, Some(user_id.to_string()));
+        assert_eq!(introspection.email.as_deref(), Some("p@example.com"));
+
+        let expired = service.generate_token(&user, Utc::now() - Duration::hours(1), None).unwrap();
+        assert!(!service.introspect(&expired).await.unwrap().active);
+        assert!(!service.introspect("not-a-token").await.unwrap().active);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user_id).execute(&db).await.unwrap();
+        let orphaned = service.introspect(&token).await.unwrap();
+        assert!(!orphaned.active);
+        assert!(orphaned.sub.is_none());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_impersonation_token_records_acting_admin(db: PgPool) {
+        let service = AuthService::new(db.clone(), "test-secret".to_string(), 1);
+
+        let user = |email: &'static str, role: &'static str| {
+            sqlx::query_scalar::<_, Uuid>(
+                "INSERT INTO users (email, password_hash, full_name, role) VALUES ($1, 'x', 'U', $2) RETURNING id"
+            )
+            .bind(email)
+            .bind(role)
+            .fetch_one(&db)
+        };
+        let admin_id = user("admin@example.com", "admin").await.unwrap();
+        let other_admin = user("admin2@example.com", "admin").await.unwrap();
+        let patient_id = user("p@example.com", "patient").await.unwrap();
+
+        let session = service.impersonate(admin_id, patient_id, None).await.unwrap();
+        assert_eq!(session.user.id, patient_id);
+        assert!(session.expires_at <= Utc::now() + Duration::minutes(IMPERSONATION_TOKEN_MINUTES));
+
+        let claims = service.verify_token(&session.token).unwrap();
+        assert_eq!(claims.sub, patient_id.to_string());
+        assert_eq!(claims.act, Some(admin_id.to_string()));
+        assert_eq!(service.introspect(&session.token).await.unwrap().act, Some(admin_id.to_string()));
+
+        // Only admins impersonate, and never other admins
+        let by_patient = service.impersonate(patient_id, admin_id, None).await;
+        assert!(matches!(by_patient, Err(AppError::Forbidden(_))));
+        let of_admin = service.impersonate(admin_id, other_admin, None).await;
+        assert!(matches!(of_admin, Err(AppError::Forbidden(_))));
+    }
 }
@@ -0,0 +1,130 @@
+//! In-memory cache of health authority keys, keeping authority lookups off the
+//! database on the proof generation and verification paths.
+//!
+//! Entries expire after a TTL. Updating an authority evicts it, so a rotated
+//! key is never served from this instance's cache; other instances pick up the
+//! change once their entry expires.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{AuthorityType, SigningCurve};
+
+/// The fields of an authority used to generate and verify proofs
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CachedAuthority {
+    pub name: String,
+    pub authority_type: AuthorityType,
+    /// SEC1 public key on `curve` in hex format
+    pub public_key: String,
+    pub curve: SigningCurve,
+    pub is_active: bool,
+}
+
+pub struct AuthorityCache {
+    /// How long an entry is served before the database is asked again; zero disables caching
+    ttl: Duration,
+    entries: RwLock<HashMap<Uuid, (Instant, Arc<CachedAuthority>)>>,
+}
+
+impl AuthorityCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, entries: RwLock::new(HashMap::new()) }
+    }
+
+    /// The authority, from the cache while its entry is fresh
+    pub async fn get(&self, db: &PgPool, authority_id: Uuid) -> Result<Option<Arc<CachedAuthority>>, AppError> {
+        if let Some(authority) = self.fresh(authority_id) {
+            metrics::counter!("authority_cache_lookups_total", "outcome" => "hit").increment(1);
+            return Ok(Some(authority));
+        }
+        metrics::counter!("authority_cache_lookups_total", "outcome" => "miss").increment(1);
+
+        let authority = sqlx::query_as::<_, CachedAuthority>(
+            "SELECT name, authority_type, public_key, curve, is_active FROM health_authorities WHERE id = $1"
+        )
+        .bind(authority_id)
+        .fetch_optional(db)
+        .await?
+        .map(Arc::new);
+
+        if let (Some(authority), false) = (&authority, self.ttl.is_zero()) {
+            self.entries
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .insert(authority_id, (Instant::now(), authority.clone()));
+        }
+        Ok(authority)
+    }
+
+    /// Drop the cached entry of an authority whose row changed
+    pub fn invalidate(&self, authority_id: Uuid) {
+        self.entries
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&authority_id);
+    }
+
+    fn fresh(&self, authority_id: Uuid) -> Option<Arc<CachedAuthority>> {
+        let entries = self.entries.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        entries
+            .get(&authority_id)
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, authority)| authority.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seed_authority(db: &PgPool) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key) VALUES ('Clinic', 'clinic', '02aa') RETURNING id"
+        )
+        .fetch_one(db)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_rotated_keys_are_served_after_invalidation(db: PgPool) {
+        let cache = AuthorityCache::new(Duration::from_secs(300));
+        let authority_id = seed_authority(&db).await;
+
+        let authority = cache.get(&db, authority_id).await.unwrap().unwrap();
+        assert_eq!(authority.public_key, "02aa");
+
+        sqlx::query("UPDATE health_authorities SET public_key = '02bb' WHERE id = $1")
+            .bind(authority_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        // Served from the cache until it is told the row changed
+        assert_eq!(cache.get(&db, authority_id).await.unwrap().unwrap().public_key, "02aa");
+
+        cache.invalidate(authority_id);
+        assert_eq!(cache.get(&db, authority_id).await.unwrap().unwrap().public_key, "02bb");
+
+        assert!(cache.get(&db, Uuid::new_v4()).await.unwrap().is_none());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_zero_ttl_disables_caching(db: PgPool) {
+        let cache = AuthorityCache::new(Duration::ZERO);
+        let authority_id = seed_authority(&db).await;
+        cache.get(&db, authority_id).await.unwrap();
+
+        sqlx::query("UPDATE health_authorities SET is_active = FALSE WHERE id = $1")
+            .bind(authority_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        assert!(!cache.get(&db, authority_id).await.unwrap().unwrap().is_active);
+    }
+}
@@ -1,12 +1,42 @@
 use crate::errors::AppError;
 use anyhow::Result;
+use axum::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use uuid::Uuid;
+
+/// On-chain operations used by the services, so tests can swap in
+/// the in-memory `MockBlockchain` instead of a live RPC endpoint
+#[async_trait]
+pub trait Blockchain: Send + Sync {
+    /// Submit a ZK proof; returns the transaction hash
+    async fn submit_zk_proof(&self, submission: BlockchainProofSubmission) -> Result<String, AppError>;
+
+    async fn verify_zk_proof(&self, proof_hash: &str, context: &str) -> Result<BlockchainVerificationResult, AppError>;
+
+    /// Revoke a ZK proof; returns the transaction hash
+    async fn revoke_zk_proof(&self, proof_hash: &str) -> Result<String, AppError>;
+
+    /// Register a health authority; returns the transaction hash
+    async fn register_health_authority(
+        &self,
+        authority_address: &str,
+        name: &str,
+        authority_type: &str,
+        public_key: &str,
+        certificate: &str,
+    ) -> Result<String, AppError>;
+
+    async fn get_health_authority(&self, authority_address: &str) -> Result<HealthAuthorityOnChain, AppError>;
+
+    async fn get_system_stats(&self) -> Result<SystemStats, AppError>;
+
+    /// Whether the transaction was mined successfully
+    async fn check_transaction_status(&self, tx_hash: &str) -> Result<bool, AppError>;
+}
 
 /// Blockchain service for interacting with smart contracts
 pub struct BlockchainService {
     rpc_url: String,
+    #[allow(dead_code)] // Transactions are signed by the RPC node for now
     private_key: String,
     contract_addresses: ContractAddresses,
     client: reqwest::Client,
@@ -19,13 +49,15 @@ pub struct ContractAddresses {
     pub health_authority_registry: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockchainProofSubmission {
     pub proof_hash: String,
     pub health_record_hash: String,
     pub authority_address: String,
     pub expires_at: u64,
-    pub proof_data: String,
+    /// IPFS CID of the proof data, which is too large to store on-chain;
+    /// empty when it couldn't be pinned
+    pub proof_cid: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -37,7 +69,7 @@ pub struct BlockchainVerificationResult {
     pub authority_name: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthAuthorityOnChain {
     pub address: String,
     pub name: String,
@@ -60,9 +92,12 @@ impl BlockchainService {
             client: reqwest::Client::new(),
         }
     }
+}
 
+#[async_trait]
+impl Blockchain for BlockchainService {
     /// Submit a ZK proof to the blockchain
-    pub async fn submit_zk_proof(
+    async fn submit_zk_proof(
         &self,
         submission: BlockchainProofSubmission,
     ) -> Result<String, AppError> {
@@ -80,7 +115,7 @@ impl BlockchainService {
     }
 
     /// Verify a ZK proof on the blockchain
-    pub async fn verify_zk_proof(
+    async fn verify_zk_proof(
         &self,
         proof_hash: &str,
         context: &str,
@@ -100,7 +135,7 @@ impl BlockchainService {
     }
 
     /// Register a health authority on the blockchain
-    pub async fn register_health_authority(
+    async fn register_health_authority(
         &self,
         authority_address: &str,
         name: &str,
@@ -128,7 +163,7 @@ impl BlockchainService {
     }
 
     /// Get health authority information from blockchain
-    pub async fn get_health_authority(
+    async fn get_health_authority(
         &self,
         authority_address: &str,
     ) -> Result<HealthAuthorityOnChain, AppError> {
@@ -147,7 +182,7 @@ impl BlockchainService {
     }
 
     /// Revoke a ZK proof on the blockchain
-    pub async fn revoke_zk_proof(&self, proof_hash: &str) -> Result<String, AppError> {
+    async fn revoke_zk_proof(&self, proof_hash: &str) -> Result<String, AppError> {
         // Prepare transaction data for revokeZKProof function
         let function_data = self.encode_revoke_proof_data(proof_hash)?;
         
@@ -162,9 +197,9 @@ impl BlockchainService {
     }
 
     /// Get system statistics from blockchain
-    pub async fn get_system_stats(&self) -> Result<SystemStats, AppError> {
+    async fn get_system_stats(&self) -> Result<SystemStats, AppError> {
         // Call getSystemStats function
-        let function_data = "0x" + &hex::encode("getSystemStats()".as_bytes()[0..4]);
+        let function_data = format!("0x{}", hex::encode(&"getSystemStats()".as_bytes()[0..4]));
         
         let result = self.call_contract(
             &self.contract_addresses.zk_health_pass_registry,
@@ -178,7 +213,7 @@ impl BlockchainService {
     }
 
     /// Check if a transaction was successful
-    pub async fn check_transaction_status(&self, tx_hash: &str) -> Result<bool, AppError> {
+    async fn check_transaction_status(&self, tx_hash: &str) -> Result<bool, AppError> {
         let response = self.client
             .post(&self.rpc_url)
             .json(&serde_json::json!({
@@ -202,22 +237,24 @@ impl BlockchainService {
 
         Ok(false)
     }
+}
 
+impl BlockchainService {
     // Private helper methods for encoding function calls
     fn encode_submit_proof_data(&self, submission: &BlockchainProofSubmission) -> Result<String, AppError> {
         // In a real implementation, you would use a proper ABI encoder
         // For this demo, we'll create a simplified encoding
-        let function_selector = "submitZKProof(bytes32,bytes32,address,uint256,bytes)";
+        let function_selector = "submitZKProof(bytes32,bytes32,address,uint256,string)";
         let selector_hash = &hex::encode(&keccak256(function_selector.as_bytes()))[0..8];
         
         // Encode parameters (simplified)
         let encoded_params = format!(
-            "{}{}{}{}{}",
+            "{}{}{}{:064x}{}",
             submission.proof_hash.trim_start_matches("0x"),
             submission.health_record_hash.trim_start_matches("0x"),
             submission.authority_address.trim_start_matches("0x"),
-            format!("{:064x}", submission.expires_at),
-            hex::encode(&submission.proof_data)
+            submission.expires_at,
+            hex::encode(&submission.proof_cid)
         );
 
         Ok(format!("0x{}{}", selector_hash, encoded_params))
@@ -387,7 +424,7 @@ impl BlockchainService {
         })
     }
 
-    fn parse_authority_result(&self, data: &str) -> Result<HealthAuthorityOnChain, AppError> {
+    fn parse_authority_result(&self, _data: &str) -> Result<HealthAuthorityOnChain, AppError> {
         // Simplified parsing - in production, use proper ABI decoder
         Ok(HealthAuthorityOnChain {
             address: "0x0000000000000000000000000000000000000000".to_string(),
@@ -399,7 +436,7 @@ impl BlockchainService {
         })
     }
 
-    fn parse_system_stats(&self, data: &str) -> Result<SystemStats, AppError> {
+    fn parse_system_stats(&self, _data: &str) -> Result<SystemStats, AppError> {
         // Simplified parsing - in production, use proper ABI decoder
         Ok(SystemStats {
             total_authorities: 1,
@@ -409,7 +446,7 @@ impl BlockchainService {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemStats {
     pub total_authorities: u64,
     pub total_proofs: u64,
@@ -423,3 +460,121 @@ fn keccak256(data: &[u8]) -> [u8; 32] {
     hasher.update(data);
     hasher.finalize().into()
 }
+
+/// Contract address of an authority, derived from its secp256k1 public key
+/// (hex, compressed or uncompressed) the way Ethereum derives account addresses
+pub fn authority_address(public_key_hex: &str) -> Result<String, AppError> {
+    let bytes = hex::decode(public_key_hex.trim_start_matches("0x"))
+        .map_err(|_| AppError::BadRequest("Invalid authority public key".to_string()))?;
+    let public_key = secp256k1::PublicKey::from_slice(&bytes)
+        .map_err(|_| AppError::BadRequest("Invalid authority public key".to_string()))?;
+    let hash = keccak256(&public_key.serialize_uncompressed()[1..]);
+    Ok(format!("0x{}", hex::encode(&hash[12..])))
+}
+
+/// In-memory chain for tests: records submissions and revocations, and
+/// verifies proofs that were submitted and not revoked
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockBlockchain {
+    state: std::sync::Mutex<MockChainState>,
+}
+
+#[cfg(test)]
+#[derive(Default)]
+struct MockChainState {
+    submissions: Vec<BlockchainProofSubmission>,
+    revoked: std::collections::HashSet<String>,
+    authorities: Vec<HealthAuthorityOnChain>,
+    transactions: Vec<String>,
+}
+
+#[cfg(test)]
+impl MockBlockchain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Proofs submitted so far, oldest first
+    pub fn submissions(&self) -> Vec<BlockchainProofSubmission> {
+        self.state.lock().unwrap().submissions.clone()
+    }
+
+    fn record_transaction(state: &mut MockChainState) -> String {
+        let tx_hash = format!("0x{:064x}", state.transactions.len() + 1);
+        state.transactions.push(tx_hash.clone());
+        tx_hash
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl Blockchain for MockBlockchain {
+    async fn submit_zk_proof(&self, submission: BlockchainProofSubmission) -> Result<String, AppError> {
+        let mut state = self.state.lock().unwrap();
+        state.submissions.push(submission);
+        Ok(Self::record_transaction(&mut state))
+    }
+
+    async fn verify_zk_proof(&self, proof_hash: &str, _context: &str) -> Result<BlockchainVerificationResult, AppError> {
+        let state = self.state.lock().unwrap();
+        let submitted = state.submissions.iter().any(|s| s.proof_hash == proof_hash);
+        Ok(BlockchainVerificationResult {
+            is_valid: submitted && !state.revoked.contains(proof_hash),
+            proof_hash: proof_hash.to_string(),
+            verified_at: chrono::Utc::now().timestamp() as u64,
+            verification_count: 1,
+            authority_name: None,
+        })
+    }
+
+    async fn revoke_zk_proof(&self, proof_hash: &str) -> Result<String, AppError> {
+        let mut state = self.state.lock().unwrap();
+        state.revoked.insert(proof_hash.to_string());
+        Ok(Self::record_transaction(&mut state))
+    }
+
+    async fn register_health_authority(
+        &self,
+        authority_address: &str,
+        name: &str,
+        authority_type: &str,
+        public_key: &str,
+        _certificate: &str,
+    ) -> Result<String, AppError> {
+        let mut state = self.state.lock().unwrap();
+        state.authorities.push(HealthAuthorityOnChain {
+            address: authority_address.to_string(),
+            name: name.to_string(),
+            authority_type: authority_type.to_string(),
+            public_key: public_key.to_string(),
+            is_active: true,
+            total_records_issued: 0,
+        });
+        Ok(Self::record_transaction(&mut state))
+    }
+
+    async fn get_health_authority(&self, authority_address: &str) -> Result<HealthAuthorityOnChain, AppError> {
+        self.state
+            .lock()
+            .unwrap()
+            .authorities
+            .iter()
+            .find(|authority| authority.address == authority_address)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound("Health authority not registered on-chain".to_string()))
+    }
+
+    async fn get_system_stats(&self) -> Result<SystemStats, AppError> {
+        let state = self.state.lock().unwrap();
+        Ok(SystemStats {
+            total_authorities: state.authorities.len() as u64,
+            total_proofs: state.submissions.len() as u64,
+            total_verifications: 0,
+        })
+    }
+
+    async fn check_transaction_status(&self, tx_hash: &str) -> Result<bool, AppError> {
+        Ok(self.state.lock().unwrap().transactions.iter().any(|tx| tx == tx_hash))
+    }
+}
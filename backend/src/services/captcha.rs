@@ -0,0 +1,119 @@
+//! CAPTCHA checks for anonymous public verification.
+//!
+//! hCaptcha and Cloudflare Turnstile share the same `siteverify` protocol: the
+//! server posts its secret and the client's response token, and gets back
+//! whether the token is valid. Tokens are single-use.
+
+use std::net::IpAddr;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::errors::AppError;
+
+const SITEVERIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptchaProvider {
+    Hcaptcha,
+    Turnstile,
+}
+
+impl CaptchaProvider {
+    fn siteverify_url(&self) -> &'static str {
+        match self {
+            CaptchaProvider::Hcaptcha => "https://api.hcaptcha.com/siteverify",
+            CaptchaProvider::Turnstile => "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SiteverifyResponse {
+    success: bool,
+    #[serde(default, rename = "error-codes")]
+    error_codes: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct CaptchaVerifier {
+    http: reqwest::Client,
+    siteverify_url: String,
+    secret: String,
+}
+
+impl CaptchaVerifier {
+    pub fn new(provider: CaptchaProvider, secret: String) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(SITEVERIFY_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            siteverify_url: provider.siteverify_url().to_string(),
+            secret,
+        }
+    }
+
+    /// Ask the provider whether `token` was issued for this site and not used yet
+    pub async fn verify(&self, token: &str, remote_ip: Option<IpAddr>) -> Result<(), AppError> {
+        let mut form = vec![("secret", self.secret.clone()), ("response", token.to_string())];
+        if let Some(ip) = remote_ip {
+            form.push(("remoteip", ip.to_string()));
+        }
+
+        let response = self
+            .http
+            .post(&self.siteverify_url)
+            .form(&form)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AppError::ServiceUnavailable(format!("CAPTCHA provider unreachable: {}", e)))?
+            .json::<SiteverifyResponse>()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("Invalid CAPTCHA provider response: {}", e)))?;
+
+        if !response.success {
+            tracing::debug!(error_codes = ?response.error_codes, "CAPTCHA token rejected");
+            return Err(AppError::Forbidden("Invalid CAPTCHA token".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Form, Json, Router};
+    use std::collections::HashMap;
+
+    /// A siteverify endpoint that accepts the token "valid" for the secret "secret"
+    async fn siteverify_server() -> String {
+        let app = Router::new().route(
+            "/siteverify",
+            post(|Form(form): Form<HashMap<String, String>>| async move {
+                let success = form.get("secret").map(String::as_str) == Some("secret")
+                    && form.get("response").map(String::as_str) == Some("valid");
+                Json(serde_json::json!({ "success": success, "error-codes": [] }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}/siteverify", address)
+    }
+
+    #[tokio::test]
+    async fn test_only_tokens_the_provider_accepts_pass() {
+        let mut verifier = CaptchaVerifier::new(CaptchaProvider::Turnstile, "secret".to_string());
+        verifier.siteverify_url = siteverify_server().await;
+
+        verifier.verify("valid", Some("203.0.113.7".parse().unwrap())).await.unwrap();
+        assert!(matches!(verifier.verify("forged", None).await, Err(AppError::Forbidden(_))));
+
+        verifier.siteverify_url = "http://127.0.0.1:9/siteverify".to_string();
+        assert!(matches!(verifier.verify("valid", None).await, Err(AppError::ServiceUnavailable(_))));
+    }
+}
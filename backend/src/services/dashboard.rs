@@ -0,0 +1,205 @@
+//! System-wide numbers for the admin dashboard.
+
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{DashboardStats, UserCounts};
+use crate::services::store::Store;
+
+/// How long computed stats are served from the store, so dashboards that
+/// refresh every few seconds don't rerun the aggregates each time
+const DASHBOARD_STATS_TTL: Duration = Duration::from_secs(30);
+
+pub struct DashboardService {
+    db: PgPool,
+    store: Arc<dyn Store>,
+}
+
+impl DashboardService {
+    pub fn new(db: PgPool, store: Arc<dyn Store>) -> Self {
+        Self { db, store }
+    }
+
+    /// Stats of `org_scope`, or of every organization when `None`; at most
+    /// `DASHBOARD_STATS_TTL` old
+    pub async fn stats(&self, org_scope: Option<Uuid>) -> Result<DashboardStats, AppError> {
+        let key = format!(
+            "dashboard_stats:{}",
+            org_scope.map_or_else(|| "all".to_string(), |org_id| org_id.to_string())
+        );
+        if let Some(cached) = self.store.get(&key).await? {
+            if let Ok(stats) = serde_json::from_str(&cached) {
+                return Ok(stats);
+            }
+        }
+
+        let stats = self.compute(org_scope).await?;
+        let cached = serde_json::to_string(&stats)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to serialize dashboard stats: {}", e)))?;
+        self.store.set(&key, &cached, DASHBOARD_STATS_TTL).await?;
+
+        Ok(stats)
+    }
+
+    async fn compute(&self, org_scope: Option<Uuid>) -> Result<DashboardStats, AppError> {
+        let (total, patients, providers, verifiers, admins) = sqlx::query_as::<_, (i64, i64, i64, i64, i64)>(
+            r#"
+            SELECT COUNT(*),
+                   COUNT(*) FILTER (WHERE role = 'patient'),
+                   COUNT(*) FILTER (WHERE role = 'provider'),
+                   COUNT(*) FILTER (WHERE role = 'verifier'),
+                   COUNT(*) FILTER (WHERE role = 'admin')
+            FROM users
+            WHERE ($1::uuid IS NULL OR org_id = $1)
+            "#
+        )
+        .bind(org_scope)
+        .fetch_one(&self.db)
+        .await?;
+
+        let (active_authorities, inactive_authorities) = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT COUNT(*) FILTER (WHERE is_active IS NOT FALSE), COUNT(*) FILTER (WHERE is_active = FALSE)
+            FROM health_authorities
+            WHERE ($1::uuid IS NULL OR org_id = $1)
+            "#
+        )
+        .bind(org_scope)
+        .fetch_one(&self.db)
+        .await?;
+
+        let (records_issued, records_revoked) = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT COUNT(*), COUNT(*) FILTER (WHERE is_revoked)
+            FROM health_records
+            WHERE ($1::uuid IS NULL OR org_id = $1)
+            "#
+        )
+        .bind(org_scope)
+        .fetch_one(&self.db)
+        .await?;
+
+        let proofs_generated = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM zk_proofs zp
+            JOIN health_records hr ON zp.health_record_id = hr.id
+            WHERE ($1::uuid IS NULL OR hr.org_id = $1)
+            "#
+        )
+        .bind(org_scope)
+        .fetch_one(&self.db)
+        .await?;
+
+        let (proof_jobs, failed_proof_jobs) = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT COUNT(*), COUNT(*) FILTER (WHERE pj.stage = 'failed')
+            FROM proof_jobs pj
+            JOIN users u ON pj.user_id = u.id
+            WHERE ($1::uuid IS NULL OR u.org_id = $1)
+            "#
+        )
+        .bind(org_scope)
+        .fetch_one(&self.db)
+        .await?;
+
+        let (total_verifications, failed_verifications) = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT COUNT(*), COUNT(*) FILTER (WHERE NOT pv.verification_result)
+            FROM proof_verifications pv
+            JOIN zk_proofs zp ON pv.proof_id = zp.id
+            JOIN health_records hr ON zp.health_record_id = hr.id
+            WHERE ($1::uuid IS NULL OR hr.org_id = $1)
+            "#
+        )
+        .bind(org_scope)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(DashboardStats {
+            users: UserCounts { total, patients, providers, verifiers, admins },
+            active_authorities,
+            inactive_authorities,
+            records_issued,
+            records_revoked,
+            proofs_generated,
+            proof_jobs,
+            failed_proof_jobs,
+            proof_job_failure_rate: failure_rate(proof_jobs, failed_proof_jobs),
+            total_verifications,
+            failed_verifications,
+            verification_failure_rate: failure_rate(total_verifications, failed_verifications),
+            generated_at: Utc::now(),
+        })
+    }
+}
+
+fn failure_rate(total: i64, failed: i64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        failed as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::store::MemoryStore;
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_stats_are_scoped_to_the_organization_and_cached(db: PgPool) {
+        let org_id: Uuid = sqlx::query_scalar("INSERT INTO organizations (name) VALUES ('Hospital') RETURNING id")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO users (email, password_hash, full_name, role, org_id) VALUES
+                ('p@example.com', 'x', 'P', 'patient', $1),
+                ('dr@example.com', 'x', 'Dr', 'provider', $1),
+                ('other@example.com', 'x', 'Other', 'patient', '00000000-0000-0000-0000-000000000001')
+            "#
+        )
+        .bind(org_id)
+        .execute(&db)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO health_authorities (name, authority_type, public_key, org_id, is_active) VALUES ('Clinic', 'clinic', '02', $1, TRUE), ('Old Lab', 'laboratory', '02', $1, FALSE)"
+        )
+        .bind(org_id)
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let service = DashboardService::new(db.clone(), Arc::new(MemoryStore::new()));
+        let stats = service.stats(Some(org_id)).await.unwrap();
+        assert_eq!((stats.users.total, stats.users.patients, stats.users.providers), (2, 1, 1));
+        assert_eq!((stats.active_authorities, stats.inactive_authorities), (1, 1));
+        assert_eq!(stats.total_verifications, 0);
+        assert_eq!(stats.verification_failure_rate, 0.0);
+
+        // Superadmins see every organization
+        assert_eq!(service.stats(None).await.unwrap().users.total, 3);
+
+        // Served from the cache until it expires
+        sqlx::query("INSERT INTO users (email, password_hash, full_name, org_id) VALUES ('new@example.com', 'x', 'New', $1)")
+            .bind(org_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        let cached = service.stats(Some(org_id)).await.unwrap();
+        assert_eq!(cached.users.total, 2);
+        assert_eq!(cached.generated_at, stats.generated_at);
+    }
+
+    #[test]
+    fn test_failure_rate() {
+        assert_eq!(failure_rate(0, 0), 0.0);
+        assert_eq!(failure_rate(4, 1), 0.25);
+    }
+}
@@ -0,0 +1,191 @@
+//! ECDSA over NIST P-256 for authorities whose keys live in HSMs or smartcards
+//! that don't support secp256k1. Mirrors the signing half of `CryptoService`.
+//!
+//! P-256 signatures can be stored and verified, but not proven: the Noir
+//! circuits only verify secp256k1 signatures.
+
+use p256::ecdsa::signature::hazmat::{PrehashSigner, PrehashVerifier};
+use p256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use p256::pkcs8::DecodePublicKey;
+
+use crate::errors::AppError;
+use crate::models::HealthRecord;
+use crate::services::crypto::HealthRecordSignature;
+use crate::services::message_format::{self, SignableFields, DEFAULT_MESSAGE_FORMAT_VERSION};
+
+pub struct P256Service {
+    message_format_version: i32,
+}
+
+impl P256Service {
+    pub fn new() -> Self {
+        Self {
+            message_format_version: DEFAULT_MESSAGE_FORMAT_VERSION,
+        }
+    }
+
+    /// Sign new records with the given message format version
+    pub fn with_message_format_version(mut self, version: i32) -> Result<Self, AppError> {
+        message_format::formatter(version)?;
+        self.message_format_version = version;
+        Ok(self)
+    }
+
+    /// Generate a P-256 ECDSA signature for a health record
+    pub fn sign_health_record(&self, fields: &SignableFields, private_key: &SigningKey) -> Result<HealthRecordSignature, AppError> {
+        let formatter = message_format::formatter(self.message_format_version)?;
+        let message_str = formatter.format(fields);
+        let msg_hash_array = formatter.message_hash(fields);
+
+        let (signature_r, signature_s) = self.sign_message_hash(&msg_hash_array, private_key)?;
+
+        Ok(HealthRecordSignature {
+            message_hash: msg_hash_array.to_vec(),
+            signature_r,
+            signature_s,
+            original_message: message_str,
+            format_version: formatter.version(),
+        })
+    }
+
+    /// Sign an already computed message hash, returning the low-S `(r, s)` pair
+    pub fn sign_message_hash(&self, message_hash: &[u8], private_key: &SigningKey) -> Result<(Vec<u8>, Vec<u8>), AppError> {
+        if message_hash.len() != 32 {
+            return Err(AppError::InternalServerError("Failed to create message from hash".to_string()));
+        }
+
+        let signature: Signature = private_key
+            .sign_prehash(message_hash)
+            .map_err(|_| AppError::CryptographicError("Failed to sign message hash".to_string()))?;
+        let signature = signature.normalize_s().unwrap_or(signature);
+
+        let signature_bytes = signature.to_bytes();
+        Ok((signature_bytes[0..32].to_vec(), signature_bytes[32..64].to_vec()))
+    }
+
+    /// Verify a P-256 ECDSA signature for a health record
+    pub fn verify_health_record_signature(
+        &self,
+        health_record: &HealthRecord,
+        public_key: &VerifyingKey,
+    ) -> Result<bool, AppError> {
+        self.verify_message_signature(
+            &health_record.message_hash,
+            &health_record.signature_r,
+            &health_record.signature_s,
+            public_key,
+        )
+    }
+
+    /// Verify an `(r, s)` signature over a message hash
+    pub fn verify_message_signature(
+        &self,
+        message_hash: &[u8],
+        signature_r: &[u8],
+        signature_s: &[u8],
+        public_key: &VerifyingKey,
+    ) -> Result<bool, AppError> {
+        if message_hash.len() != 32 {
+            return Err(AppError::InternalServerError("Invalid message hash".to_string()));
+        }
+        if signature_r.len() != 32 || signature_s.len() != 32 {
+            return Err(AppError::InternalServerError("Invalid signature format".to_string()));
+        }
+        let mut signature_bytes = [0u8; 64];
+        signature_bytes[0..32].copy_from_slice(signature_r);
+        signature_bytes[32..64].copy_from_slice(signature_s);
+
+        let signature = Signature::from_slice(&signature_bytes)
+            .map_err(|_| AppError::InternalServerError("Invalid signature format".to_string()))?;
+
+        Ok(public_key.verify_prehash(message_hash, &signature).is_ok())
+    }
+
+    /// Parse a SEC1-encoded public key from hex string
+    pub fn parse_public_key(&self, public_key_hex: &str) -> Result<VerifyingKey, AppError> {
+        let key_bytes = hex::decode(public_key_hex.trim_start_matches("0x"))
+            .map_err(|_| AppError::BadRequest("Invalid public key hex format".to_string()))?;
+
+        VerifyingKey::from_sec1_bytes(&key_bytes)
+            .map_err(|_| AppError::BadRequest("Invalid P-256 public key".to_string()))
+    }
+
+    /// Parse a `PUBLIC KEY` (SPKI) PEM block holding a P-256 key
+    pub fn public_key_from_pem(&self, public_key_pem: &str) -> Result<VerifyingKey, AppError> {
+        VerifyingKey::from_public_key_pem(public_key_pem.trim())
+            .map_err(|_| AppError::BadRequest("Invalid P-256 PEM public key".to_string()))
+    }
+
+    /// Parse private key from hex string
+    pub fn parse_private_key(&self, private_key_hex: &str) -> Result<SigningKey, AppError> {
+        let key_bytes = hex::decode(private_key_hex.trim_start_matches("0x"))
+            .map_err(|_| AppError::BadRequest("Invalid private key hex format".to_string()))?;
+
+        SigningKey::from_slice(&key_bytes)
+            .map_err(|_| AppError::BadRequest("Invalid P-256 private key".to_string()))
+    }
+
+    /// Generate a new key pair
+    pub fn generate_key_pair(&self) -> (SigningKey, VerifyingKey) {
+        let signing_key = SigningKey::random(&mut rand::rngs::OsRng);
+        let verifying_key = *signing_key.verifying_key();
+        (signing_key, verifying_key)
+    }
+}
+
+impl Default for P256Service {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::HealthRecordType;
+
+    #[test]
+    fn test_p256_signing_and_verification() {
+        let p256_service = P256Service::new();
+        let (private_key, public_key) = p256_service.generate_key_pair();
+
+        let signature = p256_service
+            .sign_health_record(
+                &SignableFields {
+                    record_type: &HealthRecordType::Vaccination,
+                    patient_identifier: "Patient123",
+                    details: "COVID19_Dose1",
+                    issue_date: "2025",
+                    issuer: "HealthAuthority",
+                    expiry_date: None,
+                },
+                &private_key,
+            )
+            .unwrap();
+        assert_eq!(signature.signature_r.len(), 32);
+        assert_eq!(signature.signature_s.len(), 32);
+        assert!(p256_service
+            .verify_message_signature(&signature.message_hash, &signature.signature_r, &signature.signature_s, &public_key)
+            .unwrap());
+
+        // Keys round-trip through their hex encodings
+        let public_hex = hex::encode(public_key.to_encoded_point(true).as_bytes());
+        let private_hex = hex::encode(private_key.to_bytes());
+        assert_eq!(p256_service.parse_public_key(&public_hex).unwrap(), public_key);
+        assert_eq!(p256_service.parse_private_key(&private_hex).unwrap(), private_key);
+
+        let (_, other_public) = p256_service.generate_key_pair();
+        assert!(!p256_service
+            .verify_message_signature(&signature.message_hash, &signature.signature_r, &signature.signature_s, &other_public)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_secp256k1_key_is_not_a_p256_key() {
+        let (_, k1_public) = crate::services::CryptoService::new().generate_key_pair();
+        // A secp256k1 point is not on P-256
+        assert!(P256Service::new()
+            .parse_public_key(&hex::encode(k1_public.serialize_uncompressed()))
+            .is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Email notifications to record owners, sent over SMTP.
+//!
+//! Sending happens on a background task: a slow or failing mail server is
+//! logged and never fails the request that triggered the notification.
+
+use lettre::{
+    message::Mailbox,
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::i18n::Locale;
+use crate::models::HealthRecordType;
+
+/// SMTP server and sender settings
+pub struct SmtpSettings<'a> {
+    pub host: &'a str,
+    pub port: u16,
+    pub username: Option<&'a str>,
+    pub password: Option<&'a str>,
+    /// `From` address, e.g. `VeryFi <no-reply@example.com>`
+    pub from: &'a str,
+}
+
+/// What happened to a record, as told to its owner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordNotification {
+    Issued,
+    Revoked,
+}
+
+impl RecordNotification {
+    /// Subject and plain-text body of the email to `full_name`, in `locale`
+    pub fn render(
+        &self,
+        locale: Locale,
+        full_name: &str,
+        record_type: &HealthRecordType,
+        authority_name: &str,
+    ) -> (String, String) {
+        let record = locale.text(record_type_label(record_type), &[]);
+        match self {
+            RecordNotification::Issued => (
+                locale.text("Your {} has been issued", &[&record]),
+                locale.text(
+                    "Hello {},\n\n{} has signed your {}. It is now available in your account, and you can generate proofs from it.\n",
+                    &[full_name, authority_name, &record],
+                ),
+            ),
+            RecordNotification::Revoked => (
+                locale.text("Your {} has been revoked", &[&record]),
+                locale.text(
+                    "Hello {},\n\n{} has revoked your {}. Proofs generated from it no longer verify. Contact {} if you believe this is a mistake.\n",
+                    &[full_name, authority_name, &record, authority_name],
+                ),
+            ),
+        }
+    }
+}
+
+fn record_type_label(record_type: &HealthRecordType) -> &'static str {
+    match record_type {
+        HealthRecordType::Vaccination => "vaccination record",
+        HealthRecordType::TestResult => "test result",
+        HealthRecordType::MedicalClearance => "medical clearance",
+        HealthRecordType::ImmunityProof => "immunity proof",
+    }
+}
+
+#[derive(Clone)]
+pub struct EmailService {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl EmailService {
+    /// SMTP over STARTTLS (implicit TLS on port 465)
+    pub fn smtp(settings: SmtpSettings) -> anyhow::Result<Self> {
+        let from = settings
+            .from
+            .parse::<Mailbox>()
+            .map_err(|e| anyhow::anyhow!("Invalid SMTP from address {}: {}", settings.from, e))?;
+
+        let builder = if settings.port == 465 {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(settings.host)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(settings.host)?
+        };
+        let mut builder = builder.port(settings.port);
+        if let (Some(username), Some(password)) = (settings.username, settings.password) {
+            builder = builder.credentials(Credentials::new(username.to_string(), password.to_string()));
+        }
+
+        Ok(Self { transport: builder.build(), from })
+    }
+
+    /// Send a plain-text email on a background task; failures are only logged
+    pub fn send_in_background(&self, to: &str, subject: String, body: String) {
+        let to_mailbox = match to.parse::<Mailbox>() {
+            Ok(mailbox) => mailbox,
+            Err(e) => {
+                tracing::warn!("Not emailing invalid address {}: {}", to, e);
+                return;
+            }
+        };
+        let message = match Message::builder().from(self.from.clone()).to(to_mailbox).subject(subject).body(body) {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!("Failed to build email to {}: {}", to, e);
+                return;
+            }
+        };
+
+        let transport = self.transport.clone();
+        let to = to.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = transport.send(message).await {
+                tracing::warn!("Failed to send email to {}: {}", to, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notifications_name_the_record_and_authority() {
+        let (subject, body) = RecordNotification::Issued.render(Locale::En, "Ada", &HealthRecordType::TestResult, "City Clinic");
+        assert_eq!(subject, "Your test result has been issued");
+        assert!(body.starts_with("Hello Ada,"));
+        assert!(body.contains("City Clinic has signed your test result"));
+
+        let (subject, body) = RecordNotification::Revoked.render(Locale::En, "Ada", &HealthRecordType::Vaccination, "City Clinic");
+        assert_eq!(subject, "Your vaccination record has been revoked");
+        assert!(body.contains("no longer verify"));
+    }
+
+    #[test]
+    fn test_notifications_are_sent_in_the_owners_locale() {
+        let (subject, body) = RecordNotification::Revoked.render(Locale::Es, "Ada", &HealthRecordType::Vaccination, "City Clinic");
+        assert_eq!(subject, "Se ha revocado su registro de vacunación");
+        assert!(body.starts_with("Hola, Ada:"));
+        assert!(body.contains("City Clinic ha revocado su registro de vacunación"));
+        assert!(body.contains("en contacto con City Clinic"));
+    }
+}
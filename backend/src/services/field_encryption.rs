@@ -0,0 +1,607 @@
+//! Envelope encryption for the sensitive fields of a health record.
+//!
+//! Each record's `patient_identifier` and `details` are encrypted with a fresh
+//! AES-256-GCM data key, and that data key is stored wrapped with the master
+//! key from `FIELD_ENCRYPTION_KEY`. Rotating the master key only re-wraps data
+//! keys; the record ciphertext is left alone. Each row keeps the ID of the
+//! master key that wrapped it, so rows under a retired key stay readable while
+//! the re-encryption job catches up.
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use hmac::{Hmac, Mac};
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{EncryptionKeyUsage, FieldEncryptionStatus};
+
+const NONCE_LEN: usize = 12;
+
+/// Records read and rewritten per query by the re-encryption job
+pub const REENCRYPT_BATCH_SIZE: i64 = 500;
+
+/// Placeholder left in `patient_identifier` once it is encrypted
+pub const REDACTED_PATIENT_IDENTIFIER: &str = "";
+
+/// The record fields that are encrypted at rest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensitiveFields {
+    pub patient_identifier: String,
+    pub details: serde_json::Value,
+}
+
+impl SensitiveFields {
+    /// What is stored in the plaintext columns of an encrypted record
+    pub fn redacted() -> Self {
+        Self {
+            patient_identifier: REDACTED_PATIENT_IDENTIFIER.to_string(),
+            details: serde_json::json!({}),
+        }
+    }
+}
+
+/// The encrypted columns of a health record
+#[derive(Debug, Clone)]
+pub struct EncryptedFields {
+    pub ciphertext: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub wrapped_key: Vec<u8>, // Nonce followed by the data key encrypted with the master key
+    pub key_id: String,
+}
+
+impl EncryptedFields {
+    /// Assemble from nullable columns; `None` means the record is stored in plaintext
+    pub fn from_columns(
+        ciphertext: Option<Vec<u8>>,
+        nonce: Option<Vec<u8>>,
+        wrapped_key: Option<Vec<u8>>,
+        key_id: Option<String>,
+    ) -> Option<Self> {
+        Some(Self {
+            ciphertext: ciphertext?,
+            nonce: nonce?,
+            wrapped_key: wrapped_key?,
+            key_id: key_id?,
+        })
+    }
+}
+
+/// Master keys used to wrap per-record data keys
+#[derive(Clone)]
+pub struct FieldCipher {
+    current: MasterKey,
+    previous: HashMap<String, MasterKey>,
+    fingerprint_key: Vec<u8>,
+}
+
+#[derive(Clone)]
+struct MasterKey {
+    id: String,
+    cipher: Aes256Gcm,
+    bytes: Vec<u8>,
+}
+
+impl MasterKey {
+    fn from_base64(key: &str) -> Result<Self, AppError> {
+        let bytes = general_purpose::STANDARD
+            .decode(key.trim())
+            .map_err(|_| AppError::CryptographicError("Field encryption key must be base64".to_string()))?;
+        let cipher = Aes256Gcm::new_from_slice(&bytes)
+            .map_err(|_| AppError::CryptographicError("Field encryption key must be 32 bytes".to_string()))?;
+
+        // Short fingerprint so stored rows record which key wrapped them
+        let id = hex::encode(&Sha256::digest(&bytes)[..8]);
+
+        Ok(Self { id, cipher, bytes })
+    }
+}
+
+impl FieldCipher {
+    /// `key` encrypts new records; `previous_keys` are only used to read older ones
+    pub fn new(key: &str, previous_keys: &[String]) -> Result<Self, AppError> {
+        let current = MasterKey::from_base64(key)?;
+        let previous = previous_keys
+            .iter()
+            .map(|key| MasterKey::from_base64(key).map(|master| (master.id.clone(), master)))
+            .collect::<Result<_, _>>()?;
+
+        // Separate key for fingerprints, so they never reuse the wrapping key directly
+        let fingerprint_key = Sha256::new()
+            .chain_update(b"health-record-fingerprint:")
+            .chain_update(&current.bytes)
+            .finalize()
+            .to_vec();
+
+        Ok(Self { current, previous, fingerprint_key })
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.current.id
+    }
+
+    /// Whether rows wrapped by `key_id` can be read with the configured keys
+    pub fn has_key(&self, key_id: &str) -> bool {
+        key_id == self.current.id || self.previous.contains_key(key_id)
+    }
+
+    pub fn encrypt(&self, fields: &SensitiveFields) -> Result<EncryptedFields, AppError> {
+        let plaintext = serde_json::to_vec(fields)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to serialize record fields: {}", e)))?;
+
+        let data_key = Aes256Gcm::generate_key(OsRng);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(&data_key)
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(|_| AppError::CryptographicError("Failed to encrypt record fields".to_string()))?;
+
+        Ok(EncryptedFields {
+            ciphertext,
+            nonce: nonce.to_vec(),
+            wrapped_key: seal(&self.current.cipher, &data_key)?,
+            key_id: self.current.id.clone(),
+        })
+    }
+
+    pub fn decrypt(&self, encrypted: &EncryptedFields) -> Result<SensitiveFields, AppError> {
+        let data_key = open(self.master_key(&encrypted.key_id)?, &encrypted.wrapped_key)?;
+        let data_cipher = Aes256Gcm::new_from_slice(&data_key)
+            .map_err(|_| AppError::CryptographicError("Corrupt record data key".to_string()))?;
+
+        if encrypted.nonce.len() != NONCE_LEN {
+            return Err(AppError::CryptographicError("Corrupt record nonce".to_string()));
+        }
+        let plaintext = data_cipher
+            .decrypt(Nonce::from_slice(&encrypted.nonce), encrypted.ciphertext.as_slice())
+            .map_err(|_| AppError::CryptographicError("Failed to decrypt record fields".to_string()))?;
+
+        serde_json::from_slice(&plaintext)
+            .map_err(|e| AppError::CryptographicError(format!("Corrupt record fields: {}", e)))
+    }
+
+    /// Wrap the record's data key with the current master key, leaving the ciphertext as is
+    pub fn rewrap(&self, encrypted: &EncryptedFields) -> Result<EncryptedFields, AppError> {
+        let data_key = open(self.master_key(&encrypted.key_id)?, &encrypted.wrapped_key)?;
+
+        Ok(EncryptedFields {
+            wrapped_key: seal(&self.current.cipher, &data_key)?,
+            key_id: self.current.id.clone(),
+            ..encrypted.clone()
+        })
+    }
+
+    /// Keyed digest of record content, so stored fingerprints cannot be
+    /// brute-forced back to a patient identifier
+    pub fn fingerprint(&self, content: &[u8]) -> Vec<u8> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.fingerprint_key).expect("HMAC accepts keys of any length");
+        mac.update(content);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn master_key(&self, key_id: &str) -> Result<&Aes256Gcm, AppError> {
+        if key_id == self.current.id {
+            return Ok(&self.current.cipher);
+        }
+
+        self.previous
+            .get(key_id)
+            .map(|master| &master.cipher)
+            .ok_or_else(|| AppError::CryptographicError(format!("Unknown field encryption key {}", key_id)))
+    }
+}
+
+/// `nonce || ciphertext` of `plaintext` under `cipher`
+fn seal(cipher: &Aes256Gcm, plaintext: &[u8]) -> Result<Vec<u8>, AppError> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| AppError::CryptographicError("Failed to wrap record data key".to_string()))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    Ok(sealed)
+}
+
+fn open(cipher: &Aes256Gcm, sealed: &[u8]) -> Result<Vec<u8>, AppError> {
+    if sealed.len() < NONCE_LEN {
+        return Err(AppError::CryptographicError("Corrupt record data key".to_string()));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| AppError::CryptographicError("Failed to unwrap record data key".to_string()))
+}
+
+#[derive(Debug, Default)]
+pub struct ReencryptSummary {
+    pub encrypted: u64,
+    pub rewrapped: u64,
+    pub failed: u64,
+}
+
+#[derive(sqlx::FromRow)]
+struct StoredFields {
+    id: Uuid,
+    patient_identifier: String,
+    details: serde_json::Value,
+    encrypted_fields: Option<Vec<u8>>,
+    encryption_nonce: Option<Vec<u8>>,
+    encrypted_data_key: Option<Vec<u8>>,
+    encryption_key_id: Option<String>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+/// Encrypt plaintext records and re-wrap records whose data key was wrapped by
+/// an older master key. Rows that cannot be read are logged and skipped, and
+/// rows updated while the job runs are left to whoever updated them.
+pub async fn reencrypt_records(db: &PgPool, cipher: &FieldCipher, batch_size: i64) -> Result<ReencryptSummary, AppError> {
+    let mut summary = ReencryptSummary::default();
+    let mut after = Uuid::nil();
+
+    loop {
+        let rows = sqlx::query_as::<_, StoredFields>(
+            r#"
+            SELECT id, patient_identifier, details, encrypted_fields, encryption_nonce, encrypted_data_key, encryption_key_id, updated_at
+            FROM health_records
+            WHERE id > $1 AND (encrypted_fields IS NULL OR encryption_key_id IS DISTINCT FROM $2)
+            ORDER BY id
+            LIMIT $3
+            "#
+        )
+        .bind(after)
+        .bind(cipher.key_id())
+        .bind(batch_size)
+        .fetch_all(db)
+        .await?;
+
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after = last.id;
+
+        for row in rows {
+            let existing = EncryptedFields::from_columns(
+                row.encrypted_fields,
+                row.encryption_nonce,
+                row.encrypted_data_key,
+                row.encryption_key_id,
+            );
+            let sealed = match &existing {
+                Some(encrypted) => cipher.rewrap(encrypted),
+                None => cipher.encrypt(&SensitiveFields {
+                    patient_identifier: row.patient_identifier,
+                    details: row.details,
+                }),
+            };
+
+            let sealed = match sealed {
+                Ok(sealed) => sealed,
+                Err(e) => {
+                    tracing::warn!("Skipping health record {}: {}", row.id, e);
+                    summary.failed += 1;
+                    continue;
+                }
+            };
+
+            // Only overwrite the version that was read; the server keeps
+            // serving writes while the job runs
+            let redacted = SensitiveFields::redacted();
+            let result = sqlx::query(
+                r#"
+                UPDATE health_records
+                SET patient_identifier = $1, details = $2, encrypted_fields = $3, encryption_nonce = $4,
+                    encrypted_data_key = $5, encryption_key_id = $6
+                WHERE id = $7 AND updated_at IS NOT DISTINCT FROM $8
+                "#
+            )
+            .bind(&redacted.patient_identifier)
+            .bind(&redacted.details)
+            .bind(&sealed.ciphertext)
+            .bind(&sealed.nonce)
+            .bind(&sealed.wrapped_key)
+            .bind(&sealed.key_id)
+            .bind(row.id)
+            .bind(row.updated_at)
+            .execute(db)
+            .await?;
+
+            if result.rows_affected() == 0 {
+                tracing::debug!("Health record {} changed during re-encryption, skipped", row.id);
+            } else if existing.is_some() {
+                summary.rewrapped += 1;
+            } else {
+                summary.encrypted += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// How many stored records each master key wrapped, and how many still need
+/// the re-encryption job
+pub async fn encryption_status(db: &PgPool, cipher: &FieldCipher, reencrypt_running: bool) -> Result<FieldEncryptionStatus, AppError> {
+    let counts = sqlx::query_as::<_, (Option<String>, i64)>(
+        r#"
+        SELECT encryption_key_id, COUNT(*) FROM health_records
+        GROUP BY encryption_key_id
+        ORDER BY encryption_key_id NULLS FIRST
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let pending_records = counts
+        .iter()
+        .filter(|(key_id, _)| key_id.as_deref() != Some(cipher.key_id()))
+        .map(|(_, records)| records)
+        .sum();
+    let keys = counts
+        .into_iter()
+        .map(|(key_id, records)| EncryptionKeyUsage {
+            configured: key_id.as_deref().map_or(true, |key_id| cipher.has_key(key_id)),
+            key_id,
+            records,
+        })
+        .collect();
+
+    Ok(FieldEncryptionStatus {
+        current_key_id: cipher.key_id().to_string(),
+        keys,
+        pending_records,
+        reencrypt_running,
+    })
+}
+
+/// Re-encryption started through the API, running in the background. At most
+/// one job runs per instance.
+#[derive(Clone, Default)]
+pub struct ReencryptJob {
+    running: Arc<AtomicBool>,
+}
+
+impl ReencryptJob {
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn start(&self, db: PgPool, cipher: FieldCipher) -> Result<(), AppError> {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Err(AppError::Conflict("A re-encryption job is already running".to_string()));
+        }
+
+        let running = self.running.clone();
+        tokio::spawn(async move {
+            match reencrypt_records(&db, &cipher, REENCRYPT_BATCH_SIZE).await {
+                Ok(summary) => tracing::info!(
+                    "Re-encryption finished: {} encrypted, {} re-wrapped, {} failed",
+                    summary.encrypted,
+                    summary.rewrapped,
+                    summary.failed
+                ),
+                Err(e) => tracing::error!("Re-encryption failed: {}", e),
+            }
+            running.store(false, Ordering::SeqCst);
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> String {
+        general_purpose::STANDARD.encode([byte; 32])
+    }
+
+    fn fields() -> SensitiveFields {
+        SensitiveFields {
+            patient_identifier: "Patient123".to_string(),
+            details: serde_json::json!({"vaccine_name": "Pfizer"}),
+        }
+    }
+
+    #[test]
+    fn test_fields_round_trip_and_ciphertext_hides_them() {
+        let cipher = FieldCipher::new(&key(1), &[]).unwrap();
+        let encrypted = cipher.encrypt(&fields()).unwrap();
+
+        assert_eq!(encrypted.key_id, cipher.key_id());
+        assert!(!String::from_utf8_lossy(&encrypted.ciphertext).contains("Patient123"));
+        assert_eq!(cipher.decrypt(&encrypted).unwrap(), fields());
+
+        // Fresh data key and nonce per record
+        let again = cipher.encrypt(&fields()).unwrap();
+        assert_ne!(again.ciphertext, encrypted.ciphertext);
+
+        let mut tampered = encrypted.clone();
+        tampered.ciphertext[0] ^= 1;
+        assert!(cipher.decrypt(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_rotation_rewraps_with_the_new_key() {
+        let old = FieldCipher::new(&key(1), &[]).unwrap();
+        let encrypted = old.encrypt(&fields()).unwrap();
+
+        let rotated = FieldCipher::new(&key(2), &[key(1)]).unwrap();
+        assert_eq!(rotated.decrypt(&encrypted).unwrap(), fields());
+
+        let rewrapped = rotated.rewrap(&encrypted).unwrap();
+        assert_eq!(rewrapped.key_id, rotated.key_id());
+        assert_eq!(rewrapped.ciphertext, encrypted.ciphertext);
+
+        // Once re-wrapped, the old key is no longer needed
+        let new_only = FieldCipher::new(&key(2), &[]).unwrap();
+        assert_eq!(new_only.decrypt(&rewrapped).unwrap(), fields());
+        assert!(new_only.decrypt(&encrypted).is_err());
+    }
+
+    #[test]
+    fn test_invalid_keys_are_rejected() {
+        assert!(FieldCipher::new("not base64!", &[]).is_err());
+        assert!(FieldCipher::new(&general_purpose::STANDARD.encode([0u8; 16]), &[]).is_err());
+    }
+
+    async fn seed_record(db: &PgPool, user_id: Uuid, authority_id: Uuid, patient_identifier: &str) -> Uuid {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO health_records (user_id, authority_id, record_type, patient_identifier, details, issue_date, signature_r, signature_s, message_hash)
+            VALUES ($1, $2, 'vaccination', $3, '{"vaccine_name": "Pfizer"}', CURRENT_DATE, '\x01', '\x01', '\x01')
+            RETURNING id
+            "#
+        )
+        .bind(user_id)
+        .bind(authority_id)
+        .bind(patient_identifier)
+        .fetch_one(db)
+        .await
+        .unwrap()
+    }
+
+    async fn key_id_of(db: &PgPool, record_id: Uuid) -> Option<String> {
+        sqlx::query_scalar("SELECT encryption_key_id FROM health_records WHERE id = $1")
+            .bind(record_id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    /// Wait until some query is blocked on a row lock
+    async fn wait_for_lock_wait(db: &PgPool) {
+        for _ in 0..500 {
+            let waiting: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM pg_stat_activity WHERE wait_event_type = 'Lock' AND datname = current_database())"
+            )
+            .fetch_one(db)
+            .await
+            .unwrap();
+            if waiting {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("No query blocked on the row lock");
+    }
+
+    /// Open a transaction that has updated `record_id` without committing
+    async fn hold_update(db: &PgPool, record_id: Uuid) -> sqlx::Transaction<'static, sqlx::Postgres> {
+        let mut tx = db.begin().await.unwrap();
+        sqlx::query("UPDATE health_records SET details = details WHERE id = $1")
+            .bind(record_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        tx
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_reencryption_rewraps_rows_and_skips_concurrent_updates(db: PgPool) {
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name) VALUES ('owner@example.com', 'x', 'Owner') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key) VALUES ('Clinic', 'clinic', '02') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let first = seed_record(&db, user_id, authority_id, "Patient1").await;
+        let second = seed_record(&db, user_id, authority_id, "Patient2").await;
+
+        // Plaintext rows are encrypted under v1, a batch at a time
+        let v1 = FieldCipher::new(&key(1), &[]).unwrap();
+        let summary = reencrypt_records(&db, &v1, 1).await.unwrap();
+        assert_eq!((summary.encrypted, summary.rewrapped, summary.failed), (2, 0, 0));
+        assert_eq!(key_id_of(&db, first).await.as_deref(), Some(v1.key_id()));
+        let patient_identifier: String = sqlx::query_scalar("SELECT patient_identifier FROM health_records WHERE id = $1")
+            .bind(first)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(patient_identifier, REDACTED_PATIENT_IDENTIFIER);
+
+        let v2 = FieldCipher::new(&key(2), &[key(1)]).unwrap();
+        let status = encryption_status(&db, &v2, false).await.unwrap();
+        assert_eq!(status.current_key_id, v2.key_id());
+        assert_eq!(status.pending_records, 2);
+        assert_eq!(status.keys.len(), 1);
+        assert_eq!(status.keys[0].key_id.as_deref(), Some(v1.key_id()));
+        assert_eq!(status.keys[0].records, 2);
+        assert!(status.keys[0].configured);
+
+        // `second` is updated while the job runs, so the job leaves it alone
+        let tx = hold_update(&db, second).await;
+        let job = tokio::spawn({
+            let (db, v2) = (db.clone(), v2.clone());
+            async move { reencrypt_records(&db, &v2, REENCRYPT_BATCH_SIZE).await }
+        });
+        wait_for_lock_wait(&db).await;
+        tx.commit().await.unwrap();
+        let summary = job.await.unwrap().unwrap();
+        assert_eq!((summary.encrypted, summary.rewrapped, summary.failed), (0, 1, 0));
+        assert_eq!(key_id_of(&db, first).await.as_deref(), Some(v2.key_id()));
+        assert_eq!(key_id_of(&db, second).await.as_deref(), Some(v1.key_id()));
+        assert_eq!(encryption_status(&db, &v2, false).await.unwrap().pending_records, 1);
+
+        // The re-wrapped row no longer needs v1
+        let row = sqlx::query_as::<_, StoredFields>(
+            r#"
+            SELECT id, patient_identifier, details, encrypted_fields, encryption_nonce, encrypted_data_key, encryption_key_id, updated_at
+            FROM health_records WHERE id = $1
+            "#
+        )
+        .bind(first)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let encrypted = EncryptedFields::from_columns(
+            row.encrypted_fields,
+            row.encryption_nonce,
+            row.encrypted_data_key,
+            row.encryption_key_id,
+        )
+        .unwrap();
+        let v2_only = FieldCipher::new(&key(2), &[]).unwrap();
+        assert_eq!(v2_only.decrypt(&encrypted).unwrap().patient_identifier, "Patient1");
+
+        // Only one background job at a time; held on a row lock so it is still running
+        let reencrypt_job = ReencryptJob::default();
+        let tx = hold_update(&db, second).await;
+        reencrypt_job.start(db.clone(), v2.clone()).unwrap();
+        assert!(reencrypt_job.is_running());
+        assert!(matches!(reencrypt_job.start(db.clone(), v2.clone()), Err(AppError::Conflict(_))));
+        assert!(encryption_status(&db, &v2, reencrypt_job.is_running()).await.unwrap().reencrypt_running);
+        wait_for_lock_wait(&db).await;
+        tx.commit().await.unwrap();
+        while reencrypt_job.is_running() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        // Once finished, another job can start and catch up on the skipped row
+        reencrypt_job.start(db.clone(), v2.clone()).unwrap();
+        while reencrypt_job.is_running() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(key_id_of(&db, second).await.as_deref(), Some(v2.key_id()));
+        let status = encryption_status(&db, &v2, false).await.unwrap();
+        assert_eq!(status.pending_records, 0);
+        assert_eq!(status.keys.len(), 1);
+        assert_eq!(status.keys[0].records, 2);
+    }
+}
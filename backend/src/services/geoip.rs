@@ -0,0 +1,90 @@
+//! Country and region of verification requests, from a MaxMind GeoIP2/GeoLite2
+//! City database on disk.
+
+use std::net::IpAddr;
+use std::path::Path;
+
+use maxminddb::{geoip2, Reader};
+
+/// Where an address is, as far as the database knows
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeoLocation {
+    /// ISO 3166-1 alpha-2 country code
+    pub country_code: Option<String>,
+    /// English name of the first-level subdivision (state, province)
+    pub region: Option<String>,
+}
+
+pub struct GeoIpService {
+    reader: Reader<Vec<u8>>,
+}
+
+impl GeoIpService {
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let reader = Reader::open_readfile(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open GeoIP database {}: {}", path.display(), e))?;
+        Ok(Self { reader })
+    }
+
+    /// Location of a public address; `None` for private, loopback and other
+    /// non-routable addresses, and for addresses the database doesn't cover
+    pub fn lookup(&self, ip: IpAddr) -> Option<GeoLocation> {
+        if !is_public(ip) {
+            return None;
+        }
+
+        let city: geoip2::City = self.reader.lookup(ip).ok()?;
+        let location = GeoLocation {
+            country_code: city.country.and_then(|country| country.iso_code).map(str::to_string),
+            region: city
+                .subdivisions
+                .and_then(|subdivisions| subdivisions.into_iter().next())
+                .and_then(|subdivision| subdivision.names)
+                .and_then(|names| names.get("en").copied())
+                .map(str::to_string),
+        };
+
+        (location != GeoLocation::default()).then_some(location)
+    }
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                // 100.64.0.0/10, carrier-grade NAT
+                || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || first & 0xfe00 == 0xfc00 // Unique local
+                    || first & 0xffc0 == 0xfe80) // Link-local
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_routable_addresses_are_not_looked_up() {
+        for ip in ["10.1.2.3", "192.168.0.1", "127.0.0.1", "100.64.0.1", "::1", "fd00::1", "fe80::1", "::ffff:10.0.0.1"] {
+            assert!(!is_public(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["8.8.8.8", "2001:4860:4860::8888", "::ffff:8.8.8.8"] {
+            assert!(is_public(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+}
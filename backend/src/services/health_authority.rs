@@ -0,0 +1,573 @@
+//! Bulk import and `did:web` identities for health authorities.
+//!
+//! Each authority is `did:web:<host>:authorities:<id>`, which resolvers fetch
+//! from `https://<host>/authorities/<id>/did.json`. The document carries the
+//! authority's stored public key as a JSON Web Key, so credentials naming the
+//! DID as issuer can be checked against it.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Months, Utc};
+use sqlx::{Acquire, PgPool};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{
+    success_rate, AuthorityCsvRow, AuthorityImportResponse, AuthorityImportRow, AuthorityStats, AuthorityStatsPeriod,
+    AuthorityStatsQuery, DidDocument, HealthAuthority, ImportRowStatus, PublicKeyJwk, SigningCurve, StatsInterval,
+    VerificationMethod, validate_public_key_format,
+};
+use crate::services::crypto::{is_pem, CryptoService};
+
+const DID_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+const JWS_2020_CONTEXT: &str = "https://w3id.org/security/suites/jws-2020/v1";
+
+/// Most data rows accepted in one CSV import
+pub const MAX_IMPORT_ROWS: usize = 1000;
+
+/// Header an authority CSV must start with
+const IMPORT_CSV_HEADER: [&str; 4] = ["name", "authority_type", "public_key", "certificate"];
+
+#[derive(Clone)]
+pub struct HealthAuthorityService {
+    db: PgPool,
+    crypto: Arc<CryptoService>,
+    /// Method-specific part of `did:web` DIDs, e.g. `pass.example.com` or `localhost%3A3000`
+    did_web_domain: String,
+}
+
+impl HealthAuthorityService {
+    pub fn new(db: PgPool, crypto: Arc<CryptoService>, public_base_url: &str) -> Result<Self, AppError> {
+        Ok(Self { db, crypto, did_web_domain: did_web_domain(public_base_url)? })
+    }
+
+    /// Create secp256k1 authorities in `org_id` from CSV rows of
+    /// `name,authority_type,public_key,certificate`. Unless `partial`, a single
+    /// invalid row leaves the whole file unimported.
+    pub async fn import_authorities(&self, org_id: Uuid, csv: &str, partial: bool) -> Result<AuthorityImportResponse, AppError> {
+        let invalid_csv = |e: csv::Error| AppError::BadRequest(format!("Invalid CSV: {}", e));
+        // Flexible, so a row may leave out the optional certificate column
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .from_reader(csv.as_bytes());
+
+        let header = reader.headers().map_err(invalid_csv)?.clone();
+        if header.iter().ne(IMPORT_CSV_HEADER) {
+            return Err(AppError::BadRequest(format!("CSV header must be {}", IMPORT_CSV_HEADER.join(","))));
+        }
+
+        let mut parsed = Vec::new();
+        let mut record = csv::StringRecord::new();
+        while reader.read_record(&mut record).map_err(invalid_csv)? {
+            if parsed.len() == MAX_IMPORT_ROWS {
+                return Err(AppError::BadRequest(format!("At most {} authorities can be imported at once", MAX_IMPORT_ROWS)));
+            }
+            let line = record.position().map_or(0, |position| position.line());
+            let row = record
+                .deserialize::<AuthorityCsvRow>(Some(&header))
+                .map_err(|e| format!("Invalid row: {}", e))
+                .and_then(|row| self.validate_import_row(row));
+            parsed.push((line, row));
+        }
+
+        let mut tx = self.db.begin().await?;
+        let mut rows = Vec::with_capacity(parsed.len());
+        for (line, row) in parsed {
+            let result = match row {
+                Ok(row) => {
+                    // Each row in its own savepoint, so a failed insert doesn't abort the rest
+                    let mut savepoint = tx.begin().await?;
+                    let inserted = sqlx::query_scalar::<_, Uuid>(
+                        r#"
+                        INSERT INTO health_authorities (name, authority_type, public_key, certificate, org_id, curve)
+                        VALUES ($1, $2, $3, $4, $5, $6)
+                        RETURNING id
+                        "#
+                    )
+                    .bind(&row.name)
+                    .bind(&row.authority_type)
+                    .bind(&row.public_key)
+                    .bind(&row.certificate)
+                    .bind(org_id)
+                    .bind(SigningCurve::Secp256k1)
+                    .fetch_one(&mut *savepoint)
+                    .await;
+                    match inserted {
+                        Ok(id) => {
+                            savepoint.commit().await?;
+                            Ok(id)
+                        }
+                        Err(e) => {
+                            savepoint.rollback().await?;
+                            Err(format!("Failed to insert: {}", e))
+                        }
+                    }
+                }
+                Err(error) => Err(error),
+            };
+
+            rows.push(match result {
+                Ok(id) => AuthorityImportRow { line, status: ImportRowStatus::Created, authority_id: Some(id), error: None },
+                Err(error) => AuthorityImportRow { line, status: ImportRowStatus::Invalid, authority_id: None, error: Some(error) },
+            });
+        }
+
+        let invalid = rows.iter().filter(|row| row.status == ImportRowStatus::Invalid).count();
+        if invalid > 0 && !partial {
+            tx.rollback().await?;
+            for row in rows.iter_mut().filter(|row| row.status == ImportRowStatus::Created) {
+                row.status = ImportRowStatus::NotImported;
+                row.authority_id = None;
+            }
+        } else {
+            tx.commit().await?;
+        }
+
+        let created = rows.iter().filter(|row| row.status == ImportRowStatus::Created).count();
+        Ok(AuthorityImportResponse { created, invalid, rows })
+    }
+
+    /// Records, proofs and verifications of an authority's records over a time range.
+    ///
+    /// `org_scope` hides authorities of other organizations, and a
+    /// `provider_scope` must be associated with the authority.
+    pub async fn statistics(
+        &self,
+        authority_id: Uuid,
+        query: &AuthorityStatsQuery,
+        provider_scope: Option<Uuid>,
+        org_scope: Option<Uuid>,
+    ) -> Result<AuthorityStats, AppError> {
+        if let (Some(from), Some(to)) = (query.from, query.to) {
+            if from > to {
+                return Err(AppError::BadRequest("`from` must not be after `to`".to_string()));
+            }
+        }
+
+        let exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM health_authorities WHERE id = $1 AND ($2::uuid IS NULL OR org_id = $2))"
+        )
+        .bind(authority_id)
+        .bind(org_scope)
+        .fetch_one(&self.db)
+        .await?;
+        if !exists {
+            return Err(AppError::NotFound("Health authority not found".to_string()));
+        }
+
+        if let Some(provider_id) = provider_scope {
+            let associated = sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(SELECT 1 FROM provider_authorities WHERE user_id = $1 AND authority_id = $2)"
+            )
+            .bind(provider_id)
+            .bind(authority_id)
+            .fetch_one(&self.db)
+            .await?;
+            if !associated {
+                return Err(AppError::Forbidden("Provider is not associated with this health authority".to_string()));
+            }
+        }
+
+        let interval = query.interval.unwrap_or_default();
+        let mut timeline: BTreeMap<DateTime<Utc>, AuthorityStatsPeriod> = BTreeMap::new();
+
+        // $1 authority, $2/$3 range, $4 date_trunc field; periods are cut in UTC
+        let records = sqlx::query_as::<_, (DateTime<Utc>, i64, i64)>(
+            r#"
+            SELECT date_trunc($4, created_at, 'UTC'), COUNT(*), COUNT(*) FILTER (WHERE is_revoked)
+            FROM health_records
+            WHERE authority_id = $1
+              AND ($2::timestamptz IS NULL OR created_at >= $2)
+              AND ($3::timestamptz IS NULL OR created_at < $3)
+            GROUP BY 1
+            "#
+        )
+        .bind(authority_id)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(interval.as_str())
+        .fetch_all(&self.db)
+        .await?;
+        for (period_start, issued, revoked) in records {
+            let period = timeline.entry(period_start).or_default();
+            period.records_issued = issued;
+            period.records_revoked = revoked;
+        }
+
+        let proofs = sqlx::query_as::<_, (DateTime<Utc>, i64)>(
+            r#"
+            SELECT date_trunc($4, zp.generated_at, 'UTC'), COUNT(*)
+            FROM zk_proofs zp
+            JOIN health_records hr ON zp.health_record_id = hr.id
+            WHERE hr.authority_id = $1
+              AND ($2::timestamptz IS NULL OR zp.generated_at >= $2)
+              AND ($3::timestamptz IS NULL OR zp.generated_at < $3)
+            GROUP BY 1
+            "#
+        )
+        .bind(authority_id)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(interval.as_str())
+        .fetch_all(&self.db)
+        .await?;
+        for (period_start, generated) in proofs {
+            timeline.entry(period_start).or_default().proofs_generated = generated;
+        }
+
+        let verifications = sqlx::query_as::<_, (DateTime<Utc>, i64, i64)>(
+            r#"
+            SELECT date_trunc($4, pv.verified_at, 'UTC'), COUNT(*), COUNT(*) FILTER (WHERE pv.verification_result)
+            FROM proof_verifications pv
+            JOIN zk_proofs zp ON pv.proof_id = zp.id
+            JOIN health_records hr ON zp.health_record_id = hr.id
+            WHERE hr.authority_id = $1
+              AND ($2::timestamptz IS NULL OR pv.verified_at >= $2)
+              AND ($3::timestamptz IS NULL OR pv.verified_at < $3)
+            GROUP BY 1
+            "#
+        )
+        .bind(authority_id)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(interval.as_str())
+        .fetch_all(&self.db)
+        .await?;
+        for (period_start, total, successful) in verifications {
+            let period = timeline.entry(period_start).or_default();
+            period.verifications = total;
+            period.successful_verifications = successful;
+        }
+
+        let timeline = fill_timeline(timeline, interval);
+        let sum = |field: fn(&AuthorityStatsPeriod) -> i64| timeline.iter().map(field).sum::<i64>();
+        let records_issued = sum(|period| period.records_issued);
+        let revoked_records = sum(|period| period.records_revoked);
+        let total_verifications = sum(|period| period.verifications);
+        let successful_verifications = sum(|period| period.successful_verifications);
+
+        Ok(AuthorityStats {
+            authority_id,
+            from: query.from,
+            to: query.to,
+            interval,
+            records_issued,
+            active_records: records_issued - revoked_records,
+            revoked_records,
+            proofs_generated: sum(|period| period.proofs_generated),
+            total_verifications,
+            successful_verifications,
+            success_rate: success_rate(total_verifications, successful_verifications),
+            timeline,
+        })
+    }
+
+    /// Check a row as `create_authority` checks its request; keys are stored as hex
+    fn validate_import_row(&self, mut row: AuthorityCsvRow) -> Result<AuthorityCsvRow, String> {
+        if row.name.chars().count() < 2 {
+            return Err("Authority name must be at least 2 characters".to_string());
+        }
+        validate_public_key_format(&row.public_key)
+            .map_err(|e| e.message.map_or_else(|| e.code.to_string(), |message| message.to_string()))?;
+        row.public_key = normalize_public_key(&self.crypto, SigningCurve::Secp256k1, &row.public_key)
+            .map_err(|e| match e {
+                AppError::BadRequest(message) => message,
+                other => other.to_string(),
+            })?;
+        if let Some(certificate) = row.certificate.as_deref().filter(|certificate| !certificate.is_empty()) {
+            openssl::x509::X509::from_pem(certificate.as_bytes())
+                .map_err(|_| "Certificate is not a PEM-encoded X.509 certificate".to_string())?;
+        }
+        row.certificate = row.certificate.filter(|certificate| !certificate.is_empty());
+
+        Ok(row)
+    }
+
+    /// The DID an authority issues credentials as
+    pub fn did(&self, authority_id: Uuid) -> String {
+        format!("did:web:{}:authorities:{}", self.did_web_domain, authority_id)
+    }
+
+    /// DID document of an active authority
+    pub async fn did_document(&self, authority_id: Uuid) -> Result<DidDocument, AppError> {
+        let authority = sqlx::query_as::<_, HealthAuthority>(
+            "SELECT * FROM health_authorities WHERE id = $1 AND is_active = TRUE"
+        )
+        .bind(authority_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Health authority not found or inactive".to_string()))?;
+
+        let did = self.did(authority.id);
+        let key_id = format!("{}#key-1", did);
+
+        Ok(DidDocument {
+            context: vec![DID_CONTEXT.to_string(), JWS_2020_CONTEXT.to_string()],
+            id: did.clone(),
+            verification_method: vec![VerificationMethod {
+                id: key_id.clone(),
+                method_type: "JsonWebKey2020".to_string(),
+                controller: did,
+                public_key_jwk: public_key_jwk(authority.curve, &authority.public_key)?,
+            }],
+            assertion_method: vec![key_id],
+        })
+    }
+}
+
+/// The periods in `periods` in order, with empty ones inserted for any gaps
+fn fill_timeline(mut periods: BTreeMap<DateTime<Utc>, AuthorityStatsPeriod>, interval: StatsInterval) -> Vec<AuthorityStatsPeriod> {
+    let (Some(&first), Some(&last)) = (periods.keys().next(), periods.keys().next_back()) else {
+        return Vec::new();
+    };
+
+    let mut timeline = Vec::new();
+    let mut period_start = first;
+    while period_start <= last {
+        let mut period = periods.remove(&period_start).unwrap_or_default();
+        period.period_start = period_start;
+        timeline.push(period);
+
+        period_start = match interval {
+            StatsInterval::Day => period_start + Duration::days(1),
+            StatsInterval::Week => period_start + Duration::weeks(1),
+            StatsInterval::Month => match period_start.checked_add_months(Months::new(1)) {
+                Some(next) => next,
+                None => break,
+            },
+        };
+    }
+
+    timeline
+}
+
+/// Hex form of a public key given as hex or PEM, rejecting keys that aren't
+/// a valid point on `curve`. PEM keys are stored as uncompressed hex.
+pub fn normalize_public_key(crypto: &CryptoService, curve: SigningCurve, public_key: &str) -> Result<String, AppError> {
+    match (curve, is_pem(public_key)) {
+        (SigningCurve::Secp256k1, false) => crypto.parse_public_key(public_key).map(|_| public_key.to_string()),
+        (SigningCurve::Secp256k1, true) => crypto
+            .public_key_from_pem(public_key)
+            .map(|key| hex::encode(key.serialize_uncompressed())),
+        (SigningCurve::P256, false) => crypto.p256().parse_public_key(public_key).map(|_| public_key.to_string()),
+        (SigningCurve::P256, true) => crypto
+            .p256()
+            .public_key_from_pem(public_key)
+            .map(|key| hex::encode(key.to_encoded_point(false).as_bytes())),
+    }
+}
+
+/// `did:web` encodes the port as `%3A` and path segments as `:`-separated parts
+fn did_web_domain(public_base_url: &str) -> Result<String, AppError> {
+    let url = reqwest::Url::parse(public_base_url)
+        .map_err(|e| AppError::InternalServerError(format!("Invalid public base URL: {}", e)))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| AppError::InternalServerError("Public base URL has no host".to_string()))?;
+
+    let mut domain = match url.port() {
+        Some(port) => format!("{}%3A{}", host, port),
+        None => host.to_string(),
+    };
+    for segment in url.path_segments().into_iter().flatten().filter(|segment| !segment.is_empty()) {
+        domain.push(':');
+        domain.push_str(segment);
+    }
+
+    Ok(domain)
+}
+
+/// Stored SEC1 hex key as a JWK; compressed keys are expanded to get `y`
+fn public_key_jwk(curve: SigningCurve, public_key_hex: &str) -> Result<PublicKeyJwk, AppError> {
+    let key_bytes = hex::decode(public_key_hex.trim_start_matches("0x"))
+        .map_err(|_| AppError::InternalServerError("Stored public key is not hex".to_string()))?;
+
+    let (crv, uncompressed) = match curve {
+        SigningCurve::Secp256k1 => {
+            let key = secp256k1::PublicKey::from_slice(&key_bytes)
+                .map_err(|_| AppError::InternalServerError("Stored public key is not a secp256k1 point".to_string()))?;
+            ("secp256k1", key.serialize_uncompressed().to_vec())
+        }
+        SigningCurve::P256 => {
+            let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(&key_bytes)
+                .map_err(|_| AppError::InternalServerError("Stored public key is not a P-256 point".to_string()))?;
+            ("P-256", key.to_encoded_point(false).as_bytes().to_vec())
+        }
+    };
+
+    Ok(PublicKeyJwk {
+        kty: "EC".to_string(),
+        crv: crv.to_string(),
+        x: general_purpose::URL_SAFE_NO_PAD.encode(&uncompressed[1..33]),
+        y: general_purpose::URL_SAFE_NO_PAD.encode(&uncompressed[33..65]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::CryptoService;
+
+    #[test]
+    fn test_did_web_domain_encodes_port_and_path() {
+        assert_eq!(did_web_domain("https://pass.example.com").unwrap(), "pass.example.com");
+        assert_eq!(did_web_domain("http://localhost:3000/").unwrap(), "localhost%3A3000");
+        assert_eq!(did_web_domain("https://example.com/health/").unwrap(), "example.com:health");
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_did_document_embeds_the_authority_key(db: PgPool) {
+        let crypto = CryptoService::new();
+        let (_, public_key) = crypto.generate_key_pair();
+        let (x, y) = crypto.get_public_key_coordinates(&public_key);
+
+        // Stored compressed, published with both coordinates
+        let authority_id = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO health_authorities (name, authority_type, public_key) VALUES ('Clinic', 'clinic', $1) RETURNING id"
+        )
+        .bind(hex::encode(public_key.serialize()))
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let service = HealthAuthorityService::new(db.clone(), Arc::new(crypto), "https://pass.example.com").unwrap();
+        let document = service.did_document(authority_id).await.unwrap();
+
+        let did = format!("did:web:pass.example.com:authorities:{}", authority_id);
+        assert_eq!(document.id, did);
+        assert_eq!(document.assertion_method, vec![format!("{}#key-1", did)]);
+        let jwk = &document.verification_method[0].public_key_jwk;
+        assert_eq!(jwk.crv, "secp256k1");
+        assert_eq!(general_purpose::URL_SAFE_NO_PAD.decode(&jwk.x).unwrap(), x);
+        assert_eq!(general_purpose::URL_SAFE_NO_PAD.decode(&jwk.y).unwrap(), y);
+
+        sqlx::query("UPDATE health_authorities SET is_active = FALSE WHERE id = $1")
+            .bind(authority_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        assert!(matches!(service.did_document(authority_id).await, Err(AppError::NotFound(_))));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_import_rejects_the_batch_unless_partial(db: PgPool) {
+        let crypto = Arc::new(CryptoService::new());
+        let (_, public_key) = crypto.generate_key_pair();
+        let pem = crypto.public_key_to_pem(&public_key);
+        let org_id = sqlx::query_scalar::<_, Uuid>("SELECT id FROM organizations LIMIT 1")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let service = HealthAuthorityService::new(db.clone(), crypto.clone(), "https://pass.example.com").unwrap();
+
+        // A quoted PEM key spans lines; the second row has a key that isn't a point
+        let csv = format!(
+            "name,authority_type,public_key,certificate\nCity Clinic,Clinic,\"{}\",\nBad Lab,Laboratory,02ff,\n",
+            pem.trim_end()
+        );
+        let count = || sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM health_authorities").fetch_one(&db);
+
+        let rejected = service.import_authorities(org_id, &csv, false).await.unwrap();
+        assert_eq!((rejected.created, rejected.invalid), (0, 1));
+        assert_eq!(rejected.rows[0].status, ImportRowStatus::NotImported);
+        assert_eq!(rejected.rows[1].status, ImportRowStatus::Invalid);
+        assert!(rejected.rows[1].line > rejected.rows[0].line);
+        assert_eq!(count().await.unwrap(), 0);
+
+        let partial = service.import_authorities(org_id, &csv, true).await.unwrap();
+        assert_eq!((partial.created, partial.invalid), (1, 1));
+        let authority_id = partial.rows[0].authority_id.unwrap();
+        let stored = sqlx::query_scalar::<_, String>("SELECT public_key FROM health_authorities WHERE id = $1")
+            .bind(authority_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(stored, hex::encode(public_key.serialize_uncompressed()));
+        assert_eq!(count().await.unwrap(), 1);
+
+        let wrong_header = service.import_authorities(org_id, "name,type\nX,Clinic\n", true).await;
+        assert!(matches!(wrong_header, Err(AppError::BadRequest(_))));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_statistics_break_activity_down_by_period(db: PgPool) {
+        let user_id = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO users (email, password_hash, full_name, role) VALUES ('provider@example.com', 'x', 'Provider', 'provider') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let authority_id = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO health_authorities (name, authority_type, public_key) VALUES ('Clinic', 'clinic', '02') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        // January: one record, revoked later. March: one record with a proof verified twice.
+        let record_ids = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO health_records (user_id, authority_id, record_type, patient_identifier, details, issue_date, signature_r, signature_s, message_hash, is_revoked, created_at)
+            VALUES ($1, $2, 'vaccination', 'P-1', '{}', CURRENT_DATE, '\x00', '\x00', '\x00', TRUE, '2025-01-15T10:00:00Z'),
+                   ($1, $2, 'test_result', 'P-1', '{}', CURRENT_DATE, '\x00', '\x00', '\x00', FALSE, '2025-03-02T10:00:00Z')
+            RETURNING id
+            "#
+        )
+        .bind(user_id)
+        .bind(authority_id)
+        .fetch_all(&db)
+        .await
+        .unwrap();
+        let proof_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO zk_proofs (health_record_id, proof_data, verification_key, proof_type, generated_at)
+            VALUES ($1, '\x01', '\x02', 'ecdsa_signature_verification', '2025-03-03T10:00:00Z')
+            RETURNING id
+            "#
+        )
+        .bind(record_ids[1])
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO proof_verifications (proof_id, verification_result, verified_at)
+            VALUES ($1, TRUE, '2025-03-04T10:00:00Z'), ($1, FALSE, '2025-03-05T10:00:00Z')
+            "#
+        )
+        .bind(proof_id)
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let service = HealthAuthorityService::new(db.clone(), Arc::new(CryptoService::new()), "https://pass.example.com").unwrap();
+        let stats = service.statistics(authority_id, &AuthorityStatsQuery::default(), None, None).await.unwrap();
+
+        assert_eq!((stats.records_issued, stats.active_records, stats.revoked_records), (2, 1, 1));
+        assert_eq!(stats.proofs_generated, 1);
+        assert_eq!((stats.total_verifications, stats.successful_verifications), (2, 1));
+        assert_eq!(stats.success_rate, 0.5);
+        let months: Vec<(String, i64, i64)> = stats
+            .timeline
+            .iter()
+            .map(|period| (period.period_start.format("%Y-%m").to_string(), period.records_issued, period.verifications))
+            .collect();
+        assert_eq!(
+            months,
+            vec![("2025-01".to_string(), 1, 0), ("2025-02".to_string(), 0, 0), ("2025-03".to_string(), 1, 2)]
+        );
+
+        let march = AuthorityStatsQuery { from: Some("2025-03-01T00:00:00Z".parse().unwrap()), ..Default::default() };
+        let stats = service.statistics(authority_id, &march, None, None).await.unwrap();
+        assert_eq!((stats.records_issued, stats.timeline.len()), (1, 1));
+
+        // Providers only see authorities they represent
+        let unassociated = service.statistics(authority_id, &march, Some(user_id), None).await;
+        assert!(matches!(unassociated, Err(AppError::Forbidden(_))));
+        let other_org = service.statistics(authority_id, &march, None, Some(Uuid::new_v4())).await;
+        assert!(matches!(other_org, Err(AppError::NotFound(_))));
+    }
+}
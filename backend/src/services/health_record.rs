@@ -1,19 +1,90 @@
 use crate::models::{
     HealthRecord, HealthRecordResponse, CreateHealthRecordRequest, HealthRecordQuery,
-    HealthRecordType, UserRole,
+    HealthRecordType, RecordSignature, SigningAuthority, UserRole, Cursor, Paginated,
+    SeriesDose, VaccinationSeriesResponse, RecordShare, RecordShareResponse,
+    RecordTransfer, RecordTransferResponse, RecordTemplate, CreateTemplateRequest, CreateFromTemplateRequest,
+    VaccinationDetails, TestResultDetails, MedicalClearanceDetails, ImmunityProofDetails, RecordSort, SigningCurve,
+    RecordExportFormat, RecordExportQuery,
 };
 use crate::errors::AppError;
-use crate::services::{AuthService, CryptoService};
+use crate::i18n::Locale;
+use crate::services::{AuthService, CryptoService, EmailService};
+use crate::services::access_log::LoggedAccess;
+use crate::services::email::RecordNotification;
+use crate::services::webhook::{RecordEvent, WebhookEvent, WebhookService};
+use crate::services::crypto::SignatureScheme;
+use crate::services::field_encryption::{EncryptedFields, SensitiveFields};
+use crate::services::message_format::{self, SignableFields};
+use crate::services::zk_proof::{csv_field, EXPORT_CHUNK_BYTES};
 use anyhow::Result;
+use futures::{Stream, TryStreamExt};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
 use uuid::Uuid;
 use std::sync::Arc;
 use std::collections::HashMap;
-use chrono::NaiveDate;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 
+const TRANSFER_TOKEN_PREFIX: &str = "xfr_";
+const TRANSFER_EXPIRATION_HOURS: i64 = 72;
+
+/// Records whose co-signatures an export loads in one query
+const EXPORT_BATCH_RECORDS: usize = 500;
+
+const RECORD_CSV_HEADER: &str = "id,record_type,patient_identifier,details,issue_date,expiry_date,authority_name,is_revoked,created_at,has_valid_signature,integrity_ok,series_id\n";
+
+/// Bind parameters Postgres accepts in one statement
+const MAX_BIND_PARAMS: usize = 65_535;
+/// Columns bound per row by the batch `INSERT` into `health_records`
+const HEALTH_RECORD_INSERT_COLUMNS: usize = 18;
+
+/// A record's signed fields as currently stored, with the hash that was signed
+pub(crate) struct StoredSignedContent<'a> {
+    pub record_type: &'a HealthRecordType,
+    pub patient_identifier: &'a str,
+    pub details: &'a serde_json::Value,
+    pub issue_date: NaiveDate,
+    pub issuer: &'a str,
+    pub expiry_date: Option<NaiveDate>,
+    pub format_version: i32,
+    pub message_hash: &'a [u8],
+}
+
+/// A record joined with its authority's name
+#[derive(sqlx::FromRow)]
+struct HealthRecordWithAuthority {
+    #[sqlx(flatten)]
+    record: HealthRecord,
+    authority_name: String,
+}
+
+/// A co-signature slot joined with its authority's name
+#[derive(sqlx::FromRow)]
+struct CoSignature {
+    health_record_id: Uuid,
+    authority_id: Uuid,
+    authority_name: String,
+    position: i32,
+    message_hash: Option<Vec<u8>>,
+}
+
+/// A record of a batch, prepared for its multi-row `INSERT`
+struct BatchRecordRow {
+    id: Uuid,
+    org_id: Uuid,
+    stored: SensitiveFields,
+    encrypted: Option<EncryptedFields>,
+    fingerprint: Option<Vec<u8>>,
+    series_id: Option<Uuid>,
+}
+
+#[derive(Clone)]
 pub struct HealthRecordService {
     auth_service: Arc<AuthService>,
     crypto_service: Arc<CryptoService>,
+    email_service: Option<Arc<EmailService>>,
+    webhook_service: Option<WebhookService>,
 }
 
 impl HealthRecordService {
@@ -21,120 +92,467 @@ impl HealthRecordService {
         Self {
             auth_service,
             crypto_service,
+            email_service: None,
+            webhook_service: None,
         }
     }
 
+    /// Publish `record.*` events to subscribed webhooks
+    pub fn with_webhooks(mut self, webhook_service: WebhookService) -> Self {
+        self.webhook_service = Some(webhook_service);
+        self
+    }
+
+    /// Email record owners when their records are issued or revoked
+    pub fn with_email(mut self, email_service: Option<Arc<EmailService>>) -> Self {
+        self.email_service = email_service;
+        self
+    }
+
+    /// Create a record in its authority's organization. `org_scope` is the
+    /// caller's organization (`None` for superadmins); authorities outside it
+    /// are treated as missing.
     pub async fn create_health_record(
         &self,
         request: CreateHealthRecordRequest,
         user_id: Uuid,
+        org_scope: Option<Uuid>,
     ) -> Result<HealthRecordResponse, AppError> {
         let db = &self.auth_service.db;
 
-        // Verify the health authority exists and is active
+        // Verify the health authority exists, is active and belongs to the caller's organization
         let authority = sqlx::query!(
-            "SELECT name, public_key FROM health_authorities WHERE id = $1 AND is_active = TRUE",
-            request.authority_id
+            "SELECT name, public_key, org_id FROM health_authorities WHERE id = $1 AND is_active = TRUE AND ($2::uuid IS NULL OR org_id = $2)",
+            request.authority_id,
+            org_scope
         )
         .fetch_optional(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Health authority not found or inactive".to_string()))?;
 
+        // Co-signers must be distinct, active authorities of the same organization other than the issuing one
+        let co_signer_ids = &request.co_signing_authority_ids;
+        if co_signer_ids.contains(&request.authority_id) {
+            return Err(AppError::BadRequest("The issuing authority cannot also co-sign the record".to_string()));
+        }
+        if (1..co_signer_ids.len()).any(|i| co_signer_ids[..i].contains(&co_signer_ids[i])) {
+            return Err(AppError::BadRequest("Co-signing authorities must be distinct".to_string()));
+        }
+        if !co_signer_ids.is_empty() {
+            let active = sqlx::query_scalar::<_, i64>(
+                "SELECT COUNT(*) FROM health_authorities WHERE id = ANY($1) AND is_active = TRUE AND org_id = $2"
+            )
+            .bind(co_signer_ids)
+            .bind(authority.org_id)
+            .fetch_one(db)
+            .await?;
+
+            if active != co_signer_ids.len() as i64 {
+                return Err(AppError::NotFound("Co-signing authority not found or inactive".to_string()));
+            }
+        }
+
+        // Patient identifier and details are only stored encrypted when field encryption is enabled
+        let fields = SensitiveFields {
+            patient_identifier: request.patient_identifier.clone(),
+            details: serde_json::to_value(&request.details)?,
+        };
+        let (stored, encrypted) = self.crypto_service.seal_fields(fields.clone())?;
+
+        // The same record must not be issued twice unless the caller insists
+        let fingerprint = if request.allow_duplicate {
+            None
+        } else {
+            let fingerprint = self.content_fingerprint(&request)?;
+            let existing = sqlx::query_scalar::<_, Uuid>(
+                "SELECT id FROM health_records WHERE content_fingerprint = $1 AND is_revoked = FALSE"
+            )
+            .bind(&fingerprint)
+            .fetch_optional(db)
+            .await?;
+            if let Some(existing_id) = existing {
+                return Err(duplicate_record(existing_id));
+            }
+            Some(fingerprint)
+        };
+
+        let mut tx = db.begin().await?;
+
+        let series_id = self.resolve_series(&mut tx, &request, authority.org_id).await?;
+
         // Create health record without signature initially
         let health_record = sqlx::query_as::<_, HealthRecord>(
             r#"
             INSERT INTO health_records (
                 user_id, authority_id, record_type, patient_identifier, 
-                details, issue_date, expiry_date, signature_r, signature_s, message_hash
+                details, issue_date, expiry_date, signature_r, signature_s, message_hash, org_id,
+                encrypted_fields, encryption_nonce, encrypted_data_key, encryption_key_id, content_fingerprint,
+                series_id
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
             RETURNING *
             "#
         )
         .bind(user_id)
         .bind(request.authority_id)
         .bind(&request.record_type)
-        .bind(&request.patient_identifier)
-        .bind(serde_json::to_value(&request.details)?)
+        .bind(&stored.patient_identifier)
+        .bind(&stored.details)
         .bind(request.issue_date)
         .bind(request.expiry_date)
         .bind(vec![0u8; 32]) // Placeholder signature_r
         .bind(vec![0u8; 32]) // Placeholder signature_s
         .bind(vec![0u8; 32]) // Placeholder message_hash
-        .fetch_one(db)
-        .await?;
+        .bind(authority.org_id)
+        .bind(encrypted.as_ref().map(|e| &e.ciphertext))
+        .bind(encrypted.as_ref().map(|e| &e.nonce))
+        .bind(encrypted.as_ref().map(|e| &e.wrapped_key))
+        .bind(encrypted.as_ref().map(|e| &e.key_id))
+        .bind(&fingerprint)
+        .bind(series_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            // A concurrent request created the same record after the check above
+            sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+                AppError::Conflict("An identical health record already exists".to_string())
+            }
+            e => e.into(),
+        })?;
+
+        for (index, authority_id) in co_signer_ids.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO record_signatures (health_record_id, authority_id, position) VALUES ($1, $2, $3)"
+            )
+            .bind(health_record.id)
+            .bind(authority_id)
+            .bind(index as i32 + 1)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        self.publish(WebhookEvent::RecordCreated(RecordEvent {
+            record_id: health_record.id,
+            record_type: health_record.record_type.clone(),
+            authority_id: health_record.authority_id,
+            occurred_at: health_record.created_at,
+        }));
+
+        let co_signatures = self.load_co_signatures(&[health_record.id]).await?.remove(&health_record.id);
+        let (signing_authorities, _) = Self::signing_status(
+            health_record.authority_id,
+            &authority.name,
+            &health_record.message_hash,
+            co_signatures.unwrap_or_default(),
+        );
 
         Ok(HealthRecordResponse {
             id: health_record.id,
             record_type: health_record.record_type,
-            patient_identifier: health_record.patient_identifier,
-            details: health_record.details,
+            patient_identifier: fields.patient_identifier,
+            details: fields.details,
             issue_date: health_record.issue_date,
             expiry_date: health_record.expiry_date,
             authority_name: authority.name,
+            authority_id: health_record.authority_id,
             is_revoked: health_record.is_revoked,
+            revoked_at: health_record.revoked_at,
+            revocation_reason: health_record.revocation_reason,
             created_at: health_record.created_at,
             has_valid_signature: false, // Not signed yet
+            integrity_ok: true,
+            signing_authorities,
+            series_id: health_record.series_id,
         })
     }
 
+    /// Create many records at once, with the same checks as
+    /// `create_health_record`. Rows are written with multi-row `INSERT`s in a
+    /// single transaction, so either every record is created or none is.
+    /// Returns the ids of the created records in request order.
+    pub async fn create_health_records_batch(
+        &self,
+        requests: Vec<CreateHealthRecordRequest>,
+        user_id: Uuid,
+        org_scope: Option<Uuid>,
+    ) -> Result<Vec<Uuid>, AppError> {
+        let db = &self.auth_service.db;
+        if requests.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Issuing and co-signing authorities must be active; issuers must be in the caller's organization
+        let mut authority_ids: Vec<Uuid> = requests
+            .iter()
+            .flat_map(|request| std::iter::once(request.authority_id).chain(request.co_signing_authority_ids.iter().copied()))
+            .collect();
+        authority_ids.sort_unstable();
+        authority_ids.dedup();
+        let authority_orgs: HashMap<Uuid, Uuid> = sqlx::query_as::<_, (Uuid, Uuid)>(
+            "SELECT id, org_id FROM health_authorities WHERE id = ANY($1) AND is_active = TRUE"
+        )
+        .bind(&authority_ids)
+        .fetch_all(db)
+        .await?
+        .into_iter()
+        .collect();
+
+        let mut rows = Vec::with_capacity(requests.len());
+        for request in &requests {
+            let org_id = authority_orgs
+                .get(&request.authority_id)
+                .copied()
+                .filter(|org_id| org_scope.map_or(true, |scope| scope == *org_id))
+                .ok_or_else(|| AppError::NotFound("Health authority not found or inactive".to_string()))?;
+
+            let co_signer_ids = &request.co_signing_authority_ids;
+            if co_signer_ids.contains(&request.authority_id) {
+                return Err(AppError::BadRequest("The issuing authority cannot also co-sign the record".to_string()));
+            }
+            if (1..co_signer_ids.len()).any(|i| co_signer_ids[..i].contains(&co_signer_ids[i])) {
+                return Err(AppError::BadRequest("Co-signing authorities must be distinct".to_string()));
+            }
+            if co_signer_ids.iter().any(|id| authority_orgs.get(id) != Some(&org_id)) {
+                return Err(AppError::NotFound("Co-signing authority not found or inactive".to_string()));
+            }
+
+            let (stored, encrypted) = self.crypto_service.seal_fields(SensitiveFields {
+                patient_identifier: request.patient_identifier.clone(),
+                details: serde_json::to_value(&request.details)?,
+            })?;
+            let fingerprint = if request.allow_duplicate { None } else { Some(self.content_fingerprint(request)?) };
+
+            rows.push(BatchRecordRow { id: Uuid::new_v4(), org_id, stored, encrypted, fingerprint, series_id: None });
+        }
+
+        // The same record must not be issued twice unless the caller insists
+        let fingerprints: Vec<Vec<u8>> = rows.iter().filter_map(|row| row.fingerprint.clone()).collect();
+        if !fingerprints.is_empty() {
+            let existing = sqlx::query_scalar::<_, Uuid>(
+                "SELECT id FROM health_records WHERE content_fingerprint = ANY($1) AND is_revoked = FALSE LIMIT 1"
+            )
+            .bind(&fingerprints)
+            .fetch_optional(db)
+            .await?;
+            if let Some(existing_id) = existing {
+                return Err(duplicate_record(existing_id));
+            }
+        }
+
+        let mut tx = db.begin().await?;
+
+        for (request, row) in requests.iter().zip(rows.iter_mut()) {
+            row.series_id = self.resolve_series(&mut tx, request, row.org_id).await?;
+        }
+
+        // Ids are assigned up front so co-signature slots can refer to them
+        let mut created_at = HashMap::with_capacity(rows.len());
+        let items: Vec<_> = requests.iter().zip(&rows).collect();
+        for chunk in items.chunks(MAX_BIND_PARAMS / HEALTH_RECORD_INSERT_COLUMNS) {
+            let mut insert = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+                r#"
+                INSERT INTO health_records (
+                    id, user_id, authority_id, record_type, patient_identifier,
+                    details, issue_date, expiry_date, signature_r, signature_s, message_hash, org_id,
+                    encrypted_fields, encryption_nonce, encrypted_data_key, encryption_key_id, content_fingerprint,
+                    series_id
+                )
+                "#,
+            );
+            insert.push_values(chunk, |mut values, (request, row)| {
+                values
+                    .push_bind(row.id)
+                    .push_bind(user_id)
+                    .push_bind(request.authority_id)
+                    .push_bind(&request.record_type)
+                    .push_bind(&row.stored.patient_identifier)
+                    .push_bind(&row.stored.details)
+                    .push_bind(request.issue_date)
+                    .push_bind(request.expiry_date)
+                    .push_bind(vec![0u8; 32]) // Placeholder signature_r
+                    .push_bind(vec![0u8; 32]) // Placeholder signature_s
+                    .push_bind(vec![0u8; 32]) // Placeholder message_hash
+                    .push_bind(row.org_id)
+                    .push_bind(row.encrypted.as_ref().map(|e| &e.ciphertext))
+                    .push_bind(row.encrypted.as_ref().map(|e| &e.nonce))
+                    .push_bind(row.encrypted.as_ref().map(|e| &e.wrapped_key))
+                    .push_bind(row.encrypted.as_ref().map(|e| &e.key_id))
+                    .push_bind(&row.fingerprint)
+                    .push_bind(row.series_id);
+            });
+            insert.push(" RETURNING id, created_at");
+
+            let inserted = insert
+                .build_query_as::<(Uuid, DateTime<Utc>)>()
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| match e {
+                    // Two identical records in the batch, or one created concurrently after the check above
+                    sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+                        AppError::Conflict("An identical health record already exists".to_string())
+                    }
+                    e => e.into(),
+                })?;
+            created_at.extend(inserted);
+        }
+
+        let slots: Vec<(Uuid, Uuid, i32)> = requests
+            .iter()
+            .zip(&rows)
+            .flat_map(|(request, row)| {
+                request
+                    .co_signing_authority_ids
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, authority_id)| (row.id, *authority_id, index as i32 + 1))
+            })
+            .collect();
+        for chunk in slots.chunks(MAX_BIND_PARAMS / 3) {
+            let mut insert = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+                "INSERT INTO record_signatures (health_record_id, authority_id, position) "
+            );
+            insert.push_values(chunk, |mut values, (record_id, authority_id, position)| {
+                values.push_bind(record_id).push_bind(authority_id).push_bind(position);
+            });
+            insert.build().execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+
+        for (request, row) in requests.iter().zip(&rows) {
+            self.publish(WebhookEvent::RecordCreated(RecordEvent {
+                record_id: row.id,
+                record_type: request.record_type.clone(),
+                authority_id: request.authority_id,
+                occurred_at: created_at.get(&row.id).copied().unwrap_or_else(Utc::now),
+            }));
+        }
+
+        Ok(rows.into_iter().map(|row| row.id).collect())
+    }
+
+    /// Sign a record with its authority's key.
+    ///
+    /// `provider_id` must be associated with the record's authority through
+    /// `provider_authorities`; `None` (admins) may sign for any authority.
+    /// Records outside `org_scope` are treated as missing.
     pub async fn sign_health_record(
         &self,
         record_id: Uuid,
         authority_private_key: &str,
-        _signer_user_id: Uuid,
+        provider_id: Option<Uuid>,
+        org_scope: Option<Uuid>,
     ) -> Result<HealthRecordResponse, AppError> {
         let db = &self.auth_service.db;
 
         // Get the health record
         let mut health_record = sqlx::query_as::<_, HealthRecord>(
-            "SELECT * FROM health_records WHERE id = $1"
+            "SELECT * FROM health_records WHERE id = $1 AND ($2::uuid IS NULL OR org_id = $2)"
         )
         .bind(record_id)
+        .bind(org_scope)
         .fetch_optional(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Health record not found".to_string()))?;
 
+        // A provider may only sign as an authority they represent
+        self.ensure_provider_represents(provider_id, health_record.authority_id).await?;
+
+        // The signed message covers the cleartext fields
+        self.crypto_service.decrypt_record(&mut health_record)?;
+
         // Get authority information
         let authority = sqlx::query!(
-            "SELECT name, public_key FROM health_authorities WHERE id = $1",
+            r#"SELECT name, public_key, curve as "curve: SigningCurve" FROM health_authorities WHERE id = $1"#,
             health_record.authority_id
         )
         .fetch_optional(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Health authority not found".to_string()))?;
 
-        // Parse the private key
-        let private_key = self.crypto_service.parse_private_key(authority_private_key)?;
-
         // Extract details for signing
-        let details_str = self.extract_details_for_signing(&health_record.details, &health_record.record_type)?;
-
-        // Generate signature
-        let signature = self.crypto_service.sign_health_record(
-            &health_record.record_type,
-            &health_record.patient_identifier,
-            &details_str,
-            &health_record.issue_date.to_string(),
-            &authority.name,
-            &private_key,
-        )?;
+        let details_str = Self::extract_details_for_signing(&health_record.details, &health_record.record_type)?;
+        let issue_date = health_record.issue_date.to_string();
+
+        let fields = SignableFields {
+            record_type: &health_record.record_type,
+            patient_identifier: &health_record.patient_identifier,
+            details: &details_str,
+            issue_date: &issue_date,
+            issuer: &authority.name,
+            expiry_date: health_record.expiry_date,
+        };
+
+        // Generate signature on the authority's curve, and make sure the key is the authority's
+        let (signature, matches_authority) = match authority.curve {
+            SigningCurve::Secp256k1 => {
+                let public_key = self.crypto_service.parse_public_key(&authority.public_key)?;
+                let private_key = self.crypto_service.parse_private_key(authority_private_key)?;
+                let signature = self.crypto_service.sign_health_record(&fields, &private_key)?;
+                let matches = self.crypto_service.verify_message_signature(
+                    &signature.message_hash,
+                    &signature.signature_r,
+                    &signature.signature_s,
+                    &public_key,
+                )?;
+                (signature, matches)
+            }
+            SigningCurve::P256 => {
+                let p256 = self.crypto_service.p256();
+                let public_key = p256.parse_public_key(&authority.public_key)?;
+                let private_key = p256.parse_private_key(authority_private_key)?;
+                let signature = p256.sign_health_record(&fields, &private_key)?;
+                let matches = p256.verify_message_signature(
+                    &signature.message_hash,
+                    &signature.signature_r,
+                    &signature.signature_s,
+                    &public_key,
+                )?;
+                (signature, matches)
+            }
+        };
+        if !matches_authority {
+            return Err(AppError::BadRequest(
+                "Private key does not match the signing authority's public key".to_string(),
+            ));
+        }
 
         // Update the health record with the signature
         health_record = sqlx::query_as::<_, HealthRecord>(
             r#"
             UPDATE health_records 
-            SET signature_r = $1, signature_s = $2, message_hash = $3, updated_at = NOW()
-            WHERE id = $4
+            SET signature_r = $1, signature_s = $2, message_hash = $3, format_version = $4, updated_at = NOW()
+            WHERE id = $5
             RETURNING *
             "#
         )
         .bind(&signature.signature_r)
         .bind(&signature.signature_s)
         .bind(&signature.message_hash)
+        .bind(signature.format_version)
         .bind(record_id)
         .fetch_one(db)
         .await?;
+        self.crypto_service.decrypt_record(&mut health_record)?;
+
+        // Co-signatures over an earlier version of the message no longer count
+        let co_signatures = self.load_co_signatures(&[record_id]).await?.remove(&record_id);
+        let (signing_authorities, has_valid_signature) = Self::signing_status(
+            health_record.authority_id,
+            &authority.name,
+            &health_record.message_hash,
+            co_signatures.unwrap_or_default(),
+        );
+
+        self.notify_owner(record_id, RecordNotification::Issued).await;
+        self.publish(WebhookEvent::RecordSigned(RecordEvent {
+            record_id,
+            record_type: health_record.record_type.clone(),
+            authority_id: health_record.authority_id,
+            occurred_at: health_record.updated_at,
+        }));
 
         Ok(HealthRecordResponse {
             id: health_record.id,
@@ -144,21 +562,212 @@ impl HealthRecordService {
             issue_date: health_record.issue_date,
             expiry_date: health_record.expiry_date,
             authority_name: authority.name,
+            authority_id: health_record.authority_id,
             is_revoked: health_record.is_revoked,
+            revoked_at: health_record.revoked_at,
+            revocation_reason: health_record.revocation_reason,
             created_at: health_record.created_at,
-            has_valid_signature: true,
+            has_valid_signature,
+            integrity_ok: true,
+            signing_authorities,
+            series_id: health_record.series_id,
         })
     }
 
+    /// Add a required co-signature over the record's current message hash.
+    ///
+    /// The record's own authority must have signed, and co-signers sign in the
+    /// order given at creation. `provider_id` and `org_scope` are scoped as in
+    /// `sign_health_record`.
+    pub async fn co_sign_health_record(
+        &self,
+        record_id: Uuid,
+        authority_id: Uuid,
+        authority_private_key: &str,
+        provider_id: Option<Uuid>,
+        org_scope: Option<Uuid>,
+    ) -> Result<HealthRecordResponse, AppError> {
+        let db = &self.auth_service.db;
+
+        let health_record = sqlx::query_as::<_, HealthRecord>(
+            "SELECT * FROM health_records WHERE id = $1 AND ($2::uuid IS NULL OR org_id = $2)"
+        )
+        .bind(record_id)
+        .bind(org_scope)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Health record not found".to_string()))?;
+
+        let slot = sqlx::query_as::<_, RecordSignature>(
+            "SELECT * FROM record_signatures WHERE health_record_id = $1 AND authority_id = $2"
+        )
+        .bind(record_id)
+        .bind(authority_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Authority is not a required co-signer of this record".to_string()))?;
+
+        self.ensure_provider_represents(provider_id, authority_id).await?;
+
+        if is_unsigned(&health_record.message_hash) {
+            return Err(AppError::BadRequest(
+                "The record's own authority must sign before it can be co-signed".to_string(),
+            ));
+        }
+        if slot.message_hash.as_deref() == Some(health_record.message_hash.as_slice()) {
+            return Err(AppError::Conflict("Authority has already co-signed this record".to_string()));
+        }
+
+        let earlier_unsigned = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM record_signatures
+                WHERE health_record_id = $1 AND position < $2
+                  AND (message_hash IS NULL OR message_hash <> $3)
+            )
+            "#
+        )
+        .bind(record_id)
+        .bind(slot.position)
+        .bind(&health_record.message_hash)
+        .fetch_one(db)
+        .await?;
+        if earlier_unsigned {
+            return Err(AppError::BadRequest("Earlier co-signers must sign first".to_string()));
+        }
+
+        let authority = sqlx::query!(
+            r#"SELECT public_key, curve as "curve: SigningCurve" FROM health_authorities WHERE id = $1 AND is_active = TRUE"#,
+            authority_id
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Health authority not found or inactive".to_string()))?;
+
+        if SignatureScheme::for_authority(&authority.curve, &authority.public_key)? != SignatureScheme::EcdsaSecp256k1 {
+            return Err(AppError::BadRequest("Co-signing requires a secp256k1 authority key".to_string()));
+        }
+        let public_key = self.crypto_service.parse_public_key(&authority.public_key)?;
+        let private_key = self.crypto_service.parse_private_key(authority_private_key)?;
+
+        let (signature_r, signature_s) = self.crypto_service.sign_message_hash(&health_record.message_hash, &private_key)?;
+        if !self.crypto_service.verify_message_signature(&health_record.message_hash, &signature_r, &signature_s, &public_key)? {
+            return Err(AppError::BadRequest(
+                "Private key does not match the co-signing authority's public key".to_string(),
+            ));
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE record_signatures
+            SET signature_r = $1, signature_s = $2, message_hash = $3, signed_by = $4, signed_at = NOW()
+            WHERE id = $5
+            "#
+        )
+        .bind(&signature_r)
+        .bind(&signature_s)
+        .bind(&health_record.message_hash)
+        .bind(provider_id)
+        .bind(slot.id)
+        .execute(db)
+        .await?;
+
+        self.get_health_record_by_id(record_id, None).await
+    }
+
+    /// Reject a provider signing for an authority they are not associated with
+    async fn ensure_provider_represents(&self, provider_id: Option<Uuid>, authority_id: Uuid) -> Result<(), AppError> {
+        let Some(provider_id) = provider_id else {
+            return Ok(());
+        };
+
+        let associated = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM provider_authorities WHERE user_id = $1 AND authority_id = $2)"
+        )
+        .bind(provider_id)
+        .bind(authority_id)
+        .fetch_one(&self.auth_service.db)
+        .await?;
+
+        if !associated {
+            return Err(AppError::Forbidden(
+                "Provider is not associated with this health authority".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Co-signature slots of the given records, in signing order
+    async fn load_co_signatures(&self, record_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<CoSignature>>, AppError> {
+        let rows = sqlx::query_as::<_, CoSignature>(
+            r#"
+            SELECT rs.health_record_id, rs.authority_id, ha.name AS authority_name, rs.position, rs.message_hash
+            FROM record_signatures rs
+            JOIN health_authorities ha ON rs.authority_id = ha.id
+            WHERE rs.health_record_id = ANY($1)
+            ORDER BY rs.health_record_id, rs.position
+            "#
+        )
+        .bind(record_ids)
+        .fetch_all(&self.auth_service.db)
+        .await?;
+
+        let mut by_record: HashMap<Uuid, Vec<CoSignature>> = HashMap::new();
+        for row in rows {
+            by_record.entry(row.health_record_id).or_default().push(row);
+        }
+
+        Ok(by_record)
+    }
+
+    /// Every signature a record requires, its own authority first, and whether
+    /// all of them cover the record's current message hash
+    fn signing_status(
+        authority_id: Uuid,
+        authority_name: &str,
+        message_hash: &[u8],
+        co_signatures: Vec<CoSignature>,
+    ) -> (Vec<SigningAuthority>, bool) {
+        let primary_signed = !is_unsigned(message_hash);
+
+        let mut authorities = vec![SigningAuthority {
+            authority_id,
+            authority_name: authority_name.to_string(),
+            position: 0,
+            signed: primary_signed,
+        }];
+        authorities.extend(co_signatures.into_iter().map(|co_signature| SigningAuthority {
+            authority_id: co_signature.authority_id,
+            authority_name: co_signature.authority_name,
+            position: co_signature.position,
+            signed: primary_signed && co_signature.message_hash.as_deref() == Some(message_hash),
+        }));
+
+        let all_signed = authorities.iter().all(|authority| authority.signed);
+        (authorities, all_signed)
+    }
+
     pub async fn get_user_health_records(
         &self,
         user_id: Uuid,
         query: HealthRecordQuery,
-    ) -> Result<Vec<HealthRecordResponse>, AppError> {
+    ) -> Result<Paginated<HealthRecordResponse>, AppError> {
         let db = &self.auth_service.db;
         let page = query.page.unwrap_or(1);
         let limit = query.limit.unwrap_or(20).min(100);
-        let offset = (page.saturating_sub(1)) * limit;
+        let cursor = query.cursor.as_deref().map(Cursor::decode).transpose()?;
+        let sort = query.sort.unwrap_or_default();
+        // Cursors hold a `created_at` position, so they only page the default order
+        if cursor.is_some() && sort != RecordSort::default() {
+            return Err(AppError::BadRequest("Cursors can only be used with the default sort".to_string()));
+        }
+        let search = query.search.as_deref().map(str::trim).filter(|search| !search.is_empty());
+        if cursor.is_some() && search.is_some() {
+            return Err(AppError::BadRequest("Cursors cannot be used with search".to_string()));
+        }
+        // A cursor switches to keyset pagination, which is stable while new records are added
+        let offset = if cursor.is_some() { 0 } else { (page.saturating_sub(1)) * limit };
 
         let mut sql = String::from(
             r#"
@@ -200,46 +809,226 @@ impl HealthRecordService {
             sql.push_str(" AND hr.is_revoked = FALSE");
         }
 
-        sql.push_str(" ORDER BY hr.created_at DESC");
+        sql.push_str(" ORDER BY hr.created_at DESC, hr.id DESC");
         sql.push_str(&format!(" LIMIT ${} OFFSET ${}", param_count + 1, param_count + 2));
         params.push(Box::new(limit as i64));
         params.push(Box::new(offset as i64));
 
         // For simplicity, we'll use a basic query here
         // In a real implementation, you'd want to use a query builder or similar
-        let records = sqlx::query!(
+        let list_sql = format!(
             r#"
             SELECT hr.*, ha.name as authority_name
             FROM health_records hr
             JOIN health_authorities ha ON hr.authority_id = ha.id
             WHERE hr.user_id = $1 AND hr.is_revoked = FALSE
-            ORDER BY hr.created_at DESC
+              AND ($4::timestamptz IS NULL OR (hr.created_at, hr.id) < ($4, $5::uuid))
+              AND ($6::text IS NULL OR hr.details_search @@ websearch_to_tsquery('simple', $6))
+            ORDER BY {}{}
             LIMIT $2 OFFSET $3
             "#,
-            user_id,
-            limit as i64,
-            offset as i64
-        )
-        .fetch_all(db)
-        .await?;
+            // The most relevant matches first, in the requested order among equals
+            if search.is_some() { "ts_rank(hr.details_search, websearch_to_tsquery('simple', $6)) DESC, " } else { "" },
+            sort.order_by()
+        );
+        let records = sqlx::query_as::<_, HealthRecordWithAuthority>(&list_sql)
+            .bind(user_id)
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .bind(cursor.map(|c| c.created_at))
+            .bind(cursor.map(|c| c.id))
+            .bind(search)
+            .fetch_all(db)
+            .await?;
 
-        let mut responses = Vec::new();
-        for record in records {
-            responses.push(HealthRecordResponse {
-                id: record.id,
-                record_type: serde_json::from_value(serde_json::Value::String(record.record_type))?,
-                patient_identifier: record.patient_identifier,
-                details: record.details,
-                issue_date: record.issue_date,
-                expiry_date: record.expiry_date,
-                authority_name: record.authority_name,
-                is_revoked: record.is_revoked,
-                created_at: record.created_at,
-                has_valid_signature: !record.signature_r.is_empty() && !record.signature_s.is_empty(),
+        let record_ids: Vec<Uuid> = records.iter().map(|row| row.record.id).collect();
+        let co_signatures = self.load_co_signatures(&record_ids).await?;
+        let responses = self.record_responses(records, co_signatures).await?;
+
+        if sort != RecordSort::default() || search.is_some() {
+            return Ok(Paginated::without_cursor(responses));
+        }
+
+        Ok(Paginated::new(responses, limit, |r| Cursor::new(r.created_at, r.id)))
+    }
+
+    /// All of `user_id`'s records as CSV or a JSON array, oldest first.
+    ///
+    /// Records are read through a database cursor and sent in chunks of about
+    /// `EXPORT_CHUNK_BYTES`, so memory stays bounded however many records the
+    /// user has. A database error midway ends the stream with that error.
+    /// Each batch is logged to `access` before it is sent, and a failed write
+    /// ends the stream too.
+    pub fn export_records(
+        &self,
+        user_id: Uuid,
+        query: RecordExportQuery,
+        access: LoggedAccess,
+    ) -> impl Stream<Item = Result<String, AppError>> + Send + 'static {
+        let service = self.clone();
+        let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, AppError>>(4);
+        tokio::spawn(async move {
+            let format = query.format.unwrap_or_default();
+            let mut chunk = String::from(match format {
+                RecordExportFormat::Csv => RECORD_CSV_HEADER,
+                RecordExportFormat::Json => "[",
             });
+            if let Err(e) = service.write_export(user_id, &query, &access, format, &mut chunk, &tx).await {
+                let _ = tx.send(Err(e)).await;
+                return;
+            }
+            if format == RecordExportFormat::Json {
+                chunk.push(']');
+            }
+            let _ = tx.send(Ok(chunk)).await;
+        });
+
+        futures::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|item| (item, rx))
+        })
+    }
+
+    /// Append every exported record to `chunk`, sending it whenever it is full
+    async fn write_export(
+        &self,
+        user_id: Uuid,
+        query: &RecordExportQuery,
+        access: &LoggedAccess,
+        format: RecordExportFormat,
+        chunk: &mut String,
+        tx: &tokio::sync::mpsc::Sender<Result<String, AppError>>,
+    ) -> Result<(), AppError> {
+        let db = &self.auth_service.db;
+        let mut first = true;
+        let mut rows = sqlx::query_as::<_, HealthRecordWithAuthority>(
+            r#"
+            SELECT hr.*, ha.name as authority_name
+            FROM health_records hr
+            JOIN health_authorities ha ON hr.authority_id = ha.id
+            WHERE hr.user_id = $1 AND ($2 OR hr.is_revoked = FALSE)
+            ORDER BY hr.created_at, hr.id
+            "#
+        )
+        .bind(user_id)
+        .bind(query.include_revoked.unwrap_or(false))
+        .fetch(db);
+
+        // Co-signatures are loaded per batch of records rather than per record
+        let mut batch = Vec::with_capacity(EXPORT_BATCH_RECORDS);
+        loop {
+            let row = rows.try_next().await?;
+            let done = row.is_none();
+            batch.extend(row);
+            if batch.len() < EXPORT_BATCH_RECORDS && !done {
+                continue;
+            }
+
+            let record_ids: Vec<Uuid> = batch.iter().map(|row: &HealthRecordWithAuthority| row.record.id).collect();
+            access.record(&record_ids).await?;
+            let co_signatures = self.load_co_signatures(&record_ids).await?;
+            for response in self.record_responses(std::mem::take(&mut batch), co_signatures).await? {
+                match format {
+                    RecordExportFormat::Csv => push_record_csv_line(&response, chunk)?,
+                    RecordExportFormat::Json => {
+                        if !std::mem::take(&mut first) {
+                            chunk.push(',');
+                        }
+                        chunk.push_str(&serde_json::to_string(&response).map_err(export_error)?);
+                    }
+                }
+            }
+
+            if done {
+                return Ok(());
+            }
+            if chunk.len() >= EXPORT_CHUNK_BYTES && tx.send(Ok(std::mem::take(chunk))).await.is_err() {
+                // The client went away
+                return Ok(());
+            }
         }
+    }
+
+    /// `record_response` for each row, in order. Decryption and hashing are
+    /// CPU-bound, so rows are checked concurrently on blocking threads, as
+    /// many at a time as `RECORD_READ_PARALLELISM` allows.
+    async fn record_responses(
+        &self,
+        rows: Vec<HealthRecordWithAuthority>,
+        mut co_signatures: HashMap<Uuid, Vec<CoSignature>>,
+    ) -> Result<Vec<HealthRecordResponse>, AppError> {
+        let record_reads = self.crypto_service.record_reads();
+        let checks = rows.into_iter().map(|row| {
+            let co_signatures = co_signatures.remove(&row.record.id).unwrap_or_default();
+            let service = self.clone();
+            let record_reads = record_reads.clone();
+            async move {
+                let permit = record_reads
+                    .acquire_owned()
+                    .await
+                    .map_err(|e| AppError::InternalServerError(format!("Record read pool closed: {}", e)))?;
+                tokio::task::spawn_blocking(move || {
+                    let _permit = permit;
+                    service.record_response(row, co_signatures)
+                })
+                .await
+                .map_err(|e| AppError::InternalServerError(format!("Record check failed: {}", e)))?
+            }
+        });
 
-        Ok(responses)
+        futures::future::try_join_all(checks).await
+    }
+
+    /// Decrypt a stored record and check its integrity and signatures
+    fn record_response(
+        &self,
+        HealthRecordWithAuthority { record, authority_name }: HealthRecordWithAuthority,
+        co_signatures: Vec<CoSignature>,
+    ) -> Result<HealthRecordResponse, AppError> {
+        let record_type = record.record_type;
+        let fields = self.crypto_service.open_fields(
+            SensitiveFields { patient_identifier: record.patient_identifier, details: record.details },
+            EncryptedFields::from_columns(
+                record.encrypted_fields,
+                record.encryption_nonce,
+                record.encrypted_data_key,
+                record.encryption_key_id,
+            ),
+        )?;
+        let integrity_ok = Self::check_integrity(record.id, &StoredSignedContent {
+            record_type: &record_type,
+            patient_identifier: &fields.patient_identifier,
+            details: &fields.details,
+            issue_date: record.issue_date,
+            issuer: &authority_name,
+            expiry_date: record.expiry_date,
+            format_version: record.format_version,
+            message_hash: &record.message_hash,
+        });
+        let (signing_authorities, has_valid_signature) = Self::signing_status(
+            record.authority_id,
+            &authority_name,
+            &record.message_hash,
+            co_signatures,
+        );
+
+        Ok(HealthRecordResponse {
+            id: record.id,
+            record_type,
+            patient_identifier: fields.patient_identifier,
+            details: fields.details,
+            issue_date: record.issue_date,
+            expiry_date: record.expiry_date,
+            authority_name,
+            authority_id: record.authority_id,
+            is_revoked: record.is_revoked,
+            revoked_at: record.revoked_at,
+            revocation_reason: record.revocation_reason,
+            created_at: record.created_at,
+            has_valid_signature,
+            integrity_ok,
+            signing_authorities,
+            series_id: record.series_id,
+        })
     }
 
     pub async fn get_health_record_by_id(
@@ -249,6 +1038,25 @@ impl HealthRecordService {
     ) -> Result<HealthRecordResponse, AppError> {
         let db = &self.auth_service.db;
 
+        // Grantees of an active share may read the record like its owner
+        let user_id = match user_id {
+            Some(uid) => match self.active_share_id(record_id, uid).await? {
+                Some(share_id) => {
+                    tracing::info!(
+                        target: "audit",
+                        event = "record_share_access",
+                        record_id = %record_id,
+                        share_id = %share_id,
+                        grantee_id = %uid,
+                        "Health record read through a share"
+                    );
+                    None
+                }
+                None => Some(uid),
+            },
+            None => None,
+        };
+
         let mut sql = String::from(
             r#"
             SELECT hr.*, ha.name as authority_name
@@ -269,17 +1077,51 @@ impl HealthRecordService {
             .await?
             .ok_or_else(|| AppError::NotFound("Health record not found or access denied".to_string()))?;
 
+            let record_type: HealthRecordType = serde_json::from_value(serde_json::Value::String(record.record_type))?;
+            let fields = self.crypto_service.open_fields(
+                SensitiveFields { patient_identifier: record.patient_identifier, details: record.details },
+                EncryptedFields::from_columns(
+                    record.encrypted_fields,
+                    record.encryption_nonce,
+                    record.encrypted_data_key,
+                    record.encryption_key_id,
+                ),
+            )?;
+            let integrity_ok = Self::check_integrity(record.id, &StoredSignedContent {
+                record_type: &record_type,
+                patient_identifier: &fields.patient_identifier,
+                details: &fields.details,
+                issue_date: record.issue_date,
+                issuer: &record.authority_name,
+                expiry_date: record.expiry_date,
+                format_version: record.format_version,
+                message_hash: &record.message_hash,
+            });
+            let co_signatures = self.load_co_signatures(&[record.id]).await?.remove(&record.id);
+            let (signing_authorities, has_valid_signature) = Self::signing_status(
+                record.authority_id,
+                &record.authority_name,
+                &record.message_hash,
+                co_signatures.unwrap_or_default(),
+            );
+
             return Ok(HealthRecordResponse {
                 id: record.id,
-                record_type: serde_json::from_value(serde_json::Value::String(record.record_type))?,
-                patient_identifier: record.patient_identifier,
-                details: record.details,
+                record_type,
+                patient_identifier: fields.patient_identifier,
+                details: fields.details,
                 issue_date: record.issue_date,
                 expiry_date: record.expiry_date,
                 authority_name: record.authority_name,
+                authority_id: record.authority_id,
                 is_revoked: record.is_revoked,
+                revoked_at: record.revoked_at,
+                revocation_reason: record.revocation_reason,
                 created_at: record.created_at,
-                has_valid_signature: !record.signature_r.is_empty() && !record.signature_s.is_empty(),
+                has_valid_signature,
+                integrity_ok,
+                signing_authorities,
+                series_id: record.series_id,
             });
         }
 
@@ -296,17 +1138,51 @@ impl HealthRecordService {
         .await?
         .ok_or_else(|| AppError::NotFound("Health record not found".to_string()))?;
 
+        let record_type: HealthRecordType = serde_json::from_value(serde_json::Value::String(record.record_type))?;
+        let fields = self.crypto_service.open_fields(
+            SensitiveFields { patient_identifier: record.patient_identifier, details: record.details },
+            EncryptedFields::from_columns(
+                record.encrypted_fields,
+                record.encryption_nonce,
+                record.encrypted_data_key,
+                record.encryption_key_id,
+            ),
+        )?;
+        let integrity_ok = Self::check_integrity(record.id, &StoredSignedContent {
+            record_type: &record_type,
+            patient_identifier: &fields.patient_identifier,
+            details: &fields.details,
+            issue_date: record.issue_date,
+            issuer: &record.authority_name,
+            expiry_date: record.expiry_date,
+            format_version: record.format_version,
+            message_hash: &record.message_hash,
+        });
+        let co_signatures = self.load_co_signatures(&[record.id]).await?.remove(&record.id);
+        let (signing_authorities, has_valid_signature) = Self::signing_status(
+            record.authority_id,
+            &record.authority_name,
+            &record.message_hash,
+            co_signatures.unwrap_or_default(),
+        );
+
         Ok(HealthRecordResponse {
             id: record.id,
-            record_type: serde_json::from_value(serde_json::Value::String(record.record_type))?,
-            patient_identifier: record.patient_identifier,
-            details: record.details,
+            record_type,
+            patient_identifier: fields.patient_identifier,
+            details: fields.details,
             issue_date: record.issue_date,
             expiry_date: record.expiry_date,
             authority_name: record.authority_name,
+            authority_id: record.authority_id,
             is_revoked: record.is_revoked,
+            revoked_at: record.revoked_at,
+            revocation_reason: record.revocation_reason,
             created_at: record.created_at,
-            has_valid_signature: !record.signature_r.is_empty() && !record.signature_s.is_empty(),
+            has_valid_signature,
+            integrity_ok,
+            signing_authorities,
+            series_id: record.series_id,
         })
     }
 
@@ -320,22 +1196,38 @@ impl HealthRecordService {
         let db = &self.auth_service.db;
 
         // Verify ownership
-        let existing_record = sqlx::query!(
-            "SELECT id FROM health_records WHERE id = $1 AND user_id = $2",
-            record_id,
-            user_id
+        let mut existing_record = sqlx::query_as::<_, HealthRecord>(
+            "SELECT * FROM health_records WHERE id = $1 AND user_id = $2"
         )
+        .bind(record_id)
+        .bind(user_id)
         .fetch_optional(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Health record not found or access denied".to_string()))?;
 
-        // Update the record
+        // Update the record, re-encrypting its fields when they are stored encrypted
         if let Some(new_details) = details {
-            sqlx::query!(
-                "UPDATE health_records SET details = $1, updated_at = NOW() WHERE id = $2",
-                serde_json::to_value(&new_details)?,
-                record_id
+            self.crypto_service.decrypt_record(&mut existing_record)?;
+            let (stored, encrypted) = self.crypto_service.seal_fields(SensitiveFields {
+                patient_identifier: existing_record.patient_identifier,
+                details: serde_json::to_value(&new_details)?,
+            })?;
+
+            sqlx::query(
+                r#"
+                UPDATE health_records
+                SET patient_identifier = $1, details = $2, encrypted_fields = $3, encryption_nonce = $4,
+                    encrypted_data_key = $5, encryption_key_id = $6, updated_at = NOW()
+                WHERE id = $7
+                "#
             )
+            .bind(&stored.patient_identifier)
+            .bind(&stored.details)
+            .bind(encrypted.as_ref().map(|e| &e.ciphertext))
+            .bind(encrypted.as_ref().map(|e| &e.nonce))
+            .bind(encrypted.as_ref().map(|e| &e.wrapped_key))
+            .bind(encrypted.as_ref().map(|e| &e.key_id))
+            .bind(record_id)
             .execute(db)
             .await?;
         }
@@ -354,6 +1246,428 @@ impl HealthRecordService {
         self.get_health_record_by_id(record_id, Some(user_id)).await
     }
 
+    /// Let another user of the record's organization view a record until
+    /// `expires_at`. Sharing with the same user again replaces the expiry.
+    pub async fn share_record(
+        &self,
+        record_id: Uuid,
+        owner_id: Uuid,
+        grantee_email: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<RecordShareResponse, AppError> {
+        let db = &self.auth_service.db;
+
+        if expires_at <= Utc::now() {
+            return Err(AppError::BadRequest("Share expiry must be in the future".to_string()));
+        }
+
+        let record_org = sqlx::query_scalar::<_, Uuid>(
+            "SELECT org_id FROM health_records WHERE id = $1 AND user_id = $2 AND is_revoked = FALSE"
+        )
+        .bind(record_id)
+        .bind(owner_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Health record not found or access denied".to_string()))?;
+
+        // Users of other organizations are treated as unknown
+        let grantee_id = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM users WHERE email = $1 AND org_id = $2"
+        )
+        .bind(grantee_email)
+        .bind(record_org)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        if grantee_id == owner_id {
+            return Err(AppError::BadRequest("Records cannot be shared with their owner".to_string()));
+        }
+
+        let share = sqlx::query_as::<_, RecordShare>(
+            r#"
+            INSERT INTO record_shares (health_record_id, owner_id, grantee_id, expires_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (health_record_id, grantee_id) WHERE revoked_at IS NULL
+            DO UPDATE SET expires_at = EXCLUDED.expires_at
+            RETURNING *
+            "#
+        )
+        .bind(record_id)
+        .bind(owner_id)
+        .bind(grantee_id)
+        .bind(expires_at)
+        .fetch_one(db)
+        .await?;
+
+        tracing::info!(
+            target: "audit",
+            event = "record_shared",
+            record_id = %record_id,
+            share_id = %share.id,
+            grantee_id = %grantee_id,
+            expires_at = %expires_at,
+            "Health record shared"
+        );
+
+        Ok(RecordShareResponse {
+            id: share.id,
+            health_record_id: share.health_record_id,
+            grantee_email: grantee_email.to_string(),
+            expires_at: share.expires_at,
+            created_at: share.created_at,
+        })
+    }
+
+    /// End a share before it expires
+    pub async fn revoke_share(&self, share_id: Uuid, owner_id: Uuid) -> Result<(), AppError> {
+        let db = &self.auth_service.db;
+
+        let result = sqlx::query(
+            "UPDATE record_shares SET revoked_at = NOW() WHERE id = $1 AND owner_id = $2 AND revoked_at IS NULL"
+        )
+        .bind(share_id)
+        .bind(owner_id)
+        .execute(db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Share not found or access denied".to_string()));
+        }
+
+        tracing::info!(target: "audit", event = "record_share_revoked", share_id = %share_id, "Health record share revoked");
+
+        Ok(())
+    }
+
+    /// Active shares of a record owned by `owner_id`
+    pub async fn get_record_shares(&self, record_id: Uuid, owner_id: Uuid) -> Result<Vec<RecordShareResponse>, AppError> {
+        let db = &self.auth_service.db;
+
+        let shares = sqlx::query_as::<_, RecordShareResponse>(
+            r#"
+            SELECT rs.id, rs.health_record_id, u.email as grantee_email, rs.expires_at, rs.created_at
+            FROM record_shares rs
+            JOIN users u ON rs.grantee_id = u.id
+            WHERE rs.health_record_id = $1 AND rs.owner_id = $2
+              AND rs.revoked_at IS NULL AND rs.expires_at > NOW()
+            ORDER BY rs.created_at DESC
+            "#
+        )
+        .bind(record_id)
+        .bind(owner_id)
+        .fetch_all(db)
+        .await?;
+
+        Ok(shares)
+    }
+
+    /// Records other users currently share with `grantee_id`
+    pub async fn get_shared_records(&self, grantee_id: Uuid) -> Result<Vec<HealthRecordResponse>, AppError> {
+        let db = &self.auth_service.db;
+
+        let record_ids = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT rs.health_record_id
+            FROM record_shares rs
+            JOIN health_records hr ON rs.health_record_id = hr.id
+            WHERE rs.grantee_id = $1 AND rs.revoked_at IS NULL AND rs.expires_at > NOW()
+              AND hr.is_revoked = FALSE
+            ORDER BY rs.created_at DESC
+            "#
+        )
+        .bind(grantee_id)
+        .fetch_all(db)
+        .await?;
+
+        let mut records = Vec::with_capacity(record_ids.len());
+        for record_id in record_ids {
+            records.push(self.get_health_record_by_id(record_id, Some(grantee_id)).await?);
+        }
+
+        Ok(records)
+    }
+
+    /// Save reusable details for an authority. `provider_id` must represent
+    /// the authority (`None` for admins); authorities outside `org_scope` are
+    /// treated as missing.
+    pub async fn create_template(
+        &self,
+        authority_id: Uuid,
+        request: CreateTemplateRequest,
+        provider_id: Option<Uuid>,
+        created_by: Uuid,
+        org_scope: Option<Uuid>,
+    ) -> Result<RecordTemplate, AppError> {
+        let db = &self.auth_service.db;
+
+        let org_id = self.find_authority_org(authority_id, org_scope).await?;
+        self.ensure_provider_represents(provider_id, authority_id).await?;
+
+        let template = sqlx::query_as::<_, RecordTemplate>(
+            r#"
+            INSERT INTO record_templates (authority_id, org_id, name, record_type, details, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#
+        )
+        .bind(authority_id)
+        .bind(org_id)
+        .bind(&request.name)
+        .bind(&request.record_type)
+        .bind(serde_json::to_value(&request.details)?)
+        .bind(created_by)
+        .fetch_one(db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+                AppError::Conflict("A template with this name already exists for the authority".to_string())
+            }
+            e => e.into(),
+        })?;
+
+        Ok(template)
+    }
+
+    /// Templates of an authority, scoped like `create_template`
+    pub async fn get_templates(
+        &self,
+        authority_id: Uuid,
+        provider_id: Option<Uuid>,
+        org_scope: Option<Uuid>,
+    ) -> Result<Vec<RecordTemplate>, AppError> {
+        self.find_authority_org(authority_id, org_scope).await?;
+        self.ensure_provider_represents(provider_id, authority_id).await?;
+
+        let templates = sqlx::query_as::<_, RecordTemplate>(
+            "SELECT * FROM record_templates WHERE authority_id = $1 ORDER BY name"
+        )
+        .bind(authority_id)
+        .fetch_all(&self.auth_service.db)
+        .await?;
+
+        Ok(templates)
+    }
+
+    /// Create a record from a template, with `overrides.details` merged over
+    /// the template's details. The merged details must be complete for the
+    /// template's record type.
+    pub async fn create_health_record_from_template(
+        &self,
+        template_id: Uuid,
+        overrides: CreateFromTemplateRequest,
+        user_id: Uuid,
+        org_scope: Option<Uuid>,
+    ) -> Result<HealthRecordResponse, AppError> {
+        let template = sqlx::query_as::<_, RecordTemplate>(
+            "SELECT * FROM record_templates WHERE id = $1 AND ($2::uuid IS NULL OR org_id = $2)"
+        )
+        .bind(template_id)
+        .bind(org_scope)
+        .fetch_optional(&self.auth_service.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Record template not found".to_string()))?;
+
+        let details = merge_details(&template.details, overrides.details);
+        Self::validate_details(&template.record_type, &details)?;
+
+        let request = CreateHealthRecordRequest {
+            authority_id: template.authority_id,
+            record_type: template.record_type,
+            patient_identifier: overrides.patient_identifier,
+            details,
+            issue_date: overrides.issue_date,
+            expiry_date: overrides.expiry_date,
+            co_signing_authority_ids: vec![],
+            allow_duplicate: overrides.allow_duplicate,
+            series_id: overrides.series_id,
+        };
+
+        self.create_health_record(request, user_id, org_scope).await
+    }
+
+    /// Check details against the structure for their record type
+    pub(crate) fn validate_details(
+        record_type: &HealthRecordType,
+        details: &HashMap<String, serde_json::Value>,
+    ) -> Result<(), AppError> {
+        let value = serde_json::to_value(details)?;
+        let result = match record_type {
+            HealthRecordType::Vaccination => serde_json::from_value::<VaccinationDetails>(value).map(|_| ()),
+            HealthRecordType::TestResult => serde_json::from_value::<TestResultDetails>(value).map(|_| ()),
+            HealthRecordType::MedicalClearance => serde_json::from_value::<MedicalClearanceDetails>(value).map(|_| ()),
+            HealthRecordType::ImmunityProof => serde_json::from_value::<ImmunityProofDetails>(value).map(|_| ()),
+        };
+
+        result.map_err(|e| AppError::Validation {
+            message: format!("details: {}", e),
+            fields: Some(HashMap::from([("details".to_string(), vec![e.to_string()])])),
+        })
+    }
+
+    async fn find_authority_org(&self, authority_id: Uuid, org_scope: Option<Uuid>) -> Result<Uuid, AppError> {
+        sqlx::query_scalar::<_, Uuid>(
+            "SELECT org_id FROM health_authorities WHERE id = $1 AND ($2::uuid IS NULL OR org_id = $2)"
+        )
+        .bind(authority_id)
+        .bind(org_scope)
+        .fetch_optional(&self.auth_service.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Health authority not found".to_string()))
+    }
+
+    /// Start handing a record over to another user of its organization. The
+    /// record only changes owner once the recipient accepts with the returned
+    /// token; starting a new transfer cancels any pending one.
+    pub async fn transfer_record(
+        &self,
+        record_id: Uuid,
+        from_user: Uuid,
+        to_user_email: &str,
+    ) -> Result<RecordTransferResponse, AppError> {
+        let db = &self.auth_service.db;
+
+        let (record_org, is_revoked) = sqlx::query_as::<_, (Uuid, bool)>(
+            "SELECT org_id, is_revoked FROM health_records WHERE id = $1 AND user_id = $2"
+        )
+        .bind(record_id)
+        .bind(from_user)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Health record not found or access denied".to_string()))?;
+
+        if is_revoked {
+            return Err(AppError::BadRequest("Revoked records cannot be transferred".to_string()));
+        }
+
+        let to_user = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE email = $1 AND org_id = $2")
+            .bind(to_user_email)
+            .bind(record_org)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        if to_user == from_user {
+            return Err(AppError::BadRequest("Records cannot be transferred to their owner".to_string()));
+        }
+
+        let token = generate_transfer_token();
+        let mut tx = db.begin().await?;
+
+        sqlx::query("DELETE FROM record_transfers WHERE health_record_id = $1 AND accepted_at IS NULL")
+            .bind(record_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let transfer = sqlx::query_as::<_, RecordTransfer>(
+            r#"
+            INSERT INTO record_transfers (health_record_id, from_user_id, to_user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#
+        )
+        .bind(record_id)
+        .bind(from_user)
+        .bind(to_user)
+        .bind(hash_transfer_token(&token))
+        .bind(Utc::now() + Duration::hours(TRANSFER_EXPIRATION_HOURS))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            target: "audit",
+            event = "record_transfer_requested",
+            record_id = %record_id,
+            transfer_id = %transfer.id,
+            from_user_id = %from_user,
+            to_user_id = %to_user,
+            "Health record transfer requested"
+        );
+
+        Ok(RecordTransferResponse {
+            id: transfer.id,
+            health_record_id: record_id,
+            recipient_email: to_user_email.to_string(),
+            expires_at: transfer.expires_at,
+            token,
+        })
+    }
+
+    /// Make `recipient_id` the owner of a record they were offered. Signatures
+    /// and proofs stay with the record; the previous owner's shares end.
+    pub async fn accept_transfer(&self, token: &str, recipient_id: Uuid) -> Result<HealthRecordResponse, AppError> {
+        let db = &self.auth_service.db;
+        let mut tx = db.begin().await?;
+
+        // Claiming the transfer first means a token can only ever be used once
+        let transfer = sqlx::query_as::<_, RecordTransfer>(
+            r#"
+            UPDATE record_transfers SET accepted_at = NOW()
+            WHERE token_hash = $1 AND to_user_id = $2 AND accepted_at IS NULL AND expires_at > NOW()
+            RETURNING *
+            "#
+        )
+        .bind(hash_transfer_token(token))
+        .bind(recipient_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Transfer not found, already accepted or expired".to_string()))?;
+
+        // The record may have been revoked since the transfer was offered
+        let moved = sqlx::query(
+            "UPDATE health_records SET user_id = $1, updated_at = NOW() WHERE id = $2 AND user_id = $3 AND is_revoked = FALSE"
+        )
+        .bind(recipient_id)
+        .bind(transfer.health_record_id)
+        .bind(transfer.from_user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if moved.rows_affected() == 0 {
+            return Err(AppError::BadRequest("Revoked records cannot be transferred".to_string()));
+        }
+
+        sqlx::query(
+            "UPDATE record_shares SET revoked_at = NOW() WHERE health_record_id = $1 AND owner_id = $2 AND revoked_at IS NULL"
+        )
+        .bind(transfer.health_record_id)
+        .bind(transfer.from_user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            target: "audit",
+            event = "record_transfer_accepted",
+            record_id = %transfer.health_record_id,
+            transfer_id = %transfer.id,
+            from_user_id = %transfer.from_user_id,
+            to_user_id = %recipient_id,
+            "Health record transferred"
+        );
+
+        self.get_health_record_by_id(transfer.health_record_id, Some(recipient_id)).await
+    }
+
+    /// The unexpired, unrevoked share of a record with a user, if any
+    async fn active_share_id(&self, record_id: Uuid, grantee_id: Uuid) -> Result<Option<Uuid>, AppError> {
+        let share_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT id FROM record_shares
+            WHERE health_record_id = $1 AND grantee_id = $2 AND revoked_at IS NULL AND expires_at > NOW()
+            "#
+        )
+        .bind(record_id)
+        .bind(grantee_id)
+        .fetch_optional(&self.auth_service.db)
+        .await?;
+
+        Ok(share_id)
+    }
+
     pub async fn delete_health_record(&self, record_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
         let db = &self.auth_service.db;
 
@@ -372,25 +1686,261 @@ impl HealthRecordService {
         Ok(())
     }
 
-    pub async fn revoke_health_record(&self, record_id: Uuid, _revoker_user_id: Uuid) -> Result<(), AppError> {
+    /// Revoke a record of the caller's organization (`org_scope`, `None` for superadmins),
+    /// keeping when and why. Revoking it again keeps the original time and reason.
+    pub async fn revoke_health_record(
+        &self,
+        record_id: Uuid,
+        _revoker_user_id: Uuid,
+        reason: &str,
+        org_scope: Option<Uuid>,
+    ) -> Result<(), AppError> {
         let db = &self.auth_service.db;
 
-        let result = sqlx::query!(
-            "UPDATE health_records SET is_revoked = TRUE, updated_at = NOW() WHERE id = $1",
-            record_id
+        let (record_type, authority_id, revoked_at) = sqlx::query_as::<_, (HealthRecordType, Uuid, DateTime<Utc>)>(
+            r#"
+            UPDATE health_records
+            SET is_revoked = TRUE,
+                revoked_at = COALESCE(revoked_at, NOW()),
+                revocation_reason = COALESCE(revocation_reason, $3),
+                updated_at = NOW()
+            WHERE id = $1 AND ($2::uuid IS NULL OR org_id = $2)
+            RETURNING record_type, authority_id, revoked_at
+            "#
         )
-        .execute(db)
-        .await?;
+        .bind(record_id)
+        .bind(org_scope)
+        .bind(reason.trim())
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Health record not found".to_string()))?;
 
-        if result.rows_affected() == 0 {
-            return Err(AppError::NotFound("Health record not found".to_string()));
-        }
+        self.notify_owner(record_id, RecordNotification::Revoked).await;
+        self.publish(WebhookEvent::RecordRevoked(RecordEvent {
+            record_id,
+            record_type,
+            authority_id,
+            occurred_at: revoked_at,
+        }));
 
         Ok(())
     }
 
-    fn extract_details_for_signing(
+    fn publish(&self, event: WebhookEvent) {
+        if let Some(webhook_service) = &self.webhook_service {
+            webhook_service.dispatch_record_event(event);
+        }
+    }
+
+    /// Email the record's owner unless they opted out. Never fails: lookup
+    /// and delivery errors are only logged.
+    async fn notify_owner(&self, record_id: Uuid, notification: RecordNotification) {
+        let Some(email_service) = &self.email_service else {
+            return;
+        };
+
+        let owner = sqlx::query_as::<_, (String, String, bool, Option<String>, HealthRecordType, String)>(
+            r#"
+            SELECT u.email, u.full_name, u.email_notifications, u.locale, hr.record_type, ha.name
+            FROM health_records hr
+            JOIN users u ON hr.user_id = u.id
+            JOIN health_authorities ha ON hr.authority_id = ha.id
+            WHERE hr.id = $1
+            "#
+        )
+        .bind(record_id)
+        .fetch_optional(&self.auth_service.db)
+        .await;
+
+        match owner {
+            Ok(Some((email, full_name, true, locale, record_type, authority_name))) => {
+                let locale = locale.as_deref().and_then(Locale::from_tag).unwrap_or_default();
+                let (subject, body) = notification.render(locale, &full_name, &record_type, &authority_name);
+                email_service.send_in_background(&email, subject, body);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to look up the owner of record {} for notification: {}", record_id, e),
+        }
+    }
+
+    /// Recompute the message hash from the record's current fields and compare it with the
+    /// stored one. Unsigned records carry a zeroed placeholder hash and always pass.
+    pub(crate) fn check_integrity(record_id: Uuid, content: &StoredSignedContent) -> bool {
+        if is_unsigned(content.message_hash) {
+            return true;
+        }
+
+        let expected_hash = Self::extract_details_for_signing(content.details, content.record_type)
+            .and_then(|details| {
+                let issue_date = content.issue_date.to_string();
+                Ok(message_format::formatter(content.format_version)?.message_hash(&SignableFields {
+                    record_type: content.record_type,
+                    patient_identifier: content.patient_identifier,
+                    details: &details,
+                    issue_date: &issue_date,
+                    issuer: content.issuer,
+                    expiry_date: content.expiry_date,
+                }))
+            });
+
+        let integrity_ok = matches!(expected_hash, Ok(hash) if hash.as_slice() == content.message_hash);
+        if !integrity_ok {
+            tracing::warn!(
+                target: "audit",
+                event = "record_integrity_mismatch",
+                record_id = %record_id,
+                format_version = content.format_version,
+                "Stored message hash does not match the health record's current contents"
+            );
+        }
+
+        integrity_ok
+    }
+
+    /// Series a new dose belongs to: the one named in the request, or else the
+    /// series of earlier doses of the same vaccine given to the same patient
+    /// by the same authority, started here if there is none yet
+    async fn resolve_series(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        request: &CreateHealthRecordRequest,
+        org_id: Uuid,
+    ) -> Result<Option<Uuid>, AppError> {
+        if !matches!(request.record_type, HealthRecordType::Vaccination) {
+            if request.series_id.is_some() {
+                return Err(AppError::BadRequest("Only vaccination records can belong to a series".to_string()));
+            }
+            return Ok(None);
+        }
+
+        if let Some(series_id) = request.series_id {
+            return sqlx::query_scalar::<_, Uuid>("SELECT id FROM vaccination_series WHERE id = $1 AND org_id = $2")
+                .bind(series_id)
+                .bind(org_id)
+                .fetch_optional(&mut **tx)
+                .await?
+                .map(Some)
+                .ok_or_else(|| AppError::NotFound("Vaccination series not found".to_string()));
+        }
+
+        let Some(vaccine_name) = request.details.get("vaccine_name").and_then(|v| v.as_str()) else {
+            return Ok(None);
+        };
+        // Keyed like the content fingerprint so the patient identifier isn't exposed
+        let series_key = self.crypto_service.content_fingerprint(&format!(
+            "series|{}|{}|{}",
+            request.authority_id,
+            request.patient_identifier.trim(),
+            vaccine_name.trim()
+        ));
+
+        let series_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO vaccination_series (authority_id, org_id, series_key)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (series_key) DO UPDATE SET series_key = EXCLUDED.series_key
+            RETURNING id
+            "#
+        )
+        .bind(request.authority_id)
+        .bind(org_id)
+        .bind(&series_key)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(Some(series_id))
+    }
+
+    /// The doses of a vaccination series in order, with its completeness.
+    ///
+    /// `user_id` limits the series to that user's doses (`None` for admins);
+    /// series outside `org_scope` are treated as missing.
+    pub async fn get_vaccination_series(
         &self,
+        series_id: Uuid,
+        user_id: Option<Uuid>,
+        org_scope: Option<Uuid>,
+    ) -> Result<VaccinationSeriesResponse, AppError> {
+        let db = &self.auth_service.db;
+
+        let records = sqlx::query_as::<_, HealthRecord>(
+            r#"
+            SELECT * FROM health_records
+            WHERE series_id = $1 AND ($2::uuid IS NULL OR user_id = $2) AND ($3::uuid IS NULL OR org_id = $3)
+            "#
+        )
+        .bind(series_id)
+        .bind(user_id)
+        .bind(org_scope)
+        .fetch_all(db)
+        .await?;
+
+        if records.is_empty() {
+            return Err(AppError::NotFound("Vaccination series not found".to_string()));
+        }
+
+        let authority_name = sqlx::query_scalar::<_, String>(
+            "SELECT ha.name FROM vaccination_series vs JOIN health_authorities ha ON vs.authority_id = ha.id WHERE vs.id = $1"
+        )
+        .bind(series_id)
+        .fetch_one(db)
+        .await?;
+
+        let mut vaccine_name = None;
+        let mut total_doses = None;
+        let mut doses = Vec::with_capacity(records.len());
+        for mut record in records {
+            self.crypto_service.decrypt_record(&mut record)?;
+
+            let detail = |key: &str| record.details.get(key).and_then(|v| v.as_u64()).map(|n| n as u32);
+            let dose_number = detail("dose_number");
+            if !record.is_revoked {
+                total_doses = total_doses.max(detail("total_doses"));
+                if vaccine_name.is_none() {
+                    vaccine_name = record.details.get("vaccine_name").and_then(|v| v.as_str()).map(str::to_string);
+                }
+            }
+
+            doses.push(SeriesDose {
+                record_id: record.id,
+                dose_number,
+                issue_date: record.issue_date,
+                is_revoked: record.is_revoked,
+            });
+        }
+        doses.sort_by_key(|dose| (dose.dose_number.unwrap_or(u32::MAX), dose.issue_date));
+
+        let (doses_received, is_complete) = series_completeness(&doses, total_doses);
+
+        Ok(VaccinationSeriesResponse {
+            series_id,
+            vaccine_name,
+            authority_name,
+            total_doses,
+            doses_received,
+            is_complete,
+            doses,
+        })
+    }
+
+    /// Identifies a record by authority, type, patient, the details that are
+    /// signed and issue date; other details don't make a record distinct
+    fn content_fingerprint(&self, request: &CreateHealthRecordRequest) -> Result<Vec<u8>, AppError> {
+        let details = Self::extract_details_for_signing(&serde_json::to_value(&request.details)?, &request.record_type)?;
+        let content = format!(
+            "{}|{}|{}|{}|{}",
+            request.authority_id,
+            message_format::record_type_tag(&request.record_type),
+            request.patient_identifier.trim(),
+            details,
+            request.issue_date
+        );
+
+        Ok(self.crypto_service.content_fingerprint(&content))
+    }
+
+    /// Condense record details into the short form embedded in the signed message
+    pub(crate) fn extract_details_for_signing(
         details: &serde_json::Value,
         record_type: &HealthRecordType,
     ) -> Result<String, AppError> {
@@ -426,3 +1976,1036 @@ impl HealthRecordService {
         }
     }
 }
+
+/// Template details with `overrides` replacing any keys both set
+fn merge_details(
+    template: &serde_json::Value,
+    overrides: HashMap<String, serde_json::Value>,
+) -> HashMap<String, serde_json::Value> {
+    let mut details: HashMap<String, serde_json::Value> = template
+        .as_object()
+        .map(|object| object.clone().into_iter().collect())
+        .unwrap_or_default();
+    details.extend(overrides);
+    details
+}
+
+fn generate_transfer_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", TRANSFER_TOKEN_PREFIX, hex::encode(bytes))
+}
+
+/// Only this digest is stored, like invite tokens
+fn hash_transfer_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Distinct dose numbers received and whether every dose up to `total_doses`
+/// is present. Revoked doses don't count.
+fn series_completeness(doses: &[SeriesDose], total_doses: Option<u32>) -> (u32, bool) {
+    let received: std::collections::BTreeSet<u32> = doses
+        .iter()
+        .filter(|dose| !dose.is_revoked)
+        .filter_map(|dose| dose.dose_number)
+        .collect();
+
+    let is_complete = match total_doses {
+        Some(total) if total > 0 => (1..=total).all(|n| received.contains(&n)),
+        _ => false,
+    };
+
+    (received.len() as u32, is_complete)
+}
+
+/// Append `record` to a CSV export; `details` is written as JSON
+fn push_record_csv_line(record: &HealthRecordResponse, csv: &mut String) -> Result<(), AppError> {
+    let record_type = serde_json::to_value(&record.record_type).map_err(export_error)?;
+    let fields = [
+        record.id.to_string(),
+        record_type.as_str().unwrap_or_default().to_string(),
+        record.patient_identifier.clone(),
+        serde_json::to_string(&record.details).map_err(export_error)?,
+        record.issue_date.to_string(),
+        record.expiry_date.map(|date| date.to_string()).unwrap_or_default(),
+        record.authority_name.clone(),
+        record.is_revoked.to_string(),
+        record.created_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        record.has_valid_signature.to_string(),
+        record.integrity_ok.to_string(),
+        record.series_id.map(|id| id.to_string()).unwrap_or_default(),
+    ];
+    let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+    csv.push_str(&fields.join(","));
+    csv.push('\n');
+    Ok(())
+}
+
+fn export_error(e: serde_json::Error) -> AppError {
+    AppError::InternalServerError(format!("Failed to serialize record export: {}", e))
+}
+
+fn duplicate_record(existing_id: Uuid) -> AppError {
+    AppError::Conflict(format!(
+        "An identical health record already exists ({}); set allow_duplicate to create it anyway",
+        existing_id
+    ))
+}
+
+/// Records are created with a zeroed placeholder hash until their authority signs
+pub(crate) fn is_unsigned(message_hash: &[u8]) -> bool {
+    message_hash.iter().all(|byte| *byte == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integrity_check_detects_tampering() {
+        let crypto_service = CryptoService::new();
+        let (private_key, _) = crypto_service.generate_key_pair();
+        let details = serde_json::json!({"vaccine_name": "Pfizer"});
+        let issue_date = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+
+        let details_str = HealthRecordService::extract_details_for_signing(&details, &HealthRecordType::Vaccination).unwrap();
+        let issue_date_str = issue_date.to_string();
+        let fields = SignableFields {
+            record_type: &HealthRecordType::Vaccination,
+            patient_identifier: "P1",
+            details: &details_str,
+            issue_date: &issue_date_str,
+            issuer: "Clinic",
+            expiry_date: None,
+        };
+        let signature = crypto_service.sign_health_record(&fields, &private_key).unwrap();
+
+        let content = StoredSignedContent {
+            record_type: &HealthRecordType::Vaccination,
+            patient_identifier: "P1",
+            details: &details,
+            issue_date,
+            issuer: "Clinic",
+            expiry_date: None,
+            format_version: signature.format_version,
+            message_hash: &signature.message_hash,
+        };
+        assert!(HealthRecordService::check_integrity(Uuid::new_v4(), &content));
+
+        let tampered = serde_json::json!({"vaccine_name": "Moderna"});
+        let tampered_content = StoredSignedContent { details: &tampered, ..content };
+        assert!(!HealthRecordService::check_integrity(Uuid::new_v4(), &tampered_content));
+
+        // Unsigned records only carry the zeroed placeholder hash
+        let unsigned = StoredSignedContent { message_hash: &[0u8; 32], ..content };
+        assert!(HealthRecordService::check_integrity(Uuid::new_v4(), &unsigned));
+
+        // From format 3 on the expiry is signed too
+        let expiry_date = NaiveDate::from_ymd_opt(2026, 1, 1);
+        let v3 = CryptoService::new().with_message_format_version(3).unwrap();
+        let signature = v3.sign_health_record(&SignableFields { expiry_date, ..fields }, &private_key).unwrap();
+        let content = StoredSignedContent {
+            expiry_date,
+            format_version: signature.format_version,
+            message_hash: &signature.message_hash,
+            ..content
+        };
+        assert!(HealthRecordService::check_integrity(Uuid::new_v4(), &content));
+        let extended = StoredSignedContent { expiry_date: NaiveDate::from_ymd_opt(2030, 1, 1), ..content };
+        assert!(!HealthRecordService::check_integrity(Uuid::new_v4(), &extended));
+    }
+
+    #[test]
+    fn test_all_co_signatures_required_for_valid_signature() {
+        let hash = vec![7u8; 32];
+        let co_signature = |position: i32, message_hash: Option<Vec<u8>>| CoSignature {
+            health_record_id: Uuid::nil(),
+            authority_id: Uuid::new_v4(),
+            authority_name: format!("Co-signer {}", position),
+            position,
+            message_hash,
+        };
+
+        let (authorities, all_signed) = HealthRecordService::signing_status(
+            Uuid::new_v4(),
+            "Issuer",
+            &hash,
+            vec![co_signature(1, Some(hash.clone())), co_signature(2, None)],
+        );
+        assert_eq!(authorities.iter().map(|a| a.position).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(authorities.iter().map(|a| a.signed).collect::<Vec<_>>(), vec![true, true, false]);
+        assert!(!all_signed);
+
+        // A co-signature over an older message hash is stale
+        let (_, all_signed) = HealthRecordService::signing_status(
+            Uuid::new_v4(),
+            "Issuer",
+            &hash,
+            vec![co_signature(1, Some(vec![1u8; 32]))],
+        );
+        assert!(!all_signed);
+
+        let (_, all_signed) = HealthRecordService::signing_status(
+            Uuid::new_v4(),
+            "Issuer",
+            &hash,
+            vec![co_signature(1, Some(hash.clone())), co_signature(2, Some(hash.clone()))],
+        );
+        assert!(all_signed);
+
+        // Nothing counts until the issuing authority has signed
+        let (authorities, all_signed) = HealthRecordService::signing_status(Uuid::new_v4(), "Issuer", &[0u8; 32], vec![]);
+        assert!(!authorities[0].signed);
+        assert!(!all_signed);
+    }
+
+    #[test]
+    fn test_template_details_are_merged_and_validated() {
+        let template = serde_json::json!({
+            "vaccine_name": "Pfizer",
+            "manufacturer": "Pfizer-BioNTech",
+            "lot_number": "OLD-LOT",
+            "total_doses": 2,
+            "vaccination_site": "Left arm",
+            "administrator": "Nurse Joy",
+        });
+        let overrides = HashMap::from([
+            ("lot_number".to_string(), serde_json::json!("EK9231")),
+            ("dose_number".to_string(), serde_json::json!(1)),
+        ]);
+
+        let merged = merge_details(&template, overrides);
+        assert_eq!(merged["lot_number"], "EK9231");
+        assert_eq!(merged["vaccine_name"], "Pfizer");
+        assert!(HealthRecordService::validate_details(&HealthRecordType::Vaccination, &merged).is_ok());
+
+        // Without the dose number the details are incomplete
+        let partial = merge_details(&template, HashMap::new());
+        let result = HealthRecordService::validate_details(&HealthRecordType::Vaccination, &partial);
+        assert!(matches!(result, Err(AppError::Validation { fields: Some(_), .. })));
+    }
+
+    #[test]
+    fn test_only_listed_sorts_are_accepted() {
+        let sort: RecordSort = serde_json::from_value(serde_json::json!("issue_date_asc")).unwrap();
+        assert_eq!(sort.order_by(), "hr.issue_date ASC, hr.id ASC");
+        assert_eq!(RecordSort::default().order_by(), "hr.created_at DESC, hr.id DESC");
+
+        // Anything else is rejected before it gets near the query
+        assert!(serde_json::from_value::<RecordSort>(serde_json::json!("issue_date; DROP TABLE users")).is_err());
+        assert!(serde_json::from_value::<RecordSort>(serde_json::json!("patient_identifier")).is_err());
+    }
+
+    #[test]
+    fn test_series_completeness() {
+        let dose = |dose_number: u32, is_revoked: bool| SeriesDose {
+            record_id: Uuid::new_v4(),
+            dose_number: Some(dose_number),
+            issue_date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            is_revoked,
+        };
+
+        assert_eq!(series_completeness(&[dose(1, false)], Some(2)), (1, false));
+        assert_eq!(series_completeness(&[dose(1, false), dose(2, false)], Some(2)), (2, true));
+        // Repeated doses count once, and revoked doses not at all
+        assert_eq!(series_completeness(&[dose(1, false), dose(1, false), dose(2, true)], Some(2)), (1, false));
+        // Without a known course length a series is never complete
+        assert_eq!(series_completeness(&[dose(1, false)], None), (1, false));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_providers_only_sign_for_their_authority(db: PgPool) {
+        let crypto_service = Arc::new(CryptoService::new());
+        let service = HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            crypto_service.clone(),
+        );
+        let (private_key, public_key) = crypto_service.generate_key_pair();
+        let private_key = hex::encode(private_key.secret_bytes());
+
+        let provider_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role) VALUES ('provider@example.com', 'x', 'Provider', 'provider') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key) VALUES ('Clinic B', 'clinic', $1) RETURNING id"
+        )
+        .bind(hex::encode(public_key.serialize()))
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let record_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO health_records (user_id, authority_id, record_type, patient_identifier, details, issue_date, signature_r, signature_s, message_hash)
+            VALUES ($1, $2, 'vaccination', 'P-1', '{}', CURRENT_DATE, '\x00', '\x00', '\x00')
+            RETURNING id
+            "#
+        )
+        .bind(provider_id)
+        .bind(authority_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let result = service.sign_health_record(record_id, &private_key, Some(provider_id), None).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+        sqlx::query("INSERT INTO provider_authorities (user_id, authority_id) VALUES ($1, $2)")
+            .bind(provider_id)
+            .bind(authority_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        assert!(service.sign_health_record(record_id, &private_key, Some(provider_id), None).await.is_ok());
+
+        // Admins are not scoped to an authority
+        assert!(service.sign_health_record(record_id, &private_key, None, None).await.is_ok());
+
+        // Nobody can sign with a key other than the authority's
+        let (foreign_key, _) = crypto_service.generate_key_pair();
+        let result = service.sign_health_record(record_id, &hex::encode(foreign_key.secret_bytes()), None, None).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_p256_records_are_only_signed_with_the_authority_key(db: PgPool) {
+        let crypto_service = Arc::new(CryptoService::new());
+        let service = HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            crypto_service.clone(),
+        );
+        let (private_key, public_key) = crypto_service.p256().generate_key_pair();
+        let (foreign_key, _) = crypto_service.p256().generate_key_pair();
+
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name) VALUES ('holder@example.com', 'x', 'Holder') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key, curve) VALUES ('Clinic', 'clinic', $1, 'p256') RETURNING id"
+        )
+        .bind(hex::encode(public_key.to_encoded_point(true).as_bytes()))
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let record_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO health_records (user_id, authority_id, record_type, patient_identifier, details, issue_date, signature_r, signature_s, message_hash)
+            VALUES ($1, $2, 'vaccination', 'P-1', '{}', CURRENT_DATE, '\x00', '\x00', '\x00')
+            RETURNING id
+            "#
+        )
+        .bind(user_id)
+        .bind(authority_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let result = service.sign_health_record(record_id, &hex::encode(foreign_key.to_bytes()), None, None).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        let signed = service.sign_health_record(record_id, &hex::encode(private_key.to_bytes()), None, None).await.unwrap();
+        assert!(signed.has_valid_signature);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_organizations_cannot_reach_each_others_records(db: PgPool) {
+        let crypto_service = Arc::new(CryptoService::new());
+        let service = HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            crypto_service.clone(),
+        );
+        let (private_key, public_key) = crypto_service.generate_key_pair();
+        let private_key = hex::encode(private_key.secret_bytes());
+
+        let org_a: Uuid = sqlx::query_scalar("INSERT INTO organizations (name) VALUES ('Hospital A') RETURNING id")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let org_b: Uuid = sqlx::query_scalar("INSERT INTO organizations (name) VALUES ('Hospital B') RETURNING id")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let user_b: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role, org_id) VALUES ('b@example.com', 'x', 'B', 'patient', $1) RETURNING id"
+        )
+        .bind(org_b)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let authority_b: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key, org_id) VALUES ('Clinic B', 'clinic', $1, $2) RETURNING id"
+        )
+        .bind(hex::encode(public_key.serialize()))
+        .bind(org_b)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let record_b: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO health_records (user_id, authority_id, record_type, patient_identifier, details, issue_date, signature_r, signature_s, message_hash, org_id)
+            VALUES ($1, $2, 'vaccination', 'P-1', '{}', CURRENT_DATE, '\x00', '\x00', '\x00', $3)
+            RETURNING id
+            "#
+        )
+        .bind(user_b)
+        .bind(authority_b)
+        .bind(org_b)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let request = || CreateHealthRecordRequest {
+            authority_id: authority_b,
+            record_type: HealthRecordType::Vaccination,
+            patient_identifier: "P-2".to_string(),
+            details: HashMap::new(),
+            issue_date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            expiry_date: None,
+            co_signing_authority_ids: vec![],
+            allow_duplicate: false,
+            series_id: None,
+        };
+
+        // Org A sees org B's authority and record as missing
+        let created = service.create_health_record(request(), user_b, Some(org_a)).await;
+        assert!(matches!(created, Err(AppError::NotFound(_))));
+        let signed = service.sign_health_record(record_b, &private_key, None, Some(org_a)).await;
+        assert!(matches!(signed, Err(AppError::NotFound(_))));
+        let revoked = service.revoke_health_record(record_b, user_b, "issued in error", Some(org_a)).await;
+        assert!(matches!(revoked, Err(AppError::NotFound(_))));
+
+        // Org B itself, and superadmins, can act on them
+        let created = service.create_health_record(request(), user_b, Some(org_b)).await.unwrap();
+        let created_org: Uuid = sqlx::query_scalar("SELECT org_id FROM health_records WHERE id = $1")
+            .bind(created.id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(created_org, org_b);
+        assert!(service.sign_health_record(record_b, &private_key, None, Some(org_b)).await.is_ok());
+        assert!(service.revoke_health_record(record_b, user_b, "issued in error", None).await.is_ok());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_identical_records_are_rejected(db: PgPool) {
+        let service = HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            Arc::new(CryptoService::new()),
+        );
+
+        let org_id: Uuid = sqlx::query_scalar("INSERT INTO organizations (name) VALUES ('Hospital') RETURNING id")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role, org_id) VALUES ('p@example.com', 'x', 'P', 'patient', $1) RETURNING id"
+        )
+        .bind(org_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key, org_id) VALUES ('Clinic', 'clinic', '02', $1) RETURNING id"
+        )
+        .bind(org_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let request = |allow_duplicate: bool| CreateHealthRecordRequest {
+            authority_id,
+            record_type: HealthRecordType::Vaccination,
+            patient_identifier: "P-1".to_string(),
+            details: HashMap::from([("vaccine_name".to_string(), serde_json::json!("Pfizer"))]),
+            issue_date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            expiry_date: None,
+            co_signing_authority_ids: vec![],
+            allow_duplicate,
+            series_id: None,
+        };
+
+        let original = service.create_health_record(request(false), user_id, None).await.unwrap();
+
+        let resubmitted = service.create_health_record(request(false), user_id, None).await;
+        assert!(matches!(resubmitted, Err(AppError::Conflict(_))));
+
+        // Explicitly allowed duplicates are created
+        assert!(service.create_health_record(request(true), user_id, None).await.is_ok());
+
+        // Revoking the original frees its fingerprint
+        service.revoke_health_record(original.id, user_id, "superseded", None).await.unwrap();
+        assert!(service.create_health_record(request(false), user_id, None).await.is_ok());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_revoking_records_when_and_why(db: PgPool) {
+        let service = HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            Arc::new(CryptoService::new()),
+        );
+
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role) VALUES ('p@example.com', 'x', 'P', 'patient') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key) VALUES ('Clinic', 'clinic', '02') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let record = service
+            .create_health_record(
+                CreateHealthRecordRequest {
+                    authority_id,
+                    record_type: HealthRecordType::Vaccination,
+                    patient_identifier: "P-1".to_string(),
+                    details: HashMap::from([("vaccine_name".to_string(), serde_json::json!("Pfizer"))]),
+                    issue_date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                    expiry_date: None,
+                    co_signing_authority_ids: vec![],
+                    allow_duplicate: false,
+                    series_id: None,
+                },
+                user_id,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(record.revoked_at.is_none() && record.revocation_reason.is_none());
+
+        service.revoke_health_record(record.id, user_id, " superseded ", None).await.unwrap();
+        let revoked = service.get_health_record_by_id(record.id, Some(user_id)).await.unwrap();
+        assert!(revoked.is_revoked);
+        assert_eq!(revoked.revocation_reason.as_deref(), Some("superseded"));
+        let revoked_at = revoked.revoked_at.unwrap();
+
+        // A second revocation keeps the original time and reason
+        service.revoke_health_record(record.id, user_id, "issued in error", None).await.unwrap();
+        let again = service.get_health_record_by_id(record.id, Some(user_id)).await.unwrap();
+        assert_eq!(again.revoked_at, Some(revoked_at));
+        assert_eq!(again.revocation_reason.as_deref(), Some("superseded"));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_records_stay_readable_across_key_rotation(db: PgPool) {
+        use crate::services::field_encryption::{self, FieldCipher};
+        use base64::{Engine as _, engine::general_purpose};
+
+        let (key_v1, key_v2) = (general_purpose::STANDARD.encode([1u8; 32]), general_purpose::STANDARD.encode([2u8; 32]));
+        let service = |cipher: FieldCipher| HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            Arc::new(CryptoService::new().with_field_cipher(cipher)),
+        );
+        let v1 = FieldCipher::new(&key_v1, &[]).unwrap();
+        let v2 = FieldCipher::new(&key_v2, &[key_v1.clone()]).unwrap();
+        let stored_key_id = |record_id: Uuid| {
+            sqlx::query_scalar::<_, Option<String>>("SELECT encryption_key_id FROM health_records WHERE id = $1")
+                .bind(record_id)
+                .fetch_one(&db)
+        };
+
+        let (user_id, authority_id, _) = seed_batch_fixtures(&db).await;
+        let record = service(v1.clone())
+            .create_health_record(batch_request(authority_id, 1), user_id, None)
+            .await
+            .unwrap();
+        assert_eq!(stored_key_id(record.id).await.unwrap().as_deref(), Some(v1.key_id()));
+
+        // After rotating to v2, the row written under v1 still reads
+        let read = service(v2.clone()).get_health_record_by_id(record.id, Some(user_id)).await.unwrap();
+        assert_eq!(read.patient_identifier, "P-1");
+        assert_eq!(read.details, serde_json::json!({"test_type": "PCR"}));
+
+        let status = field_encryption::encryption_status(&db, &v2, false).await.unwrap();
+        assert_eq!(status.pending_records, 1);
+
+        let summary = field_encryption::reencrypt_records(&db, &v2, 1).await.unwrap();
+        assert_eq!((summary.encrypted, summary.rewrapped, summary.failed), (0, 1, 0));
+        assert_eq!(stored_key_id(record.id).await.unwrap().as_deref(), Some(v2.key_id()));
+        assert_eq!(field_encryption::encryption_status(&db, &v2, false).await.unwrap().pending_records, 0);
+
+        // Once migrated, v1 can be retired
+        let v2_only = FieldCipher::new(&key_v2, &[]).unwrap();
+        let read = service(v2_only).get_health_record_by_id(record.id, Some(user_id)).await.unwrap();
+        assert_eq!(read.patient_identifier, "P-1");
+    }
+
+    /// Organization, patient user, issuing and co-signing authority for the batch tests
+    async fn seed_batch_fixtures(db: &PgPool) -> (Uuid, Uuid, Uuid) {
+        let org_id: Uuid = sqlx::query_scalar("INSERT INTO organizations (name) VALUES ('Hospital') RETURNING id")
+            .fetch_one(db)
+            .await
+            .unwrap();
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role, org_id) VALUES ('p@example.com', 'x', 'P', 'patient', $1) RETURNING id"
+        )
+        .bind(org_id)
+        .fetch_one(db)
+        .await
+        .unwrap();
+        let authority_ids: Vec<Uuid> = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key, org_id) VALUES ('Clinic', 'clinic', '02', $1), ('Lab', 'laboratory', '02', $1) RETURNING id"
+        )
+        .bind(org_id)
+        .fetch_all(db)
+        .await
+        .unwrap();
+        (user_id, authority_ids[0], authority_ids[1])
+    }
+
+    fn batch_request(authority_id: Uuid, patient: usize) -> CreateHealthRecordRequest {
+        CreateHealthRecordRequest {
+            authority_id,
+            record_type: HealthRecordType::TestResult,
+            patient_identifier: format!("P-{}", patient),
+            details: HashMap::from([("test_type".to_string(), serde_json::json!("PCR"))]),
+            issue_date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            expiry_date: None,
+            co_signing_authority_ids: vec![],
+            allow_duplicate: false,
+            series_id: None,
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_batch_creates_all_records_or_none(db: PgPool) {
+        let service = HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            Arc::new(CryptoService::new()),
+        );
+        let (user_id, authority_id, co_signer_id) = seed_batch_fixtures(&db).await;
+
+        let mut requests: Vec<_> = (0..3).map(|patient| batch_request(authority_id, patient)).collect();
+        requests[1].co_signing_authority_ids = vec![co_signer_id];
+        let ids = service.create_health_records_batch(requests, user_id, None).await.unwrap();
+        assert_eq!(ids.len(), 3);
+
+        let patients: Vec<String> = sqlx::query_scalar(
+            "SELECT patient_identifier FROM health_records WHERE id = ANY($1) ORDER BY array_position($1, id)"
+        )
+        .bind(&ids)
+        .fetch_all(&db)
+        .await
+        .unwrap();
+        assert_eq!(patients, vec!["P-0", "P-1", "P-2"]);
+        let slots: Vec<Uuid> = sqlx::query_scalar("SELECT health_record_id FROM record_signatures WHERE authority_id = $1")
+            .bind(co_signer_id)
+            .fetch_all(&db)
+            .await
+            .unwrap();
+        assert_eq!(slots, vec![ids[1]]);
+
+        // An already issued record rejects the whole batch
+        let resubmitted = vec![batch_request(authority_id, 3), batch_request(authority_id, 0)];
+        let error = service.create_health_records_batch(resubmitted, user_id, None).await.unwrap_err();
+        assert!(matches!(error, AppError::Conflict(message) if message.contains(&ids[0].to_string())));
+
+        // So do two identical records within the batch
+        let twice = vec![batch_request(authority_id, 4), batch_request(authority_id, 4)];
+        assert!(matches!(service.create_health_records_batch(twice, user_id, None).await, Err(AppError::Conflict(_))));
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM health_records").fetch_one(&db).await.unwrap();
+        assert_eq!(count, 3);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_search_ranks_the_callers_matching_records(db: PgPool) {
+        let service = HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            Arc::new(CryptoService::new()),
+        );
+        let (user_id, authority_id, _) = seed_batch_fixtures(&db).await;
+        let other_user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role) VALUES ('o@example.com', 'x', 'O', 'patient') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let mut ids = Vec::new();
+        for (owner, patient, details) in [
+            (user_id, "P-1", r#"{"vaccine_name": "Comirnaty", "manufacturer": "Pfizer"}"#),
+            (user_id, "P-2", r#"{"vaccine_name": "Pfizer", "manufacturer": "Pfizer", "vaccination_site": "City Hall"}"#),
+            (user_id, "P-3", r#"{"test_type": "PCR", "laboratory": "Central Lab"}"#),
+            (other_user_id, "P-4", r#"{"vaccine_name": "Pfizer", "manufacturer": "Pfizer"}"#),
+        ] {
+            let id: Uuid = sqlx::query_scalar(
+                r#"
+                INSERT INTO health_records (user_id, authority_id, record_type, patient_identifier, details, issue_date, signature_r, signature_s, message_hash)
+                VALUES ($1, $2, 'vaccination', $3, $4::jsonb, CURRENT_DATE, '\x00', '\x00', '\x00')
+                RETURNING id
+                "#
+            )
+            .bind(owner)
+            .bind(authority_id)
+            .bind(patient)
+            .bind(details)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+            ids.push(id);
+        }
+
+        let search = |text: &str| HealthRecordQuery {
+            record_type: None,
+            authority_id: None,
+            from_date: None,
+            to_date: None,
+            include_revoked: None,
+            page: None,
+            limit: None,
+            cursor: None,
+            sort: None,
+            search: Some(text.to_string()),
+        };
+
+        let found = service.get_user_health_records(user_id, search("pfizer")).await.unwrap();
+        // The record naming Pfizer twice ranks first; the other user's record is never returned
+        assert_eq!(found.data.iter().map(|r| r.id).collect::<Vec<_>>(), vec![ids[1], ids[0]]);
+        assert!(found.next_cursor.is_none());
+
+        let found = service.get_user_health_records(user_id, search("central lab")).await.unwrap();
+        assert_eq!(found.data.iter().map(|r| r.id).collect::<Vec<_>>(), vec![ids[2]]);
+
+        // Query syntax is data, not SQL or tsquery operators
+        let found = service.get_user_health_records(user_id, search("'); DROP TABLE health_records; --")).await.unwrap();
+        assert!(found.data.is_empty());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_export_streams_large_record_sets(db: PgPool) {
+        use crate::models::AccessType;
+        use crate::services::AccessLogService;
+
+        const RECORDS: usize = 3000;
+        let service = HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            Arc::new(CryptoService::new()),
+        );
+        let (user_id, authority_id, _) = seed_batch_fixtures(&db).await;
+        sqlx::query(
+            r#"
+            INSERT INTO health_records (user_id, authority_id, record_type, patient_identifier, details, issue_date, signature_r, signature_s, message_hash, is_revoked, created_at)
+            SELECT $1, $2, 'test_result', 'P-' || n, '{"test_type": "PCR", "note": "a, \"quoted\" note"}', DATE '2025-01-01' + n % 365, '\x00', '\x00', '\x00', n = 0,
+                   NOW() + n * INTERVAL '1 millisecond'
+            FROM generate_series(0, $3) AS n
+            "#
+        )
+        .bind(user_id)
+        .bind(authority_id)
+        .bind(RECORDS as i32)
+        .execute(&db)
+        .await
+        .unwrap();
+
+        // Every exported record is logged while the access log is enabled
+        let access = AccessLogService::new(db.clone(), true)
+            .for_reader(user_id, None, AccessType::Export, Some("portability".to_string()));
+        let exports_logged = || async {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM record_access_log WHERE access_type = 'export' AND actor_id = $1")
+                .bind(user_id)
+                .fetch_one(&db)
+                .await
+                .unwrap()
+        };
+
+        let chunks: Vec<String> = service
+            .export_records(user_id, RecordExportQuery::default(), access.clone())
+            .try_collect()
+            .await
+            .unwrap();
+        // Sent as it is read rather than as one buffer
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.len() < 2 * EXPORT_CHUNK_BYTES));
+        let csv = chunks.concat();
+        assert!(csv.starts_with(RECORD_CSV_HEADER));
+        // One line per record besides the revoked one, plus the header
+        assert_eq!(csv.lines().count(), RECORDS + 1);
+        assert!(csv.contains(r#""{""note"":""a, \""quoted\"" note"",""test_type"":""PCR""}""#));
+        assert_eq!(exports_logged().await, RECORDS as i64);
+
+        let query = RecordExportQuery { format: Some(RecordExportFormat::Json), include_revoked: Some(true) };
+        let json: Vec<String> = service.export_records(user_id, query, access).try_collect().await.unwrap();
+        let records: Vec<serde_json::Value> = serde_json::from_str(&json.concat()).unwrap();
+        assert_eq!(records.len(), RECORDS + 1);
+        assert_eq!(records[0]["patient_identifier"], "P-0");
+        assert_eq!(records[0]["is_revoked"], true);
+        assert_eq!(exports_logged().await, 2 * RECORDS as i64 + 1);
+    }
+
+    /// Compares the batch path with a loop over `create_health_record`:
+    /// `cargo test batch_insert_benchmark -- --ignored --nocapture`
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore]
+    async fn batch_insert_benchmark(db: PgPool) {
+        const RECORDS: usize = 1000;
+        let service = HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            Arc::new(CryptoService::new()),
+        );
+        let (user_id, authority_id, _) = seed_batch_fixtures(&db).await;
+
+        let started = std::time::Instant::now();
+        for patient in 0..RECORDS {
+            service.create_health_record(batch_request(authority_id, patient), user_id, None).await.unwrap();
+        }
+        let per_row = started.elapsed();
+
+        let requests = (RECORDS..2 * RECORDS).map(|patient| batch_request(authority_id, patient)).collect();
+        let started = std::time::Instant::now();
+        let ids = service.create_health_records_batch(requests, user_id, None).await.unwrap();
+        let batched = started.elapsed();
+
+        assert_eq!(ids.len(), RECORDS);
+        println!("{} records: per-row {:?}, batched {:?}", RECORDS, per_row, batched);
+    }
+
+    /// Latency of a 100-record page of encrypted records, checked one at a
+    /// time and in parallel: `cargo test record_read_benchmark -- --ignored --nocapture`
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore]
+    async fn record_read_benchmark(db: PgPool) {
+        use crate::services::crypto::DEFAULT_RECORD_READ_PARALLELISM;
+        use crate::services::field_encryption::FieldCipher;
+        use base64::{Engine as _, engine::general_purpose};
+
+        let cipher = FieldCipher::new(&general_purpose::STANDARD.encode([1u8; 32]), &[]).unwrap();
+        let service = |parallelism: usize| HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            Arc::new(CryptoService::new().with_field_cipher(cipher.clone()).with_record_read_parallelism(parallelism)),
+        );
+        let (user_id, authority_id, _) = seed_batch_fixtures(&db).await;
+        let requests = (0..100).map(|patient| batch_request(authority_id, patient)).collect();
+        service(1).create_health_records_batch(requests, user_id, None).await.unwrap();
+
+        let page = || HealthRecordQuery {
+            record_type: None,
+            authority_id: None,
+            from_date: None,
+            to_date: None,
+            include_revoked: None,
+            page: None,
+            limit: Some(100),
+            cursor: None,
+            sort: None,
+            search: None,
+        };
+        let mut pages = Vec::new();
+        for parallelism in [1, DEFAULT_RECORD_READ_PARALLELISM] {
+            let started = std::time::Instant::now();
+            let records = service(parallelism).get_user_health_records(user_id, page()).await.unwrap();
+            println!("parallelism {}: {:?}", parallelism, started.elapsed());
+            pages.push(records.data.into_iter().map(|r| r.id).collect::<Vec<_>>());
+        }
+
+        // Same records in the same order either way
+        assert_eq!(pages[0].len(), 100);
+        assert_eq!(pages[0], pages[1]);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_doses_are_grouped_into_a_series(db: PgPool) {
+        let service = HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            Arc::new(CryptoService::new()),
+        );
+
+        let org_id: Uuid = sqlx::query_scalar("INSERT INTO organizations (name) VALUES ('Hospital') RETURNING id")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name, role, org_id) VALUES ('p@example.com', 'x', 'P', 'patient', $1) RETURNING id"
+        )
+        .bind(org_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key, org_id) VALUES ('Clinic', 'clinic', '02', $1) RETURNING id"
+        )
+        .bind(org_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let dose = |vaccine_name: &str, dose_number: u32, day: u32| CreateHealthRecordRequest {
+            authority_id,
+            record_type: HealthRecordType::Vaccination,
+            patient_identifier: "P-1".to_string(),
+            details: HashMap::from([
+                ("vaccine_name".to_string(), serde_json::json!(vaccine_name)),
+                ("dose_number".to_string(), serde_json::json!(dose_number)),
+                ("total_doses".to_string(), serde_json::json!(2)),
+            ]),
+            issue_date: NaiveDate::from_ymd_opt(2025, 1, day).unwrap(),
+            expiry_date: None,
+            co_signing_authority_ids: vec![],
+            allow_duplicate: false,
+            series_id: None,
+        };
+
+        // Dose 2 is recorded first; the series is still ordered by dose number
+        let second = service.create_health_record(dose("Pfizer", 2, 22), user_id, None).await.unwrap();
+        let series_id = second.series_id.unwrap();
+        let series = service.get_vaccination_series(series_id, Some(user_id), None).await.unwrap();
+        assert_eq!((series.doses_received, series.is_complete), (1, false));
+
+        let first = service.create_health_record(dose("Pfizer", 1, 1), user_id, None).await.unwrap();
+        assert_eq!(first.series_id, Some(series_id));
+        let other = service.create_health_record(dose("Moderna", 1, 1), user_id, None).await.unwrap();
+        assert_ne!(other.series_id, Some(series_id));
+
+        let series = service.get_vaccination_series(series_id, Some(user_id), None).await.unwrap();
+        assert_eq!(series.doses.iter().map(|d| d.record_id).collect::<Vec<_>>(), vec![first.id, second.id]);
+        assert_eq!(series.total_doses, Some(2));
+        assert!(series.is_complete);
+
+        let stranger = service.get_vaccination_series(series_id, Some(Uuid::new_v4()), None).await;
+        assert!(matches!(stranger, Err(AppError::NotFound(_))));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_shares_grant_read_access_until_revoked_or_expired(db: PgPool) {
+        let service = HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            Arc::new(CryptoService::new()),
+        );
+
+        let org_id: Uuid = sqlx::query_scalar("INSERT INTO organizations (name) VALUES ('Hospital') RETURNING id")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let other_org: Uuid = sqlx::query_scalar("INSERT INTO organizations (name) VALUES ('Elsewhere') RETURNING id")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let user = |email: &'static str, org_id: Uuid| {
+            sqlx::query_scalar::<_, Uuid>(
+                "INSERT INTO users (email, password_hash, full_name, role, org_id) VALUES ($1, 'x', 'U', 'patient', $2) RETURNING id"
+            )
+            .bind(email)
+            .bind(org_id)
+            .fetch_one(&db)
+        };
+        let owner = user("owner@example.com", org_id).await.unwrap();
+        let doctor = user("doctor@example.com", org_id).await.unwrap();
+        user("outsider@example.com", other_org).await.unwrap();
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key, org_id) VALUES ('Clinic', 'clinic', '02', $1) RETURNING id"
+        )
+        .bind(org_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let record = service
+            .create_health_record(
+                CreateHealthRecordRequest {
+                    authority_id,
+                    record_type: HealthRecordType::TestResult,
+                    patient_identifier: "P-1".to_string(),
+                    details: HashMap::new(),
+                    issue_date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                    expiry_date: None,
+                    co_signing_authority_ids: vec![],
+                    allow_duplicate: false,
+                    series_id: None,
+                },
+                owner,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let expires_at = Utc::now() + Duration::days(1);
+        assert!(service.get_health_record_by_id(record.id, Some(doctor)).await.is_err());
+        let outsider = service.share_record(record.id, owner, "outsider@example.com", expires_at).await;
+        assert!(matches!(outsider, Err(AppError::NotFound(_))));
+        let share = service.share_record(record.id, owner, "doctor@example.com", expires_at).await.unwrap();
+
+        assert_eq!(service.get_health_record_by_id(record.id, Some(doctor)).await.unwrap().id, record.id);
+        assert_eq!(service.get_shared_records(doctor).await.unwrap().len(), 1);
+        // Only the owner manages shares
+        assert!(service.revoke_share(share.id, doctor).await.is_err());
+
+        sqlx::query("UPDATE record_shares SET expires_at = NOW() - INTERVAL '1 minute' WHERE id = $1")
+            .bind(share.id)
+            .execute(&db)
+            .await
+            .unwrap();
+        assert!(service.get_health_record_by_id(record.id, Some(doctor)).await.is_err());
+        assert!(service.get_shared_records(doctor).await.unwrap().is_empty());
+
+        // Sharing again renews the same share, which the owner can then revoke
+        let renewed = service.share_record(record.id, owner, "doctor@example.com", expires_at).await.unwrap();
+        assert_eq!(renewed.id, share.id);
+        assert!(service.get_health_record_by_id(record.id, Some(doctor)).await.is_ok());
+        service.revoke_share(share.id, owner).await.unwrap();
+        assert!(service.get_health_record_by_id(record.id, Some(doctor)).await.is_err());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_transfer_moves_record_once_recipient_accepts(db: PgPool) {
+        let service = HealthRecordService::new(
+            Arc::new(AuthService::new(db.clone(), "test-secret".to_string(), 1)),
+            Arc::new(CryptoService::new()),
+        );
+
+        let org_id: Uuid = sqlx::query_scalar("INSERT INTO organizations (name) VALUES ('Hospital') RETURNING id")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let user = |email: &'static str| {
+            sqlx::query_scalar::<_, Uuid>(
+                "INSERT INTO users (email, password_hash, full_name, role, org_id) VALUES ($1, 'x', 'U', 'patient', $2) RETURNING id"
+            )
+            .bind(email)
+            .bind(org_id)
+            .fetch_one(&db)
+        };
+        let guardian = user("guardian@example.com").await.unwrap();
+        let adult = user("adult@example.com").await.unwrap();
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key, org_id) VALUES ('Clinic', 'clinic', '02', $1) RETURNING id"
+        )
+        .bind(org_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let request = |day: u32| CreateHealthRecordRequest {
+            authority_id,
+            record_type: HealthRecordType::MedicalClearance,
+            patient_identifier: "P-1".to_string(),
+            details: HashMap::new(),
+            issue_date: NaiveDate::from_ymd_opt(2025, 1, day).unwrap(),
+            expiry_date: None,
+            co_signing_authority_ids: vec![],
+            allow_duplicate: false,
+            series_id: None,
+        };
+        let record = service.create_health_record(request(1), guardian, None).await.unwrap();
+
+        let transfer = service.transfer_record(record.id, guardian, "adult@example.com").await.unwrap();
+        // Nothing moves until the recipient accepts, and only they can accept
+        assert!(service.get_health_record_by_id(record.id, Some(adult)).await.is_err());
+        assert!(service.accept_transfer(&transfer.token, guardian).await.is_err());
+
+        let accepted = service.accept_transfer(&transfer.token, adult).await.unwrap();
+        assert_eq!(accepted.id, record.id);
+        assert!(service.get_health_record_by_id(record.id, Some(guardian)).await.is_err());
+        assert!(service.accept_transfer(&transfer.token, adult).await.is_err());
+
+        let revoked = service.create_health_record(request(2), guardian, None).await.unwrap();
+        service.revoke_health_record(revoked.id, guardian, "superseded", None).await.unwrap();
+        let refused = service.transfer_record(revoked.id, guardian, "adult@example.com").await;
+        assert!(matches!(refused, Err(AppError::BadRequest(_))));
+    }
+}
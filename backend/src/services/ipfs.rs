@@ -0,0 +1,127 @@
+//! Off-chain storage of proof data on IPFS.
+//!
+//! Proof blobs are too large to put on-chain, so anchoring pins them on an
+//! IPFS node and submits only the content ID (CID) next to the proof hash.
+//! Verifiers holding just the CID can have the server fetch the blob back
+//! through a gateway.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::errors::AppError;
+
+const IPFS_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest blob fetched from a gateway; proofs are far smaller
+const MAX_FETCH_BYTES: usize = 10 * 1024 * 1024;
+
+/// Reply of the node's `/api/v0/add`
+#[derive(Deserialize)]
+struct AddResponse {
+    #[serde(rename = "Hash")]
+    hash: String,
+}
+
+pub struct IpfsService {
+    http: reqwest::Client,
+    api_url: String,
+    gateway_url: String,
+}
+
+impl IpfsService {
+    pub fn new(api_url: String, gateway_url: String) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(IPFS_REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            gateway_url: gateway_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Add and pin a blob on the node; returns its CID
+    pub async fn pin(&self, data: &[u8]) -> Result<String, AppError> {
+        let form = reqwest::multipart::Form::new()
+            .part("file", reqwest::multipart::Part::bytes(data.to_vec()).file_name("proof"));
+
+        let response = self
+            .http
+            .post(format!("{}/api/v0/add?pin=true&cid-version=1", self.api_url))
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("IPFS node unreachable: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ServiceUnavailable(format!(
+                "IPFS node rejected the pin: HTTP {}",
+                response.status()
+            )));
+        }
+
+        let added: AddResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("Invalid IPFS add response: {}", e)))?;
+        Ok(added.hash)
+    }
+
+    /// Fetch a blob by CID through the gateway
+    pub async fn fetch(&self, cid: &str) -> Result<Vec<u8>, AppError> {
+        if !is_valid_cid(cid) {
+            return Err(AppError::BadRequest("Invalid IPFS CID".to_string()));
+        }
+
+        let response = self
+            .http
+            .get(format!("{}/ipfs/{}", self.gateway_url, cid))
+            .send()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("IPFS gateway unreachable: {}", e)))?;
+
+        match response.status() {
+            status if status.is_success() => {}
+            reqwest::StatusCode::NOT_FOUND => {
+                return Err(AppError::NotFound("Proof data not found on IPFS".to_string()))
+            }
+            status => {
+                return Err(AppError::ServiceUnavailable(format!("IPFS gateway returned HTTP {}", status)))
+            }
+        }
+
+        if response.content_length().map_or(false, |length| length as usize > MAX_FETCH_BYTES) {
+            return Err(AppError::PayloadTooLarge("Proof data on IPFS is too large".to_string()));
+        }
+        let data = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("Failed to read IPFS data: {}", e)))?;
+        if data.len() > MAX_FETCH_BYTES {
+            return Err(AppError::PayloadTooLarge("Proof data on IPFS is too large".to_string()));
+        }
+
+        Ok(data.to_vec())
+    }
+}
+
+/// CIDv0 (base58btc) and CIDv1 (base32/base36/base58) strings are alphanumeric;
+/// anything else would change the gateway path
+fn is_valid_cid(cid: &str) -> bool {
+    (10..=128).contains(&cid.len()) && cid.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cids_cannot_escape_the_gateway_path() {
+        assert!(is_valid_cid("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"));
+        assert!(is_valid_cid("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"));
+        assert!(!is_valid_cid("../../admin"));
+        assert!(!is_valid_cid("bafy?x=1"));
+        assert!(!is_valid_cid(""));
+    }
+}
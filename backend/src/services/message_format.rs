@@ -0,0 +1,237 @@
+//! Versioned formats for the message an authority signs over a health record.
+//!
+//! Every record stores the `format_version` it was signed with, so the exact
+//! preimage can always be rebuilt with the formatter that produced it, even
+//! after the current format changes.
+
+use chrono::NaiveDate;
+use sha2::{Digest, Sha256};
+
+use crate::errors::AppError;
+use crate::models::HealthRecordType;
+
+/// Version used for new signatures unless `MESSAGE_FORMAT_VERSION` says otherwise
+pub const DEFAULT_MESSAGE_FORMAT_VERSION: i32 = 1;
+
+/// The record fields covered by a signature
+#[derive(Debug, Clone, Copy)]
+pub struct SignableFields<'a> {
+    pub record_type: &'a HealthRecordType,
+    pub patient_identifier: &'a str,
+    pub details: &'a str,
+    pub issue_date: &'a str,
+    pub issuer: &'a str,
+    /// Covered from format 3 on, `None` for records that never expire
+    pub expiry_date: Option<NaiveDate>,
+}
+
+pub trait MessageFormatter: Send + Sync {
+    fn version(&self) -> i32;
+
+    /// Human-readable message for the record
+    fn format(&self, fields: &SignableFields) -> String;
+
+    /// Bytes of the message that are hashed and signed
+    fn signable_bytes(&self, message: &str) -> Vec<u8>;
+
+    /// SHA-256 of the signable bytes
+    fn message_hash(&self, fields: &SignableFields) -> [u8; 32] {
+        Sha256::digest(self.signable_bytes(&self.format(fields))).into()
+    }
+}
+
+/// `VaxRecord:<patient>_<details>_<date>:<issuer>`, truncated or zero-padded to
+/// 32 bytes. This is what the Noir circuits and `generate_inputs` expect.
+pub struct MessageFormatV1;
+
+impl MessageFormatter for MessageFormatV1 {
+    fn version(&self) -> i32 {
+        1
+    }
+
+    fn format(&self, fields: &SignableFields) -> String {
+        format!(
+            "{}:{}_{}_{}:{}",
+            record_type_tag(fields.record_type),
+            fields.patient_identifier,
+            fields.details,
+            fields.issue_date,
+            fields.issuer
+        )
+    }
+
+    fn signable_bytes(&self, message: &str) -> Vec<u8> {
+        let mut message_bytes = [0u8; 32];
+        let msg_bytes = message.as_bytes();
+        let copy_len = std::cmp::min(msg_bytes.len(), 32);
+        message_bytes[..copy_len].copy_from_slice(&msg_bytes[..copy_len]);
+        message_bytes.to_vec()
+    }
+}
+
+/// `v2|<tag>|<patient>|<details>|<date>|<issuer>`, signed in full so no field
+/// is lost to truncation. Not supported by `RecordTypeProof`, whose circuit
+/// hashes a fixed 32-byte message.
+pub struct MessageFormatV2;
+
+impl MessageFormatter for MessageFormatV2 {
+    fn version(&self) -> i32 {
+        2
+    }
+
+    fn format(&self, fields: &SignableFields) -> String {
+        format!(
+            "v2|{}|{}|{}|{}|{}",
+            record_type_tag(fields.record_type),
+            fields.patient_identifier,
+            fields.details,
+            fields.issue_date,
+            fields.issuer
+        )
+    }
+
+    fn signable_bytes(&self, message: &str) -> Vec<u8> {
+        message.as_bytes().to_vec()
+    }
+}
+
+/// `v3|<tag>|<patient>|<details>|<date>|<issuer>|<expiry>`, with an empty
+/// expiry for records that never expire. The signed bytes are the message's
+/// SHA-256 followed by the expiry as big-endian days since the Unix epoch, so
+/// `NotExpiredProof` can range-check an expiry the authority signed without
+/// hashing the variable-length message in the circuit.
+pub struct MessageFormatV3;
+
+impl MessageFormatter for MessageFormatV3 {
+    fn version(&self) -> i32 {
+        3
+    }
+
+    fn format(&self, fields: &SignableFields) -> String {
+        format!(
+            "v3|{}|{}|{}|{}|{}|{}",
+            record_type_tag(fields.record_type),
+            fields.patient_identifier,
+            fields.details,
+            fields.issue_date,
+            fields.issuer,
+            fields.expiry_date.map(|date| date.to_string()).unwrap_or_default()
+        )
+    }
+
+    fn signable_bytes(&self, message: &str) -> Vec<u8> {
+        // The expiry is the last field; one that doesn't parse counts as long past
+        let expiry = message.rsplit('|').next().unwrap_or_default();
+        let expiry_days = match expiry {
+            "" => NEVER_EXPIRES,
+            expiry => NaiveDate::parse_from_str(expiry, "%Y-%m-%d").map_or(0, epoch_days),
+        };
+
+        [Sha256::digest(message.as_bytes()).as_slice(), &expiry_days.to_be_bytes()].concat()
+    }
+}
+
+/// Expiry signed by format 3 for records without an expiry date
+pub const NEVER_EXPIRES: u32 = u32::MAX;
+
+/// Days from the Unix epoch to `date`, as the circuits count dates; earlier
+/// dates count as day 0
+pub fn epoch_days(date: NaiveDate) -> u32 {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid epoch date");
+    let days = (date - epoch).num_days();
+    u32::try_from(days.max(0)).unwrap_or(NEVER_EXPIRES - 1)
+}
+
+/// Formatter for a stored `format_version`
+pub fn formatter(version: i32) -> Result<&'static dyn MessageFormatter, AppError> {
+    match version {
+        1 => Ok(&MessageFormatV1),
+        2 => Ok(&MessageFormatV2),
+        3 => Ok(&MessageFormatV3),
+        other => Err(AppError::InternalServerError(format!(
+            "Unsupported message format version {}",
+            other
+        ))),
+    }
+}
+
+/// Record-type tag that prefixes every signed message
+pub fn record_type_tag(record_type: &HealthRecordType) -> &'static str {
+    match record_type {
+        HealthRecordType::Vaccination => "VaxRecord",
+        HealthRecordType::TestResult => "TestResult",
+        HealthRecordType::MedicalClearance => "MedClearance",
+        HealthRecordType::ImmunityProof => "ImmunityProof",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> SignableFields<'static> {
+        SignableFields {
+            record_type: &HealthRecordType::Vaccination,
+            patient_identifier: "Patient123",
+            details: "COVID19_Dose1",
+            issue_date: "2025-01-01",
+            issuer: "HealthAuthority",
+            expiry_date: NaiveDate::from_ymd_opt(2026, 1, 1),
+        }
+    }
+
+    #[test]
+    fn test_v1_round_trip() {
+        let v1 = formatter(1).unwrap();
+        let message = v1.format(&fields());
+        assert_eq!(message, "VaxRecord:Patient123_COVID19_Dose1_2025-01-01:HealthAuthority");
+
+        let bytes = v1.signable_bytes(&message);
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(&bytes[..], &message.as_bytes()[..32]);
+        assert_eq!(v1.message_hash(&fields()), <[u8; 32]>::from(Sha256::digest(&bytes)));
+    }
+
+    #[test]
+    fn test_v2_round_trip() {
+        let v2 = formatter(2).unwrap();
+        let message = v2.format(&fields());
+        assert_eq!(message, "v2|VaxRecord|Patient123|COVID19_Dose1|2025-01-01|HealthAuthority");
+        assert_eq!(v2.signable_bytes(&message), message.as_bytes());
+
+        // Unlike v1, a change past the first 32 bytes changes the hash
+        let mut changed = fields();
+        changed.issuer = "OtherAuthority";
+        assert_ne!(v2.message_hash(&fields()), v2.message_hash(&changed));
+        assert_eq!(formatter(1).unwrap().message_hash(&fields()), formatter(1).unwrap().message_hash(&changed));
+    }
+
+    #[test]
+    fn test_v3_signs_the_expiry() {
+        let v3 = formatter(3).unwrap();
+        let message = v3.format(&fields());
+        assert_eq!(message, "v3|VaxRecord|Patient123|COVID19_Dose1|2025-01-01|HealthAuthority|2026-01-01");
+
+        // SHA-256 of the message, then the expiry as days since the epoch
+        let bytes = v3.signable_bytes(&message);
+        assert_eq!(&bytes[..32], Sha256::digest(message.as_bytes()).as_slice());
+        assert_eq!(&bytes[32..], 20454u32.to_be_bytes());
+
+        let mut extended = fields();
+        extended.expiry_date = NaiveDate::from_ymd_opt(2027, 1, 1);
+        assert_ne!(v3.message_hash(&fields()), v3.message_hash(&extended));
+
+        let never = SignableFields { expiry_date: None, ..fields() };
+        assert!(v3.format(&never).ends_with("|HealthAuthority|"));
+        assert_eq!(&v3.signable_bytes(&v3.format(&never))[32..], NEVER_EXPIRES.to_be_bytes());
+
+        // Older formats don't cover the expiry
+        assert_eq!(formatter(2).unwrap().message_hash(&fields()), formatter(2).unwrap().message_hash(&extended));
+    }
+
+    #[test]
+    fn test_unknown_version_is_rejected() {
+        assert!(formatter(0).is_err());
+        assert!(formatter(4).is_err());
+    }
+}
@@ -1,9 +1,42 @@
 pub mod auth;
 pub mod health_record;
+pub mod health_authority;
 pub mod zk_proof;
 pub mod crypto;
+pub mod predicate;
+pub mod message_format;
+pub mod webhook;
+pub mod store;
+pub mod verifier;
+pub mod field_encryption;
+pub mod ecdsa_p256;
+pub mod passkey;
+pub mod timestamp;
+pub mod blockchain;
+pub mod captcha;
+pub mod ipfs;
+pub mod geoip;
+pub mod email;
+pub mod wallet;
+pub mod pdf;
+pub mod authority_cache;
+pub mod object_store;
+pub mod proof_backend;
+pub mod attachment;
+pub mod access_log;
+pub mod dashboard;
 
+pub use access_log::AccessLogService;
+pub use attachment::AttachmentService;
+pub use dashboard::DashboardService;
 pub use auth::*;
+pub use blockchain::BlockchainService;
+pub use email::EmailService;
 pub use health_record::*;
+pub use health_authority::HealthAuthorityService;
 pub use zk_proof::*;
 pub use crypto::*;
+pub use ecdsa_p256::P256Service;
+pub use passkey::PasskeyService;
+pub use webhook::WebhookService;
+pub use verifier::VerifierService;
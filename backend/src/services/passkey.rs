@@ -0,0 +1,342 @@
+//! WebAuthn (passkey) registration and login.
+//!
+//! Ceremonies span two requests: `start` hands the browser a challenge and
+//! parks the server-side state in the shared store, `finish` takes it back out
+//! (once) and checks the authenticator's response against it.
+
+use std::{sync::Arc, time::Duration};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use uuid::Uuid;
+use webauthn_rs::prelude::{
+    CreationChallengeResponse, Passkey, PasskeyAuthentication, PasskeyRegistration, RequestChallengeResponse, Url,
+    Webauthn, WebauthnBuilder,
+};
+
+use crate::errors::AppError;
+use crate::models::{
+    FinishPasskeyLoginRequest, FinishPasskeyRegistrationRequest, LoginResponse, WebauthnCredential,
+    WebauthnCredentialResponse,
+};
+use crate::services::store::Store;
+use crate::services::AuthService;
+
+/// How long a started ceremony can be finished
+const CEREMONY_TTL: Duration = Duration::from_secs(300);
+
+pub struct PasskeyService {
+    auth_service: Arc<AuthService>,
+    store: Arc<dyn Store>,
+    webauthn: Webauthn,
+}
+
+/// Login state kept between `start_login` and `finish_login`
+#[derive(Serialize, Deserialize)]
+struct LoginCeremony {
+    user_id: Uuid,
+    state: PasskeyAuthentication,
+}
+
+impl PasskeyService {
+    /// `rp_id` is the domain passkeys are bound to and `rp_origin` the URL the
+    /// browser reports, e.g. `example.com` and `https://app.example.com`
+    pub fn new(
+        auth_service: Arc<AuthService>,
+        store: Arc<dyn Store>,
+        rp_id: &str,
+        rp_origin: &str,
+        rp_name: &str,
+    ) -> anyhow::Result<Self> {
+        let origin = Url::parse(rp_origin)?;
+        let webauthn = WebauthnBuilder::new(rp_id, &origin)?.rp_name(rp_name).build()?;
+
+        Ok(Self { auth_service, store, webauthn })
+    }
+
+    /// Begin registering a new passkey for `user_id`. Passkeys the user already
+    /// has are excluded so the same authenticator isn't registered twice.
+    pub async fn start_registration(&self, user_id: Uuid) -> Result<CreationChallengeResponse, AppError> {
+        let user = self
+            .auth_service
+            .get_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        let existing = self
+            .user_passkeys(user_id)
+            .await?
+            .iter()
+            .map(|(_, passkey)| passkey.cred_id().clone())
+            .collect::<Vec<_>>();
+
+        let (options, state) = self
+            .webauthn
+            .start_passkey_registration(user.id, &user.email, &user.full_name, Some(existing))
+            .map_err(|e| AppError::InternalServerError(format!("Failed to start passkey registration: {}", e)))?;
+
+        self.store
+            .set(&registration_key(user_id), &encode(&state)?, CEREMONY_TTL)
+            .await?;
+
+        Ok(options)
+    }
+
+    /// Check the authenticator's attestation and store the new passkey
+    pub async fn finish_registration(
+        &self,
+        user_id: Uuid,
+        request: FinishPasskeyRegistrationRequest,
+    ) -> Result<WebauthnCredentialResponse, AppError> {
+        let state: PasskeyRegistration = self
+            .take_ceremony(&registration_key(user_id))
+            .await?
+            .ok_or_else(|| AppError::BadRequest("No passkey registration in progress".to_string()))?;
+
+        let passkey = self
+            .webauthn
+            .finish_passkey_registration(&request.credential, &state)
+            .map_err(|e| AppError::BadRequest(format!("Passkey registration failed: {}", e)))?;
+
+        let credential = sqlx::query_as::<_, WebauthnCredential>(
+            r#"
+            INSERT INTO webauthn_credentials (user_id, credential_id, passkey, name)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(passkey.cred_id().to_vec())
+        .bind(to_json(&passkey)?)
+        .bind(&request.name)
+        .fetch_one(&self.auth_service.db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                AppError::Conflict("Passkey is already registered".to_string())
+            }
+            e => e.into(),
+        })?;
+
+        tracing::info!(
+            target: "audit",
+            event = "passkey_registered",
+            user_id = %user_id,
+            credential_id = %credential.id,
+            "Passkey registered"
+        );
+
+        Ok(credential.into())
+    }
+
+    /// Begin a passkey login for the account with `email`
+    pub async fn start_login(&self, email: &str) -> Result<(Uuid, RequestChallengeResponse), AppError> {
+        // Unknown accounts and accounts without passkeys look the same to the caller
+        let invalid = || AppError::Unauthorized("Invalid credentials".to_string());
+
+        let user_id = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE email = $1")
+            .bind(email)
+            .fetch_optional(&self.auth_service.db)
+            .await?
+            .ok_or_else(invalid)?;
+
+        let passkeys = self
+            .user_passkeys(user_id)
+            .await?
+            .into_iter()
+            .map(|(_, passkey)| passkey)
+            .collect::<Vec<_>>();
+        if passkeys.is_empty() {
+            return Err(invalid());
+        }
+
+        let (options, state) = self
+            .webauthn
+            .start_passkey_authentication(&passkeys)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to start passkey login: {}", e)))?;
+
+        let ceremony_id = Uuid::new_v4();
+        self.store
+            .set(&login_key(ceremony_id), &encode(&LoginCeremony { user_id, state })?, CEREMONY_TTL)
+            .await?;
+
+        Ok((ceremony_id, options))
+    }
+
+    /// Check the authenticator's assertion and, if it holds, log the user in
+    pub async fn finish_login(&self, request: FinishPasskeyLoginRequest) -> Result<LoginResponse, AppError> {
+        let ceremony: LoginCeremony = self
+            .take_ceremony(&login_key(request.ceremony_id))
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("Passkey login expired or already used".to_string()))?;
+
+        let result = self
+            .webauthn
+            .finish_passkey_authentication(&request.credential, &ceremony.state)
+            .map_err(|_| AppError::Unauthorized("Invalid credentials".to_string()))?;
+
+        let (credential_id, mut passkey) = self
+            .user_passkeys(ceremony.user_id)
+            .await?
+            .into_iter()
+            .find(|(_, passkey)| passkey.cred_id() == result.cred_id())
+            .ok_or_else(|| AppError::Unauthorized("Invalid credentials".to_string()))?;
+
+        // Keep the signature counter current so cloned authenticators are detected
+        passkey.update_credential(&result);
+        sqlx::query("UPDATE webauthn_credentials SET passkey = $1, last_used_at = NOW() WHERE id = $2")
+            .bind(to_json(&passkey)?)
+            .bind(credential_id)
+            .execute(&self.auth_service.db)
+            .await?;
+
+        let user = self
+            .auth_service
+            .get_user_by_id(ceremony.user_id)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("Invalid credentials".to_string()))?;
+
+        tracing::info!(
+            target: "audit",
+            event = "passkey_login",
+            user_id = %user.id,
+            credential_id = %credential_id,
+            "User logged in with a passkey"
+        );
+
+        self.auth_service.issue_login(user)
+    }
+
+    pub async fn list_credentials(&self, user_id: Uuid) -> Result<Vec<WebauthnCredentialResponse>, AppError> {
+        let credentials = sqlx::query_as::<_, WebauthnCredential>(
+            "SELECT * FROM webauthn_credentials WHERE user_id = $1 ORDER BY created_at"
+        )
+        .bind(user_id)
+        .fetch_all(&self.auth_service.db)
+        .await?;
+
+        Ok(credentials.into_iter().map(Into::into).collect())
+    }
+
+    /// Remove one of the user's passkeys; other users' passkeys are treated as missing
+    pub async fn delete_credential(&self, user_id: Uuid, credential_id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM webauthn_credentials WHERE id = $1 AND user_id = $2")
+            .bind(credential_id)
+            .bind(user_id)
+            .execute(&self.auth_service.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Passkey not found".to_string()));
+        }
+
+        tracing::info!(
+            target: "audit",
+            event = "passkey_deleted",
+            user_id = %user_id,
+            credential_id = %credential_id,
+            "Passkey deleted"
+        );
+
+        Ok(())
+    }
+
+    /// The user's stored passkeys, keyed by credential row ID
+    async fn user_passkeys(&self, user_id: Uuid) -> Result<Vec<(Uuid, Passkey)>, AppError> {
+        let credentials = sqlx::query_as::<_, WebauthnCredential>(
+            "SELECT * FROM webauthn_credentials WHERE user_id = $1"
+        )
+        .bind(user_id)
+        .fetch_all(&self.auth_service.db)
+        .await?;
+
+        credentials
+            .into_iter()
+            .map(|credential| {
+                let passkey = serde_json::from_value(credential.passkey)
+                    .map_err(|e| AppError::InternalServerError(format!("Corrupt passkey: {}", e)))?;
+                Ok((credential.id, passkey))
+            })
+            .collect()
+    }
+
+    /// Ceremony state under `key`, removed so it can only be finished once
+    async fn take_ceremony<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, AppError> {
+        let Some(raw) = self.store.get(key).await? else {
+            return Ok(None);
+        };
+        self.store.delete(key).await?;
+
+        serde_json::from_str(&raw)
+            .map(Some)
+            .map_err(|e| AppError::InternalServerError(format!("Corrupt passkey ceremony state: {}", e)))
+    }
+}
+
+fn registration_key(user_id: Uuid) -> String {
+    format!("webauthn:register:{}", user_id)
+}
+
+fn login_key(ceremony_id: Uuid) -> String {
+    format!("webauthn:login:{}", ceremony_id)
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<String, AppError> {
+    serde_json::to_string(value)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to encode passkey ceremony state: {}", e)))
+}
+
+fn to_json(passkey: &Passkey) -> Result<serde_json::Value, AppError> {
+    serde_json::to_value(passkey)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to encode passkey: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::store::MemoryStore;
+    use sqlx::PgPool;
+
+    fn service(db: PgPool) -> PasskeyService {
+        let auth_service = Arc::new(AuthService::new(db, "test-secret".to_string(), 1));
+        PasskeyService::new(auth_service, Arc::new(MemoryStore::new()), "localhost", "http://localhost:3000", "VeryFi")
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_passkeys_are_managed_per_user(db: PgPool) {
+        let service = service(db.clone());
+        let mut user_ids = Vec::new();
+        for email in ["owner@example.com", "other@example.com"] {
+            let user_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO users (email, password_hash, full_name) VALUES ($1, 'x', 'User') RETURNING id"
+            )
+            .bind(email)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+            user_ids.push(user_id);
+        }
+        let (owner_id, other_id) = (user_ids[0], user_ids[1]);
+
+        // Without passkeys a login can't start, and the account's existence isn't revealed
+        assert!(matches!(service.start_login("owner@example.com").await, Err(AppError::Unauthorized(_))));
+        assert!(matches!(service.start_login("nobody@example.com").await, Err(AppError::Unauthorized(_))));
+
+        let credential_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO webauthn_credentials (user_id, credential_id, passkey, name) VALUES ($1, '\\x01', '{}', 'Laptop') RETURNING id"
+        )
+        .bind(owner_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let listed = service.list_credentials(owner_id).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name.as_deref(), Some("Laptop"));
+        assert!(service.list_credentials(other_id).await.unwrap().is_empty());
+
+        assert!(matches!(service.delete_credential(other_id, credential_id).await, Err(AppError::NotFound(_))));
+        service.delete_credential(owner_id, credential_id).await.unwrap();
+        assert!(service.list_credentials(owner_id).await.unwrap().is_empty());
+    }
+}
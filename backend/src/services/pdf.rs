@@ -0,0 +1,103 @@
+//! Printable one-page PDF health passes.
+//!
+//! The page lists the facts a proof discloses next to a QR code linking to the
+//! proof's live status. The QR code is drawn as vector squares, so the page
+//! needs no embedded images or fonts beyond the PDF built-ins.
+
+use printpdf::{BuiltinFont, Color, IndirectFontRef, Mm, PdfDocument, PdfLayerReference, Rect, Rgb};
+use qrcode::QrCode;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+/// Side of the QR code, quiet zone excluded
+const QR_SIZE: f32 = 60.0;
+
+/// What a printed pass shows
+pub struct HealthPassPdf {
+    pub proof_id: Uuid,
+    /// Label and value of every disclosed fact, in print order
+    pub facts: Vec<(&'static str, String)>,
+    /// URL encoded in the QR code
+    pub qr_payload: String,
+}
+
+impl HealthPassPdf {
+    pub fn render(&self) -> Result<Vec<u8>, AppError> {
+        let pdf_error = |e: printpdf::Error| AppError::InternalServerError(format!("Failed to render PDF pass: {}", e));
+
+        let (doc, page, layer) = PdfDocument::new("Health Pass", Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Pass");
+        let layer = doc.get_page(page).get_layer(layer);
+        let regular = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(pdf_error)?;
+        let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(pdf_error)?;
+
+        let mut y = PAGE_HEIGHT - MARGIN - 10.0;
+        layer.use_text("Health Pass", 26.0, Mm(MARGIN), Mm(y), &bold);
+        y -= 16.0;
+
+        for (label, value) in &self.facts {
+            layer.use_text(label.to_uppercase(), 9.0, Mm(MARGIN), Mm(y), &regular);
+            layer.use_text(value.as_str(), 14.0, Mm(MARGIN), Mm(y - 6.0), &bold);
+            y -= 16.0;
+        }
+
+        let qr_bottom = y - QR_SIZE - 4.0;
+        draw_qr(&layer, &self.qr_payload, MARGIN, qr_bottom)?;
+        set_gray(&layer, 0.0);
+        layer.use_text("Scan to check that this pass is still valid", 10.0, Mm(MARGIN), Mm(qr_bottom - 8.0), &regular);
+        footer(&layer, &regular, self.proof_id);
+
+        doc.save_to_bytes().map_err(pdf_error)
+    }
+}
+
+/// QR code of `payload` with its lower-left corner at (`left`, `bottom`)
+fn draw_qr(layer: &PdfLayerReference, payload: &str, left: f32, bottom: f32) -> Result<(), AppError> {
+    let code = QrCode::new(payload.as_bytes())
+        .map_err(|e| AppError::InternalServerError(format!("Failed to encode pass QR code: {}", e)))?;
+    let width = code.width();
+    let module = QR_SIZE / width as f32;
+
+    set_gray(layer, 0.0);
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        if color != qrcode::Color::Dark {
+            continue;
+        }
+        let (x, y) = ((i % width) as f32, (i / width) as f32);
+        // QR rows run top to bottom; PDF coordinates run bottom to top
+        let x0 = left + x * module;
+        let y0 = bottom + QR_SIZE - (y + 1.0) * module;
+        layer.add_rect(Rect::new(Mm(x0), Mm(y0), Mm(x0 + module), Mm(y0 + module)));
+    }
+    Ok(())
+}
+
+fn footer(layer: &PdfLayerReference, font: &IndirectFontRef, proof_id: Uuid) {
+    set_gray(layer, 0.4);
+    layer.use_text(format!("Proof ID {}", proof_id), 8.0, Mm(MARGIN), Mm(MARGIN), font);
+}
+
+fn set_gray(layer: &PdfLayerReference, level: f32) {
+    layer.set_fill_color(Color::Rgb(Rgb::new(level, level, level, None)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_a_complete_pdf() {
+        let pass = HealthPassPdf {
+            proof_id: Uuid::new_v4(),
+            facts: vec![("Record", "Vaccination".to_string()), ("Issued by", "City Clinic".to_string())],
+            qr_payload: "https://pass.example.com/api/v1/proofs/1/status".to_string(),
+        };
+
+        let pdf = pass.render().unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(String::from_utf8_lossy(&pdf).trim_end().ends_with("%%EOF"));
+    }
+}
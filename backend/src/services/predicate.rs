@@ -0,0 +1,181 @@
+//! Verifier-supplied predicates evaluated against the facts a proof discloses.
+//!
+//! A predicate is a JSON expression built from these forms:
+//!
+//! ```json
+//! {"eq": {"field": "record_type", "value": "Vaccination"}}
+//! {"and": [<predicate>, ...]}
+//! {"or": [<predicate>, ...]}
+//! {"not": <predicate>}
+//! ```
+//!
+//! `field` is one of `record_type`, `authority_name`, `authority_type`,
+//! `issue_date`, `revocation_status`, `is_expired` or `usage_exceeded`.
+//! Equality against a fact the proof did not disclose is always false.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::errors::AppError;
+use crate::models::VerificationDetails;
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Predicate {
+    And(Vec<Predicate>),
+    Or(Vec<Predicate>),
+    Not(Box<Predicate>),
+    Eq { field: PredicateField, value: Value },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PredicateField {
+    RecordType,
+    AuthorityName,
+    AuthorityType,
+    IssueDate,
+    RevocationStatus,
+    IsExpired,
+    UsageExceeded,
+}
+
+impl Predicate {
+    /// Parse a predicate from the raw JSON supplied in a verification request
+    pub fn parse(value: &Value) -> Result<Self, AppError> {
+        serde_json::from_value(value.clone())
+            .map_err(|e| AppError::BadRequest(format!("Invalid predicate: {}", e)))
+    }
+
+    /// Evaluate the predicate against the disclosed verification facts
+    pub fn evaluate(&self, details: &VerificationDetails) -> bool {
+        match self {
+            Predicate::And(predicates) => predicates.iter().all(|p| p.evaluate(details)),
+            Predicate::Or(predicates) => predicates.iter().any(|p| p.evaluate(details)),
+            Predicate::Not(predicate) => !predicate.evaluate(details),
+            Predicate::Eq { field, value } => {
+                field.disclosed_value(details).map_or(false, |disclosed| &disclosed == value)
+            }
+        }
+    }
+}
+
+impl PredicateField {
+    fn disclosed_value(&self, details: &VerificationDetails) -> Option<Value> {
+        match self {
+            PredicateField::RecordType => details.health_record_type.clone().map(Value::String),
+            PredicateField::AuthorityName => details.authority_name.clone().map(Value::String),
+            PredicateField::AuthorityType => details.authority_type.clone().map(Value::String),
+            PredicateField::IssueDate => details.issue_date.clone().map(Value::String),
+            PredicateField::RevocationStatus => serde_json::to_value(&details.revocation_status).ok(),
+            PredicateField::IsExpired => Some(Value::Bool(details.is_expired)),
+            PredicateField::UsageExceeded => Some(Value::Bool(details.usage_exceeded)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::RevocationStatus;
+    use serde_json::json;
+
+    fn details() -> VerificationDetails {
+        VerificationDetails {
+            health_record_type: Some("Vaccination".to_string()),
+            issue_date: Some("2025-01-01".to_string()),
+            authority_name: Some("Ministry of Health".to_string()),
+            authority_type: Some("Government".to_string()),
+            is_expired: false,
+            usage_exceeded: false,
+            revocation_status: RevocationStatus::Valid,
+            revoked_at: None,
+            revocation_reason: None,
+            constituent_record_types: Vec::new(),
+            co_signing_authorities: Vec::new(),
+            timestamped_at: None,
+            anchored_on_chain: None,
+        }
+    }
+
+    #[test]
+    fn test_equality() {
+        let predicate = Predicate::parse(&json!({
+            "eq": {"field": "record_type", "value": "Vaccination"}
+        }))
+        .unwrap();
+        assert!(predicate.evaluate(&details()));
+
+        let predicate = Predicate::parse(&json!({
+            "eq": {"field": "record_type", "value": "TestResult"}
+        }))
+        .unwrap();
+        assert!(!predicate.evaluate(&details()));
+
+        let predicate = Predicate::parse(&json!({
+            "eq": {"field": "revocation_status", "value": "Valid"}
+        }))
+        .unwrap();
+        assert!(predicate.evaluate(&details()));
+    }
+
+    #[test]
+    fn test_and() {
+        let predicate = Predicate::parse(&json!({
+            "and": [
+                {"eq": {"field": "record_type", "value": "Vaccination"}},
+                {"eq": {"field": "authority_type", "value": "Government"}}
+            ]
+        }))
+        .unwrap();
+        assert!(predicate.evaluate(&details()));
+
+        let predicate = Predicate::parse(&json!({
+            "and": [
+                {"eq": {"field": "record_type", "value": "Vaccination"}},
+                {"eq": {"field": "authority_type", "value": "Hospital"}}
+            ]
+        }))
+        .unwrap();
+        assert!(!predicate.evaluate(&details()));
+    }
+
+    #[test]
+    fn test_or_and_not() {
+        let predicate = Predicate::parse(&json!({
+            "or": [
+                {"eq": {"field": "record_type", "value": "TestResult"}},
+                {"not": {"eq": {"field": "is_expired", "value": true}}}
+            ]
+        }))
+        .unwrap();
+        assert!(predicate.evaluate(&details()));
+
+        let predicate = Predicate::parse(&json!({
+            "or": [
+                {"eq": {"field": "record_type", "value": "TestResult"}},
+                {"eq": {"field": "usage_exceeded", "value": true}}
+            ]
+        }))
+        .unwrap();
+        assert!(!predicate.evaluate(&details()));
+    }
+
+    #[test]
+    fn test_undisclosed_field_never_matches() {
+        let mut details = details();
+        details.issue_date = None;
+
+        let predicate = Predicate::parse(&json!({
+            "eq": {"field": "issue_date", "value": "2025-01-01"}
+        }))
+        .unwrap();
+        assert!(!predicate.evaluate(&details));
+    }
+
+    #[test]
+    fn test_invalid_grammar_is_rejected() {
+        assert!(Predicate::parse(&json!({"gt": {"field": "record_type", "value": 1}})).is_err());
+        assert!(Predicate::parse(&json!({"eq": {"field": "patient_identifier", "value": "x"}})).is_err());
+    }
+}
@@ -0,0 +1,420 @@
+//! Where proofs come from.
+//!
+//! `ZkProofService` builds each circuit's `Prover.toml` and hands it to a
+//! [`ProofBackend`]. [`NargoBackend`] runs the Noir toolchain. Builds with the
+//! `stub-proofs` feature can select [`StubProofBackend`] instead
+//! (`PROOF_BACKEND=stub`), which needs no toolchain. Its proofs prove nothing,
+//! so it is meant for CI and local development only.
+//!
+//! Every proof commits to its circuit's public inputs. Verification is given
+//! the inputs the service expects from the record and its authority, so a
+//! valid proof about some other record or key doesn't pass.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use axum::async_trait;
+#[cfg(any(test, feature = "stub-proofs"))]
+use sha2::{Digest, Sha256};
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::ProofType;
+
+/// Noir package that proves a given `ProofType`
+pub struct NoirCircuit {
+    /// Directory relative to `NOIR_CIRCUIT_PATH`
+    pub dir: &'static str,
+    /// Expected package name. The witness is read under the name in the
+    /// circuit's `Nargo.toml`, so renaming a package doesn't break proving.
+    pub package: &'static str,
+}
+
+impl NoirCircuit {
+    pub fn for_proof_type(proof_type: &ProofType) -> Self {
+        match proof_type {
+            ProofType::EcdsaSignatureVerification => Self { dir: ".", package: "health_passport_circuit" },
+            ProofType::NotExpiredProof => Self { dir: "circuits/not_expired", package: "not_expired_circuit" },
+            ProofType::RecordTypeProof => Self { dir: "circuits/record_type", package: "record_type_circuit" },
+            ProofType::AuthorityTrustedProof => Self { dir: "circuits/authority_trusted", package: "authority_trusted_circuit" },
+            ProofType::AggregateSignatureVerification => Self { dir: "circuits/aggregate", package: "aggregate_circuit" },
+        }
+    }
+}
+
+/// A circuit's public inputs in declaration order, as `bb` writes them ahead
+/// of the proof: one 32-byte big-endian field element per byte of a `[u8; N]`
+/// input and one per integer input
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublicInputs(Vec<[u8; 32]>);
+
+impl PublicInputs {
+    /// Append a `[u8; N]` input
+    pub fn bytes(mut self, bytes: &[u8]) -> Self {
+        for byte in bytes {
+            self = self.number(u64::from(*byte));
+        }
+        self
+    }
+
+    /// Append an integer input
+    pub fn number(mut self, value: u64) -> Self {
+        let mut field = [0u8; 32];
+        field[24..].copy_from_slice(&value.to_be_bytes());
+        self.0.push(field);
+        self
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.concat()
+    }
+}
+
+#[async_trait]
+pub trait ProofBackend: Send + Sync {
+    /// Run `circuit` on the inputs in `prover_toml` and return the proof.
+    /// `public_inputs` are the public values among them.
+    async fn prove(&self, circuit: &NoirCircuit, prover_toml: String, public_inputs: &PublicInputs) -> Result<Vec<u8>, AppError>;
+
+    /// Whether `proof_data` is a proof generated with `circuit` over `public_inputs`
+    async fn verify(&self, circuit: &NoirCircuit, proof_data: &[u8], public_inputs: &PublicInputs) -> Result<bool, AppError>;
+
+    /// Whether `nargo` and `bb` must be installed, for the readiness probe
+    fn uses_toolchain(&self) -> bool {
+        true
+    }
+}
+
+/// Proves with `nargo` and `bb` using the circuits under `NOIR_CIRCUIT_PATH`
+pub struct NargoBackend {
+    noir_circuit_path: String,
+    /// Verification keys by circuit package, built on first use. Verifying
+    /// must not compile a circuit per request.
+    verification_keys: Mutex<HashMap<&'static str, Arc<Vec<u8>>>>,
+}
+
+impl NargoBackend {
+    pub fn new(noir_circuit_path: String) -> Self {
+        Self { noir_circuit_path, verification_keys: Mutex::new(HashMap::new()) }
+    }
+
+    /// The circuit's verification key, compiling the circuit and writing the
+    /// key with `bb write_vk` the first time. The lock is held while building,
+    /// so concurrent verifications of a new circuit build its key once.
+    async fn verification_key(&self, circuit: &NoirCircuit) -> Result<Arc<Vec<u8>>, AppError> {
+        let mut keys = self.verification_keys.lock().await;
+        if let Some(vk) = keys.get(circuit.package) {
+            return Ok(vk.clone());
+        }
+
+        let scratch = ScratchCircuit::new(&self.noir_circuit_path, circuit)?;
+        run(&scratch.dir.path, "nargo", &["compile"]).await?;
+        let bytecode = scratch.target(&format!("{}.json", scratch.package));
+        let vk = scratch.target("vk");
+        run(&scratch.dir.path, "bb", &["write_vk", "-b", path_arg(&bytecode), "-o", path_arg(&vk)]).await?;
+
+        let vk = Arc::new(
+            fs::read(&vk).map_err(|_| AppError::InternalServerError("bb write_vk did not produce a key".to_string()))?,
+        );
+        keys.insert(circuit.package, vk.clone());
+        Ok(vk)
+    }
+}
+
+#[async_trait]
+impl ProofBackend for NargoBackend {
+    /// Solve the witness with `nargo execute` and prove it with `bb prove`
+    async fn prove(&self, circuit: &NoirCircuit, prover_toml: String, public_inputs: &PublicInputs) -> Result<Vec<u8>, AppError> {
+        let scratch = ScratchCircuit::new(&self.noir_circuit_path, circuit)?;
+        fs::write(scratch.dir.path.join("Prover.toml"), prover_toml)
+            .map_err(|_| AppError::InternalServerError("Failed to write Prover.toml".to_string()))?;
+
+        run(&scratch.dir.path, "nargo", &["execute"]).await?;
+
+        let witness = scratch.target(&format!("{}.gz", scratch.package));
+        if !witness.exists() {
+            return Err(AppError::InternalServerError(format!(
+                "Noir execution did not produce target/{}.gz; found: {}",
+                scratch.package,
+                list_dir(&scratch.dir.path.join("target"))
+            )));
+        }
+
+        let bytecode = scratch.target(&format!("{}.json", scratch.package));
+        let proof = scratch.target("proof");
+        run(&scratch.dir.path, "bb", &["prove", "-b", path_arg(&bytecode), "-w", path_arg(&witness), "-o", path_arg(&proof)]).await?;
+
+        let proof = fs::read(&proof).map_err(|_| AppError::InternalServerError("bb prove did not produce a proof".to_string()))?;
+        if !proof.starts_with(&public_inputs.to_bytes()) {
+            return Err(AppError::InternalServerError(format!(
+                "Proof of circuit {} does not commit to the expected public inputs",
+                circuit.package
+            )));
+        }
+        Ok(proof)
+    }
+
+    /// Check that the proof commits to `public_inputs`, then check it with
+    /// `bb verify` against the circuit's cached verification key. A proof that
+    /// fails either check is `Ok(false)`; a toolchain failure is an error.
+    async fn verify(&self, circuit: &NoirCircuit, proof_data: &[u8], public_inputs: &PublicInputs) -> Result<bool, AppError> {
+        if !proof_data.starts_with(&public_inputs.to_bytes()) {
+            return Ok(false);
+        }
+        let vk_data = self.verification_key(circuit).await?;
+
+        let scratch = ScratchDir::new()?;
+        let vk = scratch.path.join("vk");
+        let proof = scratch.path.join("proof");
+        fs::write(&vk, vk_data.as_slice())
+            .map_err(|_| AppError::InternalServerError("Failed to write verification key".to_string()))?;
+        fs::write(&proof, proof_data)
+            .map_err(|_| AppError::InternalServerError("Failed to write proof".to_string()))?;
+
+        let output = Command::new("bb")
+            .args(["verify", "-k", path_arg(&vk), "-p", path_arg(&proof)])
+            .current_dir(&scratch.path)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|_| AppError::InternalServerError("Failed to run bb".to_string()))?;
+
+        Ok(output.status.success())
+    }
+}
+
+/// Temporary directory, removed on drop so a timed out proof doesn't leave it behind
+struct ScratchDir {
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    fn new() -> Result<Self, AppError> {
+        let scratch = Self { path: std::env::temp_dir().join(format!("zk_proof_{}", Uuid::new_v4())) };
+        fs::create_dir_all(&scratch.path)
+            .map_err(|_| AppError::InternalServerError("Failed to create scratch directory".to_string()))?;
+        Ok(scratch)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Copy of a circuit in a scratch directory
+struct ScratchCircuit {
+    dir: ScratchDir,
+    /// Package name from the circuit's `Nargo.toml`, which names its build outputs
+    package: String,
+}
+
+impl ScratchCircuit {
+    fn new(noir_circuit_path: &str, circuit: &NoirCircuit) -> Result<Self, AppError> {
+        let circuit_src = Path::new(noir_circuit_path).join(circuit.dir);
+        let manifest = fs::read_to_string(circuit_src.join("Nargo.toml"))
+            .map_err(|_| AppError::InternalServerError(format!("Missing Noir circuit {}", circuit.package)))?;
+        let package = nargo_package_name(&manifest).ok_or_else(|| {
+            AppError::InternalServerError(format!("Nargo.toml of circuit {} has no [package] name", circuit.package))
+        })?;
+
+        let scratch = Self { dir: ScratchDir::new()?, package };
+        fs::create_dir_all(scratch.dir.path.join("src"))
+            .map_err(|_| AppError::InternalServerError("Failed to create circuit directory".to_string()))?;
+        fs::copy(circuit_src.join("src/main.nr"), scratch.dir.path.join("src/main.nr"))
+            .map_err(|_| AppError::InternalServerError("Failed to copy circuit".to_string()))?;
+        fs::write(scratch.dir.path.join("Nargo.toml"), manifest)
+            .map_err(|_| AppError::InternalServerError("Failed to copy Nargo.toml".to_string()))?;
+
+        Ok(scratch)
+    }
+
+    fn target(&self, file: &str) -> PathBuf {
+        self.dir.path.join("target").join(file)
+    }
+}
+
+/// Run a toolchain binary in `dir`, failing with its stderr on a non-zero
+/// exit. The child is killed if the future is dropped, e.g. by a timeout.
+async fn run(dir: &Path, program: &str, args: &[&str]) -> Result<(), AppError> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|_| AppError::InternalServerError(format!("Failed to run {}", program)))?;
+
+    if !output.status.success() {
+        return Err(AppError::InternalServerError(format!(
+            "{} {} failed: {}",
+            program,
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    Ok(())
+}
+
+/// Scratch paths are built from UTF-8 parts only
+fn path_arg(path: &Path) -> &str {
+    path.to_str().unwrap_or_default()
+}
+
+/// The `name` of the `[package]` table in a `Nargo.toml`
+fn nargo_package_name(manifest: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| in_package) {
+            if key.trim() == "name" {
+                let name = value.trim().strip_prefix('"')?.split('"').next()?;
+                return Some(name.to_string()).filter(|name| !name.is_empty());
+            }
+        }
+    }
+    None
+}
+
+/// Sorted file names in `dir`, for error messages
+fn list_dir(dir: &Path) -> String {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return format!("{} does not exist", dir.display());
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    if names.is_empty() {
+        return "nothing".to_string();
+    }
+    names.sort();
+    names.join(", ")
+}
+
+/// Marks stub proofs, so they are never mistaken for witnesses
+#[cfg(any(test, feature = "stub-proofs"))]
+const STUB_PROOF_PREFIX: &[u8] = b"stub-proof:v1:";
+
+/// Deterministic stand-in for the toolchain. A proof is the circuit's package
+/// name, its public inputs and the SHA-256 of all its inputs, so the same
+/// inputs always give the same bytes, and a proof only verifies against the
+/// circuit and public inputs that made it.
+#[cfg(any(test, feature = "stub-proofs"))]
+pub struct StubProofBackend;
+
+#[cfg(any(test, feature = "stub-proofs"))]
+impl StubProofBackend {
+    fn proof(circuit: &NoirCircuit, public_inputs: &PublicInputs, inputs_hash: &[u8]) -> Vec<u8> {
+        [STUB_PROOF_PREFIX, circuit.package.as_bytes(), b":", &public_inputs.to_bytes(), inputs_hash].concat()
+    }
+}
+
+#[cfg(any(test, feature = "stub-proofs"))]
+#[async_trait]
+impl ProofBackend for StubProofBackend {
+    async fn prove(&self, circuit: &NoirCircuit, prover_toml: String, public_inputs: &PublicInputs) -> Result<Vec<u8>, AppError> {
+        Ok(Self::proof(circuit, public_inputs, &Sha256::digest(prover_toml.as_bytes())))
+    }
+
+    async fn verify(&self, circuit: &NoirCircuit, proof_data: &[u8], public_inputs: &PublicInputs) -> Result<bool, AppError> {
+        let inputs_hash = proof_data.len().checked_sub(32).map(|at| &proof_data[at..]);
+        Ok(inputs_hash.map_or(false, |inputs_hash| Self::proof(circuit, public_inputs, inputs_hash) == proof_data))
+    }
+
+    fn uses_toolchain(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stub_proofs_are_deterministic_and_bound_to_their_circuit() {
+        let backend = StubProofBackend;
+        let ecdsa = NoirCircuit::for_proof_type(&ProofType::EcdsaSignatureVerification);
+        let not_expired = NoirCircuit::for_proof_type(&ProofType::NotExpiredProof);
+
+        let inputs = PublicInputs::default().bytes(&[1]);
+        let proof = backend.prove(&ecdsa, "msg_hash = [\"0x01\"]\n".to_string(), &inputs).await.unwrap();
+        assert_eq!(proof, backend.prove(&ecdsa, "msg_hash = [\"0x01\"]\n".to_string(), &inputs).await.unwrap());
+        assert_ne!(proof, backend.prove(&ecdsa, "msg_hash = [\"0x02\"]\n".to_string(), &inputs).await.unwrap());
+
+        assert!(backend.verify(&ecdsa, &proof, &inputs).await.unwrap());
+        assert!(!backend.verify(&not_expired, &proof, &inputs).await.unwrap());
+        assert!(!backend.verify(&ecdsa, &proof[1..], &inputs).await.unwrap());
+        assert!(!backend.verify(&ecdsa, &[1, 2, 3], &inputs).await.unwrap());
+        assert!(!backend.uses_toolchain());
+    }
+
+    #[tokio::test]
+    async fn test_proofs_only_verify_over_their_public_inputs() {
+        let backend = StubProofBackend;
+        let circuit = NoirCircuit::for_proof_type(&ProofType::AuthorityTrustedProof);
+        let inputs = PublicInputs::default().bytes(&[0xab; 32]).number(20_000);
+        let proof = backend.prove(&circuit, String::new(), &inputs).await.unwrap();
+
+        assert!(backend.verify(&circuit, &proof, &inputs).await.unwrap());
+        assert!(!backend.verify(&circuit, &proof, &PublicInputs::default().bytes(&[0xab; 32]).number(20_001)).await.unwrap());
+        assert!(!backend.verify(&circuit, &proof, &PublicInputs::default()).await.unwrap());
+
+        // A proof that doesn't start with the expected inputs is rejected before the toolchain runs
+        let nargo = NargoBackend::new("/nonexistent/noir".to_string());
+        assert!(!nargo.verify(&circuit, b"proof", &inputs).await.unwrap());
+    }
+
+    #[test]
+    fn test_public_inputs_are_big_endian_fields() {
+        let bytes = PublicInputs::default().bytes(&[0x01, 0xff]).number(0x0102_0304).to_bytes();
+        assert_eq!(bytes.len(), 96);
+        assert_eq!(bytes[31], 0x01);
+        assert_eq!(bytes[63], 0xff);
+        assert_eq!(&bytes[92..], &[1, 2, 3, 4]);
+        assert!(bytes[..31].iter().chain(&bytes[32..63]).chain(&bytes[64..92]).all(|byte| *byte == 0));
+    }
+
+    #[tokio::test]
+    async fn test_nargo_verification_fails_closed() {
+        let backend = NargoBackend::new("/nonexistent/noir".to_string());
+        let circuit = NoirCircuit::for_proof_type(&ProofType::AuthorityTrustedProof);
+        assert!(backend.verify(&circuit, b"proof", &PublicInputs::default()).await.is_err());
+    }
+
+    #[test]
+    fn test_scratch_circuit_is_removed_on_drop() {
+        let circuits = concat!(env!("CARGO_MANIFEST_DIR"), "/../noir");
+        let scratch = ScratchCircuit::new(circuits, &NoirCircuit::for_proof_type(&ProofType::RecordTypeProof)).unwrap();
+        assert_eq!(scratch.package, "record_type_circuit");
+        assert!(scratch.dir.path.join("src/main.nr").exists());
+
+        let dir = scratch.dir.path.clone();
+        drop(scratch);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_witness_name_comes_from_the_nargo_package() {
+        let manifest = "# comment\n[package]\nname = \"renamed_circuit\"\ntype = \"bin\"\n\n[dependencies]\nname = \"ignored\"\n";
+        assert_eq!(nargo_package_name(manifest).as_deref(), Some("renamed_circuit"));
+        assert_eq!(nargo_package_name("[dependencies]\nname = \"x\"\n"), None);
+        assert_eq!(nargo_package_name("[package]\nname = \"\"\n"), None);
+        assert_eq!(nargo_package_name("[package]\nname = \"c\" # note\n").as_deref(), Some("c"));
+
+        let circuits = Path::new(env!("CARGO_MANIFEST_DIR")).join("../noir");
+        for proof_type in [ProofType::EcdsaSignatureVerification, ProofType::NotExpiredProof, ProofType::AggregateSignatureVerification] {
+            let circuit = NoirCircuit::for_proof_type(&proof_type);
+            let manifest = fs::read_to_string(circuits.join(circuit.dir).join("Nargo.toml")).unwrap();
+            assert_eq!(nargo_package_name(&manifest).as_deref(), Some(circuit.package));
+        }
+
+        assert_eq!(list_dir(Path::new("/nonexistent/target")), "/nonexistent/target does not exist");
+    }
+}
@@ -0,0 +1,130 @@
+use axum::async_trait;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use super::Store;
+use crate::errors::AppError;
+
+/// Number of entries above which expired keys are swept on write
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// Process-local store; state is lost on restart and not shared between instances
+#[derive(Default)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+struct Entry {
+    value: String,
+    expires_at: Instant,
+}
+
+impl Entry {
+    fn is_live(&self, now: Instant) -> bool {
+        self.expires_at > now
+    }
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_entries<T>(&self, f: impl FnOnce(&mut HashMap<String, Entry>, Instant) -> T) -> T {
+        let mut entries = self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        if entries.len() > SWEEP_THRESHOLD {
+            entries.retain(|_, entry| entry.is_live(now));
+        }
+        f(&mut entries, now)
+    }
+}
+
+#[async_trait]
+impl Store for MemoryStore {
+    async fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        Ok(self.with_entries(|entries, now| {
+            entries
+                .get(key)
+                .filter(|entry| entry.is_live(now))
+                .map(|entry| entry.value.clone())
+        }))
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> Result<(), AppError> {
+        self.with_entries(|entries, now| {
+            entries.insert(key.to_string(), Entry { value: value.to_string(), expires_at: now + ttl });
+        });
+        Ok(())
+    }
+
+    async fn set_if_absent(&self, key: &str, value: &str, ttl: Duration) -> Result<bool, AppError> {
+        Ok(self.with_entries(|entries, now| {
+            if entries.get(key).is_some_and(|entry| entry.is_live(now)) {
+                return false;
+            }
+            entries.insert(key.to_string(), Entry { value: value.to_string(), expires_at: now + ttl });
+            true
+        }))
+    }
+
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u64, AppError> {
+        self.with_entries(|entries, now| {
+            let (count, expires_at) = match entries.get(key).filter(|entry| entry.is_live(now)) {
+                Some(entry) => {
+                    let count = entry.value.parse::<u64>().map_err(|_| {
+                        AppError::InternalServerError(format!("Store key {} is not a counter", key))
+                    })?;
+                    (count + 1, entry.expires_at)
+                }
+                None => (1, now + ttl),
+            };
+            entries.insert(key.to_string(), Entry { value: count.to_string(), expires_at });
+
+            Ok(count)
+        })
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        self.with_entries(|entries, _| {
+            entries.remove(key);
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_counters_and_single_use_keys() {
+        let store = MemoryStore::new();
+        let ttl = Duration::from_secs(60);
+
+        assert_eq!(store.increment("hits", ttl).await.unwrap(), 1);
+        assert_eq!(store.increment("hits", ttl).await.unwrap(), 2);
+
+        assert!(store.set_if_absent("nonce", "1", ttl).await.unwrap());
+        assert!(!store.set_if_absent("nonce", "2", ttl).await.unwrap());
+        assert_eq!(store.get("nonce").await.unwrap().as_deref(), Some("1"));
+
+        store.delete("nonce").await.unwrap();
+        assert_eq!(store.get("nonce").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_entries_expire() {
+        let store = MemoryStore::new();
+
+        store.set("session", "abc", Duration::from_millis(10)).await.unwrap();
+        store.increment("hits", Duration::from_millis(10)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert_eq!(store.get("session").await.unwrap(), None);
+        assert_eq!(store.increment("hits", Duration::from_millis(10)).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,54 @@
+//! Shared key/value state (rate-limit counters, single-use keys) that must be
+//! consistent across API instances.
+//!
+//! The in-memory store is the default and only sees its own process. Build with
+//! `--features redis` and set `REDIS_URL` to share state between instances and
+//! keep it across restarts.
+
+mod memory;
+#[cfg(feature = "redis")]
+mod redis;
+
+use axum::async_trait;
+use std::{sync::Arc, time::Duration};
+
+use crate::errors::AppError;
+
+pub use memory::MemoryStore;
+#[cfg(feature = "redis")]
+pub use self::redis::RedisStore;
+
+#[async_trait]
+pub trait Store: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<String>, AppError>;
+
+    /// Store `value` under `key`, replacing any previous value, for `ttl`
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> Result<(), AppError>;
+
+    /// Store `value` only if `key` is absent; returns whether it was stored
+    async fn set_if_absent(&self, key: &str, value: &str, ttl: Duration) -> Result<bool, AppError>;
+
+    /// Atomically increment the counter at `key`, starting its `ttl` on first use
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u64, AppError>;
+
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+}
+
+/// Build the configured store: Redis when `REDIS_URL` is set, in-memory otherwise
+pub async fn connect(redis_url: Option<&str>) -> anyhow::Result<Arc<dyn Store>> {
+    match redis_url {
+        #[cfg(feature = "redis")]
+        Some(url) => {
+            tracing::info!("Using Redis store");
+            Ok(Arc::new(RedisStore::connect(url).await?))
+        }
+        #[cfg(not(feature = "redis"))]
+        Some(_) => Err(anyhow::anyhow!(
+            "REDIS_URL is set but the server was built without the `redis` feature"
+        )),
+        None => {
+            tracing::info!("Using in-memory store; state is not shared between instances");
+            Ok(Arc::new(MemoryStore::new()))
+        }
+    }
+}
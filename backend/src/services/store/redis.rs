@@ -0,0 +1,138 @@
+use axum::async_trait;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use std::time::Duration;
+
+use super::Store;
+use crate::errors::AppError;
+
+/// Store shared by every API instance pointed at the same Redis server
+#[derive(Clone)]
+pub struct RedisStore {
+    connection: ConnectionManager,
+}
+
+impl RedisStore {
+    pub async fn connect(url: &str) -> Result<Self, redis::RedisError> {
+        let client = redis::Client::open(url)?;
+        let connection = ConnectionManager::new(client).await?;
+        Ok(Self { connection })
+    }
+}
+
+fn store_error(e: redis::RedisError) -> AppError {
+    AppError::ServiceUnavailable(format!("Redis store error: {}", e))
+}
+
+fn ttl_millis(ttl: Duration) -> u64 {
+    ttl.as_millis().max(1) as u64
+}
+
+#[async_trait]
+impl Store for RedisStore {
+    async fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        self.connection.clone().get(key).await.map_err(store_error)
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> Result<(), AppError> {
+        redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("PX")
+            .arg(ttl_millis(ttl))
+            .query_async(&mut self.connection.clone())
+            .await
+            .map_err(store_error)
+    }
+
+    async fn set_if_absent(&self, key: &str, value: &str, ttl: Duration) -> Result<bool, AppError> {
+        let stored: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl_millis(ttl))
+            .query_async(&mut self.connection.clone())
+            .await
+            .map_err(store_error)?;
+
+        Ok(stored.is_some())
+    }
+
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u64, AppError> {
+        // PEXPIRE NX (Redis 7+) only starts the window on the first increment
+        let (count,): (u64,) = redis::pipe()
+            .atomic()
+            .incr(key, 1)
+            .cmd("PEXPIRE")
+            .arg(key)
+            .arg(ttl_millis(ttl))
+            .arg("NX")
+            .ignore()
+            .query_async(&mut self.connection.clone())
+            .await
+            .map_err(store_error)?;
+
+        Ok(count)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        self.connection.clone().del(key).await.map_err(store_error)
+    }
+}
+
+/// Run against a live server, e.g. `docker compose up -d redis`, with
+/// `cargo test --features redis`. `REDIS_URL` defaults to the local container.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    async fn store() -> RedisStore {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        RedisStore::connect(&url).await.expect("Redis must be running for --features redis tests")
+    }
+
+    fn key(name: &str) -> String {
+        format!("test:{}:{}", Uuid::new_v4(), name)
+    }
+
+    #[tokio::test]
+    async fn test_counters_and_single_use_keys() {
+        let store = store().await;
+        let ttl = Duration::from_secs(60);
+        let (hits, nonce) = (key("hits"), key("nonce"));
+
+        assert_eq!(store.increment(&hits, ttl).await.unwrap(), 1);
+        assert_eq!(store.increment(&hits, ttl).await.unwrap(), 2);
+
+        assert!(store.set_if_absent(&nonce, "1", ttl).await.unwrap());
+        assert!(!store.set_if_absent(&nonce, "2", ttl).await.unwrap());
+        assert_eq!(store.get(&nonce).await.unwrap().as_deref(), Some("1"));
+
+        store.delete(&nonce).await.unwrap();
+        store.delete(&hits).await.unwrap();
+        assert_eq!(store.get(&nonce).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_state_is_shared_between_connections() {
+        let (first, second) = (store().await, store().await);
+        let hits = key("hits");
+
+        first.increment(&hits, Duration::from_secs(60)).await.unwrap();
+        assert_eq!(second.increment(&hits, Duration::from_secs(60)).await.unwrap(), 2);
+
+        first.delete(&hits).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_entries_expire() {
+        let store = store().await;
+        let session = key("session");
+
+        store.set(&session, "abc", Duration::from_millis(50)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(store.get(&session).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,334 @@
+//! RFC 3161 trusted timestamps for generated proofs.
+//!
+//! A timestamp authority (TSA) signs the proof's SHA-256 together with the
+//! time it saw it, so a verifier can tell a proof existed before a given time
+//! without trusting the server's `generated_at`.
+//!
+//! The server checks that a token covers the proof and reads its time. It does
+//! not validate the TSA's CMS signature or certificate chain; verifiers that
+//! need that check the token, which is returned as-is, against the TSA's
+//! certificate themselves.
+
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rand::RngCore;
+
+use crate::errors::AppError;
+
+const TSA_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+const BOOLEAN: u8 = 0x01;
+const INTEGER: u8 = 0x02;
+const OCTET_STRING: u8 = 0x04;
+const NULL: u8 = 0x05;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+const GENERALIZED_TIME: u8 = 0x18;
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const EXPLICIT_0: u8 = 0xa0;
+
+/// 2.16.840.1.101.3.4.2.1
+const SHA256_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+
+/// A TSA's answer for one proof
+#[derive(Debug, Clone)]
+pub struct Timestamp {
+    /// DER-encoded `TimeStampToken` (a CMS `ContentInfo`)
+    pub token: Vec<u8>,
+    pub timestamped_at: DateTime<Utc>,
+}
+
+/// Fields of a token's `TSTInfo` that the server relies on
+#[derive(Debug)]
+struct TstInfo {
+    message_imprint: Vec<u8>,
+    gen_time: DateTime<Utc>,
+    nonce: Option<Vec<u8>>,
+}
+
+pub struct TimestampClient {
+    http: reqwest::Client,
+    tsa_url: String,
+}
+
+impl TimestampClient {
+    pub fn new(tsa_url: String) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(TSA_REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            tsa_url,
+        }
+    }
+
+    /// Ask the TSA to timestamp a SHA-256 digest
+    pub async fn timestamp(&self, digest: &[u8; 32]) -> Result<Timestamp, AppError> {
+        let nonce = rand::thread_rng().next_u64();
+
+        let response = self
+            .http
+            .post(&self.tsa_url)
+            .header(reqwest::header::CONTENT_TYPE, "application/timestamp-query")
+            .body(timestamp_request(digest, nonce))
+            .send()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("Timestamp authority unreachable: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ServiceUnavailable(format!(
+                "Timestamp authority returned {}",
+                response.status()
+            )));
+        }
+
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("Timestamp authority response failed: {}", e)))?;
+
+        parse_response(&body, digest, nonce)
+    }
+}
+
+/// Time at which `token` attests `digest` existed
+pub fn verify_token(token: &[u8], digest: &[u8; 32]) -> Result<DateTime<Utc>, AppError> {
+    let tst_info = parse_token(token)?;
+    if tst_info.message_imprint != digest {
+        return Err(AppError::CryptographicError("Timestamp token does not cover this proof".to_string()));
+    }
+
+    Ok(tst_info.gen_time)
+}
+
+/// DER `TimeStampReq` for a SHA-256 digest, asking for the TSA certificate
+fn timestamp_request(digest: &[u8; 32], nonce: u64) -> Vec<u8> {
+    let algorithm = tlv(SEQUENCE, &[tlv(OBJECT_IDENTIFIER, SHA256_OID), tlv(NULL, &[])].concat());
+    let message_imprint = tlv(SEQUENCE, &[algorithm, tlv(OCTET_STRING, digest)].concat());
+
+    tlv(
+        SEQUENCE,
+        &[
+            integer(&[1]),
+            message_imprint,
+            integer(&nonce.to_be_bytes()),
+            tlv(BOOLEAN, &[0xff]),
+        ]
+        .concat(),
+    )
+}
+
+/// Token from a DER `TimeStampResp`, checked against the request it answers
+fn parse_response(response: &[u8], digest: &[u8; 32], nonce: u64) -> Result<Timestamp, AppError> {
+    let (body, _) = read(response, SEQUENCE)?;
+    let (status_info, token) = read(body, SEQUENCE)?;
+    let (status, _) = read(status_info, INTEGER)?;
+
+    // 0 = granted, 1 = granted with modifications
+    if !matches!(status, [0] | [1]) {
+        return Err(AppError::ServiceUnavailable(format!(
+            "Timestamp authority rejected the request (status {})",
+            hex::encode(status)
+        )));
+    }
+    if token.is_empty() {
+        return Err(AppError::ServiceUnavailable("Timestamp authority returned no token".to_string()));
+    }
+
+    let tst_info = parse_token(token)?;
+    if tst_info.message_imprint != digest {
+        return Err(AppError::CryptographicError("Timestamp token does not cover this proof".to_string()));
+    }
+    if tst_info.nonce.as_deref().map(strip_leading_zeros) != Some(strip_leading_zeros(&nonce.to_be_bytes())) {
+        return Err(AppError::CryptographicError("Timestamp token nonce does not match the request".to_string()));
+    }
+
+    Ok(Timestamp {
+        token: token.to_vec(),
+        timestamped_at: tst_info.gen_time,
+    })
+}
+
+/// Walk `ContentInfo` -> `SignedData` -> `encapContentInfo` down to the `TSTInfo`
+fn parse_token(token: &[u8]) -> Result<TstInfo, AppError> {
+    let (content_info, _) = read(token, SEQUENCE)?;
+    let (_, rest) = read(content_info, OBJECT_IDENTIFIER)?;
+    let (signed_data, _) = read(rest, EXPLICIT_0)?;
+    let (signed_data, _) = read(signed_data, SEQUENCE)?;
+    let (_, rest) = read(signed_data, INTEGER)?;
+    let (_, rest) = read(rest, SET)?;
+    let (encap_content_info, _) = read(rest, SEQUENCE)?;
+    let (_, rest) = read(encap_content_info, OBJECT_IDENTIFIER)?;
+    let (content, _) = read(rest, EXPLICIT_0)?;
+    let (tst_info, _) = read(content, OCTET_STRING)?;
+
+    let (tst_info, _) = read(tst_info, SEQUENCE)?;
+    let (_, rest) = read(tst_info, INTEGER)?;
+    let (_, rest) = read(rest, OBJECT_IDENTIFIER)?;
+    let (message_imprint, rest) = read(rest, SEQUENCE)?;
+    let (_, hashed_message) = read(message_imprint, SEQUENCE)?;
+    let (hashed_message, _) = read(hashed_message, OCTET_STRING)?;
+    let (_, rest) = read(rest, INTEGER)?;
+    let (gen_time, mut rest) = read(rest, GENERALIZED_TIME)?;
+
+    // After genTime: accuracy (SEQUENCE) and ordering (BOOLEAN), then nonce (INTEGER)
+    let mut nonce = None;
+    while !rest.is_empty() {
+        let (tag, content, next) = read_any(rest)?;
+        if tag == INTEGER {
+            nonce = Some(content.to_vec());
+            break;
+        }
+        rest = next;
+    }
+
+    Ok(TstInfo {
+        message_imprint: hashed_message.to_vec(),
+        gen_time: parse_generalized_time(gen_time)?,
+        nonce,
+    })
+}
+
+/// `YYYYMMDDHHMMSS[.fff]Z`, which RFC 3161 requires to be in UTC
+fn parse_generalized_time(value: &[u8]) -> Result<DateTime<Utc>, AppError> {
+    let value = std::str::from_utf8(value).map_err(|_| malformed())?;
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%d%H%M%S%.fZ").map_err(|_| malformed())?;
+
+    Ok(naive.and_utc())
+}
+
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    if content.len() < 0x80 {
+        encoded.push(content.len() as u8);
+    } else {
+        let length = strip_leading_zeros(&content.len().to_be_bytes()).to_vec();
+        encoded.push(0x80 | length.len() as u8);
+        encoded.extend(length);
+    }
+    encoded.extend_from_slice(content);
+    encoded
+}
+
+/// Unsigned big-endian integer in its minimal DER form
+fn integer(value: &[u8]) -> Vec<u8> {
+    let mut content = strip_leading_zeros(value).to_vec();
+    if content.is_empty() || content[0] & 0x80 != 0 {
+        content.insert(0, 0);
+    }
+    tlv(INTEGER, &content)
+}
+
+fn strip_leading_zeros(value: &[u8]) -> &[u8] {
+    let start = value.iter().position(|&b| b != 0).unwrap_or(value.len());
+    &value[start..]
+}
+
+/// Content of the element at the start of `input` if it has `tag`, and what follows it
+fn read(input: &[u8], tag: u8) -> Result<(&[u8], &[u8]), AppError> {
+    let (actual, content, rest) = read_any(input)?;
+    if actual != tag {
+        return Err(malformed());
+    }
+    Ok((content, rest))
+}
+
+fn read_any(input: &[u8]) -> Result<(u8, &[u8], &[u8]), AppError> {
+    let (&tag, input) = input.split_first().ok_or_else(malformed)?;
+    let (&first, input) = input.split_first().ok_or_else(malformed)?;
+
+    let (length, input) = if first < 0x80 {
+        (first as usize, input)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > std::mem::size_of::<usize>() || input.len() < count {
+            return Err(malformed());
+        }
+        let length = input[..count].iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        (length, &input[count..])
+    };
+
+    if input.len() < length {
+        return Err(malformed());
+    }
+    Ok((tag, &input[..length], &input[length..]))
+}
+
+fn malformed() -> AppError {
+    AppError::CryptographicError("Malformed timestamp token".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+
+    /// A minimal token as a TSA would return it, without signer infos
+    fn token(digest: &[u8], gen_time: &str, nonce: u64) -> Vec<u8> {
+        let algorithm = tlv(SEQUENCE, &[tlv(OBJECT_IDENTIFIER, SHA256_OID), tlv(NULL, &[])].concat());
+        let tst_info = tlv(
+            SEQUENCE,
+            &[
+                integer(&[1]),
+                tlv(OBJECT_IDENTIFIER, &[0x2a, 0x03, 0x04]),
+                tlv(SEQUENCE, &[algorithm.clone(), tlv(OCTET_STRING, digest)].concat()),
+                integer(&[0x42]),
+                tlv(GENERALIZED_TIME, gen_time.as_bytes()),
+                tlv(SEQUENCE, &integer(&[1])), // accuracy
+                integer(&nonce.to_be_bytes()),
+            ]
+            .concat(),
+        );
+        let encap_content_info = tlv(
+            SEQUENCE,
+            &[tlv(OBJECT_IDENTIFIER, &[0x2a, 0x86, 0x48]), tlv(EXPLICIT_0, &tlv(OCTET_STRING, &tst_info))].concat(),
+        );
+        let signed_data = tlv(SEQUENCE, &[integer(&[3]), tlv(SET, &algorithm), encap_content_info].concat());
+        tlv(SEQUENCE, &[tlv(OBJECT_IDENTIFIER, &[0x2a, 0x86, 0x48]), tlv(EXPLICIT_0, &signed_data)].concat())
+    }
+
+    #[test]
+    fn test_request_encodes_digest_and_nonce() {
+        let digest: [u8; 32] = Sha256::digest(b"proof").into();
+        let request = timestamp_request(&digest, 0x80);
+
+        let (body, rest) = read(&request, SEQUENCE).unwrap();
+        assert!(rest.is_empty());
+        let (version, rest) = read(body, INTEGER).unwrap();
+        assert_eq!(version, [1]);
+        let (imprint, rest) = read(rest, SEQUENCE).unwrap();
+        let (_, hashed) = read(imprint, SEQUENCE).unwrap();
+        assert_eq!(read(hashed, OCTET_STRING).unwrap().0, digest);
+        // A high bit set needs a leading zero to stay positive
+        assert_eq!(read(rest, INTEGER).unwrap().0, [0x00, 0x80]);
+    }
+
+    #[test]
+    fn test_response_token_is_checked_against_request() {
+        let digest: [u8; 32] = Sha256::digest(b"proof").into();
+        let token = token(&digest, "20260102030405.5Z", 7);
+        let status = tlv(SEQUENCE, &integer(&[0]));
+        let response = tlv(SEQUENCE, &[status, token.clone()].concat());
+
+        let timestamp = parse_response(&response, &digest, 7).unwrap();
+        assert_eq!(timestamp.token, token);
+        assert_eq!(timestamp.timestamped_at.to_rfc3339(), "2026-01-02T03:04:05.500+00:00");
+        assert_eq!(verify_token(&token, &digest).unwrap(), timestamp.timestamped_at);
+
+        assert!(parse_response(&response, &digest, 8).is_err());
+        let other: [u8; 32] = Sha256::digest(b"other proof").into();
+        assert!(parse_response(&response, &other, 7).is_err());
+        assert!(verify_token(&token, &other).is_err());
+
+        let rejected = tlv(SEQUENCE, &tlv(SEQUENCE, &integer(&[2])));
+        assert!(matches!(parse_response(&rejected, &digest, 7), Err(AppError::ServiceUnavailable(_))));
+    }
+
+    #[test]
+    fn test_truncated_token_is_rejected() {
+        let digest: [u8; 32] = Sha256::digest(b"proof").into();
+        let token = token(&digest, "20260102030405Z", 7);
+        assert!(verify_token(&token[..token.len() - 3], &digest).is_err());
+    }
+}
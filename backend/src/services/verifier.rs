@@ -0,0 +1,171 @@
+use crate::models::{CreateVerifierRequest, Verifier, VerifierResponse};
+use crate::errors::AppError;
+use crate::services::captcha::CaptchaVerifier;
+use std::net::IpAddr;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Header carrying a registered verifier's token on public verification requests
+pub const VERIFIER_TOKEN_HEADER: &str = "x-verifier-token";
+
+const TOKEN_PREFIX: &str = "vf_";
+
+#[derive(Clone)]
+pub struct VerifierService {
+    db: PgPool,
+    allow_anonymous: bool,
+    captcha: Option<CaptchaVerifier>,
+}
+
+impl VerifierService {
+    pub fn new(db: PgPool, allow_anonymous: bool) -> Self {
+        Self { db, allow_anonymous, captcha: None }
+    }
+
+    /// Require anonymous public verifications to pass a CAPTCHA
+    pub fn with_captcha(mut self, captcha: CaptchaVerifier) -> Self {
+        self.captcha = Some(captcha);
+        self
+    }
+
+    pub async fn register_verifier(&self, created_by: Uuid, request: CreateVerifierRequest) -> Result<VerifierResponse, AppError> {
+        let token = generate_token();
+
+        let verifier = sqlx::query_as::<_, Verifier>(
+            r#"
+            INSERT INTO verifiers (name, token_hash, created_by)
+            VALUES ($1, $2, $3)
+            RETURNING *
+            "#
+        )
+        .bind(&request.name)
+        .bind(hash_token(&token))
+        .bind(created_by)
+        .fetch_one(&self.db)
+        .await?;
+
+        let mut response = VerifierResponse::from(verifier);
+        response.token = Some(token);
+
+        Ok(response)
+    }
+
+    pub async fn list_verifiers(&self) -> Result<Vec<VerifierResponse>, AppError> {
+        let verifiers = sqlx::query_as::<_, Verifier>("SELECT * FROM verifiers ORDER BY created_at DESC")
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(verifiers.into_iter().map(Into::into).collect())
+    }
+
+    /// Stop accepting a verifier's token; past verifications keep referencing it
+    pub async fn deactivate_verifier(&self, verifier_id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("UPDATE verifiers SET is_active = FALSE WHERE id = $1")
+            .bind(verifier_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Verifier not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the verifier behind a public verification request.
+    ///
+    /// A token that is presented must belong to an active verifier. Requests
+    /// without one are only let through (as `None`) in anonymous mode.
+    pub async fn authenticate(&self, token: Option<&str>) -> Result<Option<Uuid>, AppError> {
+        let Some(token) = token else {
+            if self.allow_anonymous {
+                return Ok(None);
+            }
+            return Err(AppError::Forbidden("A registered verifier token is required".to_string()));
+        };
+
+        let verifier_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            UPDATE verifiers SET last_used_at = NOW()
+            WHERE token_hash = $1 AND is_active = TRUE
+            RETURNING id
+            "#
+        )
+        .bind(hash_token(token))
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::Forbidden("Unregistered verifier token".to_string()))?;
+
+        Ok(Some(verifier_id))
+    }
+
+    /// Check the CAPTCHA of a public verification made without a verifier
+    /// token. Passes when no CAPTCHA is configured.
+    pub async fn check_captcha(&self, captcha_token: Option<&str>, remote_ip: Option<IpAddr>) -> Result<(), AppError> {
+        let Some(captcha) = &self.captcha else {
+            return Ok(());
+        };
+        let token = captcha_token
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| AppError::Forbidden("A CAPTCHA token is required".to_string()))?;
+
+        captcha.verify(token, remote_ip).await
+    }
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", TOKEN_PREFIX, hex::encode(bytes))
+}
+
+/// Only this digest is stored, so a leaked table does not leak usable tokens
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_are_unique_and_stored_hashed() {
+        let token = generate_token();
+        assert!(token.starts_with(TOKEN_PREFIX));
+        assert_ne!(token, generate_token());
+
+        let hash = hash_token(&token);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_token(&token));
+        assert!(!hash.contains(&token[TOKEN_PREFIX.len()..]));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_only_registered_active_tokens_are_accepted(db: PgPool) {
+        let admin_id = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO users (email, password_hash, full_name, role) VALUES ('admin@example.com', 'x', 'Admin', 'admin') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let service = VerifierService::new(db.clone(), false);
+        let verifier = service
+            .register_verifier(admin_id, CreateVerifierRequest { name: "Airport gate".to_string() })
+            .await
+            .unwrap();
+        let token = verifier.token.unwrap();
+
+        assert_eq!(service.authenticate(Some(&token)).await.unwrap(), Some(verifier.id));
+        assert!(matches!(service.authenticate(Some("vf_unknown")).await, Err(AppError::Forbidden(_))));
+        assert!(matches!(service.authenticate(None).await, Err(AppError::Forbidden(_))));
+
+        service.deactivate_verifier(verifier.id).await.unwrap();
+        assert!(matches!(service.authenticate(Some(&token)).await, Err(AppError::Forbidden(_))));
+
+        let anonymous = VerifierService::new(db, true);
+        assert_eq!(anonymous.authenticate(None).await.unwrap(), None);
+    }
+}
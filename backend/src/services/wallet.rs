@@ -0,0 +1,329 @@
+//! Apple and Google Wallet passes for proofs.
+//!
+//! An Apple pass (`.pkpass`) is a zip bundle of `pass.json`, its images, a `manifest.json` with
+//! the SHA-1 of every file, and `signature`: a detached PKCS#7 signature over
+//! the manifest made with the Pass Type ID certificate, chained to Apple's
+//! WWDR intermediate certificate.
+//!
+//! A Google pass is a "save to wallet" link carrying a JWT signed with a
+//! service-account key. The JWT embeds the Generic pass object, and its
+//! class, so nothing has to be created through the Wallet API beforehand.
+
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+use openssl::{
+    pkcs12::Pkcs12,
+    pkcs7::{Pkcs7, Pkcs7Flags},
+    pkey::{PKey, Private},
+    stack::Stack,
+    x509::X509,
+};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+use zip::{write::FileOptions, ZipWriter};
+
+use crate::errors::AppError;
+
+const ICON: &[u8] = include_bytes!("../../assets/wallet/icon.png");
+const ICON_2X: &[u8] = include_bytes!("../../assets/wallet/icon@2x.png");
+
+const GOOGLE_SAVE_URL: &str = "https://pay.google.com/gp/v/save";
+
+pub struct ApplePassSigner {
+    pass_type_identifier: String,
+    team_identifier: String,
+    organization_name: String,
+    certificate: X509,
+    private_key: PKey<Private>,
+    wwdr_certificate: X509,
+}
+
+impl ApplePassSigner {
+    pub fn new(
+        pass_type_identifier: String,
+        team_identifier: String,
+        organization_name: String,
+        certificate: X509,
+        private_key: PKey<Private>,
+        wwdr_certificate: X509,
+    ) -> Self {
+        Self {
+            pass_type_identifier,
+            team_identifier,
+            organization_name,
+            certificate,
+            private_key,
+            wwdr_certificate,
+        }
+    }
+
+    /// Load the Pass Type ID certificate and key from a PKCS#12 (`.p12`) file
+    /// and the WWDR certificate from a PEM or DER file
+    pub fn open(
+        certificate_path: impl AsRef<Path>,
+        certificate_password: &str,
+        wwdr_certificate_path: impl AsRef<Path>,
+        pass_type_identifier: String,
+        team_identifier: String,
+        organization_name: String,
+    ) -> anyhow::Result<Self> {
+        let certificate_path = certificate_path.as_ref();
+        let parsed = Pkcs12::from_der(&std::fs::read(certificate_path)?)
+            .and_then(|pkcs12| pkcs12.parse2(certificate_password))
+            .map_err(|e| anyhow::anyhow!("Failed to read pass certificate {}: {}", certificate_path.display(), e))?;
+        let (Some(certificate), Some(private_key)) = (parsed.cert, parsed.pkey) else {
+            anyhow::bail!("Pass certificate {} must hold a certificate and its private key", certificate_path.display());
+        };
+
+        let wwdr_certificate_path = wwdr_certificate_path.as_ref();
+        let wwdr = std::fs::read(wwdr_certificate_path)?;
+        let wwdr_certificate = X509::from_pem(&wwdr)
+            .or_else(|_| X509::from_der(&wwdr))
+            .map_err(|e| anyhow::anyhow!("Failed to read WWDR certificate {}: {}", wwdr_certificate_path.display(), e))?;
+
+        Ok(Self::new(
+            pass_type_identifier,
+            team_identifier,
+            organization_name,
+            certificate,
+            private_key,
+            wwdr_certificate,
+        ))
+    }
+
+    /// Bundle `pass` (the pass-specific keys of `pass.json`) into a signed `.pkpass`
+    pub fn package(&self, mut pass: serde_json::Value) -> Result<Vec<u8>, AppError> {
+        if let Some(fields) = pass.as_object_mut() {
+            fields.insert("formatVersion".to_string(), json!(1));
+            fields.insert("passTypeIdentifier".to_string(), json!(self.pass_type_identifier));
+            fields.insert("teamIdentifier".to_string(), json!(self.team_identifier));
+            fields.insert("organizationName".to_string(), json!(self.organization_name));
+        }
+        let pass_json = serde_json::to_vec(&pass)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to serialize pass.json: {}", e)))?;
+
+        let files: [(&str, &[u8]); 3] = [("pass.json", &pass_json), ("icon.png", ICON), ("icon@2x.png", ICON_2X)];
+        let manifest: serde_json::Map<String, serde_json::Value> = files
+            .iter()
+            .map(|(name, data)| (name.to_string(), json!(hex::encode(openssl::sha::sha1(data)))))
+            .collect();
+        let manifest = serde_json::to_vec(&manifest)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to serialize manifest.json: {}", e)))?;
+        let signature = self
+            .sign_manifest(&manifest)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to sign pass manifest: {}", e)))?;
+
+        let mut entries = files.to_vec();
+        entries.push(("manifest.json", &manifest));
+        entries.push(("signature", &signature));
+        zip_bundle(&entries).map_err(|e| AppError::InternalServerError(format!("Failed to build pass bundle: {}", e)))
+    }
+
+    /// Detached PKCS#7 signature over the manifest, including the WWDR certificate
+    fn sign_manifest(&self, manifest: &[u8]) -> Result<Vec<u8>, openssl::error::ErrorStack> {
+        let mut chain = Stack::new()?;
+        chain.push(self.wwdr_certificate.clone())?;
+        Pkcs7::sign(
+            &self.certificate,
+            &self.private_key,
+            &chain,
+            manifest,
+            Pkcs7Flags::DETACHED | Pkcs7Flags::BINARY,
+        )?
+        .to_der()
+    }
+}
+
+fn zip_bundle(entries: &[(&str, &[u8])]) -> zip::result::ZipResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, data) in entries {
+        zip.start_file(*name, FileOptions::default())?;
+        zip.write_all(data)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+/// The fields used from a Google Cloud service-account key file
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+}
+
+pub struct GoogleWalletSigner {
+    issuer_id: String,
+    class_suffix: String,
+    client_email: String,
+    key: EncodingKey,
+}
+
+impl GoogleWalletSigner {
+    pub fn new(issuer_id: String, class_suffix: String, client_email: String, private_key_pem: &str) -> anyhow::Result<Self> {
+        let key = EncodingKey::from_rsa_pem(private_key_pem.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Invalid Google service-account private key: {}", e))?;
+        Ok(Self { issuer_id, class_suffix, client_email, key })
+    }
+
+    /// Load the service account from its JSON key file
+    pub fn open(service_account_path: impl AsRef<Path>, issuer_id: String, class_suffix: String) -> anyhow::Result<Self> {
+        let service_account_path = service_account_path.as_ref();
+        let account: ServiceAccountKey = serde_json::from_slice(&std::fs::read(service_account_path)?)
+            .map_err(|e| anyhow::anyhow!("Invalid service-account key file {}: {}", service_account_path.display(), e))?;
+        Self::new(issuer_id, class_suffix, account.client_email, &account.private_key)
+    }
+
+    pub fn class_id(&self) -> String {
+        format!("{}.{}", self.issuer_id, self.class_suffix)
+    }
+
+    pub fn object_id(&self, proof_id: Uuid) -> String {
+        format!("{}.{}", self.issuer_id, proof_id)
+    }
+
+    /// Link that adds the Generic pass `object` to the user's Google Wallet
+    pub fn save_url(&self, object: serde_json::Value) -> Result<String, AppError> {
+        let claims = json!({
+            "iss": self.client_email,
+            "aud": "google",
+            "typ": "savetowallet",
+            "iat": chrono::Utc::now().timestamp(),
+            "origins": [],
+            "payload": {
+                "genericClasses": [{"id": self.class_id()}],
+                "genericObjects": [object],
+            },
+        });
+        let jwt = jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &self.key)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to sign Google Wallet pass: {}", e)))?;
+
+        Ok(format!("{}/{}", GOOGLE_SAVE_URL, jwt))
+    }
+}
+
+#[cfg(test)]
+impl ApplePassSigner {
+    /// Signer with throwaway self-signed certificates
+    pub(crate) fn for_tests() -> Self {
+        let (certificate, private_key) = tests::self_signed("Pass Type ID: pass.test.veryfi");
+        let (wwdr_certificate, _) = tests::self_signed("Test WWDR");
+        Self::new(
+            "pass.test.veryfi".to_string(),
+            "TEAM123456".to_string(),
+            "VeryFi".to_string(),
+            certificate,
+            private_key,
+            wwdr_certificate,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::{
+        asn1::Asn1Time,
+        hash::MessageDigest,
+        rsa::Rsa,
+        x509::{store::X509StoreBuilder, X509NameBuilder},
+    };
+    use std::io::Read;
+
+    pub(super) fn self_signed(common_name: &str) -> (X509, PKey<Private>) {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", common_name).unwrap();
+        let name = name.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+        builder.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        (builder.build(), key)
+    }
+
+    #[test]
+    fn test_pass_bundle_has_signed_manifest_of_every_file() {
+        let (certificate, private_key) = self_signed("Pass Type ID: pass.test.veryfi");
+        let (wwdr_certificate, _) = self_signed("Test WWDR");
+        let signer = ApplePassSigner::new(
+            "pass.test.veryfi".to_string(),
+            "TEAM123456".to_string(),
+            "VeryFi".to_string(),
+            certificate.clone(),
+            private_key,
+            wwdr_certificate,
+        );
+
+        let bundle = signer.package(json!({"serialNumber": "proof-1", "description": "Health pass"})).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(bundle)).unwrap();
+        let mut read = |name: &str| {
+            let mut data = Vec::new();
+            archive.by_name(name).unwrap().read_to_end(&mut data).unwrap();
+            data
+        };
+
+        let pass: serde_json::Value = serde_json::from_slice(&read("pass.json")).unwrap();
+        assert_eq!(pass["passTypeIdentifier"], "pass.test.veryfi");
+        assert_eq!(pass["teamIdentifier"], "TEAM123456");
+        assert_eq!(pass["serialNumber"], "proof-1");
+
+        let manifest_bytes = read("manifest.json");
+        let manifest: serde_json::Value = serde_json::from_slice(&manifest_bytes).unwrap();
+        for name in ["pass.json", "icon.png", "icon@2x.png"] {
+            assert_eq!(manifest[name], hex::encode(openssl::sha::sha1(&read(name))), "{}", name);
+        }
+
+        let signature = Pkcs7::from_der(&read("signature")).unwrap();
+        let mut certs = Stack::new().unwrap();
+        certs.push(certificate).unwrap();
+        let store = X509StoreBuilder::new().unwrap().build();
+        signature
+            .verify(&certs, &store, Some(&manifest_bytes), None, Pkcs7Flags::NOVERIFY | Pkcs7Flags::BINARY)
+            .unwrap();
+        assert!(signature
+            .verify(&certs, &store, Some(b"tampered"), None, Pkcs7Flags::NOVERIFY | Pkcs7Flags::BINARY)
+            .is_err());
+    }
+
+    #[test]
+    fn test_google_save_url_carries_signed_pass_object() {
+        let rsa = Rsa::generate(2048).unwrap();
+        let private_key_pem = String::from_utf8(PKey::from_rsa(rsa.clone()).unwrap().private_key_to_pem_pkcs8().unwrap()).unwrap();
+        let public_key_pem = rsa.public_key_to_pem().unwrap();
+        let signer = GoogleWalletSigner::new(
+            "3388000000012345678".to_string(),
+            "health_pass".to_string(),
+            "wallet@project.iam.gserviceaccount.com".to_string(),
+            &private_key_pem,
+        )
+        .unwrap();
+
+        let proof_id = Uuid::new_v4();
+        let url = signer.save_url(json!({"id": signer.object_id(proof_id), "classId": signer.class_id()})).unwrap();
+        let jwt = url.strip_prefix("https://pay.google.com/gp/v/save/").unwrap();
+
+        let mut validation = jsonwebtoken::Validation::new(Algorithm::RS256);
+        validation.set_audience(&["google"]);
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+        let claims = jsonwebtoken::decode::<serde_json::Value>(
+            jwt,
+            &jsonwebtoken::DecodingKey::from_rsa_pem(&public_key_pem).unwrap(),
+            &validation,
+        )
+        .unwrap()
+        .claims;
+
+        assert_eq!(claims["iss"], "wallet@project.iam.gserviceaccount.com");
+        assert_eq!(claims["typ"], "savetowallet");
+        assert_eq!(claims["payload"]["genericClasses"][0]["id"], "3388000000012345678.health_pass");
+        assert_eq!(claims["payload"]["genericObjects"][0]["id"], format!("3388000000012345678.{}", proof_id));
+    }
+}
@@ -0,0 +1,589 @@
+use crate::models::{CreateWebhookRequest, DeliveryStatus, HealthRecordType, UpdateWebhookRequest, Webhook, WebhookResponse};
+use crate::errors::AppError;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use uuid::Uuid;
+
+const MAX_DELIVERY_ATTEMPTS: i32 = 5;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(2);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Events delivered to registered webhooks
+#[derive(Debug, Clone)]
+pub enum WebhookEvent {
+    ProofVerified {
+        proof_id: Uuid,
+        is_valid: bool,
+        verified_at: DateTime<Utc>,
+    },
+    RecordCreated(RecordEvent),
+    RecordSigned(RecordEvent),
+    RecordRevoked(RecordEvent),
+}
+
+/// A record lifecycle change; carries no patient data
+#[derive(Debug, Clone)]
+pub struct RecordEvent {
+    pub record_id: Uuid,
+    pub record_type: HealthRecordType,
+    pub authority_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl WebhookEvent {
+    /// Every event name a webhook can subscribe to
+    pub const NAMES: [&'static str; 4] = ["proof.verified", "record.created", "record.signed", "record.revoked"];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::ProofVerified { .. } => "proof.verified",
+            WebhookEvent::RecordCreated(_) => "record.created",
+            WebhookEvent::RecordSigned(_) => "record.signed",
+            WebhookEvent::RecordRevoked(_) => "record.revoked",
+        }
+    }
+
+    fn data(&self) -> serde_json::Value {
+        match self {
+            WebhookEvent::ProofVerified { proof_id, is_valid, verified_at } => json!({
+                "proof_id": proof_id,
+                "is_valid": is_valid,
+                "verified_at": verified_at,
+            }),
+            WebhookEvent::RecordCreated(event) | WebhookEvent::RecordSigned(event) | WebhookEvent::RecordRevoked(event) => json!({
+                "record_id": event.record_id,
+                "record_type": event.record_type,
+                "authority_id": event.authority_id,
+                "occurred_at": event.occurred_at,
+            }),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WebhookService {
+    db: PgPool,
+}
+
+impl WebhookService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn create_webhook(&self, user_id: Uuid, request: CreateWebhookRequest) -> Result<WebhookResponse, AppError> {
+        let secret = generate_secret();
+        let events = if request.events.is_empty() {
+            vec!["proof.verified".to_string()]
+        } else {
+            checked_events(request.events)?
+        };
+        resolve_webhook_url(&request.url).await?;
+
+        let webhook = sqlx::query_as::<_, Webhook>(
+            r#"
+            INSERT INTO webhooks (user_id, url, secret, events)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(&request.url)
+        .bind(&secret)
+        .bind(&events)
+        .fetch_one(&self.db)
+        .await?;
+
+        let mut response = WebhookResponse::from(webhook);
+        response.secret = Some(secret);
+
+        Ok(response)
+    }
+
+    pub async fn list_webhooks(&self, user_id: Uuid) -> Result<Vec<WebhookResponse>, AppError> {
+        let webhooks = sqlx::query_as::<_, Webhook>(
+            "SELECT * FROM webhooks WHERE user_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(webhooks.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_webhook(&self, webhook_id: Uuid, user_id: Uuid) -> Result<WebhookResponse, AppError> {
+        let webhook = sqlx::query_as::<_, Webhook>(
+            "SELECT * FROM webhooks WHERE id = $1 AND user_id = $2"
+        )
+        .bind(webhook_id)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))?;
+
+        Ok(webhook.into())
+    }
+
+    pub async fn update_webhook(
+        &self,
+        webhook_id: Uuid,
+        user_id: Uuid,
+        request: UpdateWebhookRequest,
+    ) -> Result<WebhookResponse, AppError> {
+        let events = request.events.map(checked_events).transpose()?;
+        if let Some(url) = &request.url {
+            resolve_webhook_url(url).await?;
+        }
+
+        let webhook = sqlx::query_as::<_, Webhook>(
+            r#"
+            UPDATE webhooks
+            SET url = COALESCE($1, url), is_active = COALESCE($2, is_active), events = COALESCE($3, events), updated_at = NOW()
+            WHERE id = $4 AND user_id = $5
+            RETURNING *
+            "#
+        )
+        .bind(&request.url)
+        .bind(request.is_active)
+        .bind(&events)
+        .bind(webhook_id)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))?;
+
+        Ok(webhook.into())
+    }
+
+    pub async fn delete_webhook(&self, webhook_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND user_id = $2")
+            .bind(webhook_id)
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Webhook not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Notify the holder of a verified proof without blocking the caller
+    pub fn dispatch_proof_verified(&self, proof_id: Uuid, is_valid: bool, verified_at: DateTime<Utc>) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let event = WebhookEvent::ProofVerified { proof_id, is_valid, verified_at };
+            if let Err(e) = service.deliver_to_proof_holder(proof_id, event).await {
+                tracing::warn!("Failed to dispatch proof.verified webhooks: {}", e);
+            }
+        });
+    }
+
+    async fn deliver_to_proof_holder(&self, proof_id: Uuid, event: WebhookEvent) -> Result<(), AppError> {
+        let webhooks = sqlx::query_as::<_, Webhook>(
+            r#"
+            SELECT w.* FROM webhooks w
+            JOIN health_records hr ON hr.user_id = w.user_id
+            JOIN zk_proofs zp ON zp.health_record_id = hr.id
+            WHERE zp.id = $1 AND w.is_active = TRUE AND $2 = ANY(w.events)
+            "#
+        )
+        .bind(proof_id)
+        .bind(event.name())
+        .fetch_all(&self.db)
+        .await?;
+
+        for webhook in webhooks {
+            self.deliver(&webhook, &event).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Announce a record lifecycle change without blocking the caller. It is
+    /// delivered to the record owner's webhooks and to those of admins of the
+    /// record's organization, when they subscribe to the event.
+    pub fn dispatch_record_event(&self, event: WebhookEvent) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = service.deliver_record_event(&event).await {
+                tracing::warn!("Failed to dispatch {} webhooks: {}", event.name(), e);
+            }
+        });
+    }
+
+    async fn deliver_record_event(&self, event: &WebhookEvent) -> Result<(), AppError> {
+        let (WebhookEvent::RecordCreated(record) | WebhookEvent::RecordSigned(record) | WebhookEvent::RecordRevoked(record)) = event else {
+            return Ok(());
+        };
+
+        for webhook in self.record_event_subscribers(record.record_id, event.name()).await? {
+            self.deliver(&webhook, event).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn record_event_subscribers(&self, record_id: Uuid, event_name: &str) -> Result<Vec<Webhook>, AppError> {
+        let webhooks = sqlx::query_as::<_, Webhook>(
+            r#"
+            SELECT w.* FROM webhooks w
+            JOIN users u ON u.id = w.user_id
+            JOIN health_records hr ON hr.id = $1
+            WHERE w.is_active = TRUE AND $2 = ANY(w.events)
+              AND (w.user_id = hr.user_id OR (u.role = 'admin' AND u.org_id = hr.org_id))
+            "#
+        )
+        .bind(record_id)
+        .bind(event_name)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(webhooks)
+    }
+
+    /// Deliver one event to one webhook, retrying with exponential backoff on failure
+    async fn deliver(&self, webhook: &Webhook, event: &WebhookEvent) -> Result<(), AppError> {
+        let delivery_id = Uuid::new_v4();
+        let payload = json!({
+            "id": delivery_id,
+            "event": event.name(),
+            "created_at": Utc::now(),
+            "data": event.data(),
+        });
+        let body = serde_json::to_vec(&payload)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to serialize webhook payload: {}", e)))?;
+        let signature = sign_payload(&webhook.secret, &body);
+
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries (id, webhook_id, event, payload, status)
+            VALUES ($1, $2, $3, $4, $5)
+            "#
+        )
+        .bind(delivery_id)
+        .bind(webhook.id)
+        .bind(event.name())
+        .bind(&payload)
+        .bind(DeliveryStatus::Pending)
+        .execute(&self.db)
+        .await?;
+
+        // The host may have been repointed since the webhook was registered, so resolve it
+        // again and connect only to the addresses that passed the check
+        let client = match resolve_webhook_url(&webhook.url).await.and_then(|(host, addrs)| delivery_client(&host, &addrs)) {
+            Ok(client) => client,
+            Err(e) => {
+                sqlx::query("UPDATE webhook_deliveries SET status = $1, last_error = $2 WHERE id = $3")
+                    .bind(DeliveryStatus::Failed)
+                    .bind(e.to_string())
+                    .bind(delivery_id)
+                    .execute(&self.db)
+                    .await?;
+                tracing::warn!("Webhook delivery {} refused: {}", delivery_id, e);
+                return Ok(());
+            }
+        };
+
+        let mut delay = INITIAL_RETRY_DELAY;
+        for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+            let result = client
+                .post(&webhook.url)
+                .header("Content-Type", "application/json")
+                .header("X-Webhook-Event", event.name())
+                .header("X-Webhook-Delivery", delivery_id.to_string())
+                .header("X-Signature", format!("sha256={}", signature))
+                .body(body.clone())
+                .send()
+                .await;
+
+            let (status_code, error) = match result {
+                Ok(response) if response.status().is_success() => {
+                    sqlx::query(
+                        r#"
+                        UPDATE webhook_deliveries
+                        SET status = $1, attempts = $2, last_status_code = $3, last_error = NULL, delivered_at = NOW()
+                        WHERE id = $4
+                        "#
+                    )
+                    .bind(DeliveryStatus::Delivered)
+                    .bind(attempt)
+                    .bind(response.status().as_u16() as i32)
+                    .bind(delivery_id)
+                    .execute(&self.db)
+                    .await?;
+
+                    return Ok(());
+                }
+                Ok(response) => (Some(response.status().as_u16() as i32), format!("Non-success status {}", response.status())),
+                Err(e) => (None, e.to_string()),
+            };
+
+            let status = if attempt == MAX_DELIVERY_ATTEMPTS {
+                DeliveryStatus::Failed
+            } else {
+                DeliveryStatus::Pending
+            };
+
+            sqlx::query(
+                r#"
+                UPDATE webhook_deliveries
+                SET status = $1, attempts = $2, last_status_code = $3, last_error = $4
+                WHERE id = $5
+                "#
+            )
+            .bind(status)
+            .bind(attempt)
+            .bind(status_code)
+            .bind(&error)
+            .bind(delivery_id)
+            .execute(&self.db)
+            .await?;
+
+            if attempt < MAX_DELIVERY_ATTEMPTS {
+                tracing::debug!(
+                    "Webhook delivery {} attempt {} failed: {}. Retrying in {}s",
+                    delivery_id,
+                    attempt,
+                    error,
+                    delay.as_secs()
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            } else {
+                tracing::warn!("Webhook delivery {} failed after {} attempts: {}", delivery_id, attempt, error);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Deduplicated event names, rejecting unknown ones
+fn checked_events(events: Vec<String>) -> Result<Vec<String>, AppError> {
+    if events.is_empty() {
+        return Err(AppError::BadRequest("Subscribe to at least one event".to_string()));
+    }
+    let mut checked = Vec::with_capacity(events.len());
+    for event in events {
+        if !WebhookEvent::NAMES.contains(&event.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Unknown webhook event {}; expected one of {}",
+                event,
+                WebhookEvent::NAMES.join(", ")
+            )));
+        }
+        if !checked.contains(&event) {
+            checked.push(event);
+        }
+    }
+    Ok(checked)
+}
+
+/// Only https URLs whose host resolves to public addresses can receive webhooks.
+/// Returns the host and the addresses it resolved to.
+async fn resolve_webhook_url(url: &str) -> Result<(String, Vec<SocketAddr>), AppError> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|_| AppError::BadRequest("Webhook URL is not a valid URL".to_string()))?;
+    if parsed.scheme() != "https" {
+        return Err(AppError::BadRequest("Webhook URL must use https".to_string()));
+    }
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| AppError::BadRequest("Webhook URL has no host".to_string()))?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let port = parsed.port_or_known_default().unwrap_or(443);
+
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|_| AppError::BadRequest(format!("Webhook host {} could not be resolved", host)))?
+        .collect();
+    if addrs.is_empty() || addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+        return Err(AppError::BadRequest("Webhook URL must resolve to a public address".to_string()));
+    }
+    Ok((host.to_string(), addrs))
+}
+
+/// Client pinned to already-checked addresses, so a rebinding DNS answer can't redirect the
+/// request; redirects are not followed for the same reason
+fn delivery_client(host: &str, addrs: &[SocketAddr]) -> Result<reqwest::Client, AppError> {
+    reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve_to_addrs(host, addrs)
+        .build()
+        .map_err(|e| AppError::InternalServerError(format!("Failed to build webhook HTTP client: {}", e)))
+}
+
+/// False for loopback, private, link-local, unspecified and other non-routable addresses
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => !(ip.is_loopback()
+            || ip.is_private()
+            || ip.is_link_local()
+            || ip.is_unspecified()
+            || ip.is_broadcast()
+            // Shared address space (100.64.0.0/10)
+            || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64)),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_ip(IpAddr::V4(mapped)),
+            None => !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local()),
+        },
+    }
+}
+
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Hex-encoded HMAC-SHA256 of the request body, sent as `X-Signature: sha256=<hex>`
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_signature_is_stable_and_keyed() {
+        let body = br#"{"event":"proof.verified"}"#;
+
+        let signature = sign_payload("secret", body);
+        assert_eq!(signature.len(), 64);
+        assert_eq!(signature, sign_payload("secret", body));
+        assert_ne!(signature, sign_payload("other-secret", body));
+    }
+
+    #[test]
+    fn test_only_known_events_can_be_subscribed() {
+        let events = checked_events(vec!["record.signed".into(), "record.signed".into(), "proof.verified".into()]).unwrap();
+        assert_eq!(events, vec!["record.signed", "proof.verified"]);
+
+        assert!(matches!(checked_events(vec!["record.deleted".into()]), Err(AppError::BadRequest(_))));
+        assert!(matches!(checked_events(Vec::new()), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_non_public_addresses_are_refused() {
+        for ip in ["127.0.0.1", "10.0.0.1", "172.16.5.4", "192.168.1.1", "169.254.169.254", "0.0.0.0", "100.64.0.1", "::1", "::", "fe80::1", "fd00::1", "::ffff:127.0.0.1"] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{} should not be public", ip);
+        }
+        for ip in ["203.0.113.10", "8.8.8.8", "2001:4860:4860::8888"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{} should be public", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhook_urls_must_be_https_and_public() {
+        assert!(resolve_webhook_url("https://203.0.113.10/hooks").await.is_ok());
+        assert!(resolve_webhook_url("https://[2001:4860:4860::8888]:8443/hooks").await.is_ok());
+
+        for url in [
+            "http://203.0.113.10/hooks",
+            "ftp://203.0.113.10/hooks",
+            "not a url",
+            "https://127.0.0.1/hooks",
+            "https://169.254.169.254/latest/meta-data",
+            "https://10.0.0.1/hooks",
+            "https://[::1]/hooks",
+            "https://localhost/hooks",
+        ] {
+            assert!(matches!(resolve_webhook_url(url).await, Err(AppError::BadRequest(_))), "{} should be refused", url);
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_record_events_reach_owner_and_org_admins(db: PgPool) {
+        let service = WebhookService::new(db.clone());
+
+        let other_org: Uuid = sqlx::query_scalar("INSERT INTO organizations (name) VALUES ('Other') RETURNING id")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let mut user_ids = Vec::new();
+        for (email, role, org_id) in [
+            ("owner@example.com", "patient", crate::models::DEFAULT_ORG_ID),
+            ("admin@example.com", "admin", crate::models::DEFAULT_ORG_ID),
+            ("outsider@example.com", "admin", other_org),
+            ("patient@example.com", "patient", crate::models::DEFAULT_ORG_ID),
+        ] {
+            let id: Uuid = sqlx::query_scalar(
+                "INSERT INTO users (email, password_hash, full_name, role, org_id) VALUES ($1, 'x', 'User', $2, $3) RETURNING id"
+            )
+            .bind(email)
+            .bind(role)
+            .bind(org_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+            user_ids.push(id);
+        }
+
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key) VALUES ('Clinic', 'clinic', '02') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let record_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO health_records (user_id, authority_id, record_type, patient_identifier, details, issue_date, signature_r, signature_s, message_hash)
+            VALUES ($1, $2, 'vaccination', 'P-1', '{}', CURRENT_DATE, '\x01', '\x01', '\x01')
+            RETURNING id
+            "#
+        )
+        .bind(user_ids[0])
+        .bind(authority_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let mut subscribed = Vec::new();
+        for user_id in &user_ids {
+            let webhook = service
+                .create_webhook(*user_id, CreateWebhookRequest {
+                    url: "https://203.0.113.10/hooks".to_string(),
+                    events: vec!["record.signed".to_string()],
+                })
+                .await
+                .unwrap();
+            subscribed.push(webhook.id);
+        }
+        // The owner's default subscription only covers proof verifications
+        let unsubscribed = service
+            .create_webhook(user_ids[0], CreateWebhookRequest { url: "https://203.0.113.20/hooks".to_string(), events: Vec::new() })
+            .await
+            .unwrap();
+        assert_eq!(unsubscribed.events, vec!["proof.verified"]);
+
+        let mut recipients: Vec<Uuid> = service
+            .record_event_subscribers(record_id, "record.signed")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|webhook| webhook.id)
+            .collect();
+        recipients.sort();
+        let mut expected = vec![subscribed[0], subscribed[1]];
+        expected.sort();
+        assert_eq!(recipients, expected);
+
+        assert!(service.record_event_subscribers(record_id, "record.revoked").await.unwrap().is_empty());
+    }
+}
@@ -1,33 +1,257 @@
-use crate::models::{ZkProof, ProofType, GenerateProofRequest, ProofResponse, VerifyProofRequest, VerificationResponse, VerificationDetails, RevocationStatus, HealthRecord};
-use crate::errors::AppError;
-use crate::services::crypto::CryptoService;
+use crate::models::{GoogleWalletLink, ProofJob, ProofJobStage, ProofAlert, LiveProofStatus, ProofStatusResponse, RevocationListPayload, RevokedProof, SignedRevocationList, SigningCurve, Cursor, Paginated, ProofSort, ProofStatus, ZkProofWithStatus, ProofVerification, VerificationEvent, VerificationExportQuery, VerificationStats, VerificationStatsBucket, ZkProof, ProofType, GenerateProofRequest, ProofResponse, VerifyProofRequest, VerificationResponse, VerificationDetails, RevocationStatus, HealthRecord, HealthRecordType, ProofDelegation, ProofDelegationResponse};
+use crate::errors::{AppError, ErrorDetails};
+use axum::response::IntoResponse;
+use crate::services::crypto::{CryptoService, SignatureScheme};
+use crate::services::HealthRecordService;
+use crate::services::webhook::WebhookService;
+use crate::services::predicate::Predicate;
+use crate::services::message_format::{self, SignableFields};
+use crate::services::timestamp::{self, TimestampClient};
+use crate::services::ipfs::IpfsService;
+use crate::services::geoip::GeoIpService;
+use crate::services::wallet::{ApplePassSigner, GoogleWalletSigner};
+use crate::services::pdf::HealthPassPdf;
+use crate::services::blockchain::{self, Blockchain, BlockchainProofSubmission};
+use crate::services::proof_backend::{NargoBackend, NoirCircuit, ProofBackend, PublicInputs};
+use crate::services::store::Store;
 use anyhow::Result;
-use chrono::{DateTime, Utc, Duration};
+use futures::{Stream, TryStreamExt};
+use tokio::sync::broadcast::error::RecvError;
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc, Duration, SubsecRound};
 use sqlx::PgPool;
 use uuid::Uuid;
-use std::process::Command;
-use std::fs;
-use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::broadcast;
+use std::collections::HashMap;
 use base64::{Engine as _, engine::general_purpose};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+/// Size of the CSV chunks the verification export is streamed in
+pub(crate) const EXPORT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Verifications buffered for each live feed subscriber
+const VERIFICATION_FEED_CAPACITY: usize = 256;
+
+/// Proof job updates buffered for each subscriber
+const PROOF_JOB_FEED_CAPACITY: usize = 64;
+
+/// Generation budget of background proofs unless configured otherwise
+const DEFAULT_GENERATION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
+const INTERRUPTED_PROOF_JOB: &str = "Proof generation was interrupted; start a new job";
+
+/// Marks delegation tokens so they are recognizable in logs and secret scanners
+const DELEGATION_TOKEN_PREFIX: &str = "dlg_";
+
+const P256_NOT_PROVABLE: &str =
+    "Record is signed with a P-256 authority key; proofs require a secp256k1 signature";
+
+/// When a proof counts as verified from suspiciously many places
+#[derive(Debug, Clone, Default)]
+pub struct AnomalyPolicy {
+    pub window_secs: u64,
+    /// Distinct IPs or GeoIP regions tolerated within the window; 0 disables detection
+    pub max_distinct_locations: u32,
+    /// Suspend flagged proofs until an admin resolves the alert
+    pub auto_suspend: bool,
+}
+
+/// Expiry and usage limit of proofs whose request leaves them out
+#[derive(Debug, Clone, Default)]
+pub struct ProofDefaults {
+    /// Hours until such proofs expire; 0 leaves them without an expiry
+    pub expiration_hours: u32,
+    /// Usage limit of such proofs, and the most any request may ask for
+    pub max_usage: Option<i32>,
+}
+
+impl ProofDefaults {
+    /// Expiry of a proof generated at `now`, explicit `expires_in_hours` taking precedence
+    fn expires_at(&self, expires_in_hours: Option<u32>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let hours = expires_in_hours.or((self.expiration_hours > 0).then_some(self.expiration_hours))?;
+        Some(now + Duration::hours(hours as i64))
+    }
+
+    /// Usage limit to store for a requested `max_usage`, capped at the configured maximum
+    fn max_usage(&self, max_usage: Option<i32>) -> Result<Option<i32>, AppError> {
+        match (max_usage, self.max_usage) {
+            (Some(requested), Some(ceiling)) if requested > ceiling => Err(AppError::BadRequest(format!(
+                "max_usage must not exceed {}",
+                ceiling
+            ))),
+            (requested, ceiling) => Ok(requested.or(ceiling)),
+        }
+    }
+}
 
 pub struct ZkProofService {
     db: PgPool,
     crypto_service: CryptoService,
-    noir_circuit_path: String,
+    proof_backend: Arc<dyn ProofBackend>,
+    webhook_service: WebhookService,
+    timestamp_client: Option<TimestampClient>,
+    blockchain: Option<Arc<dyn Blockchain>>,
+    ipfs: Option<IpfsService>,
+    crl_signing_key: Option<secp256k1::SecretKey>,
+    geoip: Option<GeoIpService>,
+    anomaly_policy: AnomalyPolicy,
+    proof_defaults: ProofDefaults,
+    /// Signer of Apple Wallet passes and the public URL their QR codes point at
+    apple_wallet: Option<(ApplePassSigner, String)>,
+    /// Signer of Google Wallet save links and the public URL their QR codes point at
+    google_wallet: Option<(GoogleWalletSigner, String)>,
+    /// Public URL the QR codes of printable passes point at
+    public_base_url: Option<String>,
+    verification_feed: broadcast::Sender<VerificationEvent>,
+    proof_jobs: broadcast::Sender<ProofJob>,
+    /// Longest a background proof generation may run
+    generation_timeout: std::time::Duration,
+    /// Where the first response to a verification request is kept, and for
+    /// how long identical requests replay it instead of using the proof again
+    verification_dedup: Option<(Arc<dyn Store>, std::time::Duration)>,
 }
 
 impl ZkProofService {
-    pub fn new(db: PgPool, crypto_service: CryptoService, noir_circuit_path: String) -> Self {
+    pub fn new(db: PgPool, crypto_service: CryptoService, noir_circuit_path: String, webhook_service: WebhookService) -> Self {
         Self {
             db,
             crypto_service,
-            noir_circuit_path,
+            proof_backend: Arc::new(NargoBackend::new(noir_circuit_path)),
+            webhook_service,
+            timestamp_client: None,
+            blockchain: None,
+            ipfs: None,
+            crl_signing_key: None,
+            geoip: None,
+            anomaly_policy: AnomalyPolicy::default(),
+            proof_defaults: ProofDefaults::default(),
+            apple_wallet: None,
+            google_wallet: None,
+            public_base_url: None,
+            verification_feed: broadcast::channel(VERIFICATION_FEED_CAPACITY).0,
+            proof_jobs: broadcast::channel(PROOF_JOB_FEED_CAPACITY).0,
+            generation_timeout: DEFAULT_GENERATION_TIMEOUT,
+            verification_dedup: None,
         }
     }
 
+    /// Verifications as they are logged. A subscriber that falls more than
+    /// `VERIFICATION_FEED_CAPACITY` events behind misses the oldest ones.
+    pub fn subscribe_verifications(&self) -> broadcast::Receiver<VerificationEvent> {
+        self.verification_feed.subscribe()
+    }
+
+    /// Generate and verify proofs with `backend` instead of `nargo`
+    pub fn with_proof_backend(mut self, backend: Arc<dyn ProofBackend>) -> Self {
+        self.proof_backend = backend;
+        self
+    }
+
+    /// Whether proofs need the `nargo` and `bb` binaries
+    pub fn uses_noir_toolchain(&self) -> bool {
+        self.proof_backend.uses_toolchain()
+    }
+
+    /// Fail background proof generations that run longer than `timeout`
+    pub fn with_generation_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.generation_timeout = timeout;
+        self
+    }
+
+    /// Timestamp every new proof with an RFC 3161 timestamp authority
+    pub fn with_timestamp_client(mut self, client: TimestampClient) -> Self {
+        self.timestamp_client = Some(client);
+        self
+    }
+
+    /// Submit every new proof to the chain
+    pub fn with_blockchain(mut self, blockchain: Arc<dyn Blockchain>) -> Self {
+        self.blockchain = Some(blockchain);
+        self
+    }
+
+    /// Pin proof data on IPFS when anchoring, so only its CID goes on-chain
+    pub fn with_ipfs(mut self, ipfs: IpfsService) -> Self {
+        self.ipfs = Some(ipfs);
+        self
+    }
+
+    /// Publish a proof revocation list signed with this key
+    pub fn with_crl_signing_key(mut self, key: secp256k1::SecretKey) -> Self {
+        self.crl_signing_key = Some(key);
+        self
+    }
+
+    /// Record the country and region of each verification's IP address
+    pub fn with_geoip(mut self, geoip: GeoIpService) -> Self {
+        self.geoip = Some(geoip);
+        self
+    }
+
+    /// Flag proofs verified from too many places in a short window
+    pub fn with_anomaly_policy(mut self, policy: AnomalyPolicy) -> Self {
+        self.anomaly_policy = policy;
+        self
+    }
+
+    /// Count identical verification requests of a proof by the same verifier
+    /// within `window` once, answering retries with the first response
+    pub fn with_verification_dedup(mut self, store: Arc<dyn Store>, window: std::time::Duration) -> Self {
+        self.verification_dedup = Some((store, window));
+        self
+    }
+
+    /// Expire and limit proofs whose request doesn't say otherwise
+    pub fn with_proof_defaults(mut self, defaults: ProofDefaults) -> Self {
+        self.proof_defaults = defaults;
+        self
+    }
+
+    /// Offer proofs as Apple Wallet passes whose QR codes link to the proof's
+    /// status under `public_base_url`
+    pub fn with_apple_wallet(mut self, signer: ApplePassSigner, public_base_url: String) -> Self {
+        self.apple_wallet = Some((signer, public_base_url.trim_end_matches('/').to_string()));
+        self
+    }
+
+    /// Offer proofs as Google Wallet passes, with QR codes like Apple Wallet's
+    pub fn with_google_wallet(mut self, signer: GoogleWalletSigner, public_base_url: String) -> Self {
+        self.google_wallet = Some((signer, public_base_url.trim_end_matches('/').to_string()));
+        self
+    }
+
+    /// Offer proofs as printable PDF passes, with QR codes like the wallet passes'
+    pub fn with_public_base_url(mut self, public_base_url: String) -> Self {
+        self.public_base_url = Some(public_base_url.trim_end_matches('/').to_string());
+        self
+    }
+
     pub async fn generate_proof(&self, request: GenerateProofRequest, user_id: Uuid) -> Result<ProofResponse, AppError> {
+        self.generate_proof_for_job(request, user_id, None).await
+    }
+
+    /// Generate a proof, recording its stages on `job_id` when run in the background
+    async fn generate_proof_for_job(
+        &self,
+        request: GenerateProofRequest,
+        user_id: Uuid,
+        job_id: Option<Uuid>,
+    ) -> Result<ProofResponse, AppError> {
+        // Checked before the expensive proof generation
+        let max_usage = self.proof_defaults.max_usage(request.max_usage)?;
+
+        // Proofs over several records take the aggregation path
+        let record_ids = request.record_ids();
+        if record_ids.len() > 1 {
+            return self.generate_aggregate_proof(request, record_ids, max_usage, user_id, job_id).await;
+        }
+
         // Get health record and verify ownership
-        let health_record = sqlx::query_as::<_, HealthRecord>(
+        let mut health_record = sqlx::query_as::<_, HealthRecord>(
             "SELECT * FROM health_records WHERE id = $1 AND user_id = $2 AND is_revoked = FALSE"
         )
         .bind(request.health_record_id)
@@ -35,40 +259,63 @@ impl ZkProofService {
         .fetch_optional(&self.db)
         .await?
         .ok_or_else(|| AppError::NotFound("Health record not found or access denied".to_string()))?;
+        // Some circuits rebuild the signed message from the cleartext fields
+        self.crypto_service.decrypt_record(&mut health_record)?;
 
         // Get health authority public key for verification
-        let authority = sqlx::query!(
-            "SELECT public_key FROM health_authorities WHERE id = $1 AND is_active = TRUE",
-            health_record.authority_id
-        )
-        .fetch_optional(&self.db)
-        .await?
-        .ok_or_else(|| AppError::NotFound("Health authority not found or inactive".to_string()))?;
+        let authority = self
+            .crypto_service
+            .authorities()
+            .get(&self.db, health_record.authority_id)
+            .await?
+            .filter(|authority| authority.is_active)
+            .ok_or_else(|| AppError::NotFound("Health authority not found or inactive".to_string()))?;
 
-        // Generate ZK proof using Noir circuit
-        let proof_data = self.generate_noir_proof(&health_record, &authority.public_key).await?;
+        // The circuits verify secp256k1 signatures only
+        if authority.curve == SigningCurve::P256 {
+            return Err(AppError::BadRequest(P256_NOT_PROVABLE.to_string()));
+        }
+
+        // Make sure the requested assertion can be proven for this record
+        let proof_type = request.proof_type.clone().unwrap_or_default();
+        self.ensure_proof_type_applicable(&proof_type, &health_record)?;
+        self.ensure_fully_co_signed(&health_record).await?;
+
+        // Generate ZK proof using the Noir circuit for the requested assertion
+        let generation_started = Instant::now();
+        let generated_at = Utc::now();
+        let proof_data = self
+            .generate_noir_proof(&proof_type, &health_record, &authority.name, &authority.public_key, generated_at, job_id)
+            .await;
+        metrics::histogram!(
+            "zk_proof_generation_duration_seconds",
+            "outcome" => if proof_data.is_ok() { "success" } else { "failure" }
+        )
+        .record(generation_started.elapsed().as_secs_f64());
+        let proof_data = proof_data?;
         
         // Calculate expiration
-        let expires_at = request.expires_in_hours.map(|hours| {
-            Utc::now() + Duration::hours(hours as i64)
-        });
+        let expires_at = self.proof_defaults.expires_at(request.expires_in_hours, Utc::now());
 
-        // Store proof in database
+        // Store proof in database; `generated_at` is the day a NotExpiredProof was checked against
         let zk_proof = sqlx::query_as::<_, ZkProof>(
             r#"
-            INSERT INTO zk_proofs (health_record_id, proof_data, verification_key, proof_type, expires_at, max_usage)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO zk_proofs (health_record_id, proof_data, verification_key, proof_type, expires_at, max_usage, generated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             RETURNING *
             "#
         )
         .bind(request.health_record_id)
         .bind(&proof_data.proof)
         .bind(&proof_data.verification_key)
-        .bind(ProofType::EcdsaSignatureVerification)
+        .bind(&proof_type)
         .bind(expires_at)
-        .bind(request.max_usage)
+        .bind(max_usage)
+        .bind(generated_at)
         .fetch_one(&self.db)
         .await?;
+        let zk_proof = self.timestamp_proof(zk_proof).await?;
+        let zk_proof = self.anchor_proof(zk_proof, &health_record, &authority.public_key).await?;
 
         Ok(ProofResponse {
             id: zk_proof.id,
@@ -80,36 +327,252 @@ impl ZkProofService {
             usage_count: zk_proof.usage_count,
             max_usage: zk_proof.max_usage,
             health_record_type: format!("{:?}", health_record.record_type),
+            status: ProofStatus::Active,
+            timestamp_token: zk_proof.timestamp_token.map(|token| general_purpose::STANDARD.encode(token)),
+            timestamped_at: zk_proof.timestamped_at,
+            anchor_tx_hash: zk_proof.anchor_tx_hash,
+            proof_cid: zk_proof.proof_cid,
         })
     }
 
-    pub async fn verify_proof(&self, request: VerifyProofRequest, verifier_id: Option<Uuid>, ip_address: Option<std::net::IpAddr>, user_agent: Option<String>) -> Result<VerificationResponse, AppError> {
-        // Decode proof data
-        let proof_data = general_purpose::STANDARD.decode(&request.proof_data)
-            .map_err(|_| AppError::BadRequest("Invalid proof data encoding".to_string()))?;
-        
-        let verification_key = general_purpose::STANDARD.decode(&request.verification_key)
-            .map_err(|_| AppError::BadRequest("Invalid verification key encoding".to_string()))?;
+    /// Start generating a proof in the background and return the queued job.
+    /// Its progress is followed with [`Self::proof_job_updates`].
+    pub async fn start_proof_job(
+        self: &Arc<Self>,
+        request: GenerateProofRequest,
+        user_id: Uuid,
+    ) -> Result<ProofJob, AppError> {
+        let job = sqlx::query_as::<_, ProofJob>("INSERT INTO proof_jobs (user_id) VALUES ($1) RETURNING *")
+            .bind(user_id)
+            .fetch_one(&self.db)
+            .await?;
 
-        // Find existing proof in database
-        let zk_proof = sqlx::query_as::<_, ZkProof>(
-            "SELECT * FROM zk_proofs WHERE proof_data = $1 AND verification_key = $2"
+        let service = Arc::clone(self);
+        let job_id = job.id;
+        tokio::spawn(async move {
+            let result = tokio::time::timeout(
+                service.generation_timeout,
+                service.generate_proof_for_job(request, user_id, Some(job_id)),
+            )
+            .await
+            .unwrap_or(Err(AppError::RequestTimeout));
+            service.finish_proof_job(job_id, result).await;
+        });
+
+        Ok(job)
+    }
+
+    /// Record that `job_id` reached `stage` and tell its subscribers; a no-op
+    /// for proofs generated in the foreground
+    async fn report_job_stage(&self, job_id: Option<Uuid>, stage: ProofJobStage) {
+        let Some(job_id) = job_id else { return };
+        let job = sqlx::query_as::<_, ProofJob>(
+            "UPDATE proof_jobs SET stage = $2, updated_at = NOW() WHERE id = $1 RETURNING *"
         )
-        .bind(&proof_data)
-        .bind(&verification_key)
-        .fetch_optional(&self.db)
+        .bind(job_id)
+        .bind(stage)
+        .fetch_one(&self.db)
+        .await;
+
+        match job {
+            Ok(job) => {
+                let _ = self.proof_jobs.send(job);
+            }
+            // Progress is informational; generation carries on
+            Err(e) => tracing::warn!(error = %e, %job_id, "Failed to record proof job stage"),
+        }
+    }
+
+    async fn finish_proof_job(&self, job_id: Uuid, result: Result<ProofResponse, AppError>) {
+        let (stage, result, error) = match result {
+            Ok(proof) => (ProofJobStage::Done, serde_json::to_value(proof).ok(), None),
+            Err(e) => (ProofJobStage::Failed, None, Some(error_details(e))),
+        };
+        let (error_code, error_message) = error.unzip();
+
+        let job = sqlx::query_as::<_, ProofJob>(
+            r#"
+            UPDATE proof_jobs
+            SET stage = $2, result = $3, error_code = $4, error_message = $5, updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#
+        )
+        .bind(job_id)
+        .bind(stage)
+        .bind(result)
+        .bind(error_code)
+        .bind(error_message)
+        .fetch_one(&self.db)
+        .await;
+
+        match job {
+            Ok(job) => {
+                let _ = self.proof_jobs.send(job);
+            }
+            Err(e) => tracing::error!(error = %e, %job_id, "Failed to record proof job outcome"),
+        }
+    }
+
+    /// A proof job of `user_id`'s. A job that reported nothing for longer than
+    /// the generation timeout, e.g. because the server restarted, is failed first.
+    pub async fn get_proof_job(&self, job_id: Uuid, user_id: Uuid) -> Result<ProofJob, AppError> {
+        let (error_code, error_message) = error_details(AppError::ServiceUnavailable(INTERRUPTED_PROOF_JOB.to_string()));
+        sqlx::query(
+            r#"
+            UPDATE proof_jobs
+            SET stage = 'failed', error_code = $3, error_message = $4, updated_at = NOW()
+            WHERE id = $1 AND stage NOT IN ('done', 'failed') AND updated_at < NOW() - make_interval(secs => $2)
+            "#
+        )
+        .bind(job_id)
+        .bind(self.generation_timeout.as_secs_f64())
+        .bind(error_code)
+        .bind(error_message)
+        .execute(&self.db)
         .await?;
 
+        sqlx::query_as::<_, ProofJob>("SELECT * FROM proof_jobs WHERE id = $1 AND user_id = $2")
+            .bind(job_id)
+            .bind(user_id)
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Proof job not found".to_string()))
+    }
+
+    /// `job_id`'s current state, then each later stage up to the final one.
+    /// Stages up to `last_seen` are skipped, so a reconnecting client picks up
+    /// where it left off.
+    pub async fn proof_job_updates(
+        self: &Arc<Self>,
+        job_id: Uuid,
+        user_id: Uuid,
+        last_seen: Option<ProofJobStage>,
+    ) -> Result<impl Stream<Item = ProofJob> + Send + 'static, AppError> {
+        // Subscribed before reading the job so no stage in between is lost
+        let updates = self.proof_jobs.subscribe();
+        let job = self.get_proof_job(job_id, user_id).await?;
+        let service = Arc::clone(self);
+
+        Ok(futures::stream::unfold(
+            (updates, Some(job), last_seen),
+            move |(mut updates, mut next, mut last_seen)| {
+                let service = Arc::clone(&service);
+                async move {
+                    loop {
+                        if last_seen.map_or(false, |stage| stage.is_final()) {
+                            return None;
+                        }
+                        let job = match next.take() {
+                            Some(job) => job,
+                            None => match tokio::time::timeout(service.generation_timeout, updates.recv()).await {
+                                Ok(Ok(job)) if job.id == job_id => job,
+                                Ok(Ok(_)) => continue,
+                                // Missed updates, or none for a while: the row has the latest state
+                                Ok(Err(RecvError::Lagged(_))) | Err(_) => {
+                                    service.get_proof_job(job_id, user_id).await.ok()?
+                                }
+                                Ok(Err(RecvError::Closed)) => return None,
+                            },
+                        };
+                        if last_seen.map_or(true, |stage| job.stage > stage) {
+                            last_seen = Some(job.stage);
+                            return Some((job, (updates, None, last_seen)));
+                        }
+                    }
+                }
+            },
+        ))
+    }
+
+    pub async fn verify_proof(&self, request: VerifyProofRequest, verifier_id: Option<Uuid>, registered_verifier_id: Option<Uuid>, ip_address: Option<std::net::IpAddr>, user_agent: Option<String>) -> Result<VerificationResponse, AppError> {
+        let verification_started = Instant::now();
+
+        // Reject malformed predicates before touching the database
+        let predicate = request.predicate.as_ref().map(Predicate::parse).transpose()?;
+
+        // A delegate presents the owner's proof by token alone
+        let delegation = match &request.delegation_token {
+            Some(token) => Some(self.active_delegation(token).await?),
+            None => None,
+        };
+
+        let zk_proof = match &delegation {
+            Some(delegation) => {
+                sqlx::query_as::<_, ZkProof>("SELECT * FROM zk_proofs WHERE id = $1")
+                    .bind(delegation.proof_id)
+                    .fetch_optional(&self.db)
+                    .await?
+            }
+            None => {
+                // Decode proof data, or fetch it from IPFS for verifiers that only hold the CID
+                let proof_data = match (request.proof_data.is_empty(), &request.proof_cid) {
+                    (false, _) => general_purpose::STANDARD.decode(&request.proof_data)
+                        .map_err(|_| AppError::BadRequest("Invalid proof data encoding".to_string()))?,
+                    (true, Some(cid)) => {
+                        let ipfs = self.ipfs.as_ref().ok_or_else(|| {
+                            AppError::BadRequest("Verification by IPFS CID is not enabled on this server".to_string())
+                        })?;
+                        ipfs.fetch(cid).await?
+                    }
+                    (true, None) => return Err(AppError::BadRequest("Proof data or proof CID is required".to_string())),
+                };
+
+                if request.verification_key.is_empty() {
+                    return Err(AppError::BadRequest("Verification key is required".to_string()));
+                }
+                let verification_key = general_purpose::STANDARD.decode(&request.verification_key)
+                    .map_err(|_| AppError::BadRequest("Invalid verification key encoding".to_string()))?;
+
+                // Find existing proof in database through the indexed hash; the blob
+                // comparison only guards against hash collisions
+                sqlx::query_as::<_, ZkProof>(
+                    "SELECT * FROM zk_proofs WHERE proof_hash = $1 AND proof_data = $2 AND verification_key = $3"
+                )
+                .bind(Sha256::digest(&proof_data).to_vec())
+                .bind(&proof_data)
+                .bind(&verification_key)
+                .fetch_optional(&self.db)
+                .await?
+            }
+        };
+
+        // A retry of a request that already succeeded gets the first response
+        // back instead of using the proof again
+        let dedup = match (&self.verification_dedup, &zk_proof) {
+            (Some((store, window)), Some(proof)) => {
+                let key = verification_dedup_key(proof.id, verifier_id, registered_verifier_id, ip_address, &request);
+                if let Some(stored) = store.get(&key).await? {
+                    if let Ok(response) = serde_json::from_str::<VerificationResponse>(&stored) {
+                        return Ok(response);
+                    }
+                }
+                Some((store, *window, key))
+            }
+            _ => None,
+        };
+        let mut counted = false;
+
         let mut verification_details = VerificationDetails {
             health_record_type: None,
             issue_date: None,
             authority_name: None,
+            authority_type: None,
             is_expired: false,
             usage_exceeded: false,
             revocation_status: RevocationStatus::Unknown,
+            revoked_at: None,
+            revocation_reason: None,
+            constituent_record_types: Vec::new(),
+            co_signing_authorities: Vec::new(),
+            timestamped_at: None,
+            anchored_on_chain: None,
         };
 
         let mut is_valid = false;
+        let mut failure_reason = None;
+        // What the proof must commit to, given its record and authority
+        let mut public_inputs = None;
 
         if let Some(proof) = &zk_proof {
             // Check expiration
@@ -123,6 +586,12 @@ impl ZkProofService {
                 is_valid = true;
             }
 
+            // The proof must be verified against the circuit it was generated with
+            if proof.proof_type != request.proof_type {
+                is_valid = false;
+                failure_reason = Some("Proof was generated for a different proof type".to_string());
+            }
+
             // Check usage limits
             if let Some(max_usage) = proof.max_usage {
                 if proof.usage_count >= max_usage {
@@ -140,7 +609,10 @@ impl ZkProofService {
             .await
             {
                 verification_details.health_record_type = Some(format!("{:?}", health_record.record_type));
-                verification_details.issue_date = Some(health_record.issue_date.to_string());
+                // A not-expired proof exists precisely to avoid disclosing the record's dates
+                if proof.proof_type != ProofType::NotExpiredProof {
+                    verification_details.issue_date = Some(health_record.issue_date.to_string());
+                }
                 verification_details.revocation_status = if health_record.is_revoked {
                     RevocationStatus::Revoked
                 } else {
@@ -149,27 +621,168 @@ impl ZkProofService {
 
                 if health_record.is_revoked {
                     is_valid = false;
+                    verification_details.revoked_at = health_record.revoked_at;
+                    verification_details.revocation_reason = health_record.revocation_reason.clone();
+                    failure_reason = Some(revoked_failure_reason(health_record.revocation_reason.as_deref()));
                 }
 
+                // Expiry dates end at the holder's local midnight, unlike the
+                // proof's own `expires_at` instant
+                if health_record
+                    .expiry_date
+                    .map_or(false, |expiry_date| date_expired(expiry_date, Utc::now(), request.client_utc_offset_minutes))
+                {
+                    verification_details.is_expired = true;
+                    is_valid = false;
+                }
+
+                // The circuit covers the issuing authority's signature; co-signatures are checked here
+                let co_signatures = self.co_signatures(&health_record).await?;
+                if co_signatures.iter().any(|(_, signed)| !signed) {
+                    is_valid = false;
+                    failure_reason = Some("Health record is missing required co-signatures".to_string());
+                }
+                verification_details.co_signing_authorities =
+                    co_signatures.into_iter().map(|(name, _)| name).collect();
+
                 // Get authority name
-                if let Ok(Some(authority)) = sqlx::query!(
-                    "SELECT name FROM health_authorities WHERE id = $1",
-                    health_record.authority_id
-                )
-                .fetch_optional(&self.db)
-                .await
+                if let Ok(Some(authority)) = self
+                    .crypto_service
+                    .authorities()
+                    .get(&self.db, health_record.authority_id)
+                    .await
                 {
-                    verification_details.authority_name = Some(authority.name);
+                    verification_details.authority_name = Some(authority.name.clone());
+                    verification_details.authority_type = Some(format!("{:?}", authority.authority_type));
+
+                    if proof.proof_type != ProofType::AggregateSignatureVerification {
+                        public_inputs = self
+                            .public_inputs(&proof.proof_type, &health_record, &authority.public_key, proof.generated_at)
+                            .ok();
+                    }
+
+                    // The disclosed authority key is only meaningful while the authority is trusted
+                    if proof.proof_type == ProofType::AuthorityTrustedProof && !authority.is_active {
+                        is_valid = false;
+                    }
+                }
+            }
+
+            // Aggregate proofs stay valid only while every constituent record does
+            if proof.proof_type == ProofType::AggregateSignatureVerification {
+                let constituents = sqlx::query_as::<_, HealthRecord>(
+                    r#"
+                    SELECT hr.* FROM zk_proof_records zpr
+                    JOIN health_records hr ON zpr.health_record_id = hr.id
+                    WHERE zpr.proof_id = $1
+                    ORDER BY zpr.position
+                    "#
+                )
+                .bind(proof.id)
+                .fetch_all(&self.db)
+                .await?;
+
+                verification_details.constituent_record_types = constituents
+                    .iter()
+                    .map(|record| format!("{:?}", record.record_type))
+                    .collect();
+
+                let mut authority_keys = Vec::with_capacity(constituents.len());
+                for constituent in &constituents {
+                    if let Some(authority) = self.crypto_service.authorities().get(&self.db, constituent.authority_id).await? {
+                        authority_keys.push(authority.public_key.clone());
+                    }
+                }
+                if authority_keys.len() == constituents.len() {
+                    public_inputs = self.aggregate_public_inputs(&constituents, &authority_keys).ok();
+                }
+
+                if let Some(revoked) = constituents.iter().find(|record| record.is_revoked) {
+                    verification_details.revocation_status = RevocationStatus::Revoked;
+                    verification_details.revoked_at = revoked.revoked_at;
+                    verification_details.revocation_reason = revoked.revocation_reason.clone();
+                    failure_reason = Some(revoked_failure_reason(revoked.revocation_reason.as_deref()));
+                    is_valid = false;
+                }
+
+                for constituent in &constituents {
+                    if self.co_signatures(constituent).await?.iter().any(|(_, signed)| !signed) {
+                        is_valid = false;
+                        failure_reason = Some("Health record is missing required co-signatures".to_string());
+                    }
+                }
+            }
+
+            // A trusted timestamp, rather than `generated_at`, shows when the proof existed
+            if let Some(token) = &proof.timestamp_token {
+                verification_details.timestamped_at =
+                    timestamp::verify_token(token, &Sha256::digest(&proof.proof_data).into()).ok();
+            }
+            if let Some(existed_before) = request.existed_before {
+                if !verification_details.timestamped_at.map_or(false, |at| at <= existed_before) {
+                    is_valid = false;
+                    failure_reason = Some("Proof has no trusted timestamp at or before the requested time".to_string());
                 }
             }
 
-            // Verify the actual ZK proof using Noir
+            // An anchored proof revoked or unknown on-chain no longer verifies; an
+            // unreachable chain leaves the result to the checks above
+            if let (Some(chain), Some(_)) = (&self.blockchain, &proof.anchor_tx_hash) {
+                let context = request.verification_context.as_ref().map(|c| c.to_string()).unwrap_or_default();
+                match chain.verify_zk_proof(&chain_proof_hash(&proof.proof_data), &context).await {
+                    Ok(result) => {
+                        verification_details.anchored_on_chain = Some(result.is_valid);
+                        if !result.is_valid {
+                            is_valid = false;
+                            failure_reason = Some("Proof is not valid on-chain".to_string());
+                        }
+                    }
+                    Err(e) => tracing::warn!("On-chain check of proof {} failed: {}", proof.id, e),
+                }
+            }
+
+            if proof.suspended_at.is_some() {
+                is_valid = false;
+                failure_reason = Some("Proof is suspended after verifications from unusually many locations".to_string());
+            }
+
+            // Check the verifier's own requirements against the disclosed facts
+            if is_valid {
+                if let Some(predicate) = &predicate {
+                    if !predicate.evaluate(&verification_details) {
+                        is_valid = false;
+                        failure_reason = Some("Verifier predicate not satisfied".to_string());
+                    }
+                }
+            }
+
+            // Verify the actual ZK proof using Noir, over the public inputs its record and authority imply
             if is_valid {
-                is_valid = self.verify_noir_proof(&proof_data, &verification_key).await.unwrap_or(false);
+                is_valid = match &public_inputs {
+                    Some(public_inputs) => self
+                        .proof_backend
+                        .verify(&NoirCircuit::for_proof_type(&proof.proof_type), &proof.proof_data, public_inputs)
+                        .await
+                        .unwrap_or_else(|e| {
+                            tracing::warn!("Verifying proof {} failed: {}", proof.id, e);
+                            false
+                        }),
+                    None => false,
+                };
+                if !is_valid {
+                    failure_reason = Some("Proof does not verify against its health record and authority".to_string());
+                }
             }
 
-            // Update usage count if verification is successful
+            // Update usage count if verification is successful; of identical
+            // requests in flight at once, only the first counts
             if is_valid {
+                counted = match &dedup {
+                    Some((store, window, key)) => store.set_if_absent(key, "", *window).await?,
+                    None => true,
+                };
+            }
+            if counted {
                 sqlx::query(
                     "UPDATE zk_proofs SET usage_count = usage_count + 1 WHERE id = $1"
                 )
@@ -179,203 +792,2913 @@ impl ZkProofService {
             }
 
             // Log verification attempt
-            sqlx::query(
+            let location = match (&self.geoip, ip_address) {
+                (Some(geoip), Some(ip)) => geoip.lookup(ip).unwrap_or_default(),
+                _ => Default::default(),
+            };
+            let event = sqlx::query_as::<_, VerificationEvent>(
                 r#"
-                INSERT INTO proof_verifications (proof_id, verifier_id, verification_result, verification_context, ip_address, user_agent)
-                VALUES ($1, $2, $3, $4, $5, $6)
+                WITH pv AS (
+                    INSERT INTO proof_verifications (proof_id, verifier_id, registered_verifier_id, verification_result, verification_context, ip_address, user_agent, country_code, region, delegation_id)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                    RETURNING *
+                )
+                SELECT pv.*, v.name AS verifier_name, pd.delegate_email, hr.user_id AS owner_id, hr.org_id
+                FROM pv
+                JOIN zk_proofs zp ON pv.proof_id = zp.id
+                JOIN health_records hr ON zp.health_record_id = hr.id
+                LEFT JOIN verifiers v ON pv.registered_verifier_id = v.id
+                LEFT JOIN proof_delegations pd ON pv.delegation_id = pd.id
                 "#
             )
             .bind(proof.id)
             .bind(verifier_id)
+            .bind(registered_verifier_id)
             .bind(is_valid)
             .bind(&request.verification_context)
             .bind(ip_address)
             .bind(user_agent)
-            .execute(&self.db)
+            .bind(location.country_code)
+            .bind(location.region)
+            .bind(delegation.as_ref().map(|delegation| delegation.id))
+            .fetch_one(&self.db)
             .await?;
+
+            // Nobody listening is not an error
+            let _ = self.verification_feed.send(event);
+
+            // Detection problems must not turn into verification failures
+            if let Err(e) = self.check_verification_velocity(proof.id).await {
+                tracing::warn!("Anomaly check for proof {} failed: {}", proof.id, e);
+            }
+        }
+
+        let result_label = if is_valid { "success" } else { "failure" };
+        metrics::counter!("zk_proof_verifications_total", "result" => result_label).increment(1);
+        metrics::histogram!("zk_proof_verification_duration_seconds", "result" => result_label)
+            .record(verification_started.elapsed().as_secs_f64());
+
+        // Let the proof holder know their proof was checked
+        let verified_at = Utc::now();
+        if let Some(proof) = &zk_proof {
+            self.webhook_service.dispatch_proof_verified(proof.id, is_valid, verified_at);
         }
 
-        Ok(VerificationResponse {
+        let response = VerificationResponse {
             is_valid,
             proof_id: zk_proof.map(|p| p.id),
-            verified_at: Utc::now(),
+            verified_at,
             verification_details,
-        })
-    }
+            failure_reason,
+        };
 
-    async fn generate_noir_proof(&self, health_record: &HealthRecord, authority_public_key: &str) -> Result<NoirProofData, AppError> {
-        // Create temporary directory for proof generation
-        let temp_dir = format!("/tmp/zk_proof_{}", Uuid::new_v4());
-        fs::create_dir_all(&temp_dir)
-            .map_err(|_| AppError::InternalServerError("Failed to create temp directory".to_string()))?;
+        if let (true, Some((store, window, key))) = (counted, &dedup) {
+            let stored = serde_json::to_string(&response)
+                .map_err(|e| AppError::InternalServerError(format!("Failed to serialize verification response: {}", e)))?;
+            store.set(key, &stored, *window).await?;
+        }
 
-        // Create Prover.toml with health record data
-        let prover_toml = self.create_prover_toml(health_record, authority_public_key)?;
-        let prover_path = format!("{}/Prover.toml", temp_dir);
-        fs::write(&prover_path, prover_toml)
-            .map_err(|_| AppError::InternalServerError("Failed to write Prover.toml".to_string()))?;
-
-        // Copy Noir circuit to temp directory
-        let circuit_src = Path::new(&self.noir_circuit_path);
-        let circuit_dst = format!("{}/src", temp_dir);
-        fs::create_dir_all(&circuit_dst)
-            .map_err(|_| AppError::InternalServerError("Failed to create circuit directory".to_string()))?;
-
-        // Copy main.nr and Nargo.toml
-        fs::copy(circuit_src.join("src/main.nr"), format!("{}/main.nr", circuit_dst))
-            .map_err(|_| AppError::InternalServerError("Failed to copy circuit".to_string()))?;
-        
-        fs::copy(circuit_src.join("Nargo.toml"), format!("{}/Nargo.toml", temp_dir))
-            .map_err(|_| AppError::InternalServerError("Failed to copy Nargo.toml".to_string()))?;
+        Ok(response)
+    }
 
-        // Execute Noir proof generation
-        let output = Command::new("nargo")
-            .args(&["execute"])
-            .current_dir(&temp_dir)
-            .output()
-            .map_err(|_| AppError::InternalServerError("Failed to execute Noir circuit".to_string()))?;
+    /// Attach a trusted timestamp to a newly stored proof when a TSA is
+    /// configured. A TSA outage doesn't fail proof generation; the proof is
+    /// returned without a timestamp instead.
+    async fn timestamp_proof(&self, zk_proof: ZkProof) -> Result<ZkProof, AppError> {
+        let Some(client) = &self.timestamp_client else {
+            return Ok(zk_proof);
+        };
 
-        if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            return Err(AppError::InternalServerError(format!("Noir execution failed: {}", error_msg)));
-        }
+        let digest: [u8; 32] = Sha256::digest(&zk_proof.proof_data).into();
+        let timestamp = match client.timestamp(&digest).await {
+            Ok(timestamp) => timestamp,
+            Err(e) => {
+                tracing::warn!("Failed to timestamp proof {}: {}", zk_proof.id, e);
+                return Ok(zk_proof);
+            }
+        };
 
-        // Read generated witness/proof
-        let witness_path = format!("{}/target/health_passport_circuit.gz", temp_dir);
-        let proof_data = fs::read(&witness_path)
-            .map_err(|_| AppError::InternalServerError("Failed to read generated proof".to_string()))?;
+        let zk_proof = sqlx::query_as::<_, ZkProof>(
+            "UPDATE zk_proofs SET timestamp_token = $1, timestamped_at = $2 WHERE id = $3 RETURNING *"
+        )
+        .bind(&timestamp.token)
+        .bind(timestamp.timestamped_at)
+        .bind(zk_proof.id)
+        .fetch_one(&self.db)
+        .await?;
 
-        // Create verification key (for this demo, we'll use the authority's public key)
-        let verification_key = hex::decode(authority_public_key.trim_start_matches("0x"))
-            .map_err(|_| AppError::InternalServerError("Invalid authority public key".to_string()))?;
+        Ok(zk_proof)
+    }
 
-        // Cleanup temp directory
-        let _ = fs::remove_dir_all(&temp_dir);
+    /// Anchor a newly stored proof's hash on-chain when blockchain integration
+    /// is enabled, recording the transaction hash. The proof data itself is
+    /// pinned on IPFS, when configured, and referenced on-chain by CID. Like
+    /// timestamping, failures don't fail generation: a proof that couldn't be
+    /// pinned is anchored by hash alone, and one that couldn't be submitted is
+    /// returned unanchored.
+    async fn anchor_proof(&self, zk_proof: ZkProof, health_record: &HealthRecord, authority_public_key: &str) -> Result<ZkProof, AppError> {
+        let Some(chain) = &self.blockchain else {
+            return Ok(zk_proof);
+        };
 
-        Ok(NoirProofData {
-            proof: proof_data,
-            verification_key,
-        })
-    }
+        let authority_address = match blockchain::authority_address(authority_public_key) {
+            Ok(authority_address) => authority_address,
+            Err(e) => {
+                tracing::warn!("Failed to anchor proof {} on-chain: {}", zk_proof.id, e);
+                return Ok(zk_proof);
+            }
+        };
 
-    fn create_prover_toml(&self, health_record: &HealthRecord, _authority_public_key: &str) -> Result<String, AppError> {
-        // Format the signature components and message hash for Noir
-        let format_bytes = |bytes: &[u8]| -> String {
-            let hex_values: Vec<String> = bytes.iter().map(|b| format!("\"0x{:02x}\"", b)).collect();
-            format!("[{}]", hex_values.join(", "))
+        let proof_cid = match &self.ipfs {
+            Some(ipfs) => match ipfs.pin(&zk_proof.proof_data).await {
+                Ok(cid) => Some(cid),
+                Err(e) => {
+                    tracing::warn!("Failed to pin proof {} on IPFS: {}", zk_proof.id, e);
+                    None
+                }
+            },
+            None => None,
         };
 
-        // Extract public key coordinates from authority public key
-        // For this demo, we'll use the same test public key from the original system
-        let pubkey_x_hex = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
-        let pubkey_y_hex = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
-        
-        let pubkey_x_bytes = hex::decode(pubkey_x_hex)
-            .map_err(|_| AppError::InternalServerError("Invalid public key X coordinate".to_string()))?;
-        let pubkey_y_bytes = hex::decode(pubkey_y_hex)
-            .map_err(|_| AppError::InternalServerError("Invalid public key Y coordinate".to_string()))?;
-
-        let prover_toml = format!(
-            r#"msg_hash = {}
-pubkey_x = {}
-pubkey_y = {}
-signature_r = {}
-signature_s = {}
-"#,
-            format_bytes(&health_record.message_hash),
-            format_bytes(&pubkey_x_bytes),
-            format_bytes(&pubkey_y_bytes),
-            format_bytes(&health_record.signature_r),
-            format_bytes(&health_record.signature_s)
-        );
+        let submission = BlockchainProofSubmission {
+            proof_hash: chain_proof_hash(&zk_proof.proof_data),
+            health_record_hash: format!("0x{}", hex::encode(&health_record.message_hash)),
+            authority_address,
+            expires_at: zk_proof.expires_at.map_or(0, |expires_at| expires_at.timestamp() as u64),
+            proof_cid: proof_cid.clone().unwrap_or_default(),
+        };
+        let tx_hash = match chain.submit_zk_proof(submission).await {
+            Ok(tx_hash) => Some(tx_hash),
+            Err(e) => {
+                tracing::warn!("Failed to anchor proof {} on-chain: {}", zk_proof.id, e);
+                None
+            }
+        };
+        if tx_hash.is_none() && proof_cid.is_none() {
+            return Ok(zk_proof);
+        }
 
-        Ok(prover_toml)
-    }
+        let zk_proof = sqlx::query_as::<_, ZkProof>(
+            "UPDATE zk_proofs SET anchor_tx_hash = $1, proof_cid = $2 WHERE id = $3 RETURNING *"
+        )
+        .bind(&tx_hash)
+        .bind(&proof_cid)
+        .bind(zk_proof.id)
+        .fetch_one(&self.db)
+        .await?;
 
-    async fn verify_noir_proof(&self, _proof_data: &[u8], _verification_key: &[u8]) -> Result<bool, AppError> {
-        // For this demo, we'll assume the proof is valid if it was generated by our system
-        // In a production system, you would use a proper Noir verifier
-        Ok(true)
+        Ok(zk_proof)
     }
 
-    pub async fn get_user_proofs(&self, user_id: Uuid, page: u32, limit: u32) -> Result<Vec<ProofResponse>, AppError> {
-        let offset = (page.saturating_sub(1)) * limit;
-        
-        let proofs = sqlx::query_as::<_, ZkProof>(
+    /// Raise an alert, and suspend the proof if the policy says so, when it has
+    /// been verified from more distinct locations within the policy window than
+    /// allowed. A location is the GeoIP region when known, the IP otherwise.
+    /// Verifications before the last resolved alert don't count again.
+    async fn check_verification_velocity(&self, proof_id: Uuid) -> Result<(), AppError> {
+        let policy = &self.anomaly_policy;
+        if policy.max_distinct_locations == 0 {
+            return Ok(());
+        }
+
+        let distinct_locations = sqlx::query_scalar::<_, i64>(
             r#"
-            SELECT zp.* FROM zk_proofs zp
-            JOIN health_records hr ON zp.health_record_id = hr.id
-            WHERE hr.user_id = $1
-            ORDER BY zp.generated_at DESC
-            LIMIT $2 OFFSET $3
+            SELECT COUNT(DISTINCT COALESCE(country_code || ':' || COALESCE(region, ''), host(ip_address)))
+            FROM proof_verifications
+            WHERE proof_id = $1
+              AND verified_at >= GREATEST($2, (SELECT MAX(resolved_at) FROM proof_alerts WHERE proof_id = $1))
             "#
         )
-        .bind(user_id)
-        .bind(limit as i64)
-        .bind(offset as i64)
-        .fetch_all(&self.db)
+        .bind(proof_id)
+        .bind(Utc::now() - Duration::seconds(policy.window_secs as i64))
+        .fetch_one(&self.db)
         .await?;
 
-        let mut responses = Vec::new();
-        for proof in proofs {
-            // Get health record type
-            let health_record_type = sqlx::query!(
-                "SELECT record_type FROM health_records WHERE id = $1",
-                proof.health_record_id
-            )
-            .fetch_optional(&self.db)
-            .await?
-            .map(|r| format!("{:?}", r.record_type))
-            .unwrap_or_else(|| "Unknown".to_string());
-
-            responses.push(ProofResponse {
-                id: proof.id,
-                proof_data: general_purpose::STANDARD.encode(&proof.proof_data),
-                verification_key: general_purpose::STANDARD.encode(&proof.verification_key),
-                proof_type: proof.proof_type,
-                generated_at: proof.generated_at,
-                expires_at: proof.expires_at,
-                usage_count: proof.usage_count,
-                max_usage: proof.max_usage,
-                health_record_type,
-            });
+        if distinct_locations <= policy.max_distinct_locations as i64 {
+            return Ok(());
         }
 
-        Ok(responses)
-    }
-
-    pub async fn revoke_proof(&self, proof_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
-        // Verify the user owns the health record associated with this proof
-        let result = sqlx::query!(
+        // A proof has at most one open alert, so later verifications don't pile up more
+        let alert = sqlx::query_as::<_, ProofAlert>(
+            r#"
+            INSERT INTO proof_alerts (proof_id, distinct_locations, window_secs, suspended)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (proof_id) WHERE resolved_at IS NULL DO NOTHING
+            RETURNING *
+            "#
+        )
+        .bind(proof_id)
+        .bind(distinct_locations as i32)
+        .bind(policy.window_secs as i32)
+        .bind(policy.auto_suspend)
+        .fetch_optional(&self.db)
+        .await?;
+        let Some(alert) = alert else {
+            return Ok(());
+        };
+
+        if policy.auto_suspend {
+            sqlx::query("UPDATE zk_proofs SET suspended_at = NOW() WHERE id = $1")
+                .bind(proof_id)
+                .execute(&self.db)
+                .await?;
+        }
+
+        tracing::warn!(
+            target: "audit",
+            event = "proof_anomaly_detected",
+            proof_id = %proof_id,
+            alert_id = %alert.id,
+            distinct_locations,
+            suspended = policy.auto_suspend,
+            "Proof verified from unusually many locations"
+        );
+
+        Ok(())
+    }
+
+    /// Alerts on proofs of `scope_user_id`'s records, or on every proof in the
+    /// organization when `None`; open alerts first, newest first
+    pub async fn get_proof_alerts(&self, scope_user_id: Option<Uuid>, org_scope: Option<Uuid>) -> Result<Vec<ProofAlert>, AppError> {
+        let alerts = sqlx::query_as::<_, ProofAlert>(
+            r#"
+            SELECT pa.* FROM proof_alerts pa
+            JOIN zk_proofs zp ON pa.proof_id = zp.id
+            JOIN health_records hr ON zp.health_record_id = hr.id
+            WHERE ($1::uuid IS NULL OR hr.user_id = $1) AND ($2::uuid IS NULL OR hr.org_id = $2)
+            ORDER BY pa.resolved_at IS NOT NULL, pa.detected_at DESC
+            LIMIT 500
+            "#
+        )
+        .bind(scope_user_id)
+        .bind(org_scope)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(alerts)
+    }
+
+    /// Close an open alert and lift the suspension of its proof
+    pub async fn resolve_proof_alert(&self, alert_id: Uuid, admin_id: Uuid, org_scope: Option<Uuid>) -> Result<ProofAlert, AppError> {
+        let mut tx = self.db.begin().await?;
+
+        let alert = sqlx::query_as::<_, ProofAlert>(
+            r#"
+            UPDATE proof_alerts pa SET resolved_at = NOW(), resolved_by = $2
+            FROM zk_proofs zp JOIN health_records hr ON zp.health_record_id = hr.id
+            WHERE pa.id = $1 AND pa.proof_id = zp.id AND pa.resolved_at IS NULL
+              AND ($3::uuid IS NULL OR hr.org_id = $3)
+            RETURNING pa.*
+            "#
+        )
+        .bind(alert_id)
+        .bind(admin_id)
+        .bind(org_scope)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Open alert not found".to_string()))?;
+
+        sqlx::query("UPDATE zk_proofs SET suspended_at = NULL WHERE id = $1")
+            .bind(alert.proof_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            target: "audit",
+            event = "proof_alert_resolved",
+            alert_id = %alert_id,
+            proof_id = %alert.proof_id,
+            resolved_by = %admin_id,
+            "Proof anomaly alert resolved"
+        );
+
+        Ok(alert)
+    }
+
+    /// Co-signing authorities of a record in signing order, with whether each
+    /// has signed the record's current message hash
+    async fn co_signatures(&self, health_record: &HealthRecord) -> Result<Vec<(String, bool)>, AppError> {
+        let rows = sqlx::query_as::<_, (String, Option<Vec<u8>>)>(
+            r#"
+            SELECT ha.name, rs.message_hash
+            FROM record_signatures rs
+            JOIN health_authorities ha ON rs.authority_id = ha.id
+            WHERE rs.health_record_id = $1
+            ORDER BY rs.position
+            "#
+        )
+        .bind(health_record.id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(name, message_hash)| {
+                let signed = message_hash.as_deref() == Some(health_record.message_hash.as_slice());
+                (name, signed)
+            })
+            .collect())
+    }
+
+    /// Proofs are only generated for records carrying every required signature
+    async fn ensure_fully_co_signed(&self, health_record: &HealthRecord) -> Result<(), AppError> {
+        let missing = self.co_signatures(health_record).await?
+            .into_iter()
+            .filter(|(_, signed)| !signed)
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "Health record is missing required co-signatures from: {}",
+                missing.join(", ")
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Reject proof types whose assertion cannot hold for the given record
+    fn ensure_proof_type_applicable(&self, proof_type: &ProofType, health_record: &HealthRecord) -> Result<(), AppError> {
+        match proof_type {
+            ProofType::NotExpiredProof => {
+                let expiry_date = health_record.expiry_date.ok_or_else(|| {
+                    AppError::BadRequest("NotExpiredProof requires a health record with an expiry date".to_string())
+                })?;
+
+                if expiry_date < Utc::now().date_naive() {
+                    return Err(AppError::BadRequest("Health record has already expired".to_string()));
+                }
+
+                // The circuit range-checks the signed expiry, which only format 3 covers
+                if health_record.format_version < 3 {
+                    return Err(AppError::BadRequest(format!(
+                        "NotExpiredProof requires a record signed with message format 3, not {}",
+                        health_record.format_version
+                    )));
+                }
+
+                Ok(())
+            }
+            ProofType::AggregateSignatureVerification => Err(AppError::BadRequest(
+                "AggregateSignatureVerification requires at least two health records".to_string(),
+            )),
+            // The record-type circuit hashes the fixed 32-byte v1 message itself
+            ProofType::RecordTypeProof if health_record.format_version != 1 => Err(AppError::BadRequest(format!(
+                "RecordTypeProof requires a record signed with message format 1, not {}",
+                health_record.format_version
+            ))),
+            ProofType::EcdsaSignatureVerification
+            | ProofType::RecordTypeProof
+            | ProofType::AuthorityTrustedProof => Ok(()),
+        }
+    }
+
+    /// Produce one proof asserting every referenced record is validly signed and non-revoked
+    async fn generate_aggregate_proof(
+        &self,
+        request: GenerateProofRequest,
+        record_ids: Vec<Uuid>,
+        max_usage: Option<i32>,
+        user_id: Uuid,
+        job_id: Option<Uuid>,
+    ) -> Result<ProofResponse, AppError> {
+        if record_ids.len() > MAX_AGGREGATED_RECORDS {
+            return Err(AppError::BadRequest(format!(
+                "At most {} health records can be aggregated into one proof",
+                MAX_AGGREGATED_RECORDS
+            )));
+        }
+
+        let proof_type = request.proof_type.clone().unwrap_or(ProofType::AggregateSignatureVerification);
+        if proof_type != ProofType::AggregateSignatureVerification {
+            return Err(AppError::BadRequest(
+                "Only AggregateSignatureVerification proofs can cover multiple health records".to_string(),
+            ));
+        }
+
+        // Every record must exist, be owned by the caller and not be revoked
+        let mut health_records = sqlx::query_as::<_, HealthRecord>(
+            "SELECT * FROM health_records WHERE id = ANY($1) AND user_id = $2 AND is_revoked = FALSE"
+        )
+        .bind(&record_ids)
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        if health_records.len() != record_ids.len() {
+            return Err(AppError::NotFound(
+                "One or more health records not found, revoked or access denied".to_string(),
+            ));
+        }
+
+        // Keep the order the caller asked for
+        health_records.sort_by_key(|record| record_ids.iter().position(|id| *id == record.id));
+
+        for health_record in &health_records {
+            self.ensure_fully_co_signed(health_record).await?;
+        }
+
+        let mut authority_keys = Vec::with_capacity(health_records.len());
+        let mut schemes = Vec::with_capacity(health_records.len());
+        for health_record in &health_records {
+            let authority = self
+                .crypto_service
+                .authorities()
+                .get(&self.db, health_record.authority_id)
+                .await?
+                .filter(|authority| authority.is_active)
+                .ok_or_else(|| AppError::NotFound("Health authority not found or inactive".to_string()))?;
+
+            schemes.push(SignatureScheme::for_authority(&authority.curve, &authority.public_key)?);
+            authority_keys.push(authority.public_key.clone());
+        }
+
+        // The aggregate circuit verifies a single signature scheme
+        schemes.dedup();
+        match schemes.as_slice() {
+            [SignatureScheme::EcdsaSecp256k1] => {}
+            [SignatureScheme::EcdsaP256] => return Err(AppError::BadRequest(P256_NOT_PROVABLE.to_string())),
+            _ => {
+                return Err(AppError::BadRequest(
+                    "Cannot aggregate records signed with different signature schemes".to_string(),
+                ))
+            }
+        }
+
+        let generation_started = Instant::now();
+        let inputs = self
+            .create_aggregate_prover_toml(&health_records, &authority_keys)
+            .and_then(|prover_toml| {
+                self.aggregate_public_inputs(&health_records, &authority_keys)
+                    .map(|public_inputs| (prover_toml, public_inputs))
+            });
+        let proof = match inputs {
+            Ok((prover_toml, public_inputs)) => {
+                self.report_job_stage(job_id, ProofJobStage::WitnessGenerated).await;
+                self.report_job_stage(job_id, ProofJobStage::Proving).await;
+                self.proof_backend
+                    .prove(&NoirCircuit::for_proof_type(&proof_type), prover_toml, &public_inputs)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        metrics::histogram!(
+            "zk_proof_generation_duration_seconds",
+            "outcome" => if proof.is_ok() { "success" } else { "failure" }
+        )
+        .record(generation_started.elapsed().as_secs_f64());
+        let proof = proof?;
+
+        // For this demo the verification key is the concatenation of the signing authority keys
+        let mut verification_key = Vec::new();
+        for key in &authority_keys {
+            verification_key.extend(
+                hex::decode(key.trim_start_matches("0x"))
+                    .map_err(|_| AppError::InternalServerError("Invalid authority public key".to_string()))?,
+            );
+        }
+
+        let expires_at = self.proof_defaults.expires_at(request.expires_in_hours, Utc::now());
+
+        let mut tx = self.db.begin().await?;
+
+        let zk_proof = sqlx::query_as::<_, ZkProof>(
+            r#"
+            INSERT INTO zk_proofs (health_record_id, proof_data, verification_key, proof_type, expires_at, max_usage)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#
+        )
+        .bind(record_ids[0])
+        .bind(&proof)
+        .bind(&verification_key)
+        .bind(&proof_type)
+        .bind(expires_at)
+        .bind(max_usage)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for (position, record_id) in record_ids.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO zk_proof_records (proof_id, health_record_id, position) VALUES ($1, $2, $3)"
+            )
+            .bind(zk_proof.id)
+            .bind(record_id)
+            .bind(position as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        let zk_proof = self.timestamp_proof(zk_proof).await?;
+        let zk_proof = self.anchor_proof(zk_proof, &health_records[0], &authority_keys[0]).await?;
+
+        let record_types: Vec<String> = health_records
+            .iter()
+            .map(|record| format!("{:?}", record.record_type))
+            .collect();
+
+        Ok(ProofResponse {
+            id: zk_proof.id,
+            proof_data: general_purpose::STANDARD.encode(&zk_proof.proof_data),
+            verification_key: general_purpose::STANDARD.encode(&zk_proof.verification_key),
+            proof_type: zk_proof.proof_type,
+            generated_at: zk_proof.generated_at,
+            expires_at: zk_proof.expires_at,
+            usage_count: zk_proof.usage_count,
+            max_usage: zk_proof.max_usage,
+            health_record_type: record_types.join(", "),
+            status: ProofStatus::Active,
+            timestamp_token: zk_proof.timestamp_token.map(|token| general_purpose::STANDARD.encode(token)),
+            timestamped_at: zk_proof.timestamped_at,
+            anchor_tx_hash: zk_proof.anchor_tx_hash,
+            proof_cid: zk_proof.proof_cid,
+        })
+    }
+
+    fn create_aggregate_prover_toml(&self, health_records: &[HealthRecord], authority_keys: &[String]) -> Result<String, AppError> {
+        let format_bytes = |bytes: &[u8]| -> String {
+            let hex_values: Vec<String> = bytes.iter().map(|b| format!("\"0x{:02x}\"", b)).collect();
+            format!("[{}]", hex_values.join(", "))
+        };
+
+        let mut msg_hashes = Vec::new();
+        let mut pubkeys_x = Vec::new();
+        let mut pubkeys_y = Vec::new();
+        let mut signatures_r = Vec::new();
+        let mut signatures_s = Vec::new();
+
+        // Unused slots repeat the first record so every array has MAX_AGGREGATED_RECORDS entries
+        for i in 0..MAX_AGGREGATED_RECORDS {
+            let index = if i < health_records.len() { i } else { 0 };
+            let health_record = &health_records[index];
+
+            let public_key = self.crypto_service.parse_public_key(&authority_keys[index])?;
+            let (pubkey_x, pubkey_y) = self.crypto_service.get_public_key_coordinates(&public_key);
+
+            msg_hashes.push(format_bytes(&health_record.message_hash));
+            pubkeys_x.push(format_bytes(&pubkey_x));
+            pubkeys_y.push(format_bytes(&pubkey_y));
+            signatures_r.push(format_bytes(&health_record.signature_r));
+            signatures_s.push(format_bytes(&health_record.signature_s));
+        }
+
+        Ok(format!(
+            r#"msg_hashes = [{}]
+pubkeys_x = [{}]
+pubkeys_y = [{}]
+signatures_r = [{}]
+signatures_s = [{}]
+record_count = "{}"
+"#,
+            msg_hashes.join(", "),
+            pubkeys_x.join(", "),
+            pubkeys_y.join(", "),
+            signatures_r.join(", "),
+            signatures_s.join(", "),
+            health_records.len()
+        ))
+    }
+
+    /// Public inputs of an aggregate proof: the message hashes and authority key
+    /// coordinates of every slot, padded like the Prover.toml, then the record count
+    fn aggregate_public_inputs(&self, health_records: &[HealthRecord], authority_keys: &[String]) -> Result<PublicInputs, AppError> {
+        if health_records.is_empty() || authority_keys.len() != health_records.len() {
+            return Err(AppError::InternalServerError("Aggregate proof records and authority keys don't match".to_string()));
+        }
+
+        let slots: Vec<usize> = (0..MAX_AGGREGATED_RECORDS)
+            .map(|i| if i < health_records.len() { i } else { 0 })
+            .collect();
+        let mut coordinates = Vec::with_capacity(slots.len());
+        for &index in &slots {
+            let public_key = self.crypto_service.parse_public_key(&authority_keys[index])?;
+            coordinates.push(self.crypto_service.get_public_key_coordinates(&public_key));
+        }
+
+        let mut public_inputs = PublicInputs::default();
+        for &index in &slots {
+            public_inputs = public_inputs.bytes(&health_records[index].message_hash);
+        }
+        for (pubkey_x, _) in &coordinates {
+            public_inputs = public_inputs.bytes(pubkey_x);
+        }
+        for (_, pubkey_y) in &coordinates {
+            public_inputs = public_inputs.bytes(pubkey_y);
+        }
+        Ok(public_inputs.number(health_records.len() as u64))
+    }
+
+    async fn generate_noir_proof(
+        &self,
+        proof_type: &ProofType,
+        health_record: &HealthRecord,
+        authority_name: &str,
+        authority_public_key: &str,
+        generated_at: DateTime<Utc>,
+        job_id: Option<Uuid>,
+    ) -> Result<NoirProofData, AppError> {
+        // Create Prover.toml with health record data
+        let prover_toml = self.create_prover_toml(proof_type, health_record, authority_name, authority_public_key, generated_at)?;
+        let public_inputs = self.public_inputs(proof_type, health_record, authority_public_key, generated_at)?;
+        self.report_job_stage(job_id, ProofJobStage::WitnessGenerated).await;
+
+        self.report_job_stage(job_id, ProofJobStage::Proving).await;
+        let proof_data = self
+            .proof_backend
+            .prove(&NoirCircuit::for_proof_type(proof_type), prover_toml, &public_inputs)
+            .await?;
+
+        // Create verification key (for this demo, we'll use the authority's public key)
+        let verification_key = hex::decode(authority_public_key.trim_start_matches("0x"))
+            .map_err(|_| AppError::InternalServerError("Invalid authority public key".to_string()))?;
+
+        Ok(NoirProofData {
+            proof: proof_data,
+            verification_key,
+        })
+    }
+
+    fn create_prover_toml(
+        &self,
+        proof_type: &ProofType,
+        health_record: &HealthRecord,
+        authority_name: &str,
+        authority_public_key: &str,
+        generated_at: DateTime<Utc>,
+    ) -> Result<String, AppError> {
+        // Format the signature components and message hash for Noir
+        let format_bytes = |bytes: &[u8]| -> String {
+            let hex_values: Vec<String> = bytes.iter().map(|b| format!("\"0x{:02x}\"", b)).collect();
+            format!("[{}]", hex_values.join(", "))
+        };
+
+        // Extract public key coordinates from authority public key
+        let public_key = self.crypto_service.parse_public_key(authority_public_key)?;
+        let (pubkey_x_bytes, pubkey_y_bytes) = self.crypto_service.get_public_key_coordinates(&public_key);
+
+        let mut prover_toml = format!(
+            r#"pubkey_x = {}
+pubkey_y = {}
+signature_r = {}
+signature_s = {}
+"#,
+            format_bytes(&pubkey_x_bytes),
+            format_bytes(&pubkey_y_bytes),
+            format_bytes(&health_record.signature_r),
+            format_bytes(&health_record.signature_s)
+        );
+
+        // The signed message, rebuilt from the record's cleartext fields
+        let signed_message = || -> Result<String, AppError> {
+            let details_str = HealthRecordService::extract_details_for_signing(
+                &health_record.details,
+                &health_record.record_type,
+            )?;
+            let issue_date = health_record.issue_date.to_string();
+            Ok(message_format::formatter(health_record.format_version)?.format(&SignableFields {
+                record_type: &health_record.record_type,
+                patient_identifier: &health_record.patient_identifier,
+                details: &details_str,
+                issue_date: &issue_date,
+                issuer: authority_name,
+                expiry_date: health_record.expiry_date,
+            }))
+        };
+
+        match proof_type {
+            ProofType::EcdsaSignatureVerification | ProofType::AuthorityTrustedProof => {
+                prover_toml.push_str(&format!("msg_hash = {}\n", format_bytes(&health_record.message_hash)));
+            }
+            ProofType::NotExpiredProof => {
+                if health_record.expiry_date.is_none() {
+                    return Err(AppError::BadRequest(
+                        "NotExpiredProof requires a health record with an expiry date".to_string(),
+                    ));
+                }
+
+                // Format 3 signs the message digest followed by the expiry days; the
+                // circuit rebuilds that hash, so the expiry it range-checks is the one
+                // that was signed. Both stay private; only current_days is disclosed.
+                let signed = message_format::formatter(health_record.format_version)?.signable_bytes(&signed_message()?);
+                let (record_digest, expiry_days) = signed.split_at(32);
+                prover_toml.push_str(&format!("record_digest = {}\n", format_bytes(record_digest)));
+                prover_toml.push_str(&format!("expiry_days = {}\n", format_bytes(expiry_days)));
+                prover_toml.push_str(&format!(
+                    "current_days = \"{}\"\n",
+                    message_format::epoch_days(generated_at.date_naive())
+                ));
+            }
+            ProofType::RecordTypeProof => {
+                // The circuit hashes the message itself, so rebuild the exact signed preimage
+                let message_bytes = message_format::formatter(health_record.format_version)?.signable_bytes(&signed_message()?);
+
+                let (type_prefix, type_prefix_len) = record_type_prefix(&health_record.record_type);
+
+                prover_toml.push_str(&format!("message = {}\n", format_bytes(&message_bytes)));
+                prover_toml.push_str(&format!("msg_hash = {}\n", format_bytes(&health_record.message_hash)));
+                prover_toml.push_str(&format!("type_prefix = {}\n", format_bytes(&type_prefix)));
+                prover_toml.push_str(&format!("type_prefix_len = \"{}\"\n", type_prefix_len));
+            }
+            ProofType::AggregateSignatureVerification => {
+                return Err(AppError::BadRequest(
+                    "Aggregate proofs cover multiple health records".to_string(),
+                ));
+            }
+        }
+
+        Ok(prover_toml)
+    }
+
+    /// Public inputs of a single-record proof, in the order its circuit declares them
+    fn public_inputs(
+        &self,
+        proof_type: &ProofType,
+        health_record: &HealthRecord,
+        authority_public_key: &str,
+        generated_at: DateTime<Utc>,
+    ) -> Result<PublicInputs, AppError> {
+        let public_key = self.crypto_service.parse_public_key(authority_public_key)?;
+        let (pubkey_x, pubkey_y) = self.crypto_service.get_public_key_coordinates(&public_key);
+
+        match proof_type {
+            ProofType::EcdsaSignatureVerification | ProofType::AuthorityTrustedProof => Ok(PublicInputs::default()
+                .bytes(&health_record.message_hash)
+                .bytes(&pubkey_x)
+                .bytes(&pubkey_y)),
+            ProofType::NotExpiredProof => Ok(PublicInputs::default()
+                .bytes(&pubkey_x)
+                .bytes(&pubkey_y)
+                .number(u64::from(message_format::epoch_days(generated_at.date_naive())))),
+            ProofType::RecordTypeProof => {
+                let (type_prefix, type_prefix_len) = record_type_prefix(&health_record.record_type);
+                Ok(PublicInputs::default()
+                    .bytes(&health_record.message_hash)
+                    .bytes(&pubkey_x)
+                    .bytes(&pubkey_y)
+                    .bytes(&type_prefix)
+                    .number(type_prefix_len as u64))
+            }
+            ProofType::AggregateSignatureVerification => Err(AppError::BadRequest(
+                "Aggregate proofs cover multiple health records".to_string(),
+            )),
+        }
+    }
+
+    pub async fn get_user_proofs(
+        &self,
+        user_id: Uuid,
+        page: u32,
+        limit: u32,
+        cursor: Option<Cursor>,
+        status: Option<ProofStatus>,
+        sort: ProofSort,
+    ) -> Result<Paginated<ProofResponse>, AppError> {
+        // Cursors hold a `generated_at` position, so they only page the default order
+        if cursor.is_some() && sort != ProofSort::default() {
+            return Err(AppError::BadRequest("Cursors can only be used with the default sort".to_string()));
+        }
+
+        // A cursor switches to keyset pagination, which is stable while new proofs are generated
+        let offset = if cursor.is_some() { 0 } else { (page.saturating_sub(1)) * limit };
+
+        let sql = format!(
+            r#"
+            SELECT * FROM (
+                SELECT zp.*, {} AS status, hr.record_type
+                FROM zk_proofs zp
+                JOIN health_records hr ON zp.health_record_id = hr.id
+                WHERE hr.user_id = $1
+            ) zp
+            WHERE ($4::timestamptz IS NULL OR (zp.generated_at, zp.id) < ($4, $5::uuid))
+              AND ($6::varchar IS NULL OR zp.status = $6)
+            ORDER BY {}
+            LIMIT $2 OFFSET $3
+            "#,
+            ProofStatus::SQL,
+            sort.order_by()
+        );
+
+        let proofs = sqlx::query_as::<_, RecordProof>(&sql)
+            .bind(user_id)
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .bind(cursor.map(|c| c.created_at))
+            .bind(cursor.map(|c| c.id))
+            .bind(status)
+            .fetch_all(&self.db)
+            .await?;
+
+        let responses: Vec<ProofResponse> = proofs
+            .into_iter()
+            .map(|RecordProof { proof: ZkProofWithStatus { proof, status }, record_type }| {
+                proof_response(proof, status, format!("{:?}", record_type))
+            })
+            .collect();
+
+        if sort != ProofSort::default() {
+            return Ok(Paginated::without_cursor(responses));
+        }
+
+        Ok(Paginated::new(responses, limit, |p| Cursor::new(p.generated_at, p.id)))
+    }
+
+    /// Proofs of the given records owned by `owner_id`, newest first, keyed by
+    /// record. Records without proofs are absent from the map.
+    pub async fn get_record_proofs(
+        &self,
+        record_ids: &[Uuid],
+        owner_id: Uuid,
+    ) -> Result<HashMap<Uuid, Vec<ProofResponse>>, AppError> {
+        let sql = format!(
+            r#"
+            SELECT zp.*, {} AS status, hr.record_type
+            FROM zk_proofs zp
+            JOIN health_records hr ON zp.health_record_id = hr.id
+            WHERE zp.health_record_id = ANY($1) AND hr.user_id = $2
+            ORDER BY zp.generated_at DESC, zp.id DESC
+            "#,
+            ProofStatus::SQL
+        );
+
+        let rows = sqlx::query_as::<_, RecordProof>(&sql)
+            .bind(record_ids)
+            .bind(owner_id)
+            .fetch_all(&self.db)
+            .await?;
+
+        let mut proofs: HashMap<Uuid, Vec<ProofResponse>> = HashMap::new();
+        for RecordProof { proof: ZkProofWithStatus { proof, status }, record_type } in rows {
+            proofs
+                .entry(proof.health_record_id)
+                .or_default()
+                .push(proof_response(proof, status, format!("{:?}", record_type)));
+        }
+        Ok(proofs)
+    }
+
+    /// Verification attempts of a proof.
+    ///
+    /// `scope_user_id` limits access to the owner of the underlying record, who
+    /// does not get the raw IP addresses and user agents; `None` (admins, within
+    /// `org_scope`) sees the raw log.
+    pub async fn get_verification_history(
+        &self,
+        proof_id: Uuid,
+        scope_user_id: Option<Uuid>,
+        org_scope: Option<Uuid>,
+        page: u32,
+        limit: u32,
+    ) -> Result<Vec<ProofVerification>, AppError> {
+        let owned = sqlx::query!(
             r#"
             SELECT zp.id FROM zk_proofs zp
             JOIN health_records hr ON zp.health_record_id = hr.id
-            WHERE zp.id = $1 AND hr.user_id = $2
+            WHERE zp.id = $1 AND ($2::uuid IS NULL OR hr.user_id = $2) AND ($3::uuid IS NULL OR hr.org_id = $3)
             "#,
             proof_id,
-            user_id
+            scope_user_id,
+            org_scope
         )
         .fetch_optional(&self.db)
         .await?;
 
-        if result.is_none() {
+        if owned.is_none() {
             return Err(AppError::NotFound("Proof not found or access denied".to_string()));
         }
 
-        // Set max_usage to current usage_count to effectively revoke the proof
-        sqlx::query(
-            "UPDATE zk_proofs SET max_usage = usage_count WHERE id = $1"
+        let offset = (page.saturating_sub(1)) * limit;
+
+        let mut verifications = sqlx::query_as::<_, ProofVerification>(
+            r#"
+            SELECT pv.*, v.name AS verifier_name, pd.delegate_email
+            FROM proof_verifications pv
+            LEFT JOIN verifiers v ON pv.registered_verifier_id = v.id
+            LEFT JOIN proof_delegations pd ON pv.delegation_id = pd.id
+            WHERE pv.proof_id = $1
+            ORDER BY pv.verified_at DESC
+            LIMIT $2 OFFSET $3
+            "#
         )
         .bind(proof_id)
-        .execute(&self.db)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.db)
         .await?;
 
-        Ok(())
+        if scope_user_id.is_some() {
+            for verification in &mut verifications {
+                verification.ip_address = None;
+                verification.user_agent = None;
+            }
+        }
+
+        Ok(verifications)
     }
-}
 
-#[derive(Debug)]
-struct NoirProofData {
-    proof: Vec<u8>,
-    verification_key: Vec<u8>,
+    /// Verification log over a time range as CSV that is safe to share for analytics.
+    ///
+    /// Timestamps are truncated to the hour, IPs to their /24 (IPv4) or /48
+    /// (IPv6) network plus a hash salted per export, and user agents, proof,
+    /// record and user IDs are left out. Rows within an hour are sorted by
+    /// their values rather than by time.
+    pub async fn export_verification_analytics(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        org_scope: Option<Uuid>,
+    ) -> Result<String, AppError> {
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err(AppError::BadRequest("`from` must not be after `to`".to_string()));
+            }
+        }
+
+        let rows = sqlx::query_as::<_, AnalyticsRow>(
+            r#"
+            SELECT date_trunc('hour', pv.verified_at) AS verified_hour, pv.verification_result,
+                   zp.proof_type, hr.record_type, ha.name AS authority_name, v.name AS verifier_name, pv.ip_address
+            FROM proof_verifications pv
+            JOIN zk_proofs zp ON pv.proof_id = zp.id
+            JOIN health_records hr ON zp.health_record_id = hr.id
+            JOIN health_authorities ha ON hr.authority_id = ha.id
+            LEFT JOIN verifiers v ON pv.registered_verifier_id = v.id
+            WHERE ($1::timestamptz IS NULL OR pv.verified_at >= $1)
+              AND ($2::timestamptz IS NULL OR pv.verified_at < $2)
+              AND ($3::uuid IS NULL OR hr.org_id = $3)
+            "#
+        )
+        .bind(from)
+        .bind(to)
+        .bind(org_scope)
+        .fetch_all(&self.db)
+        .await?;
+
+        // A fresh salt per export: client hashes can be counted within one export
+        // but not joined across exports or brute-forced back to an address
+        let mut salt = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut salt);
+
+        let mut rows: Vec<[String; 8]> = rows
+            .into_iter()
+            .map(|row| {
+                [
+                    row.verified_hour.format("%Y-%m-%dT%H:00:00Z").to_string(),
+                    row.verification_result.to_string(),
+                    row.proof_type,
+                    row.record_type,
+                    row.authority_name,
+                    row.verifier_name.unwrap_or_default(),
+                    row.ip_address.map(truncate_ip).unwrap_or_default(),
+                    row.ip_address.map(|ip| salted_client_hash(&salt, ip)).unwrap_or_default(),
+                ]
+            })
+            .collect();
+        rows.sort();
+
+        let mut csv = String::from(
+            "verified_hour,verification_result,proof_type,record_type,authority_name,verifier_name,ip_network,client_hash\n",
+        );
+        for row in rows {
+            let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+
+        Ok(csv)
+    }
+
+    /// Full verification log as CSV for compliance audits, oldest first.
+    ///
+    /// Unlike the analytics export this keeps IPs, user agents and IDs, so it
+    /// is for admins only. Rows are streamed from the database in chunks of
+    /// about `EXPORT_CHUNK_BYTES` rather than collected in memory; a database
+    /// error midway ends the stream with that error.
+    pub fn export_verifications(
+        &self,
+        query: VerificationExportQuery,
+        org_scope: Option<Uuid>,
+    ) -> Result<impl Stream<Item = Result<String, AppError>> + Send + 'static, AppError> {
+        if let (Some(from), Some(to)) = (query.from, query.to) {
+            if from > to {
+                return Err(AppError::BadRequest("`from` must not be after `to`".to_string()));
+            }
+        }
+
+        let db = self.db.clone();
+        let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, AppError>>(4);
+        tokio::spawn(async move {
+            let mut rows = sqlx::query_as::<_, VerificationExportRow>(
+                r#"
+                SELECT pv.verified_at, pv.verification_result, zp.id AS proof_id, zp.proof_type,
+                       hr.id AS health_record_id, hr.record_type, ha.id AS authority_id, ha.name AS authority_name,
+                       v.name AS verifier_name, pv.ip_address, pv.user_agent, pv.country_code, pv.region
+                FROM proof_verifications pv
+                JOIN zk_proofs zp ON pv.proof_id = zp.id
+                JOIN health_records hr ON zp.health_record_id = hr.id
+                JOIN health_authorities ha ON hr.authority_id = ha.id
+                LEFT JOIN verifiers v ON pv.registered_verifier_id = v.id
+                WHERE ($1::timestamptz IS NULL OR pv.verified_at >= $1)
+                  AND ($2::timestamptz IS NULL OR pv.verified_at < $2)
+                  AND ($3::boolean IS NULL OR pv.verification_result = $3)
+                  AND ($4::uuid IS NULL OR hr.authority_id = $4)
+                  AND ($5::uuid IS NULL OR hr.org_id = $5)
+                ORDER BY pv.verified_at, pv.id
+                "#
+            )
+            .bind(query.from)
+            .bind(query.to)
+            .bind(query.result)
+            .bind(query.authority_id)
+            .bind(org_scope)
+            .fetch(&db);
+
+            let mut chunk = String::from(VerificationExportRow::CSV_HEADER);
+            loop {
+                match rows.try_next().await {
+                    Ok(Some(row)) => {
+                        row.push_csv_line(&mut chunk);
+                        if chunk.len() >= EXPORT_CHUNK_BYTES
+                            && tx.send(Ok(std::mem::take(&mut chunk))).await.is_err()
+                        {
+                            // The client went away
+                            return;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        let _ = tx.send(Err(e.into())).await;
+                        return;
+                    }
+                }
+            }
+            if !chunk.is_empty() {
+                let _ = tx.send(Ok(chunk)).await;
+            }
+        });
+
+        Ok(futures::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|item| (item, rx))
+        }))
+    }
+
+    /// Aggregate verification outcomes over a time range.
+    ///
+    /// `scope_user_id` restricts the figures to proofs of that user's records
+    /// and `org_scope` to records of that organization; `None` lifts the restriction.
+    pub async fn verification_stats(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        scope_user_id: Option<Uuid>,
+        org_scope: Option<Uuid>,
+    ) -> Result<VerificationStats, AppError> {
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err(AppError::BadRequest("`from` must not be after `to`".to_string()));
+            }
+        }
+
+        const SCOPE: &str = r#"
+            FROM proof_verifications pv
+            JOIN zk_proofs zp ON pv.proof_id = zp.id
+            JOIN health_records hr ON zp.health_record_id = hr.id
+            JOIN health_authorities ha ON hr.authority_id = ha.id
+            WHERE ($1::uuid IS NULL OR hr.user_id = $1)
+              AND ($2::timestamptz IS NULL OR pv.verified_at >= $2)
+              AND ($3::timestamptz IS NULL OR pv.verified_at < $3)
+              AND ($4::uuid IS NULL OR hr.org_id = $4)
+        "#;
+
+        let (total, successful) = sqlx::query_as::<_, (i64, i64)>(&format!(
+            "SELECT COUNT(*), COUNT(*) FILTER (WHERE pv.verification_result) {}",
+            SCOPE
+        ))
+        .bind(scope_user_id)
+        .bind(from)
+        .bind(to)
+        .bind(org_scope)
+        .fetch_one(&self.db)
+        .await?;
+
+        let by_authority = sqlx::query_as::<_, (String, i64, i64)>(&format!(
+            "SELECT ha.name, COUNT(*), COUNT(*) FILTER (WHERE pv.verification_result) {} GROUP BY ha.name ORDER BY COUNT(*) DESC",
+            SCOPE
+        ))
+        .bind(scope_user_id)
+        .bind(from)
+        .bind(to)
+        .bind(org_scope)
+        .fetch_all(&self.db)
+        .await?;
+
+        let by_record_type = sqlx::query_as::<_, (String, i64, i64)>(&format!(
+            "SELECT hr.record_type, COUNT(*), COUNT(*) FILTER (WHERE pv.verification_result) {} GROUP BY hr.record_type ORDER BY COUNT(*) DESC",
+            SCOPE
+        ))
+        .bind(scope_user_id)
+        .bind(from)
+        .bind(to)
+        .bind(org_scope)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(VerificationStats {
+            from,
+            to,
+            total_verifications: total,
+            successful_verifications: successful,
+            failed_verifications: total - successful,
+            success_rate: crate::models::success_rate(total, successful),
+            by_authority: by_authority
+                .into_iter()
+                .map(|(key, total, successful)| VerificationStatsBucket::new(key, total, successful))
+                .collect(),
+            by_record_type: by_record_type
+                .into_iter()
+                .map(|(key, total, successful)| VerificationStatsBucket::new(key, total, successful))
+                .collect(),
+        })
+    }
+
+    pub async fn revoke_proof(&self, proof_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        // Verify the user owns the health record associated with this proof
+        let result = sqlx::query!(
+            r#"
+            SELECT zp.id FROM zk_proofs zp
+            JOIN health_records hr ON zp.health_record_id = hr.id
+            WHERE zp.id = $1 AND hr.user_id = $2
+            "#,
+            proof_id,
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        if result.is_none() {
+            return Err(AppError::NotFound("Proof not found or access denied".to_string()));
+        }
+
+        // Set max_usage to current usage_count to effectively revoke the proof
+        sqlx::query(
+            "UPDATE zk_proofs SET max_usage = usage_count, revoked_at = COALESCE(revoked_at, NOW()) WHERE id = $1"
+        )
+        .bind(proof_id)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Let `delegate_email` present one of the owner's proofs until
+    /// `expires_at`. The returned token stands in for the proof data and
+    /// verification key when verifying; it cannot generate new proofs.
+    pub async fn delegate_proof(
+        &self,
+        proof_id: Uuid,
+        owner_id: Uuid,
+        delegate_email: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<ProofDelegationResponse, AppError> {
+        let (proof_expires_at, revoked_at) = sqlx::query_as::<_, (Option<DateTime<Utc>>, Option<DateTime<Utc>>)>(
+            r#"
+            SELECT zp.expires_at, zp.revoked_at FROM zk_proofs zp
+            JOIN health_records hr ON zp.health_record_id = hr.id
+            WHERE zp.id = $1 AND hr.user_id = $2
+            "#
+        )
+        .bind(proof_id)
+        .bind(owner_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Proof not found or access denied".to_string()))?;
+
+        if revoked_at.is_some() {
+            return Err(AppError::BadRequest("Revoked proofs cannot be delegated".to_string()));
+        }
+        if expires_at <= Utc::now() {
+            return Err(AppError::BadRequest("Delegation expiry must be in the future".to_string()));
+        }
+        if proof_expires_at.map_or(false, |proof_expires_at| expires_at > proof_expires_at) {
+            return Err(AppError::BadRequest("Delegation cannot outlive the proof".to_string()));
+        }
+
+        let token = generate_delegation_token();
+        let delegation = sqlx::query_as::<_, ProofDelegation>(
+            r#"
+            INSERT INTO proof_delegations (proof_id, owner_id, delegate_email, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#
+        )
+        .bind(proof_id)
+        .bind(owner_id)
+        .bind(delegate_email)
+        .bind(hash_delegation_token(&token))
+        .bind(expires_at)
+        .fetch_one(&self.db)
+        .await?;
+
+        tracing::info!(
+            target: "audit",
+            event = "proof_delegated",
+            proof_id = %proof_id,
+            delegation_id = %delegation.id,
+            owner_id = %owner_id,
+            "Proof delegated"
+        );
+
+        Ok(ProofDelegationResponse {
+            token: Some(token),
+            ..delegation.into()
+        })
+    }
+
+    /// Delegations of one of the owner's proofs, newest first, without tokens
+    pub async fn list_delegations(&self, proof_id: Uuid, owner_id: Uuid) -> Result<Vec<ProofDelegationResponse>, AppError> {
+        let delegations = sqlx::query_as::<_, ProofDelegation>(
+            r#"
+            SELECT pd.* FROM proof_delegations pd
+            JOIN zk_proofs zp ON pd.proof_id = zp.id
+            JOIN health_records hr ON zp.health_record_id = hr.id
+            WHERE pd.proof_id = $1 AND hr.user_id = $2
+            ORDER BY pd.created_at DESC
+            "#
+        )
+        .bind(proof_id)
+        .bind(owner_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(delegations.into_iter().map(Into::into).collect())
+    }
+
+    /// Stop a delegation's token from presenting the proof
+    pub async fn revoke_delegation(&self, delegation_id: Uuid, owner_id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE proof_delegations SET revoked_at = COALESCE(revoked_at, NOW())
+            WHERE id = $1 AND owner_id = $2
+            "#
+        )
+        .bind(delegation_id)
+        .bind(owner_id)
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Delegation not found or access denied".to_string()));
+        }
+
+        tracing::info!(
+            target: "audit",
+            event = "proof_delegation_revoked",
+            delegation_id = %delegation_id,
+            owner_id = %owner_id,
+            "Proof delegation revoked"
+        );
+
+        Ok(())
+    }
+
+    /// The delegation behind a delegate's token, while neither expired nor revoked
+    async fn active_delegation(&self, token: &str) -> Result<ProofDelegation, AppError> {
+        sqlx::query_as::<_, ProofDelegation>(
+            "SELECT * FROM proof_delegations WHERE token_hash = $1 AND revoked_at IS NULL AND expires_at > NOW()"
+        )
+        .bind(hash_delegation_token(token))
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::Forbidden("Delegation token is invalid, expired or revoked".to_string()))
+    }
+
+    /// Revoke every outstanding proof covering a health record, returning how many were revoked
+    pub async fn revoke_proofs_for_record(&self, record_id: Uuid, user_id: Uuid) -> Result<u64, AppError> {
+        // Aggregate proofs list the record in zk_proof_records rather than health_record_id
+        let result = sqlx::query(
+            r#"
+            UPDATE zk_proofs SET max_usage = usage_count, revoked_at = NOW()
+            WHERE (health_record_id = $1
+                   OR id IN (SELECT proof_id FROM zk_proof_records WHERE health_record_id = $1))
+              AND revoked_at IS NULL
+            "#
+        )
+        .bind(record_id)
+        .execute(&self.db)
+        .await?;
+
+        tracing::info!(
+            record_id = %record_id,
+            revoked_by = %user_id,
+            proofs_revoked = result.rows_affected(),
+            "Revoked proofs for health record"
+        );
+
+        Ok(result.rows_affected())
+    }
+
+    /// Number of proofs revoked after `since` and the latest revocation time,
+    /// which together identify a version of the revocation list
+    pub async fn revocation_list_version(&self, since: Option<DateTime<Utc>>) -> Result<(i64, Option<DateTime<Utc>>), AppError> {
+        self.crl_signing_key()?;
+
+        let version = sqlx::query_as::<_, (i64, Option<DateTime<Utc>>)>(
+            "SELECT COUNT(*), MAX(revoked_at) FROM zk_proofs WHERE revoked_at > COALESCE($1, '-infinity'::timestamptz)"
+        )
+        .bind(since)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(version)
+    }
+
+    /// Signed list of the proofs revoked after `since`, or of all revoked proofs
+    pub async fn revocation_list(&self, since: Option<DateTime<Utc>>) -> Result<SignedRevocationList, AppError> {
+        let signing_key = self.crl_signing_key()?;
+
+        let entries = sqlx::query_as::<_, RevokedProof>(
+            r#"
+            SELECT id AS proof_id, encode(proof_hash, 'hex') AS proof_hash, revoked_at
+            FROM zk_proofs
+            WHERE revoked_at > COALESCE($1, '-infinity'::timestamptz)
+            ORDER BY revoked_at, id
+            "#
+        )
+        .bind(since)
+        .fetch_all(&self.db)
+        .await?;
+
+        let payload = serde_json::to_vec(&RevocationListPayload { issued_at: Utc::now(), since, entries })
+            .map_err(|e| AppError::InternalServerError(format!("Failed to serialize revocation list: {}", e)))?;
+        let (r, s) = self.crypto_service.sign_message_hash(&Sha256::digest(&payload), signing_key)?;
+        let public_key = secp256k1::PublicKey::from_secret_key(&secp256k1::Secp256k1::new(), signing_key);
+
+        Ok(SignedRevocationList {
+            payload: general_purpose::STANDARD.encode(&payload),
+            signature: hex::encode([r, s].concat()),
+            public_key: hex::encode(public_key.serialize()),
+        })
+    }
+
+    /// Current status of a proof, signed with the CRL key so verifier kiosks
+    /// can poll it over networks they don't trust
+    pub async fn proof_status(&self, proof_id: Uuid) -> Result<ProofStatusResponse, AppError> {
+        let signing_key = self.crl_signing_key()?;
+
+        let status = sqlx::query_scalar::<_, ProofStatus>(&format!(
+            "SELECT {} FROM zk_proofs zp JOIN health_records hr ON hr.id = zp.health_record_id WHERE zp.id = $1",
+            ProofStatus::SQL
+        ))
+        .bind(proof_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Proof not found".to_string()))?;
+
+        let status = LiveProofStatus::from(status);
+        let checked_at = Utc::now().trunc_subsecs(0);
+        let message = ProofStatusResponse::signed_message(proof_id, status, checked_at);
+        let (r, s) = self.crypto_service.sign_message_hash(&Sha256::digest(message.as_bytes()), signing_key)?;
+        let public_key = secp256k1::PublicKey::from_secret_key(&secp256k1::Secp256k1::new(), signing_key);
+
+        Ok(ProofStatusResponse {
+            proof_id,
+            status,
+            checked_at,
+            signature: hex::encode([r, s].concat()),
+            public_key: hex::encode(public_key.serialize()),
+        })
+    }
+
+    /// Signed `.pkpass` bundle of one of `user_id`'s proofs
+    pub async fn apple_wallet_pass(&self, proof_id: Uuid, user_id: Uuid) -> Result<Vec<u8>, AppError> {
+        let (signer, public_base_url) = self
+            .apple_wallet
+            .as_ref()
+            .ok_or_else(|| AppError::NotFound("Apple Wallet passes are not enabled on this server".to_string()))?;
+
+        let WalletProof { proof_type, expires_at, record_type, authority_name, status, .. } =
+            self.wallet_proof(proof_id, user_id).await?;
+
+        let mut auxiliary_fields = vec![json!({"key": "proof_type", "label": "PROOF", "value": proof_type_label(&proof_type)})];
+        if let Some(expires_at) = expires_at {
+            auxiliary_fields.push(json!({
+                "key": "expires_at",
+                "label": "EXPIRES",
+                "value": expires_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                "dateStyle": "PKDateStyleMedium",
+                "timeStyle": "PKDateStyleShort",
+            }));
+        }
+
+        let mut pass = json!({
+            "serialNumber": proof_id.to_string(),
+            "description": "Health pass",
+            "logoText": "Health Pass",
+            "voided": !matches!(status, ProofStatus::Active),
+            "barcodes": [{
+                "format": "PKBarcodeFormatQR",
+                "message": format!("{}/api/v1/proofs/{}/status", public_base_url, proof_id),
+                "messageEncoding": "iso-8859-1",
+                "altText": proof_id.to_string(),
+            }],
+            "generic": {
+                "primaryFields": [{"key": "record_type", "label": "RECORD", "value": record_type_label(&record_type)}],
+                "secondaryFields": [{"key": "authority", "label": "ISSUED BY", "value": authority_name}],
+                "auxiliaryFields": auxiliary_fields,
+                "backFields": [{"key": "proof_id", "label": "Proof ID", "value": proof_id.to_string()}],
+            },
+        });
+        if let Some(expires_at) = expires_at {
+            pass["expirationDate"] = json!(expires_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+        }
+
+        signer.package(pass)
+    }
+
+    /// Google Wallet "save" link for one of `user_id`'s proofs
+    pub async fn google_wallet_link(&self, proof_id: Uuid, user_id: Uuid) -> Result<GoogleWalletLink, AppError> {
+        let (signer, public_base_url) = self
+            .google_wallet
+            .as_ref()
+            .ok_or_else(|| AppError::NotFound("Google Wallet passes are not enabled on this server".to_string()))?;
+
+        let WalletProof { proof_type, expires_at, record_type, authority_name, status, .. } =
+            self.wallet_proof(proof_id, user_id).await?;
+
+        let localized = |value: &str| json!({"defaultValue": {"language": "en", "value": value}});
+        let mut object = json!({
+            "id": signer.object_id(proof_id),
+            "classId": signer.class_id(),
+            "state": match status {
+                ProofStatus::Active => "ACTIVE",
+                ProofStatus::Expired => "EXPIRED",
+                ProofStatus::Revoked | ProofStatus::UsedUp => "INACTIVE",
+            },
+            "cardTitle": localized("Health Pass"),
+            "header": localized(record_type_label(&record_type)),
+            "subheader": localized(&format!("Issued by {}", authority_name)),
+            "textModulesData": [
+                {"id": "proof_type", "header": "Proof", "body": proof_type_label(&proof_type)},
+                {"id": "proof_id", "header": "Proof ID", "body": proof_id.to_string()},
+            ],
+            "barcode": {
+                "type": "QR_CODE",
+                "value": format!("{}/api/v1/proofs/{}/status", public_base_url, proof_id),
+                "alternateText": proof_id.to_string(),
+            },
+            "hexBackgroundColor": "#0f766e",
+        });
+        if let Some(expires_at) = expires_at {
+            object["validTimeInterval"] = json!({
+                "end": {"date": expires_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)},
+            });
+        }
+
+        Ok(GoogleWalletLink { save_url: signer.save_url(object)? })
+    }
+
+    /// One-page printable PDF pass of one of `user_id`'s proofs
+    pub async fn proof_pdf(&self, proof_id: Uuid, user_id: Uuid) -> Result<Vec<u8>, AppError> {
+        let public_base_url = self
+            .public_base_url
+            .as_ref()
+            .ok_or_else(|| AppError::NotFound("PDF passes are not enabled on this server".to_string()))?;
+        let proof = self.wallet_proof(proof_id, user_id).await?;
+
+        HealthPassPdf {
+            proof_id,
+            facts: pdf_facts(&proof),
+            qr_payload: format!("{}/api/v1/proofs/{}/status", public_base_url, proof_id),
+        }
+        .render()
+    }
+
+    /// What a wallet pass shows about one of `user_id`'s proofs
+    async fn wallet_proof(&self, proof_id: Uuid, user_id: Uuid) -> Result<WalletProof, AppError> {
+        sqlx::query_as::<_, WalletProof>(&format!(
+            r#"
+            SELECT zp.proof_type, zp.expires_at, hr.record_type, hr.issue_date, ha.name AS authority_name, {} AS status
+            FROM zk_proofs zp
+            JOIN health_records hr ON hr.id = zp.health_record_id
+            JOIN health_authorities ha ON ha.id = hr.authority_id
+            WHERE zp.id = $1 AND hr.user_id = $2
+            "#,
+            ProofStatus::SQL
+        ))
+        .bind(proof_id)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Proof not found or access denied".to_string()))
+    }
+
+    fn crl_signing_key(&self) -> Result<&secp256k1::SecretKey, AppError> {
+        self.crl_signing_key
+            .as_ref()
+            .ok_or_else(|| AppError::NotFound("Proof revocation list is not enabled on this server".to_string()))
+    }
+}
+
+/// Number of signature slots in the aggregate circuit
+const MAX_AGGREGATED_RECORDS: usize = 4;
+
+/// Length of the zero-padded record-type tag public input in the record_type circuit
+const RECORD_TYPE_PREFIX_LEN: usize = 14;
+
+/// One verification as exported for analytics, before anonymization
+#[derive(sqlx::FromRow)]
+struct AnalyticsRow {
+    verified_hour: DateTime<Utc>,
+    verification_result: bool,
+    proof_type: String,
+    record_type: String,
+    authority_name: String,
+    verifier_name: Option<String>,
+    ip_address: Option<std::net::IpAddr>,
+}
+
+/// A proof as shown on a wallet pass
+#[derive(sqlx::FromRow)]
+struct WalletProof {
+    proof_type: ProofType,
+    expires_at: Option<DateTime<Utc>>,
+    record_type: HealthRecordType,
+    issue_date: NaiveDate,
+    authority_name: String,
+    status: ProofStatus,
+}
+
+/// Facts printed on a PDF pass, withholding what the proof type does not disclose
+fn pdf_facts(proof: &WalletProof) -> Vec<(&'static str, String)> {
+    let mut facts = vec![
+        ("Record", record_type_label(&proof.record_type).to_string()),
+        ("Issued by", proof.authority_name.clone()),
+    ];
+    // A not-expired proof exists precisely to avoid disclosing the record's dates
+    if proof.proof_type != ProofType::NotExpiredProof {
+        facts.push(("Issued on", proof.issue_date.format("%-d %B %Y").to_string()));
+    }
+    facts.push(("Proof", proof_type_label(&proof.proof_type).to_string()));
+    facts.push((
+        "Expires",
+        proof
+            .expires_at
+            .map_or_else(|| "Never".to_string(), |expires_at| expires_at.format("%-d %B %Y %H:%M UTC").to_string()),
+    ));
+    let status = match proof.status {
+        ProofStatus::Active => None,
+        ProofStatus::Expired => Some("Expired"),
+        ProofStatus::Revoked => Some("Revoked"),
+        ProofStatus::UsedUp => Some("No longer valid"),
+    };
+    if let Some(status) = status {
+        facts.push(("Status", status.to_string()));
+    }
+    facts
+}
+
+/// One verification as exported for audit, with the names it refers to
+#[derive(sqlx::FromRow)]
+struct VerificationExportRow {
+    verified_at: DateTime<Utc>,
+    verification_result: bool,
+    proof_id: Uuid,
+    proof_type: String,
+    health_record_id: Uuid,
+    record_type: String,
+    authority_id: Uuid,
+    authority_name: String,
+    verifier_name: Option<String>,
+    ip_address: Option<std::net::IpAddr>,
+    user_agent: Option<String>,
+    country_code: Option<String>,
+    region: Option<String>,
+}
+
+impl VerificationExportRow {
+    const CSV_HEADER: &'static str = "verified_at,verification_result,proof_id,proof_type,health_record_id,record_type,authority_id,authority_name,verifier_name,ip_address,user_agent,country_code,region\n";
+
+    fn push_csv_line(self, csv: &mut String) {
+        let fields = [
+            self.verified_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            self.verification_result.to_string(),
+            self.proof_id.to_string(),
+            self.proof_type,
+            self.health_record_id.to_string(),
+            self.record_type,
+            self.authority_id.to_string(),
+            self.authority_name,
+            self.verifier_name.unwrap_or_default(),
+            self.ip_address.map(|ip| ip.to_string()).unwrap_or_default(),
+            self.user_agent.unwrap_or_default(),
+            self.country_code.unwrap_or_default(),
+            self.region.unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+}
+
+fn proof_type_label(proof_type: &ProofType) -> &'static str {
+    match proof_type {
+        ProofType::EcdsaSignatureVerification => "Valid signature",
+        ProofType::NotExpiredProof => "Not expired",
+        ProofType::RecordTypeProof => "Record type",
+        ProofType::AuthorityTrustedProof => "Trusted authority",
+        ProofType::AggregateSignatureVerification => "Multiple records",
+    }
+}
+
+fn record_type_label(record_type: &HealthRecordType) -> &'static str {
+    match record_type {
+        HealthRecordType::Vaccination => "Vaccination",
+        HealthRecordType::TestResult => "Test result",
+        HealthRecordType::MedicalClearance => "Medical clearance",
+        HealthRecordType::ImmunityProof => "Immunity proof",
+    }
+}
+
+/// Failure reason for a revoked record; records revoked before reasons were
+/// kept have none
+fn revoked_failure_reason(reason: Option<&str>) -> String {
+    match reason {
+        Some(reason) => format!("Health record revoked: {}", reason),
+        None => "Health record revoked".to_string(),
+    }
+}
+
+/// `bytes32` proof hash submitted on-chain: hex SHA-256 of the proof data
+fn chain_proof_hash(proof_data: &[u8]) -> String {
+    format!("0x{}", hex::encode(Sha256::digest(proof_data)))
+}
+
+/// The network an address belongs to: /24 for IPv4, /48 for IPv6
+fn truncate_ip(ip: std::net::IpAddr) -> String {
+    match ip {
+        std::net::IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            format!("{}.{}.{}.0/24", a, b, c)
+        }
+        std::net::IpAddr::V6(v6) => {
+            let segments = v6.segments();
+            let network = std::net::Ipv6Addr::new(segments[0], segments[1], segments[2], 0, 0, 0, 0, 0);
+            format!("{}/48", network)
+        }
+    }
+}
+
+/// Truncated HMAC of the full address, keyed with a per-export salt
+fn salted_client_hash(salt: &[u8], ip: std::net::IpAddr) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(salt).expect("HMAC accepts keys of any length");
+    mac.update(ip.to_string().as_bytes());
+    hex::encode(&mac.finalize().into_bytes()[..8])
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// A proof row with its status and the type of the record it covers
+#[derive(sqlx::FromRow)]
+struct RecordProof {
+    #[sqlx(flatten)]
+    proof: ZkProofWithStatus,
+    record_type: HealthRecordType,
+}
+
+fn proof_response(proof: ZkProof, status: ProofStatus, health_record_type: String) -> ProofResponse {
+    ProofResponse {
+        id: proof.id,
+        proof_data: general_purpose::STANDARD.encode(&proof.proof_data),
+        verification_key: general_purpose::STANDARD.encode(&proof.verification_key),
+        proof_type: proof.proof_type,
+        generated_at: proof.generated_at,
+        expires_at: proof.expires_at,
+        usage_count: proof.usage_count,
+        max_usage: proof.max_usage,
+        health_record_type,
+        status,
+        timestamp_token: proof.timestamp_token.map(|token| general_purpose::STANDARD.encode(token)),
+        timestamped_at: proof.timestamped_at,
+        anchor_tx_hash: proof.anchor_tx_hash,
+        proof_cid: proof.proof_cid,
+    }
+}
+
+/// Whether a record valid through `expiry_date` has expired at `now` for a
+/// holder `utc_offset_minutes` east of UTC (UTC when `None`)
+fn date_expired(expiry_date: NaiveDate, now: DateTime<Utc>, utc_offset_minutes: Option<i32>) -> bool {
+    let offset = utc_offset_minutes
+        .and_then(|minutes| FixedOffset::east_opt(minutes * 60))
+        .unwrap_or_else(|| FixedOffset::east_opt(0).expect("zero offset is valid"));
+    expiry_date < now.with_timezone(&offset).date_naive()
+}
+
+/// The code and message a REST client would be answered `error` with
+fn error_details(error: AppError) -> (String, String) {
+    let response = error.into_response();
+    match response.extensions().get::<ErrorDetails>() {
+        Some(details) => (details.code.to_string(), details.message.clone()),
+        None => ("INTERNAL_SERVER_ERROR".to_string(), "Internal server error".to_string()),
+    }
+}
+
+/// Store key of a verification request: the proof, who verifies it, and a
+/// digest of everything else the response depends on
+fn verification_dedup_key(
+    proof_id: Uuid,
+    verifier_id: Option<Uuid>,
+    registered_verifier_id: Option<Uuid>,
+    ip_address: Option<std::net::IpAddr>,
+    request: &VerifyProofRequest,
+) -> String {
+    let verifier = match (verifier_id, registered_verifier_id, ip_address) {
+        (Some(user_id), _, _) => format!("user:{}", user_id),
+        (None, Some(verifier_id), _) => format!("verifier:{}", verifier_id),
+        (None, None, Some(ip)) => format!("ip:{}", ip),
+        (None, None, None) => "anonymous".to_string(),
+    };
+    let request_hash = Sha256::digest(
+        json!({
+            "proof_type": request.proof_type,
+            "verification_context": request.verification_context,
+            "predicate": request.predicate,
+            "existed_before": request.existed_before,
+            "client_utc_offset_minutes": request.client_utc_offset_minutes,
+            "delegation_token": request.delegation_token,
+            "nonce": request.nonce,
+        })
+        .to_string(),
+    );
+
+    format!("verification:{}:{}:{}", proof_id, verifier, hex::encode(request_hash))
+}
+
+fn generate_delegation_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", DELEGATION_TOKEN_PREFIX, hex::encode(bytes))
+}
+
+/// Only this digest is stored, like transfer tokens
+fn hash_delegation_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// The `"<tag>:"` prefix a RecordTypeProof discloses, zero-padded, and its length
+fn record_type_prefix(record_type: &HealthRecordType) -> ([u8; RECORD_TYPE_PREFIX_LEN], usize) {
+    let tag = format!("{}:", message_format::record_type_tag(record_type));
+    let mut type_prefix = [0u8; RECORD_TYPE_PREFIX_LEN];
+    type_prefix[..tag.len()].copy_from_slice(tag.as_bytes());
+    (type_prefix, tag.len())
+}
+
+#[derive(Debug)]
+struct NoirProofData {
+    proof: Vec<u8>,
+    verification_key: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::proof_backend::StubProofBackend;
+
+    /// Public key of the test private key 0x00..01
+    const TEST_AUTHORITY_KEY: &str = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+    async fn seed_proof(db: &PgPool) -> (Uuid, Vec<u8>, Vec<u8>) {
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name) VALUES ('holder@example.com', 'x', 'Holder') RETURNING id"
+        )
+        .fetch_one(db)
+        .await
+        .unwrap();
+
+        let authority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO health_authorities (name, authority_type, public_key) VALUES ('Clinic', 'clinic', $1) RETURNING id"
+        )
+        .bind(TEST_AUTHORITY_KEY)
+        .fetch_one(db)
+        .await
+        .unwrap();
+
+        let message_hash = [1u8; 32];
+        let record_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO health_records (user_id, authority_id, record_type, patient_identifier, details, issue_date, signature_r, signature_s, message_hash)
+            VALUES ($1, $2, 'vaccination', 'P-1', '{}', CURRENT_DATE, '\x01', '\x01', $3)
+            RETURNING id
+            "#
+        )
+        .bind(user_id)
+        .bind(authority_id)
+        .bind(message_hash.as_slice())
+        .fetch_one(db)
+        .await
+        .unwrap();
+
+        // A stub proof over the record's public inputs, which test_service accepts
+        let crypto = CryptoService::new();
+        let (pubkey_x, pubkey_y) = crypto.get_public_key_coordinates(&crypto.parse_public_key(TEST_AUTHORITY_KEY).unwrap());
+        let public_inputs = PublicInputs::default().bytes(&message_hash).bytes(&pubkey_x).bytes(&pubkey_y);
+        let proof_data = StubProofBackend
+            .prove(&NoirCircuit::for_proof_type(&ProofType::EcdsaSignatureVerification), String::new(), &public_inputs)
+            .await
+            .unwrap();
+        let verification_key = hex::decode(TEST_AUTHORITY_KEY).unwrap();
+        sqlx::query(
+            "INSERT INTO zk_proofs (health_record_id, proof_data, verification_key, proof_type) VALUES ($1, $2, $3, $4)"
+        )
+        .bind(record_id)
+        .bind(&proof_data)
+        .bind(&verification_key)
+        .bind(ProofType::EcdsaSignatureVerification)
+        .execute(db)
+        .await
+        .unwrap();
+
+        (record_id, proof_data, verification_key)
+    }
+
+    /// Service over the repository's circuits with default settings, proving with the stub backend
+    fn test_service(db: &PgPool) -> ZkProofService {
+        ZkProofService::new(
+            db.clone(),
+            CryptoService::new(),
+            concat!(env!("CARGO_MANIFEST_DIR"), "/../noir").to_string(),
+            WebhookService::new(db.clone()),
+        )
+        .with_proof_backend(Arc::new(StubProofBackend))
+    }
+
+    /// Verification of the given proof with every optional field unset
+    fn verify_request(proof_data: &[u8], verification_key: &[u8]) -> VerifyProofRequest {
+        VerifyProofRequest {
+            proof_data: general_purpose::STANDARD.encode(proof_data),
+            proof_cid: None,
+            verification_key: general_purpose::STANDARD.encode(verification_key),
+            proof_type: ProofType::EcdsaSignatureVerification,
+            verification_context: None,
+            predicate: None,
+            existed_before: None,
+            client_utc_offset_minutes: None,
+            captcha_token: None,
+            delegation_token: None,
+            nonce: None,
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_proofs_stop_verifying_once_record_is_revoked(db: PgPool) {
+        let service = test_service(&db);
+        let (record_id, proof_data, verification_key) = seed_proof(&db).await;
+
+        let request = || verify_request(&proof_data, &verification_key);
+
+        assert!(service.verify_proof(request(), None, None, None, None).await.unwrap().is_valid);
+
+        sqlx::query(
+            "UPDATE health_records SET is_revoked = TRUE, revoked_at = NOW(), revocation_reason = 'superseded' WHERE id = $1"
+        )
+            .bind(record_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        assert_eq!(service.revoke_proofs_for_record(record_id, Uuid::new_v4()).await.unwrap(), 1);
+
+        let response = service.verify_proof(request(), None, None, None, None).await.unwrap();
+        assert!(!response.is_valid);
+        assert!(response.verification_details.usage_exceeded);
+        assert!(matches!(response.verification_details.revocation_status, RevocationStatus::Revoked));
+        assert_eq!(response.verification_details.revocation_reason.as_deref(), Some("superseded"));
+        assert!(response.verification_details.revoked_at.is_some());
+        assert_eq!(response.failure_reason.as_deref(), Some("Health record revoked: superseded"));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_identical_verifications_within_window_count_once(db: PgPool) {
+        let service = test_service(&db).with_verification_dedup(Arc::new(crate::services::store::MemoryStore::new()), std::time::Duration::from_secs(60));
+        let (record_id, proof_data, verification_key) = seed_proof(&db).await;
+        let client: Option<std::net::IpAddr> = Some("203.0.113.9".parse().unwrap());
+
+        let request = |nonce: Option<&str>| VerifyProofRequest {
+            nonce: nonce.map(str::to_string),
+            ..verify_request(&proof_data, &verification_key)
+        };
+        let usage_count = || {
+            sqlx::query_scalar::<_, i32>("SELECT usage_count FROM zk_proofs WHERE health_record_id = $1")
+                .bind(record_id)
+                .fetch_one(&db)
+        };
+
+        let first = service.verify_proof(request(None), None, None, client, None).await.unwrap();
+        let retry = service.verify_proof(request(None), None, None, client, None).await.unwrap();
+        assert!(first.is_valid && retry.is_valid);
+        assert_eq!(retry.verified_at, first.verified_at);
+        assert_eq!(usage_count().await.unwrap(), 1);
+
+        // A deliberate re-verification with its own nonce counts again
+        assert!(service.verify_proof(request(Some("gate-2")), None, None, client, None).await.unwrap().is_valid);
+        assert_eq!(usage_count().await.unwrap(), 2);
+
+        // As does the same request from another client
+        let other_client = Some("198.51.100.7".parse().unwrap());
+        assert!(service.verify_proof(request(None), None, None, other_client, None).await.unwrap().is_valid);
+        assert_eq!(usage_count().await.unwrap(), 3);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_expired_delegation_cannot_present_proof(db: PgPool) {
+        let service = test_service(&db);
+        let (record_id, _, _) = seed_proof(&db).await;
+        let (proof_id, owner_id): (Uuid, Uuid) = sqlx::query_as(
+            "SELECT zp.id, hr.user_id FROM zk_proofs zp JOIN health_records hr ON zp.health_record_id = hr.id WHERE hr.id = $1"
+        )
+        .bind(record_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let delegation = service
+            .delegate_proof(proof_id, owner_id, "carer@example.com", Utc::now() + Duration::hours(1))
+            .await
+            .unwrap();
+        let request = || VerifyProofRequest {
+            proof_data: String::new(),
+            verification_key: String::new(),
+            delegation_token: delegation.token.clone(),
+            ..verify_request(&[], &[])
+        };
+
+        assert!(service.verify_proof(request(), None, None, None, None).await.unwrap().is_valid);
+        let history = service.get_verification_history(proof_id, Some(owner_id), None, 1, 10).await.unwrap();
+        assert_eq!(history[0].delegation_id, Some(delegation.id));
+        assert_eq!(history[0].delegate_email.as_deref(), Some("carer@example.com"));
+
+        sqlx::query("UPDATE proof_delegations SET expires_at = NOW() - INTERVAL '1 minute' WHERE id = $1")
+            .bind(delegation.id)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let result = service.verify_proof(request(), None, None, None, None).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+        let attempts: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM proof_verifications WHERE proof_id = $1")
+            .bind(proof_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(attempts, 1);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_logged_verifications_reach_live_feed_subscribers(db: PgPool) {
+        let service = test_service(&db);
+        let (record_id, proof_data, verification_key) = seed_proof(&db).await;
+        let (owner_id, org_id): (Uuid, Uuid) =
+            sqlx::query_as("SELECT user_id, org_id FROM health_records WHERE id = $1")
+                .bind(record_id)
+                .fetch_one(&db)
+                .await
+                .unwrap();
+        let mut feed = service.subscribe_verifications();
+
+        let request = verify_request(&proof_data, &verification_key);
+        let response = service
+            .verify_proof(request, None, None, Some("203.0.113.9".parse().unwrap()), Some("Kiosk/1.0".to_string()))
+            .await
+            .unwrap();
+
+        let event = feed.try_recv().unwrap();
+        assert_eq!(Some(event.verification.proof_id), response.proof_id);
+        assert_eq!(event.owner_id, owner_id);
+        assert_eq!(event.org_id, org_id);
+
+        // Owners see their own proofs' verifications without client details
+        let seen = event.visible_to(Some(owner_id), Some(org_id)).unwrap();
+        assert!(seen.ip_address.is_none() && seen.user_agent.is_none());
+        assert!(event.visible_to(Some(Uuid::new_v4()), Some(org_id)).is_none());
+        // Admins see their organization's
+        let seen = event.visible_to(None, Some(org_id)).unwrap();
+        assert_eq!(seen.user_agent.as_deref(), Some("Kiosk/1.0"));
+        assert!(event.visible_to(None, Some(Uuid::new_v4())).is_none());
+        assert!(event.visible_to(None, None).is_some());
+    }
+
+    #[test]
+    fn test_expiry_dates_end_at_the_holders_midnight() {
+        use chrono::TimeZone;
+
+        let expiry_date = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let at = |h, m| Utc.with_ymd_and_hms(2024, 3, 10, h, m, 0).unwrap();
+        let day_after = |h, m| Utc.with_ymd_and_hms(2024, 3, 11, h, m, 0).unwrap();
+
+        // UTC: valid through 23:59 on the expiry date
+        assert!(!date_expired(expiry_date, day_after(0, 0) - Duration::seconds(1), None));
+        assert!(date_expired(expiry_date, day_after(0, 0), None));
+        assert!(date_expired(expiry_date, day_after(0, 0), Some(0)));
+
+        // UTC-5: still the 10th locally until 05:00 UTC on the 11th
+        assert!(!date_expired(expiry_date, day_after(4, 59), Some(-300)));
+        assert!(date_expired(expiry_date, day_after(5, 0), Some(-300)));
+
+        // UTC+9: the 10th ends locally at 15:00 UTC
+        assert!(!date_expired(expiry_date, at(14, 59), Some(540)));
+        assert!(date_expired(expiry_date, at(15, 0), Some(540)));
+
+        // Extreme zones disagree about the same instant
+        assert!(date_expired(expiry_date, at(10, 0), Some(840)));
+        assert!(!date_expired(expiry_date, day_after(10, 0), Some(-720)));
+        assert!(!date_expired(expiry_date, at(10, 0), Some(-720)));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_record_expiry_follows_client_timezone(db: PgPool) {
+        let service = test_service(&db);
+        let (record_id, proof_data, verification_key) = seed_proof(&db).await;
+
+        // Expired yesterday as seen from UTC+14, which is 26 hours ahead of UTC-12
+        let today_far_east = (Utc::now() + Duration::hours(14)).date_naive();
+        sqlx::query("UPDATE health_records SET expiry_date = $1 WHERE id = $2")
+            .bind(today_far_east - Duration::days(1))
+            .bind(record_id)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let request = |client_utc_offset_minutes| VerifyProofRequest {
+            client_utc_offset_minutes,
+            ..verify_request(&proof_data, &verification_key)
+        };
+
+        let response = service.verify_proof(request(Some(840)), None, None, None, None).await.unwrap();
+        assert!(!response.is_valid);
+        assert!(response.verification_details.is_expired);
+
+        // Twelve hours west of UTC it is at most the expiry date
+        let response = service.verify_proof(request(Some(-720)), None, None, None, None).await.unwrap();
+        assert!(response.is_valid);
+        assert!(!response.verification_details.is_expired);
+    }
+
+    #[test]
+    fn test_ips_are_truncated_and_hashed_per_salt() {
+        let v4: std::net::IpAddr = "203.0.113.77".parse().unwrap();
+        let v6: std::net::IpAddr = "2001:db8:abcd:12::1".parse().unwrap();
+        assert_eq!(truncate_ip(v4), "203.0.113.0/24");
+        assert_eq!(truncate_ip(v6), "2001:db8:abcd::/48");
+
+        let hash = salted_client_hash(b"salt-a", v4);
+        assert_eq!(hash, salted_client_hash(b"salt-a", v4));
+        assert_ne!(hash, salted_client_hash(b"salt-b", v4));
+
+        assert_eq!(csv_field("Clinic"), "Clinic");
+        assert_eq!(csv_field("Clinic, \"North\""), "\"Clinic, \"\"North\"\"\"");
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_analytics_export_strips_identifying_fields(db: PgPool) {
+        let service = test_service(&db);
+        let (_, proof_data, verification_key) = seed_proof(&db).await;
+
+        let request = verify_request(&proof_data, &verification_key);
+        service
+            .verify_proof(request, None, None, Some("203.0.113.77".parse().unwrap()), Some("SecretBrowser/1.0".to_string()))
+            .await
+            .unwrap();
+
+        let csv = service.export_verification_analytics(None, None, None).await.unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("verified_hour,"));
+        assert!(lines[1].contains("203.0.113.0/24"));
+        assert!(lines[1].contains(":00:00Z"));
+        assert!(!csv.contains("203.0.113.77"));
+        assert!(!csv.contains("SecretBrowser"));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_wallet_passes_are_built_for_owned_proofs(db: PgPool) {
+        use std::io::Read;
+
+        let service = test_service(&db);
+        let (record_id, _, _) = seed_proof(&db).await;
+        let (proof_id, owner_id): (Uuid, Uuid) = sqlx::query_as(
+            "SELECT zp.id, hr.user_id FROM zk_proofs zp JOIN health_records hr ON hr.id = zp.health_record_id WHERE hr.id = $1"
+        )
+        .bind(record_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let disabled = service.apple_wallet_pass(proof_id, owner_id).await;
+        assert!(matches!(disabled, Err(AppError::NotFound(message)) if message.contains("not enabled")));
+        let disabled = service.google_wallet_link(proof_id, owner_id).await;
+        assert!(matches!(disabled, Err(AppError::NotFound(message)) if message.contains("not enabled")));
+
+        let service = service.with_apple_wallet(ApplePassSigner::for_tests(), "https://pass.example.com/".to_string());
+        let bundle = service.apple_wallet_pass(proof_id, owner_id).await.unwrap();
+        let mut pass_json = String::new();
+        zip::ZipArchive::new(std::io::Cursor::new(bundle))
+            .unwrap()
+            .by_name("pass.json")
+            .unwrap()
+            .read_to_string(&mut pass_json)
+            .unwrap();
+        let pass: serde_json::Value = serde_json::from_str(&pass_json).unwrap();
+        assert_eq!(pass["serialNumber"], proof_id.to_string());
+        assert_eq!(pass["voided"], false);
+        assert_eq!(pass["barcodes"][0]["message"], format!("https://pass.example.com/api/v1/proofs/{}/status", proof_id));
+        assert_eq!(pass["generic"]["secondaryFields"][0]["value"], "Clinic");
+
+        let stranger = service.apple_wallet_pass(proof_id, Uuid::new_v4()).await;
+        assert!(matches!(stranger, Err(AppError::NotFound(_))));
+
+        let disabled = service.proof_pdf(proof_id, owner_id).await;
+        assert!(matches!(disabled, Err(AppError::NotFound(message)) if message.contains("not enabled")));
+        let service = service.with_public_base_url("https://pass.example.com".to_string());
+        assert!(service.proof_pdf(proof_id, owner_id).await.unwrap().starts_with(b"%PDF-"));
+        let stranger = service.proof_pdf(proof_id, Uuid::new_v4()).await;
+        assert!(matches!(stranger, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_omitted_expiry_and_usage_fall_back_to_defaults() {
+        let now = Utc::now();
+        let defaults = ProofDefaults { expiration_hours: 24, max_usage: Some(10) };
+        assert_eq!(defaults.expires_at(None, now), Some(now + Duration::hours(24)));
+        assert_eq!(defaults.max_usage(None).unwrap(), Some(10));
+
+        // Explicit values override the defaults
+        assert_eq!(defaults.expires_at(Some(2), now), Some(now + Duration::hours(2)));
+        assert_eq!(defaults.max_usage(Some(1)).unwrap(), Some(1));
+
+        let unlimited = ProofDefaults::default();
+        assert_eq!(unlimited.expires_at(None, now), None);
+        assert_eq!(unlimited.max_usage(None).unwrap(), None);
+        assert_eq!(unlimited.max_usage(Some(1000)).unwrap(), Some(1000));
+    }
+
+    #[test]
+    fn test_configured_max_usage_is_a_ceiling() {
+        let defaults = ProofDefaults { expiration_hours: 24, max_usage: Some(10) };
+        // Asking for unlimited usage gets the ceiling
+        assert_eq!(defaults.max_usage(None).unwrap(), Some(10));
+        assert_eq!(defaults.max_usage(Some(10)).unwrap(), Some(10));
+        assert!(matches!(defaults.max_usage(Some(11)), Err(AppError::BadRequest(message)) if message.contains("10")));
+    }
+
+    #[test]
+    fn test_pdf_pass_withholds_dates_of_not_expired_proofs() {
+        let mut proof = WalletProof {
+            proof_type: ProofType::EcdsaSignatureVerification,
+            expires_at: None,
+            record_type: HealthRecordType::Vaccination,
+            issue_date: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            authority_name: "City Clinic".to_string(),
+            status: ProofStatus::Active,
+        };
+        let facts = pdf_facts(&proof);
+        assert!(facts.contains(&("Issued on", "1 March 2025".to_string())));
+        assert!(facts.contains(&("Issued by", "City Clinic".to_string())));
+        assert!(facts.contains(&("Expires", "Never".to_string())));
+
+        proof.proof_type = ProofType::NotExpiredProof;
+        assert!(pdf_facts(&proof).iter().all(|(label, _)| *label != "Issued on"));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_audit_export_streams_filtered_verifications(db: PgPool) {
+        let service = test_service(&db);
+        let (_, proof_data, verification_key) = seed_proof(&db).await;
+
+        let request = verify_request(&proof_data, &verification_key);
+        service
+            .verify_proof(request, None, None, Some("203.0.113.77".parse().unwrap()), Some("AuditBrowser/1.0".to_string()))
+            .await
+            .unwrap();
+
+        let export = |query: VerificationExportQuery| {
+            let stream = service.export_verifications(query, None).unwrap();
+            async move { stream.try_collect::<Vec<String>>().await.unwrap().concat() }
+        };
+
+        let csv = export(VerificationExportQuery::default()).await;
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("verified_at,verification_result,"));
+        assert!(lines[1].contains("203.0.113.77"));
+        assert!(lines[1].contains("AuditBrowser/1.0"));
+
+        // The seeded proof bytes never verify
+        let failed = export(VerificationExportQuery { result: Some(false), ..Default::default() }).await;
+        assert_eq!(failed.lines().count(), 2);
+
+        // Filters that match nothing leave only the header
+        let successful = export(VerificationExportQuery { result: Some(true), ..Default::default() }).await;
+        assert_eq!(successful.lines().count(), 1);
+        let other_authority = export(VerificationExportQuery { authority_id: Some(Uuid::new_v4()), ..Default::default() }).await;
+        assert_eq!(other_authority.lines().count(), 1);
+
+        let backwards = VerificationExportQuery { from: Some(Utc::now()), to: Some(Utc::now() - Duration::days(1)), ..Default::default() };
+        assert!(matches!(service.export_verifications(backwards, None), Err(AppError::BadRequest(_))));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_p256_records_cannot_be_proven(db: PgPool) {
+        let service = test_service(&db);
+        let (record_id, _, _) = seed_proof(&db).await;
+        let user_id: Uuid = sqlx::query_scalar(
+            "UPDATE health_authorities SET curve = 'p256' WHERE id = (SELECT authority_id FROM health_records WHERE id = $1) RETURNING (SELECT user_id FROM health_records WHERE id = $1)"
+        )
+        .bind(record_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let request = GenerateProofRequest {
+            health_record_id: record_id,
+            health_record_ids: Vec::new(),
+            proof_type: None,
+            expires_in_hours: None,
+            max_usage: None,
+            proof_context: None,
+        };
+        let error = service.generate_proof(request, user_id).await.unwrap_err();
+        assert!(matches!(error, AppError::BadRequest(message) if message == P256_NOT_PROVABLE));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_not_expired_proofs_check_the_signed_expiry(db: PgPool) {
+        let service = test_service(&db);
+        let (record_id, _, _) = seed_proof(&db).await;
+        let expiry_date = Utc::now().date_naive() + Duration::days(30);
+        let record = sqlx::query_as::<_, HealthRecord>(
+            "UPDATE health_records SET expiry_date = $2 WHERE id = $1 RETURNING *"
+        )
+        .bind(record_id)
+        .bind(expiry_date)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        // Formats before 3 don't sign the expiry
+        let error = service.ensure_proof_type_applicable(&ProofType::NotExpiredProof, &record).unwrap_err();
+        assert!(matches!(error, AppError::BadRequest(message) if message.contains("message format 3")));
+
+        let record = HealthRecord { format_version: 3, ..record };
+        service.ensure_proof_type_applicable(&ProofType::NotExpiredProof, &record).unwrap();
+        let public_key = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let generated_at = Utc::now();
+        let prover_toml = service
+            .create_prover_toml(&ProofType::NotExpiredProof, &record, "Clinic", public_key, generated_at)
+            .unwrap();
+
+        // current_days is public and fixed by generated_at, so verification can recompute it
+        let public_inputs = |at| service.public_inputs(&ProofType::NotExpiredProof, &record, public_key, at).unwrap().to_bytes();
+        let today = public_inputs(generated_at);
+        assert_eq!(today[today.len() - 4..], message_format::epoch_days(generated_at.date_naive()).to_be_bytes());
+        assert_ne!(today, public_inputs(generated_at + Duration::days(1)));
+
+        // The private inputs hash to what format 3 signs for the record
+        let input = |name: &str| -> Vec<u8> {
+            let line = prover_toml.lines().find(|line| line.starts_with(&format!("{} = ", name))).unwrap();
+            line.split('"').filter(|part| part.starts_with("0x")).map(|byte| u8::from_str_radix(&byte[2..], 16).unwrap()).collect()
+        };
+        let (record_digest, expiry_days) = (input("record_digest"), input("expiry_days"));
+        assert_eq!(expiry_days, message_format::epoch_days(expiry_date).to_be_bytes());
+        let details = HealthRecordService::extract_details_for_signing(&record.details, &record.record_type).unwrap();
+        let issue_date = record.issue_date.to_string();
+        let signed_hash = message_format::formatter(3).unwrap().message_hash(&SignableFields {
+            record_type: &record.record_type,
+            patient_identifier: &record.patient_identifier,
+            details: &details,
+            issue_date: &issue_date,
+            issuer: "Clinic",
+            expiry_date: Some(expiry_date),
+        });
+        assert_eq!(Sha256::digest([record_digest, expiry_days].concat()).as_slice(), signed_hash);
+        assert!(!prover_toml.contains("msg_hash"));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_stub_backend_proofs_round_trip_without_nargo(db: PgPool) {
+        let service = ZkProofService::new(
+            db.clone(),
+            CryptoService::new(),
+            "/nonexistent/noir".to_string(),
+            WebhookService::new(db.clone()),
+        )
+        .with_proof_backend(Arc::new(StubProofBackend));
+        assert!(!service.uses_noir_toolchain());
+        let (record_id, _, _) = seed_proof(&db).await;
+        // Public key of the test private key 0x00..01
+        let user_id: Uuid = sqlx::query_scalar(
+            "UPDATE health_authorities SET public_key = '0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798' WHERE id = (SELECT authority_id FROM health_records WHERE id = $1) RETURNING (SELECT user_id FROM health_records WHERE id = $1)"
+        )
+        .bind(record_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let request = GenerateProofRequest {
+            health_record_id: record_id,
+            health_record_ids: Vec::new(),
+            proof_type: None,
+            expires_in_hours: None,
+            max_usage: None,
+            proof_context: None,
+        };
+        let proof = service.generate_proof(request, user_id).await.unwrap();
+        assert!(general_purpose::STANDARD.decode(&proof.proof_data).unwrap().starts_with(b"stub-proof:v1:health_passport_circuit:"));
+
+        let response = service
+            .verify_proof(
+                VerifyProofRequest {
+                    proof_data: proof.proof_data,
+                    verification_key: proof.verification_key,
+                    ..verify_request(&[], &[])
+                },
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(response.is_valid);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_proof_jobs_report_stages_and_resume(db: PgPool) {
+        use futures::StreamExt;
+
+        let service = Arc::new(
+            ZkProofService::new(
+                db.clone(),
+                CryptoService::new(),
+                "/nonexistent/noir".to_string(),
+                WebhookService::new(db.clone()),
+            )
+            .with_proof_backend(Arc::new(StubProofBackend)),
+        );
+        let (record_id, _, _) = seed_proof(&db).await;
+        let user_id: Uuid = sqlx::query_scalar(
+            "UPDATE health_authorities SET public_key = '0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798' WHERE id = (SELECT authority_id FROM health_records WHERE id = $1) RETURNING (SELECT user_id FROM health_records WHERE id = $1)"
+        )
+        .bind(record_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let request = |health_record_id| GenerateProofRequest {
+            health_record_id,
+            health_record_ids: Vec::new(),
+            proof_type: None,
+            expires_in_hours: None,
+            max_usage: None,
+            proof_context: None,
+        };
+
+        let job = service.start_proof_job(request(record_id), user_id).await.unwrap();
+        assert_eq!(job.stage, ProofJobStage::Queued);
+        let updates: Vec<ProofJob> = service.proof_job_updates(job.id, user_id, None).await.unwrap().collect().await;
+        let stages: Vec<ProofJobStage> = updates.iter().map(|job| job.stage).collect();
+        assert!(stages.windows(2).all(|pair| pair[0] < pair[1]));
+        let done = updates.last().unwrap();
+        assert_eq!(done.stage, ProofJobStage::Done);
+        let proof_id = done.result.as_ref().unwrap()["id"].as_str().unwrap();
+        assert!(Uuid::parse_str(proof_id).is_ok());
+
+        // A reconnecting client gets the final stage once, and nothing after seeing it
+        let resumed: Vec<ProofJob> =
+            service.proof_job_updates(job.id, user_id, Some(ProofJobStage::Proving)).await.unwrap().collect().await;
+        assert_eq!(resumed.iter().map(|job| job.stage).collect::<Vec<_>>(), vec![ProofJobStage::Done]);
+        assert_eq!(
+            service.proof_job_updates(job.id, user_id, Some(ProofJobStage::Done)).await.unwrap().count().await,
+            0
+        );
+        // Jobs are private to the user who started them
+        assert!(matches!(
+            service.proof_job_updates(job.id, Uuid::new_v4(), None).await.err(),
+            Some(AppError::NotFound(_))
+        ));
+
+        let job = service.start_proof_job(request(Uuid::new_v4()), user_id).await.unwrap();
+        let updates: Vec<ProofJob> = service.proof_job_updates(job.id, user_id, None).await.unwrap().collect().await;
+        let failed = updates.last().unwrap();
+        assert_eq!(failed.stage, ProofJobStage::Failed);
+        assert_eq!(failed.error_code.as_deref(), Some("NOT_FOUND"));
+        assert_eq!(failed.error_message.as_deref(), Some("Health record not found or access denied"));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_existed_before_requires_trusted_timestamp(db: PgPool) {
+        let service = test_service(&db);
+        let (_, proof_data, verification_key) = seed_proof(&db).await;
+
+        let request = VerifyProofRequest {
+            existed_before: Some(Utc::now()),
+            ..verify_request(&proof_data, &verification_key)
+        };
+
+        // `generated_at` alone is not evidence of when the proof existed
+        let response = service.verify_proof(request, None, None, None, None).await.unwrap();
+        assert!(!response.is_valid);
+        assert!(response.verification_details.timestamped_at.is_none());
+        assert!(response.failure_reason.unwrap().contains("trusted timestamp"));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_generated_proofs_are_submitted_on_chain(db: PgPool) {
+        let chain = Arc::new(blockchain::MockBlockchain::new());
+        let service = test_service(&db).with_blockchain(chain.clone());
+        let (record_id, proof_data, _) = seed_proof(&db).await;
+        // Public key of the test private key 0x00..01
+        let authority_key = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+        let zk_proof = sqlx::query_as::<_, ZkProof>("SELECT * FROM zk_proofs WHERE health_record_id = $1")
+            .bind(record_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let health_record = sqlx::query_as::<_, HealthRecord>("SELECT * FROM health_records WHERE id = $1")
+            .bind(record_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+
+        // Both generation paths hand the stored proof to `anchor_proof`; nargo
+        // isn't needed to exercise that step
+        let zk_proof = service.anchor_proof(zk_proof, &health_record, authority_key).await.unwrap();
+
+        let submissions = chain.submissions();
+        assert_eq!(submissions.len(), 1);
+        let proof_hash = chain_proof_hash(&proof_data);
+        assert_eq!(submissions[0].proof_hash, proof_hash);
+        assert_eq!(submissions[0].health_record_hash, "0x01");
+        assert_eq!(submissions[0].authority_address, blockchain::authority_address(authority_key).unwrap());
+        let tx_hash = zk_proof.anchor_tx_hash.expect("anchor transaction is stored");
+        assert!(chain.check_transaction_status(&tx_hash).await.unwrap());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_proofs_revoked_on_chain_stop_verifying(db: PgPool) {
+        let chain = Arc::new(blockchain::MockBlockchain::new());
+        let service = test_service(&db).with_blockchain(chain.clone());
+        let (record_id, proof_data, verification_key) = seed_proof(&db).await;
+        let health_record = sqlx::query_as::<_, HealthRecord>("SELECT * FROM health_records WHERE id = $1")
+            .bind(record_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let zk_proof = sqlx::query_as::<_, ZkProof>("SELECT * FROM zk_proofs WHERE health_record_id = $1")
+            .bind(record_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let authority_key = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        service.anchor_proof(zk_proof, &health_record, authority_key).await.unwrap();
+
+        let request = || verify_request(&proof_data, &verification_key);
+
+        let response = service.verify_proof(request(), None, None, None, None).await.unwrap();
+        assert_eq!(response.verification_details.anchored_on_chain, Some(true));
+
+        chain.revoke_zk_proof(&chain_proof_hash(&proof_data)).await.unwrap();
+        let response = service.verify_proof(request(), None, None, None, None).await.unwrap();
+        assert!(!response.is_valid);
+        assert_eq!(response.verification_details.anchored_on_chain, Some(false));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_revocation_list_is_signed_and_incremental(db: PgPool) {
+        let crypto_service = CryptoService::new();
+        let (signing_key, public_key) = crypto_service.generate_key_pair();
+        let service = test_service(&db).with_crl_signing_key(signing_key);
+        let (record_id, proof_data, _) = seed_proof(&db).await;
+        assert_eq!(service.revocation_list_version(None).await.unwrap(), (0, None));
+
+        service.revoke_proofs_for_record(record_id, Uuid::new_v4()).await.unwrap();
+
+        let list = service.revocation_list(None).await.unwrap();
+        let payload = general_purpose::STANDARD.decode(&list.payload).unwrap();
+        let signature = hex::decode(&list.signature).unwrap();
+        assert_eq!(list.public_key, hex::encode(public_key.serialize()));
+        assert!(crypto_service
+            .verify_message_signature(&Sha256::digest(&payload), &signature[..32], &signature[32..], &public_key)
+            .unwrap());
+
+        let payload: RevocationListPayload = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(payload.entries.len(), 1);
+        assert_eq!(payload.entries[0].proof_hash, hex::encode(Sha256::digest(&proof_data)));
+
+        // Polling from the latest revocation returns nothing new
+        let (count, last_revoked_at) = service.revocation_list_version(None).await.unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(service.revocation_list_version(last_revoked_at).await.unwrap(), (0, None));
+        let list = service.revocation_list(last_revoked_at).await.unwrap();
+        let payload: RevocationListPayload =
+            serde_json::from_slice(&general_purpose::STANDARD.decode(&list.payload).unwrap()).unwrap();
+        assert!(payload.entries.is_empty());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_proof_status_is_signed_and_tracks_revocation(db: PgPool) {
+        let crypto_service = CryptoService::new();
+        let (signing_key, public_key) = crypto_service.generate_key_pair();
+        let service = test_service(&db).with_crl_signing_key(signing_key);
+        let (record_id, _, _) = seed_proof(&db).await;
+        let proof_id: Uuid = sqlx::query_scalar("SELECT id FROM zk_proofs WHERE health_record_id = $1")
+            .bind(record_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+
+        let response = service.proof_status(proof_id).await.unwrap();
+        assert_eq!(response.status, LiveProofStatus::Valid);
+        let message = ProofStatusResponse::signed_message(proof_id, response.status, response.checked_at);
+        let signature = hex::decode(&response.signature).unwrap();
+        assert!(crypto_service
+            .verify_message_signature(&Sha256::digest(message.as_bytes()), &signature[..32], &signature[32..], &public_key)
+            .unwrap());
+
+        sqlx::query("UPDATE health_records SET is_revoked = TRUE WHERE id = $1")
+            .bind(record_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        assert_eq!(service.proof_status(proof_id).await.unwrap().status, LiveProofStatus::Revoked);
+
+        let error = service.proof_status(Uuid::new_v4()).await.unwrap_err();
+        assert!(matches!(error, AppError::NotFound(_)));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_public_revocation_endpoints_expose_only_opaque_ids(db: PgPool) {
+        let (signing_key, _) = CryptoService::new().generate_key_pair();
+        let service = test_service(&db).with_crl_signing_key(signing_key);
+        let (record_id, _, _) = seed_proof(&db).await;
+        let (proof_id, owner_id): (Uuid, Uuid) = sqlx::query_as(
+            "SELECT zp.id, hr.user_id FROM zk_proofs zp JOIN health_records hr ON hr.id = zp.health_record_id WHERE hr.id = $1"
+        )
+        .bind(record_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        service.revoke_proofs_for_record(record_id, owner_id).await.unwrap();
+
+        // Deliberately public across organizations, so nothing beyond the proof itself is listed
+        let list = service.revocation_list(None).await.unwrap();
+        let payload: serde_json::Value =
+            serde_json::from_slice(&general_purpose::STANDARD.decode(&list.payload).unwrap()).unwrap();
+        let entry = payload["entries"][0].as_object().unwrap();
+        let mut keys: Vec<_> = entry.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, ["proof_hash", "proof_id", "revoked_at"]);
+        assert_eq!(entry["proof_id"], json!(proof_id));
+
+        let status = serde_json::to_value(service.proof_status(proof_id).await.unwrap()).unwrap();
+        let mut keys: Vec<_> = status.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, ["checked_at", "proof_id", "public_key", "signature", "status"]);
+
+        for response in [payload.to_string(), status.to_string()] {
+            assert!(!response.contains(&record_id.to_string()));
+            assert!(!response.contains(&owner_id.to_string()));
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_proofs_verified_from_many_places_are_suspended(db: PgPool) {
+        let service = test_service(&db).with_anomaly_policy(AnomalyPolicy { window_secs: 3600, max_distinct_locations: 2, auto_suspend: true });
+        let (record_id, proof_data, verification_key) = seed_proof(&db).await;
+        let owner_id: Uuid = sqlx::query_scalar("SELECT user_id FROM health_records WHERE id = $1")
+            .bind(record_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+
+        let request = || verify_request(&proof_data, &verification_key);
+        let verify_from = |ip: &str| service.verify_proof(request(), None, None, Some(ip.parse().unwrap()), None);
+
+        // Repeat checks from one place are fine
+        assert!(verify_from("203.0.113.1").await.unwrap().is_valid);
+        assert!(verify_from("203.0.113.1").await.unwrap().is_valid);
+        assert!(verify_from("198.51.100.1").await.unwrap().is_valid);
+        assert!(service.get_proof_alerts(Some(owner_id), None).await.unwrap().is_empty());
+
+        // A third place within the window flags and suspends the proof
+        verify_from("192.0.2.1").await.unwrap();
+        let alerts = service.get_proof_alerts(Some(owner_id), None).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].distinct_locations, 3);
+        assert!(alerts[0].suspended);
+
+        let response = verify_from("203.0.113.1").await.unwrap();
+        assert!(!response.is_valid);
+        assert!(response.failure_reason.unwrap().contains("suspended"));
+        assert_eq!(service.get_proof_alerts(Some(owner_id), None).await.unwrap().len(), 1);
+
+        // Other users see nothing; resolving lifts the suspension
+        assert!(service.get_proof_alerts(Some(Uuid::new_v4()), None).await.unwrap().is_empty());
+        service.resolve_proof_alert(alerts[0].id, owner_id, None).await.unwrap();
+        let response = service.verify_proof(request(), None, None, None, None).await.unwrap();
+        assert!(response.failure_reason.map_or(true, |reason| !reason.contains("suspended")));
+    }
+
+    /// The seeded proof of `record_id` and the record's owner
+    async fn proof_and_owner(db: &PgPool, record_id: Uuid) -> (Uuid, Uuid) {
+        sqlx::query_as(
+            "SELECT zp.id, hr.user_id FROM zk_proofs zp JOIN health_records hr ON hr.id = zp.health_record_id WHERE hr.id = $1"
+        )
+        .bind(record_id)
+        .fetch_one(db)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_verification_history_is_readable_by_the_owner_only(db: PgPool) {
+        let service = test_service(&db);
+        let (record_id, proof_data, verification_key) = seed_proof(&db).await;
+        let (proof_id, owner_id) = proof_and_owner(&db, record_id).await;
+        let other_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash, full_name) VALUES ('other@example.com', 'x', 'Other') RETURNING id"
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let ip: std::net::IpAddr = "203.0.113.7".parse().unwrap();
+        for _ in 0..2 {
+            service
+                .verify_proof(verify_request(&proof_data, &verification_key), None, None, Some(ip), Some("kiosk/1.0".to_string()))
+                .await
+                .unwrap();
+        }
+
+        // Owners see when their proof was checked, but not the raw IPs and user agents
+        let history = service.get_verification_history(proof_id, Some(owner_id), None, 1, 20).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history.iter().all(|v| v.proof_id == proof_id && v.ip_address.is_none() && v.user_agent.is_none()));
+        assert!(history[0].verified_at >= history[1].verified_at);
+        assert_eq!(service.get_verification_history(proof_id, Some(owner_id), None, 2, 1).await.unwrap().len(), 1);
+
+        let raw = service.get_verification_history(proof_id, None, None, 1, 20).await.unwrap();
+        assert_eq!(raw[0].ip_address, Some(ip));
+        assert_eq!(raw[0].user_agent.as_deref(), Some("kiosk/1.0"));
+
+        for (user, org) in [(Some(other_id), None), (None, Some(Uuid::new_v4()))] {
+            assert!(matches!(
+                service.get_verification_history(proof_id, user, org, 1, 20).await,
+                Err(AppError::NotFound(_))
+            ));
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_verification_stats_count_outcomes_in_range(db: PgPool) {
+        let service = test_service(&db);
+        let (record_id, _, _) = seed_proof(&db).await;
+        let (proof_id, owner_id) = proof_and_owner(&db, record_id).await;
+        sqlx::query(
+            r#"
+            INSERT INTO proof_verifications (proof_id, verification_result, verified_at)
+            VALUES ($1, TRUE, NOW()), ($1, TRUE, NOW()), ($1, FALSE, NOW()), ($1, TRUE, NOW() - INTERVAL '10 days')
+            "#
+        )
+        .bind(proof_id)
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let stats = service.verification_stats(None, None, Some(owner_id), None).await.unwrap();
+        assert_eq!((stats.total_verifications, stats.successful_verifications, stats.failed_verifications), (4, 3, 1));
+        assert_eq!(stats.success_rate, 0.75);
+        assert_eq!(stats.by_authority.len(), 1);
+        assert_eq!(stats.by_authority[0].key, "Clinic");
+        assert_eq!(stats.by_authority[0].total_verifications, 4);
+        assert_eq!(stats.by_record_type.len(), 1);
+        assert_eq!(stats.by_record_type[0].key, "vaccination");
+        assert_eq!(stats.by_record_type[0].successful_verifications, 3);
+
+        // `from` is inclusive and `to` exclusive
+        let from = Utc::now() - Duration::days(1);
+        let stats = service.verification_stats(Some(from), None, Some(owner_id), None).await.unwrap();
+        assert_eq!((stats.total_verifications, stats.successful_verifications), (3, 2));
+        let stats = service.verification_stats(None, Some(from), None, None).await.unwrap();
+        assert_eq!((stats.total_verifications, stats.successful_verifications), (1, 1));
+
+        // Other users and organizations see none of it
+        let stats = service.verification_stats(None, None, Some(Uuid::new_v4()), None).await.unwrap();
+        assert_eq!(stats.total_verifications, 0);
+        assert_eq!(stats.success_rate, 0.0);
+        assert!(stats.by_authority.is_empty());
+        assert_eq!(service.verification_stats(None, None, None, Some(Uuid::new_v4())).await.unwrap().total_verifications, 0);
+
+        assert!(matches!(
+            service.verification_stats(Some(Utc::now()), Some(from), None, None).await,
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_user_proofs_filter_by_derived_status(db: PgPool) {
+        let service = test_service(&db);
+        let (record_id, _, _) = seed_proof(&db).await;
+        let (expired_id, owner_id) = proof_and_owner(&db, record_id).await;
+        let proofs_with = |status| service.get_user_proofs(owner_id, 1, 20, None, status, ProofSort::default());
+
+        let all = proofs_with(None).await.unwrap().data;
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].status, ProofStatus::Active);
+
+        sqlx::query("UPDATE zk_proofs SET expires_at = NOW() - INTERVAL '1 hour' WHERE id = $1")
+            .bind(expired_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        let used_up_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO zk_proofs (health_record_id, proof_data, verification_key, proof_type, usage_count, max_usage)
+            VALUES ($1, '\x070809', '\x0a', $2, 1, 1)
+            RETURNING id
+            "#
+        )
+        .bind(record_id)
+        .bind(ProofType::EcdsaSignatureVerification)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let ids = |proofs: Vec<ProofResponse>| proofs.into_iter().map(|p| p.id).collect::<Vec<_>>();
+        assert!(proofs_with(Some(ProofStatus::Active)).await.unwrap().data.is_empty());
+        assert_eq!(ids(proofs_with(Some(ProofStatus::Expired)).await.unwrap().data), [expired_id]);
+        assert_eq!(ids(proofs_with(Some(ProofStatus::UsedUp)).await.unwrap().data), [used_up_id]);
+
+        // Revocation of the record takes precedence over expiry and usage
+        sqlx::query("UPDATE health_records SET is_revoked = TRUE WHERE id = $1")
+            .bind(record_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        assert!(proofs_with(Some(ProofStatus::Expired)).await.unwrap().data.is_empty());
+        assert!(proofs_with(Some(ProofStatus::UsedUp)).await.unwrap().data.is_empty());
+        let revoked = proofs_with(Some(ProofStatus::Revoked)).await.unwrap().data;
+        assert_eq!(revoked.len(), 2);
+        assert!(revoked.iter().all(|p| p.status == ProofStatus::Revoked));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_proofs_are_found_by_hash_and_matched_in_full(db: PgPool) {
+        let service = test_service(&db);
+        let (record_id, proof_data, verification_key) = seed_proof(&db).await;
+        let (proof_id, _) = proof_and_owner(&db, record_id).await;
+        let proof_hash: Vec<u8> = sqlx::query_scalar("SELECT proof_hash FROM zk_proofs WHERE id = $1")
+            .bind(proof_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(proof_hash, Sha256::digest(&proof_data).to_vec());
+
+        // A second proof under the same hash, told apart by the full comparison
+        let other_key = vec![9u8, 9, 9];
+        let other_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO zk_proofs (health_record_id, proof_data, verification_key, proof_type) VALUES ($1, $2, $3, $4) RETURNING id"
+        )
+        .bind(record_id)
+        .bind(&proof_data)
+        .bind(&other_key)
+        .bind(ProofType::EcdsaSignatureVerification)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let found = |key: &[u8]| service.verify_proof(verify_request(&proof_data, key), None, None, None, None);
+        assert_eq!(found(&verification_key).await.unwrap().proof_id, Some(proof_id));
+        assert_eq!(found(&other_key).await.unwrap().proof_id, Some(other_id));
+
+        let response = found(&[1, 1, 1]).await.unwrap();
+        assert_eq!(response.proof_id, None);
+        assert!(!response.is_valid);
+        let response = service
+            .verify_proof(verify_request(&[1, 2, 4], &verification_key), None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(response.proof_id, None);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_proofs_of_another_type_are_rejected_with_a_reason(db: PgPool) {
+        let service = test_service(&db);
+        let (_, proof_data, verification_key) = seed_proof(&db).await;
+
+        let request = VerifyProofRequest {
+            proof_type: ProofType::RecordTypeProof,
+            ..verify_request(&proof_data, &verification_key)
+        };
+        let response = service.verify_proof(request, None, None, None, None).await.unwrap();
+        assert!(!response.is_valid);
+        assert_eq!(response.failure_reason.as_deref(), Some("Proof was generated for a different proof type"));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_proofs_must_commit_to_their_record_and_authority(db: PgPool) {
+        let service = test_service(&db);
+        let (record_id, proof_data, verification_key) = seed_proof(&db).await;
+        let verify = || service.verify_proof(verify_request(&proof_data, &verification_key), None, None, None, None);
+        assert!(verify().await.unwrap().is_valid);
+
+        // A sound proof whose public message hash isn't the record's
+        sqlx::query("UPDATE health_records SET message_hash = $2 WHERE id = $1")
+            .bind(record_id)
+            .bind([2u8; 32].as_slice())
+            .execute(&db)
+            .await
+            .unwrap();
+        let response = verify().await.unwrap();
+        assert!(!response.is_valid);
+        assert_eq!(response.failure_reason.as_deref(), Some("Proof does not verify against its health record and authority"));
+
+        // Or whose public key isn't the authority's (the key of private key 0x00..02)
+        sqlx::query("UPDATE health_records SET message_hash = $2 WHERE id = $1")
+            .bind(record_id)
+            .bind([1u8; 32].as_slice())
+            .execute(&db)
+            .await
+            .unwrap();
+        assert!(verify().await.unwrap().is_valid);
+        sqlx::query(
+            "UPDATE health_authorities SET public_key = '02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5' WHERE id = (SELECT authority_id FROM health_records WHERE id = $1)"
+        )
+        .bind(record_id)
+        .execute(&db)
+        .await
+        .unwrap();
+        assert!(!verify().await.unwrap().is_valid);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_aggregate_proofs_commit_to_every_record(db: PgPool) {
+        let service = test_service(&db);
+        let (record_id, _, _) = seed_proof(&db).await;
+        let (_, owner_id) = proof_and_owner(&db, record_id).await;
+        let second_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO health_records (user_id, authority_id, record_type, patient_identifier, details, issue_date, signature_r, signature_s, message_hash)
+            SELECT user_id, authority_id, 'test_result', 'P-1', '{}', CURRENT_DATE, '\x01', '\x01', $2 FROM health_records WHERE id = $1
+            RETURNING id
+            "#
+        )
+        .bind(record_id)
+        .bind([3u8; 32].as_slice())
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let request = GenerateProofRequest {
+            health_record_id: record_id,
+            health_record_ids: vec![second_id],
+            proof_type: None,
+            expires_in_hours: None,
+            max_usage: None,
+            proof_context: None,
+        };
+        let proof = service.generate_proof(request, owner_id).await.unwrap();
+        let proof_data = general_purpose::STANDARD.decode(&proof.proof_data).unwrap();
+        let verification_key = general_purpose::STANDARD.decode(&proof.verification_key).unwrap();
+        let verify = || {
+            service.verify_proof(
+                VerifyProofRequest {
+                    proof_type: ProofType::AggregateSignatureVerification,
+                    ..verify_request(&proof_data, &verification_key)
+                },
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+        assert!(verify().await.unwrap().is_valid);
+
+        // Every slot's hash is public, so a changed constituent no longer matches
+        sqlx::query("UPDATE health_records SET message_hash = $2 WHERE id = $1")
+            .bind(second_id)
+            .bind([4u8; 32].as_slice())
+            .execute(&db)
+            .await
+            .unwrap();
+        let response = verify().await.unwrap();
+        assert!(!response.is_valid);
+        assert_eq!(response.failure_reason.as_deref(), Some("Proof does not verify against its health record and authority"));
+    }
 }
@@ -0,0 +1,152 @@
+use reqwest::blocking::{Client, RequestBuilder};
+use serde_json::{json, Value};
+
+use crate::cli::ApiAction;
+use crate::health_records::{HealthRecord, HealthRecordTemplates, HealthRecordType};
+
+/// Minimal blocking client for the backend's REST API
+pub struct ApiClient {
+    base_url: String,
+    token: Option<String>,
+    client: Client,
+}
+
+impl ApiClient {
+    pub fn new(base_url: &str, token: Option<&str>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.map(str::to_string),
+            client: Client::new(),
+        }
+    }
+
+    pub fn login(&self, email: &str, password: &str) -> Result<Value, String> {
+        self.send(
+            self.client
+                .post(self.url("/api/v1/auth/login"))
+                .json(&json!({ "email": email, "password": password })),
+        )
+    }
+
+    pub fn create_record(&self, body: &Value) -> Result<Value, String> {
+        self.send(self.authorized(self.client.post(self.url("/api/v1/health-records")))?.json(body))
+    }
+
+    pub fn generate_proof(&self, body: &Value) -> Result<Value, String> {
+        self.send(self.authorized(self.client.post(self.url("/api/v1/proofs/generate")))?.json(body))
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> Result<RequestBuilder, String> {
+        let token = self
+            .token
+            .as_deref()
+            .ok_or("This command needs --token (or ZK_HEALTH_API_TOKEN); get one with `api login`")?;
+        Ok(request.bearer_auth(token))
+    }
+
+    /// Send the request and return its JSON body, failing on non-2xx statuses
+    /// with the backend's error body in the message
+    fn send(&self, request: RequestBuilder) -> Result<Value, String> {
+        let response = request.send().map_err(|e| format!("Request failed: {}", e))?;
+        let status = response.status();
+        let text = response.text().map_err(|e| format!("Failed to read response: {}", e))?;
+        let body = serde_json::from_str(&text).unwrap_or(Value::String(text));
+
+        if status.is_success() {
+            Ok(body)
+        } else {
+            Err(format!("Backend returned {}: {}", status, body))
+        }
+    }
+}
+
+/// Body for `POST /api/v1/health-records` built from a local record
+pub fn create_record_body(record: &HealthRecord, authority_id: &str, issue_date: &str) -> Value {
+    json!({
+        "authority_id": authority_id,
+        "record_type": record.record_type.api_name(),
+        "patient_identifier": record.patient_id,
+        "details": {
+            "details": record.details,
+            "issuer": record.issuer,
+        },
+        "issue_date": issue_date,
+    })
+}
+
+pub fn run(base_url: &str, token: Option<&str>, action: ApiAction) -> Result<(), String> {
+    let client = ApiClient::new(base_url, token);
+
+    let response = match action {
+        ApiAction::Login { email, password } => client.login(&email, &password)?,
+        ApiAction::CreateRecord { authority_id, template, patient_id, details, record_type, issuer, issue_date } => {
+            let record = match template {
+                Some(name) => HealthRecordTemplates::get_templates()
+                    .remove(name.as_str())
+                    .ok_or_else(|| format!(
+                        "Template '{}' not found. Available templates: {:?}",
+                        name,
+                        HealthRecordTemplates::list_available()
+                    ))?,
+                None => {
+                    // clap requires these when no template is given
+                    let record_type = record_type.unwrap_or_default();
+                    let rt = HealthRecordType::from_cli_name(&record_type).ok_or_else(|| format!(
+                        "Invalid record type: {}. Valid types: vaccination, test, clearance, immunity",
+                        record_type
+                    ))?;
+                    HealthRecord::new(
+                        rt,
+                        patient_id.unwrap_or_default(),
+                        details.unwrap_or_default(),
+                        issue_date.clone().unwrap_or_default(),
+                        issuer,
+                    )
+                }
+            };
+            let issue_date = issue_date.unwrap_or_else(|| record.date.clone());
+
+            client.create_record(&create_record_body(&record, &authority_id, &issue_date))?
+        }
+        ApiAction::GenerateProof { record_id, proof_type, expires_in_hours, max_usage } => {
+            client.generate_proof(&json!({
+                "health_record_id": record_id,
+                "proof_type": proof_type,
+                "expires_in_hours": expires_in_hours,
+                "max_usage": max_usage,
+            }))?
+        }
+    };
+
+    let pretty = serde_json::to_string_pretty(&response).map_err(|e| e.to_string())?;
+    println!("{}", pretty);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_record_body_matches_backend_request() {
+        let record = HealthRecordTemplates::get_templates().remove("negative_test").unwrap();
+        let body = create_record_body(&record, "3f0c6a4e-0000-0000-0000-000000000001", "2025-09-27");
+
+        assert_eq!(body["record_type"], "TestResult");
+        assert_eq!(body["patient_identifier"], "Patient456");
+        assert_eq!(body["details"]["details"], "COVID19_Negative");
+        assert_eq!(body["details"]["issuer"], "TestLab");
+        assert_eq!(body["issue_date"], "2025-09-27");
+    }
+
+    #[test]
+    fn test_base_url_trailing_slash_is_ignored() {
+        let client = ApiClient::new("http://localhost:3000/", None);
+        assert_eq!(client.url("/api/v1/auth/login"), "http://localhost:3000/api/v1/auth/login");
+        assert!(client.authorized(client.client.post(client.url("/x"))).is_err());
+    }
+}
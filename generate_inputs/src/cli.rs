@@ -1,10 +1,18 @@
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::style::ColorChoice;
 
 #[derive(Parser)]
 #[command(name = "zk-health-generator")]
 #[command(about = "Generate ECDSA inputs for ZK Health Pass verification")]
 #[command(version = "1.0")]
 pub struct Cli {
+    /// When to use icons and colors in status output
+    #[arg(long, value_enum, global = true, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -37,6 +45,150 @@ pub enum Commands {
     },
     /// List available templates
     List,
+    /// Prompt for a health record step by step
+    Wizard,
     /// Generate with default example (for backward compatibility)
     Default,
+    /// Generate a new secp256k1 keypair for a health authority
+    Keygen {
+        /// Also write the keys to this JSON file
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// Sign the SHA-256 hash of an arbitrary message
+    Sign {
+        /// Message to hash and sign
+        #[arg(short, long)]
+        message: String,
+        /// Private key hex (with or without 0x)
+        #[arg(short, long)]
+        private_key: String,
+    },
+    /// Check a signature over the SHA-256 hash of a message
+    VerifySig {
+        /// Message that was hashed and signed
+        #[arg(short, long)]
+        message: String,
+        /// Signature R as hex
+        #[arg(long, requires = "signature_s", required_unless_present = "signature")]
+        signature_r: Option<String>,
+        /// Signature S as hex
+        #[arg(long, requires = "signature_r")]
+        signature_s: Option<String>,
+        /// Compact signature (R || S, 64 bytes) as base64, instead of --signature-r/--signature-s
+        #[arg(long, conflicts_with_all = ["signature_r", "signature_s"])]
+        signature: Option<String>,
+        /// Public key X coordinate as hex
+        #[arg(long)]
+        pubkey_x: String,
+        /// Public key Y coordinate as hex
+        #[arg(long)]
+        pubkey_y: String,
+    },
+    /// Rewrite a signature made elsewhere in the low-S form Noir requires
+    Normalize {
+        /// Signature R as hex
+        #[arg(long, requires = "signature_s", required_unless_present = "signature")]
+        signature_r: Option<String>,
+        /// Signature S as hex
+        #[arg(long, requires = "signature_r")]
+        signature_s: Option<String>,
+        /// Compact signature (R || S, 64 bytes) as base64, instead of --signature-r/--signature-s
+        #[arg(long, conflicts_with_all = ["signature_r", "signature_s"])]
+        signature: Option<String>,
+    },
+    /// Call a running ZK Health Pass backend and print its JSON responses
+    Api {
+        /// Backend base URL
+        #[arg(long, env = "ZK_HEALTH_API_URL", default_value = "http://localhost:3000")]
+        base_url: String,
+        /// Bearer token, as printed by `api login`
+        #[arg(long, env = "ZK_HEALTH_API_TOKEN")]
+        token: Option<String>,
+        #[command(subcommand)]
+        action: ApiAction,
+    },
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to complete for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+}
+
+/// Write `shell`'s completion script for this binary. It is generated from
+/// `Cli`, so new subcommands and flags are completed without further changes.
+pub fn write_completions(shell: Shell, out: &mut dyn Write) {
+    clap_complete::generate(shell, &mut Cli::command(), env!("CARGO_BIN_NAME"), out);
+}
+
+#[derive(Subcommand)]
+pub enum ApiAction {
+    /// Log in and print the session token
+    Login {
+        #[arg(short, long)]
+        email: String,
+        #[arg(short, long)]
+        password: String,
+    },
+    /// Create a health record from a template or custom fields
+    CreateRecord {
+        /// Issuing authority ID
+        #[arg(short, long)]
+        authority_id: String,
+        /// Template name (see `list`); the custom fields below are ignored when set
+        #[arg(short, long)]
+        template: Option<String>,
+        /// Patient ID
+        #[arg(long, required_unless_present = "template")]
+        patient_id: Option<String>,
+        /// Health record details
+        #[arg(short, long, required_unless_present = "template")]
+        details: Option<String>,
+        /// Record type (vaccination, test, clearance, immunity)
+        #[arg(short, long, required_unless_present = "template")]
+        record_type: Option<String>,
+        /// Issuer
+        #[arg(short, long, default_value = "HealthAuthority")]
+        issuer: String,
+        /// Issue date (YYYY-MM-DD); defaults to the template's date
+        #[arg(long, required_unless_present = "template")]
+        issue_date: Option<String>,
+    },
+    /// Generate a proof for a signed health record
+    GenerateProof {
+        /// Health record ID
+        #[arg(short, long)]
+        record_id: String,
+        /// Proof type, e.g. EcdsaSignatureVerification (the backend default)
+        #[arg(short, long)]
+        proof_type: Option<String>,
+        /// Hours until the proof expires
+        #[arg(long)]
+        expires_in_hours: Option<u32>,
+        /// Maximum number of verifications
+        #[arg(long)]
+        max_usage: Option<i32>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completions_cover_every_subcommand() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut script = Vec::new();
+            write_completions(shell, &mut script);
+            let script = String::from_utf8(script).unwrap();
+
+            assert!(script.contains(env!("CARGO_BIN_NAME")), "{}", shell);
+            let command = Cli::command();
+            let api = command.find_subcommand("api").unwrap();
+            for subcommand in command.get_subcommands().chain(api.get_subcommands()) {
+                assert!(script.contains(subcommand.get_name()), "{} lacks {}", shell, subcommand.get_name());
+            }
+        }
+    }
 }
@@ -9,6 +9,29 @@ pub enum HealthRecordType {
     ImmunityProof,
 }
 
+impl HealthRecordType {
+    /// Parse the short names accepted on the command line
+    pub fn from_cli_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "vaccination" => Some(HealthRecordType::Vaccination),
+            "test" => Some(HealthRecordType::TestResult),
+            "clearance" => Some(HealthRecordType::MedicalClearance),
+            "immunity" => Some(HealthRecordType::ImmunityProof),
+            _ => None,
+        }
+    }
+
+    /// Name of the type in the backend's JSON API
+    pub fn api_name(&self) -> &'static str {
+        match self {
+            HealthRecordType::Vaccination => "Vaccination",
+            HealthRecordType::TestResult => "TestResult",
+            HealthRecordType::MedicalClearance => "MedicalClearance",
+            HealthRecordType::ImmunityProof => "ImmunityProof",
+        }
+    }
+}
+
 /// Health record structure
 #[derive(Debug, Clone)]
 pub struct HealthRecord {
@@ -36,7 +59,7 @@ impl HealthRecord {
         }
     }
 
-    /// Format the health record for signing
+    /// Format the health record for signing (the backend's message format version 1)
     pub fn to_signable_string(&self) -> String {
         let type_str = match self.record_type {
             HealthRecordType::Vaccination => "VaxRecord",
@@ -0,0 +1,388 @@
+use base64::{Engine as _, engine::general_purpose};
+use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1, SecretKey};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+use crate::style;
+
+/// A keypair in the hex formats the backend and circuits use
+#[derive(Debug, Serialize)]
+pub struct KeyPairHex {
+    /// 32-byte private key
+    pub private_key: String,
+    /// 65-byte uncompressed public key (`04 || x || y`), as taken by `POST /api/v1/authorities`
+    pub public_key: String,
+    pub pubkey_x: String,
+    pub pubkey_y: String,
+}
+
+impl KeyPairHex {
+    pub fn new(secret_key: &SecretKey, public_key: &PublicKey) -> Self {
+        let uncompressed = public_key.serialize_uncompressed();
+        Self {
+            private_key: hex::encode(secret_key.secret_bytes()),
+            public_key: hex::encode(uncompressed),
+            pubkey_x: hex::encode(&uncompressed[1..33]),
+            pubkey_y: hex::encode(&uncompressed[33..65]),
+        }
+    }
+}
+
+/// Generate a random keypair, print it and optionally write it to `out` as JSON
+pub fn keygen(out: Option<&Path>) -> Result<(), String> {
+    let secp = Secp256k1::new();
+    let (secret_key, public_key) = secp.generate_keypair(&mut secp256k1::rand::thread_rng());
+    let keys = KeyPairHex::new(&secret_key, &public_key);
+
+    style::status("🔑", "Generated secp256k1 keypair");
+    println!("  • Private key: {}", keys.private_key);
+    println!("  • Public key (uncompressed): {}", keys.public_key);
+    println!("  • Public key X: {}", keys.pubkey_x);
+    println!("  • Public key Y: {}", keys.pubkey_y);
+    println!();
+    style::warning("Keep the private key secret; register only the public key with the backend.");
+
+    if let Some(path) = out {
+        let json = serde_json::to_string_pretty(&keys).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        style::status("📁", format!("Keys written to {}", path.display()));
+    }
+
+    Ok(())
+}
+
+/// A normalized (low-S) signature over the SHA-256 hash of a message
+#[derive(Debug)]
+pub struct SignedMessage {
+    pub msg_hash: [u8; 32],
+    pub signature_r: [u8; 32],
+    pub signature_s: [u8; 32],
+}
+
+/// Bytes as a Prover.toml array of quoted hex strings, e.g. `["0x01", "0xff"]`
+pub fn noir_byte_array(bytes: &[u8]) -> String {
+    let hex_values: Vec<String> = bytes.iter().map(|b| format!("\"0x{:02x}\"", b)).collect();
+    format!("[{}]", hex_values.join(", "))
+}
+
+pub fn parse_private_key(private_key_hex: &str) -> Result<SecretKey, String> {
+    let bytes = hex::decode(private_key_hex.trim_start_matches("0x"))
+        .map_err(|_| "Invalid private key hex format".to_string())?;
+    SecretKey::from_slice(&bytes).map_err(|_| "Invalid private key".to_string())
+}
+
+/// Sign a hash with an RFC 6979 nonce and normalize S as Noir requires.
+///
+/// The nonce is derived from the key and the hash rather than drawn at random,
+/// so the same inputs give the same `(r, s)` on every run and platform, and
+/// generated Prover.toml files can be compared byte for byte in golden tests.
+pub fn sign_deterministic(msg_hash: &[u8; 32], secret_key: &SecretKey) -> Signature {
+    let message_obj = Message::from_digest_slice(msg_hash).expect("32 bytes");
+    let mut signature = Secp256k1::signing_only().sign_ecdsa(&message_obj, secret_key);
+    signature.normalize_s();
+    signature
+}
+
+/// Hash `message` with SHA-256 and sign the hash with [`sign_deterministic`].
+/// The signature is verified before it is returned.
+pub fn sign_message(message: &str, secret_key: &SecretKey) -> Result<SignedMessage, String> {
+    let secp = Secp256k1::new();
+    let msg_hash: [u8; 32] = Sha256::digest(message.as_bytes()).into();
+    let message_obj = Message::from_digest_slice(&msg_hash).expect("32 bytes");
+    let signature = sign_deterministic(&msg_hash, secret_key);
+
+    let public_key = PublicKey::from_secret_key(&secp, secret_key);
+    secp.verify_ecdsa(&message_obj, &signature, &public_key)
+        .map_err(|e| format!("Signature verification failed: {:?}", e))?;
+
+    let compact = signature.serialize_compact();
+    let mut signature_r = [0u8; 32];
+    let mut signature_s = [0u8; 32];
+    signature_r.copy_from_slice(&compact[..32]);
+    signature_s.copy_from_slice(&compact[32..]);
+
+    Ok(SignedMessage { msg_hash, signature_r, signature_s })
+}
+
+/// Sign `message` and print the hash and signature as hex and Noir arrays
+pub fn sign(message: &str, private_key_hex: &str) -> Result<(), String> {
+    let signed = sign_message(message, &parse_private_key(private_key_hex)?)?;
+
+    style::status("📝", format!("Message: '{}'", message));
+    style::success("Signature verified successfully in Rust");
+    style::status("🔁", "RFC 6979 nonce: the same message and key always give this signature");
+    style::section("📊", "Hex:");
+    println!("  • msg_hash: 0x{}", hex::encode(signed.msg_hash));
+    println!("  • signature_r: 0x{}", hex::encode(signed.signature_r));
+    println!("  • signature_s: 0x{}", hex::encode(signed.signature_s));
+    style::section("📄", "Noir arrays:");
+    println!("msg_hash = {}", noir_byte_array(&signed.msg_hash));
+    println!("signature_r = {}", noir_byte_array(&signed.signature_r));
+    println!("signature_s = {}", noir_byte_array(&signed.signature_s));
+
+    Ok(())
+}
+
+/// A signature as given on the command line
+pub enum SignatureInput {
+    Hex { r: String, s: String },
+    /// Compact `R || S` encoding
+    Base64(String),
+}
+
+impl SignatureInput {
+    /// From the `--signature-r`/`--signature-s` or `--signature` flags
+    pub fn from_args(signature_r: Option<String>, signature_s: Option<String>, signature: Option<String>) -> Self {
+        match signature {
+            Some(compact) => SignatureInput::Base64(compact),
+            // clap requires both halves when --signature is absent
+            None => SignatureInput::Hex {
+                r: signature_r.unwrap_or_default(),
+                s: signature_s.unwrap_or_default(),
+            },
+        }
+    }
+
+    pub fn to_compact(&self) -> Result<[u8; 64], String> {
+        let mut compact = [0u8; 64];
+        match self {
+            SignatureInput::Hex { r, s } => {
+                compact[..32].copy_from_slice(&decode_hex_32("signature_r", r)?);
+                compact[32..].copy_from_slice(&decode_hex_32("signature_s", s)?);
+            }
+            SignatureInput::Base64(blob) => {
+                let bytes = general_purpose::STANDARD
+                    .decode(blob.trim())
+                    .map_err(|_| "Invalid base64 signature".to_string())?;
+                if bytes.len() != 64 {
+                    return Err(format!("Compact signature must be 64 bytes, got {}", bytes.len()));
+                }
+                compact.copy_from_slice(&bytes);
+            }
+        }
+        Ok(compact)
+    }
+}
+
+fn decode_hex_32(name: &str, value: &str) -> Result<[u8; 32], String> {
+    let bytes = hex::decode(value.trim_start_matches("0x")).map_err(|_| format!("Invalid {} hex format", name))?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| format!("{} must be 32 bytes, got {}", name, bytes.len()))
+}
+
+/// Outcome of checking a signature the way the circuit would
+#[derive(Debug, PartialEq, Eq)]
+pub struct SignatureCheck {
+    /// The signature matches the message hash and key (ignoring the S range)
+    pub verified: bool,
+    /// S is in the lower half of the curve order, as Noir requires
+    pub low_s: bool,
+}
+
+pub fn check_signature(
+    message: &str,
+    signature: &SignatureInput,
+    pubkey_x: &str,
+    pubkey_y: &str,
+) -> Result<SignatureCheck, String> {
+    let mut public_key_bytes = [0u8; 65];
+    public_key_bytes[0] = 0x04;
+    public_key_bytes[1..33].copy_from_slice(&decode_hex_32("pubkey_x", pubkey_x)?);
+    public_key_bytes[33..].copy_from_slice(&decode_hex_32("pubkey_y", pubkey_y)?);
+    let public_key = PublicKey::from_slice(&public_key_bytes)
+        .map_err(|_| "Public key is not a point on secp256k1".to_string())?;
+
+    let signature = Signature::from_compact(&signature.to_compact()?)
+        .map_err(|_| "Invalid signature encoding".to_string())?;
+    let mut normalized = signature;
+    normalized.normalize_s();
+    let low_s = normalized == signature;
+
+    let msg_hash: [u8; 32] = Sha256::digest(message.as_bytes()).into();
+    let message_obj = Message::from_digest_slice(&msg_hash).expect("32 bytes");
+
+    // libsecp256k1 only accepts low-S signatures, so check the normalized form
+    // and report the S range separately
+    let verified = Secp256k1::verification_only()
+        .verify_ecdsa(&message_obj, &normalized, &public_key)
+        .is_ok();
+
+    Ok(SignatureCheck { verified, low_s })
+}
+
+/// The low-S form of `signature`, and whether it had to change. `(r, n - s)`
+/// verifies for the same message and key as `(r, s)`, so no re-signing is needed.
+pub fn normalize_signature(signature: &SignatureInput) -> Result<([u8; 32], [u8; 32], bool), String> {
+    let original = Signature::from_compact(&signature.to_compact()?)
+        .map_err(|_| "Invalid signature encoding".to_string())?;
+    let mut normalized = original;
+    normalized.normalize_s();
+
+    let compact = normalized.serialize_compact();
+    let mut signature_r = [0u8; 32];
+    let mut signature_s = [0u8; 32];
+    signature_r.copy_from_slice(&compact[..32]);
+    signature_s.copy_from_slice(&compact[32..]);
+
+    Ok((signature_r, signature_s, normalized != original))
+}
+
+/// Print the low-S form of `signature` as hex, base64 and Noir arrays
+pub fn normalize(signature: &SignatureInput) -> Result<(), String> {
+    let (signature_r, signature_s, changed) = normalize_signature(signature)?;
+    let compact = [signature_r, signature_s].concat();
+
+    if changed {
+        style::success("Replaced high S with n - S; the signature still verifies for the same message and key");
+    } else {
+        style::status("🔧", "Signature was already low-S; unchanged");
+    }
+    style::section("📊", "Hex:");
+    println!("  • signature_r: 0x{}", hex::encode(signature_r));
+    println!("  • signature_s: 0x{}", hex::encode(signature_s));
+    println!("  • signature (base64): {}", general_purpose::STANDARD.encode(&compact));
+    style::section("📄", "Noir arrays:");
+    println!("signature_r = {}", noir_byte_array(&signature_r));
+    println!("signature_s = {}", noir_byte_array(&signature_s));
+
+    Ok(())
+}
+
+/// Print whether the signature verifies and whether S is low; fails when it doesn't verify
+pub fn verify_sig(message: &str, signature: &SignatureInput, pubkey_x: &str, pubkey_y: &str) -> Result<(), String> {
+    let check = check_signature(message, signature, pubkey_x, pubkey_y)?;
+    let msg_hash: [u8; 32] = Sha256::digest(message.as_bytes()).into();
+
+    style::status("📝", format!("Message: '{}'", message));
+    style::status("🔍", format!("Message hash: 0x{}", hex::encode(msg_hash)));
+    style::status("🔧", format!("Signature normalized (low-S): {}", style::mark(check.low_s)));
+    if !check.low_s {
+        style::warning("Noir rejects high-S signatures; call normalize_s() before exporting them");
+    }
+
+    if check.verified {
+        style::success("Signature verified");
+        Ok(())
+    } else {
+        Err("Signature verification failed".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keypair_hex_layout() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+        let keys = KeyPairHex::new(&secret_key, &public_key);
+
+        assert_eq!(keys.private_key, "01".repeat(32));
+        assert_eq!(keys.public_key.len(), 130);
+        assert_eq!(keys.public_key, format!("04{}{}", keys.pubkey_x, keys.pubkey_y));
+        // Round-trips through the same parsing the backend does
+        let parsed = PublicKey::from_slice(&hex::decode(&keys.public_key).unwrap()).unwrap();
+        assert_eq!(parsed, public_key);
+    }
+
+    #[test]
+    fn test_sign_message_is_low_s_and_verifiable() {
+        let secret_key = parse_private_key(&format!("0x{}", "01".repeat(32))).unwrap();
+        let signed = sign_message("hello noir", &secret_key).unwrap();
+
+        assert_eq!(signed.msg_hash, <[u8; 32]>::from(Sha256::digest(b"hello noir")));
+        assert!(signed.signature_s[0] < 0x80);
+
+        let secp = Secp256k1::new();
+        let mut compact = [0u8; 64];
+        compact[..32].copy_from_slice(&signed.signature_r);
+        compact[32..].copy_from_slice(&signed.signature_s);
+        let signature = secp256k1::ecdsa::Signature::from_compact(&compact).unwrap();
+        let message = Message::from_digest_slice(&signed.msg_hash).unwrap();
+        secp.verify_ecdsa(&message, &signature, &PublicKey::from_secret_key(&secp, &secret_key)).unwrap();
+    }
+
+    #[test]
+    fn test_signing_is_reproducible() {
+        let secret_key = parse_private_key(&"01".repeat(32)).unwrap();
+        let first = sign_message("hello noir", &secret_key).unwrap();
+        for _ in 0..3 {
+            let again = sign_message("hello noir", &secret_key).unwrap();
+            assert_eq!((again.signature_r, again.signature_s), (first.signature_r, first.signature_s));
+        }
+
+        // RFC 6979 known answer for private key 1, as in the checked-in Prover.toml
+        let msg_hash = decode_hex_32("msg_hash", "1ffdcddd748313eb96986b3b117f7726222a2402d6c7acae0d9bc079b6068a3c").unwrap();
+        let compact = sign_deterministic(&msg_hash, &secret_key_one()).serialize_compact();
+        assert_eq!(hex::encode(&compact[..32]), "951a71d9197924170f7f98dc2eb88500ce9aaadada9b3c976b31671396a697a2");
+        assert_eq!(hex::encode(&compact[32..]), "5d1b3da079e5c6dffa30fa98657b0183917748ea61d07f88d87d16c4a226b139");
+        assert_ne!(sign_deterministic(&msg_hash, &secret_key), sign_deterministic(&msg_hash, &secret_key_one()));
+    }
+
+    fn secret_key_one() -> SecretKey {
+        parse_private_key(&format!("{}01", "00".repeat(31))).unwrap()
+    }
+
+    #[test]
+    fn test_noir_byte_array_format() {
+        assert_eq!(noir_byte_array(&[0x01, 0xff]), r#"["0x01", "0xff"]"#);
+        assert!(parse_private_key("not-hex").is_err());
+    }
+
+    #[test]
+    fn test_check_signature_accepts_hex_and_base64() {
+        let secret_key = parse_private_key(&"01".repeat(32)).unwrap();
+        let keys = KeyPairHex::new(&secret_key, &PublicKey::from_secret_key(&Secp256k1::new(), &secret_key));
+        let signed = sign_message("hello noir", &secret_key).unwrap();
+
+        let hex_input = SignatureInput::Hex {
+            r: hex::encode(signed.signature_r),
+            s: format!("0x{}", hex::encode(signed.signature_s)),
+        };
+        let compact = hex_input.to_compact().unwrap();
+        let base64_input = SignatureInput::Base64(general_purpose::STANDARD.encode(compact));
+
+        for input in [&hex_input, &base64_input] {
+            let check = check_signature("hello noir", input, &keys.pubkey_x, &keys.pubkey_y).unwrap();
+            assert_eq!(check, SignatureCheck { verified: true, low_s: true });
+        }
+
+        let check = check_signature("tampered", &hex_input, &keys.pubkey_x, &keys.pubkey_y).unwrap();
+        assert!(!check.verified);
+    }
+
+    #[test]
+    fn test_check_signature_flags_high_s() {
+        let secret_key = parse_private_key(&"01".repeat(32)).unwrap();
+        let keys = KeyPairHex::new(&secret_key, &PublicKey::from_secret_key(&Secp256k1::new(), &secret_key));
+        let signed = sign_message("hello noir", &secret_key).unwrap();
+
+        // n - s is the same signature with S in the upper half of the order
+        let order = secp256k1::constants::CURVE_ORDER;
+        let mut high_s = [0u8; 32];
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let diff = order[i] as i16 - signed.signature_s[i] as i16 - borrow;
+            borrow = (diff < 0) as i16;
+            high_s[i] = diff.rem_euclid(256) as u8;
+        }
+
+        let input = SignatureInput::Hex { r: hex::encode(signed.signature_r), s: hex::encode(high_s) };
+        let check = check_signature("hello noir", &input, &keys.pubkey_x, &keys.pubkey_y).unwrap();
+        assert_eq!(check, SignatureCheck { verified: true, low_s: false });
+
+        // Normalizing restores the signature as signed, which is low-S and verifies
+        let (r, s, changed) = normalize_signature(&input).unwrap();
+        assert!(changed);
+        assert_eq!((r, s), (signed.signature_r, signed.signature_s));
+        let normalized = SignatureInput::Hex { r: hex::encode(r), s: hex::encode(s) };
+        let check = check_signature("hello noir", &normalized, &keys.pubkey_x, &keys.pubkey_y).unwrap();
+        assert_eq!(check, SignatureCheck { verified: true, low_s: true });
+        assert!(!normalize_signature(&normalized).unwrap().2);
+    }
+}
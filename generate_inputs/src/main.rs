@@ -1,56 +1,89 @@
 use secp256k1::{Message, Secp256k1, SecretKey, PublicKey};
 use sha2::{Digest, Sha256};
 use std::fs;
-use hex;
 use clap::Parser;
 
+mod api;
 mod health_records;
 mod cli;
+mod keys;
+mod style;
+mod wizard;
 
 use health_records::{HealthRecord, HealthRecordType, HealthRecordTemplates};
 use cli::{Cli, Commands};
 
 fn main() {
     let cli = Cli::parse();
+    style::init(cli.color);
+
+    // Commands that don't generate circuit inputs skip the generator banner
+    let command = match cli.command {
+        Commands::Keygen { out } => return exit_on_error(keys::keygen(out.as_deref())),
+        Commands::Sign { message, private_key } => return exit_on_error(keys::sign(&message, &private_key)),
+        Commands::VerifySig { message, signature_r, signature_s, signature, pubkey_x, pubkey_y } => {
+            let signature = keys::SignatureInput::from_args(signature_r, signature_s, signature);
+            return exit_on_error(keys::verify_sig(&message, &signature, &pubkey_x, &pubkey_y));
+        }
+        Commands::Normalize { signature_r, signature_s, signature } => {
+            let signature = keys::SignatureInput::from_args(signature_r, signature_s, signature);
+            return exit_on_error(keys::normalize(&signature));
+        }
+        Commands::Api { base_url, token, action } => {
+            return exit_on_error(api::run(&base_url, token.as_deref(), action));
+        }
+        Commands::Completions { shell } => return cli::write_completions(shell, &mut std::io::stdout()),
+        command => command,
+    };
     
-    println!("🔐 ZK Health Pass Input Generator");
-    println!("📋 Using pre-computed hash approach (no SHA-256 in circuit)\n");
+    style::status("🔐", "ZK Health Pass Input Generator");
+    style::status("📋", "Using pre-computed hash approach (no SHA-256 in circuit)\n");
     
     // Determine which health record to use
-    let health_record = match cli.command {
+    let health_record = match command {
         Commands::Template { name } => {
             let templates = HealthRecordTemplates::get_templates();
             match templates.get(name.as_str()) {
                 Some(record) => record.clone(),
                 None => {
-                    eprintln!("❌ Template '{}' not found!", name);
+                    style::error(format!("Template '{}' not found!", name));
                     eprintln!("Available templates: {:?}", HealthRecordTemplates::list_available());
                     std::process::exit(1);
                 }
             }
         }
         Commands::Custom { patient_id, details, record_type, date, issuer } => {
-            let rt = match record_type.to_lowercase().as_str() {
-                "vaccination" => HealthRecordType::Vaccination,
-                "test" => HealthRecordType::TestResult,
-                "clearance" => HealthRecordType::MedicalClearance,
-                "immunity" => HealthRecordType::ImmunityProof,
-                _ => {
-                    eprintln!("❌ Invalid record type: {}", record_type);
+            let rt = match HealthRecordType::from_cli_name(&record_type) {
+                Some(rt) => rt,
+                None => {
+                    style::error(format!("Invalid record type: {}", record_type));
                     eprintln!("Valid types: vaccination, test, clearance, immunity");
                     std::process::exit(1);
                 }
             };
             HealthRecord::new(rt, patient_id, details, date, issuer)
         }
+        Commands::Wizard => match wizard::prompt_health_record() {
+            Ok(record) => record,
+            Err(e) => {
+                style::error(e);
+                std::process::exit(1);
+            }
+        },
         Commands::List => {
-            println!("📋 Available Health Record Templates:");
+            style::status("📋", "Available Health Record Templates:");
             let templates = HealthRecordTemplates::get_templates();
             for (name, record) in templates.iter() {
                 println!("  • {}: {}", name, record.to_signable_string());
             }
             return;
         }
+        Commands::Keygen { .. }
+        | Commands::Sign { .. }
+        | Commands::VerifySig { .. }
+        | Commands::Normalize { .. }
+        | Commands::Api { .. }
+        | Commands::Completions { .. } => unreachable!("handled above"),
         Commands::Default => {
             // Default behavior for backward compatibility
             HealthRecord::new(
@@ -64,11 +97,18 @@ fn main() {
     };
 
     let message_str = health_record.to_signable_string();
-    println!("📝 Health record: '{}'", message_str);
+    style::status("📝", format!("Health record: '{}'", message_str));
     
     generate_ecdsa_inputs(&message_str);
 }
 
+fn exit_on_error(result: Result<(), String>) {
+    if let Err(e) = result {
+        style::error(e);
+        std::process::exit(1);
+    }
+}
+
 fn generate_ecdsa_inputs(message_str: &str) {
     // Initialize secp256k1 context
     let secp = Secp256k1::signing_only();
@@ -97,18 +137,16 @@ fn generate_ecdsa_inputs(message_str: &str) {
     
     // ⭐ COMPUTE SHA-256 HASH OUTSIDE THE CIRCUIT ⭐
     let mut hasher = Sha256::new();
-    hasher.update(&message_bytes);
+    hasher.update(message_bytes);
     let msg_hash_bytes = hasher.finalize();
     let msg_hash_array: [u8; 32] = msg_hash_bytes.into();
     
-    println!("🔍 Message hash: 0x{}", hex::encode(&msg_hash_array));
+    style::status("🔍", format!("Message hash: 0x{}", hex::encode(msg_hash_array)));
     
-    // Sign the message hash
+    // Sign the message hash with an RFC 6979 nonce, normalized for Noir
+    // compatibility, so the same record always gives the same Prover.toml
     let message_obj = Message::from_digest_slice(&msg_hash_array).expect("32 bytes");
-    let mut signature = secp.sign_ecdsa(&message_obj, &secret_key);
-    
-    // ⭐ CRITICAL: Normalize signature for Noir compatibility ⭐
-    signature.normalize_s();
+    let signature = keys::sign_deterministic(&msg_hash_array, &secret_key);
     
     let signature_bytes = signature.serialize_compact();
     let signature_r_bytes = &signature_bytes[0..32];
@@ -117,9 +155,9 @@ fn generate_ecdsa_inputs(message_str: &str) {
     // Verify signature works in Rust first
     let verify_secp = Secp256k1::verification_only();
     match verify_secp.verify_ecdsa(&message_obj, &signature, &public_key) {
-        Ok(_) => println!("✅ Signature verified successfully in Rust"),
+        Ok(_) => style::success("Signature verified successfully in Rust"),
         Err(e) => {
-            eprintln!("❌ Signature verification failed in Rust: {:?}", e);
+            style::error(format!("Signature verification failed in Rust: {:?}", e));
             panic!("Cannot proceed with invalid signature");
         }
     }
@@ -127,59 +165,89 @@ fn generate_ecdsa_inputs(message_str: &str) {
     // Check signature normalization
     let s_first_byte = signature_s_bytes[0];
     let is_normalized = s_first_byte < 0x80;
-    println!("🔧 Signature normalized (low-S): {} (s[0] = 0x{:02x})", is_normalized, s_first_byte);
+    style::status("🔧", format!("Signature normalized (low-S): {} (s[0] = 0x{:02x})", is_normalized, s_first_byte));
     
     if !is_normalized {
-        println!("⚠️  Warning: Signature may not be properly normalized for Noir");
+        style::warning("Warning: Signature may not be properly normalized for Noir");
     }
     
-    // Format byte arrays for Prover.toml (Noir expects string format)
-    let format_byte_array = |bytes: &[u8]| -> String {
-        let hex_values: Vec<String> = bytes.iter().map(|b| format!("\"0x{:02x}\"", b)).collect();
-        format!("[{}]", hex_values.join(", "))
-    };
-    
     // Create Prover.toml content
-    let prover_toml = format!(
-        r#"msg_hash = {}
-pubkey_x = {}
-pubkey_y = {}
-signature_r = {}
-signature_s = {}
-"#,
-        format_byte_array(&msg_hash_array),
-        format_byte_array(pubkey_x_bytes),
-        format_byte_array(pubkey_y_bytes),
-        format_byte_array(signature_r_bytes),
-        format_byte_array(signature_s_bytes)
-    );
+    let prover_toml = prover_toml(&msg_hash_array, pubkey_x_bytes, pubkey_y_bytes, signature_r_bytes, signature_s_bytes);
 
     // Write to file
     fs::write("Prover.toml", &prover_toml).expect("Failed to write Prover.toml");
     
-    println!("\n🎯 Successfully generated Prover.toml!");
-    println!("📁 Location: ./Prover.toml");
+    style::section("🎯", "Successfully generated Prover.toml!");
+    style::status("📁", "Location: ./Prover.toml");
     
     // Print the first few lines to verify
-    println!("\n📄 Generated file preview:");
+    style::section("📄", "Generated file preview:");
     let lines: Vec<&str> = prover_toml.lines().take(8).collect();
     for line in lines {
         println!("   {}", line);
     }
     println!("   ...");
     
-    println!("\n📊 Verification Details:");
-    println!("  • Private key: 0x{}", hex::encode(&secret_bytes));
+    style::section("📊", "Verification Details:");
+    println!("  • Private key: 0x{}", hex::encode(secret_bytes));
     println!("  • Message: '{}'", message_str);
-    println!("  • Message hash: 0x{}", hex::encode(&msg_hash_array));
-    println!("  • Signature verified: ✅");
-    println!("  • Signature normalized: {}", if is_normalized { "✅" } else { "⚠️" });
+    println!("  • Message hash: 0x{}", hex::encode(msg_hash_array));
+    println!("  • Signature verified: {}", style::mark(true));
+    println!("  • Signature normalized: {}", style::mark(is_normalized));
     
-    println!("\n🚀 Next Steps:");
+    style::section("🚀", "Next Steps:");
     println!("  1. Copy this Prover.toml to your Noir project directory");
     println!("  2. Replace your main.nr with the working circuit code");
     println!("  3. Run: nargo check");
     println!("  4. Run: nargo prove");
     
-    println!("\n💡 This approach avoids SHA-256 function name issues!");
-}
\ No newline at end of file
+    println!();
+    style::status("💡", "This approach avoids SHA-256 function name issues!");
+}
+
+/// Prover.toml for the ECDSA circuit (Noir expects byte arrays as quoted hex strings)
+fn prover_toml(msg_hash: &[u8], pubkey_x: &[u8], pubkey_y: &[u8], signature_r: &[u8], signature_s: &[u8]) -> String {
+    format!(
+        r#"msg_hash = {}
+pubkey_x = {}
+pubkey_y = {}
+signature_r = {}
+signature_s = {}
+"#,
+        keys::noir_byte_array(msg_hash),
+        keys::noir_byte_array(pubkey_x),
+        keys::noir_byte_array(pubkey_y),
+        keys::noir_byte_array(signature_r),
+        keys::noir_byte_array(signature_s)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_inputs_match_the_golden_prover_toml() {
+        let message = HealthRecord::new(
+            HealthRecordType::Vaccination,
+            "Patient123".to_string(),
+            "COVID19_Dose1".to_string(),
+            "2025".to_string(),
+            "HealthAuthority".to_string(),
+        )
+        .to_signable_string();
+        let mut message_bytes = [0u8; 32];
+        let copy_len = message.len().min(32);
+        message_bytes[..copy_len].copy_from_slice(&message.as_bytes()[..copy_len]);
+        let msg_hash: [u8; 32] = Sha256::digest(message_bytes).into();
+
+        let mut secret_bytes = [0u8; 32];
+        secret_bytes[31] = 1;
+        let secret_key = SecretKey::from_slice(&secret_bytes).unwrap();
+        let public_key = PublicKey::from_secret_key(&Secp256k1::signing_only(), &secret_key).serialize_uncompressed();
+        let signature = keys::sign_deterministic(&msg_hash, &secret_key).serialize_compact();
+
+        let toml = prover_toml(&msg_hash, &public_key[1..33], &public_key[33..], &signature[..32], &signature[32..]);
+        assert_eq!(toml, include_str!("../Prover.toml"));
+    }
+}
@@ -0,0 +1,102 @@
+use clap::ValueEnum;
+use console::style;
+use std::ffi::OsString;
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// When status lines get icons and colors
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Only on a terminal, and only when NO_COLOR is unset
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Resolve `choice` once at startup; every status helper below follows it
+pub fn init(choice: ColorChoice) {
+    let enabled = resolve(choice, std::env::var_os("NO_COLOR"), std::io::stdout().is_terminal());
+    ENABLED.store(enabled, Ordering::Relaxed);
+    console::set_colors_enabled(enabled);
+    console::set_colors_enabled_stderr(enabled);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// NO_COLOR counts when set to anything but the empty string (https://no-color.org)
+fn resolve(choice: ColorChoice, no_color: Option<OsString>, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => no_color.is_none_or(|value| value.is_empty()) && is_terminal,
+    }
+}
+
+fn line(enabled: bool, icon: &str, message: &str) -> String {
+    if enabled {
+        format!("{} {}", icon, message)
+    } else {
+        message.to_string()
+    }
+}
+
+/// Print a status line, prefixed with `icon` when styling is enabled
+pub fn status(icon: &str, message: impl Display) {
+    println!("{}", line(enabled(), icon, &message.to_string()));
+}
+
+/// Print a section title after a blank line
+pub fn section(icon: &str, title: impl Display) {
+    println!();
+    status(icon, title);
+}
+
+pub fn success(message: impl Display) {
+    println!("{}", style(line(enabled(), "✅", &message.to_string())).green());
+}
+
+pub fn warning(message: impl Display) {
+    // The warning sign renders narrow, hence the extra space
+    println!("{}", style(line(enabled(), "⚠️ ", &message.to_string())).yellow());
+}
+
+/// Print an error to stderr; without styling it is marked with `error:` instead
+pub fn error(message: impl Display) {
+    let message = if enabled() { format!("❌ {}", message) } else { format!("error: {}", message) };
+    eprintln!("{}", style(message).red().for_stderr());
+}
+
+/// A yes/no for inline use, e.g. "Signature normalized: ✅"
+pub fn mark(ok: bool) -> &'static str {
+    match (enabled(), ok) {
+        (true, true) => "✅",
+        (true, false) => "⚠️",
+        (false, true) => "yes",
+        (false, false) => "no",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_color_and_flag_decide_styling() {
+        assert!(resolve(ColorChoice::Auto, None, true));
+        assert!(resolve(ColorChoice::Auto, Some(OsString::new()), true));
+        assert!(!resolve(ColorChoice::Auto, Some("1".into()), true));
+        assert!(!resolve(ColorChoice::Auto, None, false));
+
+        assert!(resolve(ColorChoice::Always, Some("1".into()), false));
+        assert!(!resolve(ColorChoice::Never, None, true));
+
+        assert_eq!(line(true, "📝", "Message"), "📝 Message");
+        assert_eq!(line(false, "📝", "Message"), "Message");
+    }
+}
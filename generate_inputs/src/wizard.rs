@@ -0,0 +1,148 @@
+use dialoguer::theme::{ColorfulTheme, SimpleTheme, Theme};
+use dialoguer::{Confirm, Input, Select};
+
+use crate::health_records::{HealthRecord, HealthRecordTemplates, HealthRecordType};
+use crate::style;
+
+/// Short record type names in the order they are offered, as taken by `custom --record-type`
+const RECORD_TYPES: [&str; 4] = ["vaccination", "test", "clearance", "immunity"];
+
+/// Ask for a health record step by step, starting from a template or from scratch
+pub fn prompt_health_record() -> Result<HealthRecord, String> {
+    let theme: Box<dyn Theme> = if style::enabled() { Box::new(ColorfulTheme::default()) } else { Box::new(SimpleTheme) };
+    let theme = theme.as_ref();
+    let templates = HealthRecordTemplates::get_templates();
+    let template_names = HealthRecordTemplates::list_available();
+
+    let mut choices: Vec<String> = template_names
+        .iter()
+        .map(|name| format!("{} ({})", name, templates[name].to_signable_string()))
+        .collect();
+    choices.push("Custom record".to_string());
+
+    let choice = Select::with_theme(theme)
+        .with_prompt("Start from")
+        .items(&choices)
+        .default(0)
+        .interact()
+        .map_err(prompt_error)?;
+
+    let template = template_names.get(choice).map(|name| templates[name].clone());
+    if let Some(template) = &template {
+        let use_as_is = Confirm::with_theme(theme)
+            .with_prompt("Use the template as-is?")
+            .default(true)
+            .interact()
+            .map_err(prompt_error)?;
+        if use_as_is {
+            return Ok(template.clone());
+        }
+    }
+
+    let record_type = Select::with_theme(theme)
+        .with_prompt("Record type")
+        .items(&RECORD_TYPES)
+        .default(template.as_ref().map_or(0, |template| cli_name_index(&template.record_type)))
+        .interact()
+        .map_err(prompt_error)?;
+    let record_type = HealthRecordType::from_cli_name(RECORD_TYPES[record_type]).expect("offered types parse");
+
+    let field = |prompt: &str, initial: Option<&str>, validate: fn(&str) -> Result<(), String>| {
+        let mut input = Input::<String>::with_theme(theme).with_prompt(prompt);
+        if let Some(initial) = initial {
+            input = input.with_initial_text(initial);
+        }
+        input
+            .validate_with(|value: &String| validate(value))
+            .interact_text()
+            .map(|value| value.trim().to_string())
+            .map_err(prompt_error)
+    };
+
+    let patient_id = field("Patient ID", template.as_ref().map(|t| t.patient_id.as_str()), validate_field)?;
+    let details = field("Details", template.as_ref().map(|t| t.details.as_str()), validate_field)?;
+    let date = field(
+        "Date (YYYY or YYYY-MM-DD)",
+        Some(template.as_ref().map_or("2025", |t| t.date.as_str())),
+        validate_date,
+    )?;
+    let issuer = field(
+        "Issuer",
+        Some(template.as_ref().map_or("HealthAuthority", |t| t.issuer.as_str())),
+        validate_field,
+    )?;
+
+    Ok(HealthRecord::new(record_type, patient_id, details, date, issuer))
+}
+
+fn cli_name_index(record_type: &HealthRecordType) -> usize {
+    match record_type {
+        HealthRecordType::Vaccination => 0,
+        HealthRecordType::TestResult => 1,
+        HealthRecordType::MedicalClearance => 2,
+        HealthRecordType::ImmunityProof => 3,
+    }
+}
+
+fn prompt_error(error: dialoguer::Error) -> String {
+    format!("Interactive input failed ({}); use `custom` with flags instead", error)
+}
+
+/// Non-empty and free of `:`, which separates the issuer in the signed message
+fn validate_field(value: &str) -> Result<(), String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("Required".to_string());
+    }
+    if value.contains(':') {
+        return Err("Must not contain ':'".to_string());
+    }
+    Ok(())
+}
+
+/// A year or a calendar date
+fn validate_date(value: &str) -> Result<(), String> {
+    let parts: Vec<&str> = value.trim().split('-').collect();
+    let number = |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_digit());
+
+    let valid = match parts.as_slice() {
+        [year] => number(year, 4),
+        [year, month, day] if number(year, 4) && number(month, 2) && number(day, 2) => {
+            let (month, day): (u32, u32) = (month.parse().unwrap_or(0), day.parse().unwrap_or(0));
+            (1..=12).contains(&month) && (1..=31).contains(&day)
+        }
+        _ => false,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err("Expected YYYY or YYYY-MM-DD".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_and_dates_are_validated() {
+        assert!(validate_field("Patient123").is_ok());
+        assert!(validate_field("  ").is_err());
+        assert!(validate_field("Lab:North").is_err());
+
+        assert!(validate_date("2025").is_ok());
+        assert!(validate_date("2025-09-27").is_ok());
+        assert!(validate_date("2025-13-01").is_err());
+        assert!(validate_date("25-09-27").is_err());
+        assert!(validate_date("soon").is_err());
+    }
+
+    #[test]
+    fn test_offered_record_types_round_trip() {
+        for (index, name) in RECORD_TYPES.iter().enumerate() {
+            let record_type = HealthRecordType::from_cli_name(name).unwrap();
+            assert_eq!(cli_name_index(&record_type), index);
+        }
+    }
+}